@@ -101,6 +101,27 @@ impl AutoreleasePool {
     pub fn new() -> Option<Self> {
         Self::alloc()?.init()
     }
+
+    /// Run `f` inside a fresh autorelease pool.
+    ///
+    /// Objects autoreleased while `f` runs are released when it returns or
+    /// unwinds, like an `@autoreleasepool` block. Wrap each iteration of a
+    /// render loop in this so per-frame objects do not pile up until the
+    /// thread's outer pool drains. Values `f` returns must be retained to
+    /// outlive the pool.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// loop {
+    ///     AutoreleasePool::with(|| render_frame(&layer));
+    /// }
+    /// ```
+    #[inline]
+    pub fn with<R>(f: impl FnOnce() -> R) -> R {
+        let _pool = Self::new();
+        f()
+    }
 }
 
 impl Referencing for AutoreleasePool {
@@ -167,4 +188,13 @@ mod tests {
             std::mem::size_of::<*mut c_void>()
         );
     }
+
+    #[test]
+    fn test_autorelease_pool_with() {
+        let length = AutoreleasePool::with(|| {
+            let string = crate::String::from_str("pooled").unwrap();
+            string.length()
+        });
+        assert_eq!(length, 6);
+    }
 }
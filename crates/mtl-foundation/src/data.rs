@@ -46,6 +46,28 @@ impl Data {
         unsafe { msg_send_0(self.as_ptr(), sel!(length)) }
     }
 
+    /// Borrow the data's bytes.
+    ///
+    /// The slice points into the `NSData` itself, so nothing is copied;
+    /// call `to_vec()` on it to keep the bytes past the object's lifetime.
+    /// For an autoreleased object that is until the enclosing autorelease
+    /// pool drains. The object must not be mutated through
+    /// [`mutable_bytes`](Self::mutable_bytes) while the slice is alive.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        let length = self.length();
+        if length == 0 {
+            return &[];
+        }
+        unsafe {
+            let ptr: *const c_void = msg_send_0(self.as_ptr(), sel!(bytes));
+            if ptr.is_null() {
+                return &[];
+            }
+            std::slice::from_raw_parts(ptr as *const u8, length)
+        }
+    }
+
     /// Create a Data from a raw pointer.
     ///
     /// # Safety
@@ -99,4 +121,19 @@ mod tests {
             std::mem::size_of::<*mut c_void>()
         );
     }
+
+    #[test]
+    fn test_data_bytes() {
+        let source = [1u8, 2, 3, 4];
+        let data = unsafe {
+            let ptr: *mut c_void = mtl_sys::msg_send_2(
+                mtl_sys::class!(NSData).as_ptr(),
+                sel!(dataWithBytes:length:),
+                source.as_ptr() as *const c_void,
+                source.len() as UInteger,
+            );
+            Data::from_ptr(ptr).unwrap()
+        };
+        assert_eq!(data.bytes(), source);
+    }
 }
@@ -67,7 +67,7 @@ pub type ErrorDomain = *mut String;
 pub type ErrorUserInfoKey = *mut String;
 
 // Error domain constants - linked from Foundation framework
-#[link(name = "Foundation", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "Foundation", kind = "framework"))]
 unsafe extern "C" {
     #[link_name = "NSCocoaErrorDomain"]
     static COCOA_ERROR_DOMAIN: *mut c_void;
@@ -130,6 +130,8 @@ pub use url::Url;
 pub use autorelease::{AutoreleasePool, AutoreleasePoolScope};
 
 // Notification
+#[cfg(feature = "blocks")]
+pub use notification::NotificationObserver;
 pub use notification::{Notification, NotificationCenter, NotificationName};
 
 // Lock
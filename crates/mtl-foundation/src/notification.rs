@@ -20,14 +20,31 @@
 //!     static class NotificationCenter* defaultCenter();
 //!     Object* addObserver(NotificationName name, Object* pObj, void* pQueue, ObserverBlock block);
 //!     void removeObserver(Object* pObserver);
+//!     void postNotificationName(NotificationName name, Object* pObj);
 //! };
 //! }
 //! ```
+//!
+//! With the `blocks` feature, [`NotificationCenter::observe`] registers a
+//! Rust closure and returns a [`NotificationObserver`] that unregisters it on
+//! drop:
+//!
+//! ```ignore
+//! use mtl_foundation::{NotificationCenter, ProcessInfo};
+//!
+//! let center = NotificationCenter::default_center().unwrap();
+//! let _observer = center
+//!     .observe(ProcessInfo::THERMAL_STATE_DID_CHANGE_NOTIFICATION, move |_| {
+//!         let state = ProcessInfo::process_info().unwrap().thermal_state();
+//!         renderer_tx.send(ThermalStateChanged(state)).unwrap();
+//!     })
+//!     .unwrap();
+//! ```
 
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
-use mtl_sys::{class, msg_send_0, msg_send_1, sel};
+use mtl_sys::{class, msg_send_0, msg_send_1, msg_send_2, sel};
 
 use crate::dictionary::Dictionary;
 use crate::object::{Object, Referencing};
@@ -54,6 +71,11 @@ impl Notification {
         unsafe { msg_send_0(self.as_ptr(), sel!(name)) }
     }
 
+    /// Get the notification name as a Rust string.
+    pub fn name_string(&self) -> Option<std::string::String> {
+        unsafe { String::from_ptr(self.name() as *mut c_void)?.to_string() }
+    }
+
     /// Get the notification object.
     ///
     /// C++ equivalent: `NS::Object* object() const`
@@ -124,7 +146,8 @@ impl NotificationCenter {
     /// C++ equivalent: `Object* addObserver(NotificationName name, Object* pObj, void* pQueue, ObserverBlock block)`
     ///
     /// Note: The block parameter is a raw pointer to an Objective-C block.
-    /// Use with mtl_sys block types.
+    /// Use with mtl_sys block types, or use [`observe`](Self::observe) to
+    /// register a closure.
     #[inline]
     pub fn add_observer(
         &self,
@@ -145,6 +168,66 @@ impl NotificationCenter {
         }
     }
 
+    /// Call `callback` for every notification named `name` posted to this
+    /// center, until the returned observer is dropped.
+    ///
+    /// The callback runs synchronously on the thread that posts the
+    /// notification, so it may run on several threads at once and must be
+    /// `Sync`. Returns `None` if `name` contains a NUL byte or the
+    /// registration fails.
+    ///
+    /// C++ equivalent: `Object* addObserver(NotificationName name, Object* pObj, void* pQueue, ObserverBlock block)`
+    #[cfg(feature = "blocks")]
+    pub fn observe<F>(&self, name: &str, callback: F) -> Option<NotificationObserver>
+    where
+        F: Fn(&Notification) + Send + Sync + 'static,
+    {
+        let name = String::from_str(name)?;
+        let block = mtl_sys::OneArgBlock::from_fn(move |notification_ptr: *mut c_void| {
+            if let Some(notification) = unsafe { Notification::from_ptr(notification_ptr) } {
+                callback(&notification);
+            }
+        });
+
+        let observer = self.add_observer(
+            name.as_ptr() as NotificationName,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            block.as_ptr(),
+        );
+
+        // Transfer block ownership to the notification center
+        std::mem::forget(block);
+
+        let observer = unsafe { Object::from_ptr(observer as *mut c_void)? };
+        // The center holds the registration; keep our own reference so the
+        // token stays valid until it is removed.
+        Some(NotificationObserver {
+            center: self.clone(),
+            observer: observer.retain(),
+        })
+    }
+
+    /// Post a notification named `name` with an optional sender `object`.
+    ///
+    /// Observers registered for `name` run synchronously before this returns.
+    ///
+    /// C++ equivalent: `void postNotificationName(NotificationName name, Object* pObj)`
+    pub fn post_notification(&self, name: &str, object: Option<&Object>) {
+        let Some(name) = String::from_str(name) else {
+            return;
+        };
+        let object = object.map_or(std::ptr::null(), |o| o.as_ptr());
+        unsafe {
+            let _: () = msg_send_2(
+                self.as_ptr(),
+                sel!(postNotificationName:object:),
+                name.as_ptr(),
+                object,
+            );
+        }
+    }
+
     /// Remove an observer.
     ///
     /// C++ equivalent: `void removeObserver(Object* pObserver)`
@@ -184,6 +267,37 @@ impl std::fmt::Debug for NotificationCenter {
     }
 }
 
+/// A closure registered with [`NotificationCenter::observe`].
+///
+/// The closure is unregistered when this is dropped.
+#[cfg(feature = "blocks")]
+pub struct NotificationObserver {
+    center: NotificationCenter,
+    observer: Object,
+}
+
+#[cfg(feature = "blocks")]
+impl Drop for NotificationObserver {
+    fn drop(&mut self) {
+        self.center.remove_observer(&self.observer);
+        self.observer.release();
+    }
+}
+
+#[cfg(feature = "blocks")]
+unsafe impl Send for NotificationObserver {}
+#[cfg(feature = "blocks")]
+unsafe impl Sync for NotificationObserver {}
+
+#[cfg(feature = "blocks")]
+impl std::fmt::Debug for NotificationObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NotificationObserver")
+            .field("observer", &self.observer.as_ptr())
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -203,4 +317,31 @@ mod tests {
             std::mem::size_of::<*mut c_void>()
         );
     }
+
+    #[cfg(feature = "blocks")]
+    #[test]
+    fn test_observe() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const NAME: &str = "MtlFoundationTestNotification";
+        let center = NotificationCenter::default_center().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let observer = {
+            let count = Arc::clone(&count);
+            center
+                .observe(NAME, move |notification| {
+                    assert_eq!(notification.name_string().as_deref(), Some(NAME));
+                    count.fetch_add(1, Ordering::SeqCst);
+                })
+                .unwrap()
+        };
+        center.post_notification(NAME, None);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        drop(observer);
+        center.post_notification(NAME, None);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
 }
@@ -126,6 +126,20 @@ pub type ProcessPerformanceProfile = Integer;
 pub struct ProcessInfo(NonNull<c_void>);
 
 impl ProcessInfo {
+    /// Name of the notification posted when [`thermal_state`](Self::thermal_state)
+    /// changes.
+    ///
+    /// C++ equivalent: `NS::ProcessInfoThermalStateDidChangeNotification`
+    pub const THERMAL_STATE_DID_CHANGE_NOTIFICATION: &str =
+        "NSProcessInfoThermalStateDidChangeNotification";
+
+    /// Name of the notification posted when [`is_low_power_mode_enabled`](Self::is_low_power_mode_enabled)
+    /// changes.
+    ///
+    /// C++ equivalent: `NS::ProcessInfoPowerStateDidChangeNotification`
+    pub const POWER_STATE_DID_CHANGE_NOTIFICATION: &str =
+        "NSProcessInfoPowerStateDidChangeNotification";
+
     /// Get the process info singleton.
     ///
     /// C++ equivalent: `static ProcessInfo* processInfo()`
@@ -74,7 +74,7 @@
 use std::ffi::{c_char, c_void};
 use std::ptr::NonNull;
 
-use mtl_sys::{class, msg_send_0, msg_send_1, msg_send_2, msg_send_4, sel};
+use mtl_sys::{class, msg_send_0, msg_send_1, msg_send_2, msg_send_3, msg_send_4, sel};
 
 use crate::objc_runtime::ComparisonResult;
 use crate::object::{Copying, Referencing};
@@ -363,6 +363,10 @@ impl std::ops::BitAndAssign for StringCompareOptions {
 
 /// An Objective-C string object.
 ///
+/// Metal methods with an `_ns` suffix, such as `set_label_ns`, take a
+/// `String` directly. Keep a retained string around to pass to them and
+/// skip the conversion from `&str` in per-frame code.
+///
 /// C++ equivalent: `NS::String`
 #[repr(transparent)]
 #[derive(Clone)]
@@ -617,11 +621,29 @@ impl String {
 
     /// Create a string from a Rust &str.
     ///
-    /// This is a convenience method for Rust users.
+    /// The returned string is autoreleased. The bytes are copied straight
+    /// from `s` without an intermediate `CString`, so no Rust heap
+    /// allocation is made. Returns `None` if `s` contains a NUL byte.
     #[inline]
     pub fn from_str(s: &str) -> Option<Self> {
-        let c_str = std::ffi::CString::new(s).ok()?;
-        Self::string_with_cstring(c_str.as_ptr(), StringEncoding::UTF8)
+        if s.contains('\0') {
+            return None;
+        }
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(class!(NSString).as_ptr(), sel!(alloc));
+            let ptr: *mut c_void = msg_send_3(
+                ptr,
+                sel!(initWithBytes:length:encoding:),
+                s.as_ptr() as *const c_void,
+                s.len() as UInteger,
+                StringEncoding::UTF8.0,
+            );
+            if ptr.is_null() {
+                return None;
+            }
+            let ptr: *mut c_void = msg_send_0(ptr, sel!(autorelease));
+            Self::from_ptr(ptr)
+        }
     }
 
     /// Convert to a Rust String.
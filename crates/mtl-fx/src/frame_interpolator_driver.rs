@@ -0,0 +1,408 @@
+//! Presentation of interpolated frames through CAMetalLayer drawables.
+//!
+//! Generating a frame is one encode call; showing it is the hard part. The
+//! generated frame lies between the previous real frame and the one just
+//! rendered, so it has to reach the display first and the real frame half a
+//! real-frame interval later, on refresh boundaries, or the doubled frame
+//! rate turns into judder. [`FrameInterpolatorDriver`] keeps the previous
+//! frame's color as history, encodes the interpolation into one drawable,
+//! and presents that drawable and the real one with `presentDrawable:atTime:`
+//! at evenly spaced times from a [`PresentScheduler`].
+//!
+//! The layer must have `framebufferOnly` disabled so the interpolator can
+//! write the generated drawable's texture.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut driver = FrameInterpolatorDriver::new(&device, &descriptor, refresh_range)?;
+//!
+//! // Every real frame, after rendering into `real.texture()` and the
+//! // interpolator's color, depth and motion inputs:
+//! let generated = layer.next_drawable().unwrap();
+//! let inputs = InterpolationInputs::new(&color, &depth, &motion, delta_time);
+//! driver.encode_and_present(&command_buffer, &inputs, &generated, &real)?;
+//! command_buffer.commit();
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::UInteger;
+use mtl_gpu::{
+    BlitCommandEncoder, CommandBuffer, Device, StorageMode, Texture, TextureDescriptor,
+    TextureUsage,
+};
+use mtl_quartz_core::{DisplayRefreshRange, MetalDrawable, PresentScheduler, current_media_time};
+
+use crate::{FrameInterpolator, FrameInterpolatorDescriptor};
+
+// ============================================================
+// FrameInterpolationError
+// ============================================================
+
+/// Error produced while creating or running a [`FrameInterpolatorDriver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameInterpolationError {
+    /// The device doesn't support frame interpolation.
+    Unsupported,
+    /// The interpolator could not be created from the descriptor.
+    Creation,
+    /// The history texture or a blit encoder could not be created.
+    Allocation,
+    /// A drawable has no texture.
+    MissingDrawableTexture,
+}
+
+impl fmt::Display for FrameInterpolationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameInterpolationError::Unsupported => {
+                write!(f, "device does not support frame interpolation")
+            }
+            FrameInterpolationError::Creation => write!(f, "failed to create frame interpolator"),
+            FrameInterpolationError::Allocation => {
+                write!(f, "failed to allocate frame interpolation resources")
+            }
+            FrameInterpolationError::MissingDrawableTexture => {
+                write!(f, "drawable has no texture")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FrameInterpolationError {}
+
+// ============================================================
+// InterpolationInputs
+// ============================================================
+
+/// Camera parameters of the rendered frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InterpolationCamera {
+    /// Distance to the near plane.
+    pub near_plane: f32,
+    /// Distance to the far plane.
+    pub far_plane: f32,
+    /// Vertical field of view in degrees.
+    pub field_of_view: f32,
+    /// Width divided by height.
+    pub aspect_ratio: f32,
+}
+
+/// The rendered frame's inputs to one [`FrameInterpolatorDriver::encode_and_present`].
+#[derive(Copy, Clone, Debug)]
+pub struct InterpolationInputs<'a> {
+    /// Color of the frame just rendered.
+    pub color: &'a Texture,
+    /// Depth of the frame just rendered.
+    pub depth: &'a Texture,
+    /// Motion vectors of the frame just rendered.
+    pub motion: &'a Texture,
+    /// UI to composite over the generated frame, if any.
+    pub ui: Option<&'a Texture>,
+    /// Seconds between the previous real frame and this one.
+    pub delta_time: f32,
+    /// Camera parameters, if they changed since the last frame.
+    pub camera: Option<InterpolationCamera>,
+    /// Sub-pixel offset the frame was rendered with.
+    pub jitter: [f32; 2],
+    /// Scale from motion texture values to pixels.
+    pub motion_vector_scale: [f32; 2],
+}
+
+impl<'a> InterpolationInputs<'a> {
+    /// Create inputs with no UI, jitter or camera change, and motion
+    /// vectors in pixels.
+    pub fn new(
+        color: &'a Texture,
+        depth: &'a Texture,
+        motion: &'a Texture,
+        delta_time: f32,
+    ) -> Self {
+        Self {
+            color,
+            depth,
+            motion,
+            ui: None,
+            delta_time,
+            camera: None,
+            jitter: [0.0; 2],
+            motion_vector_scale: [1.0, 1.0],
+        }
+    }
+
+    /// Set the UI texture.
+    pub fn with_ui(mut self, ui: &'a Texture) -> Self {
+        self.ui = Some(ui);
+        self
+    }
+
+    /// Set the camera parameters.
+    pub fn with_camera(mut self, camera: InterpolationCamera) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Set the jitter offset.
+    pub fn with_jitter(mut self, jitter: [f32; 2]) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the motion vector scale.
+    pub fn with_motion_vector_scale(mut self, scale: [f32; 2]) -> Self {
+        self.motion_vector_scale = scale;
+        self
+    }
+}
+
+/// Presentation times chosen by [`FrameInterpolatorDriver::encode_and_present`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InterpolatedPresentTimes {
+    /// When the generated frame is shown, or `None` if none was generated
+    /// because there was no history yet.
+    pub generated: Option<f64>,
+    /// When the real frame is shown.
+    pub real: f64,
+}
+
+// ============================================================
+// FrameInterpolatorDriver
+// ============================================================
+
+/// Encodes frame interpolation and paces real and generated frames.
+pub struct FrameInterpolatorDriver {
+    interpolator: FrameInterpolator,
+    scheduler: PresentScheduler,
+    history: Option<Texture>,
+    reset: bool,
+    depth_reversed: bool,
+}
+
+impl FrameInterpolatorDriver {
+    /// Create the interpolator described by `descriptor` on `device`,
+    /// presenting on a display with `refresh_range`.
+    pub fn new(
+        device: &Device,
+        descriptor: &FrameInterpolatorDescriptor,
+        refresh_range: DisplayRefreshRange,
+    ) -> Result<Self, FrameInterpolationError> {
+        if !FrameInterpolatorDescriptor::supports_device(device) {
+            return Err(FrameInterpolationError::Unsupported);
+        }
+        let interpolator = descriptor
+            .new_frame_interpolator(device)
+            .ok_or(FrameInterpolationError::Creation)?;
+        Ok(Self::from_interpolator(interpolator, refresh_range))
+    }
+
+    /// Drive an existing interpolator.
+    pub fn from_interpolator(
+        interpolator: FrameInterpolator,
+        refresh_range: DisplayRefreshRange,
+    ) -> Self {
+        Self {
+            interpolator,
+            scheduler: PresentScheduler::new(refresh_range),
+            history: None,
+            reset: true,
+            depth_reversed: false,
+        }
+    }
+
+    /// Get the interpolator.
+    pub fn interpolator(&self) -> &FrameInterpolator {
+        &self.interpolator
+    }
+
+    /// Get the scheduler choosing presentation times.
+    pub fn scheduler(&self) -> &PresentScheduler {
+        &self.scheduler
+    }
+
+    /// Set the display refresh range, such as after the window moves to
+    /// another screen.
+    pub fn set_refresh_range(&mut self, range: DisplayRefreshRange) {
+        self.scheduler.set_refresh_range(range);
+    }
+
+    /// Set whether the depth input is reversed, with 1 nearest.
+    pub fn set_depth_reversed(&mut self, reversed: bool) {
+        self.depth_reversed = reversed;
+    }
+
+    /// Drop the history so the next frame is presented without a generated
+    /// frame before it, such as after a camera cut or a pause.
+    pub fn reset_history(&mut self) {
+        self.history = None;
+        self.reset = true;
+        self.scheduler.reset();
+    }
+
+    /// Get the present interval for `delta_time` seconds between real
+    /// frames: half of it, rounded to the nearest whole number of display
+    /// refreshes.
+    pub fn present_interval(&self, delta_time: f32) -> f64 {
+        let real_rate = 1.0 / f64::from(delta_time.max(f32::EPSILON));
+        self.scheduler
+            .refresh_range()
+            .present_interval(2.0 * real_rate)
+    }
+
+    /// Encode a frame between the previous real frame and this one into
+    /// `generated`, and present it followed by `real`.
+    ///
+    /// `real` must already hold the rendered frame, and `generated` must be
+    /// a drawable from a layer with `framebufferOnly` disabled. The
+    /// generated frame is presented at the next paced time and the real
+    /// frame one present interval later. On the first frame, or after
+    /// [`reset_history`](Self::reset_history), only `real` is presented and
+    /// `generated` is left unused.
+    pub fn encode_and_present(
+        &mut self,
+        command_buffer: &CommandBuffer,
+        inputs: &InterpolationInputs<'_>,
+        generated: &MetalDrawable,
+        real: &MetalDrawable,
+    ) -> Result<InterpolatedPresentTimes, FrameInterpolationError> {
+        let output = match &self.history {
+            Some(_) => Some(
+                generated
+                    .texture()
+                    .ok_or(FrameInterpolationError::MissingDrawableTexture)?,
+            ),
+            None => None,
+        };
+        let history = self.history.take();
+        if let (Some(history), Some(output)) = (&history, &output) {
+            self.encode_interpolation(command_buffer, inputs, history, output);
+        }
+        self.history = Some(self.store_history(command_buffer, inputs.color, history)?);
+
+        let interval = self.present_interval(inputs.delta_time);
+        self.scheduler.set_target_frame_rate(Some(1.0 / interval));
+        let now = current_media_time();
+        let times = if output.is_some() {
+            let generated_time = self.scheduler.next_present_time(now);
+            generated.present_on_at_time(command_buffer, generated_time);
+            InterpolatedPresentTimes {
+                generated: Some(generated_time),
+                real: self.scheduler.next_present_time(generated_time),
+            }
+        } else {
+            InterpolatedPresentTimes {
+                generated: None,
+                real: self.scheduler.next_present_time(now),
+            }
+        };
+        real.present_on_at_time(command_buffer, times.real);
+        Ok(times)
+    }
+
+    fn encode_interpolation(
+        &mut self,
+        command_buffer: &CommandBuffer,
+        inputs: &InterpolationInputs<'_>,
+        history: &Texture,
+        output: &Texture,
+    ) {
+        let interpolator = &self.interpolator;
+        interpolator.set_prev_color_texture(history);
+        interpolator.set_color_texture(inputs.color);
+        interpolator.set_depth_texture(inputs.depth);
+        interpolator.set_motion_texture(inputs.motion);
+        interpolator.set_output_texture(output);
+        if let Some(ui) = inputs.ui {
+            interpolator.set_ui_texture(ui);
+        }
+        interpolator.set_is_ui_texture_composited(inputs.ui.is_some());
+        interpolator.set_delta_time(inputs.delta_time);
+        if let Some(camera) = inputs.camera {
+            interpolator.set_near_plane(camera.near_plane);
+            interpolator.set_far_plane(camera.far_plane);
+            interpolator.set_field_of_view(camera.field_of_view);
+            interpolator.set_aspect_ratio(camera.aspect_ratio);
+        }
+        interpolator.set_jitter_offset_x(inputs.jitter[0]);
+        interpolator.set_jitter_offset_y(inputs.jitter[1]);
+        interpolator.set_motion_vector_scale_x(inputs.motion_vector_scale[0]);
+        interpolator.set_motion_vector_scale_y(inputs.motion_vector_scale[1]);
+        interpolator.set_depth_reversed(self.depth_reversed);
+        interpolator.set_should_reset_history(self.reset);
+        interpolator.encode_to_command_buffer(command_buffer);
+        self.reset = false;
+    }
+
+    /// Copy `color` into the history texture, reusing `previous` if it
+    /// still matches.
+    fn store_history(
+        &self,
+        command_buffer: &CommandBuffer,
+        color: &Texture,
+        previous: Option<Texture>,
+    ) -> Result<Texture, FrameInterpolationError> {
+        let (width, height) = (color.width(), color.height());
+        let history = match previous.filter(|texture| {
+            texture.width() == width
+                && texture.height() == height
+                && texture.pixel_format() == color.pixel_format()
+        }) {
+            Some(texture) => texture,
+            None => new_history_texture(&color.device(), color, width, height)?,
+        };
+        let encoder =
+            unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+                .ok_or(FrameInterpolationError::Allocation)?;
+        encoder.copy_from_texture_to_texture(color, &history);
+        encoder.end_encoding();
+        Ok(history)
+    }
+}
+
+impl fmt::Debug for FrameInterpolatorDriver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameInterpolatorDriver")
+            .field("interpolator", &self.interpolator)
+            .field("scheduler", &self.scheduler)
+            .field("has_history", &self.history.is_some())
+            .finish()
+    }
+}
+
+fn new_history_texture(
+    device: &Device,
+    color: &Texture,
+    width: UInteger,
+    height: UInteger,
+) -> Result<Texture, FrameInterpolationError> {
+    let descriptor =
+        TextureDescriptor::texture_2d_descriptor(color.pixel_format(), width, height, false)
+            .ok_or(FrameInterpolationError::Allocation)?;
+    descriptor.set_storage_mode(StorageMode::PRIVATE);
+    descriptor.set_usage(TextureUsage::SHADER_READ);
+    device
+        .new_texture_with_descriptor(&descriptor)
+        .map_err(|_| FrameInterpolationError::Allocation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_present_interval_halves_real_cadence() {
+        let Some(desc) = FrameInterpolatorDescriptor::new() else {
+            return;
+        };
+        let Some(device) = mtl_gpu::device::system_default() else {
+            return;
+        };
+        let range = DisplayRefreshRange::fixed(1.0 / 120.0);
+        let Ok(driver) = FrameInterpolatorDriver::new(&device, &desc, range) else {
+            return;
+        };
+        assert!((driver.present_interval(1.0 / 30.0) - 2.0 / 120.0).abs() < 1e-9);
+        assert!((driver.present_interval(1.0 / 60.0) - 1.0 / 120.0).abs() < 1e-9);
+        assert!((driver.present_interval(1.0 / 40.0) - 2.0 / 120.0).abs() < 1e-6);
+    }
+}
@@ -13,6 +13,8 @@
 //! - [`TemporalScaler`] - AI-based temporal upscaling using motion vectors
 //! - [`TemporalDenoisedScaler`] - Temporal upscaling with ray tracing denoising
 //! - [`FrameInterpolator`] - Frame generation for smoother gameplay
+//! - [`FrameInterpolatorDriver`] - Paced presentation of real and generated frames
+//! - [`TemporalPipeline`] - Per-frame state and validation for a [`TemporalScaler`]
 //!
 //! # Example
 //!
@@ -40,12 +42,22 @@
 
 mod enums;
 mod frame_interpolator;
+mod frame_interpolator_driver;
 mod spatial_scaler;
 mod temporal_denoised_scaler;
+mod temporal_pipeline;
 mod temporal_scaler;
 
 pub use enums::SpatialScalerColorProcessingMode;
 pub use frame_interpolator::{FrameInterpolator, FrameInterpolatorDescriptor};
+pub use frame_interpolator_driver::{
+    FrameInterpolationError, FrameInterpolatorDriver, InterpolatedPresentTimes,
+    InterpolationCamera, InterpolationInputs,
+};
 pub use spatial_scaler::{SpatialScaler, SpatialScalerDescriptor};
 pub use temporal_denoised_scaler::{TemporalDenoisedScaler, TemporalDenoisedScalerDescriptor};
+pub use temporal_pipeline::{
+    JITTER_SEQUENCE_LENGTH, TemporalFrameInputs, TemporalInput, TemporalPipeline,
+    TemporalPipelineError,
+};
 pub use temporal_scaler::{TemporalScaler, TemporalScalerDescriptor};
@@ -0,0 +1,544 @@
+//! Per-frame driver for a temporal scaler.
+//!
+//! A [`TemporalScaler`] carries a dozen properties that have to be set
+//! before every encode, and mismatched texture formats or usage flags only
+//! show up as validation failures or garbage output. [`TemporalPipeline`]
+//! owns the scaler, keeps the exposure, motion vector scale and jitter
+//! sequence between frames, checks the frame's textures against what the
+//! scaler was created for, and encodes with one call.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut pipeline = TemporalPipeline::new(&device, &descriptor)?;
+//! pipeline.set_motion_vector_scale([render_width as f32, render_height as f32]);
+//!
+//! // Every frame:
+//! let jitter = pipeline.next_jitter();
+//! // ... render with the projection offset by `jitter` ...
+//! let inputs = TemporalFrameInputs::new(&color, &depth, &motion, &output).with_jitter(jitter);
+//! pipeline.encode(&command_buffer, &inputs)?;
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::UInteger;
+use mtl_gpu::{CommandBuffer, Device, PixelFormat, Texture, TextureUsage};
+
+use crate::{TemporalScaler, TemporalScalerDescriptor};
+
+// ============================================================
+// TemporalPipelineError
+// ============================================================
+
+/// A texture passed to [`TemporalPipeline::encode`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TemporalInput {
+    /// The jittered, low-resolution color input.
+    Color,
+    /// The depth input.
+    Depth,
+    /// The motion vector input.
+    Motion,
+    /// The upscaled output.
+    Output,
+    /// The optional exposure texture.
+    Exposure,
+    /// The optional reactive mask.
+    ReactiveMask,
+}
+
+impl fmt::Display for TemporalInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TemporalInput::Color => "color",
+            TemporalInput::Depth => "depth",
+            TemporalInput::Motion => "motion",
+            TemporalInput::Output => "output",
+            TemporalInput::Exposure => "exposure",
+            TemporalInput::ReactiveMask => "reactive mask",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Error produced while creating or encoding a [`TemporalPipeline`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemporalPipelineError {
+    /// The device doesn't support temporal scaling.
+    Unsupported,
+    /// The scaler could not be created from the descriptor.
+    Creation,
+    /// A texture's pixel format differs from the scaler's.
+    Format {
+        input: TemporalInput,
+        expected: PixelFormat,
+        actual: PixelFormat,
+    },
+    /// A texture lacks usage flags the scaler requires.
+    Usage {
+        input: TemporalInput,
+        required: TextureUsage,
+        actual: TextureUsage,
+    },
+    /// A texture is smaller than the scaler's input or output size.
+    Size {
+        input: TemporalInput,
+        expected: (UInteger, UInteger),
+        actual: (UInteger, UInteger),
+    },
+}
+
+impl fmt::Display for TemporalPipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemporalPipelineError::Unsupported => {
+                write!(f, "device does not support temporal scaling")
+            }
+            TemporalPipelineError::Creation => write!(f, "failed to create temporal scaler"),
+            TemporalPipelineError::Format {
+                input,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} texture has pixel format {:?}, scaler expects {:?}",
+                input, actual, expected
+            ),
+            TemporalPipelineError::Usage {
+                input,
+                required,
+                actual,
+            } => write!(
+                f,
+                "{} texture has usage {:#x}, scaler requires {:#x}",
+                input,
+                actual.bits(),
+                required.bits()
+            ),
+            TemporalPipelineError::Size {
+                input,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} texture is {}x{}, scaler needs at least {}x{}",
+                input, actual.0, actual.1, expected.0, expected.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TemporalPipelineError {}
+
+// ============================================================
+// TemporalFrameInputs
+// ============================================================
+
+/// The textures and per-frame values for one [`TemporalPipeline::encode`].
+#[derive(Copy, Clone, Debug)]
+pub struct TemporalFrameInputs<'a> {
+    /// The jittered color input at the render resolution.
+    pub color: &'a Texture,
+    /// The depth input at the render resolution.
+    pub depth: &'a Texture,
+    /// The motion vectors at the render resolution.
+    pub motion: &'a Texture,
+    /// The texture to write the upscaled frame to.
+    pub output: &'a Texture,
+    /// A 1x1 exposure texture, used instead of the pipeline's pre-exposure
+    /// when the scaler isn't using auto exposure.
+    pub exposure: Option<&'a Texture>,
+    /// A reactive mask, if the scaler was created with one enabled.
+    pub reactive_mask: Option<&'a Texture>,
+    /// The sub-pixel offset the frame was rendered with, in input pixels.
+    pub jitter: [f32; 2],
+    /// The region of the inputs holding the frame, if smaller than the
+    /// scaler's input size because of dynamic resolution.
+    pub content_size: Option<(UInteger, UInteger)>,
+}
+
+impl<'a> TemporalFrameInputs<'a> {
+    /// Create inputs with no jitter, exposure texture or reactive mask.
+    pub fn new(
+        color: &'a Texture,
+        depth: &'a Texture,
+        motion: &'a Texture,
+        output: &'a Texture,
+    ) -> Self {
+        Self {
+            color,
+            depth,
+            motion,
+            output,
+            exposure: None,
+            reactive_mask: None,
+            jitter: [0.0; 2],
+            content_size: None,
+        }
+    }
+
+    /// Set the jitter offset.
+    pub fn with_jitter(mut self, jitter: [f32; 2]) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the exposure texture.
+    pub fn with_exposure(mut self, exposure: &'a Texture) -> Self {
+        self.exposure = Some(exposure);
+        self
+    }
+
+    /// Set the reactive mask.
+    pub fn with_reactive_mask(mut self, mask: &'a Texture) -> Self {
+        self.reactive_mask = Some(mask);
+        self
+    }
+
+    /// Set the content size for dynamic resolution.
+    pub fn with_content_size(mut self, width: UInteger, height: UInteger) -> Self {
+        self.content_size = Some((width, height));
+        self
+    }
+}
+
+// ============================================================
+// TemporalPipeline
+// ============================================================
+
+/// Number of frames in the jitter sequence from
+/// [`TemporalPipeline::next_jitter`].
+pub const JITTER_SEQUENCE_LENGTH: u32 = 32;
+
+/// A temporal scaler with the state it needs between frames.
+pub struct TemporalPipeline {
+    scaler: TemporalScaler,
+    pre_exposure: f32,
+    motion_vector_scale: [f32; 2],
+    depth_reversed: bool,
+    reset: bool,
+    frame_index: u32,
+}
+
+impl TemporalPipeline {
+    /// Create the scaler described by `descriptor` on `device`.
+    pub fn new(
+        device: &Device,
+        descriptor: &TemporalScalerDescriptor,
+    ) -> Result<Self, TemporalPipelineError> {
+        if !TemporalScalerDescriptor::supports_device(device) {
+            return Err(TemporalPipelineError::Unsupported);
+        }
+        let scaler = descriptor
+            .new_temporal_scaler(device)
+            .ok_or(TemporalPipelineError::Creation)?;
+        Ok(Self::from_scaler(scaler))
+    }
+
+    /// Drive an existing scaler.
+    ///
+    /// Motion vectors are assumed to be in input pixels and depth not
+    /// reversed until set otherwise.
+    pub fn from_scaler(scaler: TemporalScaler) -> Self {
+        Self {
+            scaler,
+            pre_exposure: 1.0,
+            motion_vector_scale: [1.0, 1.0],
+            depth_reversed: false,
+            reset: true,
+            frame_index: 0,
+        }
+    }
+
+    /// Get the scaler.
+    pub fn scaler(&self) -> &TemporalScaler {
+        &self.scaler
+    }
+
+    /// Get the pre-exposure applied to the color input.
+    pub fn pre_exposure(&self) -> f32 {
+        self.pre_exposure
+    }
+
+    /// Set the pre-exposure applied to the color input.
+    pub fn set_pre_exposure(&mut self, value: f32) {
+        self.pre_exposure = value;
+    }
+
+    /// Get the scale from motion texture values to input pixels.
+    pub fn motion_vector_scale(&self) -> [f32; 2] {
+        self.motion_vector_scale
+    }
+
+    /// Set the scale from motion texture values to input pixels, such as
+    /// the render size for motion vectors in UV units.
+    pub fn set_motion_vector_scale(&mut self, scale: [f32; 2]) {
+        self.motion_vector_scale = scale;
+    }
+
+    /// Check if the depth input is reversed.
+    pub fn is_depth_reversed(&self) -> bool {
+        self.depth_reversed
+    }
+
+    /// Set whether the depth input is reversed, with 1 nearest.
+    pub fn set_depth_reversed(&mut self, reversed: bool) {
+        self.depth_reversed = reversed;
+    }
+
+    /// Discard the scaler's history on the next encode, such as after a
+    /// camera cut. The first encode always resets.
+    pub fn reset_history(&mut self) {
+        self.reset = true;
+    }
+
+    /// Get the number of frames encoded.
+    pub fn frame_index(&self) -> u32 {
+        self.frame_index
+    }
+
+    /// Get the jitter offset to render the next frame with, in input pixels
+    /// within `[-0.5, 0.5)`.
+    ///
+    /// Follows a Halton(2, 3) sequence of [`JITTER_SEQUENCE_LENGTH`] frames
+    /// indexed by [`frame_index`](Self::frame_index).
+    pub fn next_jitter(&self) -> [f32; 2] {
+        let index = self.frame_index % JITTER_SEQUENCE_LENGTH + 1;
+        [halton(index, 2) - 0.5, halton(index, 3) - 0.5]
+    }
+
+    /// Check the inputs' pixel formats, usage flags and sizes against the
+    /// scaler.
+    pub fn validate(&self, inputs: &TemporalFrameInputs<'_>) -> Result<(), TemporalPipelineError> {
+        let scaler = &self.scaler;
+        let input_size = (scaler.input_width(), scaler.input_height());
+        let output_size = (scaler.output_width(), scaler.output_height());
+        check(
+            TemporalInput::Color,
+            inputs.color,
+            Some(scaler.color_texture_format()),
+            scaler.color_texture_usage(),
+            input_size,
+        )?;
+        check(
+            TemporalInput::Depth,
+            inputs.depth,
+            Some(scaler.depth_texture_format()),
+            scaler.depth_texture_usage(),
+            input_size,
+        )?;
+        check(
+            TemporalInput::Motion,
+            inputs.motion,
+            Some(scaler.motion_texture_format()),
+            scaler.motion_texture_usage(),
+            input_size,
+        )?;
+        check(
+            TemporalInput::Output,
+            inputs.output,
+            Some(scaler.output_texture_format()),
+            scaler.output_texture_usage(),
+            output_size,
+        )?;
+        if let Some(exposure) = inputs.exposure {
+            check(
+                TemporalInput::Exposure,
+                exposure,
+                None,
+                TextureUsage::SHADER_READ,
+                (1, 1),
+            )?;
+        }
+        if let Some(mask) = inputs.reactive_mask {
+            check(
+                TemporalInput::ReactiveMask,
+                mask,
+                Some(scaler.reactive_texture_format()),
+                scaler.reactive_texture_usage(),
+                input_size,
+            )?;
+        }
+        if let Some(size) = inputs.content_size
+            && (size.0 > input_size.0 || size.1 > input_size.1)
+        {
+            return Err(TemporalPipelineError::Size {
+                input: TemporalInput::Color,
+                expected: size,
+                actual: input_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate `inputs`, set them and the pipeline's state on the scaler,
+    /// and encode the upscale into `command_buffer`.
+    ///
+    /// Nothing is encoded if validation fails.
+    pub fn encode(
+        &mut self,
+        command_buffer: &CommandBuffer,
+        inputs: &TemporalFrameInputs<'_>,
+    ) -> Result<(), TemporalPipelineError> {
+        self.validate(inputs)?;
+        let scaler = &self.scaler;
+        scaler.set_color_texture(inputs.color);
+        scaler.set_depth_texture(inputs.depth);
+        scaler.set_motion_texture(inputs.motion);
+        scaler.set_output_texture(inputs.output);
+        if let Some(exposure) = inputs.exposure {
+            scaler.set_exposure_texture(exposure);
+        }
+        if let Some(mask) = inputs.reactive_mask {
+            scaler.set_reactive_mask_texture(mask);
+        }
+        let (width, height) = inputs
+            .content_size
+            .unwrap_or((scaler.input_width(), scaler.input_height()));
+        scaler.set_input_content_width(width);
+        scaler.set_input_content_height(height);
+        scaler.set_pre_exposure(self.pre_exposure);
+        scaler.set_jitter_offset_x(inputs.jitter[0]);
+        scaler.set_jitter_offset_y(inputs.jitter[1]);
+        scaler.set_motion_vector_scale_x(self.motion_vector_scale[0]);
+        scaler.set_motion_vector_scale_y(self.motion_vector_scale[1]);
+        scaler.set_depth_reversed(self.depth_reversed);
+        scaler.set_reset(self.reset);
+        scaler.encode_to_command_buffer(command_buffer);
+
+        self.reset = false;
+        self.frame_index = self.frame_index.wrapping_add(1);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for TemporalPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TemporalPipeline")
+            .field("scaler", &self.scaler)
+            .field("pre_exposure", &self.pre_exposure)
+            .field("motion_vector_scale", &self.motion_vector_scale)
+            .field("depth_reversed", &self.depth_reversed)
+            .field("frame_index", &self.frame_index)
+            .finish()
+    }
+}
+
+fn check(
+    input: TemporalInput,
+    texture: &Texture,
+    format: Option<PixelFormat>,
+    usage: TextureUsage,
+    min_size: (UInteger, UInteger),
+) -> Result<(), TemporalPipelineError> {
+    if let Some(expected) = format
+        && texture.pixel_format() != expected
+    {
+        return Err(TemporalPipelineError::Format {
+            input,
+            expected,
+            actual: texture.pixel_format(),
+        });
+    }
+    if !texture.usage().contains(usage) {
+        return Err(TemporalPipelineError::Usage {
+            input,
+            required: usage,
+            actual: texture.usage(),
+        });
+    }
+    let size = (texture.width(), texture.height());
+    if size.0 < min_size.0 || size.1 < min_size.1 {
+        return Err(TemporalPipelineError::Size {
+            input,
+            expected: min_size,
+            actual: size,
+        });
+    }
+    Ok(())
+}
+
+/// The `index`th element of the Halton sequence in `base`, in `[0, 1)`.
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_halton_jitter() {
+        assert_eq!(halton(1, 2), 0.5);
+        assert_eq!(halton(3, 2), 0.75);
+        assert!((halton(2, 3) - 2.0 / 3.0).abs() < 1e-6);
+        for index in 1..=JITTER_SEQUENCE_LENGTH {
+            let x = halton(index, 2) - 0.5;
+            assert!((-0.5..0.5).contains(&x));
+        }
+    }
+
+    #[test]
+    fn test_temporal_pipeline_validation() {
+        let Some(device) = mtl_gpu::device::system_default() else {
+            return;
+        };
+        let Some(desc) = TemporalScalerDescriptor::new() else {
+            return;
+        };
+        desc.set_color_texture_format(PixelFormat::RGBA16_FLOAT);
+        desc.set_depth_texture_format(PixelFormat::DEPTH32_FLOAT);
+        desc.set_motion_texture_format(PixelFormat::RG16_FLOAT);
+        desc.set_output_texture_format(PixelFormat::RGBA16_FLOAT);
+        desc.set_input_width(64);
+        desc.set_input_height(64);
+        desc.set_output_width(128);
+        desc.set_output_height(128);
+        let Ok(pipeline) = TemporalPipeline::new(&device, &desc) else {
+            return;
+        };
+        assert_eq!(pipeline.next_jitter(), [0.0, halton(1, 3) - 0.5]);
+
+        let texture = |format, size, usage| {
+            let desc = mtl_gpu::TextureDescriptor::texture_2d_descriptor(format, size, size, false)
+                .unwrap();
+            desc.set_storage_mode(mtl_gpu::StorageMode::PRIVATE);
+            desc.set_usage(usage);
+            device.new_texture_with_descriptor(&desc).unwrap()
+        };
+        let all =
+            TextureUsage::SHADER_READ | TextureUsage::SHADER_WRITE | TextureUsage::RENDER_TARGET;
+        let color = texture(PixelFormat::RGBA16_FLOAT, 64, all);
+        let depth = texture(PixelFormat::DEPTH32_FLOAT, 64, all);
+        let motion = texture(PixelFormat::RG16_FLOAT, 64, all);
+        let output = texture(PixelFormat::RGBA16_FLOAT, 128, all);
+        let small = texture(PixelFormat::RGBA16_FLOAT, 64, all);
+
+        let inputs = TemporalFrameInputs::new(&color, &depth, &motion, &output);
+        assert_eq!(pipeline.validate(&inputs), Ok(()));
+        let inputs = TemporalFrameInputs::new(&color, &depth, &motion, &small);
+        assert!(matches!(
+            pipeline.validate(&inputs),
+            Err(TemporalPipelineError::Size {
+                input: TemporalInput::Output,
+                ..
+            })
+        ));
+        let inputs = TemporalFrameInputs::new(&color, &color, &motion, &output);
+        assert!(matches!(
+            pipeline.validate(&inputs),
+            Err(TemporalPipelineError::Format {
+                input: TemporalInput::Depth,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,125 @@
+//! MetalPerformanceShaders enumerations.
+//!
+//! Corresponds to enums in `MPSCore/MPSCoreTypes.h` and
+//! `MPSImage/MPSImageKernel.h`.
+
+use mtl_foundation::UInteger;
+
+/// How a kernel samples pixels outside the source texture.
+///
+/// C++ equivalent: `MPSImageEdgeMode`
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ImageEdgeMode(pub UInteger);
+
+impl ImageEdgeMode {
+    /// Out-of-bounds pixels read as zero.
+    pub const ZERO: Self = Self(0);
+
+    /// Out-of-bounds pixels read the nearest edge pixel.
+    pub const CLAMP: Self = Self(1);
+
+    /// Out-of-bounds pixels mirror the image, excluding the edge pixel.
+    pub const MIRROR: Self = Self(2);
+
+    /// Out-of-bounds pixels mirror the image, including the edge pixel.
+    pub const MIRROR_WITH_EDGE: Self = Self(3);
+
+    /// Out-of-bounds pixels read a constant value.
+    pub const CONSTANT: Self = Self(4);
+}
+
+/// Alpha interpretation for image conversion.
+///
+/// C++ equivalent: `MPSAlphaType`
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AlphaType(pub UInteger);
+
+impl AlphaType {
+    /// Color channels are not multiplied by alpha.
+    pub const NON_PREMULTIPLIED: Self = Self(0);
+
+    /// Alpha is ignored and treated as 1.0.
+    pub const ALPHA_IS_ONE: Self = Self(1);
+
+    /// Color channels are premultiplied by alpha.
+    pub const PREMULTIPLIED: Self = Self(2);
+}
+
+/// Element type of matrix data.
+///
+/// C++ equivalent: `MPSDataType`
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DataType(pub u32);
+
+impl DataType {
+    const FLOAT_BIT: u32 = 0x1000_0000;
+    const SIGNED_BIT: u32 = 0x2000_0000;
+    const NORMALIZED_BIT: u32 = 0x4000_0000;
+
+    /// Invalid data type.
+    pub const INVALID: Self = Self(0);
+
+    /// 32-bit float.
+    pub const FLOAT32: Self = Self(Self::FLOAT_BIT | 32);
+
+    /// 16-bit float.
+    pub const FLOAT16: Self = Self(Self::FLOAT_BIT | 16);
+
+    /// Signed 8-bit integer.
+    pub const INT8: Self = Self(Self::SIGNED_BIT | 8);
+
+    /// Signed 16-bit integer.
+    pub const INT16: Self = Self(Self::SIGNED_BIT | 16);
+
+    /// Signed 32-bit integer.
+    pub const INT32: Self = Self(Self::SIGNED_BIT | 32);
+
+    /// Unsigned 8-bit integer.
+    pub const UINT8: Self = Self(8);
+
+    /// Unsigned 16-bit integer.
+    pub const UINT16: Self = Self(16);
+
+    /// Unsigned 32-bit integer.
+    pub const UINT32: Self = Self(32);
+
+    /// Unsigned 8-bit integer normalized to `[0, 1]`.
+    pub const UNORM8: Self = Self(Self::NORMALIZED_BIT | 8);
+
+    /// Get the size of one element in bytes.
+    #[inline]
+    pub const fn size_in_bytes(self) -> UInteger {
+        ((self.0 & 0xFFFF) as UInteger) / 8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_mode_values() {
+        assert_eq!(ImageEdgeMode::ZERO.0, 0);
+        assert_eq!(ImageEdgeMode::CLAMP.0, 1);
+        assert_eq!(ImageEdgeMode::CONSTANT.0, 4);
+    }
+
+    #[test]
+    fn test_data_type_values() {
+        assert_eq!(DataType::FLOAT32.0, 0x1000_0020);
+        assert_eq!(DataType::FLOAT16.0, 0x1000_0010);
+        assert_eq!(DataType::INT8.0, 0x2000_0008);
+        assert_eq!(DataType::UINT32.0, 32);
+        assert_eq!(DataType::UNORM8.0, 0x4000_0008);
+    }
+
+    #[test]
+    fn test_data_type_size() {
+        assert_eq!(DataType::FLOAT32.size_in_bytes(), 4);
+        assert_eq!(DataType::FLOAT16.size_in_bytes(), 2);
+        assert_eq!(DataType::UNORM8.size_in_bytes(), 1);
+    }
+}
@@ -0,0 +1,147 @@
+//! ImageConversion implementation.
+//!
+//! Corresponds to `MPSImage/MPSImageConversion.h`.
+
+use std::ffi::c_void;
+use std::ptr::{self, NonNull};
+
+use mtl_foundation::Referencing;
+use mtl_sys::{msg_send_0, msg_send_3, msg_send_5, sel};
+
+use crate::enums::AlphaType;
+
+/// Pixel format and alpha conversion between textures.
+///
+/// C++ equivalent: `MPSImageConversion`
+///
+/// Copies a source texture into a destination texture of the same size,
+/// converting between their pixel formats and alpha representations.
+#[repr(transparent)]
+pub struct ImageConversion(NonNull<c_void>);
+
+impl ImageConversion {
+    /// Create an ImageConversion from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid MPSImageConversion object.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
+        NonNull::new(ptr).map(Self)
+    }
+
+    /// Get the raw pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+
+    /// Create a conversion between alpha representations.
+    ///
+    /// `background_color` is composited under the source when the
+    /// destination alpha is [`AlphaType::ALPHA_IS_ONE`]. No color space
+    /// conversion is performed.
+    ///
+    /// C++ equivalent: `initWithDevice:srcAlpha:destAlpha:backgroundColor:conversionInfo:`
+    pub fn new(
+        device: &mtl_gpu::Device,
+        source_alpha: AlphaType,
+        destination_alpha: AlphaType,
+        background_color: Option<[f64; 4]>,
+    ) -> Option<Self> {
+        unsafe {
+            let class = mtl_sys::Class::get("MPSImageConversion")?;
+            let ptr: *mut c_void = msg_send_0(class.as_ptr(), sel!(alloc));
+            if ptr.is_null() {
+                return None;
+            }
+            let background = background_color
+                .as_ref()
+                .map_or(ptr::null(), |color| color.as_ptr());
+            let ptr: *mut c_void = msg_send_5(
+                ptr,
+                sel!(initWithDevice:srcAlpha:destAlpha:backgroundColor:conversionInfo:),
+                device.as_ptr(),
+                source_alpha,
+                destination_alpha,
+                background,
+                ptr::null::<c_void>(),
+            );
+            Self::from_raw(ptr)
+        }
+    }
+
+    // ========== Properties ==========
+
+    /// Get the alpha representation of the source.
+    pub fn source_alpha(&self) -> AlphaType {
+        unsafe { msg_send_0(self.as_ptr(), sel!(sourceAlpha)) }
+    }
+
+    /// Get the alpha representation of the destination.
+    pub fn destination_alpha(&self) -> AlphaType {
+        unsafe { msg_send_0(self.as_ptr(), sel!(destinationAlpha)) }
+    }
+
+    // ========== Encoding ==========
+
+    /// Encode the conversion from `source` into `destination`.
+    ///
+    /// C++ equivalent: `encodeToCommandBuffer:sourceTexture:destinationTexture:`
+    pub fn encode(
+        &self,
+        command_buffer: &mtl_gpu::CommandBuffer,
+        source: &mtl_gpu::Texture,
+        destination: &mtl_gpu::Texture,
+    ) {
+        unsafe {
+            let _: () = msg_send_3(
+                self.as_ptr(),
+                sel!(encodeToCommandBuffer:sourceTexture:destinationTexture:),
+                command_buffer.as_ptr(),
+                source.as_ptr(),
+                destination.as_ptr(),
+            );
+        }
+    }
+}
+
+impl Clone for ImageConversion {
+    fn clone(&self) -> Self {
+        unsafe {
+            mtl_sys::msg_send_0::<*mut c_void>(self.as_ptr(), mtl_sys::sel!(retain));
+        }
+        Self(self.0)
+    }
+}
+
+impl Drop for ImageConversion {
+    fn drop(&mut self) {
+        unsafe {
+            mtl_sys::msg_send_0::<()>(self.as_ptr(), mtl_sys::sel!(release));
+        }
+    }
+}
+
+impl Referencing for ImageConversion {
+    #[inline]
+    fn as_ptr(&self) -> *const c_void {
+        self.0.as_ptr()
+    }
+}
+
+unsafe impl Send for ImageConversion {}
+unsafe impl Sync for ImageConversion {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_conversion_size() {
+        assert_eq!(
+            std::mem::size_of::<ImageConversion>(),
+            std::mem::size_of::<*mut c_void>()
+        );
+    }
+}
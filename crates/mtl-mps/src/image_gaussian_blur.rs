@@ -0,0 +1,135 @@
+//! ImageGaussianBlur implementation.
+//!
+//! Corresponds to `MPSImage/MPSImageConvolution.h`.
+
+use std::ffi::c_void;
+use std::ptr::NonNull;
+
+use mtl_foundation::Referencing;
+use mtl_sys::{msg_send_0, msg_send_1, msg_send_2, msg_send_3, sel};
+
+use crate::enums::ImageEdgeMode;
+
+/// Gaussian blur filter.
+///
+/// C++ equivalent: `MPSImageGaussianBlur`
+///
+/// Blurs a source texture into a destination texture of the same size. The
+/// destination must not alias the source.
+#[repr(transparent)]
+pub struct ImageGaussianBlur(NonNull<c_void>);
+
+impl ImageGaussianBlur {
+    /// Create an ImageGaussianBlur from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid MPSImageGaussianBlur object.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
+        NonNull::new(ptr).map(Self)
+    }
+
+    /// Get the raw pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+
+    /// Create a blur with standard deviation `sigma`, in pixels.
+    ///
+    /// C++ equivalent: `initWithDevice:sigma:`
+    pub fn new(device: &mtl_gpu::Device, sigma: f32) -> Option<Self> {
+        unsafe {
+            let class = mtl_sys::Class::get("MPSImageGaussianBlur")?;
+            let ptr: *mut c_void = msg_send_0(class.as_ptr(), sel!(alloc));
+            if ptr.is_null() {
+                return None;
+            }
+            let ptr: *mut c_void =
+                msg_send_2(ptr, sel!(initWithDevice:sigma:), device.as_ptr(), sigma);
+            Self::from_raw(ptr)
+        }
+    }
+
+    // ========== Properties ==========
+
+    /// Get the standard deviation of the blur.
+    pub fn sigma(&self) -> f32 {
+        unsafe { msg_send_0(self.as_ptr(), sel!(sigma)) }
+    }
+
+    /// Get how pixels outside the source are sampled.
+    pub fn edge_mode(&self) -> ImageEdgeMode {
+        unsafe { msg_send_0(self.as_ptr(), sel!(edgeMode)) }
+    }
+
+    /// Set how pixels outside the source are sampled.
+    pub fn set_edge_mode(&self, mode: ImageEdgeMode) {
+        unsafe {
+            let _: () = msg_send_1(self.as_ptr(), sel!(setEdgeMode:), mode);
+        }
+    }
+
+    // ========== Encoding ==========
+
+    /// Encode the blur from `source` into `destination`.
+    ///
+    /// C++ equivalent: `encodeToCommandBuffer:sourceTexture:destinationTexture:`
+    pub fn encode(
+        &self,
+        command_buffer: &mtl_gpu::CommandBuffer,
+        source: &mtl_gpu::Texture,
+        destination: &mtl_gpu::Texture,
+    ) {
+        unsafe {
+            let _: () = msg_send_3(
+                self.as_ptr(),
+                sel!(encodeToCommandBuffer:sourceTexture:destinationTexture:),
+                command_buffer.as_ptr(),
+                source.as_ptr(),
+                destination.as_ptr(),
+            );
+        }
+    }
+}
+
+impl Clone for ImageGaussianBlur {
+    fn clone(&self) -> Self {
+        unsafe {
+            mtl_sys::msg_send_0::<*mut c_void>(self.as_ptr(), mtl_sys::sel!(retain));
+        }
+        Self(self.0)
+    }
+}
+
+impl Drop for ImageGaussianBlur {
+    fn drop(&mut self) {
+        unsafe {
+            mtl_sys::msg_send_0::<()>(self.as_ptr(), mtl_sys::sel!(release));
+        }
+    }
+}
+
+impl Referencing for ImageGaussianBlur {
+    #[inline]
+    fn as_ptr(&self) -> *const c_void {
+        self.0.as_ptr()
+    }
+}
+
+unsafe impl Send for ImageGaussianBlur {}
+unsafe impl Sync for ImageGaussianBlur {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_gaussian_blur_size() {
+        assert_eq!(
+            std::mem::size_of::<ImageGaussianBlur>(),
+            std::mem::size_of::<*mut c_void>()
+        );
+    }
+}
@@ -0,0 +1,67 @@
+// Clippy allows for FFI binding patterns
+#![allow(clippy::not_unsafe_ptr_arg_deref)] // Raw pointer args are passed to Obj-C, not dereferenced in Rust
+#![allow(clippy::missing_safety_doc)] // from_raw patterns are consistent across the crate
+
+//! MetalPerformanceShaders bindings for image filters and matrix kernels.
+//!
+//! This crate provides safe Rust bindings to a core subset of Apple's
+//! MetalPerformanceShaders framework. Kernels take the `mtl-gpu` [`Device`],
+//! [`CommandBuffer`], [`Texture`] and [`Buffer`] types directly.
+//!
+//! # Key Types
+//!
+//! - [`ImageGaussianBlur`] - Gaussian blur between textures
+//! - [`ImageConversion`] - Pixel format and alpha conversion between textures
+//! - [`Matrix`] / [`MatrixDescriptor`] - Row-major matrix views over buffers
+//! - [`MatrixMultiplication`] - General matrix multiply
+//!
+//! # Example
+//!
+//! ```ignore
+//! use mtl_mps::{DataType, Matrix, MatrixDescriptor, MatrixMultiplication};
+//!
+//! let desc = MatrixDescriptor::packed(64, 64, DataType::FLOAT32).unwrap();
+//! let a = Matrix::new(&a_buffer, &desc).unwrap();
+//! let b = Matrix::new(&b_buffer, &desc).unwrap();
+//! let c = Matrix::new(&c_buffer, &desc).unwrap();
+//!
+//! let gemm = MatrixMultiplication::new(&device, 64, 64, 64).unwrap();
+//! gemm.encode(&command_buffer, &a, &b, &c);
+//! command_buffer.commit();
+//! ```
+//!
+//! [`Device`]: mtl_gpu::Device
+//! [`CommandBuffer`]: mtl_gpu::CommandBuffer
+//! [`Texture`]: mtl_gpu::Texture
+//! [`Buffer`]: mtl_gpu::Buffer
+
+#![allow(dead_code)]
+
+use std::ffi::c_void;
+
+use mtl_foundation::Referencing;
+
+mod enums;
+mod image_conversion;
+mod image_gaussian_blur;
+mod matrix;
+
+pub use enums::{AlphaType, DataType, ImageEdgeMode};
+pub use image_conversion::ImageConversion;
+pub use image_gaussian_blur::ImageGaussianBlur;
+pub use matrix::{Matrix, MatrixDescriptor, MatrixMultiplication};
+
+#[cfg_attr(
+    target_vendor = "apple",
+    link(name = "MetalPerformanceShaders", kind = "framework")
+)]
+unsafe extern "C" {
+    fn MPSSupportsMTLDevice(device: *const c_void) -> bool;
+}
+
+/// Check if MetalPerformanceShaders supports a device.
+///
+/// C++ equivalent: `BOOL MPSSupportsMTLDevice(id<MTLDevice>)`
+pub fn supports_device(device: &mtl_gpu::Device) -> bool {
+    unsafe { MPSSupportsMTLDevice(device.as_ptr()) }
+}
@@ -0,0 +1,428 @@
+//! Matrix and MatrixMultiplication implementation.
+//!
+//! Corresponds to `MPSCore/MPSMatrix.h` and
+//! `MPSMatrix/MPSMatrixMultiplication.h`.
+
+use std::ffi::c_void;
+use std::ptr::NonNull;
+
+use mtl_foundation::{Referencing, UInteger};
+use mtl_sys::{msg_send_0, msg_send_1, msg_send_2, msg_send_4, msg_send_8, sel};
+
+use crate::enums::DataType;
+
+// ============================================================
+// MatrixDescriptor
+// ============================================================
+
+/// Describes the layout of a row-major matrix in a buffer.
+///
+/// C++ equivalent: `MPSMatrixDescriptor`
+#[repr(transparent)]
+pub struct MatrixDescriptor(NonNull<c_void>);
+
+impl MatrixDescriptor {
+    /// Create a MatrixDescriptor from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid MPSMatrixDescriptor object.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
+        NonNull::new(ptr).map(Self)
+    }
+
+    /// Get the raw pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+
+    /// Create a descriptor for a `rows` x `columns` matrix.
+    ///
+    /// `row_bytes` is the stride between rows and must be at least
+    /// `columns * data_type.size_in_bytes()`.
+    ///
+    /// C++ equivalent: `matrixDescriptorWithRows:columns:rowBytes:dataType:`
+    pub fn new(
+        rows: UInteger,
+        columns: UInteger,
+        row_bytes: UInteger,
+        data_type: DataType,
+    ) -> Option<Self> {
+        unsafe {
+            let class = mtl_sys::Class::get("MPSMatrixDescriptor")?;
+            let ptr: *mut c_void = msg_send_4(
+                class.as_ptr(),
+                sel!(matrixDescriptorWithRows:columns:rowBytes:dataType:),
+                rows,
+                columns,
+                row_bytes,
+                data_type,
+            );
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            Self::from_raw(ptr)
+        }
+    }
+
+    /// Create a descriptor for a tightly packed `rows` x `columns` matrix.
+    pub fn packed(rows: UInteger, columns: UInteger, data_type: DataType) -> Option<Self> {
+        Self::new(
+            rows,
+            columns,
+            columns * data_type.size_in_bytes(),
+            data_type,
+        )
+    }
+
+    /// Get the recommended row stride for `columns` elements of `data_type`.
+    ///
+    /// C++ equivalent: `rowBytesForColumns:dataType:`
+    pub fn row_bytes_for_columns(columns: UInteger, data_type: DataType) -> UInteger {
+        unsafe {
+            let class = match mtl_sys::Class::get("MPSMatrixDescriptor") {
+                Some(c) => c,
+                None => return columns * data_type.size_in_bytes(),
+            };
+            msg_send_2(
+                class.as_ptr(),
+                sel!(rowBytesForColumns:dataType:),
+                columns,
+                data_type,
+            )
+        }
+    }
+
+    // ========== Properties ==========
+
+    /// Get the number of rows.
+    pub fn rows(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(rows)) }
+    }
+
+    /// Get the number of columns.
+    pub fn columns(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(columns)) }
+    }
+
+    /// Get the stride between rows in bytes.
+    pub fn row_bytes(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(rowBytes)) }
+    }
+
+    /// Get the element type.
+    pub fn data_type(&self) -> DataType {
+        unsafe { msg_send_0(self.as_ptr(), sel!(dataType)) }
+    }
+}
+
+impl Clone for MatrixDescriptor {
+    fn clone(&self) -> Self {
+        unsafe {
+            mtl_sys::msg_send_0::<*mut c_void>(self.as_ptr(), mtl_sys::sel!(retain));
+        }
+        Self(self.0)
+    }
+}
+
+impl Drop for MatrixDescriptor {
+    fn drop(&mut self) {
+        unsafe {
+            mtl_sys::msg_send_0::<()>(self.as_ptr(), mtl_sys::sel!(release));
+        }
+    }
+}
+
+impl Referencing for MatrixDescriptor {
+    #[inline]
+    fn as_ptr(&self) -> *const c_void {
+        self.0.as_ptr()
+    }
+}
+
+unsafe impl Send for MatrixDescriptor {}
+unsafe impl Sync for MatrixDescriptor {}
+
+// ============================================================
+// Matrix
+// ============================================================
+
+/// A matrix view over a Metal buffer.
+///
+/// C++ equivalent: `MPSMatrix`
+#[repr(transparent)]
+pub struct Matrix(NonNull<c_void>);
+
+impl Matrix {
+    /// Create a Matrix from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid MPSMatrix object.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
+        NonNull::new(ptr).map(Self)
+    }
+
+    /// Get the raw pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+
+    /// Wrap `buffer` with the layout described by `descriptor`.
+    ///
+    /// C++ equivalent: `initWithBuffer:descriptor:`
+    pub fn new(buffer: &mtl_gpu::Buffer, descriptor: &MatrixDescriptor) -> Option<Self> {
+        unsafe {
+            let class = mtl_sys::Class::get("MPSMatrix")?;
+            let ptr: *mut c_void = msg_send_0(class.as_ptr(), sel!(alloc));
+            if ptr.is_null() {
+                return None;
+            }
+            let ptr: *mut c_void = msg_send_2(
+                ptr,
+                sel!(initWithBuffer:descriptor:),
+                buffer.as_ptr(),
+                descriptor.as_ptr(),
+            );
+            Self::from_raw(ptr)
+        }
+    }
+
+    // ========== Properties ==========
+
+    /// Get the number of rows.
+    pub fn rows(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(rows)) }
+    }
+
+    /// Get the number of columns.
+    pub fn columns(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(columns)) }
+    }
+
+    /// Get the stride between rows in bytes.
+    pub fn row_bytes(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(rowBytes)) }
+    }
+
+    /// Get the element type.
+    pub fn data_type(&self) -> DataType {
+        unsafe { msg_send_0(self.as_ptr(), sel!(dataType)) }
+    }
+
+    /// Get the buffer holding the matrix data.
+    pub fn data(&self) -> Option<mtl_gpu::Buffer> {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(data));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            mtl_gpu::Buffer::from_raw(ptr)
+        }
+    }
+}
+
+impl Clone for Matrix {
+    fn clone(&self) -> Self {
+        unsafe {
+            mtl_sys::msg_send_0::<*mut c_void>(self.as_ptr(), mtl_sys::sel!(retain));
+        }
+        Self(self.0)
+    }
+}
+
+impl Drop for Matrix {
+    fn drop(&mut self) {
+        unsafe {
+            mtl_sys::msg_send_0::<()>(self.as_ptr(), mtl_sys::sel!(release));
+        }
+    }
+}
+
+impl Referencing for Matrix {
+    #[inline]
+    fn as_ptr(&self) -> *const c_void {
+        self.0.as_ptr()
+    }
+}
+
+unsafe impl Send for Matrix {}
+unsafe impl Sync for Matrix {}
+
+// ============================================================
+// MatrixMultiplication
+// ============================================================
+
+/// General matrix multiply: `C = alpha * op(A) * op(B) + beta * C`.
+///
+/// C++ equivalent: `MPSMatrixMultiplication`
+#[repr(transparent)]
+pub struct MatrixMultiplication(NonNull<c_void>);
+
+impl MatrixMultiplication {
+    /// Create a MatrixMultiplication from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid MPSMatrixMultiplication object.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
+        NonNull::new(ptr).map(Self)
+    }
+
+    /// Get the raw pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+
+    /// Create a kernel computing `C = A * B`.
+    ///
+    /// The result is `result_rows` x `result_columns`, and `interior_columns`
+    /// is the shared dimension of `A` and `B`.
+    ///
+    /// C++ equivalent: `initWithDevice:resultRows:resultColumns:interiorColumns:`
+    pub fn new(
+        device: &mtl_gpu::Device,
+        result_rows: UInteger,
+        result_columns: UInteger,
+        interior_columns: UInteger,
+    ) -> Option<Self> {
+        unsafe {
+            let class = mtl_sys::Class::get("MPSMatrixMultiplication")?;
+            let ptr: *mut c_void = msg_send_0(class.as_ptr(), sel!(alloc));
+            if ptr.is_null() {
+                return None;
+            }
+            let ptr: *mut c_void = msg_send_4(
+                ptr,
+                sel!(initWithDevice:resultRows:resultColumns:interiorColumns:),
+                device.as_ptr(),
+                result_rows,
+                result_columns,
+                interior_columns,
+            );
+            Self::from_raw(ptr)
+        }
+    }
+
+    /// Create a kernel with transposed operands and scale factors.
+    ///
+    /// C++ equivalent: `initWithDevice:transposeLeft:transposeRight:resultRows:resultColumns:interiorColumns:alpha:beta:`
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_options(
+        device: &mtl_gpu::Device,
+        transpose_left: bool,
+        transpose_right: bool,
+        result_rows: UInteger,
+        result_columns: UInteger,
+        interior_columns: UInteger,
+        alpha: f64,
+        beta: f64,
+    ) -> Option<Self> {
+        unsafe {
+            let class = mtl_sys::Class::get("MPSMatrixMultiplication")?;
+            let ptr: *mut c_void = msg_send_0(class.as_ptr(), sel!(alloc));
+            if ptr.is_null() {
+                return None;
+            }
+            let ptr: *mut c_void = msg_send_8(
+                ptr,
+                sel!(initWithDevice:transposeLeft:transposeRight:resultRows:resultColumns:interiorColumns:alpha:beta:),
+                device.as_ptr(),
+                transpose_left,
+                transpose_right,
+                result_rows,
+                result_columns,
+                interior_columns,
+                alpha,
+                beta,
+            );
+            Self::from_raw(ptr)
+        }
+    }
+
+    // ========== Properties ==========
+
+    /// Get the number of batch entries processed per encode.
+    pub fn batch_size(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(batchSize)) }
+    }
+
+    /// Set the number of batch entries processed per encode.
+    pub fn set_batch_size(&self, size: UInteger) {
+        unsafe {
+            let _: () = msg_send_1(self.as_ptr(), sel!(setBatchSize:), size);
+        }
+    }
+
+    // ========== Encoding ==========
+
+    /// Encode the multiplication of `left` by `right` into `result`.
+    ///
+    /// C++ equivalent: `encodeToCommandBuffer:leftMatrix:rightMatrix:resultMatrix:`
+    pub fn encode(
+        &self,
+        command_buffer: &mtl_gpu::CommandBuffer,
+        left: &Matrix,
+        right: &Matrix,
+        result: &Matrix,
+    ) {
+        unsafe {
+            let _: () = msg_send_4(
+                self.as_ptr(),
+                sel!(encodeToCommandBuffer:leftMatrix:rightMatrix:resultMatrix:),
+                command_buffer.as_ptr(),
+                left.as_ptr(),
+                right.as_ptr(),
+                result.as_ptr(),
+            );
+        }
+    }
+}
+
+impl Clone for MatrixMultiplication {
+    fn clone(&self) -> Self {
+        unsafe {
+            mtl_sys::msg_send_0::<*mut c_void>(self.as_ptr(), mtl_sys::sel!(retain));
+        }
+        Self(self.0)
+    }
+}
+
+impl Drop for MatrixMultiplication {
+    fn drop(&mut self) {
+        unsafe {
+            mtl_sys::msg_send_0::<()>(self.as_ptr(), mtl_sys::sel!(release));
+        }
+    }
+}
+
+impl Referencing for MatrixMultiplication {
+    #[inline]
+    fn as_ptr(&self) -> *const c_void {
+        self.0.as_ptr()
+    }
+}
+
+unsafe impl Send for MatrixMultiplication {}
+unsafe impl Sync for MatrixMultiplication {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_sizes() {
+        let ptr = std::mem::size_of::<*mut c_void>();
+        assert_eq!(std::mem::size_of::<MatrixDescriptor>(), ptr);
+        assert_eq!(std::mem::size_of::<Matrix>(), ptr);
+        assert_eq!(std::mem::size_of::<MatrixMultiplication>(), ptr);
+    }
+}
@@ -0,0 +1,174 @@
+//! Frame pacing feedback from drawable presentation times.
+//!
+//! [`FramePacer`] records when each drawable actually reached the display
+//! and reports the measured refresh cadence and how many refreshes were
+//! missed, so a render loop can lower its workload or its target rate.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let pacer = FramePacer::new(1.0 / 60.0);
+//!
+//! // Each frame:
+//! let frame = swap_chain.acquire_frame()?;
+//! pacer.track(&frame.drawable);
+//! frame.present(&command_buffer);
+//! command_buffer.commit();
+//!
+//! let stats = pacer.stats();
+//! if stats.missed_frames > missed_last_second {
+//!     // drop quality
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "blocks")]
+use crate::metal_drawable::MetalDrawable;
+use crate::metal_drawable::TimeInterval;
+
+/// Number of recent presentation intervals averaged by [`FramePacer`].
+const INTERVAL_WINDOW: usize = 120;
+
+/// Presentation statistics reported by [`FramePacer::stats`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FramePacingStats {
+    /// Drawables shown on the display.
+    pub presented: u64,
+    /// Drawables discarded without being shown.
+    pub dropped: u64,
+    /// Display refreshes that passed without a new frame.
+    pub missed_frames: u64,
+    /// Time between the last two presented frames.
+    pub last_interval: Option<TimeInterval>,
+    /// Mean time between presented frames over the recent window.
+    pub average_interval: Option<TimeInterval>,
+}
+
+impl FramePacingStats {
+    /// Get the measured presentation rate in frames per second.
+    pub fn frame_rate(&self) -> Option<f64> {
+        self.average_interval
+            .filter(|interval| *interval > 0.0)
+            .map(|interval| 1.0 / interval)
+    }
+}
+
+#[derive(Debug)]
+struct PacerState {
+    stats: FramePacingStats,
+    last_presented: Option<TimeInterval>,
+    intervals: VecDeque<TimeInterval>,
+}
+
+/// Measures presentation cadence from drawables' `presentedTime`.
+///
+/// Cloning a pacer shares its statistics.
+#[derive(Clone, Debug)]
+pub struct FramePacer {
+    refresh_interval: TimeInterval,
+    state: Arc<Mutex<PacerState>>,
+}
+
+impl FramePacer {
+    /// Create a pacer for a display refreshing every `refresh_interval`
+    /// seconds, e.g. `1.0 / 60.0`.
+    pub fn new(refresh_interval: TimeInterval) -> Self {
+        Self {
+            refresh_interval,
+            state: Arc::new(Mutex::new(PacerState {
+                stats: FramePacingStats::default(),
+                last_presented: None,
+                intervals: VecDeque::with_capacity(INTERVAL_WINDOW),
+            })),
+        }
+    }
+
+    /// Get the display refresh interval the pacer measures against.
+    pub fn refresh_interval(&self) -> TimeInterval {
+        self.refresh_interval
+    }
+
+    /// Record `drawable`'s presentation time once it has been presented.
+    ///
+    /// Call before presenting the drawable.
+    #[cfg(feature = "blocks")]
+    pub fn track(&self, drawable: &MetalDrawable) {
+        let pacer = self.clone();
+        drawable.add_presented_handler(move |drawable| {
+            pacer.record_presented_time(drawable.presented_time());
+        });
+    }
+
+    /// Record the `presentedTime` of a drawable.
+    ///
+    /// A time of 0.0 counts the drawable as dropped. Times must be recorded
+    /// in presentation order.
+    pub fn record_presented_time(&self, presented_time: TimeInterval) {
+        let mut state = self.state.lock().unwrap();
+        if presented_time <= 0.0 {
+            state.stats.dropped += 1;
+            return;
+        }
+        state.stats.presented += 1;
+
+        let previous = state.last_presented.replace(presented_time);
+        let Some(interval) = previous
+            .map(|previous| presented_time - previous)
+            .filter(|interval| *interval > 0.0)
+        else {
+            return;
+        };
+
+        if self.refresh_interval > 0.0 {
+            let refreshes = (interval / self.refresh_interval).round() as u64;
+            state.stats.missed_frames += refreshes.saturating_sub(1);
+        }
+        if state.intervals.len() == INTERVAL_WINDOW {
+            state.intervals.pop_front();
+        }
+        state.intervals.push_back(interval);
+        state.stats.last_interval = Some(interval);
+        state.stats.average_interval =
+            Some(state.intervals.iter().sum::<TimeInterval>() / state.intervals.len() as f64);
+    }
+
+    /// Get the statistics recorded so far.
+    pub fn stats(&self) -> FramePacingStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// Clear the recorded statistics.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.stats = FramePacingStats::default();
+        state.last_presented = None;
+        state.intervals.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_pacer_counts_missed_frames() {
+        let pacer = FramePacer::new(1.0 / 60.0);
+        let frame = 1.0 / 60.0;
+        for time in [1.0, 1.0 + frame, 1.0 + 2.0 * frame, 1.0 + 4.0 * frame] {
+            pacer.record_presented_time(time);
+        }
+        pacer.record_presented_time(0.0);
+
+        let stats = pacer.stats();
+        assert_eq!(stats.presented, 4);
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.missed_frames, 1);
+        assert!((stats.last_interval.unwrap() - 2.0 * frame).abs() < 1e-9);
+        assert!((stats.frame_rate().unwrap() - 45.0).abs() < 1e-6);
+
+        pacer.reset();
+        assert_eq!(pacer.stats(), FramePacingStats::default());
+    }
+}
@@ -0,0 +1,468 @@
+//! Extended dynamic range output.
+//!
+//! Showing HDR content through a [`MetalLayer`] takes four settings that
+//! have to agree: `wantsExtendedDynamicRangeContent`, a pixel format with
+//! enough range, a Core Graphics color space with the right transfer
+//! function, and for PQ and HLG content, `CAEDRMetadata` describing the
+//! mastering display. [`MetalLayer::configure_hdr`] applies all of them for
+//! an [`HdrMode`], with the color spaces created through [`ColorSpace`] so
+//! no separate Core Graphics bindings are needed.
+//!
+//! [`EdrHeadroom`] reports how far above SDR white the display can
+//! currently go, which tone mappers use to scale highlights.
+//!
+//! # Example
+//!
+//! ```ignore
+//! layer.configure_hdr(HdrMode::Pq, Some(&EdrMetadata::hdr10(0.005, 1000.0, 100.0).unwrap()))?;
+//!
+//! // Each frame:
+//! let headroom = EdrHeadroom::main_screen().map_or(1.0, |h| h.current);
+//! tone_map_params.max_output = headroom;
+//! ```
+
+use std::ffi::c_void;
+use std::fmt;
+use std::ptr::NonNull;
+
+use mtl_foundation::Referencing;
+use mtl_gpu::PixelFormat;
+use mtl_sys::{msg_send_0, msg_send_1, msg_send_3, sel};
+
+use crate::metal_layer::MetalLayer;
+use crate::types::{CGColorSpaceRef, CGFloat};
+
+#[cfg_attr(
+    target_vendor = "apple",
+    link(name = "CoreGraphics", kind = "framework")
+)]
+unsafe extern "C" {
+    fn CGColorSpaceCreateWithName(name: *const c_void) -> CGColorSpaceRef;
+    fn CGColorSpaceRetain(space: CGColorSpaceRef) -> CGColorSpaceRef;
+    fn CGColorSpaceRelease(space: CGColorSpaceRef);
+
+    static kCGColorSpaceExtendedSRGB: *const c_void;
+    static kCGColorSpaceExtendedLinearSRGB: *const c_void;
+    static kCGColorSpaceExtendedLinearDisplayP3: *const c_void;
+    static kCGColorSpaceITUR_2100_PQ: *const c_void;
+    static kCGColorSpaceITUR_2100_HLG: *const c_void;
+}
+
+// ============================================================================
+// HdrMode
+// ============================================================================
+
+/// How a layer's pixel values map to display luminance.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HdrMode {
+    /// sRGB-encoded values, with 1.0 at SDR white and values above it
+    /// extending into the headroom.
+    ExtendedSrgb,
+    /// Linear sRGB primaries, with 1.0 at SDR white.
+    ExtendedLinearSrgb,
+    /// Linear Display P3 primaries, with 1.0 at SDR white.
+    ExtendedLinearDisplayP3,
+    /// BT.2100 primaries with the PQ transfer function, for HDR10 content
+    /// in absolute luminance.
+    Pq,
+    /// BT.2100 primaries with the hybrid log-gamma transfer function.
+    Hlg,
+}
+
+impl HdrMode {
+    /// Get the drawable pixel format for this mode.
+    ///
+    /// Extended-range modes need a float format to store values above 1.0.
+    /// PQ and HLG encode the whole range in `[0, 1]`, so 10 bits suffice.
+    pub fn pixel_format(self) -> PixelFormat {
+        match self {
+            HdrMode::ExtendedSrgb
+            | HdrMode::ExtendedLinearSrgb
+            | HdrMode::ExtendedLinearDisplayP3 => PixelFormat::RGBA16_FLOAT,
+            HdrMode::Pq | HdrMode::Hlg => PixelFormat::BGR10A2_UNORM,
+        }
+    }
+
+    /// Check if the transfer function is linear.
+    pub fn is_linear(self) -> bool {
+        matches!(
+            self,
+            HdrMode::ExtendedLinearSrgb | HdrMode::ExtendedLinearDisplayP3
+        )
+    }
+
+    /// Get the Core Graphics color space name constant, a `CFStringRef`.
+    pub fn colorspace_name(self) -> *const c_void {
+        unsafe {
+            match self {
+                HdrMode::ExtendedSrgb => kCGColorSpaceExtendedSRGB,
+                HdrMode::ExtendedLinearSrgb => kCGColorSpaceExtendedLinearSRGB,
+                HdrMode::ExtendedLinearDisplayP3 => kCGColorSpaceExtendedLinearDisplayP3,
+                HdrMode::Pq => kCGColorSpaceITUR_2100_PQ,
+                HdrMode::Hlg => kCGColorSpaceITUR_2100_HLG,
+            }
+        }
+    }
+
+    /// Create the color space for this mode.
+    pub fn colorspace(self) -> Option<ColorSpace> {
+        unsafe { ColorSpace::with_name(self.colorspace_name()) }
+    }
+}
+
+// ============================================================================
+// ColorSpace
+// ============================================================================
+
+/// A retained `CGColorSpaceRef`.
+#[repr(transparent)]
+pub struct ColorSpace(NonNull<c_void>);
+
+impl ColorSpace {
+    /// Create a color space from a Core Graphics name constant.
+    ///
+    /// C equivalent: `CGColorSpaceCreateWithName(name)`
+    ///
+    /// # Safety
+    ///
+    /// `name` must be a valid `CFStringRef`.
+    pub unsafe fn with_name(name: *const c_void) -> Option<Self> {
+        NonNull::new(unsafe { CGColorSpaceCreateWithName(name) }).map(Self)
+    }
+
+    /// Wrap a `CGColorSpaceRef`, retaining it.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid `CGColorSpaceRef`.
+    pub unsafe fn from_raw(ptr: CGColorSpaceRef) -> Option<Self> {
+        let ptr = NonNull::new(ptr)?;
+        unsafe { CGColorSpaceRetain(ptr.as_ptr()) };
+        Some(Self(ptr))
+    }
+
+    /// Get the raw `CGColorSpaceRef`, for [`MetalLayer::set_colorspace`].
+    #[inline]
+    pub fn as_raw(&self) -> CGColorSpaceRef {
+        self.0.as_ptr()
+    }
+}
+
+impl Clone for ColorSpace {
+    fn clone(&self) -> Self {
+        unsafe { CGColorSpaceRetain(self.as_raw()) };
+        Self(self.0)
+    }
+}
+
+impl Drop for ColorSpace {
+    fn drop(&mut self) {
+        unsafe { CGColorSpaceRelease(self.as_raw()) };
+    }
+}
+
+unsafe impl Send for ColorSpace {}
+unsafe impl Sync for ColorSpace {}
+
+impl fmt::Debug for ColorSpace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ColorSpace").field(&self.0).finish()
+    }
+}
+
+// ============================================================================
+// EdrMetadata
+// ============================================================================
+
+/// Mastering display information for PQ and HLG content.
+///
+/// C equivalent: `CAEDRMetadata`
+#[repr(transparent)]
+pub struct EdrMetadata(NonNull<c_void>);
+
+impl EdrMetadata {
+    /// Metadata for HDR10 content mastered on a display with the given
+    /// luminance range in nits.
+    ///
+    /// `optical_output_scale` is the luminance in nits of a pixel value of
+    /// 1.0 after decoding; 100 matches the usual SDR reference white.
+    ///
+    /// C equivalent: `+[CAEDRMetadata HDR10MetadataWithMinLuminance:maxLuminance:opticalOutputScale:]`
+    pub fn hdr10(
+        min_luminance: f32,
+        max_luminance: f32,
+        optical_output_scale: f32,
+    ) -> Option<Self> {
+        unsafe {
+            let class = mtl_sys::Class::get("CAEDRMetadata")?;
+            let ptr: *mut c_void = msg_send_3(
+                class.as_ptr(),
+                sel!(HDR10MetadataWithMinLuminance: maxLuminance: opticalOutputScale:),
+                min_luminance,
+                max_luminance,
+                optical_output_scale,
+            );
+            Self::retained(ptr)
+        }
+    }
+
+    /// Metadata for HLG content.
+    ///
+    /// C equivalent: `+[CAEDRMetadata HLGMetadata]`
+    pub fn hlg() -> Option<Self> {
+        unsafe {
+            let class = mtl_sys::Class::get("CAEDRMetadata")?;
+            let ptr: *mut c_void = msg_send_0(class.as_ptr(), sel!(HLGMetadata));
+            Self::retained(ptr)
+        }
+    }
+
+    /// Retain an autoreleased object returned by a class method.
+    unsafe fn retained(ptr: *mut c_void) -> Option<Self> {
+        let ptr = NonNull::new(ptr)?;
+        unsafe { msg_send_0::<*mut c_void>(ptr.as_ptr(), sel!(retain)) };
+        Some(Self(ptr))
+    }
+
+    /// Create from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid, retained `CAEDRMetadata` object.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
+        NonNull::new(ptr).map(Self)
+    }
+
+    /// Get the raw pointer.
+    #[inline]
+    pub fn as_raw(&self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+}
+
+impl Clone for EdrMetadata {
+    fn clone(&self) -> Self {
+        unsafe { msg_send_0::<*mut c_void>(self.as_ptr(), sel!(retain)) };
+        Self(self.0)
+    }
+}
+
+impl Drop for EdrMetadata {
+    fn drop(&mut self) {
+        unsafe { msg_send_0::<()>(self.as_ptr(), sel!(release)) };
+    }
+}
+
+impl Referencing for EdrMetadata {
+    #[inline]
+    fn as_ptr(&self) -> *const c_void {
+        self.0.as_ptr()
+    }
+}
+
+unsafe impl Send for EdrMetadata {}
+unsafe impl Sync for EdrMetadata {}
+
+impl fmt::Debug for EdrMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EdrMetadata").field(&self.0).finish()
+    }
+}
+
+// ============================================================================
+// EdrHeadroom
+// ============================================================================
+
+/// How far above SDR white a display can show, as multiples of SDR white.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EdrHeadroom {
+    /// The headroom available now. Changes with display brightness and
+    /// ambient light; 1.0 when no EDR content is on screen.
+    pub current: CGFloat,
+    /// The most headroom the display can offer.
+    pub potential: CGFloat,
+    /// The headroom at which the display stays accurate to reference
+    /// luminance, or `None` if unknown.
+    pub reference: Option<CGFloat>,
+}
+
+impl EdrHeadroom {
+    /// Query the main screen's headroom.
+    ///
+    /// Uses `NSScreen` on macOS 10.15 and later and `UIScreen`
+    /// `currentEDRHeadroom` on iOS 16 and later. Returns `None` if neither
+    /// AppKit nor UIKit is loaded or the OS is too old.
+    pub fn main_screen() -> Option<Self> {
+        unsafe {
+            if let Some(class) = mtl_sys::Class::get("NSScreen") {
+                let screen: *mut c_void = msg_send_0(class.as_ptr(), sel!(mainScreen));
+                return Self::for_screen(screen);
+            }
+            let class = mtl_sys::Class::get("UIScreen")?;
+            if !class.instances_respond_to(sel!(currentEDRHeadroom)) {
+                return None;
+            }
+            let screen: *mut c_void = msg_send_0(class.as_ptr(), sel!(mainScreen));
+            if screen.is_null() {
+                return None;
+            }
+            Some(Self {
+                current: msg_send_0(screen, sel!(currentEDRHeadroom)),
+                potential: msg_send_0(screen, sel!(potentialEDRHeadroom)),
+                reference: None,
+            })
+        }
+    }
+
+    /// Query the headroom of an `NSScreen`, such as a window's `screen`.
+    ///
+    /// # Safety
+    ///
+    /// `screen` must be null or a valid `NSScreen`.
+    pub unsafe fn for_screen(screen: *mut c_void) -> Option<Self> {
+        let class = mtl_sys::Class::get("NSScreen")?;
+        if screen.is_null()
+            || !class.instances_respond_to(sel!(maximumExtendedDynamicRangeColorComponentValue))
+        {
+            return None;
+        }
+        unsafe {
+            let reference = class
+                .instances_respond_to(sel!(
+                    maximumReferenceExtendedDynamicRangeColorComponentValue
+                ))
+                .then(|| {
+                    msg_send_0(
+                        screen,
+                        sel!(maximumReferenceExtendedDynamicRangeColorComponentValue),
+                    )
+                })
+                .filter(|&value: &CGFloat| value > 0.0);
+            Some(Self {
+                current: msg_send_0(screen, sel!(maximumExtendedDynamicRangeColorComponentValue)),
+                potential: msg_send_0(
+                    screen,
+                    sel!(maximumPotentialExtendedDynamicRangeColorComponentValue),
+                ),
+                reference,
+            })
+        }
+    }
+
+    /// Check if the display can show anything brighter than SDR white.
+    pub fn supports_edr(&self) -> bool {
+        self.potential > 1.0
+    }
+}
+
+// ============================================================================
+// MetalLayer
+// ============================================================================
+
+/// Error produced by [`MetalLayer::configure_hdr`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HdrConfigError {
+    /// Core Graphics doesn't know the mode's color space.
+    ColorSpaceUnavailable(HdrMode),
+    /// `CAEDRMetadata` isn't available on this OS.
+    MetadataUnavailable,
+}
+
+impl fmt::Display for HdrConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HdrConfigError::ColorSpaceUnavailable(mode) => {
+                write!(f, "color space for {:?} is unavailable", mode)
+            }
+            HdrConfigError::MetadataUnavailable => write!(f, "EDR metadata is unavailable"),
+        }
+    }
+}
+
+impl std::error::Error for HdrConfigError {}
+
+impl MetalLayer {
+    /// Get the layer's EDR metadata.
+    ///
+    /// C equivalent: `CAEDRMetadata *EDRMetadata`
+    pub fn edr_metadata(&self) -> Option<EdrMetadata> {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(EDRMetadata));
+            EdrMetadata::retained(ptr)
+        }
+    }
+
+    /// Set the layer's EDR metadata, or clear it with `None`.
+    ///
+    /// C equivalent: `void setEDRMetadata:(CAEDRMetadata *)`
+    pub fn set_edr_metadata(&self, metadata: Option<&EdrMetadata>) {
+        let ptr = metadata.map_or(std::ptr::null(), |metadata| metadata.as_ptr());
+        unsafe {
+            let _: () = msg_send_1(self.as_ptr(), sel!(setEDRMetadata:), ptr);
+        }
+    }
+
+    /// Configure the layer for `mode`: enable extended dynamic range and set
+    /// the pixel format, color space and EDR metadata.
+    ///
+    /// `metadata` defaults to [`EdrMetadata::hlg`] for [`HdrMode::Hlg`] and
+    /// to none otherwise. The metadata is ignored for extended-range modes.
+    pub fn configure_hdr(
+        &self,
+        mode: HdrMode,
+        metadata: Option<&EdrMetadata>,
+    ) -> Result<(), HdrConfigError> {
+        let colorspace = mode
+            .colorspace()
+            .ok_or(HdrConfigError::ColorSpaceUnavailable(mode))?;
+        let metadata = match (mode, metadata) {
+            (HdrMode::Pq, metadata) => metadata.cloned(),
+            (HdrMode::Hlg, Some(metadata)) => Some(metadata.clone()),
+            (HdrMode::Hlg, None) => {
+                Some(EdrMetadata::hlg().ok_or(HdrConfigError::MetadataUnavailable)?)
+            }
+            _ => None,
+        };
+        self.set_wants_extended_dynamic_range_content(true);
+        self.set_pixel_format(mode.pixel_format());
+        self.set_colorspace(colorspace.as_raw());
+        self.set_edr_metadata(metadata.as_ref());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_pixel_formats() {
+        assert_eq!(HdrMode::Pq.pixel_format(), PixelFormat::BGR10A2_UNORM);
+        assert_eq!(
+            HdrMode::ExtendedLinearDisplayP3.pixel_format(),
+            PixelFormat::RGBA16_FLOAT
+        );
+        assert!(HdrMode::ExtendedLinearSrgb.is_linear());
+        assert!(!HdrMode::Hlg.is_linear());
+    }
+
+    #[test]
+    fn test_configure_hdr() {
+        let layer = match MetalLayer::layer() {
+            Some(l) => l,
+            None => return,
+        };
+        layer.configure_hdr(HdrMode::Hlg, None).unwrap();
+        assert!(layer.wants_extended_dynamic_range_content());
+        assert_eq!(layer.pixel_format(), PixelFormat::BGR10A2_UNORM);
+        assert!(!layer.colorspace().is_null());
+        assert!(layer.edr_metadata().is_some());
+
+        layer
+            .configure_hdr(HdrMode::ExtendedLinearDisplayP3, None)
+            .unwrap();
+        assert_eq!(layer.pixel_format(), PixelFormat::RGBA16_FLOAT);
+        assert!(layer.edr_metadata().is_none());
+    }
+}
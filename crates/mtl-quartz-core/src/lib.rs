@@ -4,7 +4,14 @@
 
 //! QuartzCore/CoreAnimation bindings.
 //!
-//! Provides CAMetalLayer and CAMetalDrawable for display integration.
+//! Provides CAMetalLayer and CAMetalDrawable for display integration, and a
+//! [`SwapChain`] that adds frame pacing on top of them. [`FramePacer`]
+//! measures the cadence at which drawables actually reach the display, and
+//! [`PresentScheduler`] paces presentation below the refresh rate of
+//! adaptive-sync and ProMotion displays.
+//! [`MetalLayer::snapshot_next_frame`] copies a presented frame back to the
+//! CPU for UI tests. [`MetalLayer::configure_hdr`] sets a layer up for PQ, HLG
+//! or extended-range output.
 //!
 //! This crate provides safe Rust bindings to the QuartzCore framework's Metal
 //! integration types. These are used to display Metal-rendered content on screen.
@@ -32,10 +39,26 @@
 
 #![allow(dead_code)]
 
+mod frame_pacer;
+mod hdr;
 mod metal_drawable;
 mod metal_layer;
+mod present_scheduler;
+#[cfg(feature = "blocks")]
+mod snapshot;
+#[cfg(feature = "blocks")]
+mod swap_chain;
 mod types;
 
+pub use frame_pacer::{FramePacer, FramePacingStats};
+pub use hdr::{ColorSpace, EdrHeadroom, EdrMetadata, HdrConfigError, HdrMode};
 pub use metal_drawable::MetalDrawable;
 pub use metal_layer::MetalLayer;
+pub use present_scheduler::{DisplayRefreshRange, PresentScheduler, current_media_time};
+#[cfg(feature = "blocks")]
+pub use snapshot::{FrameSnapshot, PendingSnapshot, SnapshotError};
+#[cfg(feature = "blocks")]
+pub use swap_chain::{
+    AcquireTimeout, DEFAULT_MAX_FRAMES_IN_FLIGHT, Frame, SwapChain, SwapChainError,
+};
 pub use types::{CGColorSpaceRef, CGFloat, CGSize};
@@ -49,6 +49,9 @@ impl MetalDrawable {
     pub fn layer(&self) -> Option<MetalLayer> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(layer));
+            if !ptr.is_null() {
+                let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            }
             MetalLayer::from_raw(ptr)
         }
     }
@@ -62,10 +65,35 @@ impl MetalDrawable {
     pub fn texture(&self) -> Option<mtl_gpu::Texture> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(texture));
+            if !ptr.is_null() {
+                let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            }
             mtl_gpu::Texture::from_raw(ptr)
         }
     }
 
+    /// Create the usual pass for drawing this frame: the drawable's texture
+    /// as color attachment 0, cleared to `clear_color` and stored, plus an
+    /// optional depth (and stencil) attachment.
+    ///
+    /// See [`RenderPassDescriptor::for_texture`](mtl_gpu::RenderPassDescriptor::for_texture).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let drawable = layer.next_drawable().unwrap();
+    /// let pass = drawable
+    ///     .render_pass_descriptor(ClearColor::new(0.0, 0.0, 0.0, 1.0), None)
+    ///     .unwrap();
+    /// ```
+    pub fn render_pass_descriptor(
+        &self,
+        clear_color: mtl_gpu::ClearColor,
+        depth: Option<&mtl_gpu::Texture>,
+    ) -> Option<mtl_gpu::RenderPassDescriptor> {
+        mtl_gpu::RenderPassDescriptor::for_texture(&self.texture()?, clear_color, depth)
+    }
+
     // ============================================================
     // MTLDrawable protocol methods
     // ============================================================
@@ -79,6 +107,37 @@ impl MetalDrawable {
         }
     }
 
+    /// Schedule the drawable for presentation when `command_buffer`
+    /// completes.
+    ///
+    /// C++ equivalent: `void CommandBuffer::presentDrawable(Drawable*)`
+    ///
+    /// If a [snapshot](MetalLayer::snapshot_next_frame) of the layer was
+    /// requested, a copy of the drawable's texture is encoded into
+    /// `command_buffer` first, so call this once rendering is encoded.
+    pub fn present_on(&self, command_buffer: &mtl_gpu::CommandBuffer) {
+        #[cfg(feature = "blocks")]
+        crate::snapshot::capture_if_requested(self, command_buffer);
+        unsafe { command_buffer.present_drawable(self.as_ptr()) };
+    }
+
+    /// Schedule the drawable for presentation at host time
+    /// `presentation_time` once `command_buffer` completes.
+    ///
+    /// C++ equivalent: `void CommandBuffer::presentDrawable(Drawable*, CFTimeInterval)`
+    ///
+    /// Like [`present_on`](Self::present_on), encodes a requested snapshot
+    /// first.
+    pub fn present_on_at_time(
+        &self,
+        command_buffer: &mtl_gpu::CommandBuffer,
+        presentation_time: TimeInterval,
+    ) {
+        #[cfg(feature = "blocks")]
+        crate::snapshot::capture_if_requested(self, command_buffer);
+        unsafe { command_buffer.present_drawable_at_time(self.as_ptr(), presentation_time) };
+    }
+
     /// Present the drawable at the specified time.
     ///
     /// C++ equivalent: `void presentAtTime(CFTimeInterval presentationTime)`
@@ -121,8 +180,39 @@ impl MetalDrawable {
         unsafe { msg_send_0(self.as_ptr(), sel!(presentedTime)) }
     }
 
-    // Note: addPresentedHandler is not implemented here to avoid requiring block support.
-    // Users can implement block callbacks using the metal-sys block infrastructure if needed.
+    /// Add a handler to be called when the drawable is presented.
+    ///
+    /// C++ equivalent: `void addPresentedHandler(void (^)(MTL::Drawable*))`
+    ///
+    /// The handler runs once the drawable has been shown on the display, or
+    /// discarded; [`presented_time`](Self::presented_time) is 0.0 in the
+    /// latter case.
+    #[cfg(feature = "blocks")]
+    pub fn add_presented_handler<F>(&self, handler: F)
+    where
+        F: Fn(&MetalDrawable) + Send + 'static,
+    {
+        let block = mtl_sys::OneArgBlock::from_fn(move |drawable_ptr: *mut c_void| {
+            unsafe {
+                if let Some(drawable) = MetalDrawable::from_raw(drawable_ptr) {
+                    handler(&drawable);
+                    // Don't drop - Metal owns this reference
+                    std::mem::forget(drawable);
+                }
+            }
+        });
+
+        unsafe {
+            msg_send_1::<(), *const c_void>(
+                self.as_ptr(),
+                sel!(addPresentedHandler:),
+                block.as_ptr(),
+            );
+        }
+
+        // The block is retained by Metal
+        std::mem::forget(block);
+    }
 }
 
 impl Clone for MetalDrawable {
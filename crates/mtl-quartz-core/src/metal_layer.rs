@@ -5,7 +5,7 @@
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
-use mtl_foundation::{Referencing, UInteger};
+use mtl_foundation::{AutoreleasePool, Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, sel};
 
 use crate::metal_drawable::MetalDrawable;
@@ -124,13 +124,42 @@ impl MetalLayer {
     ///
     /// Returns None if no drawable is available (e.g., if all drawables are in use).
     /// This is a blocking call that may wait for a drawable to become available.
+    ///
+    /// The drawable is retained, but Core Animation also autoreleases
+    /// objects while handing it out. Call this inside an
+    /// [`AutoreleasePool`] each frame, or use
+    /// [`with_next_drawable`](Self::with_next_drawable), so they are
+    /// released per frame rather than when the thread's pool drains.
     pub fn next_drawable(&self) -> Option<MetalDrawable> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(nextDrawable));
+            if !ptr.is_null() {
+                let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            }
             MetalDrawable::from_raw(ptr)
         }
     }
 
+    /// Get the next drawable and pass it to `f` inside an autorelease pool.
+    ///
+    /// Everything autoreleased while acquiring, encoding and presenting the
+    /// frame is released when `f` returns. Returns `None` without calling
+    /// `f` if no drawable is available.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// layer.with_next_drawable(|drawable| {
+    ///     let command_buffer = queue.command_buffer().unwrap();
+    ///     // Encode into drawable.texture()...
+    ///     unsafe { command_buffer.present_drawable(drawable.as_raw()) };
+    ///     command_buffer.commit();
+    /// });
+    /// ```
+    pub fn with_next_drawable<R>(&self, f: impl FnOnce(&MetalDrawable) -> R) -> Option<R> {
+        AutoreleasePool::with(|| self.next_drawable().map(|drawable| f(&drawable)))
+    }
+
     /// Get the maximum number of drawables.
     ///
     /// C++ equivalent: `NS::UInteger maximumDrawableCount() const`
@@ -188,6 +217,30 @@ impl MetalLayer {
         }
     }
 
+    /// Check whether the layer displays extended dynamic range content.
+    ///
+    /// C++ equivalent: `bool wantsExtendedDynamicRangeContent() const`
+    ///
+    /// When true, pixel values above 1.0 are shown brighter than reference
+    /// white on displays that support it. Requires a floating-point or
+    /// 10-bit pixel format and an extended-range color space.
+    pub fn wants_extended_dynamic_range_content(&self) -> bool {
+        unsafe { msg_send_0(self.as_ptr(), sel!(wantsExtendedDynamicRangeContent)) }
+    }
+
+    /// Set whether the layer displays extended dynamic range content.
+    ///
+    /// C++ equivalent: `void setWantsExtendedDynamicRangeContent(bool wantsExtendedDynamicRangeContent)`
+    pub fn set_wants_extended_dynamic_range_content(&self, wants: bool) {
+        unsafe {
+            let _: () = msg_send_1(
+                self.as_ptr(),
+                sel!(setWantsExtendedDynamicRangeContent:),
+                wants,
+            );
+        }
+    }
+
     /// Check whether nextDrawable is allowed to time out.
     ///
     /// C++ equivalent: `bool allowsNextDrawableTimeout() const`
@@ -208,6 +261,29 @@ impl MetalLayer {
         }
     }
 
+    /// Check whether drawables are presented inside a Core Animation
+    /// transaction.
+    ///
+    /// C++ equivalent: `bool presentsWithTransaction() const`
+    ///
+    /// When true, a drawable's contents appear in the same frame as other
+    /// layer changes in the current transaction. Present with
+    /// [`MetalDrawable::present`] after the command buffer is scheduled
+    /// rather than with `presentDrawable:` on the command buffer.
+    pub fn presents_with_transaction(&self) -> bool {
+        unsafe { msg_send_0(self.as_ptr(), sel!(presentsWithTransaction)) }
+    }
+
+    /// Set whether drawables are presented inside a Core Animation
+    /// transaction.
+    ///
+    /// C++ equivalent: `void setPresentsWithTransaction(bool presentsWithTransaction)`
+    pub fn set_presents_with_transaction(&self, presents: bool) {
+        unsafe {
+            let _: () = msg_send_1(self.as_ptr(), sel!(setPresentsWithTransaction:), presents);
+        }
+    }
+
     /// Get the residency set used by this layer.
     ///
     /// C++ equivalent: `MTL::ResidencySet* residencySet() const`
@@ -307,5 +383,13 @@ mod tests {
         assert_eq!(layer.maximum_drawable_count(), 2);
         layer.set_maximum_drawable_count(3);
         assert_eq!(layer.maximum_drawable_count(), 3);
+
+        // Test extended dynamic range and transactional presentation
+        layer.set_wants_extended_dynamic_range_content(true);
+        assert!(layer.wants_extended_dynamic_range_content());
+        layer.set_presents_with_transaction(true);
+        assert!(layer.presents_with_transaction());
+        layer.set_presents_with_transaction(false);
+        assert!(!layer.presents_with_transaction());
     }
 }
@@ -0,0 +1,251 @@
+//! Present timestamps for frame rates below the display's refresh rate.
+//!
+//! On adaptive-sync and ProMotion displays, presenting as soon as a frame is
+//! ready lets the display follow the render loop's jitter: a game capped at
+//! 40 fps on a 120 Hz panel shows frames for 2, 4 and 3 refreshes in turn
+//! instead of 3 each. [`PresentScheduler`] picks a present interval that is
+//! a whole number of the display's shortest refresh intervals and hands out
+//! evenly spaced presentation times on that grid for
+//! `presentDrawable:atTime:`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let range = DisplayRefreshRange::main_screen().unwrap_or(DisplayRefreshRange::fixed(1.0 / 60.0));
+//! let mut scheduler = PresentScheduler::new(range);
+//! scheduler.set_target_frame_rate(Some(40.0));
+//!
+//! // Each frame, once rendering is encoded:
+//! scheduler.present(&drawable, &command_buffer);
+//! command_buffer.commit();
+//! ```
+
+use std::ffi::c_void;
+
+use mtl_sys::{msg_send_0, sel};
+
+use crate::metal_drawable::{MetalDrawable, TimeInterval};
+
+#[cfg_attr(target_vendor = "apple", link(name = "QuartzCore", kind = "framework"))]
+unsafe extern "C" {
+    fn CACurrentMediaTime() -> TimeInterval;
+}
+
+/// Get the current host time in seconds, the clock presentation times are
+/// measured against.
+///
+/// C equivalent: `CACurrentMediaTime()`
+pub fn current_media_time() -> TimeInterval {
+    unsafe { CACurrentMediaTime() }
+}
+
+// ============================================================================
+// DisplayRefreshRange
+// ============================================================================
+
+/// The refresh intervals a display can run at.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DisplayRefreshRange {
+    /// The shortest refresh interval, at the display's highest rate.
+    pub min_interval: TimeInterval,
+    /// The longest refresh interval, at the display's lowest rate. Equal to
+    /// `min_interval` for fixed-rate displays.
+    pub max_interval: TimeInterval,
+}
+
+impl DisplayRefreshRange {
+    /// A display refreshing every `interval` seconds.
+    pub fn fixed(interval: TimeInterval) -> Self {
+        Self {
+            min_interval: interval,
+            max_interval: interval,
+        }
+    }
+
+    /// Query the main screen's refresh range.
+    ///
+    /// Uses `NSScreen` `minimumRefreshInterval` and `maximumRefreshInterval`
+    /// on macOS 12 and later, and `UIScreen` `maximumFramesPerSecond`
+    /// elsewhere. Returns `None` if neither AppKit nor UIKit is loaded.
+    pub fn main_screen() -> Option<Self> {
+        unsafe {
+            if let Some(class) = mtl_sys::Class::get("NSScreen") {
+                let screen: *mut c_void = msg_send_0(class.as_ptr(), sel!(mainScreen));
+                if screen.is_null() || !class.instances_respond_to(sel!(minimumRefreshInterval)) {
+                    return None;
+                }
+                let min_interval: TimeInterval = msg_send_0(screen, sel!(minimumRefreshInterval));
+                let max_interval: TimeInterval = msg_send_0(screen, sel!(maximumRefreshInterval));
+                return Self::new(min_interval, max_interval);
+            }
+            let class = mtl_sys::Class::get("UIScreen")?;
+            let screen: *mut c_void = msg_send_0(class.as_ptr(), sel!(mainScreen));
+            if screen.is_null() {
+                return None;
+            }
+            let frames_per_second: isize = msg_send_0(screen, sel!(maximumFramesPerSecond));
+            if frames_per_second <= 0 {
+                return None;
+            }
+            Some(Self::fixed(1.0 / frames_per_second as TimeInterval))
+        }
+    }
+
+    fn new(min_interval: TimeInterval, max_interval: TimeInterval) -> Option<Self> {
+        (min_interval > 0.0).then(|| Self {
+            min_interval,
+            max_interval: max_interval.max(min_interval),
+        })
+    }
+
+    /// Get the display's highest refresh rate in hertz.
+    pub fn max_frame_rate(&self) -> f64 {
+        1.0 / self.min_interval
+    }
+
+    /// Check if the display can vary its refresh rate.
+    pub fn is_adaptive(&self) -> bool {
+        self.max_interval > self.min_interval
+    }
+
+    /// Get the present interval for content targeting `frame_rate` frames
+    /// per second.
+    ///
+    /// Rounds to the nearest whole number of shortest refresh intervals, so
+    /// 30, 40 and 60 fps on a 120 Hz display present every 4, 3 and 2
+    /// refreshes. Rates above the display's maximum present every refresh.
+    pub fn present_interval(&self, frame_rate: f64) -> TimeInterval {
+        if frame_rate <= 0.0 {
+            return self.min_interval;
+        }
+        let refreshes = (1.0 / frame_rate / self.min_interval).round().max(1.0);
+        refreshes * self.min_interval
+    }
+}
+
+// ============================================================================
+// PresentScheduler
+// ============================================================================
+
+/// Chooses evenly paced presentation times for a render loop.
+#[derive(Clone, Debug)]
+pub struct PresentScheduler {
+    range: DisplayRefreshRange,
+    target_frame_rate: Option<f64>,
+    interval: TimeInterval,
+    last_present_time: Option<TimeInterval>,
+}
+
+impl PresentScheduler {
+    /// Create a scheduler for a display with `range`, presenting at the
+    /// display's highest rate.
+    pub fn new(range: DisplayRefreshRange) -> Self {
+        Self {
+            range,
+            target_frame_rate: None,
+            interval: range.min_interval,
+            last_present_time: None,
+        }
+    }
+
+    /// Get the display refresh range.
+    pub fn refresh_range(&self) -> DisplayRefreshRange {
+        self.range
+    }
+
+    /// Set the display refresh range, such as after the window moves to
+    /// another screen.
+    pub fn set_refresh_range(&mut self, range: DisplayRefreshRange) {
+        self.range = range;
+        self.set_target_frame_rate(self.target_frame_rate);
+    }
+
+    /// Get the target frame rate, or `None` for the display's highest rate.
+    pub fn target_frame_rate(&self) -> Option<f64> {
+        self.target_frame_rate
+    }
+
+    /// Set the target frame rate, or `None` for the display's highest rate.
+    pub fn set_target_frame_rate(&mut self, frame_rate: Option<f64>) {
+        self.target_frame_rate = frame_rate;
+        self.interval = match frame_rate {
+            Some(frame_rate) => self.range.present_interval(frame_rate),
+            None => self.range.min_interval,
+        };
+    }
+
+    /// Get the interval between scheduled presentations.
+    pub fn present_interval(&self) -> TimeInterval {
+        self.interval
+    }
+
+    /// Get the effective frame rate of the present interval.
+    pub fn frame_rate(&self) -> f64 {
+        1.0 / self.interval
+    }
+
+    /// Forget the previous presentation time so the next frame starts a new
+    /// pacing grid, such as after a pause.
+    pub fn reset(&mut self) {
+        self.last_present_time = None;
+    }
+
+    /// Get the presentation time for a frame that is ready at `now`.
+    ///
+    /// The time is one present interval after the previous one. A frame
+    /// that is too late for that slot moves to the first later slot on the
+    /// same grid, dropping a beat rather than shifting every later frame;
+    /// one that is more than an interval late starts a new grid at `now`.
+    pub fn next_present_time(&mut self, now: TimeInterval) -> TimeInterval {
+        let time = match self.last_present_time {
+            Some(last) if now - last <= 2.0 * self.interval => {
+                let missed = ((now - last) / self.interval).floor().max(0.0);
+                last + (missed + 1.0) * self.interval
+            }
+            _ => now,
+        };
+        self.last_present_time = Some(time);
+        time
+    }
+
+    /// Schedule `drawable` for presentation on `command_buffer` at the next
+    /// paced time.
+    ///
+    /// See [`MetalDrawable::present_on_at_time`].
+    pub fn present(&mut self, drawable: &MetalDrawable, command_buffer: &mtl_gpu::CommandBuffer) {
+        let time = self.next_present_time(current_media_time());
+        drawable.present_on_at_time(command_buffer, time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_present_interval_locks_to_refresh_multiples() {
+        let range = DisplayRefreshRange {
+            min_interval: 1.0 / 120.0,
+            max_interval: 1.0 / 24.0,
+        };
+        assert!(range.is_adaptive());
+        for (frame_rate, refreshes) in [(30.0, 4.0), (40.0, 3.0), (60.0, 2.0), (144.0, 1.0)] {
+            let interval = range.present_interval(frame_rate);
+            assert!((interval - refreshes / 120.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_next_present_time_stays_on_grid() {
+        let mut scheduler = PresentScheduler::new(DisplayRefreshRange::fixed(1.0 / 120.0));
+        scheduler.set_target_frame_rate(Some(40.0));
+        let interval = 3.0 / 120.0;
+
+        assert_eq!(scheduler.next_present_time(1.0), 1.0);
+        let second = scheduler.next_present_time(1.001);
+        assert!((second - (1.0 + interval)).abs() < 1e-12);
+        let late = scheduler.next_present_time(1.0 + 2.5 * interval);
+        assert!((late - (1.0 + 3.0 * interval)).abs() < 1e-12);
+        assert_eq!(scheduler.next_present_time(2.0), 2.0);
+    }
+}
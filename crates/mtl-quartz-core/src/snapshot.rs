@@ -0,0 +1,385 @@
+//! Snapshots of presented frames.
+//!
+//! [`MetalLayer::snapshot_next_frame`] asks for a copy of the next frame
+//! the layer presents, so UI tests can assert on what actually reached the
+//! screen rather than on an offscreen render target. The copy is encoded
+//! into the command buffer that presents the drawable, by
+//! [`MetalDrawable::present_on`] or [`Frame::present`](crate::Frame::present),
+//! right before the present, and read back when that command buffer
+//! completes. Drawables presented with [`MetalDrawable::present`] or a raw
+//! `present_drawable` call are not captured.
+//!
+//! Drawable textures can only be read when the layer is not
+//! framebuffer-only, so requesting a snapshot clears the layer's
+//! [`framebuffer_only`](MetalLayer::framebuffer_only) flag. Drawables
+//! acquired before that are skipped and the request waits for the next
+//! readable one.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let snapshot = swap_chain.layer().snapshot_next_frame();
+//!
+//! let frame = swap_chain.acquire_frame()?;
+//! // ... encode rendering into frame.texture ...
+//! frame.present(&command_buffer);
+//! command_buffer.commit();
+//!
+//! let image = snapshot.wait()?;
+//! assert_eq!(image.pixel(0, 0), Some(&[0, 0, 255, 255][..]));
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use mtl_foundation::{Referencing, UInteger};
+use mtl_gpu::{
+    BlitCommandEncoder, CommandBuffer, CommandBufferStatus, Origin, PixelFormat, ResourceOptions,
+    Size,
+};
+
+use crate::metal_drawable::MetalDrawable;
+use crate::metal_layer::MetalLayer;
+
+/// Snapshot requests waiting for their layer's next present, keyed by the
+/// layer's address.
+static REQUESTS: Mutex<Vec<(usize, Arc<SnapshotSlot>)>> = Mutex::new(Vec::new());
+
+// ============================================================================
+// FrameSnapshot
+// ============================================================================
+
+/// A CPU copy of a presented drawable's texture.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FrameSnapshot {
+    /// Width of the image in pixels.
+    pub width: UInteger,
+    /// Height of the image in pixels.
+    pub height: UInteger,
+    /// Pixel format of the drawable, and so of `data`.
+    pub pixel_format: PixelFormat,
+    /// Bytes per row of `data`.
+    pub bytes_per_row: UInteger,
+    /// Tightly packed pixel rows, top row first.
+    pub data: Vec<u8>,
+}
+
+impl FrameSnapshot {
+    /// Get the bytes of the pixel at `x`, `y`, or `None` if it lies outside
+    /// the image.
+    pub fn pixel(&self, x: UInteger, y: UInteger) -> Option<&[u8]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let bytes_per_pixel = self.bytes_per_row / self.width;
+        let start = y * self.bytes_per_row + x * bytes_per_pixel;
+        self.data.get(start..start + bytes_per_pixel)
+    }
+}
+
+impl fmt::Debug for FrameSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameSnapshot")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("pixel_format", &self.pixel_format)
+            .field("bytes_per_row", &self.bytes_per_row)
+            .finish_non_exhaustive()
+    }
+}
+
+// ============================================================================
+// SnapshotError
+// ============================================================================
+
+/// Error produced by a frame snapshot.
+#[derive(Clone, Debug)]
+pub enum SnapshotError {
+    /// The drawable's pixel format has no fixed per-pixel size.
+    UnsupportedPixelFormat(PixelFormat),
+    /// The staging buffer or blit encoder could not be created.
+    Allocation,
+    /// The command buffer that presented the frame failed.
+    CommandBuffer(Option<mtl_foundation::Error>),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::UnsupportedPixelFormat(format) => {
+                write!(f, "pixel format {:?} cannot be snapshotted", format)
+            }
+            SnapshotError::Allocation => write!(f, "failed to allocate snapshot resources"),
+            SnapshotError::CommandBuffer(Some(err)) => {
+                write!(f, "presenting command buffer failed: {}", err)
+            }
+            SnapshotError::CommandBuffer(None) => write!(f, "presenting command buffer failed"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::CommandBuffer(Some(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// PendingSnapshot
+// ============================================================================
+
+#[derive(Default)]
+struct SnapshotState {
+    result: Option<Result<FrameSnapshot, SnapshotError>>,
+    waker: Option<Waker>,
+}
+
+#[derive(Default)]
+struct SnapshotSlot {
+    state: Mutex<SnapshotState>,
+    ready: Condvar,
+}
+
+impl SnapshotSlot {
+    fn resolve(&self, result: Result<FrameSnapshot, SnapshotError>) {
+        let waker = {
+            let mut state = self.state.lock().unwrap();
+            state.result = Some(result);
+            state.waker.take()
+        };
+        self.ready.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Future returned by [`MetalLayer::snapshot_next_frame`].
+///
+/// Resolves once the command buffer presenting the next frame completes.
+/// Outside an async runtime, block on it with [`wait`](Self::wait).
+pub struct PendingSnapshot {
+    slot: Arc<SnapshotSlot>,
+}
+
+impl PendingSnapshot {
+    /// Check if the snapshot is available.
+    pub fn is_complete(&self) -> bool {
+        self.slot.state.lock().unwrap().result.is_some()
+    }
+
+    /// Block until the snapshot is available.
+    ///
+    /// The frame must be presented from another thread, or already be
+    /// committed, or this waits forever.
+    pub fn wait(self) -> Result<FrameSnapshot, SnapshotError> {
+        let mut state = self.slot.state.lock().unwrap();
+        loop {
+            if let Some(result) = state.result.take() {
+                return result;
+            }
+            state = self.slot.ready.wait(state).unwrap();
+        }
+    }
+}
+
+impl Future for PendingSnapshot {
+    type Output = Result<FrameSnapshot, SnapshotError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.slot.state.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            return Poll::Ready(result);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for PendingSnapshot {
+    fn drop(&mut self) {
+        REQUESTS
+            .lock()
+            .unwrap()
+            .retain(|(_, slot)| !Arc::ptr_eq(slot, &self.slot));
+    }
+}
+
+impl fmt::Debug for PendingSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingSnapshot")
+            .field("complete", &self.is_complete())
+            .finish()
+    }
+}
+
+// ============================================================================
+// Capture
+// ============================================================================
+
+impl MetalLayer {
+    /// Capture the next frame this layer presents.
+    ///
+    /// The returned future resolves with a copy of the drawable texture
+    /// presented next through [`MetalDrawable::present_on`] or
+    /// [`Frame::present`](crate::Frame::present). Clears the layer's
+    /// framebuffer-only flag so the drawable can be read. Dropping the
+    /// future cancels the request.
+    pub fn snapshot_next_frame(&self) -> PendingSnapshot {
+        if self.framebuffer_only() {
+            self.set_framebuffer_only(false);
+        }
+        let slot = Arc::new(SnapshotSlot::default());
+        REQUESTS
+            .lock()
+            .unwrap()
+            .push((self.as_ptr() as usize, Arc::clone(&slot)));
+        PendingSnapshot { slot }
+    }
+}
+
+/// Encode a copy of `drawable` into `command_buffer` if a snapshot of its
+/// layer was requested.
+pub(crate) fn capture_if_requested(drawable: &MetalDrawable, command_buffer: &CommandBuffer) {
+    let Some(layer) = drawable.layer() else {
+        return;
+    };
+    let key = layer.as_ptr() as usize;
+    let Some(texture) = drawable.texture() else {
+        return;
+    };
+    if texture.is_framebuffer_only() {
+        return;
+    }
+    let slots: Vec<Arc<SnapshotSlot>> = {
+        let mut requests = REQUESTS.lock().unwrap();
+        if !requests.iter().any(|(layer, _)| *layer == key) {
+            return;
+        }
+        let (taken, kept) = std::mem::take(&mut *requests)
+            .into_iter()
+            .partition(|(layer, _)| *layer == key);
+        *requests = kept;
+        taken.into_iter().map(|(_, slot)| slot).collect()
+    };
+    let resolve_all = move |result: Result<FrameSnapshot, SnapshotError>| {
+        let (last, rest) = slots.split_last().expect("at least one request");
+        for slot in rest {
+            slot.resolve(result.clone());
+        }
+        last.resolve(result);
+    };
+
+    let pixel_format = texture.pixel_format();
+    let Some(bytes_per_pixel) = pixel_format.bytes_per_pixel() else {
+        resolve_all(Err(SnapshotError::UnsupportedPixelFormat(pixel_format)));
+        return;
+    };
+    let (width, height) = (texture.width(), texture.height());
+    let bytes_per_row = width * bytes_per_pixel;
+    let length = bytes_per_row * height;
+
+    let device = texture.device();
+    let unified = device.has_unified_memory();
+    let options = if unified {
+        ResourceOptions::STORAGE_MODE_SHARED
+    } else {
+        ResourceOptions::STORAGE_MODE_MANAGED
+    };
+    let Some(staging) = device.new_buffer(length, options) else {
+        resolve_all(Err(SnapshotError::Allocation));
+        return;
+    };
+    let Some(encoder) =
+        (unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) })
+    else {
+        resolve_all(Err(SnapshotError::Allocation));
+        return;
+    };
+    encoder.copy_from_texture_to_buffer(
+        &texture,
+        0,
+        0,
+        Origin::new(0, 0, 0),
+        Size::new(width, height, 1),
+        &staging,
+        0,
+        bytes_per_row,
+        length,
+    );
+    if !unified {
+        encoder.synchronize_buffer(&staging);
+    }
+    encoder.end_encoding();
+
+    // The completion block may be invoked through a `Fn`, so hand the
+    // one-shot state over through a slot.
+    let pending = Mutex::new(Some(resolve_all));
+    command_buffer.add_completed_handler(move |command_buffer| {
+        let Some(resolve_all) = pending.lock().unwrap().take() else {
+            return;
+        };
+        if command_buffer.status() == CommandBufferStatus::ERROR {
+            resolve_all(Err(SnapshotError::CommandBuffer(command_buffer.error())));
+            return;
+        }
+        let result = match staging.contents() {
+            Some(ptr) => {
+                let bytes = unsafe { std::slice::from_raw_parts(ptr as *const u8, length) };
+                Ok(FrameSnapshot {
+                    width,
+                    height,
+                    pixel_format,
+                    bytes_per_row,
+                    data: bytes.to_vec(),
+                })
+            }
+            None => Err(SnapshotError::Allocation),
+        };
+        resolve_all(result);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_pixel() {
+        let snapshot = FrameSnapshot {
+            width: 2,
+            height: 2,
+            pixel_format: PixelFormat::BGRA8_UNORM,
+            bytes_per_row: 8,
+            data: (0..16).collect(),
+        };
+        assert_eq!(snapshot.pixel(1, 0), Some(&[4, 5, 6, 7][..]));
+        assert_eq!(snapshot.pixel(0, 1), Some(&[8, 9, 10, 11][..]));
+        assert_eq!(snapshot.pixel(2, 0), None);
+    }
+
+    #[test]
+    fn test_dropped_request_is_unregistered() {
+        let Some(layer) = MetalLayer::layer() else {
+            return;
+        };
+        let pending = layer.snapshot_next_frame();
+        assert!(!layer.framebuffer_only());
+        assert!(!pending.is_complete());
+        let key = layer.as_ptr() as usize;
+        drop(pending);
+        assert!(
+            !REQUESTS
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|(layer, _)| *layer == key)
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! Swap chain built on CAMetalLayer.
+//!
+//! [`SwapChain`] owns a [`MetalLayer`], limits how many frames the CPU may
+//! encode ahead of the GPU, and hands out a [`Frame`] per render loop
+//! iteration. A frame's slot is returned when the command buffer that
+//! presents it completes, so per-frame resources indexed by
+//! [`Frame::index`] are never overwritten while the GPU still reads them.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut swap_chain = SwapChain::new(&device, PixelFormat::BGRA8_UNORM)
+//!     .expect("failed to create layer")
+//!     .with_max_frames_in_flight(2);
+//!
+//! // On window resize:
+//! swap_chain.resize(CGSize::new(width, height));
+//!
+//! // Each frame:
+//! let frame = swap_chain.acquire_frame()?;
+//! let uniforms = &uniform_buffers[frame.index];
+//! // ... encode rendering into frame.texture ...
+//! frame.present(&command_buffer);
+//! command_buffer.commit();
+//! ```
+
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use mtl_foundation::AutoreleasePool;
+use mtl_gpu::{CommandBuffer, Device, PixelFormat, Texture};
+
+use crate::metal_drawable::MetalDrawable;
+use crate::metal_layer::MetalLayer;
+use crate::present_scheduler::PresentScheduler;
+use crate::types::CGSize;
+
+/// Default number of frames the CPU may encode ahead of the GPU.
+pub const DEFAULT_MAX_FRAMES_IN_FLIGHT: usize = 3;
+
+/// How long [`SwapChain::acquire_frame`] waits before giving up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AcquireTimeout {
+    /// Wait indefinitely for a frame slot and a drawable.
+    ///
+    /// Disables the layer's drawable timeout.
+    Block,
+    /// Wait at most this long for a frame slot.
+    ///
+    /// Keeps the layer's drawable timeout enabled, so the drawable itself
+    /// is abandoned after Core Animation's one-second limit.
+    After(Duration),
+}
+
+impl Default for AcquireTimeout {
+    fn default() -> Self {
+        AcquireTimeout::After(Duration::from_secs(1))
+    }
+}
+
+/// Error produced by [`SwapChain::acquire_frame`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwapChainError {
+    /// No frame slot became free within the timeout.
+    Timeout,
+    /// The layer returned no drawable, or a drawable without a texture.
+    DrawableUnavailable,
+}
+
+impl fmt::Display for SwapChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwapChainError::Timeout => write!(f, "timed out waiting for a frame in flight"),
+            SwapChainError::DrawableUnavailable => write!(f, "no drawable available"),
+        }
+    }
+}
+
+impl std::error::Error for SwapChainError {}
+
+// ============================================================================
+// FrameSemaphore
+// ============================================================================
+
+/// Counting semaphore bounding the frames in flight.
+struct FrameSemaphore {
+    available: Mutex<usize>,
+    released: Condvar,
+}
+
+impl FrameSemaphore {
+    fn new(count: usize) -> Self {
+        Self {
+            available: Mutex::new(count),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Take a slot, waiting at most `timeout` (or forever if `None`).
+    fn acquire(&self, timeout: Option<Duration>) -> bool {
+        let available = self.available.lock().unwrap();
+        let mut available = match timeout {
+            None => self.released.wait_while(available, |n| *n == 0).unwrap(),
+            Some(timeout) => {
+                let (available, result) = self
+                    .released
+                    .wait_timeout_while(available, timeout, |n| *n == 0)
+                    .unwrap();
+                if result.timed_out() && *available == 0 {
+                    return false;
+                }
+                available
+            }
+        };
+        *available -= 1;
+        true
+    }
+
+    fn release(&self) {
+        *self.available.lock().unwrap() += 1;
+        self.released.notify_one();
+    }
+
+    fn available(&self) -> usize {
+        *self.available.lock().unwrap()
+    }
+}
+
+/// A held frame slot, returned to the semaphore on drop.
+struct FramePermit(Arc<FrameSemaphore>);
+
+impl Drop for FramePermit {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+// ============================================================================
+// Frame
+// ============================================================================
+
+/// A drawable acquired from a [`SwapChain`].
+///
+/// Holds one of the swap chain's frame slots. The slot is released when the
+/// command buffer passed to [`present`](Self::present) completes, or when
+/// the frame is dropped without being presented.
+pub struct Frame {
+    /// The drawable to present.
+    pub drawable: MetalDrawable,
+    /// The drawable's texture to render into.
+    pub texture: Texture,
+    /// Index of the frame slot, in `0..max_frames_in_flight`.
+    pub index: usize,
+    permit: FramePermit,
+}
+
+impl Frame {
+    /// Schedule the drawable for presentation on `command_buffer`.
+    ///
+    /// The frame slot is released once `command_buffer` completes. See
+    /// [`MetalDrawable::present_on`].
+    pub fn present(self, command_buffer: &CommandBuffer) {
+        self.drawable.present_on(command_buffer);
+        self.release_on_completion(command_buffer);
+    }
+
+    /// Schedule the drawable for presentation on `command_buffer` at the
+    /// next time chosen by `scheduler`.
+    ///
+    /// The frame slot is released once `command_buffer` completes. See
+    /// [`PresentScheduler::present`].
+    pub fn present_scheduled(
+        self,
+        command_buffer: &CommandBuffer,
+        scheduler: &mut PresentScheduler,
+    ) {
+        scheduler.present(&self.drawable, command_buffer);
+        self.release_on_completion(command_buffer);
+    }
+
+    fn release_on_completion(self, command_buffer: &CommandBuffer) {
+        let permit = Mutex::new(Some(self.permit));
+        command_buffer.add_completed_handler(move |_| {
+            permit.lock().unwrap().take();
+        });
+    }
+}
+
+impl fmt::Debug for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Frame")
+            .field("drawable", &self.drawable)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+// ============================================================================
+// SwapChain
+// ============================================================================
+
+/// A [`MetalLayer`] with frame pacing and drawable acquisition.
+pub struct SwapChain {
+    layer: MetalLayer,
+    frames: Arc<FrameSemaphore>,
+    max_frames_in_flight: usize,
+    timeout: AcquireTimeout,
+    frame_count: u64,
+}
+
+impl SwapChain {
+    /// Create a swap chain with a new layer for `device` in `pixel_format`.
+    pub fn new(device: &Device, pixel_format: PixelFormat) -> Option<Self> {
+        let layer = MetalLayer::layer()?;
+        layer.set_device(device);
+        layer.set_pixel_format(pixel_format);
+        Some(Self::with_layer(layer))
+    }
+
+    /// Create a swap chain around an existing, configured layer.
+    pub fn with_layer(layer: MetalLayer) -> Self {
+        let timeout = AcquireTimeout::default();
+        layer.set_allows_next_drawable_timeout(true);
+        Self {
+            layer,
+            frames: Arc::new(FrameSemaphore::new(DEFAULT_MAX_FRAMES_IN_FLIGHT)),
+            max_frames_in_flight: DEFAULT_MAX_FRAMES_IN_FLIGHT,
+            timeout,
+            frame_count: 0,
+        }
+    }
+
+    /// Set how many frames the CPU may encode ahead of the GPU.
+    ///
+    /// Must be called before the first frame is acquired.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is zero.
+    pub fn with_max_frames_in_flight(mut self, count: usize) -> Self {
+        assert!(count > 0, "at least one frame must be allowed in flight");
+        self.frames = Arc::new(FrameSemaphore::new(count));
+        self.max_frames_in_flight = count;
+        self
+    }
+
+    /// Set the acquisition timeout policy.
+    pub fn with_timeout(mut self, timeout: AcquireTimeout) -> Self {
+        self.layer
+            .set_allows_next_drawable_timeout(timeout != AcquireTimeout::Block);
+        self.timeout = timeout;
+        self
+    }
+
+    /// Get the layer.
+    pub fn layer(&self) -> &MetalLayer {
+        &self.layer
+    }
+
+    /// Get the maximum number of frames in flight.
+    pub fn max_frames_in_flight(&self) -> usize {
+        self.max_frames_in_flight
+    }
+
+    /// Get the number of frames acquired but not yet completed.
+    pub fn frames_in_flight(&self) -> usize {
+        self.max_frames_in_flight - self.frames.available()
+    }
+
+    /// Get the number of frames acquired so far.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Get the acquisition timeout policy.
+    pub fn timeout(&self) -> AcquireTimeout {
+        self.timeout
+    }
+
+    /// Get the size of the layer's drawables in pixels.
+    pub fn drawable_size(&self) -> CGSize {
+        self.layer.drawable_size()
+    }
+
+    /// Resize the layer's drawables to `size` pixels.
+    ///
+    /// Call when the window is resized. Returns `true` if the size changed;
+    /// zero-sized requests (e.g. a minimized window) are ignored.
+    pub fn resize(&self, size: CGSize) -> bool {
+        if size.width <= 0.0 || size.height <= 0.0 || size == self.layer.drawable_size() {
+            return false;
+        }
+        self.layer.set_drawable_size(size);
+        true
+    }
+
+    /// Wait for a free frame slot and acquire the next drawable.
+    pub fn acquire_frame(&mut self) -> Result<Frame, SwapChainError> {
+        let timeout = match self.timeout {
+            AcquireTimeout::Block => None,
+            AcquireTimeout::After(duration) => Some(duration),
+        };
+        if !self.frames.acquire(timeout) {
+            return Err(SwapChainError::Timeout);
+        }
+        let permit = FramePermit(Arc::clone(&self.frames));
+
+        let (drawable, texture) = AutoreleasePool::with(|| {
+            let drawable = self
+                .layer
+                .next_drawable()
+                .ok_or(SwapChainError::DrawableUnavailable)?;
+            let texture = drawable
+                .texture()
+                .ok_or(SwapChainError::DrawableUnavailable)?;
+            Ok((drawable, texture))
+        })?;
+        let index = (self.frame_count % self.max_frames_in_flight as u64) as usize;
+        self.frame_count += 1;
+
+        Ok(Frame {
+            drawable,
+            texture,
+            index,
+            permit,
+        })
+    }
+
+    /// Block until every acquired frame has completed.
+    pub fn wait_idle(&self) {
+        for _ in 0..self.max_frames_in_flight {
+            self.frames.acquire(None);
+        }
+        for _ in 0..self.max_frames_in_flight {
+            self.frames.release();
+        }
+    }
+}
+
+impl fmt::Debug for SwapChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwapChain")
+            .field("layer", &self.layer)
+            .field("max_frames_in_flight", &self.max_frames_in_flight)
+            .field("frames_in_flight", &self.frames_in_flight())
+            .field("timeout", &self.timeout)
+            .field("frame_count", &self.frame_count)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_semaphore_times_out_when_exhausted() {
+        let semaphore = FrameSemaphore::new(2);
+        assert!(semaphore.acquire(Some(Duration::ZERO)));
+        assert!(semaphore.acquire(Some(Duration::ZERO)));
+        assert!(!semaphore.acquire(Some(Duration::from_millis(10))));
+        semaphore.release();
+        assert!(semaphore.acquire(Some(Duration::ZERO)));
+    }
+
+    #[test]
+    fn test_frame_permit_releases_on_drop() {
+        let semaphore = Arc::new(FrameSemaphore::new(1));
+        assert!(semaphore.acquire(None));
+        let permit = FramePermit(Arc::clone(&semaphore));
+        assert_eq!(semaphore.available(), 0);
+        drop(permit);
+        assert_eq!(semaphore.available(), 1);
+    }
+
+    #[test]
+    fn test_frame_semaphore_wakes_blocked_acquire() {
+        let semaphore = Arc::new(FrameSemaphore::new(1));
+        assert!(semaphore.acquire(None));
+        let waiter = {
+            let semaphore = Arc::clone(&semaphore);
+            std::thread::spawn(move || semaphore.acquire(None))
+        };
+        semaphore.release();
+        assert!(waiter.join().unwrap());
+    }
+
+    #[test]
+    fn test_default_timeout_matches_core_animation() {
+        assert_eq!(
+            AcquireTimeout::default(),
+            AcquireTimeout::After(Duration::from_secs(1))
+        );
+    }
+}
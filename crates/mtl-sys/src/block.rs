@@ -53,7 +53,7 @@ const BLOCK_HAS_SIGNATURE: i32 = 1 << 30;
 // External Symbols from libobjc
 // =============================================================================
 
-#[link(name = "objc")]
+#[cfg_attr(target_vendor = "apple", link(name = "objc"))]
 unsafe extern "C" {
     /// The class object for stack-allocated blocks.
     static _NSConcreteStackBlock: *const c_void;
@@ -37,7 +37,7 @@ use std::ffi::c_void;
 pub type DeviceObserver = *mut c_void;
 
 // Link against the Metal framework for device creation functions
-#[link(name = "Metal", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "Metal", kind = "framework"))]
 unsafe extern "C" {
     /// Create the system default Metal device.
     ///
@@ -166,7 +166,7 @@ pub type IOCompressionContext = *mut c_void;
 // IO Compression Functions
 // =============================================================================
 
-#[link(name = "Metal", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "Metal", kind = "framework"))]
 unsafe extern "C" {
     /// Get the default chunk size for IO compression.
     ///
@@ -5,8 +5,10 @@
 //!
 //! - Architecture-aware message sending (`objc_msgSend` variants)
 //! - Selector and class caching
-//! - Objective-C block support (Clang ABI)
+//! - Objective-C block support (Clang ABI), behind the `blocks` feature
 //! - Custom macros for enums and bitflags
+//! - Debug-build checking of `msg_send` signatures against the runtime's
+//!   type encodings, behind the `verify-signatures` feature
 //!
 //! # Safety
 //!
@@ -17,11 +19,14 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
 
+#[cfg(feature = "blocks")]
 mod block;
 mod functions;
 mod macros;
 mod msg_send;
 mod runtime;
+#[cfg(all(feature = "verify-signatures", debug_assertions))]
+mod verify;
 
 // Re-export runtime types
 pub use runtime::{CachedClass, CachedSel, Class, MethodDescription, Protocol, Sel, get_protocol};
@@ -33,6 +38,7 @@ pub use msg_send::{
 };
 
 // Re-export block types
+#[cfg(feature = "blocks")]
 pub use block::{
     BlockLiteral, CommandBufferHandler, DeallocatorBlock, DeviceNotificationHandler,
     DrawablePresentedHandler, EventBlock, HeapOneArgBlock, LogHandlerBlock,
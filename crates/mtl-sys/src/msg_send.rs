@@ -22,9 +22,13 @@
 use std::ffi::c_void;
 
 use crate::runtime::Sel;
+#[cfg(all(feature = "verify-signatures", debug_assertions))]
+use crate::verify::RustType;
 
-// Link against libobjc for message sending functions
-#[link(name = "objc")]
+// Link against libobjc for message sending functions. Linking is limited to
+// Apple targets so code that only uses the `mock` device compiles and links
+// elsewhere.
+#[cfg_attr(target_vendor = "apple", link(name = "objc"))]
 unsafe extern "C" {
     fn objc_msgSend();
 
@@ -66,9 +70,12 @@ const fn requires_stret<T>() -> bool {
 /// Check if a type is a floating-point type (for fpret on x86).
 #[inline]
 #[allow(dead_code)]
-fn is_float<T: 'static>() -> bool {
-    use std::any::TypeId;
-    TypeId::of::<T>() == TypeId::of::<f32>() || TypeId::of::<T>() == TypeId::of::<f64>()
+fn is_float<T>() -> bool {
+    // `TypeId` would need `T: 'static`, which return types like `&'a T`
+    // don't satisfy. Primitive type names are unique, so compare those.
+    use std::any::type_name;
+    (size_of::<T>() == size_of::<f32>() && type_name::<T>() == type_name::<f32>())
+        || (size_of::<T>() == size_of::<f64>() && type_name::<T>() == type_name::<f64>())
 }
 
 // =============================================================================
@@ -84,6 +91,11 @@ fn is_float<T: 'static>() -> bool {
 /// - The return type `R` must match the actual return type of the method
 #[inline]
 pub unsafe fn msg_send_0<R>(obj: *const c_void, sel: Sel) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(obj, sel, RustType::of::<R>(), &[])
+    };
+
     #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
     if is_float::<R>() {
         let f: unsafe extern "C" fn(*const c_void, Sel) -> R =
@@ -119,6 +131,11 @@ pub unsafe fn msg_send_0<R>(obj: *const c_void, sel: Sel) -> R {
 /// - The return type `R` must match the actual return type of the method
 #[inline]
 pub unsafe fn msg_send_1<R, A>(obj: *const c_void, sel: Sel, a: A) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(obj, sel, RustType::of::<R>(), &[RustType::of::<A>()])
+    };
+
     #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
     if is_float::<R>() {
         let f: unsafe extern "C" fn(*const c_void, Sel, A) -> R =
@@ -151,6 +168,16 @@ pub unsafe fn msg_send_1<R, A>(obj: *const c_void, sel: Sel, a: A) -> R {
 /// See `msg_send_0` for safety requirements.
 #[inline]
 pub unsafe fn msg_send_2<R, A, B>(obj: *const c_void, sel: Sel, a: A, b: B) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(
+            obj,
+            sel,
+            RustType::of::<R>(),
+            &[RustType::of::<A>(), RustType::of::<B>()],
+        )
+    };
+
     #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
     if is_float::<R>() {
         let f: unsafe extern "C" fn(*const c_void, Sel, A, B) -> R =
@@ -183,6 +210,20 @@ pub unsafe fn msg_send_2<R, A, B>(obj: *const c_void, sel: Sel, a: A, b: B) -> R
 /// See `msg_send_0` for safety requirements.
 #[inline]
 pub unsafe fn msg_send_3<R, A, B, C>(obj: *const c_void, sel: Sel, a: A, b: B, c: C) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(
+            obj,
+            sel,
+            RustType::of::<R>(),
+            &[
+                RustType::of::<A>(),
+                RustType::of::<B>(),
+                RustType::of::<C>(),
+            ],
+        )
+    };
+
     #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
     if is_float::<R>() {
         let f: unsafe extern "C" fn(*const c_void, Sel, A, B, C) -> R =
@@ -215,6 +256,21 @@ pub unsafe fn msg_send_3<R, A, B, C>(obj: *const c_void, sel: Sel, a: A, b: B, c
 /// See `msg_send_0` for safety requirements.
 #[inline]
 pub unsafe fn msg_send_4<R, A, B, C, D>(obj: *const c_void, sel: Sel, a: A, b: B, c: C, d: D) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(
+            obj,
+            sel,
+            RustType::of::<R>(),
+            &[
+                RustType::of::<A>(),
+                RustType::of::<B>(),
+                RustType::of::<C>(),
+                RustType::of::<D>(),
+            ],
+        )
+    };
+
     #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
     if is_float::<R>() {
         let f: unsafe extern "C" fn(*const c_void, Sel, A, B, C, D) -> R =
@@ -255,6 +311,22 @@ pub unsafe fn msg_send_5<R, A, B, C, D, E>(
     d: D,
     e: E,
 ) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(
+            obj,
+            sel,
+            RustType::of::<R>(),
+            &[
+                RustType::of::<A>(),
+                RustType::of::<B>(),
+                RustType::of::<C>(),
+                RustType::of::<D>(),
+                RustType::of::<E>(),
+            ],
+        )
+    };
+
     #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
     if is_float::<R>() {
         let f: unsafe extern "C" fn(*const c_void, Sel, A, B, C, D, E) -> R =
@@ -297,6 +369,23 @@ pub unsafe fn msg_send_6<R, A, B, C, D, E, F>(
     e: E,
     f_arg: F,
 ) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(
+            obj,
+            sel,
+            RustType::of::<R>(),
+            &[
+                RustType::of::<A>(),
+                RustType::of::<B>(),
+                RustType::of::<C>(),
+                RustType::of::<D>(),
+                RustType::of::<E>(),
+                RustType::of::<F>(),
+            ],
+        )
+    };
+
     #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
     if is_float::<R>() {
         let func: unsafe extern "C" fn(*const c_void, Sel, A, B, C, D, E, F) -> R =
@@ -340,6 +429,24 @@ pub unsafe fn msg_send_7<R, A, B, C, D, E, F, G>(
     f_arg: F,
     g: G,
 ) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(
+            obj,
+            sel,
+            RustType::of::<R>(),
+            &[
+                RustType::of::<A>(),
+                RustType::of::<B>(),
+                RustType::of::<C>(),
+                RustType::of::<D>(),
+                RustType::of::<E>(),
+                RustType::of::<F>(),
+                RustType::of::<G>(),
+            ],
+        )
+    };
+
     #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
     if is_float::<R>() {
         let func: unsafe extern "C" fn(*const c_void, Sel, A, B, C, D, E, F, G) -> R =
@@ -384,6 +491,25 @@ pub unsafe fn msg_send_8<R, A, B, C, D, E, F, G, H>(
     g: G,
     h: H,
 ) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(
+            obj,
+            sel,
+            RustType::of::<R>(),
+            &[
+                RustType::of::<A>(),
+                RustType::of::<B>(),
+                RustType::of::<C>(),
+                RustType::of::<D>(),
+                RustType::of::<E>(),
+                RustType::of::<F>(),
+                RustType::of::<G>(),
+                RustType::of::<H>(),
+            ],
+        )
+    };
+
     #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
     if is_float::<R>() {
         let func: unsafe extern "C" fn(*const c_void, Sel, A, B, C, D, E, F, G, H) -> R =
@@ -429,6 +555,26 @@ pub unsafe fn msg_send_9<R, A, B, C, D, E, F, G, H, I>(
     h: H,
     i: I,
 ) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(
+            obj,
+            sel,
+            RustType::of::<R>(),
+            &[
+                RustType::of::<A>(),
+                RustType::of::<B>(),
+                RustType::of::<C>(),
+                RustType::of::<D>(),
+                RustType::of::<E>(),
+                RustType::of::<F>(),
+                RustType::of::<G>(),
+                RustType::of::<H>(),
+                RustType::of::<I>(),
+            ],
+        )
+    };
+
     // For 9+ args, we don't bother with fpret optimization (rare case)
     #[cfg(not(target_arch = "aarch64"))]
     if requires_stret::<R>() {
@@ -469,6 +615,27 @@ pub unsafe fn msg_send_10<R, A, B, C, D, E, F, G, H, I, J>(
     i: I,
     j: J,
 ) -> R {
+    #[cfg(all(feature = "verify-signatures", debug_assertions))]
+    unsafe {
+        crate::verify::verify(
+            obj,
+            sel,
+            RustType::of::<R>(),
+            &[
+                RustType::of::<A>(),
+                RustType::of::<B>(),
+                RustType::of::<C>(),
+                RustType::of::<D>(),
+                RustType::of::<E>(),
+                RustType::of::<F>(),
+                RustType::of::<G>(),
+                RustType::of::<H>(),
+                RustType::of::<I>(),
+                RustType::of::<J>(),
+            ],
+        )
+    };
+
     #[cfg(not(target_arch = "aarch64"))]
     if requires_stret::<R>() {
         let mut result = std::mem::MaybeUninit::<R>::uninit();
@@ -31,7 +31,7 @@ unsafe impl Send for Class {}
 unsafe impl Sync for Class {}
 
 // Link against libobjc
-#[link(name = "objc")]
+#[cfg_attr(target_vendor = "apple", link(name = "objc"))]
 unsafe extern "C" {
     fn sel_registerName(name: *const c_char) -> Sel;
     fn objc_lookUpClass(name: *const c_char) -> *const c_void;
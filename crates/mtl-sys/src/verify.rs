@@ -0,0 +1,353 @@
+//! Debug-build checking of message send signatures.
+//!
+//! With the `verify-signatures` feature, every `msg_send_N` call in a build
+//! with debug assertions compares the Rust return and argument types against
+//! the method's Objective-C type encoding (`method_getTypeEncoding`) the first
+//! time each class, selector and Rust signature is seen, and panics on a
+//! mismatch. This catches calls such as reading a `BOOL` return as a pointer,
+//! which otherwise corrupt registers or the stack silently.
+//!
+//! Types are compared by size, and by whether they are floating point, since
+//! that is what decides how a value is passed. Methods the runtime can't find
+//! (forwarded or proxied messages) and encodings that can't be sized are not
+//! checked.
+
+use std::collections::HashSet;
+use std::ffi::{CStr, c_char, c_void};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Mutex, OnceLock};
+
+use crate::runtime::Sel;
+
+#[cfg_attr(target_vendor = "apple", link(name = "objc"))]
+unsafe extern "C" {
+    fn object_getClass(obj: *const c_void) -> *const c_void;
+    fn class_getInstanceMethod(cls: *const c_void, sel: Sel) -> *const c_void;
+    fn method_getTypeEncoding(method: *const c_void) -> *const c_char;
+    fn class_getName(cls: *const c_void) -> *const c_char;
+    fn sel_getName(sel: Sel) -> *const c_char;
+}
+
+/// A Rust type as seen by the calling convention.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct RustType {
+    name: &'static str,
+    size: usize,
+}
+
+impl RustType {
+    #[inline]
+    pub(crate) fn of<T>() -> Self {
+        Self {
+            name: std::any::type_name::<T>(),
+            size: std::mem::size_of::<T>(),
+        }
+    }
+
+    /// Whether the type is floating point, if that is known from its name.
+    ///
+    /// Wrapper structs are only compared by size.
+    fn is_float(&self) -> Option<bool> {
+        match self.name {
+            "f32" | "f64" => Some(true),
+            "bool" | "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "usize"
+            | "isize" => Some(false),
+            name if name.starts_with('*') || name.starts_with('&') => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// An Objective-C type decoded from a type encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct EncodedType {
+    size: usize,
+    align: usize,
+    is_float: bool,
+}
+
+const POINTER: EncodedType = EncodedType {
+    size: std::mem::size_of::<usize>(),
+    align: std::mem::align_of::<usize>(),
+    is_float: false,
+};
+
+const fn scalar(size: usize, is_float: bool) -> EncodedType {
+    EncodedType {
+        size,
+        align: if size == 0 { 1 } else { size },
+        is_float,
+    }
+}
+
+/// Decode the type at the start of `encoding`, returning it and the rest of
+/// the string. Returns `None` for types that can't be sized.
+fn decode_type(encoding: &[u8]) -> Option<(EncodedType, &[u8])> {
+    // Type qualifiers: const, in, inout, out, bycopy, byref, oneway, atomic
+    let mut encoding = encoding;
+    while let [
+        b'r' | b'n' | b'N' | b'o' | b'O' | b'R' | b'V' | b'A',
+        rest @ ..,
+    ] = encoding
+    {
+        encoding = rest;
+    }
+    let (&code, rest) = encoding.split_first()?;
+    match code {
+        b'c' | b'C' | b'B' => Some((scalar(1, false), rest)),
+        b's' | b'S' => Some((scalar(2, false), rest)),
+        // `l` is a 32-bit quantity even in 64-bit programs
+        b'i' | b'I' | b'l' | b'L' => Some((scalar(4, false), rest)),
+        b'q' | b'Q' => Some((scalar(8, false), rest)),
+        b'f' => Some((scalar(4, true), rest)),
+        b'd' => Some((scalar(8, true), rest)),
+        b'v' => Some((scalar(0, false), rest)),
+        b'*' | b'#' | b':' | b'?' => Some((POINTER, rest)),
+        b'@' => {
+            let rest = match rest {
+                [b'?', rest @ ..] => rest,
+                [b'"', rest @ ..] => {
+                    let end = rest.iter().position(|&c| c == b'"')?;
+                    &rest[end + 1..]
+                }
+                _ => rest,
+            };
+            Some((POINTER, rest))
+        }
+        b'^' => {
+            // Pointees may be opaque (`^{Foo}`), so only their extent matters.
+            let rest = match decode_type(rest) {
+                Some((_, rest)) => rest,
+                None => skip_type(rest)?,
+            };
+            Some((POINTER, rest))
+        }
+        b'[' => {
+            let digits = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+            let count: usize = std::str::from_utf8(&rest[..digits]).ok()?.parse().ok()?;
+            let (element, rest) = decode_type(&rest[digits..])?;
+            let rest = rest.strip_prefix(b"]")?;
+            let ty = EncodedType {
+                size: element.size * count,
+                align: element.align,
+                is_float: false,
+            };
+            Some((ty, rest))
+        }
+        b'{' | b'(' => {
+            let close = if code == b'{' { b'}' } else { b')' };
+            let name_end = rest.iter().position(|&c| c == b'=' || c == close)?;
+            if rest[name_end] == close {
+                // Opaque struct or union
+                return None;
+            }
+            let mut fields = &rest[name_end + 1..];
+            let mut size = 0usize;
+            let mut align = 1;
+            while fields.first() != Some(&close) {
+                let (field, next) = decode_type(fields)?;
+                align = align.max(field.align);
+                size = if code == b'{' {
+                    size.next_multiple_of(field.align) + field.size
+                } else {
+                    size.max(field.size)
+                };
+                fields = next;
+            }
+            let ty = EncodedType {
+                size: size.next_multiple_of(align),
+                align,
+                is_float: false,
+            };
+            Some((ty, &fields[1..]))
+        }
+        _ => None,
+    }
+}
+
+/// Skip over the type at the start of `encoding` without sizing it.
+fn skip_type(encoding: &[u8]) -> Option<&[u8]> {
+    let mut depth = 0usize;
+    for (i, &c) in encoding.iter().enumerate() {
+        match c {
+            b'{' | b'(' | b'[' => depth += 1,
+            b'}' | b')' | b']' => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+        if depth == 0 {
+            return Some(&encoding[i + 1..]);
+        }
+    }
+    None
+}
+
+/// Decode a method type encoding such as `c24@0:8@16` into its return type
+/// followed by its parameter types, including `self` and `_cmd`.
+fn decode_method(encoding: &[u8]) -> Option<Vec<EncodedType>> {
+    let mut types = Vec::new();
+    let mut rest = encoding;
+    while !rest.is_empty() {
+        let (ty, next) = decode_type(rest)?;
+        types.push(ty);
+        // Skip the frame offset that follows each type
+        let offset = next
+            .iter()
+            .take_while(|&&c| c == b'-' || c.is_ascii_digit())
+            .count();
+        rest = &next[offset..];
+    }
+    Some(types)
+}
+
+fn matches(rust: &RustType, encoded: &EncodedType) -> bool {
+    rust.size == encoded.size && rust.is_float().is_none_or(|f| f == encoded.is_float)
+}
+
+/// Describe the mismatch between a Rust signature and a method's encoded
+/// types, or return `None` if they agree.
+fn mismatch(ret: &RustType, args: &[RustType], encoded: &[EncodedType]) -> Option<String> {
+    let [encoded_ret, _self, _cmd, encoded_args @ ..] = encoded else {
+        return None;
+    };
+    if encoded_args.len() != args.len() {
+        return Some(format!(
+            "takes {} arguments but was sent {}",
+            encoded_args.len(),
+            args.len()
+        ));
+    }
+    // Discarding a return value is fine unless it changes the convention.
+    let discarded = ret.size == 0 && encoded_ret.size <= 2 * POINTER.size;
+    if !discarded && !matches(ret, encoded_ret) {
+        return Some(format!(
+            "returns a {}-byte {} value but was read as `{}`",
+            encoded_ret.size,
+            if encoded_ret.is_float {
+                "floating-point"
+            } else {
+                "integer"
+            },
+            ret.name
+        ));
+    }
+    for (i, (arg, encoded)) in args.iter().zip(encoded_args).enumerate() {
+        if !matches(arg, encoded) {
+            return Some(format!(
+                "argument {} is a {}-byte {} value but was sent as `{}`",
+                i + 1,
+                encoded.size,
+                if encoded.is_float {
+                    "floating-point"
+                } else {
+                    "integer"
+                },
+                arg.name
+            ));
+        }
+    }
+    None
+}
+
+/// Signatures already checked, by class, selector and Rust signature hash.
+fn verified() -> &'static Mutex<HashSet<(usize, usize, u64)>> {
+    static VERIFIED: OnceLock<Mutex<HashSet<(usize, usize, u64)>>> = OnceLock::new();
+    VERIFIED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+unsafe fn c_str(ptr: *const c_char) -> std::borrow::Cow<'static, str> {
+    if ptr.is_null() {
+        "<unknown>".into()
+    } else {
+        unsafe { CStr::from_ptr(ptr) }.to_string_lossy()
+    }
+}
+
+/// Check a message send of `sel` to `obj` with return type `ret` and
+/// argument types `args` against the method's type encoding.
+///
+/// # Panics
+///
+/// Panics if the Rust types don't match the encoding.
+///
+/// # Safety
+///
+/// `obj` must be null or a valid Objective-C object or class pointer.
+pub(crate) unsafe fn verify(obj: *const c_void, sel: Sel, ret: RustType, args: &[RustType]) {
+    if obj.is_null() || sel.is_null() {
+        return;
+    }
+    let cls = unsafe { object_getClass(obj) };
+    if cls.is_null() {
+        return;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    ret.name.hash(&mut hasher);
+    for arg in args {
+        arg.name.hash(&mut hasher);
+    }
+    let key = (cls as usize, sel.as_ptr() as usize, hasher.finish());
+    if !verified()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key)
+    {
+        return;
+    }
+
+    let method = unsafe { class_getInstanceMethod(cls, sel) };
+    if method.is_null() {
+        return;
+    }
+    let encoding = unsafe { method_getTypeEncoding(method) };
+    if encoding.is_null() {
+        return;
+    }
+    let encoding = unsafe { CStr::from_ptr(encoding) };
+    let Some(encoded) = decode_method(encoding.to_bytes()) else {
+        return;
+    };
+
+    if let Some(problem) = mismatch(&ret, args, &encoded) {
+        let (class_name, sel_name) =
+            unsafe { (c_str(class_getName(cls)), c_str(sel_getName(sel))) };
+        panic!(
+            "msg_send signature mismatch: [{class_name} {sel_name}] {problem} \
+             (Objective-C encoding `{}`)",
+            encoding.to_string_lossy()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_method() {
+        let types = decode_method(b"v40@0:8{CGSize=dd}16r^{Opaque=}32").unwrap();
+        assert_eq!(types.len(), 5);
+        assert_eq!(types[3].size, 16);
+        assert_eq!(types[4], POINTER);
+
+        let types = decode_method(b"{?=[3Q]}24@0:8@\"NSString\"16").unwrap();
+        assert_eq!(types[0].size, 24);
+        assert!(decode_method(b"v16@0:8^{Opaque}16").is_some());
+        assert!(decode_method(b"{Opaque}16@0:8").is_none());
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let encoded = decode_method(b"c24@0:8@16").unwrap();
+        let arg = [RustType::of::<*mut c_void>()];
+        assert!(mismatch(&RustType::of::<bool>(), &arg, &encoded).is_none());
+        assert!(mismatch(&RustType::of::<()>(), &arg, &encoded).is_none());
+        assert!(mismatch(&RustType::of::<*mut c_void>(), &arg, &encoded).is_some());
+        assert!(mismatch(&RustType::of::<bool>(), &[], &encoded).is_some());
+
+        let encoded = decode_method(b"v24@0:8d16").unwrap();
+        let ret = RustType::of::<()>();
+        assert!(mismatch(&ret, &[RustType::of::<f64>()], &encoded).is_none());
+        assert!(mismatch(&ret, &[RustType::of::<u64>()], &encoded).is_some());
+    }
+}
@@ -0,0 +1,143 @@
+/*
+ * C API for mtl-gpu, enabled with the `capi` feature.
+ *
+ * Handles are created by mtl_*_create functions and destroyed by the
+ * matching mtl_*_release. Fallible calls return an MtlStatus; on failure
+ * mtl_last_error_message() describes the error on the calling thread.
+ * Panics never unwind into the caller: they are reported as
+ * MTL_STATUS_PANICKED, or a zero or NULL result for calls without a status.
+ */
+
+#ifndef MTL_GPU_H
+#define MTL_GPU_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef unsigned long MtlUInteger;
+
+typedef struct MtlDevice MtlDevice;
+typedef struct MtlCommandQueue MtlCommandQueue;
+typedef struct MtlBuffer MtlBuffer;
+typedef struct MtlTexture MtlTexture;
+typedef struct MtlComputePipeline MtlComputePipeline;
+typedef struct MtlCommandBuffer MtlCommandBuffer;
+typedef struct MtlComputeEncoder MtlComputeEncoder;
+typedef struct MtlRenderPipeline MtlRenderPipeline;
+typedef struct MtlRenderEncoder MtlRenderEncoder;
+
+typedef enum MtlStatus {
+    MTL_STATUS_OK = 0,
+    MTL_STATUS_NULL_ARGUMENT = 1,
+    MTL_STATUS_INVALID_ARGUMENT = 2,
+    MTL_STATUS_NO_DEVICE = 3,
+    MTL_STATUS_ALLOCATION_FAILED = 4,
+    MTL_STATUS_COMPILE_FAILED = 5,
+    MTL_STATUS_FUNCTION_NOT_FOUND = 6,
+    MTL_STATUS_PIPELINE_FAILED = 7,
+    MTL_STATUS_COMMAND_BUFFER_FAILED = 8,
+    MTL_STATUS_PANICKED = 9,
+} MtlStatus;
+
+const char *mtl_last_error_message(void);
+
+/* Device */
+MtlStatus mtl_device_create_system_default(MtlDevice **out);
+size_t mtl_device_name(const MtlDevice *device, char *buffer, size_t capacity);
+void mtl_device_release(MtlDevice *device);
+
+/* Command queue */
+MtlStatus mtl_command_queue_create(const MtlDevice *device, MtlCommandQueue **out);
+void mtl_command_queue_release(MtlCommandQueue *queue);
+
+/* Buffer; options is an MTLResourceOptions */
+MtlStatus mtl_buffer_create(const MtlDevice *device, MtlUInteger length, MtlUInteger options,
+                            MtlBuffer **out);
+void *mtl_buffer_contents(const MtlBuffer *buffer);
+MtlUInteger mtl_buffer_length(const MtlBuffer *buffer);
+void mtl_buffer_release(MtlBuffer *buffer);
+
+/* Texture; pixel_format is an MTLPixelFormat and usage an MTLTextureUsage */
+MtlStatus mtl_texture_create_2d(const MtlDevice *device, MtlUInteger pixel_format,
+                                MtlUInteger width, MtlUInteger height, MtlUInteger usage,
+                                MtlTexture **out);
+MtlUInteger mtl_texture_width(const MtlTexture *texture);
+MtlUInteger mtl_texture_height(const MtlTexture *texture);
+void mtl_texture_release(MtlTexture *texture);
+
+/* Compute pipeline */
+MtlStatus mtl_compute_pipeline_create(const MtlDevice *device, const char *source,
+                                      const char *function_name, MtlComputePipeline **out);
+MtlUInteger mtl_compute_pipeline_thread_execution_width(const MtlComputePipeline *pipeline);
+void mtl_compute_pipeline_release(MtlComputePipeline *pipeline);
+
+/* Render pipeline; color_pixel_format is an MTLPixelFormat */
+MtlStatus mtl_render_pipeline_create(const MtlDevice *device, const char *source,
+                                     const char *vertex_function, const char *fragment_function,
+                                     MtlUInteger color_pixel_format, MtlRenderPipeline **out);
+void mtl_render_pipeline_release(MtlRenderPipeline *pipeline);
+
+/* Drawable; drawable is an id<CAMetalDrawable> */
+MtlStatus mtl_drawable_texture(void *drawable, MtlTexture **out);
+
+/* Command buffer; drawable is an id<CAMetalDrawable> */
+MtlStatus mtl_command_buffer_create(const MtlCommandQueue *queue, MtlCommandBuffer **out);
+MtlStatus mtl_command_buffer_present_drawable(const MtlCommandBuffer *command_buffer,
+                                              void *drawable);
+MtlStatus mtl_command_buffer_commit(const MtlCommandBuffer *command_buffer);
+MtlStatus mtl_command_buffer_wait_until_completed(const MtlCommandBuffer *command_buffer);
+void mtl_command_buffer_release(MtlCommandBuffer *command_buffer);
+
+/* Compute encoder; set_bytes takes at most 4096 bytes */
+MtlStatus mtl_compute_encoder_create(const MtlCommandBuffer *command_buffer,
+                                     MtlComputeEncoder **out);
+MtlStatus mtl_compute_encoder_set_pipeline(const MtlComputeEncoder *encoder,
+                                           const MtlComputePipeline *pipeline);
+MtlStatus mtl_compute_encoder_set_buffer(const MtlComputeEncoder *encoder, const MtlBuffer *buffer,
+                                         MtlUInteger offset, MtlUInteger index);
+MtlStatus mtl_compute_encoder_set_bytes(const MtlComputeEncoder *encoder, const void *bytes,
+                                        size_t length, MtlUInteger index);
+MtlStatus mtl_compute_encoder_set_texture(const MtlComputeEncoder *encoder,
+                                          const MtlTexture *texture, MtlUInteger index);
+MtlStatus mtl_compute_encoder_dispatch_threads(const MtlComputeEncoder *encoder, MtlUInteger width,
+                                               MtlUInteger height, MtlUInteger depth,
+                                               MtlUInteger group_width, MtlUInteger group_height,
+                                               MtlUInteger group_depth);
+MtlStatus mtl_compute_encoder_end_encoding(const MtlComputeEncoder *encoder);
+void mtl_compute_encoder_release(MtlComputeEncoder *encoder);
+
+/* Render encoder; load_action is an MTLLoadAction and primitive_type an MTLPrimitiveType */
+MtlStatus mtl_render_encoder_create(const MtlCommandBuffer *command_buffer,
+                                    const MtlTexture *texture, MtlUInteger load_action,
+                                    double clear_red, double clear_green, double clear_blue,
+                                    double clear_alpha, MtlRenderEncoder **out);
+MtlStatus mtl_render_encoder_set_pipeline(const MtlRenderEncoder *encoder,
+                                          const MtlRenderPipeline *pipeline);
+MtlStatus mtl_render_encoder_set_vertex_buffer(const MtlRenderEncoder *encoder,
+                                               const MtlBuffer *buffer, MtlUInteger offset,
+                                               MtlUInteger index);
+MtlStatus mtl_render_encoder_set_vertex_bytes(const MtlRenderEncoder *encoder, const void *bytes,
+                                              size_t length, MtlUInteger index);
+MtlStatus mtl_render_encoder_set_fragment_buffer(const MtlRenderEncoder *encoder,
+                                                 const MtlBuffer *buffer, MtlUInteger offset,
+                                                 MtlUInteger index);
+MtlStatus mtl_render_encoder_set_fragment_bytes(const MtlRenderEncoder *encoder,
+                                                const void *bytes, size_t length,
+                                                MtlUInteger index);
+MtlStatus mtl_render_encoder_set_fragment_texture(const MtlRenderEncoder *encoder,
+                                                  const MtlTexture *texture, MtlUInteger index);
+MtlStatus mtl_render_encoder_draw_primitives(const MtlRenderEncoder *encoder,
+                                             MtlUInteger primitive_type, MtlUInteger vertex_start,
+                                             MtlUInteger vertex_count, MtlUInteger instance_count);
+MtlStatus mtl_render_encoder_end_encoding(const MtlRenderEncoder *encoder);
+void mtl_render_encoder_release(MtlRenderEncoder *encoder);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MTL_GPU_H */
@@ -0,0 +1,371 @@
+//! One-call builder for primitive acceleration structures.
+//!
+//! Building a usable acceleration structure takes several steps: query the
+//! sizes for the descriptor, allocate the structure and a scratch buffer,
+//! encode the build, read back the compacted size, and copy into a smaller
+//! structure. [`AccelerationStructureBuilder`] does all of them.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let triangles = AccelerationStructureTriangleGeometryDescriptor::new().unwrap();
+//! triangles.set_vertex_buffer(Some(&vertices));
+//! triangles.set_vertex_stride(12);
+//! triangles.set_triangle_count(vertex_count / 3);
+//!
+//! let builder = AccelerationStructureBuilder::new()
+//!     .unwrap()
+//!     .triangle_geometry(triangles)
+//!     .usage(AccelerationStructureUsage::REFIT);
+//! let structure = builder.build(&queue)?;
+//!
+//! // After animating the vertices in place:
+//! builder.refit(&queue, &structure)?;
+//! ```
+
+use std::ffi::c_void;
+use std::fmt;
+
+use mtl_foundation::{Referencing, UInteger};
+use mtl_sys::{msg_send_0, sel};
+
+use super::{
+    AccelerationStructure, AccelerationStructureBoundingBoxGeometryDescriptor,
+    AccelerationStructureCommandEncoder, AccelerationStructureDescriptor,
+    AccelerationStructureSizes, AccelerationStructureTriangleGeometryDescriptor,
+    PrimitiveAccelerationStructureDescriptor,
+};
+use crate::enums::{AccelerationStructureUsage, CommandBufferStatus, ResourceOptions};
+use crate::{Buffer, CommandBuffer, CommandQueue, Device};
+
+/// Error produced by an [`AccelerationStructureBuilder`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AccelerationStructureBuildError {
+    /// No geometry was added.
+    NoGeometry,
+    /// The device does not support ray tracing.
+    Unsupported,
+    /// A structure, scratch buffer, command buffer or encoder could not be
+    /// created.
+    Allocation,
+    /// `refit` was called on a builder whose usage doesn't include
+    /// [`AccelerationStructureUsage::REFIT`].
+    NotRefittable,
+    /// The command buffer encoding a build, refit or compaction failed.
+    CommandBufferFailed,
+}
+
+impl fmt::Display for AccelerationStructureBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccelerationStructureBuildError::NoGeometry => {
+                write!(f, "acceleration structure has no geometry")
+            }
+            AccelerationStructureBuildError::Unsupported => {
+                write!(f, "device does not support ray tracing")
+            }
+            AccelerationStructureBuildError::Allocation => {
+                write!(f, "failed to allocate acceleration structure resources")
+            }
+            AccelerationStructureBuildError::NotRefittable => {
+                write!(f, "acceleration structure usage does not include refit")
+            }
+            AccelerationStructureBuildError::CommandBufferFailed => {
+                write!(f, "acceleration structure command buffer failed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AccelerationStructureBuildError {}
+
+/// A geometry descriptor of any supported kind.
+enum Geometry {
+    Triangles(AccelerationStructureTriangleGeometryDescriptor),
+    BoundingBoxes(AccelerationStructureBoundingBoxGeometryDescriptor),
+}
+
+impl Geometry {
+    fn as_ptr(&self) -> *const c_void {
+        match self {
+            Geometry::Triangles(geometry) => geometry.as_ptr(),
+            Geometry::BoundingBoxes(geometry) => geometry.as_ptr(),
+        }
+    }
+}
+
+// ============================================================================
+// AccelerationStructureBuilder
+// ============================================================================
+
+/// Builds, compacts and refits a primitive acceleration structure.
+///
+/// [`build`](Self::build) and [`refit`](Self::refit) commit their own
+/// command buffers and block until the GPU finishes, so they belong in
+/// loading code or between frames rather than inside a frame's encoding.
+pub struct AccelerationStructureBuilder {
+    descriptor: PrimitiveAccelerationStructureDescriptor,
+    geometry: Vec<Geometry>,
+    compact: bool,
+}
+
+impl AccelerationStructureBuilder {
+    /// Start building a structure with no geometry.
+    ///
+    /// Compaction is enabled by default.
+    pub fn new() -> Option<Self> {
+        Some(Self {
+            descriptor: PrimitiveAccelerationStructureDescriptor::new()?,
+            geometry: Vec::new(),
+            compact: true,
+        })
+    }
+
+    /// Add triangle geometry.
+    pub fn triangle_geometry(
+        mut self,
+        geometry: AccelerationStructureTriangleGeometryDescriptor,
+    ) -> Self {
+        self.geometry.push(Geometry::Triangles(geometry));
+        self.sync_geometry();
+        self
+    }
+
+    /// Add bounding-box geometry for custom intersection functions.
+    pub fn bounding_box_geometry(
+        mut self,
+        geometry: AccelerationStructureBoundingBoxGeometryDescriptor,
+    ) -> Self {
+        self.geometry.push(Geometry::BoundingBoxes(geometry));
+        self.sync_geometry();
+        self
+    }
+
+    /// Set the usage flags. Include [`AccelerationStructureUsage::REFIT`]
+    /// to allow [`refit`](Self::refit).
+    pub fn usage(self, usage: AccelerationStructureUsage) -> Self {
+        self.descriptor.set_usage(usage);
+        self
+    }
+
+    /// Set whether [`build`](Self::build) copies the result into a
+    /// compacted structure.
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Get the descriptor, for settings without a builder method.
+    pub fn descriptor(&self) -> &PrimitiveAccelerationStructureDescriptor {
+        &self.descriptor
+    }
+
+    /// Get the number of geometry descriptors added.
+    pub fn geometry_count(&self) -> usize {
+        self.geometry.len()
+    }
+
+    /// Query the structure and scratch sizes `device` needs for this
+    /// geometry.
+    pub fn sizes(&self, device: &Device) -> AccelerationStructureSizes {
+        unsafe { device.acceleration_structure_sizes_with_descriptor(self.descriptor.as_ptr()) }
+    }
+
+    /// Build the acceleration structure on `queue` and wait for it.
+    ///
+    /// With compaction enabled, a second command buffer copies the result
+    /// into a structure of the compacted size once the first completes.
+    pub fn build(
+        &self,
+        queue: &CommandQueue,
+    ) -> Result<AccelerationStructure, AccelerationStructureBuildError> {
+        let device = self.check_device(queue)?;
+        let sizes = self.sizes(&device);
+        let structure = device
+            .new_acceleration_structure_with_size(sizes.acceleration_structure_size)
+            .ok_or(AccelerationStructureBuildError::Allocation)?;
+        let scratch = scratch_buffer(&device, sizes.build_scratch_buffer_size)?;
+        let descriptor = self.base_descriptor();
+
+        let compacted_size = if self.compact {
+            Some(
+                device
+                    .new_buffer(size_of::<u32>(), ResourceOptions::STORAGE_MODE_SHARED)
+                    .ok_or(AccelerationStructureBuildError::Allocation)?,
+            )
+        } else {
+            None
+        };
+
+        submit(queue, |encoder| {
+            encoder.build_acceleration_structure(&structure, &descriptor, &scratch, 0);
+            if let Some(compacted_size) = &compacted_size {
+                encoder.write_compacted_acceleration_structure_size(&structure, compacted_size, 0);
+            }
+        })?;
+
+        let Some(compacted_size) = compacted_size else {
+            return Ok(structure);
+        };
+        let size = unsafe { *(compacted_size.contents().unwrap() as *const u32) } as UInteger;
+        let compacted = device
+            .new_acceleration_structure_with_size(size)
+            .ok_or(AccelerationStructureBuildError::Allocation)?;
+        submit(queue, |encoder| {
+            encoder.copy_and_compact_acceleration_structure(&structure, &compacted);
+        })?;
+        Ok(compacted)
+    }
+
+    /// Refit `structure` in place after its geometry moved, and wait for it.
+    ///
+    /// The vertex or bounding-box buffers may change contents but not
+    /// primitive counts. `structure` must have been built from this
+    /// builder with [`AccelerationStructureUsage::REFIT`].
+    pub fn refit(
+        &self,
+        queue: &CommandQueue,
+        structure: &AccelerationStructure,
+    ) -> Result<(), AccelerationStructureBuildError> {
+        let usage = self.descriptor.usage();
+        if (usage & AccelerationStructureUsage::REFIT).bits() == 0 {
+            return Err(AccelerationStructureBuildError::NotRefittable);
+        }
+        let device = self.check_device(queue)?;
+        let scratch = scratch_buffer(&device, self.sizes(&device).refit_scratch_buffer_size)?;
+        let descriptor = self.base_descriptor();
+        submit(queue, |encoder| {
+            encoder.refit_acceleration_structure(structure, &descriptor, structure, &scratch, 0);
+        })
+    }
+
+    fn check_device(
+        &self,
+        queue: &CommandQueue,
+    ) -> Result<Device, AccelerationStructureBuildError> {
+        if self.geometry.is_empty() {
+            return Err(AccelerationStructureBuildError::NoGeometry);
+        }
+        let device = queue.device();
+        if !device.supports_raytracing() {
+            return Err(AccelerationStructureBuildError::Unsupported);
+        }
+        Ok(device)
+    }
+
+    /// Point the descriptor's geometry array at `self.geometry`.
+    fn sync_geometry(&self) {
+        let objects: Vec<*const c_void> = self.geometry.iter().map(Geometry::as_ptr).collect();
+        // The array retains each descriptor and the descriptor retains the array.
+        if let Some(array) =
+            mtl_foundation::Array::<c_void>::array_with_objects(objects.as_ptr(), objects.len())
+        {
+            unsafe { self.descriptor.set_geometry_descriptors_ptr(array.as_ptr()) };
+        }
+    }
+
+    /// View the primitive descriptor as the base descriptor the encoder takes.
+    fn base_descriptor(&self) -> AccelerationStructureDescriptor {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.descriptor.as_ptr(), sel!(retain));
+            AccelerationStructureDescriptor::from_raw(ptr)
+                .expect("failed to retain acceleration structure descriptor")
+        }
+    }
+}
+
+impl fmt::Debug for AccelerationStructureBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccelerationStructureBuilder")
+            .field("geometry_count", &self.geometry.len())
+            .field("usage", &self.descriptor.usage())
+            .field("compact", &self.compact)
+            .finish()
+    }
+}
+
+fn scratch_buffer(
+    device: &Device,
+    size: UInteger,
+) -> Result<Buffer, AccelerationStructureBuildError> {
+    device
+        .new_buffer(size.max(1), ResourceOptions::STORAGE_MODE_PRIVATE)
+        .ok_or(AccelerationStructureBuildError::Allocation)
+}
+
+/// Encode with `f` on a new command buffer, commit it and wait.
+fn submit<F>(queue: &CommandQueue, f: F) -> Result<(), AccelerationStructureBuildError>
+where
+    F: FnOnce(&AccelerationStructureCommandEncoder),
+{
+    let command_buffer: CommandBuffer = queue
+        .command_buffer()
+        .ok_or(AccelerationStructureBuildError::Allocation)?;
+    let encoder = unsafe {
+        AccelerationStructureCommandEncoder::from_raw(
+            command_buffer.acceleration_structure_command_encoder(),
+        )
+    }
+    .ok_or(AccelerationStructureBuildError::Allocation)?;
+    f(&encoder);
+    encoder.end_encoding();
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+    if command_buffer.status() == CommandBufferStatus::ERROR {
+        return Err(AccelerationStructureBuildError::CommandBufferFailed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::system_default;
+
+    #[test]
+    fn test_build_without_geometry_fails() {
+        let device = system_default().expect("no Metal device");
+        let queue = device.new_command_queue().expect("no command queue");
+        let builder = AccelerationStructureBuilder::new().expect("failed to create builder");
+        assert_eq!(
+            builder.build(&queue).err(),
+            Some(AccelerationStructureBuildError::NoGeometry)
+        );
+    }
+
+    #[test]
+    fn test_build_compacted_triangle() {
+        let device = system_default().expect("no Metal device");
+
+        if !device.supports_raytracing() {
+            println!("Skipping test - device does not support ray tracing");
+            return;
+        }
+
+        let vertices: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let bytes = unsafe {
+            std::slice::from_raw_parts(vertices.as_ptr() as *const u8, size_of_val(&vertices))
+        };
+        let vertex_buffer = device
+            .new_buffer_with_bytes(bytes, ResourceOptions::STORAGE_MODE_SHARED)
+            .expect("failed to create vertex buffer");
+
+        let triangles = AccelerationStructureTriangleGeometryDescriptor::new()
+            .expect("failed to create geometry");
+        triangles.set_vertex_buffer(Some(&vertex_buffer));
+        triangles.set_vertex_stride(3 * size_of::<f32>());
+        triangles.set_triangle_count(1);
+
+        let builder = AccelerationStructureBuilder::new()
+            .expect("failed to create builder")
+            .triangle_geometry(triangles)
+            .usage(AccelerationStructureUsage::REFIT);
+        assert_eq!(builder.geometry_count(), 1);
+
+        let queue = device.new_command_queue().expect("no command queue");
+        let structure = builder.build(&queue).expect("build failed");
+        assert!(structure.size() > 0);
+        assert!(structure.size() <= builder.sizes(&device).acceleration_structure_size);
+        builder.refit(&queue, &structure).expect("refit failed");
+    }
+}
@@ -71,6 +71,11 @@ impl AccelerationStructureCommandEncoder {
     /// C++ equivalent: `void endEncoding()`
     #[inline]
     pub fn end_encoding(&self) {
+        #[cfg(feature = "validation")]
+        crate::validation::end_encoding(
+            self.as_ptr(),
+            "AccelerationStructureCommandEncoder::end_encoding",
+        );
         unsafe {
             msg_send_0::<()>(self.as_ptr(), sel!(endEncoding));
         }
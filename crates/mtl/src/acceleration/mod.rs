@@ -5,6 +5,7 @@
 //!
 //! Corresponds to `Metal/MTLAccelerationStructure.hpp` and related headers.
 
+mod builder;
 mod curve;
 mod descriptors;
 mod encoder;
@@ -16,6 +17,7 @@ mod pass;
 mod structure;
 
 // Re-export all public types
+pub use builder::{AccelerationStructureBuildError, AccelerationStructureBuilder};
 pub use curve::AccelerationStructureCurveGeometryDescriptor;
 pub use descriptors::{AccelerationStructureDescriptor, PrimitiveAccelerationStructureDescriptor};
 pub use encoder::AccelerationStructureCommandEncoder;
@@ -4,10 +4,14 @@
 //!
 //! This module defines the Allocation trait which corresponds to the
 //! `MTL::Allocation` protocol in metal-cpp. Resource types like Buffer,
-//! Texture, and Heap already have `allocated_size()` as an inherent method,
-//! so this trait serves primarily for documentation and generic programming.
+//! Texture, and Heap already have `allocated_size()` as an inherent method;
+//! the trait is implemented for them so they can be handled generically, as
+//! [`ResidencySetBuilder`](crate::ResidencySetBuilder) does.
 
-use mtl_foundation::UInteger;
+use mtl_foundation::{Referencing, UInteger};
+use mtl_sys::{msg_send_0, sel};
+
+use crate::{Buffer, Heap, Texture};
 
 /// Protocol for types that track their allocated memory size.
 ///
@@ -16,7 +20,7 @@ use mtl_foundation::UInteger;
 /// This trait corresponds to the Objective-C protocol that all Metal resource
 /// types conform to. In Rust, concrete types like [`Buffer`](crate::Buffer),
 /// [`Texture`](crate::Texture), and [`Heap`](crate::Heap) implement this
-/// method directly.
+/// trait.
 ///
 /// # Example
 ///
@@ -34,6 +38,27 @@ pub trait Allocation {
     fn allocated_size(&self) -> UInteger;
 }
 
+impl Allocation for Buffer {
+    #[inline]
+    fn allocated_size(&self) -> UInteger {
+        Buffer::allocated_size(self)
+    }
+}
+
+impl Allocation for Texture {
+    #[inline]
+    fn allocated_size(&self) -> UInteger {
+        Texture::allocated_size(self)
+    }
+}
+
+impl Allocation for Heap {
+    #[inline]
+    fn allocated_size(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(allocatedSize)) }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
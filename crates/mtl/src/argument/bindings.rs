@@ -0,0 +1,173 @@
+//! Typed iteration over reflected bindings.
+
+use std::ffi::c_void;
+use std::ptr::NonNull;
+
+use mtl_foundation::UInteger;
+use mtl_sys::{msg_send_0, msg_send_1, sel};
+
+use super::{
+    Binding, BufferBinding, ObjectPayloadBinding, TensorBinding, TextureBinding, ThreadgroupBinding,
+};
+use crate::enums::BindingType;
+
+// ============================================================================
+// BindingKind
+// ============================================================================
+
+/// A binding viewed as its concrete type.
+///
+/// Returned by [`Binding::kind`].
+#[derive(Clone)]
+pub enum BindingKind {
+    /// A buffer, with its data type, size and alignment.
+    Buffer(BufferBinding),
+    /// A texture, with its texture type and data type.
+    Texture(TextureBinding),
+    /// A sampler state.
+    Sampler(Binding),
+    /// A threadgroup memory allocation.
+    ThreadgroupMemory(ThreadgroupBinding),
+    /// A mesh shader object payload.
+    ObjectPayload(ObjectPayloadBinding),
+    /// A tensor.
+    Tensor(TensorBinding),
+    /// Any other binding (imageblocks, function tables, acceleration
+    /// structures).
+    Other(Binding),
+}
+
+impl std::fmt::Debug for BindingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (variant, name, index) = match self {
+            BindingKind::Buffer(b) => ("Buffer", b.name(), b.index()),
+            BindingKind::Texture(b) => ("Texture", b.name(), b.index()),
+            BindingKind::Sampler(b) => ("Sampler", b.name(), b.index()),
+            BindingKind::ThreadgroupMemory(b) => ("ThreadgroupMemory", b.name(), b.index()),
+            BindingKind::ObjectPayload(b) => ("ObjectPayload", b.name(), b.index()),
+            BindingKind::Tensor(b) => ("Tensor", b.name(), b.index()),
+            BindingKind::Other(b) => ("Other", b.name(), b.index()),
+        };
+        f.debug_struct(variant)
+            .field("name", &name)
+            .field("index", &index)
+            .finish()
+    }
+}
+
+impl Binding {
+    /// Get the binding as its concrete type, based on
+    /// [`binding_type`](Self::binding_type).
+    pub fn kind(&self) -> BindingKind {
+        let retained = || unsafe {
+            let ptr: *mut c_void = msg_send_0(self.as_raw(), sel!(retain));
+            NonNull::new_unchecked(ptr)
+        };
+        match self.binding_type() {
+            BindingType::BUFFER => BindingKind::Buffer(BufferBinding(retained())),
+            BindingType::TEXTURE => BindingKind::Texture(TextureBinding(retained())),
+            BindingType::SAMPLER => BindingKind::Sampler(self.clone()),
+            BindingType::THREADGROUP_MEMORY => {
+                BindingKind::ThreadgroupMemory(ThreadgroupBinding(retained()))
+            }
+            BindingType::OBJECT_PAYLOAD => {
+                BindingKind::ObjectPayload(ObjectPayloadBinding(retained()))
+            }
+            BindingType::TENSOR => BindingKind::Tensor(TensorBinding(retained())),
+            _ => BindingKind::Other(self.clone()),
+        }
+    }
+}
+
+// ============================================================================
+// Bindings
+// ============================================================================
+
+/// An iterator over the bindings of a reflected function or pipeline stage.
+///
+/// Holds a reference to the underlying `NS::Array<MTL::Binding>`.
+pub struct Bindings {
+    array: Option<NonNull<c_void>>,
+    index: UInteger,
+    count: UInteger,
+}
+
+impl Bindings {
+    /// Create an iterator over an `NS::Array` of bindings.
+    ///
+    /// # Safety
+    ///
+    /// `array` must be null or a valid `NS::Array` whose elements are
+    /// `MTL::Binding` objects. It is retained, not consumed.
+    pub(crate) unsafe fn from_array(array: *mut c_void) -> Self {
+        let array = NonNull::new(array);
+        let count = match array {
+            Some(array) => unsafe {
+                let _: *mut c_void = msg_send_0(array.as_ptr(), sel!(retain));
+                msg_send_0(array.as_ptr(), sel!(count))
+            },
+            None => 0,
+        };
+        Self {
+            array,
+            index: 0,
+            count,
+        }
+    }
+}
+
+impl Iterator for Bindings {
+    type Item = Binding;
+
+    fn next(&mut self) -> Option<Binding> {
+        let array = self.array?;
+        while self.index < self.count {
+            let index = self.index;
+            self.index += 1;
+            unsafe {
+                let ptr: *mut c_void = msg_send_1(array.as_ptr(), sel!(objectAtIndex:), index);
+                if ptr.is_null() {
+                    continue;
+                }
+                let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+                return Binding::from_raw(ptr);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.count - self.index))
+    }
+}
+
+impl Clone for Bindings {
+    fn clone(&self) -> Self {
+        let mut bindings =
+            unsafe { Self::from_array(self.array.map_or(std::ptr::null_mut(), NonNull::as_ptr)) };
+        bindings.index = self.index;
+        bindings
+    }
+}
+
+impl Drop for Bindings {
+    fn drop(&mut self) {
+        if let Some(array) = self.array {
+            unsafe {
+                msg_send_0::<()>(array.as_ptr(), sel!(release));
+            }
+        }
+    }
+}
+
+unsafe impl Send for Bindings {}
+unsafe impl Sync for Bindings {}
+
+impl std::fmt::Debug for Bindings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Bindings")
+            .field("index", &self.index)
+            .field("count", &self.count)
+            .finish()
+    }
+}
@@ -7,6 +7,7 @@
 mod argument;
 mod array_type;
 mod binding;
+mod bindings;
 mod buffer_binding;
 mod encoder;
 mod object_payload_binding;
@@ -23,6 +24,7 @@ mod type_info;
 pub use argument::Argument;
 pub use array_type::ArrayType;
 pub use binding::Binding;
+pub use bindings::{BindingKind, Bindings};
 pub use buffer_binding::BufferBinding;
 pub use encoder::ArgumentEncoder;
 pub use object_payload_binding::ObjectPayloadBinding;
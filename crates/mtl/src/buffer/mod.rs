@@ -4,6 +4,10 @@
 //!
 //! Buffers store data that can be accessed by shaders. They are the most basic
 //! type of Metal resource.
+//!
+//! [`BufferView`] and [`BufferSlice`] describe byte ranges of a buffer.
+//! [`BufferView::read_into`] and [`Buffer::read_into_staged`] read ranges
+//! back into caller-provided slices.
 
 use std::ffi::c_void;
 use std::ptr::NonNull;
@@ -14,6 +18,13 @@ use mtl_sys::{msg_send_0, msg_send_1, sel};
 use crate::enums::{BufferSparseTier, ResourceOptions};
 use crate::types::ResourceID;
 
+mod readback;
+mod view;
+
+pub use readback::BufferReadbackError;
+pub(crate) use view::sealed;
+pub use view::{BufferSlice, BufferView};
+
 /// A buffer resource that stores data for shader access.
 ///
 /// C++ equivalent: `MTL::Buffer`
@@ -163,7 +174,10 @@ impl Buffer {
         }
     }
 
-    /// Get a remote storage buffer (for cross-process sharing).
+    /// Get the buffer on another device that this remote view refers to.
+    ///
+    /// Returns `None` unless the buffer was created with
+    /// [`new_remote_buffer_view_for_device`](Self::new_remote_buffer_view_for_device).
     ///
     /// C++ equivalent: `Buffer* remoteStorageBuffer() const`
     #[inline]
@@ -179,7 +193,24 @@ impl Buffer {
         }
     }
 
-    /// Create a remote buffer for a remote device.
+    /// Create a view of this buffer for a peer device.
+    ///
+    /// The view lets `device` read and write this buffer's memory directly,
+    /// such as in blits that move data between GPUs without a round trip
+    /// through system memory. The buffer must use private storage and
+    /// `device` must be in the same peer group; see
+    /// [`Device::is_peer_of`](crate::Device::is_peer_of). Returns `None`
+    /// otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let group = device.peer_group().expect("no peers");
+    /// for peer in group.devices().iter().filter(|peer| device.is_peer_of(peer)) {
+    ///     let remote = buffer.new_remote_buffer_view_for_device(peer).unwrap();
+    ///     peer_blit.copy_from_buffer_to_buffer(&remote, 0, &local, 0, remote.length());
+    /// }
+    /// ```
     ///
     /// C++ equivalent: `Buffer* newRemoteBufferViewForDevice(Device*)`
     pub fn new_remote_buffer_view_for_device(&self, device: &crate::Device) -> Option<Buffer> {
@@ -0,0 +1,179 @@
+//! Blit-based buffer readback.
+//!
+//! Private buffers can't be read from the CPU, and managed buffers need a
+//! synchronizing blit first. The helpers here encode a blit of a byte range
+//! into a CPU-visible staging buffer, wait for the GPU, and copy the bytes
+//! into a caller-provided slice, so small frequent readbacks such as query
+//! results don't allocate a `Vec` each time.
+
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use super::{Buffer, BufferView};
+use crate::enums::{CommandBufferStatus, ResourceOptions};
+use crate::gpu_vec::Pod;
+use crate::{BlitCommandEncoder, CommandQueue};
+
+/// Error produced by a staged buffer readback.
+#[derive(Debug)]
+pub enum BufferReadbackError {
+    /// The range lies outside the source buffer or the staging buffer is
+    /// too small.
+    OutOfRange,
+    /// The staging buffer is not CPU-accessible.
+    NotCpuAccessible,
+    /// A command buffer, encoder, or staging buffer could not be created.
+    Allocation,
+    /// The command buffer failed on the GPU.
+    CommandBuffer(Option<mtl_foundation::Error>),
+}
+
+impl fmt::Display for BufferReadbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BufferReadbackError::OutOfRange => write!(f, "readback range is outside the buffer"),
+            BufferReadbackError::NotCpuAccessible => {
+                write!(f, "staging buffer is not CPU-accessible")
+            }
+            BufferReadbackError::Allocation => {
+                write!(f, "failed to allocate readback resources")
+            }
+            BufferReadbackError::CommandBuffer(Some(err)) => {
+                write!(f, "readback command buffer failed: {}", err)
+            }
+            BufferReadbackError::CommandBuffer(None) => {
+                write!(f, "readback command buffer failed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BufferReadbackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BufferReadbackError::CommandBuffer(Some(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl Buffer {
+    /// Read elements at byte `offset` into `dst` through a staging buffer.
+    ///
+    /// Encodes a blit into a new CPU-visible staging buffer on `queue`,
+    /// commits it, blocks until the GPU finishes, and fills all of `dst`.
+    /// Works for every storage mode, including private buffers. To also
+    /// avoid the staging allocation, keep a staging buffer and use
+    /// [`read_into_with_staging`](Self::read_into_with_staging).
+    pub fn read_into_staged<T: Pod>(
+        &self,
+        queue: &CommandQueue,
+        offset: UInteger,
+        dst: &mut [T],
+    ) -> Result<(), BufferReadbackError> {
+        let len = std::mem::size_of_val(dst);
+        if len == 0 {
+            return Ok(());
+        }
+        let options = if self.device().has_unified_memory() {
+            ResourceOptions::STORAGE_MODE_SHARED
+        } else {
+            ResourceOptions::STORAGE_MODE_MANAGED
+        };
+        let staging = self
+            .device()
+            .new_buffer(len, options)
+            .ok_or(BufferReadbackError::Allocation)?;
+        self.read_into_with_staging(queue, &staging, offset, dst)
+    }
+
+    /// Read elements at byte `offset` into `dst` through `staging`.
+    ///
+    /// Like [`read_into_staged`](Self::read_into_staged), but blits into the
+    /// start of `staging`, which must be shared or managed and at least as
+    /// large as `dst`. The caller must make sure no other work is using
+    /// `staging`.
+    pub fn read_into_with_staging<T: Pod>(
+        &self,
+        queue: &CommandQueue,
+        staging: &impl BufferView,
+        offset: UInteger,
+        dst: &mut [T],
+    ) -> Result<(), BufferReadbackError> {
+        let len = std::mem::size_of_val(dst);
+        let in_range = offset
+            .checked_add(len)
+            .is_some_and(|end| end <= self.length());
+        if !in_range || len > staging.length() {
+            return Err(BufferReadbackError::OutOfRange);
+        }
+        if len == 0 {
+            return Ok(());
+        }
+        if staging.contents().is_none() {
+            return Err(BufferReadbackError::NotCpuAccessible);
+        }
+
+        let command_buffer = queue
+            .command_buffer()
+            .ok_or(BufferReadbackError::Allocation)?;
+        let encoder =
+            unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+                .ok_or(BufferReadbackError::Allocation)?;
+        encoder.copy_from_buffer_to_buffer(self, offset, staging.buffer(), staging.offset(), len);
+        let storage = staging.buffer().resource_options().bits() & (0xf << 4);
+        if storage == ResourceOptions::STORAGE_MODE_MANAGED.bits() {
+            encoder.synchronize_buffer(staging.buffer());
+        }
+        encoder.end_encoding();
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+
+        if command_buffer.status() == CommandBufferStatus::ERROR {
+            return Err(BufferReadbackError::CommandBuffer(command_buffer.error()));
+        }
+        if !staging.read_into(0, dst) {
+            return Err(BufferReadbackError::NotCpuAccessible);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_into_staged_private_buffer() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().unwrap();
+        let source = device
+            .new_buffer(64, ResourceOptions::STORAGE_MODE_SHARED)
+            .unwrap();
+        let values: Vec<u32> = (0..16).collect();
+        assert!(source.write_bytes(0, unsafe {
+            std::slice::from_raw_parts(values.as_ptr() as *const u8, 64)
+        }));
+
+        let private = device
+            .new_buffer(64, ResourceOptions::STORAGE_MODE_PRIVATE)
+            .unwrap();
+        let command_buffer = queue.command_buffer().unwrap();
+        let encoder =
+            unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }.unwrap();
+        encoder.copy_from_buffer_to_buffer(&source, 0, &private, 0, 64);
+        encoder.end_encoding();
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+
+        let mut dst = [0u32; 4];
+        private.read_into_staged(&queue, 8, &mut dst).unwrap();
+        assert_eq!(dst, [2, 3, 4, 5]);
+        assert!(!private.read_into(0, &mut dst));
+        assert!(matches!(
+            private.read_into_staged(&queue, 56, &mut dst),
+            Err(BufferReadbackError::OutOfRange)
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+//! Byte ranges of buffers.
+//!
+//! [`BufferView`] is implemented by [`Buffer`], [`BufferSlice`] and other
+//! buffer-backed types such as [`GpuVec`](crate::GpuVec), so uploads,
+//! bindings and readbacks can be written once for all of them. Offsets
+//! passed to the trait's methods are relative to the start of the view.
+//!
+//! The trait is sealed: its provided methods read and write through
+//! [`offset`](BufferView::offset) and [`length`](BufferView::length), so
+//! only the crate's own types, which keep those within the buffer, can
+//! implement it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! fn bind_vertices(encoder: &RenderCommandEncoder, vertices: &impl BufferView, index: UInteger) {
+//!     encoder.set_vertex_buffer(vertices.buffer(), vertices.offset(), index);
+//! }
+//!
+//! let (positions, normals) = mesh_buffer.split_at(position_bytes).unwrap();
+//! positions.write_bytes(0, &position_data);
+//! bind_vertices(&encoder, &positions, 0);
+//! bind_vertices(&encoder, &normals, 1);
+//! ```
+
+use std::ffi::c_void;
+use std::ops::Range;
+
+use mtl_foundation::UInteger;
+
+use super::Buffer;
+use crate::enums::ResourceOptions;
+use crate::gpu_vec::Pod;
+
+pub(crate) mod sealed {
+    /// Restricts [`BufferView`](super::BufferView) to this crate's types.
+    pub trait Sealed {}
+}
+
+/// A contiguous byte range of a [`Buffer`].
+///
+/// This trait is sealed and can't be implemented outside the crate.
+pub trait BufferView: sealed::Sealed {
+    /// Get the buffer holding the bytes.
+    fn buffer(&self) -> &Buffer;
+
+    /// Get the offset of the view's first byte in [`buffer`](Self::buffer).
+    fn offset(&self) -> UInteger;
+
+    /// Get the length of the view in bytes.
+    fn length(&self) -> UInteger;
+
+    /// Get the GPU address of the view's first byte.
+    fn gpu_address(&self) -> u64 {
+        self.buffer().gpu_address() + self.offset() as u64
+    }
+
+    /// Get a CPU pointer to the view's first byte.
+    ///
+    /// Returns `None` if the buffer is not CPU-accessible.
+    fn contents(&self) -> Option<*mut c_void> {
+        let contents = self.buffer().contents()?;
+        Some(unsafe { contents.byte_add(self.offset()) })
+    }
+
+    /// Notify Metal that the CPU modified `length` bytes at `location` in a
+    /// managed buffer.
+    fn did_modify_range(&self, location: UInteger, length: UInteger) {
+        self.buffer()
+            .did_modify_range(self.offset() + location, length);
+    }
+
+    /// Get the sub-range `range` of the view.
+    ///
+    /// Returns `None` if `range` is not within the view.
+    fn slice(&self, range: Range<UInteger>) -> Option<BufferSlice<'_>> {
+        if range.start > range.end || range.end > self.length() {
+            return None;
+        }
+        Some(BufferSlice {
+            buffer: self.buffer(),
+            offset: self.offset() + range.start,
+            length: range.end - range.start,
+        })
+    }
+
+    /// Split the view into the bytes before and after `mid`.
+    fn split_at(&self, mid: UInteger) -> Option<(BufferSlice<'_>, BufferSlice<'_>)> {
+        Some((self.slice(0..mid)?, self.slice(mid..self.length())?))
+    }
+
+    /// Copy `bytes` into the view at `offset`, notifying Metal if the
+    /// buffer is managed.
+    ///
+    /// Returns `false` without writing if the buffer is not CPU-accessible
+    /// or the bytes don't fit.
+    ///
+    /// The caller must make sure the GPU is not accessing the range.
+    fn write_bytes(&self, offset: UInteger, bytes: &[u8]) -> bool {
+        let fits = offset
+            .checked_add(bytes.len())
+            .is_some_and(|end| end <= self.length());
+        let Some(contents) = self.contents().filter(|_| fits) else {
+            return false;
+        };
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                (contents as *mut u8).add(offset),
+                bytes.len(),
+            );
+        }
+        let storage = self.buffer().resource_options().bits() & (0xf << 4);
+        if storage == ResourceOptions::STORAGE_MODE_MANAGED.bits() && !bytes.is_empty() {
+            self.did_modify_range(offset, bytes.len());
+        }
+        true
+    }
+
+    /// Copy the view's bytes into a vector.
+    ///
+    /// Returns `None` if the buffer is not CPU-accessible. The caller must
+    /// make sure the GPU has finished writing the range, and for managed
+    /// buffers that it was synchronized with a blit.
+    fn read_bytes(&self) -> Option<Vec<u8>> {
+        let contents = self.contents()?;
+        let bytes = unsafe { std::slice::from_raw_parts(contents as *const u8, self.length()) };
+        Some(bytes.to_vec())
+    }
+
+    /// Copy elements from the view at byte `offset` into `dst`, without
+    /// allocating.
+    ///
+    /// Fills all of `dst`, reading `dst.len() * size_of::<T>()` bytes; pass
+    /// a `&mut [u8]` to read raw bytes. `offset` need not be aligned for
+    /// `T`. Returns `false` without reading if the buffer is not
+    /// CPU-accessible or the range doesn't fit in the view. As with
+    /// [`read_bytes`](Self::read_bytes), the caller must make sure the GPU
+    /// has finished writing the range. Private buffers can be read with
+    /// [`Buffer::read_into_staged`].
+    fn read_into<T: Pod>(&self, offset: UInteger, dst: &mut [T]) -> bool
+    where
+        Self: Sized,
+    {
+        let len = std::mem::size_of_val(dst);
+        let fits = offset
+            .checked_add(len)
+            .is_some_and(|end| end <= self.length());
+        let Some(contents) = self.contents().filter(|_| fits) else {
+            return false;
+        };
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                (contents as *const u8).add(offset),
+                dst.as_mut_ptr() as *mut u8,
+                len,
+            );
+        }
+        true
+    }
+}
+
+impl sealed::Sealed for Buffer {}
+
+impl BufferView for Buffer {
+    fn buffer(&self) -> &Buffer {
+        self
+    }
+
+    fn offset(&self) -> UInteger {
+        0
+    }
+
+    fn length(&self) -> UInteger {
+        Buffer::length(self)
+    }
+}
+
+// ============================================================================
+// BufferSlice
+// ============================================================================
+
+/// A borrowed byte range of a [`Buffer`].
+///
+/// Create one with [`BufferSlice::new`] or [`BufferView::slice`].
+#[derive(Copy, Clone, Debug)]
+pub struct BufferSlice<'a> {
+    buffer: &'a Buffer,
+    offset: UInteger,
+    length: UInteger,
+}
+
+impl<'a> BufferSlice<'a> {
+    /// Create a view of `range` in `buffer`.
+    ///
+    /// Returns `None` if `range` is not within the buffer.
+    pub fn new(buffer: &'a Buffer, range: Range<UInteger>) -> Option<Self> {
+        if range.start > range.end || range.end > buffer.length() {
+            return None;
+        }
+        Some(Self {
+            buffer,
+            offset: range.start,
+            length: range.end - range.start,
+        })
+    }
+
+    /// Get the byte range of the slice in its buffer.
+    pub fn range(&self) -> Range<UInteger> {
+        self.offset..self.offset + self.length
+    }
+}
+
+impl sealed::Sealed for BufferSlice<'_> {}
+
+impl BufferView for BufferSlice<'_> {
+    fn buffer(&self) -> &Buffer {
+        self.buffer
+    }
+
+    fn offset(&self) -> UInteger {
+        self.offset
+    }
+
+    fn length(&self) -> UInteger {
+        self.length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_slices() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let buffer = device
+            .new_buffer(64, ResourceOptions::STORAGE_MODE_SHARED)
+            .unwrap();
+
+        let (head, tail) = buffer.split_at(16).unwrap();
+        assert_eq!((head.range(), tail.range()), (0..16, 16..64));
+        assert_eq!(tail.gpu_address(), buffer.gpu_address() + 16);
+        assert!(buffer.slice(60..65).is_none());
+        assert!(tail.slice(40..50).is_none());
+        assert!(BufferSlice::new(&buffer, 0..64).is_some());
+
+        let inner = tail.slice(8..12).unwrap();
+        assert_eq!(inner.range(), 24..28);
+        assert!(inner.write_bytes(0, &[1, 2, 3, 4]));
+        assert!(!inner.write_bytes(2, &[1, 2, 3]));
+
+        let bytes = buffer.read_bytes().unwrap();
+        assert_eq!(&bytes[24..28], &[1, 2, 3, 4]);
+        assert_eq!(inner.read_bytes().unwrap(), [1, 2, 3, 4]);
+
+        let mut bytes = [0u8; 3];
+        assert!(inner.read_into(1, &mut bytes));
+        assert_eq!(bytes, [2, 3, 4]);
+        let mut words = [0u32; 2];
+        assert!(buffer.read_into(21, &mut words));
+        assert_eq!(words[0].to_ne_bytes(), [0, 0, 0, 1]);
+        assert!(!inner.read_into(2, &mut words));
+    }
+}
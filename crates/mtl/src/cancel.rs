@@ -0,0 +1,221 @@
+//! Cancellation of asynchronous GPU work.
+//!
+//! A [`CancellationToken`] is shared by the async operations started for
+//! one scene, level or app lifetime. Cancelling it cancels IO loads that
+//! have not started (through `MTLIOCommandBuffer tryCancel`), resolves
+//! pending upload futures with a cancellation error, and skips compile
+//! completion handlers, so shutdown and scene switches don't wait on work
+//! whose results would be thrown away. Committed GPU command buffers still
+//! run to completion; only the work waiting on them is dropped.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let scene = CancellationToken::new();
+//!
+//! let load = loader.batch()?.buffer(&vertices, 0, size, &file, 0).commit();
+//! let load = load.with_cancellation(&scene);
+//! let upload = texture
+//!     .upload_from_bytes_async(&queue, &pixels, width * 4, options)?
+//!     .with_cancellation(&scene);
+//! device.new_render_pipeline_state_async(&descriptor, scene.guard(|state, error| {
+//!     // Not called once the scene is cancelled.
+//! }));
+//!
+//! // On scene switch:
+//! scene.cancel();
+//! ```
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+type Handler = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct Handlers {
+    next_id: u64,
+    handlers: Vec<(u64, Handler)>,
+}
+
+#[derive(Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    handlers: Mutex<Handlers>,
+}
+
+// ============================================================================
+// CancellationToken
+// ============================================================================
+
+/// A shared flag that cancels the async operations it was passed to.
+///
+/// Clones share the same state; cancelling any clone cancels them all.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<TokenState>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the token and run its cancellation handlers.
+    ///
+    /// Handlers run on the calling thread. Cancelling twice does nothing.
+    pub fn cancel(&self) {
+        if self.state.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let handlers = std::mem::take(&mut self.state.handlers.lock().unwrap().handlers);
+        for (_, handler) in handlers {
+            handler();
+        }
+    }
+
+    /// Check if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Run `handler` when the token is cancelled, or now if it already is.
+    ///
+    /// The handler is unregistered when the returned registration is
+    /// dropped.
+    pub fn on_cancel(&self, handler: impl FnOnce() + Send + 'static) -> CancelRegistration {
+        let mut handlers = self.state.handlers.lock().unwrap();
+        if self.is_cancelled() {
+            drop(handlers);
+            handler();
+            return CancelRegistration { token: None, id: 0 };
+        }
+        let id = handlers.next_id;
+        handlers.next_id += 1;
+        handlers.handlers.push((id, Box::new(handler)));
+        CancelRegistration {
+            token: Some(Arc::clone(&self.state)),
+            id,
+        }
+    }
+
+    /// Wrap a completion handler so it is skipped once the token is
+    /// cancelled.
+    ///
+    /// Fits the `(result, error)` handlers of the async library and
+    /// pipeline creation methods; the results of a skipped call are
+    /// released.
+    pub fn guard<A, B, F>(&self, handler: F) -> impl Fn(A, B) + Send + 'static
+    where
+        F: Fn(A, B) + Send + 'static,
+    {
+        let token = self.clone();
+        move |a, b| {
+            if !token.is_cancelled() {
+                handler(a, b);
+            }
+        }
+    }
+
+    /// Wrap a three-argument completion handler, such as those of the
+    /// pipeline creation methods that return reflection, so it is skipped
+    /// once the token is cancelled.
+    pub fn guard3<A, B, C, F>(&self, handler: F) -> impl Fn(A, B, C) + Send + 'static
+    where
+        F: Fn(A, B, C) + Send + 'static,
+    {
+        let token = self.clone();
+        move |a, b, c| {
+            if !token.is_cancelled() {
+                handler(a, b, c);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// A handler registered with [`CancellationToken::on_cancel`].
+///
+/// Dropping it unregisters the handler.
+#[must_use = "dropping the registration unregisters the handler"]
+pub struct CancelRegistration {
+    token: Option<Arc<TokenState>>,
+    id: u64,
+}
+
+impl Drop for CancelRegistration {
+    fn drop(&mut self) {
+        if let Some(token) = &self.token {
+            token
+                .handlers
+                .lock()
+                .unwrap()
+                .handlers
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+impl fmt::Debug for CancelRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelRegistration")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_cancellation_handlers() {
+        let token = CancellationToken::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let counter = Arc::clone(&calls);
+        let kept = token.on_cancel(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let counter = Arc::clone(&calls);
+        drop(token.on_cancel(move || {
+            counter.fetch_add(10, Ordering::SeqCst);
+        }));
+
+        token.clone().cancel();
+        token.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let counter = Arc::clone(&calls);
+        let _late = token.on_cancel(move || {
+            counter.fetch_add(100, Ordering::SeqCst);
+        });
+        assert_eq!(calls.load(Ordering::SeqCst), 101);
+        drop(kept);
+    }
+
+    #[test]
+    fn test_guard_skips_after_cancel() {
+        let token = CancellationToken::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let handler = token.guard(move |a: usize, b: usize| {
+            counter.fetch_add(a + b, Ordering::SeqCst);
+        });
+
+        handler(1, 2);
+        token.cancel();
+        handler(10, 20);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
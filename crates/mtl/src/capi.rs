@@ -0,0 +1,1386 @@
+//! C API over a subset of the safe wrappers.
+//!
+//! Exposes devices, command queues, buffers, textures, compute and render
+//! pipelines, compute and render encoding, and drawable presentation as
+//! `extern "C"` functions, so C, C++ and Swift hosts can load a Rust plugin
+//! and drive the same validated layer as Rust callers. The functions are exported unmangled
+//! with an `mtl_` prefix; link them into a host by building a `cdylib` or
+//! `staticlib` crate that depends on `mtl-gpu` with the `capi` feature.
+//! The matching declarations are in `include/mtl_gpu.h`.
+//!
+//! # Conventions
+//!
+//! - Objects are opaque handles created by `mtl_*_create` functions and
+//!   destroyed by the matching `mtl_*_release`. Each handle owns one strong
+//!   reference to its Metal object; releasing `NULL` does nothing.
+//! - Fallible functions return an [`MtlStatus`] and write their result
+//!   through an out pointer, which is left untouched on failure.
+//! - When a function fails, a description of the failure, including
+//!   compiler output, can be read with [`mtl_last_error_message`] on the
+//!   same thread.
+//! - A panic, for example from a check of the `validation` feature, is
+//!   caught at the API boundary and reported as [`MtlStatus::Panicked`]
+//!   instead of unwinding into the host. Functions without a status return
+//!   their zero value. This needs the default `panic = "unwind"` strategy.
+//!
+//! # Example
+//!
+//! ```c
+//! MtlDevice *device;
+//! MtlComputePipeline *pipeline;
+//! if (mtl_device_create_system_default(&device) != MTL_STATUS_OK) return;
+//! if (mtl_compute_pipeline_create(device, source, "main0", &pipeline) != MTL_STATUS_OK) {
+//!     fprintf(stderr, "%s\n", mtl_last_error_message());
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::panic::AssertUnwindSafe;
+
+use mtl_foundation::{AutoreleasePool, Referencing, UInteger};
+use mtl_sys::{msg_send_0, sel};
+
+use crate::encoder::MAX_INLINE_BYTES;
+use crate::enums::{
+    CommandBufferStatus, LoadAction, PixelFormat, PrimitiveType, ResourceOptions, TextureUsage,
+};
+use crate::pass::RenderPassDescriptor;
+use crate::types::{ClearColor, Size};
+use crate::{
+    Buffer, CommandBuffer, CommandQueue, ComputeCommandEncoder, ComputePipelineState, Device,
+    RenderCommandEncoder, RenderPipelineDescriptor, RenderPipelineState, SourcePipelineError,
+    Texture, TextureDescriptor,
+};
+
+/// Handle to a [`Device`].
+pub type MtlDevice = Device;
+/// Handle to a [`CommandQueue`].
+pub type MtlCommandQueue = CommandQueue;
+/// Handle to a [`Buffer`].
+pub type MtlBuffer = Buffer;
+/// Handle to a [`Texture`].
+pub type MtlTexture = Texture;
+/// Handle to a [`ComputePipelineState`].
+pub type MtlComputePipeline = ComputePipelineState;
+/// Handle to a [`CommandBuffer`].
+pub type MtlCommandBuffer = CommandBuffer;
+/// Handle to a [`ComputeCommandEncoder`].
+pub type MtlComputeEncoder = ComputeCommandEncoder;
+/// Handle to a [`RenderPipelineState`].
+pub type MtlRenderPipeline = RenderPipelineState;
+/// Handle to a [`RenderCommandEncoder`].
+pub type MtlRenderEncoder = RenderCommandEncoder;
+
+// ============================================================================
+// Status codes
+// ============================================================================
+
+/// Result of a C API call.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MtlStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullArgument = 1,
+    /// An argument was out of range or a string wasn't valid UTF-8.
+    InvalidArgument = 2,
+    /// No Metal device is available.
+    NoDevice = 3,
+    /// Metal failed to create the object.
+    AllocationFailed = 4,
+    /// Shader source failed to compile.
+    CompileFailed = 5,
+    /// The shader library has no function with the requested name.
+    FunctionNotFound = 6,
+    /// Pipeline creation failed.
+    PipelineFailed = 7,
+    /// A command buffer completed with an error.
+    CommandBufferFailed = 8,
+    /// The call panicked; the message describes the panic.
+    Panicked = 9,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn fail(status: MtlStatus, message: impl ToString) -> MtlStatus {
+    let message = message.to_string().replace('\0', " ");
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+    status
+}
+
+/// Run `body`, returning `on_panic` and recording the message if it
+/// panics, so no unwind crosses into the host.
+///
+/// The body runs inside an autorelease pool: hosts may call in from threads
+/// that never drain one, and Metal autoreleases the objects it returns.
+fn guard_or<T>(on_panic: T, body: impl FnOnce() -> T) -> T {
+    match std::panic::catch_unwind(AssertUnwindSafe(|| AutoreleasePool::with(body))) {
+        Ok(value) => value,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic payload".to_string());
+            fail(MtlStatus::Panicked, format!("panic: {message}"));
+            on_panic
+        }
+    }
+}
+
+/// Run `body`, turning a panic into [`MtlStatus::Panicked`].
+fn guard(body: impl FnOnce() -> MtlStatus) -> MtlStatus {
+    guard_or(MtlStatus::Panicked, body)
+}
+
+/// Get a description of the last failed call on this thread, or `NULL` if
+/// none has failed.
+///
+/// The string stays valid until the next failing call on the thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn mtl_last_error_message() -> *const c_char {
+    guard_or(std::ptr::null(), || {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(std::ptr::null(), |message| message.as_ptr())
+        })
+    })
+}
+
+unsafe fn handle<'a, T>(ptr: *const T) -> Result<&'a T, MtlStatus> {
+    unsafe { ptr.as_ref() }.ok_or_else(|| fail(MtlStatus::NullArgument, "null handle"))
+}
+
+unsafe fn string<'a>(ptr: *const c_char) -> Result<&'a str, MtlStatus> {
+    if ptr.is_null() {
+        return Err(fail(MtlStatus::NullArgument, "null string"));
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(|err| fail(MtlStatus::InvalidArgument, err))
+}
+
+/// Check a buffer binding offset.
+fn check_offset(buffer: &Buffer, offset: UInteger) -> Result<(), MtlStatus> {
+    if offset >= buffer.length() {
+        return Err(fail(
+            MtlStatus::InvalidArgument,
+            format!("offset {} is past the end of the buffer", offset),
+        ));
+    }
+    Ok(())
+}
+
+/// Get the bytes passed to a `set_bytes` function, which Metal limits to
+/// [`MAX_INLINE_BYTES`].
+unsafe fn inline_bytes<'a>(bytes: *const c_void, length: usize) -> Result<&'a [u8], MtlStatus> {
+    if bytes.is_null() {
+        return Err(fail(MtlStatus::NullArgument, "null bytes"));
+    }
+    if length > MAX_INLINE_BYTES {
+        return Err(fail(
+            MtlStatus::InvalidArgument,
+            format!(
+                "{} bytes exceeds the {} byte setBytes limit; use a buffer",
+                length, MAX_INLINE_BYTES
+            ),
+        ));
+    }
+    Ok(unsafe { std::slice::from_raw_parts(bytes.cast::<u8>(), length) })
+}
+
+unsafe fn write_out<T>(out: *mut *mut T, value: T) -> MtlStatus {
+    if out.is_null() {
+        return fail(MtlStatus::NullArgument, "null out pointer");
+    }
+    unsafe { *out = Box::into_raw(Box::new(value)) };
+    MtlStatus::Ok
+}
+
+unsafe fn release<T>(ptr: *mut T) {
+    if !ptr.is_null() {
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+}
+
+macro_rules! try_status {
+    ($expr:expr) => {
+        match $expr {
+            Ok(value) => value,
+            Err(status) => return status,
+        }
+    };
+}
+
+// ============================================================================
+// Device
+// ============================================================================
+
+/// Create a handle to the system default device.
+///
+/// # Safety
+///
+/// `out` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_device_create_system_default(out: *mut *mut MtlDevice) -> MtlStatus {
+    guard(|| match crate::device::system_default() {
+        Some(device) => unsafe { write_out(out, device) },
+        None => fail(MtlStatus::NoDevice, "no Metal device available"),
+    })
+}
+
+/// Copy the device name into `buffer` as a NUL-terminated string,
+/// truncating to `capacity` bytes.
+///
+/// Returns the length of the full name in bytes, excluding the NUL.
+///
+/// # Safety
+///
+/// `device` must be a live handle and `buffer` valid for `capacity` bytes
+/// of writes, or null with `capacity` zero.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_device_name(
+    device: *const MtlDevice,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> usize {
+    guard_or(0, || {
+        let Some(device) = (unsafe { device.as_ref() }) else {
+            return 0;
+        };
+        let name = device.name().as_bytes();
+        if !buffer.is_null() && capacity > 0 {
+            let len = name.len().min(capacity - 1);
+            unsafe {
+                std::ptr::copy_nonoverlapping(name.as_ptr(), buffer.cast::<u8>(), len);
+                *buffer.add(len) = 0;
+            }
+        }
+        name.len()
+    })
+}
+
+/// Release a device handle.
+///
+/// # Safety
+///
+/// `device` must be null or a handle not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_device_release(device: *mut MtlDevice) {
+    guard_or((), || unsafe { release(device) })
+}
+
+// ============================================================================
+// Command queue
+// ============================================================================
+
+/// Create a command queue on `device`.
+///
+/// # Safety
+///
+/// `device` must be a live handle and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_command_queue_create(
+    device: *const MtlDevice,
+    out: *mut *mut MtlCommandQueue,
+) -> MtlStatus {
+    guard(|| {
+        let device = try_status!(unsafe { handle(device) });
+        match device.new_command_queue() {
+            Some(queue) => unsafe { write_out(out, queue) },
+            None => fail(
+                MtlStatus::AllocationFailed,
+                "failed to create command queue",
+            ),
+        }
+    })
+}
+
+/// Release a command queue handle.
+///
+/// # Safety
+///
+/// `queue` must be null or a handle not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_command_queue_release(queue: *mut MtlCommandQueue) {
+    guard_or((), || unsafe { release(queue) })
+}
+
+// ============================================================================
+// Buffer
+// ============================================================================
+
+/// Create a buffer of `length` bytes with `MTLResourceOptions` `options`.
+///
+/// # Safety
+///
+/// `device` must be a live handle and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_buffer_create(
+    device: *const MtlDevice,
+    length: UInteger,
+    options: UInteger,
+    out: *mut *mut MtlBuffer,
+) -> MtlStatus {
+    guard(|| {
+        let device = try_status!(unsafe { handle(device) });
+        if length == 0 {
+            return fail(MtlStatus::InvalidArgument, "buffer length must be non-zero");
+        }
+        match device.new_buffer(length, ResourceOptions(options)) {
+            Some(buffer) => unsafe { write_out(out, buffer) },
+            None => fail(
+                MtlStatus::AllocationFailed,
+                format!("failed to allocate a {} byte buffer", length),
+            ),
+        }
+    })
+}
+
+/// Get a pointer to the buffer's contents, or `NULL` for private buffers.
+///
+/// # Safety
+///
+/// `buffer` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_buffer_contents(buffer: *const MtlBuffer) -> *mut c_void {
+    guard_or(std::ptr::null_mut(), || {
+        unsafe { buffer.as_ref() }
+            .and_then(Buffer::contents)
+            .unwrap_or(std::ptr::null_mut())
+    })
+}
+
+/// Get the buffer's length in bytes.
+///
+/// # Safety
+///
+/// `buffer` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_buffer_length(buffer: *const MtlBuffer) -> UInteger {
+    guard_or(0, || unsafe { buffer.as_ref() }.map_or(0, Buffer::length))
+}
+
+/// Release a buffer handle.
+///
+/// # Safety
+///
+/// `buffer` must be null or a handle not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_buffer_release(buffer: *mut MtlBuffer) {
+    guard_or((), || unsafe { release(buffer) })
+}
+
+// ============================================================================
+// Texture
+// ============================================================================
+
+/// Create a 2D texture without mipmaps.
+///
+/// `pixel_format` is an `MTLPixelFormat` and `usage` an `MTLTextureUsage`.
+///
+/// # Safety
+///
+/// `device` must be a live handle and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_texture_create_2d(
+    device: *const MtlDevice,
+    pixel_format: UInteger,
+    width: UInteger,
+    height: UInteger,
+    usage: UInteger,
+    out: *mut *mut MtlTexture,
+) -> MtlStatus {
+    guard(|| {
+        let device = try_status!(unsafe { handle(device) });
+        let Some(descriptor) = TextureDescriptor::texture_2d_descriptor(
+            PixelFormat(pixel_format),
+            width,
+            height,
+            false,
+        ) else {
+            return fail(
+                MtlStatus::AllocationFailed,
+                "failed to create texture descriptor",
+            );
+        };
+        descriptor.set_usage(TextureUsage(usage));
+        match device.new_texture_with_descriptor(&descriptor) {
+            Ok(texture) => unsafe { write_out(out, texture) },
+            Err(err) => fail(MtlStatus::InvalidArgument, err),
+        }
+    })
+}
+
+/// Get the texture's width in pixels.
+///
+/// # Safety
+///
+/// `texture` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_texture_width(texture: *const MtlTexture) -> UInteger {
+    guard_or(0, || unsafe { texture.as_ref() }.map_or(0, Texture::width))
+}
+
+/// Get the texture's height in pixels.
+///
+/// # Safety
+///
+/// `texture` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_texture_height(texture: *const MtlTexture) -> UInteger {
+    guard_or(0, || unsafe { texture.as_ref() }.map_or(0, Texture::height))
+}
+
+/// Release a texture handle.
+///
+/// # Safety
+///
+/// `texture` must be null or a handle not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_texture_release(texture: *mut MtlTexture) {
+    guard_or((), || unsafe { release(texture) })
+}
+
+// ============================================================================
+// Compute pipeline
+// ============================================================================
+
+/// Compile Metal shading language `source` and create a compute pipeline
+/// for its `function_name` kernel.
+///
+/// On failure the compiler or pipeline log is available from
+/// [`mtl_last_error_message`].
+///
+/// # Safety
+///
+/// `device` must be a live handle, `source` and `function_name`
+/// NUL-terminated strings and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_pipeline_create(
+    device: *const MtlDevice,
+    source: *const c_char,
+    function_name: *const c_char,
+    out: *mut *mut MtlComputePipeline,
+) -> MtlStatus {
+    guard(|| {
+        let device = try_status!(unsafe { handle(device) });
+        let source = try_status!(unsafe { string(source) });
+        let function_name = try_status!(unsafe { string(function_name) });
+        match device.new_compute_pipeline_from_source(source, function_name, None) {
+            Ok(pipeline) => unsafe { write_out(out, pipeline) },
+            Err(err) => {
+                let status = match err {
+                    SourcePipelineError::Compile { .. } => MtlStatus::CompileFailed,
+                    SourcePipelineError::FunctionNotFound { .. } => MtlStatus::FunctionNotFound,
+                    SourcePipelineError::Pipeline { .. } => MtlStatus::PipelineFailed,
+                };
+                fail(status, err)
+            }
+        }
+    })
+}
+
+/// Get the pipeline's SIMD group width, the natural threadgroup width.
+///
+/// # Safety
+///
+/// `pipeline` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_pipeline_thread_execution_width(
+    pipeline: *const MtlComputePipeline,
+) -> UInteger {
+    guard_or(0, || {
+        unsafe { pipeline.as_ref() }.map_or(0, ComputePipelineState::thread_execution_width)
+    })
+}
+
+/// Release a compute pipeline handle.
+///
+/// # Safety
+///
+/// `pipeline` must be null or a handle not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_pipeline_release(pipeline: *mut MtlComputePipeline) {
+    guard_or((), || unsafe { release(pipeline) })
+}
+
+// ============================================================================
+// Render pipeline
+// ============================================================================
+
+/// Compile Metal shading language `source` and create a render pipeline
+/// from its `vertex_function` and `fragment_function`, drawing to a single
+/// color attachment of `MTLPixelFormat` `color_pixel_format`.
+///
+/// On failure the compiler or pipeline log is available from
+/// [`mtl_last_error_message`].
+///
+/// # Safety
+///
+/// `device` must be a live handle, `source`, `vertex_function` and
+/// `fragment_function` NUL-terminated strings and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_pipeline_create(
+    device: *const MtlDevice,
+    source: *const c_char,
+    vertex_function: *const c_char,
+    fragment_function: *const c_char,
+    color_pixel_format: UInteger,
+    out: *mut *mut MtlRenderPipeline,
+) -> MtlStatus {
+    guard(|| {
+        let device = try_status!(unsafe { handle(device) });
+        let source = try_status!(unsafe { string(source) });
+        let vertex_function = try_status!(unsafe { string(vertex_function) });
+        let fragment_function = try_status!(unsafe { string(fragment_function) });
+
+        let library = match device.new_library_with_source(source, None) {
+            Ok(library) => library,
+            Err(err) => {
+                return fail(
+                    MtlStatus::CompileFailed,
+                    format!("failed to compile shader source:\n{}", err),
+                );
+            }
+        };
+        let function = |name: &str| {
+            library.new_function_with_name(name).ok_or_else(|| {
+                fail(
+                    MtlStatus::FunctionNotFound,
+                    format!(
+                        "no function named `{}` in library (available: {})",
+                        name,
+                        library.function_names().join(", ")
+                    ),
+                )
+            })
+        };
+        let vertex = try_status!(function(vertex_function));
+        let fragment = try_status!(function(fragment_function));
+
+        let Some(descriptor) = RenderPipelineDescriptor::new() else {
+            return fail(
+                MtlStatus::AllocationFailed,
+                "failed to create render pipeline descriptor",
+            );
+        };
+        descriptor.set_vertex_function(Some(&vertex));
+        descriptor.set_fragment_function(Some(&fragment));
+        if let Some(attachment) = descriptor.color_attachments().object(0) {
+            attachment.set_pixel_format(PixelFormat(color_pixel_format));
+        }
+        match unsafe { device.new_render_pipeline_state(descriptor.as_ptr()) } {
+            Ok(pipeline) => unsafe { write_out(out, pipeline) },
+            Err(err) => fail(MtlStatus::PipelineFailed, err),
+        }
+    })
+}
+
+/// Release a render pipeline handle.
+///
+/// # Safety
+///
+/// `pipeline` must be null or a handle not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_pipeline_release(pipeline: *mut MtlRenderPipeline) {
+    guard_or((), || unsafe { release(pipeline) })
+}
+
+// ============================================================================
+// Drawable
+// ============================================================================
+
+/// Get a handle to the texture of a `CAMetalDrawable`, to render into with
+/// [`mtl_render_encoder_create`] before presenting it with
+/// [`mtl_command_buffer_present_drawable`].
+///
+/// # Safety
+///
+/// `drawable` must be a valid `id<CAMetalDrawable>` and `out` valid for
+/// writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_drawable_texture(
+    drawable: *mut c_void,
+    out: *mut *mut MtlTexture,
+) -> MtlStatus {
+    guard(|| {
+        if drawable.is_null() {
+            return fail(MtlStatus::NullArgument, "null drawable");
+        }
+        let texture = unsafe {
+            let ptr: *mut c_void = msg_send_0(drawable, sel!(texture));
+            if !ptr.is_null() {
+                let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            }
+            Texture::from_raw(ptr)
+        };
+        match texture {
+            Some(texture) => unsafe { write_out(out, texture) },
+            None => fail(MtlStatus::InvalidArgument, "drawable has no texture"),
+        }
+    })
+}
+
+// ============================================================================
+// Command buffer
+// ============================================================================
+
+/// Create a command buffer on `queue`.
+///
+/// # Safety
+///
+/// `queue` must be a live handle and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_command_buffer_create(
+    queue: *const MtlCommandQueue,
+    out: *mut *mut MtlCommandBuffer,
+) -> MtlStatus {
+    guard(|| {
+        let queue = try_status!(unsafe { handle(queue) });
+        match queue.command_buffer() {
+            Some(command_buffer) => unsafe { write_out(out, command_buffer) },
+            None => fail(
+                MtlStatus::AllocationFailed,
+                "failed to create command buffer",
+            ),
+        }
+    })
+}
+
+/// Schedule presentation of a `CAMetalDrawable` when the command buffer
+/// completes.
+///
+/// # Safety
+///
+/// `command_buffer` must be a live handle and `drawable` a valid
+/// `id<CAMetalDrawable>`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_command_buffer_present_drawable(
+    command_buffer: *const MtlCommandBuffer,
+    drawable: *mut c_void,
+) -> MtlStatus {
+    guard(|| {
+        let command_buffer = try_status!(unsafe { handle(command_buffer) });
+        if drawable.is_null() {
+            return fail(MtlStatus::NullArgument, "null drawable");
+        }
+        unsafe { command_buffer.present_drawable(drawable) };
+        MtlStatus::Ok
+    })
+}
+
+/// Commit the command buffer for execution.
+///
+/// # Safety
+///
+/// `command_buffer` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_command_buffer_commit(
+    command_buffer: *const MtlCommandBuffer,
+) -> MtlStatus {
+    guard(|| {
+        let command_buffer = try_status!(unsafe { handle(command_buffer) });
+        command_buffer.commit();
+        MtlStatus::Ok
+    })
+}
+
+/// Block until the command buffer completes and report whether it failed.
+///
+/// # Safety
+///
+/// `command_buffer` must be a live, committed handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_command_buffer_wait_until_completed(
+    command_buffer: *const MtlCommandBuffer,
+) -> MtlStatus {
+    guard(|| {
+        let command_buffer = try_status!(unsafe { handle(command_buffer) });
+        command_buffer.wait_until_completed();
+        if command_buffer.status() != CommandBufferStatus::ERROR {
+            return MtlStatus::Ok;
+        }
+        match command_buffer.error() {
+            Some(err) => fail(MtlStatus::CommandBufferFailed, err),
+            None => fail(MtlStatus::CommandBufferFailed, "command buffer failed"),
+        }
+    })
+}
+
+/// Release a command buffer handle.
+///
+/// # Safety
+///
+/// `command_buffer` must be null or a handle not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_command_buffer_release(command_buffer: *mut MtlCommandBuffer) {
+    guard_or((), || unsafe { release(command_buffer) })
+}
+
+// ============================================================================
+// Compute encoder
+// ============================================================================
+
+/// Start a compute pass on `command_buffer`.
+///
+/// # Safety
+///
+/// `command_buffer` must be a live handle with no open encoder and `out`
+/// valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_encoder_create(
+    command_buffer: *const MtlCommandBuffer,
+    out: *mut *mut MtlComputeEncoder,
+) -> MtlStatus {
+    guard(|| {
+        let command_buffer = try_status!(unsafe { handle(command_buffer) });
+        match unsafe { ComputeCommandEncoder::from_raw(command_buffer.compute_command_encoder()) } {
+            Some(encoder) => unsafe { write_out(out, encoder) },
+            None => fail(
+                MtlStatus::AllocationFailed,
+                "failed to create compute encoder",
+            ),
+        }
+    })
+}
+
+/// Bind a compute pipeline.
+///
+/// # Safety
+///
+/// `encoder` and `pipeline` must be live handles.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_encoder_set_pipeline(
+    encoder: *const MtlComputeEncoder,
+    pipeline: *const MtlComputePipeline,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let pipeline = try_status!(unsafe { handle(pipeline) });
+        encoder.set_compute_pipeline_state(pipeline);
+        MtlStatus::Ok
+    })
+}
+
+/// Bind a buffer at `offset` to buffer slot `index`.
+///
+/// # Safety
+///
+/// `encoder` and `buffer` must be live handles.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_encoder_set_buffer(
+    encoder: *const MtlComputeEncoder,
+    buffer: *const MtlBuffer,
+    offset: UInteger,
+    index: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let buffer = try_status!(unsafe { handle(buffer) });
+        try_status!(check_offset(buffer, offset));
+        encoder.set_buffer(buffer, offset, index);
+        MtlStatus::Ok
+    })
+}
+
+/// Copy `length` bytes, at most 4096, into buffer slot `index`.
+///
+/// # Safety
+///
+/// `encoder` must be a live handle and `bytes` valid for `length` bytes of
+/// reads.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_encoder_set_bytes(
+    encoder: *const MtlComputeEncoder,
+    bytes: *const c_void,
+    length: usize,
+    index: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let bytes = try_status!(unsafe { inline_bytes(bytes, length) });
+        encoder.set_bytes(bytes, index);
+        MtlStatus::Ok
+    })
+}
+
+/// Bind a texture to texture slot `index`.
+///
+/// # Safety
+///
+/// `encoder` and `texture` must be live handles.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_encoder_set_texture(
+    encoder: *const MtlComputeEncoder,
+    texture: *const MtlTexture,
+    index: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let texture = try_status!(unsafe { handle(texture) });
+        encoder.set_texture(texture, index);
+        MtlStatus::Ok
+    })
+}
+
+/// Dispatch a `width` x `height` x `depth` grid of threads in threadgroups
+/// of `group_width` x `group_height` x `group_depth`.
+///
+/// # Safety
+///
+/// `encoder` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_encoder_dispatch_threads(
+    encoder: *const MtlComputeEncoder,
+    width: UInteger,
+    height: UInteger,
+    depth: UInteger,
+    group_width: UInteger,
+    group_height: UInteger,
+    group_depth: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        if group_width == 0 || group_height == 0 || group_depth == 0 {
+            return fail(
+                MtlStatus::InvalidArgument,
+                "threadgroup size must be non-zero",
+            );
+        }
+        encoder.dispatch_threads(
+            Size::new(width, height, depth),
+            Size::new(group_width, group_height, group_depth),
+        );
+        MtlStatus::Ok
+    })
+}
+
+/// End the compute pass.
+///
+/// # Safety
+///
+/// `encoder` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_encoder_end_encoding(
+    encoder: *const MtlComputeEncoder,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        encoder.end_encoding();
+        MtlStatus::Ok
+    })
+}
+
+/// Release a compute encoder handle.
+///
+/// # Safety
+///
+/// `encoder` must be null or a handle not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_compute_encoder_release(encoder: *mut MtlComputeEncoder) {
+    guard_or((), || unsafe { release(encoder) })
+}
+
+// ============================================================================
+// Render encoder
+// ============================================================================
+
+/// Start a render pass on `command_buffer` drawing to `texture`.
+///
+/// `load_action` is an `MTLLoadAction`; with `MTLLoadActionClear` the
+/// texture is cleared to the given color. The results are always stored.
+///
+/// # Safety
+///
+/// `command_buffer` and `texture` must be live handles, `command_buffer`
+/// must have no open encoder, and `out` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_create(
+    command_buffer: *const MtlCommandBuffer,
+    texture: *const MtlTexture,
+    load_action: UInteger,
+    clear_red: f64,
+    clear_green: f64,
+    clear_blue: f64,
+    clear_alpha: f64,
+    out: *mut *mut MtlRenderEncoder,
+) -> MtlStatus {
+    guard(|| {
+        let command_buffer = try_status!(unsafe { handle(command_buffer) });
+        let texture = try_status!(unsafe { handle(texture) });
+        if load_action > LoadAction::CLEAR.0 {
+            return fail(
+                MtlStatus::InvalidArgument,
+                format!("unknown load action {}", load_action),
+            );
+        }
+        let clear_color = ClearColor::new(clear_red, clear_green, clear_blue, clear_alpha);
+        let Some(pass) = RenderPassDescriptor::for_texture(texture, clear_color, None) else {
+            return fail(
+                MtlStatus::AllocationFailed,
+                "failed to create render pass descriptor",
+            );
+        };
+        if let Some(attachment) = pass.color_attachments().and_then(|a| a.object_at(0)) {
+            attachment.set_load_action(LoadAction(load_action));
+        }
+        match unsafe {
+            RenderCommandEncoder::from_raw(command_buffer.render_command_encoder(&pass))
+        } {
+            Some(encoder) => unsafe { write_out(out, encoder) },
+            None => fail(
+                MtlStatus::AllocationFailed,
+                "failed to create render encoder",
+            ),
+        }
+    })
+}
+
+/// Bind a render pipeline.
+///
+/// # Safety
+///
+/// `encoder` and `pipeline` must be live handles.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_set_pipeline(
+    encoder: *const MtlRenderEncoder,
+    pipeline: *const MtlRenderPipeline,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let pipeline = try_status!(unsafe { handle(pipeline) });
+        encoder.set_render_pipeline_state(pipeline);
+        MtlStatus::Ok
+    })
+}
+
+/// Bind a buffer at `offset` to vertex buffer slot `index`.
+///
+/// # Safety
+///
+/// `encoder` and `buffer` must be live handles.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_set_vertex_buffer(
+    encoder: *const MtlRenderEncoder,
+    buffer: *const MtlBuffer,
+    offset: UInteger,
+    index: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let buffer = try_status!(unsafe { handle(buffer) });
+        try_status!(check_offset(buffer, offset));
+        encoder.set_vertex_buffer(buffer, offset, index);
+        MtlStatus::Ok
+    })
+}
+
+/// Copy `length` bytes, at most 4096, into vertex buffer slot `index`.
+///
+/// # Safety
+///
+/// `encoder` must be a live handle and `bytes` valid for `length` bytes of
+/// reads.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_set_vertex_bytes(
+    encoder: *const MtlRenderEncoder,
+    bytes: *const c_void,
+    length: usize,
+    index: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let bytes = try_status!(unsafe { inline_bytes(bytes, length) });
+        encoder.set_vertex_bytes(bytes, index);
+        MtlStatus::Ok
+    })
+}
+
+/// Bind a buffer at `offset` to fragment buffer slot `index`.
+///
+/// # Safety
+///
+/// `encoder` and `buffer` must be live handles.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_set_fragment_buffer(
+    encoder: *const MtlRenderEncoder,
+    buffer: *const MtlBuffer,
+    offset: UInteger,
+    index: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let buffer = try_status!(unsafe { handle(buffer) });
+        try_status!(check_offset(buffer, offset));
+        encoder.set_fragment_buffer(buffer, offset, index);
+        MtlStatus::Ok
+    })
+}
+
+/// Copy `length` bytes, at most 4096, into fragment buffer slot `index`.
+///
+/// # Safety
+///
+/// `encoder` must be a live handle and `bytes` valid for `length` bytes of
+/// reads.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_set_fragment_bytes(
+    encoder: *const MtlRenderEncoder,
+    bytes: *const c_void,
+    length: usize,
+    index: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let bytes = try_status!(unsafe { inline_bytes(bytes, length) });
+        encoder.set_fragment_bytes(bytes, index);
+        MtlStatus::Ok
+    })
+}
+
+/// Bind a texture to fragment texture slot `index`.
+///
+/// # Safety
+///
+/// `encoder` and `texture` must be live handles.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_set_fragment_texture(
+    encoder: *const MtlRenderEncoder,
+    texture: *const MtlTexture,
+    index: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        let texture = try_status!(unsafe { handle(texture) });
+        encoder.set_fragment_texture(texture, index);
+        MtlStatus::Ok
+    })
+}
+
+/// Draw `instance_count` instances of `vertex_count` vertices starting at
+/// `vertex_start`, as `MTLPrimitiveType` `primitive_type`.
+///
+/// # Safety
+///
+/// `encoder` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_draw_primitives(
+    encoder: *const MtlRenderEncoder,
+    primitive_type: UInteger,
+    vertex_start: UInteger,
+    vertex_count: UInteger,
+    instance_count: UInteger,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        if primitive_type > PrimitiveType::TRIANGLE_STRIP.0 {
+            return fail(
+                MtlStatus::InvalidArgument,
+                format!("unknown primitive type {}", primitive_type),
+            );
+        }
+        encoder.draw_primitives_instanced(
+            PrimitiveType(primitive_type),
+            vertex_start,
+            vertex_count,
+            instance_count,
+        );
+        MtlStatus::Ok
+    })
+}
+
+/// End the render pass.
+///
+/// # Safety
+///
+/// `encoder` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_end_encoding(
+    encoder: *const MtlRenderEncoder,
+) -> MtlStatus {
+    guard(|| {
+        let encoder = try_status!(unsafe { handle(encoder) });
+        encoder.end_encoding();
+        MtlStatus::Ok
+    })
+}
+
+/// Release a render encoder handle.
+///
+/// # Safety
+///
+/// `encoder` must be null or a handle not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mtl_render_encoder_release(encoder: *mut MtlRenderEncoder) {
+    guard_or((), || unsafe { release(encoder) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_capi_compute_round_trip() {
+        let source = c"
+            kernel void square(device float* data [[buffer(0)]],
+                               uint gid [[thread_position_in_grid]]) {
+                data[gid] = data[gid] * data[gid];
+            }
+        ";
+        unsafe {
+            let mut device = ptr::null_mut();
+            assert_eq!(mtl_device_create_system_default(&mut device), MtlStatus::Ok);
+            let mut name = [0 as c_char; 128];
+            assert!(mtl_device_name(device, name.as_mut_ptr(), name.len()) > 0);
+
+            let mut pipeline = ptr::null_mut();
+            assert_eq!(
+                mtl_compute_pipeline_create(
+                    device,
+                    source.as_ptr(),
+                    c"missing".as_ptr(),
+                    &mut pipeline
+                ),
+                MtlStatus::FunctionNotFound
+            );
+            assert!(!mtl_last_error_message().is_null());
+            assert_eq!(
+                mtl_compute_pipeline_create(
+                    device,
+                    source.as_ptr(),
+                    c"square".as_ptr(),
+                    &mut pipeline
+                ),
+                MtlStatus::Ok
+            );
+
+            let mut buffer = ptr::null_mut();
+            let options = ResourceOptions::STORAGE_MODE_SHARED.0;
+            assert_eq!(
+                mtl_buffer_create(device, 16, options, &mut buffer),
+                MtlStatus::Ok
+            );
+            let data = mtl_buffer_contents(buffer).cast::<f32>();
+            for i in 0..4 {
+                *data.add(i) = i as f32;
+            }
+
+            let mut queue = ptr::null_mut();
+            let mut command_buffer = ptr::null_mut();
+            let mut encoder = ptr::null_mut();
+            assert_eq!(mtl_command_queue_create(device, &mut queue), MtlStatus::Ok);
+            assert_eq!(
+                mtl_command_buffer_create(queue, &mut command_buffer),
+                MtlStatus::Ok
+            );
+            assert_eq!(
+                mtl_compute_encoder_create(command_buffer, &mut encoder),
+                MtlStatus::Ok
+            );
+            assert_eq!(
+                mtl_compute_encoder_set_pipeline(encoder, pipeline),
+                MtlStatus::Ok
+            );
+            assert_eq!(
+                mtl_compute_encoder_set_buffer(encoder, buffer, 0, 0),
+                MtlStatus::Ok
+            );
+            let oversized = [0u8; MAX_INLINE_BYTES + 1];
+            assert_eq!(
+                mtl_compute_encoder_set_bytes(
+                    encoder,
+                    oversized.as_ptr().cast(),
+                    oversized.len(),
+                    1
+                ),
+                MtlStatus::InvalidArgument
+            );
+            assert_eq!(
+                mtl_compute_encoder_dispatch_threads(encoder, 4, 1, 1, 4, 1, 1),
+                MtlStatus::Ok
+            );
+            assert_eq!(mtl_compute_encoder_end_encoding(encoder), MtlStatus::Ok);
+            assert_eq!(mtl_command_buffer_commit(command_buffer), MtlStatus::Ok);
+            assert_eq!(
+                mtl_command_buffer_wait_until_completed(command_buffer),
+                MtlStatus::Ok
+            );
+            assert_eq!(*data.add(3), 9.0);
+
+            assert_eq!(
+                mtl_compute_encoder_set_pipeline(ptr::null(), pipeline),
+                MtlStatus::NullArgument
+            );
+
+            mtl_compute_encoder_release(encoder);
+            mtl_command_buffer_release(command_buffer);
+            mtl_command_queue_release(queue);
+            mtl_buffer_release(buffer);
+            mtl_compute_pipeline_release(pipeline);
+            mtl_device_release(device);
+        }
+    }
+
+    #[test]
+    fn test_capi_render_round_trip() {
+        let source = c"
+            #include <metal_stdlib>
+            using namespace metal;
+
+            vertex float4 vertex_main(uint vid [[vertex_id]]) {
+                float2 uv = float2((vid << 1) & 2, vid & 2);
+                return float4(uv * 2.0 - 1.0, 0.0, 1.0);
+            }
+
+            fragment float4 fragment_main(constant float4& color [[buffer(0)]]) {
+                return color;
+            }
+        ";
+        unsafe {
+            let mut device = ptr::null_mut();
+            assert_eq!(mtl_device_create_system_default(&mut device), MtlStatus::Ok);
+
+            let format = PixelFormat::RGBA8_UNORM.0;
+            let mut pipeline = ptr::null_mut();
+            assert_eq!(
+                mtl_render_pipeline_create(
+                    device,
+                    source.as_ptr(),
+                    c"vertex_main".as_ptr(),
+                    c"missing".as_ptr(),
+                    format,
+                    &mut pipeline
+                ),
+                MtlStatus::FunctionNotFound
+            );
+            assert_eq!(
+                mtl_render_pipeline_create(
+                    device,
+                    source.as_ptr(),
+                    c"vertex_main".as_ptr(),
+                    c"fragment_main".as_ptr(),
+                    format,
+                    &mut pipeline
+                ),
+                MtlStatus::Ok
+            );
+
+            let mut texture = ptr::null_mut();
+            let usage = TextureUsage::RENDER_TARGET.0;
+            assert_eq!(
+                mtl_texture_create_2d(device, format, 4, 4, usage, &mut texture),
+                MtlStatus::Ok
+            );
+
+            let mut queue = ptr::null_mut();
+            let mut command_buffer = ptr::null_mut();
+            let mut encoder = ptr::null_mut();
+            assert_eq!(mtl_command_queue_create(device, &mut queue), MtlStatus::Ok);
+            assert_eq!(
+                mtl_command_buffer_create(queue, &mut command_buffer),
+                MtlStatus::Ok
+            );
+            assert_eq!(
+                mtl_render_encoder_create(
+                    command_buffer,
+                    texture,
+                    LoadAction::CLEAR.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    1.0,
+                    &mut encoder
+                ),
+                MtlStatus::Ok
+            );
+            let color = [1.0f32, 0.5, 0.25, 1.0];
+            assert_eq!(
+                mtl_render_encoder_set_pipeline(encoder, pipeline),
+                MtlStatus::Ok
+            );
+            assert_eq!(
+                mtl_render_encoder_set_fragment_bytes(
+                    encoder,
+                    color.as_ptr().cast(),
+                    size_of_val(&color),
+                    0
+                ),
+                MtlStatus::Ok
+            );
+            assert_eq!(
+                mtl_render_encoder_draw_primitives(encoder, 99, 0, 3, 1),
+                MtlStatus::InvalidArgument
+            );
+            assert_eq!(
+                mtl_render_encoder_draw_primitives(encoder, PrimitiveType::TRIANGLE.0, 0, 3, 1),
+                MtlStatus::Ok
+            );
+            assert_eq!(mtl_render_encoder_end_encoding(encoder), MtlStatus::Ok);
+            assert_eq!(mtl_command_buffer_commit(command_buffer), MtlStatus::Ok);
+            assert_eq!(
+                mtl_command_buffer_wait_until_completed(command_buffer),
+                MtlStatus::Ok
+            );
+
+            assert_eq!(
+                mtl_drawable_texture(ptr::null_mut(), &mut texture),
+                MtlStatus::NullArgument
+            );
+
+            mtl_render_encoder_release(encoder);
+            mtl_command_buffer_release(command_buffer);
+            mtl_command_queue_release(queue);
+            mtl_texture_release(texture);
+            mtl_render_pipeline_release(pipeline);
+            mtl_device_release(device);
+        }
+    }
+
+    #[test]
+    fn test_capi_command_buffers_are_freed() {
+        #[cfg_attr(target_vendor = "apple", link(name = "objc"))]
+        unsafe extern "C" {
+            fn objc_initWeak(location: *mut *mut c_void, object: *mut c_void) -> *mut c_void;
+            fn objc_loadWeakRetained(location: *mut *mut c_void) -> *mut c_void;
+            fn objc_destroyWeak(location: *mut *mut c_void);
+            fn objc_release(object: *mut c_void);
+        }
+
+        unsafe {
+            let mut device = ptr::null_mut();
+            let mut queue = ptr::null_mut();
+            assert_eq!(mtl_device_create_system_default(&mut device), MtlStatus::Ok);
+            assert_eq!(mtl_command_queue_create(device, &mut queue), MtlStatus::Ok);
+
+            // No pool is drained between iterations, so anything the entry
+            // points autorelease would outlive its handle.
+            for _ in 0..256 {
+                let mut command_buffer = ptr::null_mut();
+                assert_eq!(
+                    mtl_command_buffer_create(queue, &mut command_buffer),
+                    MtlStatus::Ok
+                );
+                let mut weak = ptr::null_mut();
+                objc_initWeak(&mut weak, (*command_buffer).as_raw());
+                mtl_command_buffer_release(command_buffer);
+
+                let alive = objc_loadWeakRetained(&mut weak);
+                objc_destroyWeak(&mut weak);
+                if !alive.is_null() {
+                    objc_release(alive);
+                }
+                assert!(alive.is_null(), "command buffer outlived its handle");
+            }
+
+            mtl_command_queue_release(queue);
+            mtl_device_release(device);
+        }
+    }
+}
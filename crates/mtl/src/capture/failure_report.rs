@@ -0,0 +1,331 @@
+//! Reports of failed command buffers.
+//!
+//! A [`FailureReport`] collects what Metal knows about a command buffer
+//! that ended in an error: the error code and description, the execution
+//! state and debug signposts of each encoder, and the shader validation
+//! logs. Encoder states are only available for command buffers created with
+//! [`CommandBufferErrorOption::ENCODER_EXECUTION_STATUS`](crate::enums::CommandBufferErrorOption::ENCODER_EXECUTION_STATUS).
+//!
+//! [`FailureWatch`] wraps a [`CommandQueue`](crate::CommandQueue) so every command buffer it
+//! creates has encoder execution status enabled and a completed handler
+//! that passes a report to a callback. It can also capture the frame after
+//! a failure, so the next occurrence can be inspected in Xcode.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let watch = FailureWatch::new(queue, |report| eprintln!("{report}"))
+//!     .with_capture_on_failure("failure.gputrace");
+//!
+//! loop {
+//!     let _capture = watch.begin_frame()?;
+//!     let command_buffer = watch.command_buffer().unwrap();
+//!     encode_frame(&command_buffer);
+//!     command_buffer.commit();
+//! }
+//! ```
+
+use std::fmt;
+#[cfg(feature = "blocks")]
+use std::path::PathBuf;
+#[cfg(feature = "blocks")]
+use std::sync::Arc;
+#[cfg(feature = "blocks")]
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use mtl_foundation::Integer;
+
+#[cfg(feature = "blocks")]
+use super::{CaptureGuard, CaptureManager};
+use crate::CommandBufferEncoderInfo;
+use crate::command_buffer::CommandBuffer;
+#[cfg(feature = "blocks")]
+use crate::command_buffer::CommandBufferDescriptor;
+#[cfg(feature = "blocks")]
+use crate::command_queue::CommandQueue;
+#[cfg(feature = "blocks")]
+use crate::enums::{CaptureError, CommandBufferErrorOption};
+use crate::enums::{CommandBufferStatus, CommandEncoderErrorState};
+use crate::function_log::FunctionLog;
+
+// ============================================================================
+// EncoderFailure
+// ============================================================================
+
+/// The execution state of one encoder in a failed command buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncoderFailure {
+    /// The encoder's label.
+    pub label: Option<String>,
+    /// How far the encoder got.
+    pub error_state: CommandEncoderErrorState,
+    /// The debug signposts the encoder inserted.
+    pub signposts: Vec<String>,
+}
+
+impl EncoderFailure {
+    fn from_info(info: &CommandBufferEncoderInfo) -> Self {
+        Self {
+            label: info.label(),
+            error_state: info.error_state(),
+            signposts: info.debug_signposts(),
+        }
+    }
+
+    /// Check if this encoder caused the failure.
+    pub fn is_faulted(&self) -> bool {
+        self.error_state == CommandEncoderErrorState::FAULTED
+    }
+}
+
+// ============================================================================
+// FailureReport
+// ============================================================================
+
+/// What Metal reported about a failed command buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailureReport {
+    /// The command buffer's label.
+    pub label: Option<String>,
+    /// The command buffer's final status.
+    pub status: CommandBufferStatus,
+    /// The error code, a [`CommandBufferError`](crate::enums::CommandBufferError)
+    /// value for Metal errors.
+    pub error_code: Option<Integer>,
+    /// The error description.
+    pub error: Option<String>,
+    /// The encoders, in encoding order.
+    pub encoders: Vec<EncoderFailure>,
+    /// The shader validation logs.
+    pub logs: Vec<String>,
+}
+
+impl FailureReport {
+    /// Build a report for `command_buffer`, or `None` if it didn't fail.
+    pub fn from_command_buffer(command_buffer: &CommandBuffer) -> Option<Self> {
+        let status = command_buffer.status();
+        let error = command_buffer.error();
+        if status != CommandBufferStatus::ERROR && error.is_none() {
+            return None;
+        }
+        let encoders = error
+            .as_ref()
+            .map(CommandBufferEncoderInfo::from_error)
+            .unwrap_or_default();
+        let logs = command_buffer
+            .logs()
+            .map(|logs| logs.logs())
+            .unwrap_or_default();
+        Some(Self {
+            label: command_buffer.label(),
+            status,
+            error_code: error.as_ref().map(|error| error.code()),
+            error: error.as_ref().map(ToString::to_string),
+            encoders: encoders.iter().map(EncoderFailure::from_info).collect(),
+            logs: logs.iter().map(describe_log).collect(),
+        })
+    }
+
+    /// Get the encoders that caused the failure.
+    pub fn faulted_encoders(&self) -> impl Iterator<Item = &EncoderFailure> {
+        self.encoders.iter().filter(|encoder| encoder.is_faulted())
+    }
+}
+
+impl fmt::Display for FailureReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = self.label.as_deref().unwrap_or("<unlabeled>");
+        write!(f, "command buffer {label} failed")?;
+        if let Some(code) = self.error_code {
+            write!(f, " with error {code}")?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, ": {error}")?;
+        }
+        for encoder in &self.encoders {
+            let label = encoder.label.as_deref().unwrap_or("<unlabeled>");
+            let state = match encoder.error_state {
+                CommandEncoderErrorState::COMPLETED => "completed",
+                CommandEncoderErrorState::AFFECTED => "affected",
+                CommandEncoderErrorState::PENDING => "pending",
+                CommandEncoderErrorState::FAULTED => "FAULTED",
+                _ => "unknown",
+            };
+            write!(f, "\n  encoder {label}: {state}")?;
+            if !encoder.signposts.is_empty() {
+                write!(f, " [{}]", encoder.signposts.join(", "))?;
+            }
+        }
+        for log in &self.logs {
+            write!(f, "\n  log: {log}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Describe a log entry by its encoder, function and source location.
+fn describe_log(log: &FunctionLog) -> String {
+    let mut description = match log.encoder_label() {
+        Some(label) => format!("validation error in encoder {label}"),
+        None => "validation error".to_string(),
+    };
+    if let Some(name) = log.function().and_then(|function| function.name()) {
+        description.push_str(&format!(", function {name}"));
+    }
+    if let Some(location) = log.debug_location() {
+        let url = location.url().unwrap_or_default();
+        description.push_str(&format!(
+            " at {url}:{}:{}",
+            location.line(),
+            location.column()
+        ));
+    }
+    description
+}
+
+// ============================================================================
+// FailureWatch
+// ============================================================================
+
+#[cfg(feature = "blocks")]
+struct WatchState {
+    handler: Box<dyn Fn(&FailureReport) + Send + Sync>,
+    capture_pending: AtomicBool,
+    failures: AtomicU64,
+}
+
+/// A command queue whose command buffers report their failures.
+#[cfg(feature = "blocks")]
+pub struct FailureWatch {
+    queue: CommandQueue,
+    state: Arc<WatchState>,
+    capture_path: Option<PathBuf>,
+}
+
+#[cfg(feature = "blocks")]
+impl FailureWatch {
+    /// Wrap `queue`, calling `handler` on Metal's completion thread with a
+    /// report for each failed command buffer.
+    pub fn new<F>(queue: CommandQueue, handler: F) -> Self
+    where
+        F: Fn(&FailureReport) + Send + Sync + 'static,
+    {
+        Self {
+            queue,
+            state: Arc::new(WatchState {
+                handler: Box::new(handler),
+                capture_pending: AtomicBool::new(false),
+                failures: AtomicU64::new(0),
+            }),
+            capture_path: None,
+        }
+    }
+
+    /// Capture the frame after each failure to a trace document at `path`.
+    ///
+    /// See [`begin_frame`](Self::begin_frame).
+    pub fn with_capture_on_failure(mut self, path: impl Into<PathBuf>) -> Self {
+        self.capture_path = Some(path.into());
+        self
+    }
+
+    /// Get the wrapped queue.
+    pub fn queue(&self) -> &CommandQueue {
+        &self.queue
+    }
+
+    /// Get the number of failed command buffers so far.
+    pub fn failure_count(&self) -> u64 {
+        self.state.failures.load(Ordering::Relaxed)
+    }
+
+    /// Create a command buffer with encoder execution status enabled and a
+    /// handler that reports its failure.
+    pub fn command_buffer(&self) -> Option<CommandBuffer> {
+        let descriptor = CommandBufferDescriptor::new()?;
+        descriptor.set_error_options(CommandBufferErrorOption::ENCODER_EXECUTION_STATUS);
+        let command_buffer = self.queue.command_buffer_with_descriptor(&descriptor)?;
+        let state = Arc::clone(&self.state);
+        command_buffer.add_completed_handler(move |command_buffer| {
+            if let Some(report) = FailureReport::from_command_buffer(command_buffer) {
+                state.failures.fetch_add(1, Ordering::Relaxed);
+                state.capture_pending.store(true, Ordering::Relaxed);
+                (state.handler)(&report);
+            }
+        });
+        Some(command_buffer)
+    }
+
+    /// Call before encoding a frame. If a command buffer failed since the
+    /// last call and capture on failure is enabled, starts a capture that
+    /// lasts until the returned guard is dropped.
+    pub fn begin_frame(&self) -> Result<Option<CaptureGuard>, CaptureError> {
+        let Some(path) = &self.capture_path else {
+            return Ok(None);
+        };
+        if !self.state.capture_pending.swap(false, Ordering::Relaxed) {
+            return Ok(None);
+        }
+        CaptureManager::capture_scope_guard(&self.queue.device(), path).map(Some)
+    }
+}
+
+#[cfg(feature = "blocks")]
+impl fmt::Debug for FailureWatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FailureWatch")
+            .field("capture_path", &self.capture_path)
+            .field("failure_count", &self.failure_count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_display() {
+        let report = FailureReport {
+            label: Some("frame".to_string()),
+            status: CommandBufferStatus::ERROR,
+            error_code: Some(3),
+            error: Some("page fault".to_string()),
+            encoders: vec![
+                EncoderFailure {
+                    label: Some("shadows".to_string()),
+                    error_state: CommandEncoderErrorState::COMPLETED,
+                    signposts: Vec::new(),
+                },
+                EncoderFailure {
+                    label: Some("lighting".to_string()),
+                    error_state: CommandEncoderErrorState::FAULTED,
+                    signposts: vec!["tile 4".to_string()],
+                },
+            ],
+            logs: Vec::new(),
+        };
+        assert_eq!(
+            report.to_string(),
+            "command buffer frame failed with error 3: page fault\n  \
+             encoder shadows: completed\n  encoder lighting: FAULTED [tile 4]"
+        );
+        let faulted: Vec<_> = report.faulted_encoders().collect();
+        assert_eq!(faulted.len(), 1);
+        assert_eq!(faulted[0].label.as_deref(), Some("lighting"));
+    }
+
+    #[cfg(feature = "blocks")]
+    #[test]
+    fn test_watch_successful_command_buffer() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().expect("no command queue");
+        let watch = FailureWatch::new(queue, |report| panic!("unexpected failure: {report}"));
+        let command_buffer = watch.command_buffer().expect("no command buffer");
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+        assert!(FailureReport::from_command_buffer(&command_buffer).is_none());
+        assert_eq!(watch.failure_count(), 0);
+        assert!(watch.begin_frame().unwrap().is_none());
+    }
+}
@@ -0,0 +1,305 @@
+//! Scoped and environment-driven GPU captures.
+//!
+//! [`CaptureManager::capture_scope_guard`] writes a `.gputrace` document
+//! for everything submitted while the returned [`CaptureGuard`] is alive.
+//!
+//! [`FrameCapture`] reads the `METAL_RS_CAPTURE` environment variable so a
+//! frame can be captured without changing code:
+//!
+//! ```text
+//! MTL_CAPTURE_ENABLED=1 METAL_RS_CAPTURE=frame:3 cargo run
+//! MTL_CAPTURE_ENABLED=1 METAL_RS_CAPTURE=frames:10-12 METAL_RS_CAPTURE_PATH=/tmp/run.gputrace cargo run
+//! ```
+//!
+//! Frames are counted from 0. Metal only writes trace documents outside of
+//! Xcode when `MTL_CAPTURE_ENABLED=1` is set before the device is created.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut capture = FrameCapture::from_env(&device);
+//!
+//! loop {
+//!     if let Some(capture) = &mut capture {
+//!         capture.begin_frame()?;
+//!     }
+//!     render_frame();
+//!     if let Some(capture) = &mut capture {
+//!         capture.end_frame();
+//!     }
+//! }
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use mtl_foundation::Url;
+
+use super::{CaptureDescriptor, CaptureManager};
+use crate::Device;
+use crate::enums::{CaptureDestination, CaptureError};
+
+/// Environment variable selecting the frames [`FrameCapture`] records.
+pub const CAPTURE_ENV_VAR: &str = "METAL_RS_CAPTURE";
+
+/// Environment variable overriding the trace document path.
+pub const CAPTURE_PATH_ENV_VAR: &str = "METAL_RS_CAPTURE_PATH";
+
+// ============================================================================
+// CaptureGuard
+// ============================================================================
+
+/// An in-progress capture that stops when dropped.
+#[must_use = "the capture stops as soon as the guard is dropped"]
+pub struct CaptureGuard {
+    manager: CaptureManager,
+    path: PathBuf,
+    active: bool,
+}
+
+impl CaptureGuard {
+    /// Get the path of the trace document being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop the capture now instead of at drop.
+    pub fn stop(mut self) {
+        self.finish();
+    }
+
+    fn finish(&mut self) {
+        if self.active {
+            self.manager.stop_capture();
+            self.active = false;
+        }
+    }
+}
+
+impl Drop for CaptureGuard {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+impl std::fmt::Debug for CaptureGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CaptureGuard")
+            .field("path", &self.path)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+impl CaptureManager {
+    /// Start capturing all work on `device` to a `.gputrace` document at
+    /// `path`, stopping when the returned guard is dropped.
+    ///
+    /// Fails with [`CaptureError::NOT_SUPPORTED`] if trace documents can't
+    /// be written (for example when `MTL_CAPTURE_ENABLED` is unset) and with
+    /// [`CaptureError::INVALID_DESCRIPTOR`] if `path` isn't valid UTF-8.
+    pub fn capture_scope_guard(
+        device: &Device,
+        path: impl AsRef<Path>,
+    ) -> Result<CaptureGuard, CaptureError> {
+        let manager = Self::shared().ok_or(CaptureError::NOT_SUPPORTED)?;
+        if !manager.supports_destination(CaptureDestination::GPU_TRACE_DOCUMENT) {
+            return Err(CaptureError::NOT_SUPPORTED);
+        }
+
+        let path = path.as_ref().to_path_buf();
+        let url = Url::from_path(&path).ok_or(CaptureError::INVALID_DESCRIPTOR)?;
+        let descriptor = CaptureDescriptor::new().ok_or(CaptureError::INVALID_DESCRIPTOR)?;
+        descriptor.set_capture_device(device);
+        descriptor.set_destination(CaptureDestination::GPU_TRACE_DOCUMENT);
+        descriptor.set_output_url(&url);
+        manager.start_capture(&descriptor)?;
+
+        Ok(CaptureGuard {
+            manager,
+            path,
+            active: true,
+        })
+    }
+}
+
+// ============================================================================
+// CaptureTrigger
+// ============================================================================
+
+/// A range of frames to capture, parsed from `METAL_RS_CAPTURE`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CaptureTrigger {
+    /// First frame to capture.
+    pub first: u64,
+    /// Number of consecutive frames to capture.
+    pub count: u64,
+}
+
+impl CaptureTrigger {
+    /// Capture a single frame.
+    pub const fn frame(index: u64) -> Self {
+        Self {
+            first: index,
+            count: 1,
+        }
+    }
+
+    /// Parse `frame:N` or `frames:A-B` (inclusive).
+    ///
+    /// Returns `None` for any other value.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (kind, spec) = value.trim().split_once(':')?;
+        match kind {
+            "frame" => spec.parse().ok().map(Self::frame),
+            "frames" => {
+                let (first, last) = spec.split_once('-')?;
+                let first: u64 = first.parse().ok()?;
+                let last: u64 = last.parse().ok()?;
+                (last >= first).then(|| Self {
+                    first,
+                    count: last - first + 1,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Read the trigger from `METAL_RS_CAPTURE`.
+    pub fn from_env() -> Option<Self> {
+        std::env::var(CAPTURE_ENV_VAR)
+            .ok()
+            .and_then(|value| Self::parse(&value))
+    }
+
+    /// Get the last frame to capture.
+    pub const fn last(&self) -> u64 {
+        self.first + self.count.saturating_sub(1)
+    }
+
+    /// Check if `frame` falls within the trigger.
+    pub const fn contains(&self, frame: u64) -> bool {
+        frame >= self.first && frame <= self.last()
+    }
+}
+
+// ============================================================================
+// FrameCapture
+// ============================================================================
+
+/// Captures a configured range of frames to a trace document.
+pub struct FrameCapture {
+    device: Device,
+    trigger: CaptureTrigger,
+    path: PathBuf,
+    frame: u64,
+    guard: Option<CaptureGuard>,
+}
+
+impl FrameCapture {
+    /// Create a frame capture for `trigger`, writing to `path`.
+    pub fn new(device: &Device, trigger: CaptureTrigger, path: impl Into<PathBuf>) -> Self {
+        Self {
+            device: device.clone(),
+            trigger,
+            path: path.into(),
+            frame: 0,
+            guard: None,
+        }
+    }
+
+    /// Create a frame capture from `METAL_RS_CAPTURE`.
+    ///
+    /// The trace is written to `METAL_RS_CAPTURE_PATH`, or to
+    /// `metal_rs_frame_<first>.gputrace` in the working directory. Returns
+    /// `None` if the variable is unset or malformed.
+    pub fn from_env(device: &Device) -> Option<Self> {
+        let trigger = CaptureTrigger::from_env()?;
+        let path = std::env::var_os(CAPTURE_PATH_ENV_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| format!("metal_rs_frame_{}.gputrace", trigger.first).into());
+        Some(Self::new(device, trigger, path))
+    }
+
+    /// Get the trigger.
+    pub fn trigger(&self) -> CaptureTrigger {
+        self.trigger
+    }
+
+    /// Get the index of the current frame.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Check if the current frame is being captured.
+    pub fn is_capturing(&self) -> bool {
+        self.guard.is_some()
+    }
+
+    /// Call before encoding a frame. Starts the capture on the first
+    /// triggered frame.
+    pub fn begin_frame(&mut self) -> Result<(), CaptureError> {
+        if self.guard.is_none() && self.frame == self.trigger.first {
+            self.guard = Some(CaptureManager::capture_scope_guard(
+                &self.device,
+                &self.path,
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Call after committing a frame. Stops the capture after the last
+    /// triggered frame.
+    pub fn end_frame(&mut self) {
+        if self.frame >= self.trigger.last() {
+            self.guard = None;
+        }
+        self.frame += 1;
+    }
+}
+
+impl std::fmt::Debug for FrameCapture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameCapture")
+            .field("trigger", &self.trigger)
+            .field("path", &self.path)
+            .field("frame", &self.frame)
+            .field("capturing", &self.is_capturing())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_frame() {
+        assert_eq!(
+            CaptureTrigger::parse("frame:3"),
+            Some(CaptureTrigger::frame(3))
+        );
+        assert_eq!(
+            CaptureTrigger::parse(" frame:0 "),
+            Some(CaptureTrigger::frame(0))
+        );
+    }
+
+    #[test]
+    fn test_parse_frame_range() {
+        let trigger = CaptureTrigger::parse("frames:10-12").unwrap();
+        assert_eq!(trigger.first, 10);
+        assert_eq!(trigger.count, 3);
+        assert_eq!(trigger.last(), 12);
+        assert!(trigger.contains(11));
+        assert!(!trigger.contains(13));
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        assert_eq!(CaptureTrigger::parse(""), None);
+        assert_eq!(CaptureTrigger::parse("frame"), None);
+        assert_eq!(CaptureTrigger::parse("frame:x"), None);
+        assert_eq!(CaptureTrigger::parse("frames:5-2"), None);
+        assert_eq!(CaptureTrigger::parse("pass:1"), None);
+    }
+}
@@ -0,0 +1,924 @@
+//! Render target snapshots and image diffs for visual regression tests.
+//!
+//! [`RenderTargetCapture`] copies named render targets into CPU memory at
+//! the end of a frame and decodes them into [`CapturedImage`]s of RGBA
+//! `f32` pixels, so captures taken from different pixel formats, GPUs or
+//! machines compare directly. A [`CaptureSet`] holds one frame's images and
+//! can be written to and read from a directory, which lets a test harness
+//! keep baselines from an earlier commit. [`CaptureSet::compare`] produces a
+//! [`FrameDiffReport`] with a PSNR, a maximum error and a per-pixel
+//! threshold mask for every target.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let report = diff_code_paths(
+//!     &queue,
+//!     &DiffOptions::default(),
+//!     |command_buffer, capture| {
+//!         render_forward(command_buffer, &color, &depth);
+//!         capture.capture(command_buffer, "color", &color)?;
+//!         capture.capture(command_buffer, "depth", &depth)
+//!     },
+//!     |command_buffer, capture| {
+//!         render_deferred(command_buffer, &color, &depth);
+//!         capture.capture(command_buffer, "color", &color)?;
+//!         capture.capture(command_buffer, "depth", &depth)
+//!     },
+//! )?;
+//! assert!(report.passed(), "{report}");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::enums::{CommandBufferStatus, PixelFormat, ResourceOptions};
+use crate::types::{Origin, Size};
+use crate::{BlitCommandEncoder, Buffer, CommandBuffer, CommandQueue, Device, Texture};
+
+/// File extension used by [`CaptureSet::save`] and [`CaptureSet::load`].
+pub const CAPTURED_IMAGE_EXTENSION: &str = "mtlimg";
+
+/// Magic bytes at the start of a captured image file.
+const IMAGE_MAGIC: &[u8; 8] = b"MTLIMG01";
+
+/// Error produced while capturing or comparing render targets.
+#[derive(Debug)]
+pub enum FrameDiffError {
+    /// The target's pixel format has no CPU decoder (compressed, integer,
+    /// packed or stencil formats).
+    UnsupportedPixelFormat(PixelFormat),
+    /// The target is multisampled; capture its resolve texture instead.
+    Multisampled,
+    /// The target was created framebuffer-only and can't be copied.
+    FramebufferOnly,
+    /// A staging buffer or blit encoder could not be created.
+    Allocation,
+    /// A command buffer with captures failed.
+    CommandBuffer(Option<mtl_foundation::Error>),
+    /// Two images being compared have different dimensions.
+    SizeMismatch {
+        /// Width and height of the first image.
+        baseline: (u32, u32),
+        /// Width and height of the second image.
+        candidate: (u32, u32),
+    },
+    /// A captured image file is malformed.
+    InvalidImageFile,
+    /// Reading or writing a captured image failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for FrameDiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameDiffError::UnsupportedPixelFormat(format) => {
+                write!(
+                    f,
+                    "pixel format {:?} cannot be captured for diffing",
+                    format
+                )
+            }
+            FrameDiffError::Multisampled => {
+                write!(f, "multisampled textures cannot be captured")
+            }
+            FrameDiffError::FramebufferOnly => {
+                write!(f, "framebuffer-only textures cannot be captured")
+            }
+            FrameDiffError::Allocation => {
+                write!(f, "failed to allocate capture resources")
+            }
+            FrameDiffError::CommandBuffer(Some(err)) => {
+                write!(f, "capture command buffer failed: {}", err)
+            }
+            FrameDiffError::CommandBuffer(None) => {
+                write!(f, "capture command buffer failed")
+            }
+            FrameDiffError::SizeMismatch {
+                baseline,
+                candidate,
+            } => write!(
+                f,
+                "image sizes differ: {}x{} and {}x{}",
+                baseline.0, baseline.1, candidate.0, candidate.1
+            ),
+            FrameDiffError::InvalidImageFile => write!(f, "invalid captured image file"),
+            FrameDiffError::Io(err) => write!(f, "captured image I/O failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FrameDiffError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FrameDiffError::CommandBuffer(Some(err)) => Some(err),
+            FrameDiffError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FrameDiffError {
+    fn from(err: io::Error) -> Self {
+        FrameDiffError::Io(err)
+    }
+}
+
+// ============================================================================
+// CapturedImage
+// ============================================================================
+
+/// A decoded render target: rows of RGBA `f32` pixels, top row first.
+///
+/// Channels missing from the source format read as 0, and alpha as 1.
+/// Depth formats store depth in the red channel. Normalized formats are
+/// decoded to `0.0..=1.0` as stored, without sRGB conversion.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapturedImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl CapturedImage {
+    /// Create an image from `width * height` pixels.
+    ///
+    /// Returns `None` if the pixel count does not match.
+    pub fn new(width: u32, height: u32, pixels: Vec<[f32; 4]>) -> Option<Self> {
+        (pixels.len() == width as usize * height as usize).then_some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Decode tightly packed rows of `format` pixels.
+    ///
+    /// Returns `None` if the format is not supported or `bytes` is too
+    /// short.
+    pub fn decode(format: PixelFormat, width: u32, height: u32, bytes: &[u8]) -> Option<Self> {
+        let decode = pixel_decoder(format)?;
+        let bytes_per_pixel = format.bytes_per_pixel()?;
+        let count = width as usize * height as usize;
+        if bytes.len() < count * bytes_per_pixel {
+            return None;
+        }
+        let pixels = bytes
+            .chunks_exact(bytes_per_pixel)
+            .take(count)
+            .map(decode)
+            .collect();
+        Some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Get the width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get every pixel, row by row.
+    pub fn pixels(&self) -> &[[f32; 4]] {
+        &self.pixels
+    }
+
+    /// Get the pixel at (`x`, `y`).
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[f32; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.pixels[y as usize * self.width as usize + x as usize])
+    }
+
+    /// Convert to RGBA8, clamping each channel to `0.0..=1.0`.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| pixel.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+            .collect()
+    }
+
+    /// Encode the image in the captured image file format: the magic
+    /// `MTLIMG01`, little-endian `u32` width and height, then little-endian
+    /// `f32` RGBA pixels.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + self.pixels.len() * 16);
+        bytes.extend_from_slice(IMAGE_MAGIC);
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        for channel in self.pixels.iter().flatten() {
+            bytes.extend_from_slice(&channel.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode an image written by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FrameDiffError> {
+        let header = bytes.get(..16).ok_or(FrameDiffError::InvalidImageFile)?;
+        if &header[..8] != IMAGE_MAGIC {
+            return Err(FrameDiffError::InvalidImageFile);
+        }
+        let width = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        let height = u32::from_le_bytes(header[12..16].try_into().unwrap());
+        let data = &bytes[16..];
+        if data.len() as u64 != width as u64 * height as u64 * 16 {
+            return Err(FrameDiffError::InvalidImageFile);
+        }
+        let pixels = data
+            .chunks_exact(16)
+            .map(|pixel| std::array::from_fn(|i| le_f32(&pixel[i * 4..])))
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Compare against `candidate`, treating `self` as the expected image.
+    pub fn compare(
+        &self,
+        candidate: &CapturedImage,
+        options: &DiffOptions,
+    ) -> Result<ImageDiff, FrameDiffError> {
+        if (self.width, self.height) != (candidate.width, candidate.height) {
+            return Err(FrameDiffError::SizeMismatch {
+                baseline: (self.width, self.height),
+                candidate: (candidate.width, candidate.height),
+            });
+        }
+        let channels = if options.compare_alpha { 4 } else { 3 };
+        let mut squared_error = 0.0f64;
+        let mut max_error = 0.0f32;
+        let mut differing_pixels = 0;
+        let mask = self
+            .pixels
+            .iter()
+            .zip(&candidate.pixels)
+            .map(|(expected, actual)| {
+                let mut pixel_error = 0.0f32;
+                for c in 0..channels {
+                    let error = (expected[c] - actual[c]).abs();
+                    // NaN in only one image counts as the largest error.
+                    let error = if error.is_nan() && expected[c].is_nan() != actual[c].is_nan() {
+                        f32::INFINITY
+                    } else if error.is_nan() {
+                        0.0
+                    } else {
+                        error
+                    };
+                    pixel_error = pixel_error.max(error);
+                    squared_error += f64::from(error.min(1.0)).powi(2);
+                }
+                max_error = max_error.max(pixel_error);
+                let differs = pixel_error > options.threshold;
+                differing_pixels += differs as usize;
+                differs
+            })
+            .collect();
+
+        let samples = (self.pixels.len() * channels).max(1);
+        let mean_squared_error = squared_error / samples as f64;
+        let psnr = if mean_squared_error == 0.0 {
+            f64::INFINITY
+        } else {
+            -10.0 * mean_squared_error.log10()
+        };
+        Ok(ImageDiff {
+            width: self.width,
+            height: self.height,
+            psnr,
+            mean_squared_error,
+            max_error,
+            differing_pixels,
+            passed: differing_pixels <= options.max_differing_pixels
+                && options.min_psnr.is_none_or(|min| psnr >= min),
+            mask,
+        })
+    }
+}
+
+fn le_f32(bytes: &[u8]) -> f32 {
+    f32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+fn le_u16(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes(bytes[..2].try_into().unwrap())
+}
+
+fn unorm8(value: u8) -> f32 {
+    value as f32 / 255.0
+}
+
+fn unorm16(bytes: &[u8]) -> f32 {
+    le_u16(bytes) as f32 / 65535.0
+}
+
+fn half(bytes: &[u8]) -> f32 {
+    let bits = le_u16(bytes);
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (bits >> 10) & 0x1f;
+    let mantissa = (bits & 0x3ff) as f32;
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent as i32 - 15),
+    }
+}
+
+fn rgb10a2(bytes: &[u8]) -> [f32; 4] {
+    let bits = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+    let channel = |shift: u32| ((bits >> shift) & 0x3ff) as f32 / 1023.0;
+    [
+        channel(0),
+        channel(10),
+        channel(20),
+        (bits >> 30) as f32 / 3.0,
+    ]
+}
+
+/// Converts one pixel's bytes to RGBA.
+type PixelDecoder = fn(&[u8]) -> [f32; 4];
+
+/// Get the decoder for `format`.
+fn pixel_decoder(format: PixelFormat) -> Option<PixelDecoder> {
+    let decode: PixelDecoder = match format {
+        PixelFormat::A8_UNORM => |p| [0.0, 0.0, 0.0, unorm8(p[0])],
+        PixelFormat::R8_UNORM | PixelFormat::R8_UNORM_SRGB => |p| [unorm8(p[0]), 0.0, 0.0, 1.0],
+        PixelFormat::RG8_UNORM | PixelFormat::RG8_UNORM_SRGB => {
+            |p| [unorm8(p[0]), unorm8(p[1]), 0.0, 1.0]
+        }
+        PixelFormat::RGBA8_UNORM | PixelFormat::RGBA8_UNORM_SRGB => {
+            |p| std::array::from_fn(|i| unorm8(p[i]))
+        }
+        PixelFormat::BGRA8_UNORM | PixelFormat::BGRA8_UNORM_SRGB => {
+            |p| [unorm8(p[2]), unorm8(p[1]), unorm8(p[0]), unorm8(p[3])]
+        }
+        PixelFormat::R16_UNORM | PixelFormat::DEPTH16_UNORM => |p| [unorm16(p), 0.0, 0.0, 1.0],
+        PixelFormat::RG16_UNORM => |p| [unorm16(p), unorm16(&p[2..]), 0.0, 1.0],
+        PixelFormat::RGBA16_UNORM => |p| std::array::from_fn(|i| unorm16(&p[i * 2..])),
+        PixelFormat::R16_FLOAT => |p| [half(p), 0.0, 0.0, 1.0],
+        PixelFormat::RG16_FLOAT => |p| [half(p), half(&p[2..]), 0.0, 1.0],
+        PixelFormat::RGBA16_FLOAT => |p| std::array::from_fn(|i| half(&p[i * 2..])),
+        PixelFormat::R32_FLOAT | PixelFormat::DEPTH32_FLOAT => |p| [le_f32(p), 0.0, 0.0, 1.0],
+        PixelFormat::RG32_FLOAT => |p| [le_f32(p), le_f32(&p[4..]), 0.0, 1.0],
+        PixelFormat::RGBA32_FLOAT => |p| std::array::from_fn(|i| le_f32(&p[i * 4..])),
+        PixelFormat::RGB10A2_UNORM => rgb10a2,
+        PixelFormat::BGR10A2_UNORM => |p| {
+            let [b, g, r, a] = rgb10a2(p);
+            [r, g, b, a]
+        },
+        _ => return None,
+    };
+    Some(decode)
+}
+
+// ============================================================================
+// Diffing
+// ============================================================================
+
+/// Thresholds for comparing images.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DiffOptions {
+    /// Largest per-channel difference for a pixel to count as matching.
+    ///
+    /// The default, one 8-bit step, absorbs rounding differences between
+    /// GPUs.
+    pub threshold: f32,
+    /// Number of pixels allowed to exceed [`threshold`](Self::threshold)
+    /// before a comparison fails.
+    pub max_differing_pixels: usize,
+    /// Lowest PSNR, in decibels, for a comparison to pass.
+    pub min_psnr: Option<f64>,
+    /// Include the alpha channel in the comparison.
+    pub compare_alpha: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0 / 255.0,
+            max_differing_pixels: 0,
+            min_psnr: None,
+            compare_alpha: true,
+        }
+    }
+}
+
+/// The result of comparing two images of the same size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageDiff {
+    /// Width of both images.
+    pub width: u32,
+    /// Height of both images.
+    pub height: u32,
+    /// Peak signal-to-noise ratio in decibels, with errors clamped to 1.
+    /// Infinite for identical images.
+    pub psnr: f64,
+    /// Mean squared per-channel error, with errors clamped to 1.
+    pub mean_squared_error: f64,
+    /// Largest per-channel difference.
+    pub max_error: f32,
+    /// Number of pixels whose difference exceeds the threshold.
+    pub differing_pixels: usize,
+    /// Whether the comparison met the [`DiffOptions`] limits.
+    pub passed: bool,
+    /// One entry per pixel, row by row: `true` where the difference exceeds
+    /// the threshold.
+    pub mask: Vec<bool>,
+}
+
+impl ImageDiff {
+    /// Get the fraction of pixels that differ.
+    pub fn differing_fraction(&self) -> f64 {
+        self.differing_pixels as f64 / self.mask.len().max(1) as f64
+    }
+
+    /// Render the mask as RGBA8: red where pixels differ, black elsewhere.
+    pub fn mask_rgba8(&self) -> Vec<u8> {
+        self.mask
+            .iter()
+            .flat_map(|&differs| {
+                if differs {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 0, 255]
+                }
+            })
+            .collect()
+    }
+}
+
+/// The outcome of comparing one named target.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TargetDiff {
+    /// Both sets have the target with the same size.
+    Compared(ImageDiff),
+    /// The images have different sizes.
+    SizeMismatch {
+        /// Width and height in the baseline.
+        baseline: (u32, u32),
+        /// Width and height in the candidate.
+        candidate: (u32, u32),
+    },
+    /// Only the candidate has the target.
+    MissingInBaseline,
+    /// Only the baseline has the target.
+    MissingInCandidate,
+}
+
+impl TargetDiff {
+    /// Check if the target was compared and passed.
+    pub fn passed(&self) -> bool {
+        matches!(self, TargetDiff::Compared(diff) if diff.passed)
+    }
+}
+
+/// Per-target results of comparing two [`CaptureSet`]s.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameDiffReport {
+    /// Results keyed by target name.
+    pub targets: BTreeMap<String, TargetDiff>,
+}
+
+impl FrameDiffReport {
+    /// Check if every target was present in both sets and passed.
+    pub fn passed(&self) -> bool {
+        self.targets.values().all(TargetDiff::passed)
+    }
+
+    /// Get the names of the targets that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &str> {
+        self.targets
+            .iter()
+            .filter(|(_, diff)| !diff.passed())
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+impl fmt::Display for FrameDiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, diff) in &self.targets {
+            match diff {
+                TargetDiff::Compared(diff) => writeln!(
+                    f,
+                    "{}: {} ({} of {} pixels differ, max error {:.4}, PSNR {:.2} dB)",
+                    name,
+                    if diff.passed { "pass" } else { "FAIL" },
+                    diff.differing_pixels,
+                    diff.mask.len(),
+                    diff.max_error,
+                    diff.psnr
+                )?,
+                TargetDiff::SizeMismatch {
+                    baseline,
+                    candidate,
+                } => writeln!(
+                    f,
+                    "{}: FAIL (size {}x{}, expected {}x{})",
+                    name, candidate.0, candidate.1, baseline.0, baseline.1
+                )?,
+                TargetDiff::MissingInBaseline => {
+                    writeln!(f, "{}: FAIL (missing from baseline)", name)?
+                }
+                TargetDiff::MissingInCandidate => writeln!(f, "{}: FAIL (not captured)", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// CaptureSet
+// ============================================================================
+
+/// The images captured from one frame, keyed by target name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CaptureSet {
+    images: BTreeMap<String, CapturedImage>,
+}
+
+impl CaptureSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the image for `name`.
+    pub fn insert(&mut self, name: impl Into<String>, image: CapturedImage) {
+        self.images.insert(name.into(), image);
+    }
+
+    /// Get the image for `name`.
+    pub fn get(&self, name: &str) -> Option<&CapturedImage> {
+        self.images.get(name)
+    }
+
+    /// Iterate over the images in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &CapturedImage)> {
+        self.images
+            .iter()
+            .map(|(name, image)| (name.as_str(), image))
+    }
+
+    /// Get the number of images.
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Check if the set has no images.
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Compare every target against `candidate`, treating `self` as the
+    /// baseline.
+    pub fn compare(&self, candidate: &CaptureSet, options: &DiffOptions) -> FrameDiffReport {
+        let mut targets = BTreeMap::new();
+        for (name, expected) in &self.images {
+            let diff = match candidate.images.get(name) {
+                Some(actual) => match expected.compare(actual, options) {
+                    Ok(diff) => TargetDiff::Compared(diff),
+                    Err(_) => TargetDiff::SizeMismatch {
+                        baseline: (expected.width, expected.height),
+                        candidate: (actual.width, actual.height),
+                    },
+                },
+                None => TargetDiff::MissingInCandidate,
+            };
+            targets.insert(name.clone(), diff);
+        }
+        for name in candidate.images.keys() {
+            if !self.images.contains_key(name) {
+                targets.insert(name.clone(), TargetDiff::MissingInBaseline);
+            }
+        }
+        FrameDiffReport { targets }
+    }
+
+    /// Write each image to `directory` as `<name>.mtlimg`, creating the
+    /// directory if needed.
+    ///
+    /// Target names must be valid file names.
+    pub fn save(&self, directory: impl AsRef<Path>) -> Result<(), FrameDiffError> {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory)?;
+        for (name, image) in &self.images {
+            let path = directory.join(format!("{}.{}", name, CAPTURED_IMAGE_EXTENSION));
+            fs::write(path, image.to_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Read every `.mtlimg` file in `directory`, named by file stem.
+    pub fn load(directory: impl AsRef<Path>) -> Result<Self, FrameDiffError> {
+        let mut set = Self::new();
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_none_or(|ext| ext != CAPTURED_IMAGE_EXTENSION)
+            {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let image = CapturedImage::from_bytes(&fs::read(&path)?)?;
+            set.insert(name, image);
+        }
+        Ok(set)
+    }
+}
+
+// ============================================================================
+// RenderTargetCapture
+// ============================================================================
+
+/// A target copied into a staging buffer, waiting for its command buffer.
+struct PendingTarget {
+    name: String,
+    format: PixelFormat,
+    width: u32,
+    height: u32,
+    staging: Buffer,
+    command_buffer: CommandBuffer,
+}
+
+/// Copies render targets to the CPU for diffing.
+pub struct RenderTargetCapture {
+    device: Device,
+    managed: bool,
+    pending: Vec<PendingTarget>,
+}
+
+impl RenderTargetCapture {
+    /// Create a capture for targets on `device`.
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+            managed: !device.has_unified_memory(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Encode a copy of `texture`'s first mipmap level and slice on
+    /// `command_buffer`, stored under `name`.
+    ///
+    /// Encode it after the pass that writes `texture`.
+    pub fn capture(
+        &mut self,
+        command_buffer: &CommandBuffer,
+        name: &str,
+        texture: &Texture,
+    ) -> Result<(), FrameDiffError> {
+        let format = texture.pixel_format();
+        let bytes_per_pixel = match (pixel_decoder(format), format.bytes_per_pixel()) {
+            (Some(_), Some(bytes_per_pixel)) => bytes_per_pixel,
+            _ => return Err(FrameDiffError::UnsupportedPixelFormat(format)),
+        };
+        if texture.sample_count() > 1 {
+            return Err(FrameDiffError::Multisampled);
+        }
+        if texture.is_framebuffer_only() {
+            return Err(FrameDiffError::FramebufferOnly);
+        }
+
+        let width = texture.width();
+        let height = texture.height();
+        let bytes_per_row = width * bytes_per_pixel;
+        let options = if self.managed {
+            ResourceOptions::STORAGE_MODE_MANAGED
+        } else {
+            ResourceOptions::STORAGE_MODE_SHARED
+        };
+        let staging = self
+            .device
+            .new_buffer((bytes_per_row * height).max(1), options)
+            .ok_or(FrameDiffError::Allocation)?;
+
+        let blit = unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+            .ok_or(FrameDiffError::Allocation)?;
+        blit.set_label("Frame Diff Capture");
+        blit.copy_from_texture_to_buffer(
+            texture,
+            0,
+            0,
+            Origin::new(0, 0, 0),
+            Size::new(width, height, 1),
+            &staging,
+            0,
+            bytes_per_row,
+            bytes_per_row * height,
+        );
+        if self.managed {
+            blit.synchronize_buffer(&staging);
+        }
+        blit.end_encoding();
+
+        self.pending.push(PendingTarget {
+            name: name.to_owned(),
+            format,
+            width: width as u32,
+            height: height as u32,
+            staging,
+            command_buffer: command_buffer.clone(),
+        });
+        Ok(())
+    }
+
+    /// Get the number of captures encoded so far.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Check if nothing has been captured.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Wait for every capture's command buffer and decode the images.
+    ///
+    /// The command buffers must have been committed.
+    pub fn finish(self) -> Result<CaptureSet, FrameDiffError> {
+        let mut set = CaptureSet::new();
+        for target in self.pending {
+            target.command_buffer.wait_until_completed();
+            if target.command_buffer.status() != CommandBufferStatus::COMPLETED {
+                return Err(FrameDiffError::CommandBuffer(target.command_buffer.error()));
+            }
+            let contents = target
+                .staging
+                .contents()
+                .ok_or(FrameDiffError::Allocation)?;
+            let bytes = unsafe {
+                std::slice::from_raw_parts(contents as *const u8, target.staging.length())
+            };
+            let image = CapturedImage::decode(target.format, target.width, target.height, bytes)
+                .ok_or(FrameDiffError::UnsupportedPixelFormat(target.format))?;
+            set.insert(target.name, image);
+        }
+        Ok(set)
+    }
+}
+
+impl fmt::Debug for RenderTargetCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenderTargetCapture")
+            .field(
+                "pending",
+                &self
+                    .pending
+                    .iter()
+                    .map(|target| target.name.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Run `encode` on a new command buffer from `queue`, commit it, and return
+/// the targets it captured.
+pub fn capture_code_path<F>(queue: &CommandQueue, encode: F) -> Result<CaptureSet, FrameDiffError>
+where
+    F: FnOnce(&CommandBuffer, &mut RenderTargetCapture) -> Result<(), FrameDiffError>,
+{
+    let command_buffer = queue.command_buffer().ok_or(FrameDiffError::Allocation)?;
+    command_buffer.set_label("Frame Diff");
+    let mut capture = RenderTargetCapture::new(&queue.device());
+    encode(&command_buffer, &mut capture)?;
+    command_buffer.commit();
+    capture.finish()
+}
+
+/// Capture the targets of two code paths and compare them, treating
+/// `baseline` as the expected output.
+pub fn diff_code_paths<A, B>(
+    queue: &CommandQueue,
+    options: &DiffOptions,
+    baseline: A,
+    candidate: B,
+) -> Result<FrameDiffReport, FrameDiffError>
+where
+    A: FnOnce(&CommandBuffer, &mut RenderTargetCapture) -> Result<(), FrameDiffError>,
+    B: FnOnce(&CommandBuffer, &mut RenderTargetCapture) -> Result<(), FrameDiffError>,
+{
+    let baseline = capture_code_path(queue, baseline)?;
+    let candidate = capture_code_path(queue, candidate)?;
+    Ok(baseline.compare(&candidate, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, color: [f32; 4]) -> CapturedImage {
+        CapturedImage::new(width, height, vec![color; (width * height) as usize]).unwrap()
+    }
+
+    #[test]
+    fn test_decode() {
+        let bgra = CapturedImage::decode(PixelFormat::BGRA8_UNORM, 1, 1, &[0, 51, 255, 255]);
+        assert_eq!(bgra.unwrap().pixel(0, 0), Some([1.0, 0.2, 0.0, 1.0]));
+
+        // 1.0, -2.0, 0.5, 65504 as half floats.
+        let half_bytes = [0x3c00u16, 0xc000, 0x3800, 0x7bff]
+            .iter()
+            .flat_map(|h| h.to_le_bytes())
+            .collect::<Vec<_>>();
+        let half = CapturedImage::decode(PixelFormat::RGBA16_FLOAT, 1, 1, &half_bytes).unwrap();
+        assert_eq!(half.pixel(0, 0), Some([1.0, -2.0, 0.5, 65504.0]));
+
+        assert!(CapturedImage::decode(PixelFormat::RGBA8_UINT, 1, 1, &[0; 4]).is_none());
+        assert!(CapturedImage::decode(PixelFormat::RGBA8_UNORM, 2, 1, &[0; 4]).is_none());
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = solid(4, 4, [0.5, 0.5, 0.5, 1.0]);
+        let identical = baseline
+            .compare(&baseline, &DiffOptions::default())
+            .unwrap();
+        assert!(identical.passed);
+        assert_eq!(identical.psnr, f64::INFINITY);
+
+        let mut pixels = baseline.pixels().to_vec();
+        pixels[5] = [0.6, 0.5, 0.5, 1.0];
+        let candidate = CapturedImage::new(4, 4, pixels).unwrap();
+        let diff = baseline
+            .compare(&candidate, &DiffOptions::default())
+            .unwrap();
+        assert!(!diff.passed);
+        assert_eq!(diff.differing_pixels, 1);
+        assert!(diff.mask[5] && !diff.mask[4]);
+        assert!((diff.max_error - 0.1).abs() < 1e-6);
+        assert!(diff.psnr > 30.0 && diff.psnr.is_finite());
+
+        let lenient = DiffOptions {
+            max_differing_pixels: 1,
+            ..DiffOptions::default()
+        };
+        assert!(baseline.compare(&candidate, &lenient).unwrap().passed);
+        assert!(matches!(
+            baseline.compare(&solid(2, 2, [0.0; 4]), &lenient),
+            Err(FrameDiffError::SizeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_capture_sets() {
+        let mut baseline = CaptureSet::new();
+        baseline.insert("color", solid(2, 2, [1.0, 0.0, 0.0, 1.0]));
+        baseline.insert("depth", solid(2, 2, [0.5, 0.0, 0.0, 1.0]));
+        let mut candidate = CaptureSet::new();
+        candidate.insert("color", solid(2, 2, [1.0, 0.0, 0.0, 1.0]));
+        candidate.insert("normals", solid(2, 2, [0.0; 4]));
+
+        let report = baseline.compare(&candidate, &DiffOptions::default());
+        assert!(!report.passed());
+        assert!(report.targets["color"].passed());
+        assert_eq!(report.targets["depth"], TargetDiff::MissingInCandidate);
+        assert_eq!(report.targets["normals"], TargetDiff::MissingInBaseline);
+        assert_eq!(report.failures().collect::<Vec<_>>(), ["depth", "normals"]);
+
+        let image = solid(3, 1, [0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(CapturedImage::from_bytes(&image.to_bytes()).unwrap(), image);
+        assert!(CapturedImage::from_bytes(b"MTLIMG01").is_err());
+    }
+
+    #[test]
+    fn test_capture_code_paths() {
+        use crate::TextureDescriptor;
+        use crate::enums::{StorageMode, TextureUsage};
+
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().unwrap();
+        let descriptor =
+            TextureDescriptor::texture_2d_descriptor(PixelFormat::RGBA8_UNORM, 8, 8, false)
+                .unwrap();
+        descriptor.set_storage_mode(StorageMode::PRIVATE);
+        descriptor.set_usage(TextureUsage::RENDER_TARGET);
+        let target = device.new_texture_with_descriptor(&descriptor).unwrap();
+
+        let report = diff_code_paths(
+            &queue,
+            &DiffOptions::default(),
+            |command_buffer, capture| capture.capture(command_buffer, "color", &target),
+            |command_buffer, capture| capture.capture(command_buffer, "color", &target),
+        )
+        .unwrap();
+        assert!(report.passed(), "{report}");
+    }
+}
@@ -21,6 +21,33 @@
 //!
 //! manager.stop_capture();
 //! ```
+//!
+//! For one-off captures, [`CaptureManager::capture_scope_guard`] and
+//! [`FrameCapture`] (driven by the `METAL_RS_CAPTURE` environment variable)
+//! handle starting and stopping.
+//!
+//! [`RenderTargetCapture`] and [`diff_code_paths`] copy render targets to
+//! the CPU and compare them for visual regression tests.
+//!
+//! [`FailureReport`] summarizes a failed command buffer, and
+//! [`FailureWatch`] reports every failure on a queue and can capture the
+//! frame that follows.
+
+mod failure_report;
+mod frame_capture;
+mod frame_diff;
+
+#[cfg(feature = "blocks")]
+pub use failure_report::FailureWatch;
+pub use failure_report::{EncoderFailure, FailureReport};
+pub use frame_capture::{
+    CAPTURE_ENV_VAR, CAPTURE_PATH_ENV_VAR, CaptureGuard, CaptureTrigger, FrameCapture,
+};
+pub use frame_diff::{
+    CAPTURED_IMAGE_EXTENSION, CaptureSet, CapturedImage, DiffOptions, FrameDiffError,
+    FrameDiffReport, ImageDiff, RenderTargetCapture, TargetDiff, capture_code_path,
+    diff_code_paths,
+};
 
 use std::ffi::c_void;
 use std::ptr::NonNull;
@@ -5,12 +5,34 @@
 //! A command buffer stores encoded commands that the GPU will execute.
 
 use std::ffi::c_void;
+use std::fmt;
 use std::ptr::NonNull;
+use std::time::{Duration, Instant};
 
 use mtl_foundation::{Referencing, TimeInterval, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, sel};
 
 use crate::enums::{CommandBufferErrorOption, CommandBufferStatus, DispatchType};
+use crate::log_state::LogState;
+use crate::object_ptrs::with_object_ptrs;
+use crate::residency_set::ResidencySet;
+
+// ============================================================================
+// Timeout
+// ============================================================================
+
+/// Error returned when a bounded wait elapses before the command buffer
+/// finishes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timeout;
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out waiting for command buffer completion")
+    }
+}
+
+impl std::error::Error for Timeout {}
 
 // ============================================================================
 // CommandBufferDescriptor
@@ -105,22 +127,40 @@ impl CommandBufferDescriptor {
     /// Get the log state for the command buffer.
     ///
     /// C++ equivalent: `LogState* logState() const`
+    pub fn log_state(&self) -> Option<LogState> {
+        unsafe {
+            let ptr = self.log_state_ptr();
+            if !ptr.is_null() {
+                let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            }
+            LogState::from_raw(ptr)
+        }
+    }
+
+    /// Get the log state for the command buffer, by raw pointer.
     ///
-    /// Returns a raw pointer to the log state object.
+    /// C++ equivalent: `LogState* logState() const`
     #[inline]
-    pub fn log_state(&self) -> *mut c_void {
+    pub fn log_state_ptr(&self) -> *mut c_void {
         unsafe { msg_send_0(self.as_ptr(), sel!(logState)) }
     }
 
     /// Set the log state for the command buffer.
     ///
     /// C++ equivalent: `void setLogState(const LogState* logState)`
+    pub fn set_log_state(&self, log_state: &LogState) {
+        unsafe { self.set_log_state_ptr(log_state.as_ptr()) }
+    }
+
+    /// Set the log state for the command buffer, by raw pointer.
+    ///
+    /// C++ equivalent: `void setLogState(const LogState* logState)`
     ///
     /// # Safety
     ///
     /// The log_state pointer must be valid or null.
     #[inline]
-    pub unsafe fn set_log_state(&self, log_state: *const c_void) {
+    pub unsafe fn set_log_state_ptr(&self, log_state: *const c_void) {
         unsafe {
             msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setLogState:), log_state);
         }
@@ -210,6 +250,8 @@ impl CommandBuffer {
     /// C++ equivalent: `void commit()`
     #[inline]
     pub fn commit(&self) {
+        #[cfg(feature = "validation")]
+        crate::validation::commit(self.as_ptr());
         unsafe {
             msg_send_0::<()>(self.as_ptr(), sel!(commit));
         }
@@ -225,6 +267,63 @@ impl CommandBuffer {
         }
     }
 
+    /// Block until the command buffer completes execution or `timeout`
+    /// elapses.
+    ///
+    /// Unlike [`wait_until_completed`](Self::wait_until_completed), this
+    /// returns if a GPU fault leaves the buffer stuck. A buffer that ends in
+    /// [`CommandBufferStatus::ERROR`] counts as finished; check
+    /// [`status`](Self::status) afterwards.
+    ///
+    /// If the buffer has not been committed yet, a completed handler wakes
+    /// the caller once another thread commits it and it finishes. Metal does
+    /// not accept handlers after commit, so committed buffers are polled
+    /// with a backoff capped at one millisecond.
+    pub fn wait_until_completed_timeout(&self, timeout: Duration) -> Result<(), Timeout> {
+        let deadline = Instant::now() + timeout;
+        let status = self.status();
+        if is_finished(status) {
+            return Ok(());
+        }
+
+        #[cfg(feature = "blocks")]
+        if status == CommandBufferStatus::NOT_ENQUEUED || status == CommandBufferStatus::ENQUEUED {
+            use std::sync::{Arc, Condvar, Mutex};
+
+            let signal = Arc::new((Mutex::new(false), Condvar::new()));
+            let notified = Arc::clone(&signal);
+            self.add_completed_handler(move |_| {
+                let (done, condvar) = &*notified;
+                *done.lock().unwrap() = true;
+                condvar.notify_all();
+            });
+
+            let (done, condvar) = &*signal;
+            let mut finished = done.lock().unwrap();
+            while !*finished {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(Timeout);
+                }
+                finished = condvar.wait_timeout(finished, remaining).unwrap().0;
+            }
+            return Ok(());
+        }
+
+        let mut backoff = Duration::from_micros(10);
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Timeout);
+            }
+            std::thread::sleep(backoff.min(deadline - now));
+            if is_finished(self.status()) {
+                return Ok(());
+            }
+            backoff = (backoff * 2).min(Duration::from_millis(1));
+        }
+    }
+
     /// Block until the command buffer is scheduled for execution.
     ///
     /// C++ equivalent: `void waitUntilScheduled()`
@@ -271,6 +370,21 @@ impl CommandBuffer {
         }
     }
 
+    /// Get the execution state of each encoder from the error's user info,
+    /// in encoding order.
+    ///
+    /// Empty if the command buffer hasn't failed or wasn't created with
+    /// [`CommandBufferErrorOption::ENCODER_EXECUTION_STATUS`]. See
+    /// [`FailureReport`](crate::FailureReport) for a summary that includes
+    /// the shader logs.
+    ///
+    /// C++ equivalent: `error()->userInfo()->object(MTL::CommandBufferEncoderInfoErrorKey)`
+    pub fn error_user_info(&self) -> Vec<crate::CommandBufferEncoderInfo> {
+        self.error()
+            .map(|error| crate::CommandBufferEncoderInfo::from_error(&error))
+            .unwrap_or_default()
+    }
+
     /// Get the time when the GPU started executing the command buffer.
     ///
     /// C++ equivalent: `CFTimeInterval GPUStartTime() const`
@@ -318,6 +432,7 @@ impl CommandBuffer {
     /// Add a handler to be called when the command buffer completes.
     ///
     /// C++ equivalent: `void addCompletedHandler(void (^)(CommandBuffer*))`
+    #[cfg(feature = "blocks")]
     pub fn add_completed_handler<F>(&self, handler: F)
     where
         F: Fn(&CommandBuffer) + Send + 'static,
@@ -348,6 +463,7 @@ impl CommandBuffer {
     /// Add a handler to be called when the command buffer is scheduled.
     ///
     /// C++ equivalent: `void addScheduledHandler(void (^)(CommandBuffer*))`
+    #[cfg(feature = "blocks")]
     pub fn add_scheduled_handler<F>(&self, handler: F)
     where
         F: Fn(&CommandBuffer) + Send + 'static,
@@ -468,24 +584,44 @@ impl CommandBuffer {
     /// Use a residency set for this command buffer.
     ///
     /// C++ equivalent: `void useResidencySet(const ResidencySet*)`
+    pub fn use_residency_set(&self, residency_set: &ResidencySet) {
+        unsafe { self.use_residency_set_ptr(residency_set.as_ptr()) }
+    }
+
+    /// Use multiple residency sets for this command buffer.
+    ///
+    /// C++ equivalent: `void useResidencySets(const ResidencySet* const*, NS::UInteger count)`
+    pub fn use_residency_sets(&self, residency_sets: &[&ResidencySet]) {
+        with_object_ptrs(residency_sets, |ptrs| unsafe {
+            self.use_residency_sets_ptr(ptrs.as_ptr(), ptrs.len() as UInteger)
+        })
+    }
+
+    /// Use a residency set for this command buffer, by raw pointer.
+    ///
+    /// C++ equivalent: `void useResidencySet(const ResidencySet*)`
     ///
     /// # Safety
     ///
     /// The residency_set pointer must be valid.
-    pub unsafe fn use_residency_set(&self, residency_set: *const c_void) {
+    pub unsafe fn use_residency_set_ptr(&self, residency_set: *const c_void) {
         unsafe {
             msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(useResidencySet:), residency_set);
         }
     }
 
-    /// Use multiple residency sets for this command buffer.
+    /// Use multiple residency sets for this command buffer, by raw pointer.
     ///
     /// C++ equivalent: `void useResidencySets(const ResidencySet* const*, NS::UInteger count)`
     ///
     /// # Safety
     ///
     /// The residency_sets pointer must be valid and point to count valid pointers.
-    pub unsafe fn use_residency_sets(&self, residency_sets: *const *const c_void, count: UInteger) {
+    pub unsafe fn use_residency_sets_ptr(
+        &self,
+        residency_sets: *const *const c_void,
+        count: UInteger,
+    ) {
         unsafe {
             mtl_sys::msg_send_2::<(), *const *const c_void, UInteger>(
                 self.as_ptr(),
@@ -518,11 +654,15 @@ impl CommandBuffer {
     ///
     /// Returns a raw pointer to the encoder. The caller is responsible for ending encoding.
     pub fn blit_command_encoder(&self) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(self.as_ptr(), "blit_command_encoder");
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(blitCommandEncoder));
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, std::ptr::null());
             ptr
         }
     }
@@ -538,6 +678,8 @@ impl CommandBuffer {
         &self,
         descriptor: *const c_void,
     ) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(self.as_ptr(), "blit_command_encoder_with_descriptor");
         unsafe {
             let ptr: *mut c_void = msg_send_1(
                 self.as_ptr(),
@@ -547,6 +689,8 @@ impl CommandBuffer {
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, std::ptr::null());
             ptr
         }
     }
@@ -557,11 +701,15 @@ impl CommandBuffer {
     ///
     /// Returns a raw pointer to the encoder. The caller is responsible for ending encoding.
     pub fn compute_command_encoder(&self) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(self.as_ptr(), "compute_command_encoder");
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(computeCommandEncoder));
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, std::ptr::null());
             ptr
         }
     }
@@ -575,6 +723,11 @@ impl CommandBuffer {
         &self,
         dispatch_type: DispatchType,
     ) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(
+            self.as_ptr(),
+            "compute_command_encoder_with_dispatch_type",
+        );
         unsafe {
             let ptr: *mut c_void = msg_send_1(
                 self.as_ptr(),
@@ -584,6 +737,8 @@ impl CommandBuffer {
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, std::ptr::null());
             ptr
         }
     }
@@ -599,6 +754,8 @@ impl CommandBuffer {
         &self,
         descriptor: *const c_void,
     ) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(self.as_ptr(), "compute_command_encoder_with_descriptor");
         unsafe {
             let ptr: *mut c_void = msg_send_1(
                 self.as_ptr(),
@@ -608,6 +765,8 @@ impl CommandBuffer {
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, std::ptr::null());
             ptr
         }
     }
@@ -623,6 +782,8 @@ impl CommandBuffer {
         &self,
         descriptor: *const c_void,
     ) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(self.as_ptr(), "render_command_encoder_with_descriptor");
         unsafe {
             let ptr: *mut c_void = msg_send_1(
                 self.as_ptr(),
@@ -632,6 +793,8 @@ impl CommandBuffer {
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, descriptor);
             ptr
         }
     }
@@ -647,6 +810,11 @@ impl CommandBuffer {
         &self,
         descriptor: *const c_void,
     ) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(
+            self.as_ptr(),
+            "parallel_render_command_encoder_with_descriptor",
+        );
         unsafe {
             let ptr: *mut c_void = msg_send_1(
                 self.as_ptr(),
@@ -656,6 +824,8 @@ impl CommandBuffer {
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, descriptor);
             ptr
         }
     }
@@ -666,11 +836,15 @@ impl CommandBuffer {
     ///
     /// Returns a raw pointer to the encoder. The caller is responsible for ending encoding.
     pub fn resource_state_command_encoder(&self) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(self.as_ptr(), "resource_state_command_encoder");
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(resourceStateCommandEncoder));
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, std::ptr::null());
             ptr
         }
     }
@@ -686,6 +860,11 @@ impl CommandBuffer {
         &self,
         descriptor: *const c_void,
     ) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(
+            self.as_ptr(),
+            "resource_state_command_encoder_with_descriptor",
+        );
         unsafe {
             let ptr: *mut c_void = msg_send_1(
                 self.as_ptr(),
@@ -695,6 +874,8 @@ impl CommandBuffer {
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, std::ptr::null());
             ptr
         }
     }
@@ -705,12 +886,16 @@ impl CommandBuffer {
     ///
     /// Returns a raw pointer to the encoder. The caller is responsible for ending encoding.
     pub fn acceleration_structure_command_encoder(&self) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(self.as_ptr(), "acceleration_structure_command_encoder");
         unsafe {
             let ptr: *mut c_void =
                 msg_send_0(self.as_ptr(), sel!(accelerationStructureCommandEncoder));
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, std::ptr::null());
             ptr
         }
     }
@@ -726,6 +911,11 @@ impl CommandBuffer {
         &self,
         descriptor: *const c_void,
     ) -> *mut c_void {
+        #[cfg(feature = "validation")]
+        crate::validation::begin_encoder(
+            self.as_ptr(),
+            "acceleration_structure_command_encoder_with_descriptor",
+        );
         unsafe {
             let ptr: *mut c_void = msg_send_1(
                 self.as_ptr(),
@@ -735,6 +925,8 @@ impl CommandBuffer {
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             }
+            #[cfg(feature = "validation")]
+            crate::validation::encoder_created(self.as_ptr(), ptr, std::ptr::null());
             ptr
         }
     }
@@ -819,9 +1011,16 @@ impl CommandBuffer {
     /// C++ equivalent: `void setLabel(const NS::String*)`
     pub fn set_label(&self, label: &str) {
         if let Some(ns_label) = mtl_foundation::String::from_str(label) {
-            unsafe {
-                msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setLabel:), ns_label.as_ptr());
-            }
+            self.set_label_ns(&ns_label);
+        }
+    }
+
+    /// Set the label for this command buffer from an existing `NSString`.
+    ///
+    /// C++ equivalent: `void setLabel(const NS::String*)`
+    pub fn set_label_ns(&self, label: &mtl_foundation::String) {
+        unsafe {
+            msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setLabel:), label.as_ptr());
         }
     }
 
@@ -857,13 +1056,16 @@ impl CommandBuffer {
     /// C++ equivalent: `void pushDebugGroup(const NS::String*)`
     pub fn push_debug_group(&self, name: &str) {
         if let Some(ns_name) = mtl_foundation::String::from_str(name) {
-            unsafe {
-                msg_send_1::<(), *const c_void>(
-                    self.as_ptr(),
-                    sel!(pushDebugGroup:),
-                    ns_name.as_ptr(),
-                );
-            }
+            self.push_debug_group_ns(&ns_name);
+        }
+    }
+
+    /// Push a debug group from an existing `NSString`.
+    ///
+    /// C++ equivalent: `void pushDebugGroup(const NS::String*)`
+    pub fn push_debug_group_ns(&self, name: &mtl_foundation::String) {
+        unsafe {
+            msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(pushDebugGroup:), name.as_ptr());
         }
     }
 
@@ -895,6 +1097,11 @@ impl CommandBuffer {
     }
 }
 
+/// Check if a command buffer in `status` will make no further progress.
+fn is_finished(status: CommandBufferStatus) -> bool {
+    status == CommandBufferStatus::COMPLETED || status == CommandBufferStatus::ERROR
+}
+
 impl Clone for CommandBuffer {
     fn clone(&self) -> Self {
         unsafe {
@@ -942,4 +1149,15 @@ mod tests {
             std::mem::size_of::<*mut c_void>()
         );
     }
+
+    #[test]
+    fn test_error_user_info_empty_on_success() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().expect("no command queue");
+        let command_buffer = queue.command_buffer().expect("no command buffer");
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+        assert!(command_buffer.error().is_none());
+        assert!(command_buffer.error_user_info().is_empty());
+    }
 }
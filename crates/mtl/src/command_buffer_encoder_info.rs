@@ -8,11 +8,17 @@
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
-use mtl_foundation::Referencing;
-use mtl_sys::{msg_send_0, sel};
+use mtl_foundation::{Referencing, UInteger};
+use mtl_sys::{msg_send_0, msg_send_1, sel};
 
 use crate::enums::CommandEncoderErrorState;
 
+#[cfg_attr(target_vendor = "apple", link(name = "Metal", kind = "framework"))]
+unsafe extern "C" {
+    #[link_name = "MTLCommandBufferEncoderInfoErrorKey"]
+    static ENCODER_INFO_ERROR_KEY: *mut c_void;
+}
+
 /// Information about an encoder in a command buffer.
 ///
 /// C++ equivalent: `MTL::CommandBufferEncoderInfo`
@@ -36,6 +42,33 @@ impl CommandBufferEncoderInfo {
         self.0.as_ptr()
     }
 
+    /// Get the encoder infos attached to a command buffer error.
+    ///
+    /// Metal only attaches them to command buffers created with
+    /// [`CommandBufferErrorOption::ENCODER_EXECUTION_STATUS`](crate::enums::CommandBufferErrorOption::ENCODER_EXECUTION_STATUS).
+    ///
+    /// C++ equivalent: `error->userInfo()->object(MTL::CommandBufferEncoderInfoErrorKey)`
+    pub fn from_error(error: &mtl_foundation::Error) -> Vec<Self> {
+        let user_info = error.user_info();
+        if user_info.is_null() {
+            return Vec::new();
+        }
+        unsafe {
+            let infos: *mut c_void = msg_send_1(
+                user_info as *const c_void,
+                sel!(objectForKey:),
+                ENCODER_INFO_ERROR_KEY as *const c_void,
+            );
+            array_objects(infos)
+                .into_iter()
+                .filter_map(|ptr| {
+                    let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+                    Self::from_raw(ptr)
+                })
+                .collect()
+        }
+    }
+
     /// Get the label of the encoder.
     ///
     /// C++ equivalent: `NS::String* label() const`
@@ -72,6 +105,43 @@ impl CommandBufferEncoderInfo {
     pub fn debug_signposts_ptr(&self) -> *mut c_void {
         unsafe { msg_send_0(self.as_ptr(), sel!(debugSignposts)) }
     }
+
+    /// Get the debug signposts the encoder inserted, in order.
+    ///
+    /// C++ equivalent: `NS::Array* debugSignposts() const`
+    pub fn debug_signposts(&self) -> Vec<String> {
+        unsafe {
+            array_objects(self.debug_signposts_ptr())
+                .into_iter()
+                .filter_map(|ptr| {
+                    let utf8_ptr: *const std::ffi::c_char =
+                        msg_send_0(ptr as *const c_void, sel!(UTF8String));
+                    if utf8_ptr.is_null() {
+                        return None;
+                    }
+                    let c_str = std::ffi::CStr::from_ptr(utf8_ptr);
+                    Some(c_str.to_string_lossy().into_owned())
+                })
+                .collect()
+        }
+    }
+}
+
+/// Get the unretained elements of an NSArray, or none if `array` is null.
+///
+/// # Safety
+///
+/// `array` must be a valid NSArray or null.
+unsafe fn array_objects(array: *mut c_void) -> Vec<*mut c_void> {
+    if array.is_null() {
+        return Vec::new();
+    }
+    unsafe {
+        let count: UInteger = msg_send_0(array, sel!(count));
+        (0..count)
+            .map(|index| msg_send_1(array as *const c_void, sel!(objectAtIndex:), index))
+            .collect()
+    }
 }
 
 impl Clone for CommandBufferEncoderInfo {
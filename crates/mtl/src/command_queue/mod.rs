@@ -10,6 +10,10 @@ use std::ptr::NonNull;
 use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, sel};
 
+use crate::log_state::LogState;
+use crate::object_ptrs::with_object_ptrs;
+use crate::residency_set::ResidencySet;
+
 // ============================================================================
 // CommandQueueDescriptor
 // ============================================================================
@@ -77,22 +81,40 @@ impl CommandQueueDescriptor {
     /// Get the log state for the command queue.
     ///
     /// C++ equivalent: `LogState* logState() const`
+    pub fn log_state(&self) -> Option<LogState> {
+        unsafe {
+            let ptr = self.log_state_ptr();
+            if !ptr.is_null() {
+                let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            }
+            LogState::from_raw(ptr)
+        }
+    }
+
+    /// Get the log state for the command queue, by raw pointer.
     ///
-    /// Returns a raw pointer to the log state object.
+    /// C++ equivalent: `LogState* logState() const`
     #[inline]
-    pub fn log_state(&self) -> *mut c_void {
+    pub fn log_state_ptr(&self) -> *mut c_void {
         unsafe { msg_send_0(self.as_ptr(), sel!(logState)) }
     }
 
     /// Set the log state for the command queue.
     ///
     /// C++ equivalent: `void setLogState(const LogState* logState)`
+    pub fn set_log_state(&self, log_state: &LogState) {
+        unsafe { self.set_log_state_ptr(log_state.as_ptr()) }
+    }
+
+    /// Set the log state for the command queue, by raw pointer.
+    ///
+    /// C++ equivalent: `void setLogState(const LogState* logState)`
     ///
     /// # Safety
     ///
     /// The log_state pointer must be valid or null.
     #[inline]
-    pub unsafe fn set_log_state(&self, log_state: *const c_void) {
+    pub unsafe fn set_log_state_ptr(&self, log_state: *const c_void) {
         unsafe {
             msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setLogState:), log_state);
         }
@@ -186,14 +208,17 @@ impl CommandQueue {
             }
             // Retain to take ownership (Metal returns autoreleased object)
             let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            #[cfg(feature = "validation")]
+            crate::validation::command_buffer_created(ptr);
             crate::command_buffer::CommandBuffer::from_raw(ptr)
         }
     }
 
-    /// Create a new command buffer with an unretained reference.
+    /// Create a new command buffer that doesn't retain the resources it
+    /// references.
     ///
-    /// The returned command buffer is autoreleased and will be deallocated unless
-    /// you explicitly retain it.
+    /// The caller must keep every buffer, texture and state object used by
+    /// the command buffer alive until it completes.
     ///
     /// C++ equivalent: `CommandBuffer* commandBufferWithUnretainedReferences()`
     pub fn command_buffer_with_unretained_references(
@@ -205,6 +230,8 @@ impl CommandQueue {
             // Retain to take ownership
             if !ptr.is_null() {
                 let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+                #[cfg(feature = "validation")]
+                crate::validation::command_buffer_created(ptr);
             }
             crate::command_buffer::CommandBuffer::from_raw(ptr)
         }
@@ -231,6 +258,8 @@ impl CommandQueue {
                 return None;
             }
             let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            #[cfg(feature = "validation")]
+            crate::validation::command_buffer_created(ptr);
             crate::command_buffer::CommandBuffer::from_raw(ptr)
         }
     }
@@ -291,11 +320,14 @@ impl CommandQueue {
     }
 
     // =========================================================================
-    // Residency (macOS only)
+    // Debugging
     // =========================================================================
 
     /// Insert a debug capture boundary.
     ///
+    /// Marks a frame boundary for GPU frame capture in apps that don't
+    /// present drawables.
+    ///
     /// C++ equivalent: `void insertDebugCaptureBoundary()`
     #[inline]
     pub fn insert_debug_capture_boundary(&self) {
@@ -310,25 +342,64 @@ impl CommandQueue {
 
     /// Add a residency set to the command queue.
     ///
+    /// The set's allocations are made resident for every command buffer
+    /// committed to the queue.
+    ///
+    /// C++ equivalent: `void addResidencySet(const ResidencySet*)`
+    pub fn add_residency_set(&self, residency_set: &ResidencySet) {
+        unsafe { self.add_residency_set_ptr(residency_set.as_ptr()) }
+    }
+
+    /// Add multiple residency sets to the command queue.
+    ///
+    /// C++ equivalent: `void addResidencySets(const ResidencySet* const*, NS::UInteger count)`
+    pub fn add_residency_sets(&self, residency_sets: &[&ResidencySet]) {
+        with_object_ptrs(residency_sets, |ptrs| unsafe {
+            self.add_residency_sets_ptr(ptrs.as_ptr(), ptrs.len() as UInteger)
+        })
+    }
+
+    /// Remove a residency set from the command queue.
+    ///
+    /// C++ equivalent: `void removeResidencySet(const ResidencySet*)`
+    pub fn remove_residency_set(&self, residency_set: &ResidencySet) {
+        unsafe { self.remove_residency_set_ptr(residency_set.as_ptr()) }
+    }
+
+    /// Remove multiple residency sets from the command queue.
+    ///
+    /// C++ equivalent: `void removeResidencySets(const ResidencySet* const*, NS::UInteger count)`
+    pub fn remove_residency_sets(&self, residency_sets: &[&ResidencySet]) {
+        with_object_ptrs(residency_sets, |ptrs| unsafe {
+            self.remove_residency_sets_ptr(ptrs.as_ptr(), ptrs.len() as UInteger)
+        })
+    }
+
+    /// Add a residency set to the command queue, by raw pointer.
+    ///
     /// C++ equivalent: `void addResidencySet(const ResidencySet*)`
     ///
     /// # Safety
     ///
     /// The residency_set pointer must be valid.
-    pub unsafe fn add_residency_set(&self, residency_set: *const c_void) {
+    pub unsafe fn add_residency_set_ptr(&self, residency_set: *const c_void) {
         unsafe {
             msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(addResidencySet:), residency_set);
         }
     }
 
-    /// Add multiple residency sets to the command queue.
+    /// Add multiple residency sets to the command queue, by raw pointer.
     ///
     /// C++ equivalent: `void addResidencySets(const ResidencySet* const*, NS::UInteger count)`
     ///
     /// # Safety
     ///
     /// The residency_sets pointer must be valid and point to count valid pointers.
-    pub unsafe fn add_residency_sets(&self, residency_sets: *const *const c_void, count: UInteger) {
+    pub unsafe fn add_residency_sets_ptr(
+        &self,
+        residency_sets: *const *const c_void,
+        count: UInteger,
+    ) {
         unsafe {
             mtl_sys::msg_send_2::<(), *const *const c_void, UInteger>(
                 self.as_ptr(),
@@ -339,14 +410,14 @@ impl CommandQueue {
         }
     }
 
-    /// Remove a residency set from the command queue.
+    /// Remove a residency set from the command queue, by raw pointer.
     ///
     /// C++ equivalent: `void removeResidencySet(const ResidencySet*)`
     ///
     /// # Safety
     ///
     /// The residency_set pointer must be valid.
-    pub unsafe fn remove_residency_set(&self, residency_set: *const c_void) {
+    pub unsafe fn remove_residency_set_ptr(&self, residency_set: *const c_void) {
         unsafe {
             msg_send_1::<(), *const c_void>(
                 self.as_ptr(),
@@ -356,14 +427,14 @@ impl CommandQueue {
         }
     }
 
-    /// Remove multiple residency sets from the command queue.
+    /// Remove multiple residency sets from the command queue, by raw pointer.
     ///
     /// C++ equivalent: `void removeResidencySets(const ResidencySet* const*, NS::UInteger count)`
     ///
     /// # Safety
     ///
     /// The residency_sets pointer must be valid and point to count valid pointers.
-    pub unsafe fn remove_residency_sets(
+    pub unsafe fn remove_residency_sets_ptr(
         &self,
         residency_sets: *const *const c_void,
         count: UInteger,
@@ -449,6 +520,23 @@ impl std::fmt::Debug for CommandQueue {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_command_queue_helpers() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().unwrap();
+        queue.set_label("helpers");
+        assert_eq!(queue.label().as_deref(), Some("helpers"));
+        queue.insert_debug_capture_boundary();
+
+        let unretained = queue.command_buffer_with_unretained_references().unwrap();
+        assert!(!unretained.retained_references());
+
+        let descriptor = crate::command_buffer::CommandBufferDescriptor::new().unwrap();
+        descriptor.set_retained_references(false);
+        let command_buffer = queue.command_buffer_with_descriptor(&descriptor).unwrap();
+        assert!(!command_buffer.retained_references());
+    }
+
     #[test]
     fn test_command_queue_size() {
         assert_eq!(
@@ -0,0 +1,531 @@
+//! Checkpointed, restartable long-running compute jobs.
+//!
+//! A single command buffer that runs for minutes trips the GPU watchdog,
+//! and a job that runs for hours will sooner or later meet a transient GPU
+//! fault or an external GPU being unplugged. [`ComputeJobRunner`] splits a
+//! job into numbered chunks, submits a few chunks per command buffer, and
+//! periodically copies the job's shared buffers into a CPU-side
+//! [`JobCheckpoint`]. A failed command buffer rolls the buffers back to the
+//! checkpoint and retries; losing the device stops the job so it can be
+//! [migrated](ComputeJobRunner::migrate) to another device and resumed from
+//! the checkpoint.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut runner = ComputeJobRunner::new(&device, SIMULATION_SOURCE, "step", 100_000)?
+//!     .with_chunks_per_command_buffer(8)
+//!     .with_checkpoint_interval(1_000);
+//! runner.add_buffer(&initial_state_bytes)?;
+//!
+//! let mut encode = |encoder: &ComputeCommandEncoder, buffers: &[Buffer], chunk: u64| {
+//!     encoder.set_buffer(&buffers[0], 0, 0);
+//!     encoder.set_value(&(chunk as u32), 1);
+//!     encoder.dispatch_threads(Size::new(cells, 1, 1), Size::new(64, 1, 1));
+//! };
+//!
+//! loop {
+//!     match runner.run(&mut encode) {
+//!         Ok(()) => break,
+//!         Err(ComputeJobError::DeviceLost { .. }) => {
+//!             let fallback = device::system_default().expect("no Metal device");
+//!             runner.migrate(&fallback)?;
+//!         }
+//!         Err(err) => return Err(err.into()),
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use mtl_foundation::Integer;
+
+use crate::enums::{CommandBufferError, CommandBufferStatus, ResourceOptions};
+use crate::{Buffer, CommandQueue, ComputeCommandEncoder, ComputePipelineState, Device};
+
+/// Default number of chunks encoded into each command buffer.
+pub const DEFAULT_CHUNKS_PER_COMMAND_BUFFER: u64 = 1;
+
+/// Default number of chunks between checkpoints.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 64;
+
+/// Default number of times a failed command buffer is retried.
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Error produced by a [`ComputeJobRunner`].
+#[derive(Debug)]
+pub enum ComputeJobError {
+    /// The kernel failed to compile, or its pipeline failed to build.
+    Pipeline(mtl_foundation::Error),
+    /// The kernel function wasn't found in the compiled source.
+    FunctionNotFound(String),
+    /// A command queue, command buffer, encoder or buffer could not be
+    /// created.
+    Allocation,
+    /// The device was removed or its access revoked. The buffers hold the
+    /// checkpoint taken before `next_chunk`; call
+    /// [`ComputeJobRunner::migrate`] to continue on another device.
+    DeviceLost { next_chunk: u64 },
+    /// A command buffer kept failing after every retry. The buffers were
+    /// rolled back to the last checkpoint.
+    CommandBuffer {
+        chunk: u64,
+        error: Option<mtl_foundation::Error>,
+    },
+}
+
+impl fmt::Display for ComputeJobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputeJobError::Pipeline(err) => write!(f, "failed to build compute job: {}", err),
+            ComputeJobError::FunctionNotFound(name) => {
+                write!(f, "compute job function '{}' not found", name)
+            }
+            ComputeJobError::Allocation => write!(f, "failed to allocate compute job resources"),
+            ComputeJobError::DeviceLost { next_chunk } => {
+                write!(f, "device lost; job can resume from chunk {}", next_chunk)
+            }
+            ComputeJobError::CommandBuffer {
+                chunk,
+                error: Some(err),
+            } => write!(f, "compute job failed at chunk {}: {}", chunk, err),
+            ComputeJobError::CommandBuffer { chunk, error: None } => {
+                write!(f, "compute job failed at chunk {}", chunk)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ComputeJobError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ComputeJobError::Pipeline(err) => Some(err),
+            ComputeJobError::CommandBuffer {
+                error: Some(err), ..
+            } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// JobCheckpoint
+// ============================================================================
+
+/// A CPU copy of a job's buffers after a known number of chunks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JobCheckpoint {
+    next_chunk: u64,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl JobCheckpoint {
+    /// Get the first chunk not reflected in the checkpoint.
+    pub fn next_chunk(&self) -> u64 {
+        self.next_chunk
+    }
+
+    /// Get the number of buffers saved.
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Get the saved contents of buffer `index`.
+    pub fn buffer(&self, index: usize) -> Option<&[u8]> {
+        self.buffers.get(index).map(Vec::as_slice)
+    }
+}
+
+// ============================================================================
+// ComputeJobRunner
+// ============================================================================
+
+/// Runs a chunked compute kernel with checkpoints, retries and device
+/// migration.
+///
+/// Every chunk is encoded with the job's pipeline already set; the encode
+/// closure binds the job's buffers and dispatches. Chunks must be
+/// deterministic functions of the buffers and the chunk index, since any
+/// chunk after the last checkpoint may be run again.
+pub struct ComputeJobRunner {
+    device: Device,
+    queue: CommandQueue,
+    pipeline: ComputePipelineState,
+    source: String,
+    function: String,
+    buffers: Vec<Buffer>,
+    chunk_count: u64,
+    next_chunk: u64,
+    chunks_per_command_buffer: u64,
+    checkpoint_interval: u64,
+    max_retries: u32,
+    checkpoint: JobCheckpoint,
+    device_lost: Arc<AtomicBool>,
+}
+
+impl ComputeJobRunner {
+    /// Create a runner for `chunk_count` chunks of kernel `function` in the
+    /// Metal source `source`.
+    ///
+    /// The source is kept so the pipeline can be rebuilt on the device the
+    /// job migrates to.
+    pub fn new(
+        device: &Device,
+        source: &str,
+        function: &str,
+        chunk_count: u64,
+    ) -> Result<Self, ComputeJobError> {
+        let (queue, pipeline) = prepare(device, source, function)?;
+        Ok(Self {
+            device: device.clone(),
+            queue,
+            pipeline,
+            source: source.to_owned(),
+            function: function.to_owned(),
+            buffers: Vec::new(),
+            chunk_count,
+            next_chunk: 0,
+            chunks_per_command_buffer: DEFAULT_CHUNKS_PER_COMMAND_BUFFER,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            max_retries: DEFAULT_MAX_RETRIES,
+            checkpoint: JobCheckpoint::default(),
+            device_lost: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Set how many chunks are encoded into each command buffer.
+    ///
+    /// Keep each command buffer well under the GPU watchdog's limit of a
+    /// few seconds.
+    pub fn with_chunks_per_command_buffer(mut self, count: u64) -> Self {
+        self.chunks_per_command_buffer = count.max(1);
+        self
+    }
+
+    /// Set how many chunks run between checkpoints.
+    ///
+    /// Each checkpoint copies every buffer, so larger jobs want larger
+    /// intervals; a failure repeats at most this many chunks.
+    pub fn with_checkpoint_interval(mut self, count: u64) -> Self {
+        self.checkpoint_interval = count.max(1);
+        self
+    }
+
+    /// Set how many times a failed command buffer is retried from the last
+    /// checkpoint before [`run`](Self::run) gives up.
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Add a shared buffer initialized with `bytes` and return its index in
+    /// the slice passed to the encode closure.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the job has already started.
+    pub fn add_buffer(&mut self, bytes: &[u8]) -> Result<usize, ComputeJobError> {
+        assert!(
+            self.next_chunk == 0,
+            "buffers must be added before the job starts"
+        );
+        let buffer = new_shared_buffer(&self.device, bytes)?;
+        self.buffers.push(buffer);
+        self.checkpoint.buffers.push(bytes.to_vec());
+        Ok(self.buffers.len() - 1)
+    }
+
+    /// Get the device the job runs on.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Get the job's buffers.
+    pub fn buffers(&self) -> &[Buffer] {
+        &self.buffers
+    }
+
+    /// Get the current contents of buffer `index`.
+    pub fn buffer_contents(&self, index: usize) -> Option<&[u8]> {
+        let buffer = self.buffers.get(index)?;
+        let contents = buffer.contents()?;
+        Some(unsafe { std::slice::from_raw_parts(contents as *const u8, buffer.length()) })
+    }
+
+    /// Get the total number of chunks.
+    pub fn chunk_count(&self) -> u64 {
+        self.chunk_count
+    }
+
+    /// Get the first chunk that hasn't completed.
+    pub fn next_chunk(&self) -> u64 {
+        self.next_chunk
+    }
+
+    /// Check if every chunk has completed.
+    pub fn is_complete(&self) -> bool {
+        self.next_chunk >= self.chunk_count
+    }
+
+    /// Get the last checkpoint.
+    ///
+    /// Save it to disk to survive a process restart as well as a device
+    /// loss.
+    pub fn checkpoint(&self) -> &JobCheckpoint {
+        &self.checkpoint
+    }
+
+    /// Get the flag that stops the job at the next command buffer boundary
+    /// with [`ComputeJobError::DeviceLost`].
+    ///
+    /// Set it from a device observer, or from any thread to pause the job.
+    pub fn device_lost_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.device_lost)
+    }
+
+    /// Set the device-lost flag when the job's device is removed or its
+    /// removal is requested (macOS only).
+    ///
+    /// Pass the returned observer to
+    /// [`remove_device_observer`](crate::device::remove_device_observer)
+    /// once the job finishes.
+    #[cfg(all(target_os = "macos", feature = "blocks"))]
+    pub fn watch_device_removal(&self) -> crate::device::DeviceObserver {
+        use crate::device::DeviceNotificationName;
+
+        let registry_id = self.device.registry_id();
+        let lost = self.device_lost_flag();
+        let (_, observer) =
+            crate::device::copy_all_devices_with_observer(move |device, notification| {
+                if device.registry_id() == registry_id
+                    && notification != DeviceNotificationName::WasAdded
+                {
+                    lost.store(true, Ordering::Release);
+                }
+            });
+        observer
+    }
+
+    /// Run every remaining chunk, blocking until the job completes or fails.
+    pub fn run<F>(&mut self, encode: F) -> Result<(), ComputeJobError>
+    where
+        F: FnMut(&ComputeCommandEncoder, &[Buffer], u64),
+    {
+        self.run_chunks(u64::MAX, encode)
+    }
+
+    /// Run up to `max_chunks` more chunks, blocking until they complete.
+    ///
+    /// Useful for reporting progress or yielding between slices of a job.
+    pub fn run_chunks<F>(&mut self, max_chunks: u64, mut encode: F) -> Result<(), ComputeJobError>
+    where
+        F: FnMut(&ComputeCommandEncoder, &[Buffer], u64),
+    {
+        let target = self
+            .next_chunk
+            .saturating_add(max_chunks)
+            .min(self.chunk_count);
+        let mut failures = 0;
+        while self.next_chunk < target {
+            if self.device_lost.load(Ordering::Acquire) {
+                return Err(self.device_lost_error());
+            }
+            let batch = batch_range(self.next_chunk, self.chunks_per_command_buffer, target);
+            match self.submit(batch.clone(), &mut encode)? {
+                None => {
+                    failures = 0;
+                    self.next_chunk = batch.end;
+                    if self.next_chunk - self.checkpoint.next_chunk >= self.checkpoint_interval
+                        || self.next_chunk == self.chunk_count
+                    {
+                        self.save_checkpoint();
+                    }
+                }
+                Some(error) => {
+                    if error
+                        .as_ref()
+                        .is_some_and(|err| is_device_loss_code(err.code()))
+                    {
+                        self.device_lost.store(true, Ordering::Release);
+                        return Err(self.device_lost_error());
+                    }
+                    self.restore_checkpoint();
+                    failures += 1;
+                    if failures > self.max_retries {
+                        return Err(ComputeJobError::CommandBuffer {
+                            chunk: batch.start,
+                            error,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the job to `device`, restoring the buffers from the last
+    /// checkpoint.
+    ///
+    /// Also resumes a job on its own device after a device-lost flag that
+    /// turned out to be spurious. The runner is unchanged if the pipeline
+    /// or buffers can't be created on `device`.
+    pub fn migrate(&mut self, device: &Device) -> Result<(), ComputeJobError> {
+        let (queue, pipeline) = prepare(device, &self.source, &self.function)?;
+        let buffers = self
+            .checkpoint
+            .buffers
+            .iter()
+            .map(|bytes| new_shared_buffer(device, bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.device = device.clone();
+        self.queue = queue;
+        self.pipeline = pipeline;
+        self.buffers = buffers;
+        self.next_chunk = self.checkpoint.next_chunk;
+        self.device_lost.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    /// Encode `batch` into one command buffer, commit it and wait.
+    ///
+    /// Returns `Some` with the command buffer's error if it failed on the
+    /// GPU.
+    fn submit<F>(
+        &self,
+        batch: Range<u64>,
+        encode: &mut F,
+    ) -> Result<Option<Option<mtl_foundation::Error>>, ComputeJobError>
+    where
+        F: FnMut(&ComputeCommandEncoder, &[Buffer], u64),
+    {
+        let command_buffer = self
+            .queue
+            .command_buffer()
+            .ok_or(ComputeJobError::Allocation)?;
+        command_buffer.set_label(&format!("Compute job chunks {:?}", batch));
+        let encoder =
+            unsafe { ComputeCommandEncoder::from_raw(command_buffer.compute_command_encoder()) }
+                .ok_or(ComputeJobError::Allocation)?;
+        encoder.set_compute_pipeline_state(&self.pipeline);
+        for chunk in batch {
+            encode(&encoder, &self.buffers, chunk);
+        }
+        encoder.end_encoding();
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+
+        if command_buffer.status() == CommandBufferStatus::ERROR {
+            return Ok(Some(command_buffer.error()));
+        }
+        Ok(None)
+    }
+
+    fn save_checkpoint(&mut self) {
+        for (index, saved) in self.checkpoint.buffers.iter_mut().enumerate() {
+            let buffer = &self.buffers[index];
+            if let Some(contents) = buffer.contents() {
+                let bytes =
+                    unsafe { std::slice::from_raw_parts(contents as *const u8, saved.len()) };
+                saved.copy_from_slice(bytes);
+            }
+        }
+        self.checkpoint.next_chunk = self.next_chunk;
+    }
+
+    fn restore_checkpoint(&mut self) {
+        for (buffer, saved) in self.buffers.iter().zip(&self.checkpoint.buffers) {
+            if let Some(contents) = buffer.contents() {
+                unsafe {
+                    std::ptr::copy_nonoverlapping(saved.as_ptr(), contents as *mut u8, saved.len())
+                };
+            }
+        }
+        self.next_chunk = self.checkpoint.next_chunk;
+    }
+
+    fn device_lost_error(&self) -> ComputeJobError {
+        ComputeJobError::DeviceLost {
+            next_chunk: self.checkpoint.next_chunk,
+        }
+    }
+}
+
+impl fmt::Debug for ComputeJobRunner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComputeJobRunner")
+            .field("device", &self.device.name())
+            .field("function", &self.function)
+            .field("buffers", &self.buffers.len())
+            .field("next_chunk", &self.next_chunk)
+            .field("chunk_count", &self.chunk_count)
+            .field("checkpoint", &self.checkpoint.next_chunk)
+            .finish()
+    }
+}
+
+/// Create the queue and pipeline for a job on `device`.
+fn prepare(
+    device: &Device,
+    source: &str,
+    function: &str,
+) -> Result<(CommandQueue, ComputePipelineState), ComputeJobError> {
+    let library = device
+        .new_library_with_source(source, None)
+        .map_err(ComputeJobError::Pipeline)?;
+    let kernel = library
+        .new_function_with_name(function)
+        .ok_or_else(|| ComputeJobError::FunctionNotFound(function.to_owned()))?;
+    let pipeline = device
+        .new_compute_pipeline_state_with_function(&kernel)
+        .map_err(ComputeJobError::Pipeline)?;
+    let queue = device
+        .new_command_queue()
+        .ok_or(ComputeJobError::Allocation)?;
+    Ok((queue, pipeline))
+}
+
+fn new_shared_buffer(device: &Device, bytes: &[u8]) -> Result<Buffer, ComputeJobError> {
+    let buffer = if bytes.is_empty() {
+        device.new_buffer(1, ResourceOptions::STORAGE_MODE_SHARED)
+    } else {
+        device.new_buffer_with_bytes(bytes, ResourceOptions::STORAGE_MODE_SHARED)
+    };
+    buffer.ok_or(ComputeJobError::Allocation)
+}
+
+/// The chunks submitted together, starting at `next`.
+fn batch_range(next: u64, per_command_buffer: u64, target: u64) -> Range<u64> {
+    next..next.saturating_add(per_command_buffer).min(target)
+}
+
+/// Check if a command buffer error code means the device is gone.
+fn is_device_loss_code(code: Integer) -> bool {
+    let code = CommandBufferError(code as _);
+    code == CommandBufferError::DEVICE_REMOVED || code == CommandBufferError::ACCESS_REVOKED
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_range_stops_at_target() {
+        assert_eq!(batch_range(0, 4, 10), 0..4);
+        assert_eq!(batch_range(8, 4, 10), 8..10);
+        assert_eq!(
+            batch_range(u64::MAX - 1, 4, u64::MAX),
+            u64::MAX - 1..u64::MAX
+        );
+    }
+
+    #[test]
+    fn test_device_loss_codes() {
+        assert!(is_device_loss_code(11));
+        assert!(is_device_loss_code(4));
+        assert!(!is_device_loss_code(2));
+        assert!(!is_device_loss_code(3));
+    }
+}
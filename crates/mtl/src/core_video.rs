@@ -0,0 +1,241 @@
+//! Metal textures over Core Video pixel buffers.
+//!
+//! Frames from AVFoundation, VideoToolbox and ScreenCaptureKit arrive as
+//! `CVPixelBufferRef`s, which are usually backed by an IOSurface. A
+//! [`PixelBuffer`] wraps one and creates a [`Texture`] per plane that
+//! shares the surface's memory through
+//! [`Device::new_texture_with_iosurface`], with the plane's size and a
+//! pixel format matching the buffer's Core Video format, so decoded frames
+//! can be sampled without copying.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // In a ScreenCaptureKit or AVCaptureVideoDataOutput callback:
+//! let frame = unsafe { PixelBuffer::from_raw(sample_buffer_image) }.unwrap();
+//! let luma = frame.texture(&device, 0).unwrap();
+//! let chroma = frame.texture(&device, 1).unwrap();
+//! encoder.set_fragment_texture(&luma, 0);
+//! encoder.set_fragment_texture(&chroma, 1);
+//! ```
+
+use std::ffi::c_void;
+use std::fmt;
+use std::ptr::NonNull;
+
+use mtl_foundation::{Referencing, UInteger};
+
+use crate::enums::{PixelFormat, TextureUsage};
+use crate::{Device, Texture, TextureDescriptor};
+
+#[cfg_attr(target_vendor = "apple", link(name = "CoreVideo", kind = "framework"))]
+unsafe extern "C" {
+    fn CVPixelBufferGetWidth(pixel_buffer: *const c_void) -> usize;
+    fn CVPixelBufferGetHeight(pixel_buffer: *const c_void) -> usize;
+    fn CVPixelBufferGetPixelFormatType(pixel_buffer: *const c_void) -> u32;
+    fn CVPixelBufferGetPlaneCount(pixel_buffer: *const c_void) -> usize;
+    fn CVPixelBufferGetWidthOfPlane(pixel_buffer: *const c_void, plane: usize) -> usize;
+    fn CVPixelBufferGetHeightOfPlane(pixel_buffer: *const c_void, plane: usize) -> usize;
+    fn CVPixelBufferGetIOSurface(pixel_buffer: *const c_void) -> *mut c_void;
+}
+
+#[cfg_attr(
+    target_vendor = "apple",
+    link(name = "CoreFoundation", kind = "framework")
+)]
+unsafe extern "C" {
+    fn CFRetain(object: *const c_void) -> *const c_void;
+    fn CFRelease(object: *const c_void);
+}
+
+/// Build a Core Video four-character format code.
+const fn four_cc(code: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*code)
+}
+
+/// `kCVPixelFormatType_32BGRA`
+pub const PIXEL_FORMAT_32_BGRA: u32 = four_cc(b"BGRA");
+/// `kCVPixelFormatType_420YpCbCr8BiPlanarVideoRange`
+pub const PIXEL_FORMAT_420_VIDEO_RANGE: u32 = four_cc(b"420v");
+/// `kCVPixelFormatType_420YpCbCr8BiPlanarFullRange`
+pub const PIXEL_FORMAT_420_FULL_RANGE: u32 = four_cc(b"420f");
+/// `kCVPixelFormatType_420YpCbCr10BiPlanarVideoRange`
+pub const PIXEL_FORMAT_420_10_VIDEO_RANGE: u32 = four_cc(b"x420");
+/// `kCVPixelFormatType_420YpCbCr10BiPlanarFullRange`
+pub const PIXEL_FORMAT_420_10_FULL_RANGE: u32 = four_cc(b"xf20");
+/// `kCVPixelFormatType_64RGBAHalf`
+pub const PIXEL_FORMAT_64_RGBA_HALF: u32 = four_cc(b"RGhA");
+/// `kCVPixelFormatType_ARGB2101010LEPacked`
+pub const PIXEL_FORMAT_ARGB_2101010_LE: u32 = four_cc(b"l10r");
+
+/// Get the Metal pixel format for one plane of a Core Video format.
+///
+/// Bi-planar YCbCr formats map to a single-channel luma plane and a
+/// two-channel chroma plane. Returns `None` for formats without a direct
+/// Metal equivalent.
+pub fn metal_pixel_format(cv_format: u32, plane: usize) -> Option<PixelFormat> {
+    match (cv_format, plane) {
+        (PIXEL_FORMAT_32_BGRA, 0) => Some(PixelFormat::BGRA8_UNORM),
+        (PIXEL_FORMAT_64_RGBA_HALF, 0) => Some(PixelFormat::RGBA16_FLOAT),
+        (PIXEL_FORMAT_ARGB_2101010_LE, 0) => Some(PixelFormat::BGR10A2_UNORM),
+        (PIXEL_FORMAT_420_VIDEO_RANGE | PIXEL_FORMAT_420_FULL_RANGE, 0) => {
+            Some(PixelFormat::R8_UNORM)
+        }
+        (PIXEL_FORMAT_420_VIDEO_RANGE | PIXEL_FORMAT_420_FULL_RANGE, 1) => {
+            Some(PixelFormat::RG8_UNORM)
+        }
+        (PIXEL_FORMAT_420_10_VIDEO_RANGE | PIXEL_FORMAT_420_10_FULL_RANGE, 0) => {
+            Some(PixelFormat::R16_UNORM)
+        }
+        (PIXEL_FORMAT_420_10_VIDEO_RANGE | PIXEL_FORMAT_420_10_FULL_RANGE, 1) => {
+            Some(PixelFormat::RG16_UNORM)
+        }
+        _ => None,
+    }
+}
+
+/// A retained `CVPixelBufferRef`.
+#[repr(transparent)]
+pub struct PixelBuffer(NonNull<c_void>);
+
+impl PixelBuffer {
+    /// Wrap a `CVPixelBufferRef`, retaining it.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a valid `CVPixelBufferRef`.
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
+        let ptr = NonNull::new(ptr)?;
+        unsafe { CFRetain(ptr.as_ptr()) };
+        Some(Self(ptr))
+    }
+
+    /// Get the raw `CVPixelBufferRef`.
+    #[inline]
+    pub fn as_raw(&self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+
+    /// Get the width in pixels.
+    pub fn width(&self) -> usize {
+        unsafe { CVPixelBufferGetWidth(self.as_raw()) }
+    }
+
+    /// Get the height in pixels.
+    pub fn height(&self) -> usize {
+        unsafe { CVPixelBufferGetHeight(self.as_raw()) }
+    }
+
+    /// Get the Core Video pixel format code, such as
+    /// [`PIXEL_FORMAT_420_VIDEO_RANGE`].
+    pub fn pixel_format_type(&self) -> u32 {
+        unsafe { CVPixelBufferGetPixelFormatType(self.as_raw()) }
+    }
+
+    /// Get the number of planes; 0 for non-planar buffers.
+    pub fn plane_count(&self) -> usize {
+        unsafe { CVPixelBufferGetPlaneCount(self.as_raw()) }
+    }
+
+    /// Get the size in pixels of `plane`.
+    ///
+    /// For non-planar buffers, plane 0 is the whole buffer.
+    pub fn plane_size(&self, plane: usize) -> (usize, usize) {
+        if self.plane_count() == 0 {
+            return (self.width(), self.height());
+        }
+        unsafe {
+            (
+                CVPixelBufferGetWidthOfPlane(self.as_raw(), plane),
+                CVPixelBufferGetHeightOfPlane(self.as_raw(), plane),
+            )
+        }
+    }
+
+    /// Get the backing `IOSurfaceRef`, or `None` if the buffer isn't
+    /// IOSurface-backed.
+    ///
+    /// The surface is owned by the pixel buffer and not retained.
+    pub fn iosurface(&self) -> Option<*mut c_void> {
+        let surface = unsafe { CVPixelBufferGetIOSurface(self.as_raw()) };
+        (!surface.is_null()).then_some(surface)
+    }
+
+    /// Create a texture sharing the memory of `plane`, with the plane's
+    /// size and the format given by [`metal_pixel_format`].
+    ///
+    /// Returns `None` if the buffer isn't IOSurface-backed, the format has
+    /// no Metal equivalent, or Metal can't create the texture.
+    pub fn texture(&self, device: &Device, plane: usize) -> Option<Texture> {
+        let format = metal_pixel_format(self.pixel_format_type(), plane)?;
+        self.texture_with_format(device, plane, format)
+    }
+
+    /// Create a texture sharing the memory of `plane`, viewed as `format`.
+    ///
+    /// `format` must have the same bytes per pixel as the plane.
+    pub fn texture_with_format(
+        &self,
+        device: &Device,
+        plane: usize,
+        format: PixelFormat,
+    ) -> Option<Texture> {
+        let surface = self.iosurface()?;
+        let (width, height) = self.plane_size(plane);
+        let descriptor = TextureDescriptor::texture_2d_descriptor(
+            format,
+            width as UInteger,
+            height as UInteger,
+            false,
+        )?;
+        descriptor.set_usage(TextureUsage::SHADER_READ);
+        unsafe {
+            device.new_texture_with_iosurface(descriptor.as_ptr(), surface, plane as UInteger)
+        }
+    }
+}
+
+impl Clone for PixelBuffer {
+    fn clone(&self) -> Self {
+        unsafe { CFRetain(self.as_raw()) };
+        Self(self.0)
+    }
+}
+
+impl Drop for PixelBuffer {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.as_raw()) };
+    }
+}
+
+unsafe impl Send for PixelBuffer {}
+unsafe impl Sync for PixelBuffer {}
+
+impl fmt::Debug for PixelBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PixelBuffer")
+            .field("width", &self.width())
+            .field("height", &self.height())
+            .field("pixel_format_type", &self.pixel_format_type())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metal_pixel_format() {
+        assert_eq!(PIXEL_FORMAT_32_BGRA, 0x4247_5241);
+        assert_eq!(
+            metal_pixel_format(PIXEL_FORMAT_420_VIDEO_RANGE, 0),
+            Some(PixelFormat::R8_UNORM)
+        );
+        assert_eq!(
+            metal_pixel_format(PIXEL_FORMAT_420_10_FULL_RANGE, 1),
+            Some(PixelFormat::RG16_UNORM)
+        );
+        assert_eq!(metal_pixel_format(PIXEL_FORMAT_32_BGRA, 1), None);
+    }
+}
@@ -554,6 +554,33 @@ impl CounterSampleBuffer {
         unsafe { msg_send_0(self.as_ptr(), sel!(sampleCount)) }
     }
 
+    /// Resolve counter values for a range of samples.
+    ///
+    /// The returned data is autoreleased; read it in place with
+    /// [`Data::bytes`](mtl_foundation::Data::bytes) before the enclosing
+    /// autorelease pool drains, and copy it only if it must outlive the
+    /// pool. The layout depends on the counter set, e.g. one
+    /// [`CounterResultTimestamp`] per sample for timestamps.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let data = sample_buffer.resolve_counter_range(0, 2)?;
+    /// let bytes = data.bytes();
+    /// let start = u64::from_ne_bytes(bytes[0..8].try_into().unwrap());
+    /// let end = u64::from_ne_bytes(bytes[8..16].try_into().unwrap());
+    /// ```
+    ///
+    /// C++ equivalent: `NS::Data* resolveCounterRange(NS::Range)`
+    pub fn resolve_counter_range(
+        &self,
+        location: UInteger,
+        length: UInteger,
+    ) -> Result<mtl_foundation::Data, CounterSampleBufferError> {
+        let ptr = self.resolve_counter_range_raw(location, length)?;
+        unsafe { mtl_foundation::Data::from_ptr(ptr) }.ok_or(CounterSampleBufferError::INVALID)
+    }
+
     /// Resolve counter values for a range of samples.
     ///
     /// Returns the raw NSData pointer containing the resolved counter values.
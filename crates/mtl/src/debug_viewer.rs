@@ -0,0 +1,427 @@
+//! Thumbnails of intermediate render targets for debug UIs.
+//!
+//! A [`DebugViewer`] converts any float, normalized or depth texture
+//! produced during a frame into a small RGBA8 image. The conversion is a
+//! compute pass encoded on the frame's own command buffer, followed by a
+//! blit into a CPU-visible buffer, so nothing waits on the GPU: the bytes
+//! of the most recent completed capture are available through
+//! [`DebugViewer::bytes`] for uploading to an immediate-mode UI (egui,
+//! Dear ImGui), and [`DebugViewer::texture`] exposes the same image for UI
+//! backends that draw Metal textures directly.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut viewer = DebugViewer::new(&device, 256, 144)?;
+//! viewer.set_range(0.98, 1.0); // expand the interesting part of a depth buffer
+//!
+//! // Each frame, after the pass that writes the target:
+//! viewer.capture(&command_buffer, &depth_texture)?;
+//! command_buffer.commit();
+//!
+//! // In the UI:
+//! if let Some(rgba) = viewer.bytes() {
+//!     let image = egui::ColorImage::from_rgba_unmultiplied([256, 144], rgba);
+//!     // ...
+//! }
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use crate::enums::{CommandBufferStatus, PixelFormat, ResourceOptions, StorageMode, TextureUsage};
+use crate::types::{Origin, Size};
+use crate::{
+    BlitCommandEncoder, Buffer, CommandBuffer, ComputeCommandEncoder, ComputePipelineState, Device,
+    Texture, TextureDescriptor,
+};
+
+/// Number of captures kept, so the CPU can read one while the GPU writes
+/// the next.
+pub const DEBUG_VIEWER_SLOTS: usize = 3;
+
+/// Threadgroup edge length of the conversion kernel.
+const THREADGROUP_SIZE: UInteger = 8;
+
+const DEBUG_VIEW_SOURCE: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct DebugViewParams {
+    float min_value;
+    float max_value;
+    uint channels;
+    uint padding;
+};
+
+constexpr sampler debug_view_sampler(coord::normalized, filter::nearest, address::clamp_to_edge);
+
+static float4 debug_view_remap(float4 color, constant DebugViewParams& params) {
+    float range = max(params.max_value - params.min_value, 1e-6f);
+    color = saturate((color - params.min_value) / range);
+    switch (params.channels) {
+        case 1: return float4(color.rrr, 1.0);
+        case 2: return float4(color.ggg, 1.0);
+        case 3: return float4(color.bbb, 1.0);
+        case 4: return float4(color.aaa, 1.0);
+        default: return color;
+    }
+}
+
+static float2 debug_view_uv(texture2d<float, access::write> destination, uint2 gid) {
+    return (float2(gid) + 0.5) / float2(destination.get_width(), destination.get_height());
+}
+
+kernel void debug_view_color(texture2d<float, access::sample> source [[texture(0)]],
+                             texture2d<float, access::write> destination [[texture(1)]],
+                             constant DebugViewParams& params [[buffer(0)]],
+                             uint2 gid [[thread_position_in_grid]]) {
+    if (gid.x >= destination.get_width() || gid.y >= destination.get_height()) {
+        return;
+    }
+    float4 color = source.sample(debug_view_sampler, debug_view_uv(destination, gid));
+    destination.write(debug_view_remap(color, params), gid);
+}
+
+kernel void debug_view_depth(depth2d<float, access::sample> source [[texture(0)]],
+                             texture2d<float, access::write> destination [[texture(1)]],
+                             constant DebugViewParams& params [[buffer(0)]],
+                             uint2 gid [[thread_position_in_grid]]) {
+    if (gid.x >= destination.get_width() || gid.y >= destination.get_height()) {
+        return;
+    }
+    float depth = source.sample(debug_view_sampler, debug_view_uv(destination, gid));
+    destination.write(debug_view_remap(float4(depth, depth, depth, 1.0), params), gid);
+}
+"#;
+
+/// Which channels of the source a [`DebugViewer`] shows.
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DebugViewChannels {
+    /// All four channels as color.
+    #[default]
+    Rgba = 0,
+    /// The red channel as grayscale.
+    Red = 1,
+    /// The green channel as grayscale.
+    Green = 2,
+    /// The blue channel as grayscale.
+    Blue = 3,
+    /// The alpha channel as grayscale.
+    Alpha = 4,
+}
+
+/// Error produced by a [`DebugViewer`].
+#[derive(Debug)]
+pub enum DebugViewerError {
+    /// The conversion kernel failed to compile or link.
+    Pipeline(mtl_foundation::Error),
+    /// The source's pixel format can't be sampled as `float` (integer,
+    /// stencil-only, or packed YUV formats).
+    UnsupportedPixelFormat(PixelFormat),
+    /// The source texture wasn't created with [`TextureUsage::SHADER_READ`].
+    NotShaderReadable,
+    /// A texture, buffer, or encoder could not be created.
+    Allocation,
+}
+
+impl fmt::Display for DebugViewerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DebugViewerError::Pipeline(err) => {
+                write!(f, "failed to build debug view pipeline: {}", err)
+            }
+            DebugViewerError::UnsupportedPixelFormat(format) => {
+                write!(
+                    f,
+                    "pixel format {:?} cannot be shown in a debug view",
+                    format
+                )
+            }
+            DebugViewerError::NotShaderReadable => {
+                write!(f, "debug view source texture lacks shader read usage")
+            }
+            DebugViewerError::Allocation => {
+                write!(f, "failed to allocate debug view resources")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DebugViewerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DebugViewerError::Pipeline(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Parameters passed to the conversion kernel; matches `DebugViewParams`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct DebugViewParams {
+    min_value: f32,
+    max_value: f32,
+    channels: u32,
+    padding: u32,
+}
+
+/// One capture's GPU image and its CPU-visible copy.
+struct Slot {
+    texture: Texture,
+    staging: Buffer,
+    command_buffer: Option<CommandBuffer>,
+    frame: u64,
+}
+
+// ============================================================================
+// DebugViewer
+// ============================================================================
+
+/// Converts textures into small RGBA8 images for display in a debug UI.
+pub struct DebugViewer {
+    color_pipeline: ComputePipelineState,
+    depth_pipeline: ComputePipelineState,
+    width: UInteger,
+    height: UInteger,
+    managed: bool,
+    min_value: f32,
+    max_value: f32,
+    channels: DebugViewChannels,
+    slots: Vec<Slot>,
+    next_slot: usize,
+    frame: u64,
+}
+
+impl DebugViewer {
+    /// Create a viewer producing `width` x `height` images.
+    ///
+    /// Compiles the conversion kernels, so create it once rather than per
+    /// frame.
+    pub fn new(
+        device: &Device,
+        width: UInteger,
+        height: UInteger,
+    ) -> Result<Self, DebugViewerError> {
+        let library = device
+            .new_library_with_source(DEBUG_VIEW_SOURCE, None)
+            .map_err(DebugViewerError::Pipeline)?;
+        let pipeline = |name: &str| {
+            let function = library
+                .new_function_with_name(name)
+                .ok_or(DebugViewerError::Allocation)?;
+            device
+                .new_compute_pipeline_state_with_function(&function)
+                .map_err(DebugViewerError::Pipeline)
+        };
+        let color_pipeline = pipeline("debug_view_color")?;
+        let depth_pipeline = pipeline("debug_view_depth")?;
+
+        let width = width.max(1);
+        let height = height.max(1);
+        let managed = !device.has_unified_memory();
+        let staging_options = if managed {
+            ResourceOptions::STORAGE_MODE_MANAGED
+        } else {
+            ResourceOptions::STORAGE_MODE_SHARED
+        };
+        let descriptor = TextureDescriptor::texture_2d_descriptor(
+            PixelFormat::RGBA8_UNORM,
+            width,
+            height,
+            false,
+        )
+        .ok_or(DebugViewerError::Allocation)?;
+        descriptor.set_storage_mode(StorageMode::PRIVATE);
+        descriptor.set_usage(TextureUsage::SHADER_READ | TextureUsage::SHADER_WRITE);
+
+        let slots = (0..DEBUG_VIEWER_SLOTS)
+            .map(|_| {
+                Ok(Slot {
+                    texture: device
+                        .new_texture_with_descriptor(&descriptor)
+                        .map_err(|_| DebugViewerError::Allocation)?,
+                    staging: device
+                        .new_buffer(width * height * 4, staging_options)
+                        .ok_or(DebugViewerError::Allocation)?,
+                    command_buffer: None,
+                    frame: 0,
+                })
+            })
+            .collect::<Result<Vec<_>, DebugViewerError>>()?;
+
+        Ok(Self {
+            color_pipeline,
+            depth_pipeline,
+            width,
+            height,
+            managed,
+            min_value: 0.0,
+            max_value: 1.0,
+            channels: DebugViewChannels::default(),
+            slots,
+            next_slot: 0,
+            frame: 0,
+        })
+    }
+
+    /// Get the width of the produced images in pixels.
+    pub fn width(&self) -> UInteger {
+        self.width
+    }
+
+    /// Get the height of the produced images in pixels.
+    pub fn height(&self) -> UInteger {
+        self.height
+    }
+
+    /// Get the source value range mapped to 0..=255.
+    pub fn range(&self) -> (f32, f32) {
+        (self.min_value, self.max_value)
+    }
+
+    /// Map source values in `min..=max` to the full 0..=255 output range.
+    ///
+    /// The default `0.0..=1.0` suits normalized targets; widen it for HDR
+    /// targets or narrow it to pick out detail in a depth buffer.
+    pub fn set_range(&mut self, min: f32, max: f32) {
+        self.min_value = min;
+        self.max_value = max;
+    }
+
+    /// Get the channels shown.
+    pub fn channels(&self) -> DebugViewChannels {
+        self.channels
+    }
+
+    /// Set the channels shown.
+    pub fn set_channels(&mut self, channels: DebugViewChannels) {
+        self.channels = channels;
+    }
+
+    /// Encode a capture of `source`'s first mipmap level and slice on
+    /// `command_buffer`.
+    ///
+    /// Encode it after the pass that writes `source`. The image becomes
+    /// visible through [`bytes`](Self::bytes) and [`texture`](Self::texture)
+    /// once `command_buffer` completes.
+    pub fn capture(
+        &mut self,
+        command_buffer: &CommandBuffer,
+        source: &Texture,
+    ) -> Result<(), DebugViewerError> {
+        let format = source.pixel_format();
+        if format.is_integer()
+            || format.stencil_view_format() == Some(format)
+            || matches!(format, PixelFormat::GBGR422 | PixelFormat::BGRG422)
+        {
+            return Err(DebugViewerError::UnsupportedPixelFormat(format));
+        }
+        if (source.usage() & TextureUsage::SHADER_READ).bits() == 0 {
+            return Err(DebugViewerError::NotShaderReadable);
+        }
+        let pipeline = if format.is_depth() {
+            &self.depth_pipeline
+        } else {
+            &self.color_pipeline
+        };
+
+        let slot = &mut self.slots[self.next_slot];
+        let params = DebugViewParams {
+            min_value: self.min_value,
+            max_value: self.max_value,
+            channels: self.channels as u32,
+            padding: 0,
+        };
+
+        let compute =
+            unsafe { ComputeCommandEncoder::from_raw(command_buffer.compute_command_encoder()) }
+                .ok_or(DebugViewerError::Allocation)?;
+        compute.set_label("Debug View");
+        compute.set_compute_pipeline_state(pipeline);
+        compute.set_texture(source, 0);
+        compute.set_texture(&slot.texture, 1);
+        compute.set_value(&params, 0);
+        compute.dispatch_threadgroups(
+            Size::new(
+                self.width.div_ceil(THREADGROUP_SIZE),
+                self.height.div_ceil(THREADGROUP_SIZE),
+                1,
+            ),
+            Size::new(THREADGROUP_SIZE, THREADGROUP_SIZE, 1),
+        );
+        compute.end_encoding();
+
+        let blit = unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+            .ok_or(DebugViewerError::Allocation)?;
+        let bytes_per_row = self.width * 4;
+        blit.copy_from_texture_to_buffer(
+            &slot.texture,
+            0,
+            0,
+            Origin::new(0, 0, 0),
+            Size::new(self.width, self.height, 1),
+            &slot.staging,
+            0,
+            bytes_per_row,
+            bytes_per_row * self.height,
+        );
+        if self.managed {
+            blit.synchronize_buffer(&slot.staging);
+        }
+        blit.end_encoding();
+
+        self.frame += 1;
+        slot.command_buffer = Some(command_buffer.clone());
+        slot.frame = self.frame;
+        self.next_slot = (self.next_slot + 1) % self.slots.len();
+        Ok(())
+    }
+
+    /// Get the RGBA8 pixels of the most recent completed capture, as
+    /// tightly packed rows of [`width`](Self::width) pixels.
+    ///
+    /// Returns `None` until a capture's command buffer has completed.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        let slot = self.latest()?;
+        let contents = slot.staging.contents()?;
+        let length = self.width * self.height * 4;
+        Some(unsafe { std::slice::from_raw_parts(contents as *const u8, length) })
+    }
+
+    /// Get the RGBA8 texture of the most recent completed capture.
+    pub fn texture(&self) -> Option<&Texture> {
+        self.latest().map(|slot| &slot.texture)
+    }
+
+    /// Get the number of captures encoded so far.
+    pub fn frame_count(&self) -> u64 {
+        self.frame
+    }
+
+    fn latest(&self) -> Option<&Slot> {
+        self.slots
+            .iter()
+            .filter(|slot| {
+                slot.command_buffer
+                    .as_ref()
+                    .is_some_and(|cb| cb.status() == CommandBufferStatus::COMPLETED)
+            })
+            .max_by_key(|slot| slot.frame)
+    }
+}
+
+impl fmt::Debug for DebugViewer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugViewer")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("range", &self.range())
+            .field("channels", &self.channels)
+            .field("frame_count", &self.frame)
+            .finish()
+    }
+}
@@ -0,0 +1,251 @@
+//! Deduplicating cache of depth/stencil states.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use super::{DepthStencilDescriptor, DepthStencilState, StencilDescriptor};
+use crate::Device;
+use crate::enums::{CompareFunction, StencilOperation};
+
+// ============================================================================
+// Keys
+// ============================================================================
+
+/// The parameters of a [`StencilDescriptor`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StencilKey {
+    /// Comparison between the masked reference and stored values.
+    pub compare_function: CompareFunction,
+    /// Operation when the stencil test fails.
+    pub stencil_failure: StencilOperation,
+    /// Operation when the stencil test passes and the depth test fails.
+    pub depth_failure: StencilOperation,
+    /// Operation when both tests pass.
+    pub depth_stencil_pass: StencilOperation,
+    /// Mask applied to values before comparing.
+    pub read_mask: u32,
+    /// Mask applied to values written to the stencil attachment.
+    pub write_mask: u32,
+}
+
+impl Default for StencilKey {
+    /// Metal's defaults: always pass, keep the stored value, all bits
+    /// enabled.
+    fn default() -> Self {
+        Self {
+            compare_function: CompareFunction::ALWAYS,
+            stencil_failure: StencilOperation::KEEP,
+            depth_failure: StencilOperation::KEEP,
+            depth_stencil_pass: StencilOperation::KEEP,
+            read_mask: u32::MAX,
+            write_mask: u32::MAX,
+        }
+    }
+}
+
+impl StencilKey {
+    /// Read the parameters of `descriptor`.
+    pub fn from_descriptor(descriptor: &StencilDescriptor) -> Self {
+        Self {
+            compare_function: descriptor.stencil_compare_function(),
+            stencil_failure: descriptor.stencil_failure_operation(),
+            depth_failure: descriptor.depth_failure_operation(),
+            depth_stencil_pass: descriptor.depth_stencil_pass_operation(),
+            read_mask: descriptor.read_mask(),
+            write_mask: descriptor.write_mask(),
+        }
+    }
+
+    /// Create a descriptor with these parameters.
+    pub fn descriptor(&self) -> Option<StencilDescriptor> {
+        let descriptor = StencilDescriptor::new()?;
+        descriptor.set_stencil_compare_function(self.compare_function);
+        descriptor.set_stencil_failure_operation(self.stencil_failure);
+        descriptor.set_depth_failure_operation(self.depth_failure);
+        descriptor.set_depth_stencil_pass_operation(self.depth_stencil_pass);
+        descriptor.set_read_mask(self.read_mask);
+        descriptor.set_write_mask(self.write_mask);
+        Some(descriptor)
+    }
+}
+
+/// The parameters of a [`DepthStencilDescriptor`], used as a cache key.
+///
+/// The label is not part of the key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DepthStencilKey {
+    /// Comparison between the fragment and stored depth values.
+    pub depth_compare_function: CompareFunction,
+    /// Whether passing fragments write their depth.
+    pub depth_write_enabled: bool,
+    /// Stencil test for front-facing primitives.
+    pub front_face_stencil: StencilKey,
+    /// Stencil test for back-facing primitives.
+    pub back_face_stencil: StencilKey,
+}
+
+impl Default for DepthStencilKey {
+    /// Metal's defaults: depth test always passes, depth writes and stencil
+    /// test disabled.
+    fn default() -> Self {
+        Self {
+            depth_compare_function: CompareFunction::ALWAYS,
+            depth_write_enabled: false,
+            front_face_stencil: StencilKey::default(),
+            back_face_stencil: StencilKey::default(),
+        }
+    }
+}
+
+impl DepthStencilKey {
+    /// A depth test with the stencil test disabled.
+    pub fn depth_test(compare: CompareFunction, write_enabled: bool) -> Self {
+        Self {
+            depth_compare_function: compare,
+            depth_write_enabled: write_enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Use `stencil` for both front and back faces.
+    pub fn with_stencil(mut self, stencil: StencilKey) -> Self {
+        self.front_face_stencil = stencil;
+        self.back_face_stencil = stencil;
+        self
+    }
+
+    /// Read the parameters of `descriptor`.
+    pub fn from_descriptor(descriptor: &DepthStencilDescriptor) -> Self {
+        let stencil = |stencil: Option<StencilDescriptor>| {
+            stencil.map_or_else(StencilKey::default, |s| StencilKey::from_descriptor(&s))
+        };
+        Self {
+            depth_compare_function: descriptor.depth_compare_function(),
+            depth_write_enabled: descriptor.is_depth_write_enabled(),
+            front_face_stencil: stencil(descriptor.front_face_stencil()),
+            back_face_stencil: stencil(descriptor.back_face_stencil()),
+        }
+    }
+
+    /// Create a descriptor with these parameters.
+    pub fn descriptor(&self) -> Option<DepthStencilDescriptor> {
+        let descriptor = DepthStencilDescriptor::new()?;
+        descriptor.set_depth_compare_function(self.depth_compare_function);
+        descriptor.set_depth_write_enabled(self.depth_write_enabled);
+        descriptor.set_front_face_stencil(Some(&self.front_face_stencil.descriptor()?));
+        descriptor.set_back_face_stencil(Some(&self.back_face_stencil.descriptor()?));
+        Some(descriptor)
+    }
+}
+
+// ============================================================================
+// DepthStencilCache
+// ============================================================================
+
+/// Returns one shared [`DepthStencilState`] per distinct set of parameters.
+///
+/// Immediate-mode renderers can ask for a state every draw without creating
+/// a new Metal object each time. The cache is safe to share between threads.
+pub struct DepthStencilCache {
+    device: Device,
+    states: Mutex<HashMap<DepthStencilKey, DepthStencilState>>,
+}
+
+impl DepthStencilCache {
+    /// Create an empty cache for `device`.
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the state for `key`, creating it on first use.
+    ///
+    /// Returns `None` if the state cannot be created.
+    pub fn get(&self, key: &DepthStencilKey) -> Option<DepthStencilState> {
+        let mut states = self.states.lock().unwrap();
+        if let Some(state) = states.get(key) {
+            return Some(state.clone());
+        }
+        let state = self.device.new_depth_stencil_state(&key.descriptor()?)?;
+        states.insert(*key, state.clone());
+        Some(state)
+    }
+
+    /// Get the state matching the parameters of `descriptor`.
+    pub fn get_for_descriptor(
+        &self,
+        descriptor: &DepthStencilDescriptor,
+    ) -> Option<DepthStencilState> {
+        self.get(&DepthStencilKey::from_descriptor(descriptor))
+    }
+
+    /// Get the number of cached states.
+    pub fn len(&self) -> usize {
+        self.states.lock().unwrap().len()
+    }
+
+    /// Check if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Release every cached state.
+    pub fn clear(&self) {
+        self.states.lock().unwrap().clear();
+    }
+}
+
+impl fmt::Debug for DepthStencilCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DepthStencilCache")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::system_default;
+
+    #[test]
+    fn test_depth_stencil_key_round_trip() {
+        let key =
+            DepthStencilKey::depth_test(CompareFunction::LESS, true).with_stencil(StencilKey {
+                compare_function: CompareFunction::EQUAL,
+                depth_stencil_pass: StencilOperation::INCREMENT_WRAP,
+                read_mask: 0x0f,
+                ..StencilKey::default()
+            });
+        let descriptor = key.descriptor().unwrap();
+        assert_eq!(DepthStencilKey::from_descriptor(&descriptor), key);
+    }
+
+    #[test]
+    fn test_depth_stencil_cache() {
+        let device = system_default().expect("no Metal device");
+        let cache = DepthStencilCache::new(&device);
+
+        let less = DepthStencilKey::depth_test(CompareFunction::LESS, true);
+        let a = cache.get(&less).unwrap();
+        let b = cache.get(&less).unwrap();
+        assert_eq!(a.as_raw(), b.as_raw());
+
+        let descriptor = DepthStencilDescriptor::depth_test(CompareFunction::LESS, true).unwrap();
+        let c = cache.get_for_descriptor(&descriptor).unwrap();
+        assert_eq!(a.as_raw(), c.as_raw());
+
+        cache
+            .get(&DepthStencilKey::depth_test(
+                CompareFunction::GREATER,
+                false,
+            ))
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
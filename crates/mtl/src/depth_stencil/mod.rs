@@ -4,6 +4,10 @@
 //!
 //! Depth and stencil testing configurations.
 
+mod cache;
+
+pub use cache::{DepthStencilCache, DepthStencilKey, StencilKey};
+
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
@@ -332,6 +336,15 @@ impl DepthStencilDescriptor {
         }
     }
 
+    /// Create a descriptor that tests depth with `compare` and optionally
+    /// writes it, with stencil disabled.
+    pub fn depth_test(compare: CompareFunction, write_enabled: bool) -> Option<Self> {
+        let descriptor = Self::new()?;
+        descriptor.set_depth_compare_function(compare);
+        descriptor.set_depth_write_enabled(write_enabled);
+        Some(descriptor)
+    }
+
     /// Create a DepthStencilDescriptor from a raw pointer.
     ///
     /// # Safety
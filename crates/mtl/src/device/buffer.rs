@@ -5,7 +5,9 @@
 use std::ffi::c_void;
 
 use mtl_foundation::{Referencing, UInteger};
-use mtl_sys::{msg_send_2, msg_send_3, msg_send_4, sel};
+#[cfg(feature = "blocks")]
+use mtl_sys::msg_send_4;
+use mtl_sys::{msg_send_2, msg_send_3, sel};
 
 use super::Device;
 use crate::buffer::Buffer;
@@ -82,6 +84,7 @@ impl Device {
     ///
     /// - The pointer must remain valid until the deallocator is called.
     /// - The pointer must be page-aligned.
+    #[cfg(feature = "blocks")]
     pub unsafe fn new_buffer_with_bytes_no_copy<F>(
         &self,
         pointer: *mut c_void,
@@ -8,7 +8,7 @@ use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, sel};
 
 use super::Device;
-use crate::command_queue::CommandQueue;
+use crate::command_queue::{CommandQueue, CommandQueueDescriptor};
 
 impl Device {
     // =========================================================================
@@ -62,6 +62,16 @@ impl Device {
             CommandQueue::from_raw(ptr)
         }
     }
+
+    /// Create a new command queue with a typed descriptor.
+    ///
+    /// C++ equivalent: `CommandQueue* newCommandQueue(const CommandQueueDescriptor*)`
+    pub fn new_command_queue_from_descriptor(
+        &self,
+        descriptor: &CommandQueueDescriptor,
+    ) -> Option<CommandQueue> {
+        unsafe { self.new_command_queue_with_descriptor(descriptor.as_ptr()) }
+    }
 }
 
 #[cfg(test)]
@@ -82,6 +92,15 @@ mod tests {
         assert!(queue.is_some());
     }
 
+    #[test]
+    fn test_new_command_queue_from_descriptor() {
+        let device = system_default().expect("no Metal device");
+        let descriptor = crate::CommandQueueDescriptor::new().unwrap();
+        descriptor.set_max_command_buffer_count(16);
+        let queue = device.new_command_queue_from_descriptor(&descriptor);
+        assert!(queue.is_some());
+    }
+
     #[test]
     fn test_command_buffer_creation() {
         let device = system_default().expect("no Metal device");
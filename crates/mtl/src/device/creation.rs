@@ -5,6 +5,9 @@
 //! - `MTLCopyAllDevices()` (macOS only)
 //! - `MTLCopyAllDevicesWithObserver()` (macOS only)
 //! - `MTLRemoveDeviceObserver()` (macOS only)
+//!
+//! [`observe_devices`] wraps the observer functions in a guard with typed
+//! [`DeviceNotification`] events.
 
 use std::ffi::c_void;
 
@@ -136,7 +139,7 @@ impl DeviceObserver {
 /// // When done observing:
 /// device::remove_device_observer(observer);
 /// ```
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "blocks"))]
 pub fn copy_all_devices_with_observer<F>(handler: F) -> (Vec<Device>, DeviceObserver)
 where
     F: Fn(&Device, DeviceNotificationName) + Send + 'static,
@@ -192,7 +195,7 @@ where
 }
 
 /// Parse a notification name NSString into our enum.
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "blocks"))]
 fn parse_notification_name(ns_string: *mut c_void) -> DeviceNotificationName {
     if ns_string.is_null() {
         return DeviceNotificationName::WasRemoved; // Default fallback
@@ -232,6 +235,121 @@ pub enum DeviceNotificationName {
     WasRemoved,
 }
 
+#[cfg(all(target_os = "macos", feature = "blocks"))]
+impl DeviceNotificationName {
+    /// Attach the device the notification is about.
+    fn with_device(self, device: Device) -> DeviceNotification {
+        match self {
+            DeviceNotificationName::WasAdded => DeviceNotification::Added(device),
+            DeviceNotificationName::RemovalRequested => {
+                DeviceNotification::WasRemovalRequested(device)
+            }
+            DeviceNotificationName::WasRemoved => DeviceNotification::Removed(device),
+        }
+    }
+}
+
+/// A device hot-plug event delivered by [`observe_devices`].
+#[cfg(target_os = "macos")]
+#[derive(Clone, Debug)]
+pub enum DeviceNotification {
+    /// A device, such as an external GPU, was connected.
+    Added(Device),
+    /// The user asked to disconnect the device. Stop submitting work to it
+    /// and release its resources so the removal can complete.
+    WasRemovalRequested(Device),
+    /// The device was disconnected. Work submitted to it fails.
+    Removed(Device),
+}
+
+#[cfg(target_os = "macos")]
+impl DeviceNotification {
+    /// Get the device the notification is about.
+    pub fn device(&self) -> &Device {
+        match self {
+            DeviceNotification::Added(device)
+            | DeviceNotification::WasRemovalRequested(device)
+            | DeviceNotification::Removed(device) => device,
+        }
+    }
+
+    /// Get the notification name.
+    pub fn name(&self) -> DeviceNotificationName {
+        match self {
+            DeviceNotification::Added(_) => DeviceNotificationName::WasAdded,
+            DeviceNotification::WasRemovalRequested(_) => DeviceNotificationName::RemovalRequested,
+            DeviceNotification::Removed(_) => DeviceNotificationName::WasRemoved,
+        }
+    }
+}
+
+/// Registration returned by [`observe_devices`] (macOS only).
+///
+/// Notifications are delivered until the guard is dropped.
+#[cfg(all(target_os = "macos", feature = "blocks"))]
+pub struct DeviceObserverGuard {
+    observer: Option<DeviceObserver>,
+    devices: Vec<Device>,
+}
+
+#[cfg(all(target_os = "macos", feature = "blocks"))]
+impl DeviceObserverGuard {
+    /// Get the devices present when observation started.
+    pub fn devices(&self) -> &[Device] {
+        &self.devices
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "blocks"))]
+impl Drop for DeviceObserverGuard {
+    fn drop(&mut self) {
+        if let Some(observer) = self.observer.take() {
+            remove_device_observer(observer);
+        }
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "blocks"))]
+impl std::fmt::Debug for DeviceObserverGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceObserverGuard")
+            .field("devices", &self.devices)
+            .finish()
+    }
+}
+
+/// Observe device hot-plug events (macOS only).
+///
+/// `callback` runs on a Metal-owned thread for each external GPU that is
+/// connected, asked to disconnect, or disconnected, until the returned guard
+/// is dropped. The guard also lists the devices present at registration.
+///
+/// # Example
+///
+/// ```ignore
+/// use mtl_gpu::device::{self, DeviceNotification};
+///
+/// let observer = device::observe_devices(|notification| match notification {
+///     DeviceNotification::Added(device) => println!("connected: {}", device.name()),
+///     DeviceNotification::WasRemovalRequested(device) => renderer_tx.send(Migrate(device)).unwrap(),
+///     DeviceNotification::Removed(device) => println!("disconnected: {}", device.name()),
+/// });
+/// println!("{} devices at startup", observer.devices().len());
+/// ```
+#[cfg(all(target_os = "macos", feature = "blocks"))]
+pub fn observe_devices<F>(callback: F) -> DeviceObserverGuard
+where
+    F: Fn(DeviceNotification) + Send + 'static,
+{
+    let (devices, observer) = copy_all_devices_with_observer(move |device, name| {
+        callback(name.with_device(device.clone()));
+    });
+    DeviceObserverGuard {
+        observer: Some(observer),
+        devices,
+    }
+}
+
 /// Remove a device observer (macOS only).
 ///
 /// Call this function when you no longer need to receive device notifications.
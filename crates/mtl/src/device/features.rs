@@ -117,6 +117,14 @@ impl Device {
         unsafe { msg_send_0(self.as_ptr(), sel!(supportsPullModelInterpolation)) }
     }
 
+    /// Check if render passes can target several layers at once through
+    /// the vertex function's `[[render_target_array_index]]` output.
+    ///
+    /// Layered rendering requires the `APPLE5` or `MAC2` GPU family.
+    pub fn supports_layered_rendering(&self) -> bool {
+        self.supports_family(GPUFamily::APPLE5) || self.supports_family(GPUFamily::MAC2)
+    }
+
     /// Check if shader barycentric coordinates are supported.
     ///
     /// C++ equivalent: `bool supportsShaderBarycentricCoordinates() const`
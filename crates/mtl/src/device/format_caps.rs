@@ -0,0 +1,369 @@
+//! Pixel format capability queries.
+//!
+//! Metal only exposes a handful of per-format queries, so
+//! [`Device::format_capabilities`] combines them with the pixel format
+//! capability tables from Apple's Metal feature set tables.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let caps = device.format_capabilities(PixelFormat::RGBA16_FLOAT);
+//! assert!(caps.renderable() && caps.blendable);
+//! if !caps.supports_sample_count(4) {
+//!     plan.msaa = 1;
+//! }
+//! ```
+
+use mtl_foundation::UInteger;
+
+use super::Device;
+use crate::enums::{GPUFamily, PixelFormat, ReadWriteTextureTier};
+use crate::texture::{DecompressError, TextureData};
+
+/// Sample counts probed with [`Device::supports_texture_sample_count`].
+const SAMPLE_COUNTS: [UInteger; 4] = [1, 2, 4, 8];
+
+/// The `APPLEn` families, in order.
+const APPLE_FAMILIES: [GPUFamily; 10] = [
+    GPUFamily::APPLE1,
+    GPUFamily::APPLE2,
+    GPUFamily::APPLE3,
+    GPUFamily::APPLE4,
+    GPUFamily::APPLE5,
+    GPUFamily::APPLE6,
+    GPUFamily::APPLE7,
+    GPUFamily::APPLE8,
+    GPUFamily::APPLE9,
+    GPUFamily::APPLE10,
+];
+
+/// What a device can do with textures of one pixel format.
+///
+/// Returned by [`Device::format_capabilities`]. All fields are `false` (and
+/// `sample_counts` is zero) for formats the device does not support.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FormatCaps {
+    /// The device can create textures of this format.
+    pub supported: bool,
+    /// Shaders can sample the format with linear filtering.
+    pub filterable: bool,
+    /// The format can be used as a color attachment.
+    pub color_renderable: bool,
+    /// The format can be used as a depth or stencil attachment.
+    pub depth_stencil_renderable: bool,
+    /// Color attachments of this format support blending.
+    pub blendable: bool,
+    /// Bitmask of supported attachment sample counts; bit `n` is set if `n`
+    /// samples are supported. Zero if the format is not renderable.
+    pub sample_counts: u32,
+    /// Multisample attachments of this format can be resolved.
+    pub resolvable: bool,
+    /// Shaders can write to textures of this format with `access::write`.
+    pub writable: bool,
+    /// Shaders can read and write the same texture with
+    /// `access::read_write`.
+    pub read_write: bool,
+}
+
+impl FormatCaps {
+    /// Check if the format can be used as any kind of attachment.
+    pub fn renderable(&self) -> bool {
+        self.color_renderable || self.depth_stencil_renderable
+    }
+
+    /// Check if attachments of this format support `count` samples.
+    pub fn supports_sample_count(&self, count: UInteger) -> bool {
+        count < u32::BITS as UInteger && self.sample_counts & (1 << count) != 0
+    }
+
+    /// Iterate over the supported sample counts, lowest first.
+    pub fn sample_count_values(&self) -> impl Iterator<Item = UInteger> + '_ {
+        (1..u32::BITS as UInteger).filter(|&count| self.supports_sample_count(count))
+    }
+
+    /// Get the largest supported sample count, or 0 if the format is not
+    /// renderable.
+    pub fn max_sample_count(&self) -> UInteger {
+        self.sample_count_values().last().unwrap_or(0)
+    }
+}
+
+/// The device queries the capability tables depend on.
+#[derive(Copy, Clone, Debug)]
+struct DeviceQueries {
+    /// Highest supported `APPLEn` family, or 0 for none.
+    apple_family: u32,
+    mac2: bool,
+    bc: bool,
+    float32_filtering: bool,
+    msaa32: bool,
+    depth24_stencil8: bool,
+    read_write_tier: ReadWriteTextureTier,
+    sample_counts: u32,
+}
+
+impl Device {
+    /// Get what the device can do with textures of `format`.
+    ///
+    /// Uses [`supports_family`](Self::supports_family),
+    /// [`supports_32bit_float_filtering`](Self::supports_32bit_float_filtering),
+    /// [`supports_32bit_msaa`](Self::supports_32bit_msaa),
+    /// [`supports_bc_texture_compression`](Self::supports_bc_texture_compression),
+    /// [`read_write_texture_support`](Self::read_write_texture_support) and
+    /// [`supports_texture_sample_count`](Self::supports_texture_sample_count),
+    /// and Apple's pixel format capability tables for everything the API
+    /// cannot query.
+    pub fn format_capabilities(&self, format: PixelFormat) -> FormatCaps {
+        let apple_family = APPLE_FAMILIES
+            .iter()
+            .rposition(|&family| self.supports_family(family))
+            .map_or(0, |index| index as u32 + 1);
+        let sample_counts = SAMPLE_COUNTS
+            .iter()
+            .filter(|&&count| self.supports_texture_sample_count(count))
+            .fold(0, |mask, &count| mask | 1 << count);
+        let queries = DeviceQueries {
+            apple_family,
+            mac2: self.supports_family(GPUFamily::MAC2),
+            bc: self.supports_bc_texture_compression(),
+            float32_filtering: self.supports_32bit_float_filtering(),
+            msaa32: self.supports_32bit_msaa(),
+            depth24_stencil8: self.is_depth24_stencil8_pixel_format_supported(),
+            read_write_tier: self.read_write_texture_support(),
+            sample_counts,
+        };
+        format_caps(format, &queries)
+    }
+
+    /// Prepare a `width` x `height` image of `format` for upload.
+    ///
+    /// If the device supports `format` the data is returned as is;
+    /// otherwise compressed formats are decoded on the CPU with
+    /// [`TextureData::decompress`]. Create the texture with the returned
+    /// [`pixel_format`](TextureData::pixel_format).
+    pub fn check_and_fallback<'a>(
+        &self,
+        format: PixelFormat,
+        data: &'a [u8],
+        width: UInteger,
+        height: UInteger,
+    ) -> Result<TextureData<'a>, DecompressError> {
+        if self.format_capabilities(format).supported {
+            return Ok(TextureData::borrowed(format, data, width));
+        }
+        TextureData::decompress(format, data, width, height)
+    }
+}
+
+/// Look up `format` in the capability tables for a device with `queries`.
+fn format_caps(format: PixelFormat, queries: &DeviceQueries) -> FormatCaps {
+    if !is_supported(format, queries) {
+        return FormatCaps::default();
+    }
+    let apple = queries.apple_family;
+
+    // Compressed and packed YUV formats can only be sampled.
+    if format.is_compressed() || matches!(format, PixelFormat::GBGR422 | PixelFormat::BGRG422) {
+        return FormatCaps {
+            supported: true,
+            filterable: true,
+            ..FormatCaps::default()
+        };
+    }
+
+    if format.is_depth_stencil() {
+        let resolvable = if format.has_stencil() {
+            queries.mac2 || apple >= 5
+        } else {
+            queries.mac2 || apple >= 3
+        };
+        return FormatCaps {
+            supported: true,
+            filterable: format.is_depth(),
+            depth_stencil_renderable: true,
+            sample_counts: queries.sample_counts,
+            resolvable: resolvable && queries.sample_counts > 0b11,
+            ..FormatCaps::default()
+        };
+    }
+
+    let float32 = matches!(
+        format,
+        PixelFormat::R32_FLOAT | PixelFormat::RG32_FLOAT | PixelFormat::RGBA32_FLOAT
+    );
+    let channels32 = float32
+        || matches!(
+            format,
+            PixelFormat::R32_UINT
+                | PixelFormat::R32_SINT
+                | PixelFormat::RG32_UINT
+                | PixelFormat::RG32_SINT
+                | PixelFormat::RGBA32_UINT
+                | PixelFormat::RGBA32_SINT
+        );
+    let integer = format.is_integer();
+
+    // Alpha-only formats can never be rendered to, and shared-exponent
+    // formats only on Apple GPUs.
+    let color_renderable = match format {
+        PixelFormat::A8_UNORM => false,
+        PixelFormat::RGB9E5_FLOAT => apple >= 1,
+        _ => true,
+    };
+    let sample_counts = if !color_renderable {
+        0
+    } else if channels32 && !queries.msaa32 {
+        queries.sample_counts & 0b10
+    } else {
+        queries.sample_counts
+    };
+
+    FormatCaps {
+        supported: true,
+        filterable: !integer && (!float32 || queries.float32_filtering),
+        color_renderable,
+        depth_stencil_renderable: false,
+        blendable: color_renderable && !integer && (!float32 || queries.msaa32),
+        sample_counts,
+        resolvable: !integer && sample_counts > 0b11,
+        writable: color_renderable && (!format.is_srgb() || apple >= 2),
+        read_write: is_read_write(format, queries.read_write_tier),
+    }
+}
+
+/// Check if the device can create textures of `format` at all.
+fn is_supported(format: PixelFormat, queries: &DeviceQueries) -> bool {
+    let apple = queries.apple_family;
+    match format {
+        PixelFormat::INVALID | PixelFormat::UNSPECIALIZED => false,
+        f if f.0 >= PixelFormat::BC1_RGBA.0 && f.0 <= PixelFormat::BC7_RGBA_UNORM_SRGB.0 => {
+            queries.bc
+        }
+        f if f.0 >= PixelFormat::ASTC_4X4_HDR.0 && f.0 <= PixelFormat::ASTC_12X12_HDR.0 => {
+            apple >= 6
+        }
+        f if f.0 >= PixelFormat::ASTC_4X4_SRGB.0 && f.0 <= PixelFormat::ASTC_12X12_LDR.0 => {
+            apple >= 2
+        }
+        f if f.is_compressed() => apple >= 1,
+        PixelFormat::DEPTH24_UNORM_STENCIL8 | PixelFormat::X24_STENCIL8 => queries.depth24_stencil8,
+        PixelFormat::BGRA10_XR
+        | PixelFormat::BGRA10_XR_SRGB
+        | PixelFormat::BGR10_XR
+        | PixelFormat::BGR10_XR_SRGB => apple >= 3,
+        PixelFormat::B5G6R5_UNORM
+        | PixelFormat::A1BGR5_UNORM
+        | PixelFormat::ABGR4_UNORM
+        | PixelFormat::BGR5A1_UNORM
+        | PixelFormat::R8_UNORM_SRGB
+        | PixelFormat::RG8_UNORM_SRGB => apple >= 1,
+        _ => true,
+    }
+}
+
+/// Check if `format` supports `access::read_write` at `tier`.
+fn is_read_write(format: PixelFormat, tier: ReadWriteTextureTier) -> bool {
+    let tier1 = matches!(
+        format,
+        PixelFormat::R32_FLOAT | PixelFormat::R32_UINT | PixelFormat::R32_SINT
+    );
+    let tier2 = matches!(
+        format,
+        PixelFormat::RGBA32_FLOAT
+            | PixelFormat::RGBA32_UINT
+            | PixelFormat::RGBA32_SINT
+            | PixelFormat::RGBA16_FLOAT
+            | PixelFormat::RGBA16_UINT
+            | PixelFormat::RGBA16_SINT
+            | PixelFormat::RGBA8_UNORM
+            | PixelFormat::RGBA8_UINT
+            | PixelFormat::RGBA8_SINT
+            | PixelFormat::R16_FLOAT
+            | PixelFormat::R16_UINT
+            | PixelFormat::R16_SINT
+            | PixelFormat::R8_UNORM
+            | PixelFormat::R8_UINT
+            | PixelFormat::R8_SINT
+    );
+    (tier1 && tier.0 >= ReadWriteTextureTier::TIER1.0)
+        || (tier2 && tier.0 >= ReadWriteTextureTier::TIER2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::system_default;
+
+    fn apple7() -> DeviceQueries {
+        DeviceQueries {
+            apple_family: 7,
+            mac2: true,
+            bc: true,
+            float32_filtering: true,
+            msaa32: true,
+            depth24_stencil8: false,
+            read_write_tier: ReadWriteTextureTier::TIER2,
+            sample_counts: 0b1_0001_0110,
+        }
+    }
+
+    fn apple4() -> DeviceQueries {
+        DeviceQueries {
+            apple_family: 4,
+            mac2: false,
+            bc: false,
+            float32_filtering: false,
+            msaa32: false,
+            depth24_stencil8: false,
+            read_write_tier: ReadWriteTextureTier::TIER1,
+            sample_counts: 0b1_0001_0110,
+        }
+    }
+
+    #[test]
+    fn test_format_caps_tables() {
+        let rgba16f = format_caps(PixelFormat::RGBA16_FLOAT, &apple7());
+        assert!(rgba16f.filterable && rgba16f.color_renderable && rgba16f.blendable);
+        assert!(rgba16f.resolvable && rgba16f.writable && rgba16f.read_write);
+        assert_eq!(
+            rgba16f.sample_count_values().collect::<Vec<_>>(),
+            [1, 2, 4, 8]
+        );
+        assert_eq!(rgba16f.max_sample_count(), 8);
+
+        let rgba32f = format_caps(PixelFormat::RGBA32_FLOAT, &apple4());
+        assert!(!rgba32f.filterable && !rgba32f.blendable && !rgba32f.resolvable);
+        assert_eq!(rgba32f.max_sample_count(), 1);
+        assert!(!rgba32f.read_write);
+
+        let r32u = format_caps(PixelFormat::R32_UINT, &apple4());
+        assert!(r32u.color_renderable && r32u.read_write);
+        assert!(!r32u.filterable && !r32u.blendable && !r32u.resolvable);
+
+        let bc7 = format_caps(PixelFormat::BC7_RGBA_UNORM, &apple4());
+        assert_eq!(bc7, FormatCaps::default());
+        let bc7 = format_caps(PixelFormat::BC7_RGBA_UNORM, &apple7());
+        assert!(bc7.supported && bc7.filterable && !bc7.renderable());
+
+        let depth = format_caps(PixelFormat::DEPTH32_FLOAT, &apple7());
+        assert!(depth.depth_stencil_renderable && !depth.color_renderable);
+        assert!(depth.resolvable && !depth.writable);
+        assert!(!format_caps(PixelFormat::DEPTH24_UNORM_STENCIL8, &apple7()).supported);
+
+        assert!(!format_caps(PixelFormat::ASTC_4X4_HDR, &apple4()).supported);
+        assert!(!format_caps(PixelFormat::INVALID, &apple7()).supported);
+    }
+
+    #[test]
+    fn test_format_capabilities() {
+        let device = system_default().expect("no Metal device");
+
+        let caps = device.format_capabilities(PixelFormat::RGBA8_UNORM);
+        assert!(caps.supported && caps.filterable && caps.color_renderable);
+        assert!(caps.blendable && caps.supports_sample_count(1));
+        assert!(caps.supports_sample_count(4));
+
+        let depth = device.format_capabilities(PixelFormat::DEPTH32_FLOAT);
+        assert!(depth.depth_stencil_renderable);
+    }
+}
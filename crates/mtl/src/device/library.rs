@@ -8,7 +8,7 @@ use mtl_foundation::Referencing;
 use mtl_sys::{msg_send_0, msg_send_2, msg_send_3, sel};
 
 use super::Device;
-use crate::library::{CompileOptions, Library};
+use crate::library::{CompileOptions, DynamicLibrary, Library};
 
 impl Device {
     // =========================================================================
@@ -218,6 +218,86 @@ impl Device {
         }
     }
 
+    /// Create a dynamic library from a library compiled with
+    /// [`LibraryType::DYNAMIC`](crate::enums::LibraryType::DYNAMIC).
+    ///
+    /// C++ equivalent: `DynamicLibrary* newDynamicLibrary(const Library*, NS::Error**)`
+    pub fn new_dynamic_library(
+        &self,
+        library: &Library,
+    ) -> Result<DynamicLibrary, mtl_foundation::Error> {
+        let mut error: *mut c_void = std::ptr::null_mut();
+        unsafe {
+            let ptr: *mut c_void = msg_send_2(
+                self.as_ptr(),
+                sel!(newDynamicLibrary: error:),
+                library.as_ptr(),
+                &mut error as *mut _,
+            );
+
+            if ptr.is_null() {
+                if !error.is_null() {
+                    let _: *mut c_void = msg_send_0(error, sel!(retain));
+                    return Err(mtl_foundation::Error::from_ptr(error)
+                        .expect("error pointer should be valid"));
+                }
+                return Err(mtl_foundation::Error::error(
+                    std::ptr::null_mut(),
+                    -1,
+                    std::ptr::null_mut(),
+                )
+                .expect("failed to create error object"));
+            }
+
+            Ok(DynamicLibrary::from_raw(ptr).expect("dynamic library should be valid"))
+        }
+    }
+
+    /// Load a dynamic library serialized with
+    /// [`DynamicLibrary::serialize_to_url`].
+    ///
+    /// C++ equivalent: `DynamicLibrary* newDynamicLibrary(const NS::URL*, NS::Error**)`
+    pub fn new_dynamic_library_with_url(
+        &self,
+        url: &mtl_foundation::Url,
+    ) -> Result<DynamicLibrary, mtl_foundation::Error> {
+        let mut error: *mut c_void = std::ptr::null_mut();
+        unsafe {
+            let ptr: *mut c_void = msg_send_2(
+                self.as_ptr(),
+                sel!(newDynamicLibraryWithURL: error:),
+                url.as_ptr(),
+                &mut error as *mut _,
+            );
+
+            if ptr.is_null() {
+                if !error.is_null() {
+                    let _: *mut c_void = msg_send_0(error, sel!(retain));
+                    return Err(mtl_foundation::Error::from_ptr(error)
+                        .expect("error pointer should be valid"));
+                }
+                return Err(mtl_foundation::Error::error(
+                    std::ptr::null_mut(),
+                    -1,
+                    std::ptr::null_mut(),
+                )
+                .expect("failed to create error object"));
+            }
+
+            Ok(DynamicLibrary::from_raw(ptr).expect("dynamic library should be valid"))
+        }
+    }
+
+    /// Load a dynamic library from a `.metallib` file written by
+    /// [`DynamicLibrary::serialize`].
+    pub fn new_dynamic_library_with_path(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<DynamicLibrary, mtl_foundation::Error> {
+        let url = crate::library::file_url(path.as_ref())?;
+        self.new_dynamic_library_with_url(&url)
+    }
+
     // =========================================================================
     // Async Library Creation
     // =========================================================================
@@ -227,6 +307,7 @@ impl Device {
     /// C++ equivalent: `void newLibrary(const NS::String* source, const CompileOptions* options, NewLibraryCompletionHandler)`
     ///
     /// The completion handler is called with the library and any error that occurred.
+    #[cfg(feature = "blocks")]
     pub fn new_library_with_source_async<F>(
         &self,
         source: &str,
@@ -281,6 +362,7 @@ impl Device {
     /// # Safety
     ///
     /// The descriptor pointer must be valid.
+    #[cfg(feature = "blocks")]
     pub unsafe fn new_library_with_stitched_descriptor_async<F>(
         &self,
         descriptor: *const c_void,
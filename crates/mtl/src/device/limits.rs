@@ -11,6 +11,7 @@ use super::Device;
 use crate::enums::{
     PixelFormat, ResourceOptions, SparsePageSize, SparseTextureRegionAlignmentMode, TextureType,
 };
+use crate::texture::Texture;
 use crate::types::{Region, Size, SizeAndAlign};
 
 impl Device {
@@ -166,6 +167,19 @@ impl Device {
         }
     }
 
+    /// Get the sparse tile size for an existing texture.
+    ///
+    /// Uses the texture's type, pixel format and sample count with the
+    /// default sparse page size; textures placed in a heap with a different
+    /// page size should use [`sparse_tile_size_with_page_size`](Self::sparse_tile_size_with_page_size).
+    pub fn sparse_tile_size_for_texture(&self, texture: &Texture) -> Size {
+        self.sparse_tile_size(
+            texture.texture_type(),
+            texture.pixel_format(),
+            texture.sample_count(),
+        )
+    }
+
     /// Convert pixel regions to tile regions for sparse textures.
     ///
     /// C++ equivalent: `void convertSparsePixelRegions(...)`
@@ -0,0 +1,371 @@
+//! GPU memory usage breakdown.
+//!
+//! [`Device::current_allocated_size`] says how much memory the device holds
+//! but not what for. A [`MemoryTracker`] keeps a reference to each buffer,
+//! texture and heap the app registers and builds a [`MemoryReport`] that
+//! splits the device total by resource kind and label, which is what is
+//! needed to find the cause of steady memory growth before iOS jetsam
+//! terminates the app.
+//!
+//! The tracker retains every resource it tracks, so untrack a resource
+//! with [`MemoryTracker::untrack`] when the app releases it; otherwise the
+//! tracker keeps it alive. Resources allocated from a heap are listed but
+//! not added to the tracked total, since their memory belongs to the heap;
+//! track the heap to account for it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let tracker = MemoryTracker::new(&device);
+//! tracker.track_buffer(&vertex_buffer);
+//! tracker.track_texture(&shadow_map);
+//!
+//! // Periodically, or on a memory warning:
+//! let report = tracker.report();
+//! eprintln!("{report}");
+//!
+//! // When the app is done with a resource:
+//! tracker.untrack(&shadow_map);
+//! ```
+
+use std::ffi::c_void;
+use std::fmt;
+use std::ptr::NonNull;
+use std::sync::Mutex;
+
+use mtl_foundation::{Referencing, UInteger};
+use mtl_sys::{msg_send_0, sel};
+
+use super::Device;
+use crate::{Buffer, Heap, Texture};
+
+/// The kind of a tracked resource.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ResourceKind {
+    /// A [`Buffer`].
+    Buffer,
+    /// A [`Texture`].
+    Texture,
+    /// A [`Heap`].
+    Heap,
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ResourceKind::Buffer => "buffer",
+            ResourceKind::Texture => "texture",
+            ResourceKind::Heap => "heap",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Tracked resources of one kind that share a label.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryReportEntry {
+    /// The resources' kind.
+    pub kind: ResourceKind,
+    /// The resources' label, if they have one.
+    pub label: Option<String>,
+    /// Number of resources.
+    pub count: usize,
+    /// Sum of the resources' allocated sizes in bytes.
+    pub allocated_size: UInteger,
+    /// Part of `allocated_size` allocated from heaps.
+    pub heap_backed_size: UInteger,
+}
+
+/// A breakdown of a device's memory by tracked resource.
+///
+/// Returned by [`MemoryTracker::report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// [`Device::current_allocated_size`] when the report was made.
+    pub device_allocated_size: UInteger,
+    /// Tracked resources grouped by kind and label, largest first.
+    pub entries: Vec<MemoryReportEntry>,
+}
+
+impl MemoryReport {
+    /// Get the memory held by tracked resources, leaving out resources
+    /// allocated from heaps, whose memory belongs to the heap.
+    pub fn tracked_size(&self) -> UInteger {
+        self.entries
+            .iter()
+            .map(|entry| entry.allocated_size - entry.heap_backed_size)
+            .sum()
+    }
+
+    /// Get the device memory not accounted for by tracked resources.
+    pub fn untracked_size(&self) -> UInteger {
+        self.device_allocated_size
+            .saturating_sub(self.tracked_size())
+    }
+
+    /// Get the allocated size of tracked resources of `kind`.
+    pub fn size_of_kind(&self, kind: ResourceKind) -> UInteger {
+        self.entries
+            .iter()
+            .filter(|entry| entry.kind == kind)
+            .map(|entry| entry.allocated_size)
+            .sum()
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "device: {} bytes, tracked: {} bytes, untracked: {} bytes",
+            self.device_allocated_size,
+            self.tracked_size(),
+            self.untracked_size()
+        )?;
+        for entry in &self.entries {
+            write!(
+                f,
+                "  {:>12} bytes  {:<7} x{:<4} {}",
+                entry.allocated_size,
+                entry.kind,
+                entry.count,
+                entry.label.as_deref().unwrap_or("<unlabeled>")
+            )?;
+            if entry.heap_backed_size > 0 {
+                write!(f, " ({} bytes from heaps)", entry.heap_backed_size)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A resource retained by the tracker.
+struct Tracked {
+    object: NonNull<c_void>,
+    kind: ResourceKind,
+}
+
+// SAFETY: Metal resources may be retained, released and queried from any
+// thread.
+unsafe impl Send for Tracked {}
+
+impl Tracked {
+    fn allocated_size(&self) -> UInteger {
+        unsafe { msg_send_0(self.object.as_ptr(), sel!(allocatedSize)) }
+    }
+
+    fn is_heap_backed(&self) -> bool {
+        if self.kind == ResourceKind::Heap {
+            return false;
+        }
+        unsafe {
+            let heap: *mut c_void = msg_send_0(self.object.as_ptr(), sel!(heap));
+            !heap.is_null()
+        }
+    }
+
+    fn label(&self) -> Option<String> {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.object.as_ptr(), sel!(label));
+            if ptr.is_null() {
+                return None;
+            }
+            let utf8_ptr: *const std::ffi::c_char =
+                msg_send_0(ptr as *const c_void, sel!(UTF8String));
+            if utf8_ptr.is_null() {
+                return None;
+            }
+            let c_str = std::ffi::CStr::from_ptr(utf8_ptr);
+            Some(c_str.to_string_lossy().into_owned())
+        }
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        unsafe {
+            msg_send_0::<()>(self.object.as_ptr(), sel!(release));
+        }
+    }
+}
+
+// ============================================================================
+// MemoryTracker
+// ============================================================================
+
+/// Registry of resources to include in a [`MemoryReport`].
+pub struct MemoryTracker {
+    device: Device,
+    tracked: Mutex<Vec<Tracked>>,
+}
+
+impl MemoryTracker {
+    /// Create an empty tracker for resources of `device`.
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+            tracked: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn track(&self, object: *const c_void, kind: ResourceKind) {
+        let Some(object) = NonNull::new(object as *mut c_void) else {
+            return;
+        };
+        let mut tracked = self.tracked.lock().unwrap();
+        if tracked.iter().any(|entry| entry.object == object) {
+            return;
+        }
+        unsafe {
+            let _: *mut c_void = msg_send_0(object.as_ptr(), sel!(retain));
+        }
+        tracked.push(Tracked { object, kind });
+    }
+
+    /// Include `buffer` in reports until it is untracked.
+    pub fn track_buffer(&self, buffer: &Buffer) {
+        self.track(buffer.as_ptr(), ResourceKind::Buffer);
+    }
+
+    /// Include `texture` in reports until it is untracked.
+    pub fn track_texture(&self, texture: &Texture) {
+        self.track(texture.as_ptr(), ResourceKind::Texture);
+    }
+
+    /// Include `heap` in reports until it is untracked.
+    pub fn track_heap(&self, heap: &Heap) {
+        self.track(heap.as_ptr(), ResourceKind::Heap);
+    }
+
+    /// Stop tracking `resource` and release the tracker's reference to it.
+    ///
+    /// Returns `false` if it was not tracked.
+    pub fn untrack<R: Referencing>(&self, resource: &R) -> bool {
+        let mut tracked = self.tracked.lock().unwrap();
+        let before = tracked.len();
+        tracked.retain(|entry| !std::ptr::eq(entry.object.as_ptr(), resource.as_ptr()));
+        tracked.len() != before
+    }
+
+    /// Get the number of resources being tracked.
+    pub fn len(&self) -> usize {
+        self.tracked.lock().unwrap().len()
+    }
+
+    /// Check if no resources are being tracked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stop tracking every resource.
+    pub fn clear(&self) {
+        self.tracked.lock().unwrap().clear();
+    }
+
+    /// Break down the device's memory by the tracked resources.
+    pub fn report(&self) -> MemoryReport {
+        let tracked = self.tracked.lock().unwrap();
+
+        let mut entries: Vec<MemoryReportEntry> = Vec::new();
+        for resource in tracked.iter() {
+            let kind = resource.kind;
+            let label = resource.label();
+            let size = resource.allocated_size();
+            let heap_backed_size = if resource.is_heap_backed() { size } else { 0 };
+            match entries
+                .iter_mut()
+                .find(|entry| entry.kind == kind && entry.label == label)
+            {
+                Some(entry) => {
+                    entry.count += 1;
+                    entry.allocated_size += size;
+                    entry.heap_backed_size += heap_backed_size;
+                }
+                None => entries.push(MemoryReportEntry {
+                    kind,
+                    label,
+                    count: 1,
+                    allocated_size: size,
+                    heap_backed_size,
+                }),
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.allocated_size));
+
+        MemoryReport {
+            device_allocated_size: self.device.current_allocated_size(),
+            entries,
+        }
+    }
+}
+
+impl fmt::Debug for MemoryTracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryTracker")
+            .field("device", &self.device)
+            .field("tracked", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::ResourceOptions;
+
+    #[test]
+    fn test_memory_report_totals() {
+        let report = MemoryReport {
+            device_allocated_size: 10_000,
+            entries: vec![
+                MemoryReportEntry {
+                    kind: ResourceKind::Heap,
+                    label: Some("scratch".into()),
+                    count: 1,
+                    allocated_size: 4096,
+                    heap_backed_size: 0,
+                },
+                MemoryReportEntry {
+                    kind: ResourceKind::Buffer,
+                    label: None,
+                    count: 3,
+                    allocated_size: 3072,
+                    heap_backed_size: 1024,
+                },
+            ],
+        };
+        assert_eq!(report.tracked_size(), 6144);
+        assert_eq!(report.untracked_size(), 3856);
+        assert_eq!(report.size_of_kind(ResourceKind::Buffer), 3072);
+        assert_eq!(report.size_of_kind(ResourceKind::Texture), 0);
+    }
+
+    #[test]
+    fn test_memory_tracker_groups_by_label() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let tracker = MemoryTracker::new(&device);
+        let buffers: Vec<_> = (0..2)
+            .map(|_| {
+                let buffer = device
+                    .new_buffer(4096, ResourceOptions::STORAGE_MODE_SHARED)
+                    .unwrap();
+                buffer.set_label("vertices");
+                tracker.track_buffer(&buffer);
+                buffer
+            })
+            .collect();
+
+        let report = tracker.report();
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].count, 2);
+        assert!(report.size_of_kind(ResourceKind::Buffer) >= 8192);
+
+        assert!(tracker.untrack(&buffers[0]));
+        assert!(!tracker.untrack(&buffers[0]));
+        assert_eq!(tracker.report().entries[0].count, 1);
+        tracker.clear();
+        assert!(tracker.report().entries.is_empty());
+        assert!(tracker.is_empty());
+    }
+}
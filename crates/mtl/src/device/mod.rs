@@ -19,7 +19,12 @@
 mod architecture;
 mod creation;
 mod features;
+mod format_caps;
 mod limits;
+#[cfg(feature = "memory-report")]
+mod memory_report;
+#[cfg(target_os = "macos")]
+mod peer_group;
 mod properties;
 
 // Resource creation modules
@@ -44,12 +49,27 @@ pub use creation::{Timestamp, system_default};
 
 #[cfg(target_os = "macos")]
 pub use creation::{
-    DeviceObserver, copy_all_devices, copy_all_devices_with_observer, remove_device_observer,
+    DeviceNotification, DeviceNotificationName, DeviceObserver, copy_all_devices,
+    remove_device_observer,
 };
+#[cfg(all(target_os = "macos", feature = "blocks"))]
+pub use creation::{DeviceObserverGuard, copy_all_devices_with_observer, observe_devices};
+
+#[cfg(target_os = "macos")]
+pub use peer_group::PeerGroup;
 
 // Re-export Architecture
 pub use architecture::Architecture;
 
+// Re-export FormatCaps
+pub use format_caps::FormatCaps;
+
+pub use pipeline::SourcePipelineError;
+
+// Re-export memory report types
+#[cfg(feature = "memory-report")]
+pub use memory_report::{MemoryReport, MemoryReportEntry, MemoryTracker, ResourceKind};
+
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
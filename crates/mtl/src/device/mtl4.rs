@@ -11,7 +11,7 @@ use super::Device;
 use crate::function_table::FunctionHandle;
 use crate::mtl4::{
     Archive, ArgumentTable, ArgumentTableDescriptor, CommandAllocator, CommandAllocatorDescriptor,
-    CommandQueue, CommandQueueDescriptor, Compiler, CompilerDescriptor, CounterHeap,
+    CommandBuffer, CommandQueue, CommandQueueDescriptor, Compiler, CompilerDescriptor, CounterHeap,
     CounterHeapDescriptor, CounterHeapType, PipelineDataSetSerializer,
     PipelineDataSetSerializerDescriptor,
 };
@@ -69,6 +69,24 @@ impl Device {
         }
     }
 
+    // =========================================================================
+    // Command Buffer
+    // =========================================================================
+
+    /// Create a new MTL4 command buffer.
+    ///
+    /// The command buffer is not tied to a queue. Record into it between
+    /// `begin_command_buffer()` and `end_command_buffer()`, then commit it on
+    /// any MTL4 command queue.
+    ///
+    /// C++ equivalent: `MTL4::CommandBuffer* newCommandBuffer()`
+    pub fn new_command_buffer(&self) -> Option<CommandBuffer> {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(newCommandBuffer));
+            CommandBuffer::from_raw(ptr)
+        }
+    }
+
     // =========================================================================
     // Command Queue
     // =========================================================================
@@ -0,0 +1,143 @@
+//! Groups of GPUs connected for direct peer-to-peer access.
+//!
+//! On Mac Pro systems, GPUs joined by Infinity Fabric Link form a peer
+//! group: each can read and write the others' private memory through
+//! remote buffer and texture views, without copying through system memory.
+//! [`PeerGroup`] enumerates the devices of a group in peer index order,
+//! creates remote views of a resource for a given peer, and creates shared
+//! events for ordering work across the group's command queues.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let group = PeerGroup::all().into_iter().next().expect("no peer group");
+//! let (first, second) = (group.device(0).unwrap(), group.device(1).unwrap());
+//! let event = group.new_shared_event().unwrap();
+//!
+//! // Render the left half on the first GPU, then copy it on the second.
+//! let remote = group.remote_texture(&left_half, 1).unwrap();
+//! first_command_buffer.encode_signal_event(event.as_event(), 1);
+//! second_command_buffer.encode_wait_for_event(event.as_event(), 1);
+//! second_blit.copy_from_texture_to_texture(&remote, &composite);
+//! ```
+
+use super::{Device, copy_all_devices};
+use crate::sync::SharedEvent;
+use crate::{Buffer, Texture};
+
+/// The devices of one peer group, ordered by peer index.
+#[derive(Clone, Debug)]
+pub struct PeerGroup {
+    id: u64,
+    devices: Vec<Device>,
+}
+
+impl PeerGroup {
+    /// Get the peer group `device` belongs to, or `None` if it has no
+    /// peers.
+    pub fn of(device: &Device) -> Option<Self> {
+        let id = device.peer_group_id();
+        if id == 0 {
+            return None;
+        }
+        Self::all().into_iter().find(|group| group.id == id)
+    }
+
+    /// Get every peer group on the system.
+    pub fn all() -> Vec<Self> {
+        let mut groups: Vec<Self> = Vec::new();
+        for device in copy_all_devices() {
+            let id = device.peer_group_id();
+            if id == 0 {
+                continue;
+            }
+            match groups.iter_mut().find(|group| group.id == id) {
+                Some(group) => group.devices.push(device),
+                None => groups.push(Self {
+                    id,
+                    devices: vec![device],
+                }),
+            }
+        }
+        for group in &mut groups {
+            group.devices.sort_by_key(Device::peer_index);
+        }
+        groups
+    }
+
+    /// Get the peer group ID shared by the devices.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Get the devices, ordered by peer index.
+    pub fn devices(&self) -> &[Device] {
+        &self.devices
+    }
+
+    /// Get the device with `peer_index`.
+    pub fn device(&self, peer_index: u32) -> Option<&Device> {
+        self.devices
+            .iter()
+            .find(|device| device.peer_index() == peer_index)
+    }
+
+    /// Get the number of devices in the group.
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    /// Check if the group has no devices.
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    /// Check if `device` belongs to the group.
+    pub fn contains(&self, device: &Device) -> bool {
+        device.peer_group_id() == self.id
+    }
+
+    /// Create a shared event on the first device in the group.
+    ///
+    /// Shared events can be signalled and waited on by command buffers of
+    /// any device, so one event orders work across the group's queues.
+    pub fn new_shared_event(&self) -> Option<SharedEvent> {
+        self.devices.first()?.new_shared_event()
+    }
+
+    /// Create a view of `buffer` for the device with `peer_index`.
+    ///
+    /// Returns `None` if there is no such peer, or if Metal can't create the
+    /// view; the buffer must use private storage on another device in the
+    /// group.
+    pub fn remote_buffer(&self, buffer: &Buffer, peer_index: u32) -> Option<Buffer> {
+        buffer.new_remote_buffer_view_for_device(self.device(peer_index)?)
+    }
+
+    /// Create a view of `texture` for the device with `peer_index`.
+    ///
+    /// Returns `None` if there is no such peer, or if Metal can't create the
+    /// view; the texture must use private storage on another device in the
+    /// group.
+    pub fn remote_texture(&self, texture: &Texture, peer_index: u32) -> Option<Texture> {
+        texture.new_remote_texture_view_for_device(self.device(peer_index)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_group_of_default_device() {
+        let device = crate::device::system_default().expect("no Metal device");
+        match PeerGroup::of(&device) {
+            Some(group) => {
+                assert!(group.contains(&device));
+                assert_eq!(group.len(), device.peer_count() as usize);
+                assert!(group.device(device.peer_index()).is_some());
+            }
+            None => assert_eq!(device.peer_group_id(), 0),
+        }
+    }
+}
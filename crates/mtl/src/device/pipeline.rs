@@ -3,18 +3,83 @@
 //! Corresponds to pipeline state creation methods in `Metal/MTLDevice.hpp`.
 
 use std::ffi::c_void;
+use std::fmt;
 
 use mtl_foundation::Referencing;
-use mtl_sys::{msg_send_0, msg_send_2, msg_send_3, sel};
+#[cfg(feature = "blocks")]
+use mtl_sys::msg_send_3;
+use mtl_sys::{msg_send_0, msg_send_2, sel};
 
 use super::Device;
 use crate::error::ValidationError;
-use crate::library::Function;
+use crate::library::{CompileOptions, Function};
 use crate::pipeline::{
-    ComputePipelineDescriptor, ComputePipelineReflection, ComputePipelineState,
-    MeshRenderPipelineDescriptor, RenderPipelineDescriptor, RenderPipelineReflection,
-    RenderPipelineState, TileRenderPipelineDescriptor,
+    ComputePipelineDescriptor, ComputePipelineState, MeshRenderPipelineDescriptor,
+    RenderPipelineDescriptor, RenderPipelineState, TileRenderPipelineDescriptor,
 };
+#[cfg(feature = "blocks")]
+use crate::pipeline::{ComputePipelineReflection, RenderPipelineReflection};
+
+/// Error produced by [`Device::new_compute_pipeline_from_source`].
+#[derive(Debug)]
+pub enum SourcePipelineError {
+    /// The source failed to compile. `log` holds the compiler output.
+    Compile {
+        log: String,
+        error: mtl_foundation::Error,
+    },
+    /// The library has no function with the requested name.
+    FunctionNotFound {
+        name: String,
+        available: Vec<String>,
+    },
+    /// The pipeline state could not be created from the function.
+    Pipeline {
+        log: String,
+        error: mtl_foundation::Error,
+    },
+}
+
+impl SourcePipelineError {
+    /// Get the compiler or pipeline creation log, if any.
+    pub fn log(&self) -> Option<&str> {
+        match self {
+            SourcePipelineError::Compile { log, .. }
+            | SourcePipelineError::Pipeline { log, .. } => Some(log),
+            SourcePipelineError::FunctionNotFound { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for SourcePipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourcePipelineError::Compile { log, .. } => {
+                write!(f, "failed to compile shader source:\n{}", log)
+            }
+            SourcePipelineError::FunctionNotFound { name, available } => {
+                write!(f, "no function named `{}` in library", name)?;
+                if !available.is_empty() {
+                    write!(f, " (available: {})", available.join(", "))?;
+                }
+                Ok(())
+            }
+            SourcePipelineError::Pipeline { log, .. } => {
+                write!(f, "failed to create compute pipeline:\n{}", log)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SourcePipelineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SourcePipelineError::Compile { error, .. }
+            | SourcePipelineError::Pipeline { error, .. } => Some(error),
+            SourcePipelineError::FunctionNotFound { .. } => None,
+        }
+    }
+}
 
 impl Device {
     // =========================================================================
@@ -55,7 +120,11 @@ impl Device {
                 .expect("failed to create error object"));
             }
 
-            Ok(RenderPipelineState::from_raw(ptr).expect("render pipeline state should be valid"))
+            let state =
+                RenderPipelineState::from_raw(ptr).expect("render pipeline state should be valid");
+            #[cfg(feature = "validation")]
+            let state = state.with_descriptor_formats(descriptor);
+            Ok(state)
         }
     }
 
@@ -141,10 +210,201 @@ impl Device {
                 .expect("failed to create error object"));
             }
 
+            let state =
+                RenderPipelineState::from_raw(ptr).expect("render pipeline state should be valid");
+            #[cfg(feature = "validation")]
+            let state = state.with_descriptor_formats(descriptor);
+            Ok(state)
+        }
+    }
+
+    /// Create a mesh render pipeline state with options.
+    ///
+    /// C++ equivalent: `RenderPipelineState* newRenderPipelineState(const MeshRenderPipelineDescriptor*, PipelineOption, RenderPipelineReflection**, NS::Error**)`
+    ///
+    /// # Safety
+    ///
+    /// The descriptor and reflection pointers must be valid.
+    pub unsafe fn new_mesh_render_pipeline_state_with_reflection(
+        &self,
+        descriptor: *const c_void,
+        options: crate::enums::PipelineOption,
+        reflection: *mut *mut c_void,
+    ) -> Result<RenderPipelineState, mtl_foundation::Error> {
+        let mut error: *mut c_void = std::ptr::null_mut();
+        unsafe {
+            let ptr: *mut c_void = mtl_sys::msg_send_4(
+                self.as_ptr(),
+                sel!(newRenderPipelineStateWithMeshDescriptor: options: reflection: error:),
+                descriptor,
+                options,
+                reflection,
+                &mut error as *mut _,
+            );
+
+            if ptr.is_null() {
+                if !error.is_null() {
+                    let _: *mut c_void = msg_send_0(error, sel!(retain));
+                    return Err(mtl_foundation::Error::from_ptr(error)
+                        .expect("error pointer should be valid"));
+                }
+                return Err(mtl_foundation::Error::error(
+                    std::ptr::null_mut(),
+                    -1,
+                    std::ptr::null_mut(),
+                )
+                .expect("failed to create error object"));
+            }
+
+            let state =
+                RenderPipelineState::from_raw(ptr).expect("render pipeline state should be valid");
+            #[cfg(feature = "validation")]
+            let state = state.with_descriptor_formats(descriptor);
+            Ok(state)
+        }
+    }
+
+    /// Create a mesh render pipeline state with validation.
+    ///
+    /// This safe method validates the descriptor before calling Metal APIs:
+    /// - Ensures a mesh function is set (required; the object function is optional)
+    /// - Validates raster sample count is supported by the device
+    ///
+    /// Draw with the resulting pipeline using
+    /// [`RenderCommandEncoder::draw_mesh_threadgroups`](crate::RenderCommandEncoder::draw_mesh_threadgroups)
+    /// or [`draw_mesh_threads`](crate::RenderCommandEncoder::draw_mesh_threads).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = MeshRenderPipelineDescriptor::new().unwrap();
+    /// desc.set_object_function(Some(&object_fn));
+    /// desc.set_mesh_function(Some(&mesh_fn));
+    /// desc.set_fragment_function(Some(&fragment_fn));
+    /// desc.color_attachments().unwrap().object(0).unwrap()
+    ///     .set_pixel_format(PixelFormat::BGRA8_UNORM);
+    ///
+    /// let pipeline = device.new_mesh_render_pipeline_state_with_descriptor(&desc)?;
+    /// encoder.set_render_pipeline_state(&pipeline);
+    /// encoder.set_mesh_buffer(&meshlets, 0, 0);
+    /// encoder.draw_mesh_threadgroups(
+    ///     Size::new(meshlet_count, 1, 1),
+    ///     Size::new(32, 1, 1),
+    ///     Size::new(64, 1, 1),
+    /// );
+    /// ```
+    pub fn new_mesh_render_pipeline_state_with_descriptor(
+        &self,
+        descriptor: &MeshRenderPipelineDescriptor,
+    ) -> Result<RenderPipelineState, ValidationError> {
+        if descriptor.mesh_function().is_none() {
+            return Err(ValidationError::MissingMeshFunction);
+        }
+
+        let sample_count = descriptor.raster_sample_count();
+        if sample_count > 1 && !self.supports_texture_sample_count(sample_count) {
+            return Err(ValidationError::UnsupportedRasterSampleCount(sample_count));
+        }
+
+        unsafe {
+            self.new_mesh_render_pipeline_state_with_reflection(
+                descriptor.as_ptr(),
+                crate::enums::PipelineOption::NONE,
+                std::ptr::null_mut(),
+            )
+            .map_err(ValidationError::from)
+        }
+    }
+
+    /// Create a tile render pipeline state with options.
+    ///
+    /// C++ equivalent: `RenderPipelineState* newRenderPipelineState(const TileRenderPipelineDescriptor*, PipelineOption, RenderPipelineReflection**, NS::Error**)`
+    ///
+    /// # Safety
+    ///
+    /// The descriptor and reflection pointers must be valid.
+    pub unsafe fn new_tile_render_pipeline_state_with_reflection(
+        &self,
+        descriptor: *const c_void,
+        options: crate::enums::PipelineOption,
+        reflection: *mut *mut c_void,
+    ) -> Result<RenderPipelineState, mtl_foundation::Error> {
+        let mut error: *mut c_void = std::ptr::null_mut();
+        unsafe {
+            let ptr: *mut c_void = mtl_sys::msg_send_4(
+                self.as_ptr(),
+                sel!(newRenderPipelineStateWithTileDescriptor: options: reflection: error:),
+                descriptor,
+                options,
+                reflection,
+                &mut error as *mut _,
+            );
+
+            if ptr.is_null() {
+                if !error.is_null() {
+                    let _: *mut c_void = msg_send_0(error, sel!(retain));
+                    return Err(mtl_foundation::Error::from_ptr(error)
+                        .expect("error pointer should be valid"));
+                }
+                return Err(mtl_foundation::Error::error(
+                    std::ptr::null_mut(),
+                    -1,
+                    std::ptr::null_mut(),
+                )
+                .expect("failed to create error object"));
+            }
+
             Ok(RenderPipelineState::from_raw(ptr).expect("render pipeline state should be valid"))
         }
     }
 
+    /// Create a tile render pipeline state with validation.
+    ///
+    /// This safe method validates the descriptor before calling Metal APIs:
+    /// - Ensures a tile function is set
+    /// - Validates raster sample count is supported by the device
+    ///
+    /// Tile shaders run on Apple family 4 and later GPUs. Bind the pipeline
+    /// inside a render pass and dispatch it with
+    /// [`RenderCommandEncoder::dispatch_threads_per_tile`](crate::RenderCommandEncoder::dispatch_threads_per_tile).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = TileRenderPipelineDescriptor::new().unwrap();
+    /// desc.set_tile_function(Some(&resolve_lights));
+    /// desc.set_threadgroup_size_matches_tile_size(true);
+    /// desc.color_attachments().unwrap().object(0).unwrap()
+    ///     .set_pixel_format(PixelFormat::BGRA8_UNORM);
+    ///
+    /// let pipeline = device.new_tile_render_pipeline_state_with_descriptor(&desc)?;
+    /// encoder.set_render_pipeline_state(&pipeline);
+    /// encoder.set_tile_buffer(&lights, 0, 0);
+    /// encoder.dispatch_threads_per_tile(Size::new(encoder.tile_width(), encoder.tile_height(), 1));
+    /// ```
+    pub fn new_tile_render_pipeline_state_with_descriptor(
+        &self,
+        descriptor: &TileRenderPipelineDescriptor,
+    ) -> Result<RenderPipelineState, ValidationError> {
+        if descriptor.tile_function().is_none() {
+            return Err(ValidationError::MissingTileFunction);
+        }
+
+        let sample_count = descriptor.raster_sample_count();
+        if sample_count > 1 && !self.supports_texture_sample_count(sample_count) {
+            return Err(ValidationError::UnsupportedRasterSampleCount(sample_count));
+        }
+
+        unsafe {
+            self.new_tile_render_pipeline_state_with_reflection(
+                descriptor.as_ptr(),
+                crate::enums::PipelineOption::NONE,
+                std::ptr::null_mut(),
+            )
+            .map_err(ValidationError::from)
+        }
+    }
+
     // =========================================================================
     // Compute Pipeline State Creation
     // =========================================================================
@@ -186,6 +446,39 @@ impl Device {
         }
     }
 
+    /// Compile `source`, look up `function_name`, and create a compute
+    /// pipeline state from it.
+    ///
+    /// Meant for quick experiments and tests; keep the [`Library`] around
+    /// when building several pipelines from the same source. Errors carry
+    /// the compiler log, or the function names the library does contain.
+    ///
+    /// [`Library`]: crate::library::Library
+    pub fn new_compute_pipeline_from_source(
+        &self,
+        source: &str,
+        function_name: &str,
+        options: Option<&CompileOptions>,
+    ) -> Result<ComputePipelineState, SourcePipelineError> {
+        let library = self
+            .new_library_with_source(source, options)
+            .map_err(|error| SourcePipelineError::Compile {
+                log: error.to_string(),
+                error,
+            })?;
+        let function = library
+            .new_function_with_name(function_name)
+            .ok_or_else(|| SourcePipelineError::FunctionNotFound {
+                name: function_name.to_string(),
+                available: library.function_names(),
+            })?;
+        self.new_compute_pipeline_state_with_function(&function)
+            .map_err(|error| SourcePipelineError::Pipeline {
+                log: error.to_string(),
+                error,
+            })
+    }
+
     /// Create a compute pipeline state with options.
     ///
     /// C++ equivalent: `ComputePipelineState* newComputePipelineState(const Function*, PipelineOption, ComputePipelineReflection**, NS::Error**)`
@@ -325,6 +618,7 @@ impl Device {
     /// C++ equivalent: `void newRenderPipelineState(const RenderPipelineDescriptor*, NewRenderPipelineStateCompletionHandler)`
     ///
     /// The completion handler is called with the pipeline state and any error that occurred.
+    #[cfg(feature = "blocks")]
     pub fn new_render_pipeline_state_async<F>(
         &self,
         descriptor: &RenderPipelineDescriptor,
@@ -366,6 +660,7 @@ impl Device {
     /// C++ equivalent: `void newRenderPipelineState(const RenderPipelineDescriptor*, PipelineOption, NewRenderPipelineStateWithReflectionCompletionHandler)`
     ///
     /// The completion handler is called with the pipeline state, reflection data, and any error.
+    #[cfg(feature = "blocks")]
     pub fn new_render_pipeline_state_with_reflection_async<F>(
         &self,
         descriptor: &RenderPipelineDescriptor,
@@ -419,6 +714,7 @@ impl Device {
     /// Create a tile render pipeline state with reflection asynchronously.
     ///
     /// C++ equivalent: `void newRenderPipelineState(const TileRenderPipelineDescriptor*, PipelineOption, NewRenderPipelineStateWithReflectionCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_tile_render_pipeline_state_with_reflection_async<F>(
         &self,
         descriptor: &TileRenderPipelineDescriptor,
@@ -472,6 +768,7 @@ impl Device {
     /// Create a mesh render pipeline state with reflection asynchronously.
     ///
     /// C++ equivalent: `void newRenderPipelineState(const MeshRenderPipelineDescriptor*, PipelineOption, NewRenderPipelineStateWithReflectionCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_mesh_render_pipeline_state_with_reflection_async<F>(
         &self,
         descriptor: &MeshRenderPipelineDescriptor,
@@ -529,6 +826,7 @@ impl Device {
     /// Create a compute pipeline state from a function asynchronously.
     ///
     /// C++ equivalent: `void newComputePipelineState(const Function*, NewComputePipelineStateCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_compute_pipeline_state_with_function_async<F>(
         &self,
         function: &Function,
@@ -568,6 +866,7 @@ impl Device {
     /// Create a compute pipeline state with reflection asynchronously.
     ///
     /// C++ equivalent: `void newComputePipelineState(const Function*, PipelineOption, NewComputePipelineStateWithReflectionCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_compute_pipeline_state_with_function_and_reflection_async<F>(
         &self,
         function: &Function,
@@ -621,6 +920,7 @@ impl Device {
     /// Create a compute pipeline state from a descriptor asynchronously.
     ///
     /// C++ equivalent: `void newComputePipelineState(const ComputePipelineDescriptor*, PipelineOption, NewComputePipelineStateWithReflectionCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_compute_pipeline_state_with_descriptor_async<F>(
         &self,
         descriptor: &ComputePipelineDescriptor,
@@ -674,8 +974,19 @@ impl Device {
 
 #[cfg(test)]
 mod tests {
+    use super::SourcePipelineError;
     use crate::device::system_default;
 
+    #[test]
+    fn test_tile_pipeline_requires_tile_function() {
+        let device = system_default().expect("no Metal device");
+        let descriptor = crate::TileRenderPipelineDescriptor::new().unwrap();
+        assert!(matches!(
+            device.new_tile_render_pipeline_state_with_descriptor(&descriptor),
+            Err(crate::ValidationError::MissingTileFunction)
+        ));
+    }
+
     #[test]
     fn test_new_compute_pipeline_state() {
         let device = system_default().expect("no Metal device");
@@ -705,4 +1016,85 @@ mod tests {
         assert!(pipeline.max_total_threads_per_threadgroup() > 0);
         assert!(pipeline.thread_execution_width() > 0);
     }
+
+    #[test]
+    fn test_new_compute_pipeline_from_source() {
+        let device = system_default().expect("no Metal device");
+        let source = r#"
+            kernel void fill(device uint* data [[buffer(0)]], uint id [[thread_position_in_grid]]) {
+                data[id] = id;
+            }
+        "#;
+
+        let pipeline = device.new_compute_pipeline_from_source(source, "fill", None);
+        assert!(pipeline.is_ok());
+
+        match device.new_compute_pipeline_from_source(source, "missing", None) {
+            Err(SourcePipelineError::FunctionNotFound { available, .. }) => {
+                assert_eq!(available, ["fill"]);
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+
+        let error = device
+            .new_compute_pipeline_from_source("kernel void broken( {", "broken", None)
+            .unwrap_err();
+        assert!(matches!(error, SourcePipelineError::Compile { .. }));
+        assert!(!error.log().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_new_mesh_render_pipeline_state() {
+        let device = system_default().expect("no Metal device");
+
+        let descriptor = crate::MeshRenderPipelineDescriptor::new().unwrap();
+        assert!(matches!(
+            device.new_mesh_render_pipeline_state_with_descriptor(&descriptor),
+            Err(crate::ValidationError::MissingMeshFunction)
+        ));
+
+        if !device.supports_family(crate::GPUFamily::METAL3) {
+            return;
+        }
+
+        let source = r#"
+            #include <metal_stdlib>
+            using namespace metal;
+
+            struct VertexOut {
+                float4 position [[position]];
+            };
+
+            using TriangleMesh = mesh<VertexOut, void, 3, 1, topology::triangle>;
+
+            [[mesh]] void test_mesh(TriangleMesh output) {
+                output.set_primitive_count(1);
+                for (uint i = 0; i < 3; i++) {
+                    VertexOut v;
+                    v.position = float4(float(i & 1) * 2.0 - 1.0, float(i >> 1) * 2.0 - 1.0, 0.0, 1.0);
+                    output.set_vertex(i, v);
+                    output.set_index(i, i);
+                }
+            }
+
+            fragment float4 test_fragment() {
+                return float4(1.0);
+            }
+        "#;
+
+        let library = device
+            .new_library_with_source(source, None)
+            .expect("failed to compile shader");
+        descriptor.set_mesh_function(library.new_function_with_name("test_mesh").as_ref());
+        descriptor.set_fragment_function(library.new_function_with_name("test_fragment").as_ref());
+        descriptor
+            .color_attachments()
+            .unwrap()
+            .object(0)
+            .unwrap()
+            .set_pixel_format(crate::PixelFormat::RGBA8_UNORM);
+
+        let pipeline = device.new_mesh_render_pipeline_state_with_descriptor(&descriptor);
+        assert!(pipeline.is_ok());
+    }
 }
@@ -8,7 +8,7 @@ use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_2, sel};
 
 use super::{Architecture, Device};
-use crate::enums::{ArgumentBuffersTier, DeviceLocation, ReadWriteTextureTier};
+use crate::enums::{ArgumentBuffersTier, DeviceLocation, GPUFamily, ReadWriteTextureTier};
 use crate::types::SamplePosition;
 
 impl Device {
@@ -113,6 +113,34 @@ impl Device {
         unsafe { msg_send_0(self.as_ptr(), sel!(isRemovable)) }
     }
 
+    /// Check if the device is a software or paravirtualized renderer rather
+    /// than a physical GPU.
+    ///
+    /// macOS virtual machines, including most hosted CI runners, expose an
+    /// "Apple Paravirtual device" that forwards to the host or renders on
+    /// the CPU. It supports few GPU families and its timings and limits say
+    /// little about real hardware. This is a heuristic based on the device
+    /// name and on the device missing every Apple and Mac GPU family; test
+    /// harnesses can use it to skip performance-sensitive checks.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let device = device::system_default().unwrap();
+    /// if device.is_software_renderer() {
+    ///     eprintln!("skipping: {} is not a hardware GPU", device.name());
+    ///     return;
+    /// }
+    /// ```
+    pub fn is_software_renderer(&self) -> bool {
+        if is_software_renderer_name(self.name()) {
+            return true;
+        }
+        ![GPUFamily::APPLE1, GPUFamily::MAC1, GPUFamily::MAC2]
+            .into_iter()
+            .any(|family| self.supports_family(family))
+    }
+
     /// Get the current allocated memory size in bytes.
     ///
     /// C++ equivalent: `NS::UInteger currentAllocatedSize() const`
@@ -169,6 +197,24 @@ impl Device {
         unsafe { msg_send_0(self.as_ptr(), sel!(peerCount)) }
     }
 
+    /// Check if `other` is a different device in the same peer group.
+    ///
+    /// Peers are GPUs connected by Infinity Fabric Link on macOS, which can
+    /// access each other's private buffers and textures through
+    /// [`Buffer::new_remote_buffer_view_for_device`](crate::Buffer::new_remote_buffer_view_for_device).
+    /// See also `PeerGroup`.
+    pub fn is_peer_of(&self, other: &Device) -> bool {
+        let group = self.peer_group_id();
+        group != 0 && group == other.peer_group_id() && self.registry_id() != other.registry_id()
+    }
+
+    /// Get the peer group this device belongs to, or `None` if it has no
+    /// peers.
+    #[cfg(target_os = "macos")]
+    pub fn peer_group(&self) -> Option<super::PeerGroup> {
+        super::PeerGroup::of(self)
+    }
+
     /// Get the maximum data transfer rate in bytes per second.
     ///
     /// C++ equivalent: `uint64_t maxTransferRate() const`
@@ -316,6 +362,16 @@ impl Device {
     }
 }
 
+/// Device name fragments of known software and paravirtualized renderers.
+const SOFTWARE_RENDERER_NAMES: &[&str] = &["paravirtual", "software", "llvmpipe", "swiftshader"];
+
+fn is_software_renderer_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SOFTWARE_RENDERER_NAMES
+        .iter()
+        .any(|fragment| name.contains(fragment))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::device::system_default;
@@ -344,4 +400,18 @@ mod tests {
         assert!(cpu > 0);
         assert!(gpu > 0);
     }
+
+    #[test]
+    fn test_device_peer_group() {
+        let device = system_default().expect("no Metal device");
+        assert!(!device.is_peer_of(&device));
+    }
+
+    #[test]
+    fn test_software_renderer_names() {
+        assert!(super::is_software_renderer_name("Apple Paravirtual device"));
+        assert!(super::is_software_renderer_name("llvmpipe (LLVM 15.0.7)"));
+        assert!(!super::is_software_renderer_name("Apple M2 Pro"));
+        assert!(!super::is_software_renderer_name("AMD Radeon Pro 5500M"));
+    }
 }
@@ -10,7 +10,7 @@ use mtl_sys::{msg_send_1, msg_send_3, sel};
 use super::Device;
 use crate::enums::TextureType;
 use crate::error::ValidationError;
-use crate::texture::{Texture, TextureDescriptor};
+use crate::texture::{SharedTextureHandle, Texture, TextureDescriptor};
 
 impl Device {
     // =========================================================================
@@ -181,6 +181,34 @@ impl Device {
             Texture::from_raw(ptr)
         }
     }
+
+    // =========================================================================
+    // Shared Textures
+    // =========================================================================
+
+    /// Create a texture that can be shared across processes.
+    ///
+    /// The descriptor must use private storage. Pass the texture's
+    /// [`Texture::new_shared_texture_handle`] to another process (or another
+    /// device) and open it with [`new_shared_texture_from_handle`](Self::new_shared_texture_from_handle).
+    ///
+    /// Returns `None` if the device does not support shared textures for
+    /// this descriptor.
+    ///
+    /// C++ equivalent: `Texture* newSharedTexture(const TextureDescriptor*)`
+    pub fn new_shared_texture(&self, descriptor: &TextureDescriptor) -> Option<Texture> {
+        unsafe { self.new_shared_texture_with_descriptor(descriptor.as_ptr()) }
+    }
+
+    /// Open a texture shared by another process or device.
+    ///
+    /// The handle must have been created on this device, or on a device in
+    /// the same peer group.
+    ///
+    /// C++ equivalent: `Texture* newSharedTexture(const SharedTextureHandle*)`
+    pub fn new_shared_texture_from_handle(&self, handle: &SharedTextureHandle) -> Option<Texture> {
+        unsafe { self.new_shared_texture_with_handle(handle.as_ptr()) }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,510 @@
+//! A recording, GPU-free implementation of the device traits.
+//!
+//! [`MockDevice`] allocates buffers in host memory and completes command
+//! buffers immediately on commit. Every call is appended to a log shared by
+//! the device and everything it creates, so tests can assert on what the
+//! code under test did. Misuse that the Metal validation layer would report
+//! panics with a `Metal validation:` message.
+
+use std::ffi::c_void;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use mtl_foundation::UInteger;
+
+use super::{BufferLike, CommandBufferLike, CommandQueueLike, DeviceLike};
+use crate::enums::{CommandBufferStatus, ResourceOptions};
+
+/// Storage mode bits of [`ResourceOptions`].
+const STORAGE_MODE_MASK: UInteger = 0xF << 4;
+
+type CallLog = Arc<Mutex<Vec<MockCall>>>;
+
+// ============================================================================
+// MockCall
+// ============================================================================
+
+/// A call recorded by [`MockDevice`] or one of its objects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MockCall {
+    /// [`DeviceLike::new_buffer`].
+    NewBuffer {
+        length: UInteger,
+        options: ResourceOptions,
+    },
+    /// [`DeviceLike::new_buffer_with_bytes`].
+    NewBufferWithBytes {
+        length: UInteger,
+        options: ResourceOptions,
+    },
+    /// [`DeviceLike::new_command_queue`].
+    NewCommandQueue,
+    /// [`CommandQueueLike::command_buffer`].
+    NewCommandBuffer { queue_label: Option<String> },
+    /// [`CommandBufferLike::commit`].
+    Commit { label: Option<String> },
+    /// [`CommandBufferLike::wait_until_completed`].
+    WaitUntilCompleted { label: Option<String> },
+    /// [`BufferLike::did_modify_range`].
+    DidModifyRange {
+        location: UInteger,
+        length: UInteger,
+    },
+}
+
+// ============================================================================
+// MockDevice
+// ============================================================================
+
+/// A device that records calls instead of talking to a GPU.
+///
+/// Clones share the same call log.
+#[derive(Clone)]
+pub struct MockDevice {
+    name: String,
+    unified_memory: bool,
+    max_buffer_length: UInteger,
+    calls: CallLog,
+}
+
+impl MockDevice {
+    /// Create a mock device with a 256 MiB buffer limit and unified memory.
+    pub fn new() -> Self {
+        Self {
+            name: "Mock Device".to_string(),
+            unified_memory: true,
+            max_buffer_length: 256 << 20,
+            calls: Arc::default(),
+        }
+    }
+
+    /// Set the reported device name.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Set whether the device reports unified memory.
+    pub fn with_unified_memory(mut self, unified_memory: bool) -> Self {
+        self.unified_memory = unified_memory;
+        self
+    }
+
+    /// Set the largest buffer the device will allocate.
+    pub fn with_max_buffer_length(mut self, max_buffer_length: UInteger) -> Self {
+        self.max_buffer_length = max_buffer_length;
+        self
+    }
+
+    /// Get a copy of every call recorded so far, in order.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Clear the call log.
+    pub fn clear_calls(&self) {
+        self.calls.lock().unwrap().clear();
+    }
+
+    fn record(&self, call: MockCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+
+    fn allocate(&self, length: UInteger, options: ResourceOptions) -> Option<MockBuffer> {
+        if length == 0 || length > self.max_buffer_length {
+            return None;
+        }
+        Some(MockBuffer {
+            storage: Arc::new(Mutex::new(vec![0; length.div_ceil(16)])),
+            length,
+            options,
+            label: Arc::default(),
+            calls: Arc::clone(&self.calls),
+        })
+    }
+}
+
+impl Default for MockDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceLike for MockDevice {
+    type Buffer = MockBuffer;
+    type CommandQueue = MockCommandQueue;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn has_unified_memory(&self) -> bool {
+        self.unified_memory
+    }
+
+    fn max_buffer_length(&self) -> UInteger {
+        self.max_buffer_length
+    }
+
+    fn new_buffer(&self, length: UInteger, options: ResourceOptions) -> Option<MockBuffer> {
+        self.record(MockCall::NewBuffer { length, options });
+        self.allocate(length, options)
+    }
+
+    fn new_buffer_with_bytes(&self, bytes: &[u8], options: ResourceOptions) -> Option<MockBuffer> {
+        self.record(MockCall::NewBufferWithBytes {
+            length: bytes.len(),
+            options,
+        });
+        let buffer = self.allocate(bytes.len(), options)?;
+        buffer.write(0, bytes);
+        Some(buffer)
+    }
+
+    fn new_command_queue(&self) -> Option<MockCommandQueue> {
+        self.record(MockCall::NewCommandQueue);
+        Some(MockCommandQueue {
+            label: Arc::default(),
+            calls: Arc::clone(&self.calls),
+        })
+    }
+}
+
+impl fmt::Debug for MockDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockDevice")
+            .field("name", &self.name)
+            .field("calls", &self.calls.lock().unwrap().len())
+            .finish()
+    }
+}
+
+// ============================================================================
+// MockBuffer
+// ============================================================================
+
+/// A host-memory buffer created by [`MockDevice`].
+///
+/// Contents are 16-byte aligned. Clones share the same storage.
+#[derive(Clone)]
+pub struct MockBuffer {
+    storage: Arc<Mutex<Vec<u128>>>,
+    length: UInteger,
+    options: ResourceOptions,
+    label: Arc<Mutex<Option<String>>>,
+    calls: CallLog,
+}
+
+impl MockBuffer {
+    /// Get the options the buffer was created with.
+    pub fn options(&self) -> ResourceOptions {
+        self.options
+    }
+
+    /// Get a copy of the buffer's bytes.
+    pub fn bytes(&self) -> Vec<u8> {
+        let storage = self.storage.lock().unwrap();
+        let bytes =
+            unsafe { std::slice::from_raw_parts(storage.as_ptr().cast::<u8>(), self.length) };
+        bytes.to_vec()
+    }
+
+    /// Copy `bytes` into the buffer at `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write extends past the end of the buffer.
+    pub fn write(&self, offset: UInteger, bytes: &[u8]) {
+        assert!(
+            offset
+                .checked_add(bytes.len())
+                .is_some_and(|end| end <= self.length),
+            "Metal validation: write of {} bytes at offset {} exceeds buffer length {}",
+            bytes.len(),
+            offset,
+            self.length
+        );
+        let mut storage = self.storage.lock().unwrap();
+        unsafe {
+            let dst = storage.as_mut_ptr().cast::<u8>().add(offset);
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len());
+        }
+    }
+}
+
+impl BufferLike for MockBuffer {
+    fn length(&self) -> UInteger {
+        self.length
+    }
+
+    fn contents(&self) -> Option<*mut c_void> {
+        let mode = ResourceOptions(self.options.0 & STORAGE_MODE_MASK);
+        if mode == ResourceOptions::STORAGE_MODE_PRIVATE
+            || mode == ResourceOptions::STORAGE_MODE_MEMORYLESS
+        {
+            return None;
+        }
+        // The allocation never moves, so the pointer stays valid for the
+        // lifetime of the storage, like Metal's contents pointer.
+        Some(self.storage.lock().unwrap().as_mut_ptr().cast())
+    }
+
+    fn did_modify_range(&self, location: UInteger, length: UInteger) {
+        assert!(
+            location
+                .checked_add(length)
+                .is_some_and(|end| end <= self.length),
+            "Metal validation: modified range of {} bytes at {} exceeds buffer length {}",
+            length,
+            location,
+            self.length
+        );
+        self.calls
+            .lock()
+            .unwrap()
+            .push(MockCall::DidModifyRange { location, length });
+    }
+
+    fn label(&self) -> Option<String> {
+        self.label.lock().unwrap().clone()
+    }
+
+    fn set_label(&self, label: &str) {
+        *self.label.lock().unwrap() = Some(label.to_string());
+    }
+}
+
+impl fmt::Debug for MockBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockBuffer")
+            .field("length", &self.length)
+            .field("label", &self.label())
+            .finish()
+    }
+}
+
+// ============================================================================
+// MockCommandQueue
+// ============================================================================
+
+/// A command queue created by [`MockDevice`].
+#[derive(Clone)]
+pub struct MockCommandQueue {
+    label: Arc<Mutex<Option<String>>>,
+    calls: CallLog,
+}
+
+impl CommandQueueLike for MockCommandQueue {
+    type CommandBuffer = MockCommandBuffer;
+
+    fn command_buffer(&self) -> Option<MockCommandBuffer> {
+        self.calls.lock().unwrap().push(MockCall::NewCommandBuffer {
+            queue_label: self.label(),
+        });
+        Some(MockCommandBuffer {
+            status: Arc::new(Mutex::new(CommandBufferStatus::NOT_ENQUEUED)),
+            label: Arc::default(),
+            calls: Arc::clone(&self.calls),
+        })
+    }
+
+    fn label(&self) -> Option<String> {
+        self.label.lock().unwrap().clone()
+    }
+
+    fn set_label(&self, label: &str) {
+        *self.label.lock().unwrap() = Some(label.to_string());
+    }
+}
+
+impl fmt::Debug for MockCommandQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockCommandQueue")
+            .field("label", &self.label())
+            .finish()
+    }
+}
+
+// ============================================================================
+// MockCommandBuffer
+// ============================================================================
+
+/// A command buffer created by [`MockCommandQueue`].
+///
+/// Completes as soon as it is committed.
+#[derive(Clone)]
+pub struct MockCommandBuffer {
+    status: Arc<Mutex<CommandBufferStatus>>,
+    label: Arc<Mutex<Option<String>>>,
+    calls: CallLog,
+}
+
+impl CommandBufferLike for MockCommandBuffer {
+    fn commit(&self) {
+        let mut status = self.status.lock().unwrap();
+        assert!(
+            *status == CommandBufferStatus::NOT_ENQUEUED
+                || *status == CommandBufferStatus::ENQUEUED,
+            "Metal validation: commit called on a command buffer that was already committed"
+        );
+        *status = CommandBufferStatus::COMPLETED;
+        self.calls.lock().unwrap().push(MockCall::Commit {
+            label: self.label(),
+        });
+    }
+
+    fn wait_until_completed(&self) {
+        assert!(
+            self.status() == CommandBufferStatus::COMPLETED,
+            "Metal validation: wait_until_completed called on an uncommitted command buffer"
+        );
+        self.calls
+            .lock()
+            .unwrap()
+            .push(MockCall::WaitUntilCompleted {
+                label: self.label(),
+            });
+    }
+
+    fn status(&self) -> CommandBufferStatus {
+        *self.status.lock().unwrap()
+    }
+
+    fn label(&self) -> Option<String> {
+        self.label.lock().unwrap().clone()
+    }
+
+    fn set_label(&self, label: &str) {
+        *self.label.lock().unwrap() = Some(label.to_string());
+    }
+}
+
+impl fmt::Debug for MockCommandBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockCommandBuffer")
+            .field("status", &self.status())
+            .field("label", &self.label())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_allocation_and_limits() {
+        let device = MockDevice::new().with_max_buffer_length(64);
+        assert!(
+            device
+                .new_buffer(0, ResourceOptions::STORAGE_MODE_SHARED)
+                .is_none()
+        );
+        assert!(
+            device
+                .new_buffer(65, ResourceOptions::STORAGE_MODE_SHARED)
+                .is_none()
+        );
+
+        let buffer = device
+            .new_buffer_with_bytes(&[1, 2, 3], ResourceOptions::STORAGE_MODE_SHARED)
+            .unwrap();
+        assert_eq!(buffer.length(), 3);
+        assert_eq!(buffer.bytes(), vec![1, 2, 3]);
+
+        let contents = buffer.contents().unwrap();
+        assert_eq!(contents as usize % 16, 0);
+        unsafe { *contents.cast::<u8>().add(1) = 9 };
+        assert_eq!(buffer.bytes(), vec![1, 9, 3]);
+
+        let private = device
+            .new_buffer(16, ResourceOptions::STORAGE_MODE_PRIVATE)
+            .unwrap();
+        assert!(private.contents().is_none());
+
+        assert_eq!(device.calls().len(), 4);
+        assert_eq!(
+            device.calls()[2],
+            MockCall::NewBufferWithBytes {
+                length: 3,
+                options: ResourceOptions::STORAGE_MODE_SHARED
+            }
+        );
+    }
+
+    #[test]
+    fn test_command_buffer_lifecycle() {
+        let device = MockDevice::new();
+        let queue = device.new_command_queue().unwrap();
+        queue.set_label("main");
+        let command_buffer = queue.command_buffer().unwrap();
+        command_buffer.set_label("frame");
+
+        assert_eq!(command_buffer.status(), CommandBufferStatus::NOT_ENQUEUED);
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+        assert_eq!(command_buffer.status(), CommandBufferStatus::COMPLETED);
+
+        let frame = Some("frame".to_string());
+        assert_eq!(
+            device.calls(),
+            vec![
+                MockCall::NewCommandQueue,
+                MockCall::NewCommandBuffer {
+                    queue_label: Some("main".to_string())
+                },
+                MockCall::Commit {
+                    label: frame.clone()
+                },
+                MockCall::WaitUntilCompleted { label: frame },
+            ]
+        );
+
+        device.clear_calls();
+        assert!(device.calls().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "already committed")]
+    fn test_double_commit_panics() {
+        let device = MockDevice::new();
+        let command_buffer = device
+            .new_command_queue()
+            .unwrap()
+            .command_buffer()
+            .unwrap();
+        command_buffer.commit();
+        command_buffer.commit();
+    }
+
+    #[test]
+    #[should_panic(expected = "uncommitted")]
+    fn test_wait_before_commit_panics() {
+        let device = MockDevice::new();
+        let command_buffer = device
+            .new_command_queue()
+            .unwrap()
+            .command_buffer()
+            .unwrap();
+        command_buffer.wait_until_completed();
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds buffer length")]
+    fn test_modified_range_out_of_bounds_panics() {
+        let device = MockDevice::new();
+        let buffer = device
+            .new_buffer(8, ResourceOptions::STORAGE_MODE_MANAGED)
+            .unwrap();
+        buffer.did_modify_range(4, 8);
+    }
+    #[test]
+    #[should_panic(expected = "exceeds buffer length")]
+    fn test_overflowing_write_panics() {
+        let device = MockDevice::new();
+        let buffer = device
+            .new_buffer(8, ResourceOptions::STORAGE_MODE_SHARED)
+            .unwrap();
+        buffer.write(UInteger::MAX, &[0; 2]);
+    }
+}
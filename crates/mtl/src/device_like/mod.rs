@@ -0,0 +1,215 @@
+//! Traits over the most commonly used device, buffer and queue APIs.
+//!
+//! Engine code written against [`DeviceLike`] instead of [`Device`] can be
+//! unit-tested without a GPU by substituting [`MockDevice`](mock::MockDevice)
+//! (feature `mock`), which records every call and enforces the same usage
+//! rules the Metal validation layer does.
+//!
+//! The traits cover buffer allocation, command queue creation and command
+//! buffer submission. Code that needs the rest of the API can take the
+//! concrete types directly, or add a bound such as
+//! `D: DeviceLike<Buffer = Buffer>`.
+//!
+//! # Example
+//!
+//! ```ignore
+//! fn upload<D: DeviceLike>(device: &D, data: &[u8]) -> Option<D::Buffer> {
+//!     let buffer = device.new_buffer_with_bytes(data, ResourceOptions::STORAGE_MODE_SHARED)?;
+//!     buffer.set_label("upload");
+//!     Some(buffer)
+//! }
+//!
+//! // On a Mac:
+//! upload(&device::system_default().unwrap(), &bytes);
+//!
+//! // In a unit test on any machine:
+//! let mock = MockDevice::new();
+//! let buffer = upload(&mock, &bytes).unwrap();
+//! assert_eq!(buffer.bytes(), bytes);
+//! assert_eq!(mock.calls().len(), 1);
+//! ```
+
+use std::ffi::c_void;
+
+use mtl_foundation::UInteger;
+
+use crate::enums::{CommandBufferStatus, ResourceOptions};
+use crate::{Buffer, CommandBuffer, CommandQueue, Device};
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+// ============================================================================
+// Traits
+// ============================================================================
+
+/// The subset of [`Buffer`] used by most engine code.
+pub trait BufferLike {
+    /// Get the length of the buffer in bytes.
+    fn length(&self) -> UInteger;
+
+    /// Get a CPU-accessible pointer to the buffer's contents.
+    fn contents(&self) -> Option<*mut c_void>;
+
+    /// Inform the device that a range of a managed buffer was modified.
+    fn did_modify_range(&self, location: UInteger, length: UInteger);
+
+    /// Get the debug label.
+    fn label(&self) -> Option<String>;
+
+    /// Set the debug label.
+    fn set_label(&self, label: &str);
+}
+
+/// The subset of [`CommandBuffer`] used by most engine code.
+pub trait CommandBufferLike {
+    /// Commit the command buffer for execution.
+    fn commit(&self);
+
+    /// Block until the command buffer completes execution.
+    fn wait_until_completed(&self);
+
+    /// Get the current status of the command buffer.
+    fn status(&self) -> CommandBufferStatus;
+
+    /// Get the debug label.
+    fn label(&self) -> Option<String>;
+
+    /// Set the debug label.
+    fn set_label(&self, label: &str);
+}
+
+/// The subset of [`CommandQueue`] used by most engine code.
+pub trait CommandQueueLike {
+    /// The command buffer type this queue creates.
+    type CommandBuffer: CommandBufferLike;
+
+    /// Create a new command buffer.
+    fn command_buffer(&self) -> Option<Self::CommandBuffer>;
+
+    /// Get the debug label.
+    fn label(&self) -> Option<String>;
+
+    /// Set the debug label.
+    fn set_label(&self, label: &str);
+}
+
+/// The subset of [`Device`] used by most engine code.
+pub trait DeviceLike {
+    /// The buffer type this device allocates.
+    type Buffer: BufferLike;
+    /// The command queue type this device creates.
+    type CommandQueue: CommandQueueLike;
+
+    /// Get the device name.
+    fn name(&self) -> &str;
+
+    /// Check if the device shares memory between the CPU and GPU.
+    fn has_unified_memory(&self) -> bool;
+
+    /// Get the largest buffer the device can allocate, in bytes.
+    fn max_buffer_length(&self) -> UInteger;
+
+    /// Create a new zero-filled buffer.
+    fn new_buffer(&self, length: UInteger, options: ResourceOptions) -> Option<Self::Buffer>;
+
+    /// Create a new buffer initialized with `bytes`.
+    fn new_buffer_with_bytes(&self, bytes: &[u8], options: ResourceOptions)
+    -> Option<Self::Buffer>;
+
+    /// Create a new command queue.
+    fn new_command_queue(&self) -> Option<Self::CommandQueue>;
+}
+
+// ============================================================================
+// Metal implementations
+// ============================================================================
+
+impl BufferLike for Buffer {
+    fn length(&self) -> UInteger {
+        Buffer::length(self)
+    }
+
+    fn contents(&self) -> Option<*mut c_void> {
+        Buffer::contents(self)
+    }
+
+    fn did_modify_range(&self, location: UInteger, length: UInteger) {
+        Buffer::did_modify_range(self, location, length)
+    }
+
+    fn label(&self) -> Option<String> {
+        Buffer::label(self)
+    }
+
+    fn set_label(&self, label: &str) {
+        Buffer::set_label(self, label)
+    }
+}
+
+impl CommandBufferLike for CommandBuffer {
+    fn commit(&self) {
+        CommandBuffer::commit(self)
+    }
+
+    fn wait_until_completed(&self) {
+        CommandBuffer::wait_until_completed(self)
+    }
+
+    fn status(&self) -> CommandBufferStatus {
+        CommandBuffer::status(self)
+    }
+
+    fn label(&self) -> Option<String> {
+        CommandBuffer::label(self)
+    }
+
+    fn set_label(&self, label: &str) {
+        CommandBuffer::set_label(self, label)
+    }
+}
+
+impl CommandQueueLike for CommandQueue {
+    type CommandBuffer = CommandBuffer;
+
+    fn command_buffer(&self) -> Option<CommandBuffer> {
+        CommandQueue::command_buffer(self)
+    }
+
+    fn label(&self) -> Option<String> {
+        CommandQueue::label(self)
+    }
+
+    fn set_label(&self, label: &str) {
+        CommandQueue::set_label(self, label)
+    }
+}
+
+impl DeviceLike for Device {
+    type Buffer = Buffer;
+    type CommandQueue = CommandQueue;
+
+    fn name(&self) -> &str {
+        Device::name(self)
+    }
+
+    fn has_unified_memory(&self) -> bool {
+        Device::has_unified_memory(self)
+    }
+
+    fn max_buffer_length(&self) -> UInteger {
+        Device::max_buffer_length(self)
+    }
+
+    fn new_buffer(&self, length: UInteger, options: ResourceOptions) -> Option<Buffer> {
+        Device::new_buffer(self, length, options)
+    }
+
+    fn new_buffer_with_bytes(&self, bytes: &[u8], options: ResourceOptions) -> Option<Buffer> {
+        Device::new_buffer_with_bytes(self, bytes, options)
+    }
+
+    fn new_command_queue(&self) -> Option<CommandQueue> {
+        Device::new_command_queue(self)
+    }
+}
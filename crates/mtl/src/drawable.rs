@@ -95,6 +95,7 @@ impl Drawable {
     /// C++ equivalent: `void addPresentedHandler(void (^)(Drawable*))`
     ///
     /// The handler is called when the drawable has been presented to the display.
+    #[cfg(feature = "blocks")]
     pub fn add_presented_handler<F>(&self, handler: F)
     where
         F: Fn(&Drawable) + Send + 'static,
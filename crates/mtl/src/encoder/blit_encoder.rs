@@ -10,7 +10,10 @@ use mtl_sys::{msg_send_0, msg_send_1, sel};
 
 use crate::Buffer;
 use crate::Texture;
+use crate::counter::CounterSampleBuffer;
 use crate::enums::BlitOption;
+use crate::indirect::IndirectCommandBuffer;
+use crate::resource::Resource;
 use crate::types::{Origin, Region, Size};
 
 /// A command encoder for data transfer operations.
@@ -100,6 +103,8 @@ impl BlitCommandEncoder {
     /// C++ equivalent: `void endEncoding()`
     #[inline]
     pub fn end_encoding(&self) {
+        #[cfg(feature = "validation")]
+        crate::validation::end_encoding(self.as_ptr(), "BlitCommandEncoder::end_encoding");
         unsafe {
             msg_send_0::<()>(self.as_ptr(), sel!(endEncoding));
         }
@@ -223,6 +228,11 @@ impl BlitCommandEncoder {
         destination_offset: UInteger,
         size: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::copy_from_buffer_to_buffer",
+        );
         unsafe {
             mtl_sys::msg_send_5::<(), *const c_void, UInteger, *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -256,6 +266,11 @@ impl BlitCommandEncoder {
         destination_level: UInteger,
         destination_origin: Origin,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::copy_from_buffer_to_texture",
+        );
         unsafe {
             mtl_sys::msg_send_9::<
                 (),
@@ -301,6 +316,11 @@ impl BlitCommandEncoder {
         destination_origin: Origin,
         options: BlitOption,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::copy_from_buffer_to_texture_with_options",
+        );
         unsafe {
             mtl_sys::msg_send_10::<
                 (),
@@ -351,6 +371,11 @@ impl BlitCommandEncoder {
         destination_bytes_per_row: UInteger,
         destination_bytes_per_image: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::copy_from_texture_to_buffer",
+        );
         unsafe {
             mtl_sys::msg_send_9::<
                 (),
@@ -396,6 +421,11 @@ impl BlitCommandEncoder {
         destination_bytes_per_image: UInteger,
         options: BlitOption,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::copy_from_texture_to_buffer_with_options",
+        );
         unsafe {
             mtl_sys::msg_send_10::<
                 (),
@@ -439,6 +469,11 @@ impl BlitCommandEncoder {
         source_texture: &Texture,
         destination_texture: &Texture,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::copy_from_texture_to_texture",
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, *const c_void>(
                 self.as_ptr(),
@@ -465,6 +500,11 @@ impl BlitCommandEncoder {
         destination_level: UInteger,
         destination_origin: Origin,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::copy_from_texture_to_texture_region",
+        );
         unsafe {
             mtl_sys::msg_send_9::<
                 (),
@@ -508,6 +548,11 @@ impl BlitCommandEncoder {
         slice_count: UInteger,
         level_count: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::copy_from_texture_to_texture_slices",
+        );
         unsafe {
             mtl_sys::msg_send_8::<
                 (),
@@ -542,7 +587,15 @@ impl BlitCommandEncoder {
     ///
     /// C++ equivalent: `void fillBuffer(const Buffer*, NS::Range, uint8_t)`
     pub fn fill_buffer(&self, buffer: &Buffer, offset: UInteger, length: UInteger, value: u8) {
-        let range = mtl_foundation::Range::new(offset, length);
+        self.fill_buffer_range(buffer, mtl_foundation::Range::new(offset, length), value);
+    }
+
+    /// Fill `range` bytes of a buffer with a value.
+    ///
+    /// C++ equivalent: `void fillBuffer(const Buffer*, NS::Range, uint8_t)`
+    pub fn fill_buffer_range(&self, buffer: &Buffer, range: mtl_foundation::Range, value: u8) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(self.as_ptr(), "BlitCommandEncoder::fill_buffer");
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, mtl_foundation::Range, u8>(
                 self.as_ptr(),
@@ -563,6 +616,8 @@ impl BlitCommandEncoder {
     /// C++ equivalent: `void generateMipmaps(const Texture*)`
     #[inline]
     pub fn generate_mipmaps(&self, texture: &Texture) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(self.as_ptr(), "BlitCommandEncoder::generate_mipmaps");
         unsafe {
             msg_send_1::<(), *const c_void>(
                 self.as_ptr(),
@@ -581,6 +636,11 @@ impl BlitCommandEncoder {
     /// C++ equivalent: `void optimizeContentsForCPUAccess(const Texture*)`
     #[inline]
     pub fn optimize_contents_for_cpu_access(&self, texture: &Texture) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::optimize_contents_for_cpu_access",
+        );
         unsafe {
             msg_send_1::<(), *const c_void>(
                 self.as_ptr(),
@@ -600,6 +660,11 @@ impl BlitCommandEncoder {
         slice: UInteger,
         level: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::optimize_contents_for_cpu_access_slice",
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -616,6 +681,11 @@ impl BlitCommandEncoder {
     /// C++ equivalent: `void optimizeContentsForGPUAccess(const Texture*)`
     #[inline]
     pub fn optimize_contents_for_gpu_access(&self, texture: &Texture) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::optimize_contents_for_gpu_access",
+        );
         unsafe {
             msg_send_1::<(), *const c_void>(
                 self.as_ptr(),
@@ -635,6 +705,11 @@ impl BlitCommandEncoder {
         slice: UInteger,
         level: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::optimize_contents_for_gpu_access_slice",
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -666,11 +741,27 @@ impl BlitCommandEncoder {
         }
     }
 
+    /// Synchronize any managed resource, such as a [`Buffer`] or
+    /// [`Texture`].
+    ///
+    /// C++ equivalent: `void synchronizeResource(const Resource*)`
+    #[inline]
+    pub fn synchronize_resource(&self, resource: &dyn Resource) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::synchronize_resource",
+        );
+        unsafe { self.synchronize_resource_ptr(resource.as_resource_ptr()) };
+    }
+
     /// Synchronize a managed buffer.
     ///
     /// C++ equivalent: `void synchronizeResource(const Resource*)`
     #[inline]
     pub fn synchronize_buffer(&self, buffer: &Buffer) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(self.as_ptr(), "BlitCommandEncoder::synchronize_buffer");
         unsafe { self.synchronize_resource_ptr(buffer.as_ptr()) };
     }
 
@@ -679,6 +770,8 @@ impl BlitCommandEncoder {
     /// C++ equivalent: `void synchronizeResource(const Resource*)`
     #[inline]
     pub fn synchronize_texture(&self, texture: &Texture) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(self.as_ptr(), "BlitCommandEncoder::synchronize_texture");
         unsafe { self.synchronize_resource_ptr(texture.as_ptr()) };
     }
 
@@ -687,6 +780,11 @@ impl BlitCommandEncoder {
     /// C++ equivalent: `void synchronizeTexture(const Texture*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn synchronize_texture_slice(&self, texture: &Texture, slice: UInteger, level: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "BlitCommandEncoder::synchronize_texture_slice",
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -831,13 +929,7 @@ impl BlitCommandEncoder {
     ) {
         let range = mtl_foundation::Range::new(source_offset, source_length);
         unsafe {
-            mtl_sys::msg_send_4::<
-                (),
-                *const c_void,
-                mtl_foundation::Range,
-                *const c_void,
-                UInteger,
-            >(
+            mtl_sys::msg_send_4::<(), *const c_void, mtl_foundation::Range, *const c_void, UInteger>(
                 self.as_ptr(),
                 sel!(copyIndirectCommandBuffer: sourceRange: destination: destinationIndex:),
                 source,
@@ -848,6 +940,28 @@ impl BlitCommandEncoder {
         }
     }
 
+    /// Copy `source_range` commands of an indirect command buffer into
+    /// `destination`, starting at `destination_index`.
+    ///
+    /// C++ equivalent: `void copyIndirectCommandBuffer(const IndirectCommandBuffer*, NS::Range, const IndirectCommandBuffer*, NS::UInteger)`
+    pub fn copy_indirect_command_buffer(
+        &self,
+        source: &IndirectCommandBuffer,
+        source_range: mtl_foundation::Range,
+        destination: &IndirectCommandBuffer,
+        destination_index: UInteger,
+    ) {
+        unsafe {
+            self.copy_indirect_command_buffer_ptr(
+                source.as_ptr(),
+                source_range.location,
+                source_range.length,
+                destination.as_ptr(),
+                destination_index,
+            );
+        }
+    }
+
     /// Optimize an indirect command buffer (raw pointer version).
     ///
     /// C++ equivalent: `void optimizeIndirectCommandBuffer(...)`
@@ -872,6 +986,24 @@ impl BlitCommandEncoder {
         }
     }
 
+    /// Remove redundant state and empty commands from `range` of an
+    /// indirect command buffer.
+    ///
+    /// C++ equivalent: `void optimizeIndirectCommandBuffer(const IndirectCommandBuffer*, NS::Range)`
+    pub fn optimize_indirect_command_buffer(
+        &self,
+        indirect_command_buffer: &IndirectCommandBuffer,
+        range: mtl_foundation::Range,
+    ) {
+        unsafe {
+            self.optimize_indirect_command_buffer_ptr(
+                indirect_command_buffer.as_ptr(),
+                range.location,
+                range.length,
+            );
+        }
+    }
+
     /// Reset commands in an indirect command buffer (raw pointer version).
     ///
     /// C++ equivalent: `void resetCommandsInBuffer(...)`
@@ -896,10 +1028,62 @@ impl BlitCommandEncoder {
         }
     }
 
+    /// Reset `range` commands of an indirect command buffer.
+    ///
+    /// C++ equivalent: `void resetCommandsInBuffer(const IndirectCommandBuffer*, NS::Range)`
+    pub fn reset_commands_in_buffer(
+        &self,
+        indirect_command_buffer: &IndirectCommandBuffer,
+        range: mtl_foundation::Range,
+    ) {
+        unsafe {
+            self.reset_commands_in_buffer_ptr(
+                indirect_command_buffer.as_ptr(),
+                range.location,
+                range.length,
+            );
+        }
+    }
+
     // =========================================================================
     // Counter Sampling
     // =========================================================================
 
+    /// Sample the GPU counters into `sample_buffer` at `sample_index`.
+    ///
+    /// C++ equivalent: `void sampleCountersInBuffer(const CounterSampleBuffer*, NS::UInteger, bool)`
+    pub fn sample_counters_in_buffer(
+        &self,
+        sample_buffer: &CounterSampleBuffer,
+        sample_index: UInteger,
+        barrier: bool,
+    ) {
+        unsafe {
+            self.sample_counters_in_buffer_ptr(sample_buffer.as_ptr(), sample_index, barrier)
+        };
+    }
+
+    /// Resolve `range` samples of `sample_buffer` into `destination_buffer`.
+    ///
+    /// C++ equivalent: `void resolveCounters(const CounterSampleBuffer*, NS::Range, const Buffer*, NS::UInteger)`
+    pub fn resolve_counters(
+        &self,
+        sample_buffer: &CounterSampleBuffer,
+        range: mtl_foundation::Range,
+        destination_buffer: &Buffer,
+        destination_offset: UInteger,
+    ) {
+        unsafe {
+            self.resolve_counters_ptr(
+                sample_buffer.as_ptr(),
+                range.location,
+                range.length,
+                destination_buffer,
+                destination_offset,
+            );
+        }
+    }
+
     /// Sample counters (raw pointer version).
     ///
     /// C++ equivalent: `void sampleCountersInBuffer(...)`
@@ -941,13 +1125,7 @@ impl BlitCommandEncoder {
     ) {
         let range = mtl_foundation::Range::new(offset, length);
         unsafe {
-            mtl_sys::msg_send_4::<
-                (),
-                *const c_void,
-                mtl_foundation::Range,
-                *const c_void,
-                UInteger,
-            >(
+            mtl_sys::msg_send_4::<(), *const c_void, mtl_foundation::Range, *const c_void, UInteger>(
                 self.as_ptr(),
                 sel!(resolveCounters: inRange: destinationBuffer: destinationOffset:),
                 sample_buffer,
@@ -1005,4 +1183,58 @@ mod tests {
             std::mem::size_of::<*mut c_void>()
         );
     }
+
+    #[test]
+    fn test_fill_and_texture_round_trip() {
+        use crate::enums::{PixelFormat, ResourceOptions};
+
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().expect("no command queue");
+        let source = device
+            .new_buffer(64, ResourceOptions::STORAGE_MODE_SHARED)
+            .expect("failed to create buffer");
+        let result = device
+            .new_buffer(64, ResourceOptions::STORAGE_MODE_SHARED)
+            .expect("failed to create buffer");
+        let desc =
+            crate::TextureDescriptor::texture_2d_descriptor(PixelFormat::RGBA8_UNORM, 4, 4, false)
+                .unwrap();
+        let texture = device
+            .new_texture_with_descriptor(&desc)
+            .expect("failed to create texture");
+
+        let command_buffer = queue.command_buffer().expect("no command buffer");
+        let blit = unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+            .expect("no blit encoder");
+        blit.fill_buffer_range(&source, mtl_foundation::Range::new(0, 64), 0x5a);
+        blit.copy_from_buffer_to_texture(
+            &source,
+            0,
+            16,
+            64,
+            Size::new(4, 4, 1),
+            &texture,
+            0,
+            0,
+            Origin::new(0, 0, 0),
+        );
+        blit.copy_from_texture_to_buffer(
+            &texture,
+            0,
+            0,
+            Origin::new(0, 0, 0),
+            Size::new(4, 4, 1),
+            &result,
+            0,
+            16,
+            64,
+        );
+        blit.end_encoding();
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+
+        let contents = result.contents().expect("no contents") as *const u8;
+        let bytes = unsafe { std::slice::from_raw_parts(contents, 64) };
+        assert!(bytes.iter().all(|&byte| byte == 0x5a));
+    }
 }
@@ -0,0 +1,319 @@
+//! Blit helpers for 3D textures and cube arrays.
+//!
+//! The raw copy methods take a slice, an origin and, for buffers, a row and
+//! image pitch. For 3D textures the depth lives in the origin and size
+//! rather than the slice, and the image pitch must cover a whole `width x
+//! height` plane. For cube arrays each cube occupies six consecutive slices.
+//! The helpers here compute those values from the texture and validate them
+//! before encoding, since Metal reports mistakes only through the
+//! validation layer.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Upload a 64^3 density volume packed in a buffer.
+//! let region = Region::new_3d(0, 0, 0, 64, 64, 64);
+//! encoder.copy_buffer_to_texture_3d(&staging, 0, &volume, 0, region)?;
+//!
+//! // Copy depth slices 16..32 into the start of another volume.
+//! encoder.copy_texture_depth_slices(&volume, 0, 16..32, &scratch, 0, 0)?;
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+use mtl_foundation::UInteger;
+
+use super::BlitCommandEncoder;
+use crate::enums::{PixelFormat, TextureType};
+use crate::types::{Origin, Region, Size};
+use crate::{Buffer, Texture};
+
+/// Number of slices one cube occupies in a cube or cube array texture.
+pub const CUBE_FACE_COUNT: UInteger = 6;
+
+/// Error produced by the volume blit helpers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlitError {
+    /// A texture has the wrong type for the helper.
+    TextureType(TextureType),
+    /// The pixel format has no fixed row and image pitch (packed YUV or
+    /// combined depth/stencil).
+    UnsupportedPixelFormat(PixelFormat),
+    /// A region, slice range or mipmap level lies outside a texture.
+    InvalidRegion,
+    /// The buffer is too small for the copy.
+    BufferTooSmall {
+        /// Bytes the copy reads or writes, including the offset.
+        required: UInteger,
+        /// Length of the buffer.
+        length: UInteger,
+    },
+}
+
+impl fmt::Display for BlitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlitError::TextureType(texture_type) => {
+                write!(f, "unexpected texture type {:?}", texture_type)
+            }
+            BlitError::UnsupportedPixelFormat(format) => {
+                write!(f, "pixel format {:?} cannot be copied to a buffer", format)
+            }
+            BlitError::InvalidRegion => write!(f, "region is outside the texture"),
+            BlitError::BufferTooSmall { required, length } => write!(
+                f,
+                "copy needs {} bytes but the buffer holds {}",
+                required, length
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BlitError {}
+
+/// Get the slice holding `face` of cube `cube` in a cube array texture.
+///
+/// Faces are ordered +X, -X, +Y, -Y, +Z, -Z.
+pub const fn cube_array_slice(cube: UInteger, face: UInteger) -> UInteger {
+    cube * CUBE_FACE_COUNT + face
+}
+
+/// Get the size of `texture` at mipmap `level`.
+fn level_size(texture: &Texture, level: UInteger) -> Size {
+    let mip = |extent: UInteger| (extent >> level).max(1);
+    Size::new(
+        mip(texture.width()),
+        mip(texture.height()),
+        mip(texture.depth()),
+    )
+}
+
+fn expect_type(texture: &Texture, types: &[TextureType]) -> Result<(), BlitError> {
+    let texture_type = texture.texture_type();
+    if types.contains(&texture_type) {
+        Ok(())
+    } else {
+        Err(BlitError::TextureType(texture_type))
+    }
+}
+
+/// Get the row and image pitch of a tightly packed copy of `size`.
+fn pitches(format: PixelFormat, size: Size) -> Result<(UInteger, UInteger), BlitError> {
+    let unsupported = BlitError::UnsupportedPixelFormat(format);
+    let bytes_per_row = format.bytes_per_row(size.width).ok_or(unsupported)?;
+    let bytes_per_image = format
+        .bytes_per_image(size.width, size.height)
+        .ok_or(unsupported)?;
+    Ok((bytes_per_row, bytes_per_image))
+}
+
+fn check_buffer(
+    buffer: &Buffer,
+    offset: UInteger,
+    bytes_per_image: UInteger,
+    depth: UInteger,
+) -> Result<UInteger, BlitError> {
+    let length = buffer.length();
+    let too_small = BlitError::BufferTooSmall {
+        required: UInteger::MAX,
+        length,
+    };
+    let bytes = bytes_per_image.checked_mul(depth).ok_or(too_small)?;
+    let required = offset.checked_add(bytes).ok_or(too_small)?;
+    if required > length {
+        return Err(BlitError::BufferTooSmall { required, length });
+    }
+    Ok(bytes)
+}
+
+impl BlitCommandEncoder {
+    // =========================================================================
+    // 3D Texture Copy
+    // =========================================================================
+
+    /// Copy a region between two 3D textures.
+    ///
+    /// `source_region.origin.z` and `destination_origin.z` select the first
+    /// depth image and `source_region.size.depth` the number of images.
+    pub fn copy_texture_3d_region(
+        &self,
+        source: &Texture,
+        source_level: UInteger,
+        source_region: Region,
+        destination: &Texture,
+        destination_level: UInteger,
+        destination_origin: Origin,
+    ) -> Result<(), BlitError> {
+        expect_type(source, &[TextureType::TYPE_3D])?;
+        expect_type(destination, &[TextureType::TYPE_3D])?;
+        let size = source_region.size;
+        if !source.contains_region(source_region.origin, size, source_level)
+            || !destination.contains_region(destination_origin, size, destination_level)
+        {
+            return Err(BlitError::InvalidRegion);
+        }
+        self.copy_from_texture_to_texture_region(
+            source,
+            0,
+            source_level,
+            source_region.origin,
+            size,
+            destination,
+            0,
+            destination_level,
+            destination_origin,
+        );
+        Ok(())
+    }
+
+    /// Copy whole depth images `source_depth` of one 3D texture level into
+    /// another, starting at depth `destination_depth`.
+    ///
+    /// Both levels must have the same width and height.
+    pub fn copy_texture_depth_slices(
+        &self,
+        source: &Texture,
+        source_level: UInteger,
+        source_depth: Range<UInteger>,
+        destination: &Texture,
+        destination_level: UInteger,
+        destination_depth: UInteger,
+    ) -> Result<(), BlitError> {
+        let plane = level_size(source, source_level);
+        let destination_plane = level_size(destination, destination_level);
+        if plane.width != destination_plane.width || plane.height != destination_plane.height {
+            return Err(BlitError::InvalidRegion);
+        }
+        let depth = source_depth.end.saturating_sub(source_depth.start);
+        self.copy_texture_3d_region(
+            source,
+            source_level,
+            Region::new_3d(0, 0, source_depth.start, plane.width, plane.height, depth),
+            destination,
+            destination_level,
+            Origin::new(0, 0, destination_depth),
+        )
+    }
+
+    // =========================================================================
+    // Cube Array Copy
+    // =========================================================================
+
+    /// Copy `cube_count` whole cubes, all six faces, between cube or cube
+    /// array textures.
+    ///
+    /// `source_cube` and `destination_cube` are cube indices, not slices;
+    /// use [`cube_array_slice`] to address a single face.
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_cubes(
+        &self,
+        source: &Texture,
+        source_cube: UInteger,
+        source_level: UInteger,
+        destination: &Texture,
+        destination_cube: UInteger,
+        destination_level: UInteger,
+        cube_count: UInteger,
+        level_count: UInteger,
+    ) -> Result<(), BlitError> {
+        let cubes = [TextureType::TYPE_CUBE, TextureType::TYPE_CUBE_ARRAY];
+        expect_type(source, &cubes)?;
+        expect_type(destination, &cubes)?;
+        let fits = |texture: &Texture, cube: UInteger, level: UInteger| {
+            cube.checked_add(cube_count)
+                .is_some_and(|end| end <= texture.array_length())
+                && level
+                    .checked_add(level_count)
+                    .is_some_and(|end| end <= texture.mipmap_level_count())
+        };
+        if cube_count == 0
+            || level_count == 0
+            || !fits(source, source_cube, source_level)
+            || !fits(destination, destination_cube, destination_level)
+        {
+            return Err(BlitError::InvalidRegion);
+        }
+        self.copy_from_texture_to_texture_slices(
+            source,
+            cube_array_slice(source_cube, 0),
+            source_level,
+            destination,
+            cube_array_slice(destination_cube, 0),
+            destination_level,
+            cube_count * CUBE_FACE_COUNT,
+            level_count,
+        );
+        Ok(())
+    }
+
+    // =========================================================================
+    // Buffer <-> 3D Texture Copy
+    // =========================================================================
+
+    /// Copy tightly packed depth images from `buffer` into a region of a 3D
+    /// texture.
+    ///
+    /// The row and image pitch are computed from the texture's pixel format
+    /// (block-compressed formats are packed by block). Returns the number of
+    /// bytes read from the buffer.
+    pub fn copy_buffer_to_texture_3d(
+        &self,
+        buffer: &Buffer,
+        offset: UInteger,
+        texture: &Texture,
+        level: UInteger,
+        region: Region,
+    ) -> Result<UInteger, BlitError> {
+        expect_type(texture, &[TextureType::TYPE_3D])?;
+        if !texture.contains_region(region.origin, region.size, level) {
+            return Err(BlitError::InvalidRegion);
+        }
+        let (bytes_per_row, bytes_per_image) = pitches(texture.pixel_format(), region.size)?;
+        let bytes = check_buffer(buffer, offset, bytes_per_image, region.size.depth)?;
+        self.copy_from_buffer_to_texture(
+            buffer,
+            offset,
+            bytes_per_row,
+            bytes_per_image,
+            region.size,
+            texture,
+            0,
+            level,
+            region.origin,
+        );
+        Ok(bytes)
+    }
+
+    /// Copy a region of a 3D texture into `buffer` as tightly packed depth
+    /// images.
+    ///
+    /// Returns the number of bytes written to the buffer.
+    pub fn copy_texture_3d_to_buffer(
+        &self,
+        texture: &Texture,
+        level: UInteger,
+        region: Region,
+        buffer: &Buffer,
+        offset: UInteger,
+    ) -> Result<UInteger, BlitError> {
+        expect_type(texture, &[TextureType::TYPE_3D])?;
+        if !texture.contains_region(region.origin, region.size, level) {
+            return Err(BlitError::InvalidRegion);
+        }
+        let (bytes_per_row, bytes_per_image) = pitches(texture.pixel_format(), region.size)?;
+        let bytes = check_buffer(buffer, offset, bytes_per_image, region.size.depth)?;
+        self.copy_from_texture_to_buffer(
+            texture,
+            0,
+            level,
+            region.origin,
+            region.size,
+            buffer,
+            offset,
+            bytes_per_row,
+            bytes_per_image,
+        );
+        Ok(bytes)
+    }
+}
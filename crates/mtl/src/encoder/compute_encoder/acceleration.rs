@@ -99,6 +99,37 @@ impl ComputeCommandEncoder {
         }
     }
 
+    /// Set a visible function table at a buffer index.
+    ///
+    /// C++ equivalent: `void setVisibleFunctionTable(const VisibleFunctionTable*, NS::UInteger)`
+    #[inline]
+    pub fn set_visible_function_table(
+        &self,
+        visible_function_table: &crate::VisibleFunctionTable,
+        buffer_index: UInteger,
+    ) {
+        unsafe {
+            self.set_visible_function_table_ptr(visible_function_table.as_ptr(), buffer_index)
+        };
+    }
+
+    /// Set an intersection function table at a buffer index.
+    ///
+    /// C++ equivalent: `void setIntersectionFunctionTable(const IntersectionFunctionTable*, NS::UInteger)`
+    #[inline]
+    pub fn set_intersection_function_table(
+        &self,
+        intersection_function_table: &crate::IntersectionFunctionTable,
+        buffer_index: UInteger,
+    ) {
+        unsafe {
+            self.set_intersection_function_table_ptr(
+                intersection_function_table.as_ptr(),
+                buffer_index,
+            )
+        };
+    }
+
     /// Set multiple visible function tables at a range of buffer indices (raw pointer version).
     ///
     /// C++ equivalent: `void setVisibleFunctionTables(const VisibleFunctionTable* const*, NS::Range)`
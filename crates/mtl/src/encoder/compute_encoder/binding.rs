@@ -7,6 +7,8 @@ use mtl_sys::sel;
 
 use crate::Buffer;
 use crate::Texture;
+use crate::encoder::InlineValue;
+use crate::object_ptrs::with_object_ptrs;
 
 use super::ComputeCommandEncoder;
 
@@ -20,6 +22,13 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void setBuffer(const Buffer*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_buffer(&self, buffer: &Buffer, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_buffer",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -42,6 +51,13 @@ impl ComputeCommandEncoder {
         stride: UInteger,
         index: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_buffer_with_stride",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, UInteger, UInteger, UInteger>(
                 self.as_ptr(),
@@ -59,6 +75,13 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void setBufferOffset(NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_buffer_offset(&self, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_buffer_offset",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), UInteger, UInteger>(
                 self.as_ptr(),
@@ -79,6 +102,13 @@ impl ComputeCommandEncoder {
         stride: UInteger,
         index: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_buffer_offset_with_stride",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), UInteger, UInteger, UInteger>(
                 self.as_ptr(),
@@ -95,6 +125,13 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void setBytes(const void*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_bytes(&self, bytes: &[u8], index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_bytes",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -106,11 +143,44 @@ impl ComputeCommandEncoder {
         }
     }
 
+    /// Set inline bytes from a single value.
+    ///
+    /// Copies `size_of::<T>()` bytes of `value`. Types larger than
+    /// [`MAX_INLINE_BYTES`](crate::encoder::MAX_INLINE_BYTES) fail to compile.
+    ///
+    /// C++ equivalent: `void setBytes(const void*, NS::UInteger, NS::UInteger)`
+    #[inline]
+    pub fn set_value<T: Copy>(&self, value: &T, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_value",
+            crate::validation::Binding::Buffer,
+            index,
+        );
+        unsafe {
+            mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
+                self.as_ptr(),
+                sel!(setBytes: length: atIndex:),
+                value as *const T as *const c_void,
+                InlineValue::<T>::SIZE as UInteger,
+                index,
+            );
+        }
+    }
+
     /// Set inline bytes at an index with attribute stride.
     ///
     /// C++ equivalent: `void setBytes(const void*, NS::UInteger, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_bytes_with_stride(&self, bytes: &[u8], stride: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_bytes_with_stride",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, UInteger, UInteger, UInteger>(
                 self.as_ptr(),
@@ -140,12 +210,7 @@ impl ComputeCommandEncoder {
     ) {
         let range = mtl_foundation::Range::new(range_location, range_length);
         unsafe {
-            mtl_sys::msg_send_3::<
-                (),
-                *const *const c_void,
-                *const UInteger,
-                mtl_foundation::Range,
-            >(
+            mtl_sys::msg_send_3::<(), *const *const c_void, *const UInteger, mtl_foundation::Range>(
                 self.as_ptr(),
                 sel!(setBuffers: offsets: withRange:),
                 buffers,
@@ -155,6 +220,36 @@ impl ComputeCommandEncoder {
         }
     }
 
+    /// Set buffers at consecutive indices starting at `start_index`.
+    ///
+    /// `offsets` holds one offset per buffer.
+    ///
+    /// C++ equivalent: `void setBuffers(const Buffer* const*, const NS::UInteger*, NS::Range)`
+    pub fn set_buffers(&self, buffers: &[&Buffer], offsets: &[UInteger], start_index: UInteger) {
+        assert_eq!(
+            buffers.len(),
+            offsets.len(),
+            "ComputeCommandEncoder::set_buffers needs one offset per buffer"
+        );
+        #[cfg(feature = "validation")]
+        if let Some(last) = buffers.len().checked_sub(1) {
+            crate::validation::check_binding(
+                self.as_ptr(),
+                "ComputeCommandEncoder::set_buffers",
+                crate::validation::Binding::Buffer,
+                start_index + last,
+            );
+        }
+        with_object_ptrs(buffers, |ptrs| unsafe {
+            self.set_buffers_ptr(
+                ptrs.as_ptr(),
+                offsets.as_ptr(),
+                start_index,
+                ptrs.len() as UInteger,
+            )
+        })
+    }
+
     /// Set multiple buffers at a range of indices with strides (raw pointer version).
     ///
     /// C++ equivalent: `void setBuffers(const Buffer* const*, const NS::UInteger*, const NS::UInteger*, NS::Range)`
@@ -199,6 +294,13 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void setTexture(const Texture*, NS::UInteger)`
     #[inline]
     pub fn set_texture(&self, texture: &Texture, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_texture",
+            crate::validation::Binding::Texture,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -234,6 +336,24 @@ impl ComputeCommandEncoder {
         }
     }
 
+    /// Set textures at consecutive indices starting at `start_index`.
+    ///
+    /// C++ equivalent: `void setTextures(const Texture* const*, NS::Range)`
+    pub fn set_textures(&self, textures: &[&Texture], start_index: UInteger) {
+        #[cfg(feature = "validation")]
+        if let Some(last) = textures.len().checked_sub(1) {
+            crate::validation::check_binding(
+                self.as_ptr(),
+                "ComputeCommandEncoder::set_textures",
+                crate::validation::Binding::Texture,
+                start_index + last,
+            );
+        }
+        with_object_ptrs(textures, |ptrs| unsafe {
+            self.set_textures_ptr(ptrs.as_ptr(), start_index, ptrs.len() as UInteger)
+        })
+    }
+
     // =========================================================================
     // Sampler Bindings
     // =========================================================================
@@ -243,6 +363,13 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void setSamplerState(const SamplerState*, NS::UInteger)`
     #[inline]
     pub fn set_sampler_state(&self, sampler: &crate::SamplerState, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_sampler_state",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -264,6 +391,13 @@ impl ComputeCommandEncoder {
         lod_max_clamp: f32,
         index: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_sampler_state_with_lod_clamps",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, f32, f32, UInteger>(
                 self.as_ptr(),
@@ -301,6 +435,24 @@ impl ComputeCommandEncoder {
         }
     }
 
+    /// Set sampler states at consecutive indices starting at `start_index`.
+    ///
+    /// C++ equivalent: `void setSamplerStates(const SamplerState* const*, NS::Range)`
+    pub fn set_sampler_states(&self, samplers: &[&crate::SamplerState], start_index: UInteger) {
+        #[cfg(feature = "validation")]
+        if let Some(last) = samplers.len().checked_sub(1) {
+            crate::validation::check_binding(
+                self.as_ptr(),
+                "ComputeCommandEncoder::set_sampler_states",
+                crate::validation::Binding::Sampler,
+                start_index + last,
+            );
+        }
+        with_object_ptrs(samplers, |ptrs| unsafe {
+            self.set_sampler_states_ptr(ptrs.as_ptr(), start_index, ptrs.len() as UInteger)
+        })
+    }
+
     /// Set multiple sampler states with LOD clamps at a range of indices (raw pointer version).
     ///
     /// C++ equivalent: `void setSamplerStates(const SamplerState* const*, const float*, const float*, NS::Range)`
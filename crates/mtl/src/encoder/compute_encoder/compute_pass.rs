@@ -0,0 +1,100 @@
+//! Builder-style helper for one-shot compute dispatches.
+
+use mtl_foundation::UInteger;
+
+use crate::types::Size;
+use crate::{Buffer, ComputePipelineState, SamplerState, Texture};
+
+use super::ComputeCommandEncoder;
+
+/// Binds resources by index and dispatches a compute pipeline in one chain.
+///
+/// Each binding method forwards to the encoder immediately; nothing is
+/// buffered. The pipeline is bound when the pass is created.
+///
+/// # Example
+///
+/// ```ignore
+/// ComputePass::new(&encoder, &pipeline)
+///     .buffer(0, &input, 0)
+///     .buffer(1, &output, 0)
+///     .bytes(2, &scale.to_ne_bytes())
+///     .dispatch(Size::new(count, 1, 1));
+/// ```
+pub struct ComputePass<'a> {
+    encoder: &'a ComputeCommandEncoder,
+    pipeline: &'a ComputePipelineState,
+}
+
+impl<'a> ComputePass<'a> {
+    /// Start a pass on `encoder` using `pipeline`.
+    pub fn new(encoder: &'a ComputeCommandEncoder, pipeline: &'a ComputePipelineState) -> Self {
+        encoder.set_compute_pipeline_state(pipeline);
+        Self { encoder, pipeline }
+    }
+
+    /// Bind a buffer at `index`.
+    pub fn buffer(self, index: UInteger, buffer: &Buffer, offset: UInteger) -> Self {
+        self.encoder.set_buffer(buffer, offset, index);
+        self
+    }
+
+    /// Bind inline constant bytes at `index`.
+    pub fn bytes(self, index: UInteger, bytes: &[u8]) -> Self {
+        self.encoder.set_bytes(bytes, index);
+        self
+    }
+
+    /// Bind a single value as inline bytes at `index`.
+    pub fn value<T: Copy>(self, index: UInteger, value: &T) -> Self {
+        self.encoder.set_value(value, index);
+        self
+    }
+
+    /// Bind a texture at `index`.
+    pub fn texture(self, index: UInteger, texture: &Texture) -> Self {
+        self.encoder.set_texture(texture, index);
+        self
+    }
+
+    /// Bind a sampler state at `index`.
+    pub fn sampler(self, index: UInteger, sampler: &SamplerState) -> Self {
+        self.encoder.set_sampler_state(sampler, index);
+        self
+    }
+
+    /// Reserve threadgroup memory at `index`.
+    pub fn threadgroup_memory(self, index: UInteger, length: UInteger) -> Self {
+        self.encoder.set_threadgroup_memory_length(length, index);
+        self
+    }
+
+    /// Dispatch exactly `grid` threads with an automatically chosen
+    /// threadgroup size.
+    ///
+    /// See [`ComputeCommandEncoder::dispatch_exact`].
+    pub fn dispatch(self, grid: Size) {
+        self.encoder.dispatch_exact(self.pipeline, grid);
+    }
+
+    /// Dispatch whole threadgroups covering `grid`.
+    ///
+    /// See [`ComputeCommandEncoder::dispatch_covering`].
+    pub fn dispatch_covering(self, grid: Size) {
+        self.encoder.dispatch_covering(self.pipeline, grid);
+    }
+
+    /// Dispatch with an explicit threadgroup count and size.
+    pub fn dispatch_threadgroups(self, threadgroups: Size, threads_per_threadgroup: Size) {
+        self.encoder
+            .dispatch_threadgroups(threadgroups, threads_per_threadgroup);
+    }
+}
+
+impl std::fmt::Debug for ComputePass<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComputePass")
+            .field("pipeline", self.pipeline)
+            .finish()
+    }
+}
@@ -5,8 +5,8 @@ use std::ffi::c_void;
 use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_1, sel};
 
-use crate::Buffer;
 use crate::types::{Region, Size};
+use crate::{Buffer, ComputePipelineState};
 
 use super::ComputeCommandEncoder;
 
@@ -57,6 +57,11 @@ impl ComputeCommandEncoder {
         threadgroups_per_grid: Size,
         threads_per_threadgroup: Size,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::dispatch_threadgroups",
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), Size, Size>(
                 self.as_ptr(),
@@ -77,6 +82,11 @@ impl ComputeCommandEncoder {
         indirect_buffer_offset: UInteger,
         threads_per_threadgroup: Size,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::dispatch_threadgroups_with_indirect_buffer",
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, Size>(
                 self.as_ptr(),
@@ -93,6 +103,8 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void dispatchThreads(MTL::Size, MTL::Size)`
     #[inline]
     pub fn dispatch_threads(&self, threads_per_grid: Size, threads_per_threadgroup: Size) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(self.as_ptr(), "ComputeCommandEncoder::dispatch_threads");
         unsafe {
             mtl_sys::msg_send_2::<(), Size, Size>(
                 self.as_ptr(),
@@ -102,4 +114,34 @@ impl ComputeCommandEncoder {
             );
         }
     }
+
+    // =========================================================================
+    // Convenience Dispatch
+    // =========================================================================
+
+    /// Bind `pipeline` and dispatch exactly `grid` threads.
+    ///
+    /// The threadgroup size is derived from the pipeline with
+    /// [`ComputePipelineState::threadgroup_size_for_grid`]. This uses
+    /// non-uniform threadgroups (`dispatchThreads`), so the shader does not
+    /// need bounds checks; the device must support non-uniform threadgroups.
+    pub fn dispatch_exact(&self, pipeline: &ComputePipelineState, grid: Size) {
+        self.set_compute_pipeline_state(pipeline);
+        self.dispatch_threads(grid, pipeline.threadgroup_size_for_grid(grid));
+    }
+
+    /// Bind `pipeline` and dispatch enough whole threadgroups to cover `grid`.
+    ///
+    /// Works on every device but may launch threads past the edge of the
+    /// grid, so the shader must bounds-check its thread position.
+    pub fn dispatch_covering(&self, pipeline: &ComputePipelineState, grid: Size) {
+        let group = pipeline.threadgroup_size_for_grid(grid);
+        let groups = Size::new(
+            grid.width.div_ceil(group.width),
+            grid.height.max(1).div_ceil(group.height),
+            grid.depth.max(1).div_ceil(group.depth),
+        );
+        self.set_compute_pipeline_state(pipeline);
+        self.dispatch_threadgroups(groups, group);
+    }
 }
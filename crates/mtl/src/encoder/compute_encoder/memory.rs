@@ -8,6 +8,8 @@ use mtl_sys::{msg_send_1, sel};
 use crate::Buffer;
 use crate::Texture;
 use crate::enums::{BarrierScope, ResourceUsage};
+use crate::object_ptrs::{with_object_ptrs, with_resource_ptrs};
+use crate::resource::Resource;
 
 use super::ComputeCommandEncoder;
 
@@ -88,6 +90,23 @@ impl ComputeCommandEncoder {
         unsafe { self.use_resource_ptr(texture.as_ptr(), usage) };
     }
 
+    /// Declare that a resource will be used.
+    ///
+    /// C++ equivalent: `void useResource(const Resource*, MTL::ResourceUsage)`
+    #[inline]
+    pub fn use_resource(&self, resource: &dyn Resource, usage: ResourceUsage) {
+        unsafe { self.use_resource_ptr(resource.as_resource_ptr(), usage) };
+    }
+
+    /// Declare that several resources will be used.
+    ///
+    /// C++ equivalent: `void useResources(const Resource* const*, NS::UInteger, MTL::ResourceUsage)`
+    pub fn use_resources(&self, resources: &[&dyn Resource], usage: ResourceUsage) {
+        with_resource_ptrs(resources, |ptrs| unsafe {
+            self.use_resources_ptr(ptrs.as_ptr(), ptrs.len() as UInteger, usage)
+        });
+    }
+
     /// Declare that a heap will be used (raw pointer version).
     ///
     /// C++ equivalent: `void useHeap(const Heap*)`
@@ -110,6 +129,15 @@ impl ComputeCommandEncoder {
         unsafe { self.use_heap_ptr(heap.as_ptr()) };
     }
 
+    /// Declare that several heaps will be used.
+    ///
+    /// C++ equivalent: `void useHeaps(const Heap* const*, NS::UInteger)`
+    pub fn use_heaps(&self, heaps: &[&crate::Heap]) {
+        with_object_ptrs(heaps, |ptrs| unsafe {
+            self.use_heaps_ptr(ptrs.as_ptr(), ptrs.len() as UInteger)
+        });
+    }
+
     /// Declare that multiple heaps will be used (raw pointer version).
     ///
     /// C++ equivalent: `void useHeaps(const Heap* const*, NS::UInteger)`
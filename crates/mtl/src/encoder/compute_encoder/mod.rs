@@ -10,11 +10,16 @@ use mtl_sys::{msg_send_0, msg_send_1, sel};
 
 mod acceleration;
 mod binding;
+mod compute_pass;
 mod dispatch;
 mod indirect;
 mod memory;
 mod pipeline;
 
+pub use compute_pass::ComputePass;
+
+use crate::types::{Region, Size};
+
 /// Indirect arguments for dispatching threadgroups.
 ///
 /// C++ equivalent: `MTL::DispatchThreadgroupsIndirectArguments`
@@ -24,6 +29,16 @@ pub struct DispatchThreadgroupsIndirectArguments {
     pub threadgroups_per_grid: [u32; 3],
 }
 
+impl DispatchThreadgroupsIndirectArguments {
+    /// Create arguments dispatching `threadgroups_per_grid` threadgroups.
+    #[inline]
+    pub fn new(threadgroups_per_grid: Size) -> Self {
+        Self {
+            threadgroups_per_grid: size_to_u32(threadgroups_per_grid),
+        }
+    }
+}
+
 /// Indirect arguments for dispatching threads.
 ///
 /// C++ equivalent: `MTL::DispatchThreadsIndirectArguments`
@@ -34,6 +49,17 @@ pub struct DispatchThreadsIndirectArguments {
     pub threads_per_threadgroup: [u32; 3],
 }
 
+impl DispatchThreadsIndirectArguments {
+    /// Create arguments dispatching `threads_per_grid` threads.
+    #[inline]
+    pub fn new(threads_per_grid: Size, threads_per_threadgroup: Size) -> Self {
+        Self {
+            threads_per_grid: size_to_u32(threads_per_grid),
+            threads_per_threadgroup: size_to_u32(threads_per_threadgroup),
+        }
+    }
+}
+
 /// Indirect arguments for stage-in region.
 ///
 /// C++ equivalent: `MTL::StageInRegionIndirectArguments`
@@ -44,6 +70,33 @@ pub struct StageInRegionIndirectArguments {
     pub stage_in_size: [u32; 3],
 }
 
+impl StageInRegionIndirectArguments {
+    /// Create arguments for a stage-in region.
+    #[inline]
+    pub fn new(region: Region) -> Self {
+        Self {
+            stage_in_origin: [
+                region.origin.x as u32,
+                region.origin.y as u32,
+                region.origin.z as u32,
+            ],
+            stage_in_size: size_to_u32(region.size),
+        }
+    }
+}
+
+impl From<Region> for StageInRegionIndirectArguments {
+    #[inline]
+    fn from(region: Region) -> Self {
+        Self::new(region)
+    }
+}
+
+#[inline]
+fn size_to_u32(size: Size) -> [u32; 3] {
+    [size.width as u32, size.height as u32, size.depth as u32]
+}
+
 /// A command encoder for compute operations.
 ///
 /// C++ equivalent: `MTL::ComputeCommandEncoder`
@@ -120,9 +173,16 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void setLabel(const NS::String*)`
     pub fn set_label(&self, label: &str) {
         if let Some(ns_label) = mtl_foundation::String::from_str(label) {
-            unsafe {
-                msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setLabel:), ns_label.as_ptr());
-            }
+            self.set_label_ns(&ns_label);
+        }
+    }
+
+    /// Set the label for this encoder from an existing `NSString`.
+    ///
+    /// C++ equivalent: `void setLabel(const NS::String*)`
+    pub fn set_label_ns(&self, label: &mtl_foundation::String) {
+        unsafe {
+            msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setLabel:), label.as_ptr());
         }
     }
 
@@ -131,6 +191,8 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void endEncoding()`
     #[inline]
     pub fn end_encoding(&self) {
+        #[cfg(feature = "validation")]
+        crate::validation::end_encoding(self.as_ptr(), "ComputeCommandEncoder::end_encoding");
         unsafe {
             msg_send_0::<()>(self.as_ptr(), sel!(endEncoding));
         }
@@ -141,13 +203,20 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void insertDebugSignpost(const NS::String*)`
     pub fn insert_debug_signpost(&self, string: &str) {
         if let Some(ns_string) = mtl_foundation::String::from_str(string) {
-            unsafe {
-                msg_send_1::<(), *const c_void>(
-                    self.as_ptr(),
-                    sel!(insertDebugSignpost:),
-                    ns_string.as_ptr(),
-                );
-            }
+            self.insert_debug_signpost_ns(&ns_string);
+        }
+    }
+
+    /// Insert a debug signpost from an existing `NSString`.
+    ///
+    /// C++ equivalent: `void insertDebugSignpost(const NS::String*)`
+    pub fn insert_debug_signpost_ns(&self, string: &mtl_foundation::String) {
+        unsafe {
+            msg_send_1::<(), *const c_void>(
+                self.as_ptr(),
+                sel!(insertDebugSignpost:),
+                string.as_ptr(),
+            );
         }
     }
 
@@ -156,13 +225,16 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void pushDebugGroup(const NS::String*)`
     pub fn push_debug_group(&self, string: &str) {
         if let Some(ns_string) = mtl_foundation::String::from_str(string) {
-            unsafe {
-                msg_send_1::<(), *const c_void>(
-                    self.as_ptr(),
-                    sel!(pushDebugGroup:),
-                    ns_string.as_ptr(),
-                );
-            }
+            self.push_debug_group_ns(&ns_string);
+        }
+    }
+
+    /// Push a debug group from an existing `NSString`.
+    ///
+    /// C++ equivalent: `void pushDebugGroup(const NS::String*)`
+    pub fn push_debug_group_ns(&self, string: &mtl_foundation::String) {
+        unsafe {
+            msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(pushDebugGroup:), string.as_ptr());
         }
     }
 
@@ -260,4 +332,66 @@ mod tests {
     fn test_stage_in_region_indirect_arguments_size() {
         assert_eq!(std::mem::size_of::<StageInRegionIndirectArguments>(), 24); // 6 * 4 bytes
     }
+
+    #[test]
+    fn test_indirect_arguments_from_sizes() {
+        let region = Region::new_2d(1, 2, 30, 40);
+        let args = StageInRegionIndirectArguments::from(region);
+        assert_eq!({ args.stage_in_origin }, [1, 2, 0]);
+        assert_eq!({ args.stage_in_size }, [30, 40, 1]);
+
+        let args = DispatchThreadsIndirectArguments::new(Size::new(100, 1, 1), Size::new(32, 1, 1));
+        assert_eq!({ args.threads_per_grid }, [100, 1, 1]);
+        assert_eq!({ args.threads_per_threadgroup }, [32, 1, 1]);
+    }
+
+    #[test]
+    fn test_dispatch_threadgroups_indirect() {
+        use crate::enums::ResourceOptions;
+
+        const SOURCE: &str = r#"
+            #include <metal_stdlib>
+            using namespace metal;
+            kernel void fill(device uint* out [[buffer(0)]],
+                             threadgroup uint* scratch [[threadgroup(0)]],
+                             uint gid [[thread_position_in_grid]],
+                             uint lid [[thread_position_in_threadgroup]]) {
+                scratch[lid] = gid * 2;
+                threadgroup_barrier(mem_flags::mem_threadgroup);
+                out[gid] = scratch[lid];
+            }
+        "#;
+
+        let device = crate::device::system_default().expect("no Metal device");
+        let pipeline = device
+            .new_compute_pipeline_from_source(SOURCE, "fill", None)
+            .unwrap();
+        let queue = device.new_command_queue().unwrap();
+        let options = ResourceOptions::STORAGE_MODE_SHARED;
+        let output = device.new_buffer(64 * 4, options).unwrap();
+        let args = DispatchThreadgroupsIndirectArguments::new(Size::new(4, 1, 1));
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                (&args as *const DispatchThreadgroupsIndirectArguments).cast::<u8>(),
+                std::mem::size_of_val(&args),
+            )
+        };
+        let indirect = device.new_buffer_with_bytes(bytes, options).unwrap();
+
+        let command_buffer = queue.command_buffer().unwrap();
+        let encoder =
+            unsafe { ComputeCommandEncoder::from_raw(command_buffer.compute_command_encoder()) }
+                .unwrap();
+        encoder.set_compute_pipeline_state(&pipeline);
+        encoder.set_buffer(&output, 0, 0);
+        encoder.set_threadgroup_memory_length(16 * 4, 0);
+        encoder.dispatch_threadgroups_with_indirect_buffer(&indirect, 0, Size::new(16, 1, 1));
+        encoder.end_encoding();
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+
+        let out =
+            unsafe { std::slice::from_raw_parts(output.contents().unwrap().cast::<u32>(), 64) };
+        assert!(out.iter().enumerate().all(|(i, &v)| v == i as u32 * 2));
+    }
 }
@@ -15,6 +15,11 @@ impl ComputeCommandEncoder {
     /// C++ equivalent: `void setComputePipelineState(const ComputePipelineState*)`
     #[inline]
     pub fn set_compute_pipeline_state(&self, state: &crate::ComputePipelineState) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "ComputeCommandEncoder::set_compute_pipeline_state",
+        );
         unsafe {
             msg_send_1::<(), *const c_void>(
                 self.as_ptr(),
@@ -0,0 +1,604 @@
+//! CPU-side encode timing per pass and per frame.
+//!
+//! GPU timestamps show how long a pass runs on the GPU, but not how long the
+//! CPU spent encoding it. [`EncodeTimings`] measures the wall time from
+//! encoder creation to `end_encoding` for each named pass, groups the passes
+//! by frame and accumulates per-pass statistics, so CPU-bound encoding
+//! hotspots can be told apart from GPU cost.
+//!
+//! Timing is opt-in: nothing is measured unless the encoder is created
+//! through an [`EncodeTimings`] (or a span is opened with
+//! [`EncodeTimings::begin`]), and a disabled recorder skips the clock
+//! entirely. Every finished pass is also passed to the observer set with
+//! [`EncodeTimings::set_observer`], which can forward it to a tracing or
+//! profiling backend.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let timings = EncodeTimings::new();
+//!
+//! // Each frame:
+//! let shadows = timings.render_encoder(&command_buffer, &shadow_pass, "shadows").unwrap();
+//! // ... encode draws through `shadows` ...
+//! shadows.end_encoding();
+//!
+//! let blur = timings.compute_encoder(&command_buffer, "blur").unwrap();
+//! // ... encode dispatches ...
+//! blur.end_encoding();
+//!
+//! let frame = timings.end_frame();
+//! println!("frame {} encoded in {:?}", frame.index, frame.total_time());
+//!
+//! // Later:
+//! println!("{}", timings.report());
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Write as _};
+use std::ops::Deref;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use super::{
+    BlitCommandEncoder, ComputeCommandEncoder, ParallelRenderCommandEncoder, RenderCommandEncoder,
+};
+use crate::CommandBuffer;
+use crate::pass::RenderPassDescriptor;
+
+/// Number of frames kept by [`EncodeTimings::new`].
+pub const DEFAULT_ENCODE_HISTORY: usize = 120;
+
+/// The kind of encoder a pass used.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EncoderKind {
+    Render,
+    ParallelRender,
+    Compute,
+    Blit,
+    ResourceState,
+    AccelerationStructure,
+}
+
+impl EncoderKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            EncoderKind::Render => "render",
+            EncoderKind::ParallelRender => "parallel",
+            EncoderKind::Compute => "compute",
+            EncoderKind::Blit => "blit",
+            EncoderKind::ResourceState => "state",
+            EncoderKind::AccelerationStructure => "accel",
+        }
+    }
+}
+
+/// CPU time spent encoding one pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PassEncodeTime {
+    /// Pass name.
+    pub name: String,
+    /// The kind of encoder.
+    pub kind: EncoderKind,
+    /// Index of the frame the pass belongs to.
+    pub frame: u64,
+    /// Time from encoder creation to `end_encoding`.
+    pub time: Duration,
+}
+
+/// The passes encoded during one frame, in the order they finished.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameEncodeTimes {
+    /// Frame index, counting from zero.
+    pub index: u64,
+    /// Passes finished during the frame.
+    pub passes: Vec<PassEncodeTime>,
+}
+
+impl FrameEncodeTimes {
+    /// Total encode time of every pass in the frame.
+    pub fn total_time(&self) -> Duration {
+        self.passes.iter().map(|pass| pass.time).sum()
+    }
+
+    /// Get the most expensive pass of the frame.
+    pub fn slowest(&self) -> Option<&PassEncodeTime> {
+        self.passes.iter().max_by_key(|pass| pass.time)
+    }
+}
+
+/// Accumulated encode statistics for a single named pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PassEncodeRecord {
+    /// Pass name.
+    pub name: String,
+    /// The kind of encoder.
+    pub kind: EncoderKind,
+    /// Number of times the pass was encoded.
+    pub count: u64,
+    /// Total encode time across all frames.
+    pub total_time: Duration,
+    /// Slowest single encode.
+    pub max_time: Duration,
+    /// Most recent encode time.
+    pub last_time: Duration,
+}
+
+impl PassEncodeRecord {
+    /// Average encode time across all frames.
+    pub fn mean_time(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos((self.total_time.as_nanos() / self.count as u128) as u64)
+    }
+}
+
+type Observer = Box<dyn Fn(&PassEncodeTime) + Send + Sync>;
+
+#[derive(Default)]
+struct TimingState {
+    frame: u64,
+    current: Vec<PassEncodeTime>,
+    history: VecDeque<FrameEncodeTimes>,
+    records: BTreeMap<String, PassEncodeRecord>,
+}
+
+// ============================================================================
+// EncodeTimings
+// ============================================================================
+
+/// Thread-safe recorder of per-pass CPU encode times.
+pub struct EncodeTimings {
+    enabled: AtomicBool,
+    history_len: usize,
+    state: Mutex<TimingState>,
+    observer: Mutex<Option<Observer>>,
+}
+
+impl Default for EncodeTimings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EncodeTimings {
+    /// Create an enabled recorder keeping [`DEFAULT_ENCODE_HISTORY`] frames.
+    pub fn new() -> Self {
+        Self::with_history(DEFAULT_ENCODE_HISTORY)
+    }
+
+    /// Create an enabled recorder keeping the last `frames` frames.
+    pub fn with_history(frames: usize) -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            history_len: frames,
+            state: Mutex::new(TimingState::default()),
+            observer: Mutex::new(None),
+        }
+    }
+
+    /// Enable or disable timing.
+    ///
+    /// While disabled, spans and timed encoders read no clock and record
+    /// nothing.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Check if timing is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Call `observer` with every pass as it is recorded.
+    ///
+    /// The observer runs on the thread that ended the pass.
+    pub fn set_observer(&self, observer: impl Fn(&PassEncodeTime) + Send + Sync + 'static) {
+        *self.observer.lock().unwrap() = Some(Box::new(observer));
+    }
+
+    /// Remove the observer.
+    pub fn clear_observer(&self) {
+        *self.observer.lock().unwrap() = None;
+    }
+
+    /// Start timing a pass. The time is recorded when the span is finished
+    /// or dropped.
+    pub fn begin(&self, name: &str, kind: EncoderKind) -> EncodeSpan<'_> {
+        EncodeSpan {
+            timings: self,
+            name: name.to_string(),
+            kind,
+            start: self.is_enabled().then(Instant::now),
+        }
+    }
+
+    /// Record one pass of the current frame.
+    pub fn record(&self, name: &str, kind: EncoderKind, time: Duration) {
+        let pass = {
+            let mut state = self.state.lock().unwrap();
+            let record =
+                state
+                    .records
+                    .entry(name.to_string())
+                    .or_insert_with(|| PassEncodeRecord {
+                        name: name.to_string(),
+                        kind,
+                        count: 0,
+                        total_time: Duration::ZERO,
+                        max_time: Duration::ZERO,
+                        last_time: Duration::ZERO,
+                    });
+            record.kind = kind;
+            record.count += 1;
+            record.total_time += time;
+            record.max_time = record.max_time.max(time);
+            record.last_time = time;
+
+            let pass = PassEncodeTime {
+                name: name.to_string(),
+                kind,
+                frame: state.frame,
+                time,
+            };
+            state.current.push(pass.clone());
+            pass
+        };
+        if let Some(observer) = self.observer.lock().unwrap().as_ref() {
+            observer(&pass);
+        }
+    }
+
+    /// Finish the current frame and return its passes.
+    pub fn end_frame(&self) -> FrameEncodeTimes {
+        let mut state = self.state.lock().unwrap();
+        let frame = FrameEncodeTimes {
+            index: state.frame,
+            passes: std::mem::take(&mut state.current),
+        };
+        state.frame += 1;
+        if self.history_len > 0 {
+            if state.history.len() == self.history_len {
+                state.history.pop_front();
+            }
+            state.history.push_back(frame.clone());
+        }
+        frame
+    }
+
+    /// Get the index of the frame currently being recorded.
+    pub fn current_frame(&self) -> u64 {
+        self.state.lock().unwrap().frame
+    }
+
+    /// Get the most recently finished frame.
+    pub fn last_frame(&self) -> Option<FrameEncodeTimes> {
+        self.state.lock().unwrap().history.back().cloned()
+    }
+
+    /// Get the retained frames, oldest first.
+    pub fn frames(&self) -> Vec<FrameEncodeTimes> {
+        self.state.lock().unwrap().history.iter().cloned().collect()
+    }
+
+    /// Get the record for a named pass.
+    pub fn get(&self, name: &str) -> Option<PassEncodeRecord> {
+        self.state.lock().unwrap().records.get(name).cloned()
+    }
+
+    /// Get all records, most expensive (by total encode time) first.
+    pub fn records(&self) -> Vec<PassEncodeRecord> {
+        let mut records: Vec<_> = self
+            .state
+            .lock()
+            .unwrap()
+            .records
+            .values()
+            .cloned()
+            .collect();
+        records.sort_by_key(|r| std::cmp::Reverse(r.total_time));
+        records
+    }
+
+    /// Remove all records and frames and restart at frame zero.
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = TimingState::default();
+    }
+
+    /// Render a human-readable report, most expensive passes first,
+    /// followed by the breakdown of the last finished frame.
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:<32} {:>8} {:>8} {:>12} {:>12} {:>12}",
+            "pass", "kind", "count", "mean (ms)", "max (ms)", "last (ms)"
+        );
+        for record in self.records() {
+            let _ = writeln!(
+                out,
+                "{:<32} {:>8} {:>8} {:>12.3} {:>12.3} {:>12.3}",
+                record.name,
+                record.kind.as_str(),
+                record.count,
+                record.mean_time().as_secs_f64() * 1000.0,
+                record.max_time.as_secs_f64() * 1000.0,
+                record.last_time.as_secs_f64() * 1000.0
+            );
+        }
+        if let Some(frame) = self.last_frame() {
+            let _ = writeln!(
+                out,
+                "\nframe {}: {:.3} ms",
+                frame.index,
+                frame.total_time().as_secs_f64() * 1000.0
+            );
+            for pass in &frame.passes {
+                let _ = writeln!(
+                    out,
+                    "  {:<30} {:>8} {:>12.3}",
+                    pass.name,
+                    pass.kind.as_str(),
+                    pass.time.as_secs_f64() * 1000.0
+                );
+            }
+        }
+        out
+    }
+
+    // =========================================================================
+    // Timed Encoder Creation
+    // =========================================================================
+
+    /// Create a render command encoder timed until `end_encoding`.
+    pub fn render_encoder(
+        &self,
+        command_buffer: &CommandBuffer,
+        descriptor: &RenderPassDescriptor,
+        name: &str,
+    ) -> Option<TimedEncoder<'_, RenderCommandEncoder>> {
+        let span = self.begin(name, EncoderKind::Render);
+        let encoder = unsafe {
+            RenderCommandEncoder::from_raw(command_buffer.render_command_encoder(descriptor))
+        }?;
+        Some(TimedEncoder { encoder, span })
+    }
+
+    /// Create a parallel render command encoder timed until `end_encoding`.
+    pub fn parallel_render_encoder(
+        &self,
+        command_buffer: &CommandBuffer,
+        descriptor: &RenderPassDescriptor,
+        name: &str,
+    ) -> Option<TimedEncoder<'_, ParallelRenderCommandEncoder>> {
+        let span = self.begin(name, EncoderKind::ParallelRender);
+        let encoder = unsafe {
+            ParallelRenderCommandEncoder::from_raw(
+                command_buffer.parallel_render_command_encoder(descriptor),
+            )
+        }?;
+        Some(TimedEncoder { encoder, span })
+    }
+
+    /// Create a compute command encoder timed until `end_encoding`.
+    pub fn compute_encoder(
+        &self,
+        command_buffer: &CommandBuffer,
+        name: &str,
+    ) -> Option<TimedEncoder<'_, ComputeCommandEncoder>> {
+        let span = self.begin(name, EncoderKind::Compute);
+        let encoder =
+            unsafe { ComputeCommandEncoder::from_raw(command_buffer.compute_command_encoder()) }?;
+        Some(TimedEncoder { encoder, span })
+    }
+
+    /// Create a blit command encoder timed until `end_encoding`.
+    pub fn blit_encoder(
+        &self,
+        command_buffer: &CommandBuffer,
+        name: &str,
+    ) -> Option<TimedEncoder<'_, BlitCommandEncoder>> {
+        let span = self.begin(name, EncoderKind::Blit);
+        let encoder =
+            unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }?;
+        Some(TimedEncoder { encoder, span })
+    }
+}
+
+impl fmt::Debug for EncodeTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("EncodeTimings")
+            .field("enabled", &self.is_enabled())
+            .field("frame", &state.frame)
+            .field("passes", &state.records.len())
+            .finish()
+    }
+}
+
+// ============================================================================
+// EncodeSpan
+// ============================================================================
+
+/// An open pass timing, returned by [`EncodeTimings::begin`].
+///
+/// The elapsed time is recorded by [`finish`](Self::finish) or on drop.
+pub struct EncodeSpan<'a> {
+    timings: &'a EncodeTimings,
+    name: String,
+    kind: EncoderKind,
+    start: Option<Instant>,
+}
+
+impl EncodeSpan<'_> {
+    /// Record the pass and return its encode time.
+    ///
+    /// Returns zero if timing was disabled when the span began.
+    pub fn finish(mut self) -> Duration {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Duration {
+        match self.start.take() {
+            Some(start) => {
+                let time = start.elapsed();
+                self.timings.record(&self.name, self.kind, time);
+                time
+            }
+            None => Duration::ZERO,
+        }
+    }
+}
+
+impl Drop for EncodeSpan<'_> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl fmt::Debug for EncodeSpan<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncodeSpan")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
+
+// ============================================================================
+// TimedEncoder
+// ============================================================================
+
+/// An encoder whose CPU encode time is recorded when encoding ends.
+///
+/// Dereferences to the wrapped encoder. Call
+/// [`end_encoding`](Self::end_encoding) on the wrapper, not the encoder, so
+/// the pass is recorded at the right moment.
+pub struct TimedEncoder<'a, E> {
+    encoder: E,
+    span: EncodeSpan<'a>,
+}
+
+impl<E> TimedEncoder<'_, E> {
+    /// Get the wrapped encoder.
+    pub fn encoder(&self) -> &E {
+        &self.encoder
+    }
+}
+
+impl<E> Deref for TimedEncoder<'_, E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        &self.encoder
+    }
+}
+
+macro_rules! timed_end_encoding {
+    ($($encoder:ty),* $(,)?) => {
+        $(
+            impl TimedEncoder<'_, $encoder> {
+                /// End encoding and record the pass.
+                ///
+                /// Returns the time since the encoder was created.
+                pub fn end_encoding(self) -> Duration {
+                    self.encoder.end_encoding();
+                    self.span.finish()
+                }
+            }
+        )*
+    };
+}
+
+timed_end_encoding!(
+    RenderCommandEncoder,
+    ParallelRenderCommandEncoder,
+    ComputeCommandEncoder,
+    BlitCommandEncoder,
+);
+
+impl<E: fmt::Debug> fmt::Debug for TimedEncoder<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimedEncoder")
+            .field("encoder", &self.encoder)
+            .field("span", &self.span)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_frames_and_records() {
+        let timings = EncodeTimings::with_history(2);
+        for _ in 0..3 {
+            timings.record("shadows", EncoderKind::Render, Duration::from_millis(2));
+            timings.record("blur", EncoderKind::Compute, Duration::from_millis(1));
+            timings.end_frame();
+        }
+        timings.record("shadows", EncoderKind::Render, Duration::from_millis(5));
+        let frame = timings.end_frame();
+        assert_eq!(frame.index, 3);
+        assert_eq!(frame.total_time(), Duration::from_millis(5));
+        assert_eq!(frame.slowest().unwrap().name, "shadows");
+
+        let frames = timings.frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].index, 2);
+
+        let shadows = timings.get("shadows").unwrap();
+        assert_eq!(shadows.count, 4);
+        assert_eq!(shadows.max_time, Duration::from_millis(5));
+        assert_eq!(shadows.mean_time(), Duration::from_micros(2750));
+        assert_eq!(timings.records()[0].name, "shadows");
+        assert!(timings.report().contains("blur"));
+    }
+
+    #[test]
+    fn test_span_and_observer() {
+        let timings = EncodeTimings::new();
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&seen);
+        timings.set_observer(move |pass| {
+            assert_eq!(pass.name, "upload");
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        timings.begin("upload", EncoderKind::Blit).finish();
+        drop(timings.begin("upload", EncoderKind::Blit));
+        timings.set_enabled(false);
+        assert_eq!(
+            timings.begin("upload", EncoderKind::Blit).finish(),
+            Duration::ZERO
+        );
+
+        assert_eq!(seen.load(Ordering::Relaxed), 2);
+        assert_eq!(timings.end_frame().passes.len(), 2);
+    }
+
+    #[test]
+    fn test_timed_encoder() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().unwrap();
+        let command_buffer = queue.command_buffer().unwrap();
+
+        let timings = EncodeTimings::new();
+        let encoder = timings.compute_encoder(&command_buffer, "empty").unwrap();
+        encoder.set_label("empty");
+        encoder.end_encoding();
+        let encoder = timings.blit_encoder(&command_buffer, "copy").unwrap();
+        encoder.end_encoding();
+        command_buffer.commit();
+
+        let frame = timings.end_frame();
+        assert_eq!(frame.passes.len(), 2);
+        assert_eq!(frame.passes[0].kind, EncoderKind::Compute);
+        assert_eq!(frame.passes[1].name, "copy");
+    }
+}
@@ -13,19 +13,48 @@
 //! - [`ResourceStateCommandEncoder`] - Sparse texture mapping operations
 
 mod blit_encoder;
+mod blit_volume;
 mod compute_encoder;
+mod encode_timing;
 mod parallel_render_encoder;
 mod render_encoder;
 mod resource_state_encoder;
 
 pub use blit_encoder::BlitCommandEncoder;
-pub use compute_encoder::ComputeCommandEncoder;
+pub use blit_volume::{BlitError, CUBE_FACE_COUNT, cube_array_slice};
+pub use compute_encoder::{ComputeCommandEncoder, ComputePass};
+pub use encode_timing::{
+    DEFAULT_ENCODE_HISTORY, EncodeSpan, EncodeTimings, EncoderKind, FrameEncodeTimes,
+    PassEncodeRecord, PassEncodeTime, TimedEncoder,
+};
 pub use parallel_render_encoder::ParallelRenderCommandEncoder;
 pub use render_encoder::RenderCommandEncoder;
-pub use resource_state_encoder::{MapIndirectArguments, ResourceStateCommandEncoder};
+pub use resource_state_encoder::{
+    MapIndirectArguments, ResourceStateCommandEncoder, SparseTextureMappingBatch,
+};
 
 // Re-export compute types
 pub use compute_encoder::{
     DispatchThreadgroupsIndirectArguments, DispatchThreadsIndirectArguments,
     StageInRegionIndirectArguments,
 };
+
+/// Largest value the `set_*_value` methods accept, in bytes.
+///
+/// Metal limits `setBytes` and its per-stage variants to 4 KB; larger data
+/// belongs in a buffer.
+pub const MAX_INLINE_BYTES: usize = 4096;
+
+/// Compile-time size check for values passed inline to an encoder.
+pub(crate) struct InlineValue<T>(std::marker::PhantomData<T>);
+
+impl<T> InlineValue<T> {
+    /// `size_of::<T>()`, failing to compile if it exceeds [`MAX_INLINE_BYTES`].
+    pub(crate) const SIZE: usize = {
+        assert!(
+            size_of::<T>() <= MAX_INLINE_BYTES,
+            "value is too large for setBytes; use a buffer"
+        );
+        size_of::<T>()
+    };
+}
@@ -97,6 +97,11 @@ impl ParallelRenderCommandEncoder {
     /// C++ equivalent: `void endEncoding()`
     #[inline]
     pub fn end_encoding(&self) {
+        #[cfg(feature = "validation")]
+        crate::validation::end_encoding(
+            self.as_ptr(),
+            "ParallelRenderCommandEncoder::end_encoding",
+        );
         unsafe {
             msg_send_0::<()>(self.as_ptr(), sel!(endEncoding));
         }
@@ -185,6 +190,80 @@ impl ParallelRenderCommandEncoder {
         }
     }
 
+    /// Create `count` render command encoders and encode into them on
+    /// scoped threads.
+    ///
+    /// `encode` is called once per encoder with its index. The GPU executes
+    /// the encoders in index order, regardless of which thread finishes
+    /// first. Every sub-encoder has ended encoding when this returns; end
+    /// this encoder afterwards with [`end_encoding`](Self::end_encoding).
+    ///
+    /// Returns the closure results in index order, or `None` if an encoder
+    /// could not be created.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let parallel = command_buffer.parallel_render_command_encoder(&pass);
+    /// let parallel = unsafe { ParallelRenderCommandEncoder::from_raw(parallel) }.unwrap();
+    /// let chunks: Vec<_> = draws.chunks(draws.len().div_ceil(4)).collect();
+    /// parallel.with_sub_encoders(chunks.len(), |i, encoder| {
+    ///     encoder.set_render_pipeline_state(&pipeline);
+    ///     for draw in chunks[i] {
+    ///         draw.encode(encoder);
+    ///     }
+    /// });
+    /// parallel.end_encoding();
+    /// ```
+    pub fn with_sub_encoders<F, R>(&self, count: usize, encode: F) -> Option<Vec<R>>
+    where
+        F: Fn(usize, &crate::RenderCommandEncoder) -> R + Sync,
+        R: Send,
+    {
+        // Creation order determines execution order, so create every
+        // encoder on this thread before handing them out.
+        let mut encoders = Vec::with_capacity(count);
+        for _ in 0..count {
+            match self.render_command_encoder() {
+                Some(encoder) => encoders.push(encoder),
+                None => {
+                    for encoder in &encoders {
+                        encoder.end_encoding();
+                    }
+                    return None;
+                }
+            }
+        }
+
+        let encode = &encode;
+        let results = std::thread::scope(|scope| {
+            let Some((last, rest)) = encoders.split_last() else {
+                return Vec::new();
+            };
+            let handles: Vec<_> = rest
+                .iter()
+                .enumerate()
+                .map(|(i, encoder)| scope.spawn(move || encode(i, encoder)))
+                .collect();
+            let last = encode(rest.len(), last);
+            let mut results: Vec<R> = handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .collect();
+            results.push(last);
+            results
+        });
+
+        for encoder in &encoders {
+            encoder.end_encoding();
+        }
+        Some(results)
+    }
+
     /// Set the store action for a color attachment.
     ///
     /// C++ equivalent: `void setColorStoreAction(StoreAction, NS::UInteger)`
@@ -322,4 +401,35 @@ mod tests {
             std::mem::size_of::<*mut c_void>()
         );
     }
+
+    #[test]
+    fn test_with_sub_encoders() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().unwrap();
+        let command_buffer = queue.command_buffer().unwrap();
+        let pass = crate::pass::RenderPassDescriptor::new().unwrap();
+        pass.set_render_target_width(1);
+        pass.set_render_target_height(1);
+        pass.set_default_raster_sample_count(1);
+
+        let parallel = unsafe {
+            ParallelRenderCommandEncoder::from_raw(
+                command_buffer.parallel_render_command_encoder(&pass),
+            )
+        }
+        .unwrap();
+        let labels = parallel
+            .with_sub_encoders(4, |i, encoder| {
+                encoder.set_label(&format!("chunk {}", i));
+                encoder.label()
+            })
+            .unwrap();
+        parallel.end_encoding();
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+
+        let expected: Vec<_> = (0..4).map(|i| Some(format!("chunk {}", i))).collect();
+        assert_eq!(labels, expected);
+        assert!(parallel.with_sub_encoders(0, |_, _| ()).is_some());
+    }
 }
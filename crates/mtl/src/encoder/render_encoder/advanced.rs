@@ -18,6 +18,8 @@ use mtl_sys::{msg_send_0, msg_send_1, sel};
 use crate::Buffer;
 use crate::Texture;
 use crate::enums::{BarrierScope, RenderStages, ResourceUsage};
+use crate::object_ptrs::{with_object_ptrs, with_resource_ptrs};
+use crate::resource::Resource;
 use crate::types::VertexAmplificationViewMapping;
 
 use super::RenderCommandEncoder;
@@ -149,6 +151,33 @@ impl RenderCommandEncoder {
         unsafe { self.use_resource_ptr(texture.as_ptr(), usage) };
     }
 
+    /// Declare that a resource will be used by `stages`.
+    ///
+    /// C++ equivalent: `void useResource(const Resource*, MTL::ResourceUsage, MTL::RenderStages)`
+    #[inline]
+    pub fn use_resource(
+        &self,
+        resource: &dyn Resource,
+        usage: ResourceUsage,
+        stages: RenderStages,
+    ) {
+        unsafe { self.use_resource_ptr_with_stages(resource.as_resource_ptr(), usage, stages) };
+    }
+
+    /// Declare that several resources will be used by `stages`.
+    ///
+    /// C++ equivalent: `void useResources(const Resource* const*, NS::UInteger, MTL::ResourceUsage, MTL::RenderStages)`
+    pub fn use_resources(
+        &self,
+        resources: &[&dyn Resource],
+        usage: ResourceUsage,
+        stages: RenderStages,
+    ) {
+        with_resource_ptrs(resources, |ptrs| unsafe {
+            self.use_resources_with_stages_ptr(ptrs.as_ptr(), ptrs.len() as UInteger, usage, stages)
+        });
+    }
+
     /// Declare that a heap will be used (raw pointer version).
     ///
     /// C++ equivalent: `void useHeap(const Heap*)`
@@ -190,6 +219,23 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Declare that a heap will be used by `stages`.
+    ///
+    /// C++ equivalent: `void useHeap(const Heap*, MTL::RenderStages)`
+    #[inline]
+    pub fn use_heap_with_stages(&self, heap: &crate::Heap, stages: RenderStages) {
+        unsafe { self.use_heap_ptr_with_stages(heap.as_ptr(), stages) };
+    }
+
+    /// Declare that several heaps will be used by `stages`.
+    ///
+    /// C++ equivalent: `void useHeaps(const Heap* const*, NS::UInteger, MTL::RenderStages)`
+    pub fn use_heaps(&self, heaps: &[&crate::Heap], stages: RenderStages) {
+        with_object_ptrs(heaps, |ptrs| unsafe {
+            self.use_heaps_with_stages_ptr(ptrs.as_ptr(), ptrs.len() as UInteger, stages)
+        });
+    }
+
     /// Declare that multiple heaps will be used (raw pointer version).
     ///
     /// C++ equivalent: `void useHeaps(const Heap* const*, NS::UInteger)`
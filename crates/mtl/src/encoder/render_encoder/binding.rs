@@ -10,6 +10,8 @@ use mtl_sys::sel;
 
 use crate::Buffer;
 use crate::Texture;
+use crate::encoder::InlineValue;
+use crate::object_ptrs::with_object_ptrs;
 
 use super::RenderCommandEncoder;
 
@@ -22,6 +24,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setVertexBuffer(const Buffer*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_vertex_buffer(&self, buffer: &Buffer, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_buffer",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -44,6 +53,13 @@ impl RenderCommandEncoder {
         stride: UInteger,
         index: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_buffer_with_stride",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, UInteger, UInteger, UInteger>(
                 self.as_ptr(),
@@ -61,6 +77,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setVertexBufferOffset(NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_vertex_buffer_offset(&self, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_buffer_offset",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), UInteger, UInteger>(
                 self.as_ptr(),
@@ -76,6 +99,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setVertexBytes(const void*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_vertex_bytes(&self, bytes: &[u8], index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_bytes",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -87,11 +117,44 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set inline vertex bytes from a single value.
+    ///
+    /// Copies `size_of::<T>()` bytes of `value`. Types larger than
+    /// [`MAX_INLINE_BYTES`](crate::encoder::MAX_INLINE_BYTES) fail to compile.
+    ///
+    /// C++ equivalent: `void setVertexBytes(const void*, NS::UInteger, NS::UInteger)`
+    #[inline]
+    pub fn set_vertex_value<T: Copy>(&self, value: &T, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_value",
+            crate::validation::Binding::Buffer,
+            index,
+        );
+        unsafe {
+            mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
+                self.as_ptr(),
+                sel!(setVertexBytes: length: atIndex:),
+                value as *const T as *const c_void,
+                InlineValue::<T>::SIZE as UInteger,
+                index,
+            );
+        }
+    }
+
     /// Set inline vertex bytes with attribute stride.
     ///
     /// C++ equivalent: `void setVertexBytes(const void*, NS::UInteger, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_vertex_bytes_with_stride(&self, bytes: &[u8], stride: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_bytes_with_stride",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, UInteger, UInteger, UInteger>(
                 self.as_ptr(),
@@ -114,6 +177,13 @@ impl RenderCommandEncoder {
         stride: UInteger,
         index: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_buffer_offset_with_stride",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), UInteger, UInteger, UInteger>(
                 self.as_ptr(),
@@ -142,12 +212,7 @@ impl RenderCommandEncoder {
     ) {
         let range = mtl_foundation::Range::new(range_location, range_length);
         unsafe {
-            mtl_sys::msg_send_3::<
-                (),
-                *const *const c_void,
-                *const UInteger,
-                mtl_foundation::Range,
-            >(
+            mtl_sys::msg_send_3::<(), *const *const c_void, *const UInteger, mtl_foundation::Range>(
                 self.as_ptr(),
                 sel!(setVertexBuffers: offsets: withRange:),
                 buffers,
@@ -157,6 +222,41 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set vertex buffers at consecutive indices starting at `start_index`.
+    ///
+    /// `offsets` holds one offset per buffer.
+    ///
+    /// C++ equivalent: `void setVertexBuffers(const Buffer* const*, const NS::UInteger*, NS::Range)`
+    pub fn set_vertex_buffers(
+        &self,
+        buffers: &[&Buffer],
+        offsets: &[UInteger],
+        start_index: UInteger,
+    ) {
+        assert_eq!(
+            buffers.len(),
+            offsets.len(),
+            "RenderCommandEncoder::set_vertex_buffers needs one offset per buffer"
+        );
+        #[cfg(feature = "validation")]
+        if let Some(last) = buffers.len().checked_sub(1) {
+            crate::validation::check_binding(
+                self.as_ptr(),
+                "RenderCommandEncoder::set_vertex_buffers",
+                crate::validation::Binding::Buffer,
+                start_index + last,
+            );
+        }
+        with_object_ptrs(buffers, |ptrs| unsafe {
+            self.set_vertex_buffers_ptr(
+                ptrs.as_ptr(),
+                offsets.as_ptr(),
+                start_index,
+                ptrs.len() as UInteger,
+            )
+        })
+    }
+
     /// Set multiple vertex buffers with strides at a range of indices (raw pointer version).
     ///
     /// C++ equivalent: `void setVertexBuffers(const Buffer* const*, const NS::UInteger*, const NS::UInteger*, NS::Range)`
@@ -201,6 +301,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setVertexTexture(const Texture*, NS::UInteger)`
     #[inline]
     pub fn set_vertex_texture(&self, texture: &Texture, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_texture",
+            crate::validation::Binding::Texture,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -236,6 +343,24 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set vertex textures at consecutive indices starting at `start_index`.
+    ///
+    /// C++ equivalent: `void setVertexTextures(const Texture* const*, NS::Range)`
+    pub fn set_vertex_textures(&self, textures: &[&Texture], start_index: UInteger) {
+        #[cfg(feature = "validation")]
+        if let Some(last) = textures.len().checked_sub(1) {
+            crate::validation::check_binding(
+                self.as_ptr(),
+                "RenderCommandEncoder::set_vertex_textures",
+                crate::validation::Binding::Texture,
+                start_index + last,
+            );
+        }
+        with_object_ptrs(textures, |ptrs| unsafe {
+            self.set_vertex_textures_ptr(ptrs.as_ptr(), start_index, ptrs.len() as UInteger)
+        })
+    }
+
     // =========================================================================
     // Vertex Samplers
     // =========================================================================
@@ -245,6 +370,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setVertexSamplerState(const SamplerState*, NS::UInteger)`
     #[inline]
     pub fn set_vertex_sampler_state(&self, sampler: &crate::SamplerState, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_sampler_state",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -266,6 +398,13 @@ impl RenderCommandEncoder {
         lod_max_clamp: f32,
         index: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_vertex_sampler_state_with_lod_clamps",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, f32, f32, UInteger>(
                 self.as_ptr(),
@@ -303,6 +442,28 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set vertex sampler states at consecutive indices starting at `start_index`.
+    ///
+    /// C++ equivalent: `void setVertexSamplerStates(const SamplerState* const*, NS::Range)`
+    pub fn set_vertex_sampler_states(
+        &self,
+        samplers: &[&crate::SamplerState],
+        start_index: UInteger,
+    ) {
+        #[cfg(feature = "validation")]
+        if let Some(last) = samplers.len().checked_sub(1) {
+            crate::validation::check_binding(
+                self.as_ptr(),
+                "RenderCommandEncoder::set_vertex_sampler_states",
+                crate::validation::Binding::Sampler,
+                start_index + last,
+            );
+        }
+        with_object_ptrs(samplers, |ptrs| unsafe {
+            self.set_vertex_sampler_states_ptr(ptrs.as_ptr(), start_index, ptrs.len() as UInteger)
+        })
+    }
+
     /// Set multiple vertex sampler states with LOD clamps at a range of indices (raw pointer version).
     ///
     /// C++ equivalent: `void setVertexSamplerStates(const SamplerState* const*, const float*, const float*, NS::Range)`
@@ -347,6 +508,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setFragmentBuffer(const Buffer*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_fragment_buffer(&self, buffer: &Buffer, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_fragment_buffer",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -363,6 +531,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setFragmentBufferOffset(NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_fragment_buffer_offset(&self, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_fragment_buffer_offset",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), UInteger, UInteger>(
                 self.as_ptr(),
@@ -378,6 +553,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setFragmentBytes(const void*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_fragment_bytes(&self, bytes: &[u8], index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_fragment_bytes",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -389,6 +571,32 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set inline fragment bytes from a single value.
+    ///
+    /// Copies `size_of::<T>()` bytes of `value`. Types larger than
+    /// [`MAX_INLINE_BYTES`](crate::encoder::MAX_INLINE_BYTES) fail to compile.
+    ///
+    /// C++ equivalent: `void setFragmentBytes(const void*, NS::UInteger, NS::UInteger)`
+    #[inline]
+    pub fn set_fragment_value<T: Copy>(&self, value: &T, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_fragment_value",
+            crate::validation::Binding::Buffer,
+            index,
+        );
+        unsafe {
+            mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
+                self.as_ptr(),
+                sel!(setFragmentBytes: length: atIndex:),
+                value as *const T as *const c_void,
+                InlineValue::<T>::SIZE as UInteger,
+                index,
+            );
+        }
+    }
+
     /// Set multiple fragment buffers at a range of indices (raw pointer version).
     ///
     /// C++ equivalent: `void setFragmentBuffers(const Buffer* const*, const NS::UInteger*, NS::Range)`
@@ -406,12 +614,7 @@ impl RenderCommandEncoder {
     ) {
         let range = mtl_foundation::Range::new(range_location, range_length);
         unsafe {
-            mtl_sys::msg_send_3::<
-                (),
-                *const *const c_void,
-                *const UInteger,
-                mtl_foundation::Range,
-            >(
+            mtl_sys::msg_send_3::<(), *const *const c_void, *const UInteger, mtl_foundation::Range>(
                 self.as_ptr(),
                 sel!(setFragmentBuffers: offsets: withRange:),
                 buffers,
@@ -421,6 +624,41 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set fragment buffers at consecutive indices starting at `start_index`.
+    ///
+    /// `offsets` holds one offset per buffer.
+    ///
+    /// C++ equivalent: `void setFragmentBuffers(const Buffer* const*, const NS::UInteger*, NS::Range)`
+    pub fn set_fragment_buffers(
+        &self,
+        buffers: &[&Buffer],
+        offsets: &[UInteger],
+        start_index: UInteger,
+    ) {
+        assert_eq!(
+            buffers.len(),
+            offsets.len(),
+            "RenderCommandEncoder::set_fragment_buffers needs one offset per buffer"
+        );
+        #[cfg(feature = "validation")]
+        if let Some(last) = buffers.len().checked_sub(1) {
+            crate::validation::check_binding(
+                self.as_ptr(),
+                "RenderCommandEncoder::set_fragment_buffers",
+                crate::validation::Binding::Buffer,
+                start_index + last,
+            );
+        }
+        with_object_ptrs(buffers, |ptrs| unsafe {
+            self.set_fragment_buffers_ptr(
+                ptrs.as_ptr(),
+                offsets.as_ptr(),
+                start_index,
+                ptrs.len() as UInteger,
+            )
+        })
+    }
+
     // =========================================================================
     // Fragment Textures
     // =========================================================================
@@ -430,6 +668,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setFragmentTexture(const Texture*, NS::UInteger)`
     #[inline]
     pub fn set_fragment_texture(&self, texture: &Texture, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_fragment_texture",
+            crate::validation::Binding::Texture,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -465,6 +710,24 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set fragment textures at consecutive indices starting at `start_index`.
+    ///
+    /// C++ equivalent: `void setFragmentTextures(const Texture* const*, NS::Range)`
+    pub fn set_fragment_textures(&self, textures: &[&Texture], start_index: UInteger) {
+        #[cfg(feature = "validation")]
+        if let Some(last) = textures.len().checked_sub(1) {
+            crate::validation::check_binding(
+                self.as_ptr(),
+                "RenderCommandEncoder::set_fragment_textures",
+                crate::validation::Binding::Texture,
+                start_index + last,
+            );
+        }
+        with_object_ptrs(textures, |ptrs| unsafe {
+            self.set_fragment_textures_ptr(ptrs.as_ptr(), start_index, ptrs.len() as UInteger)
+        })
+    }
+
     // =========================================================================
     // Fragment Samplers
     // =========================================================================
@@ -474,6 +737,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setFragmentSamplerState(const SamplerState*, NS::UInteger)`
     #[inline]
     pub fn set_fragment_sampler_state(&self, sampler: &crate::SamplerState, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_fragment_sampler_state",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -495,6 +765,13 @@ impl RenderCommandEncoder {
         lod_max_clamp: f32,
         index: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_fragment_sampler_state_with_lod_clamps",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, f32, f32, UInteger>(
                 self.as_ptr(),
@@ -532,6 +809,28 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set fragment sampler states at consecutive indices starting at `start_index`.
+    ///
+    /// C++ equivalent: `void setFragmentSamplerStates(const SamplerState* const*, NS::Range)`
+    pub fn set_fragment_sampler_states(
+        &self,
+        samplers: &[&crate::SamplerState],
+        start_index: UInteger,
+    ) {
+        #[cfg(feature = "validation")]
+        if let Some(last) = samplers.len().checked_sub(1) {
+            crate::validation::check_binding(
+                self.as_ptr(),
+                "RenderCommandEncoder::set_fragment_sampler_states",
+                crate::validation::Binding::Sampler,
+                start_index + last,
+            );
+        }
+        with_object_ptrs(samplers, |ptrs| unsafe {
+            self.set_fragment_sampler_states_ptr(ptrs.as_ptr(), start_index, ptrs.len() as UInteger)
+        })
+    }
+
     /// Set multiple fragment sampler states with LOD clamps at a range of indices (raw pointer version).
     ///
     /// C++ equivalent: `void setFragmentSamplerStates(const SamplerState* const*, const float*, const float*, NS::Range)`
@@ -29,6 +29,8 @@ impl RenderCommandEncoder {
         vertex_start: UInteger,
         vertex_count: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(self.as_ptr(), "RenderCommandEncoder::draw_primitives");
         unsafe {
             mtl_sys::msg_send_3::<(), PrimitiveType, UInteger, UInteger>(
                 self.as_ptr(),
@@ -51,6 +53,11 @@ impl RenderCommandEncoder {
         vertex_count: UInteger,
         instance_count: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_primitives_instanced",
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), PrimitiveType, UInteger, UInteger, UInteger>(
                 self.as_ptr(),
@@ -75,6 +82,11 @@ impl RenderCommandEncoder {
         instance_count: UInteger,
         base_instance: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_primitives_instanced_base_instance",
+        );
         unsafe {
             mtl_sys::msg_send_5::<(), PrimitiveType, UInteger, UInteger, UInteger, UInteger>(
                 self.as_ptr(),
@@ -98,6 +110,11 @@ impl RenderCommandEncoder {
         indirect_buffer: &Buffer,
         indirect_buffer_offset: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_primitives_indirect",
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), PrimitiveType, *const c_void, UInteger>(
                 self.as_ptr(),
@@ -125,6 +142,11 @@ impl RenderCommandEncoder {
         index_buffer: &Buffer,
         index_buffer_offset: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_indexed_primitives",
+        );
         unsafe {
             mtl_sys::msg_send_5::<(), PrimitiveType, UInteger, IndexType, *const c_void, UInteger>(
                 self.as_ptr(),
@@ -151,6 +173,11 @@ impl RenderCommandEncoder {
         index_buffer_offset: UInteger,
         instance_count: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_indexed_primitives_instanced",
+        );
         unsafe {
             mtl_sys::msg_send_6::<
                 (),
@@ -189,6 +216,11 @@ impl RenderCommandEncoder {
         base_vertex: Integer,
         base_instance: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_indexed_primitives_instanced_base_vertex_base_instance",
+        );
         unsafe {
             mtl_sys::msg_send_8::<
                 (),
@@ -228,6 +260,11 @@ impl RenderCommandEncoder {
         indirect_buffer: &Buffer,
         indirect_buffer_offset: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_indexed_primitives_indirect",
+        );
         unsafe {
             mtl_sys::msg_send_6::<
                 (),
@@ -299,6 +336,8 @@ impl RenderCommandEncoder {
         instance_count: UInteger,
         base_instance: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(self.as_ptr(), "RenderCommandEncoder::draw_patches");
         let patch_index_buffer_ptr = patch_index_buffer
             .map(|b| b.as_ptr())
             .unwrap_or(std::ptr::null());
@@ -342,6 +381,11 @@ impl RenderCommandEncoder {
         instance_count: UInteger,
         base_instance: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_indexed_patches",
+        );
         let patch_index_buffer_ptr = patch_index_buffer
             .map(|b| b.as_ptr())
             .unwrap_or(std::ptr::null());
@@ -387,6 +431,11 @@ impl RenderCommandEncoder {
         indirect_buffer: &Buffer,
         indirect_buffer_offset: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_indexed_patches_indirect",
+        );
         let patch_index_buffer_ptr = patch_index_buffer
             .map(|b| b.as_ptr())
             .unwrap_or(std::ptr::null());
@@ -425,6 +474,11 @@ impl RenderCommandEncoder {
         indirect_buffer: &Buffer,
         indirect_buffer_offset: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_patches_indirect",
+        );
         let patch_index_buffer_ptr = patch_index_buffer
             .map(|b| b.as_ptr())
             .unwrap_or(std::ptr::null());
@@ -455,6 +509,11 @@ impl RenderCommandEncoder {
         threads_per_object_threadgroup: Size,
         threads_per_mesh_threadgroup: Size,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_mesh_threadgroups",
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), Size, Size, Size>(
                 self.as_ptr(),
@@ -476,6 +535,8 @@ impl RenderCommandEncoder {
         threads_per_object_threadgroup: Size,
         threads_per_mesh_threadgroup: Size,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(self.as_ptr(), "RenderCommandEncoder::draw_mesh_threads");
         unsafe {
             mtl_sys::msg_send_3::<(), Size, Size, Size>(
                 self.as_ptr(),
@@ -498,6 +559,11 @@ impl RenderCommandEncoder {
         threads_per_object_threadgroup: Size,
         threads_per_mesh_threadgroup: Size,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::draw_mesh_threadgroups_indirect",
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, UInteger, Size, Size>(
                 self.as_ptr(),
@@ -519,6 +585,11 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void dispatchThreadsPerTile(MTL::Size)`
     #[inline]
     pub fn dispatch_threads_per_tile(&self, threads_per_tile: Size) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_encoding(
+            self.as_ptr(),
+            "RenderCommandEncoder::dispatch_threads_per_tile",
+        );
         unsafe {
             msg_send_1::<(), Size>(
                 self.as_ptr(),
@@ -22,6 +22,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setObjectBuffer(const Buffer*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_object_buffer(&self, buffer: &Buffer, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_object_buffer",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -38,6 +45,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setObjectTexture(const Texture*, NS::UInteger)`
     #[inline]
     pub fn set_object_texture(&self, texture: &Texture, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_object_texture",
+            crate::validation::Binding::Texture,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -53,6 +67,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setObjectSamplerState(const SamplerState*, NS::UInteger)`
     #[inline]
     pub fn set_object_sampler_state(&self, sampler: &crate::SamplerState, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_object_sampler_state",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -83,6 +104,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setObjectBufferOffset(NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_object_buffer_offset(&self, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_object_buffer_offset",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), UInteger, UInteger>(
                 self.as_ptr(),
@@ -98,6 +126,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setObjectBytes(const void*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_object_bytes(&self, bytes: &[u8], index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_object_bytes",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -126,12 +161,7 @@ impl RenderCommandEncoder {
     ) {
         let range = mtl_foundation::Range::new(range_location, range_length);
         unsafe {
-            mtl_sys::msg_send_3::<
-                (),
-                *const *const c_void,
-                *const UInteger,
-                mtl_foundation::Range,
-            >(
+            mtl_sys::msg_send_3::<(), *const *const c_void, *const UInteger, mtl_foundation::Range>(
                 self.as_ptr(),
                 sel!(setObjectBuffers: offsets: withRange:),
                 buffers,
@@ -177,6 +207,13 @@ impl RenderCommandEncoder {
         lod_max_clamp: f32,
         index: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_object_sampler_state_with_lod_clamps",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, f32, f32, UInteger>(
                 self.as_ptr(),
@@ -258,6 +295,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setMeshBuffer(const Buffer*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_mesh_buffer(&self, buffer: &Buffer, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_mesh_buffer",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -274,6 +318,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setMeshTexture(const Texture*, NS::UInteger)`
     #[inline]
     pub fn set_mesh_texture(&self, texture: &Texture, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_mesh_texture",
+            crate::validation::Binding::Texture,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -289,6 +340,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setMeshSamplerState(const SamplerState*, NS::UInteger)`
     #[inline]
     pub fn set_mesh_sampler_state(&self, sampler: &crate::SamplerState, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_mesh_sampler_state",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), *const c_void, UInteger>(
                 self.as_ptr(),
@@ -304,6 +362,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setMeshBufferOffset(NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_mesh_buffer_offset(&self, offset: UInteger, index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_mesh_buffer_offset",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_2::<(), UInteger, UInteger>(
                 self.as_ptr(),
@@ -319,6 +384,13 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setMeshBytes(const void*, NS::UInteger, NS::UInteger)`
     #[inline]
     pub fn set_mesh_bytes(&self, bytes: &[u8], index: UInteger) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_mesh_bytes",
+            crate::validation::Binding::Buffer,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_3::<(), *const c_void, UInteger, UInteger>(
                 self.as_ptr(),
@@ -347,12 +419,7 @@ impl RenderCommandEncoder {
     ) {
         let range = mtl_foundation::Range::new(range_location, range_length);
         unsafe {
-            mtl_sys::msg_send_3::<
-                (),
-                *const *const c_void,
-                *const UInteger,
-                mtl_foundation::Range,
-            >(
+            mtl_sys::msg_send_3::<(), *const *const c_void, *const UInteger, mtl_foundation::Range>(
                 self.as_ptr(),
                 sel!(setMeshBuffers: offsets: withRange:),
                 buffers,
@@ -398,6 +465,13 @@ impl RenderCommandEncoder {
         lod_max_clamp: f32,
         index: UInteger,
     ) {
+        #[cfg(feature = "validation")]
+        crate::validation::check_binding(
+            self.as_ptr(),
+            "RenderCommandEncoder::set_mesh_sampler_state_with_lod_clamps",
+            crate::validation::Binding::Sampler,
+            index,
+        );
         unsafe {
             mtl_sys::msg_send_4::<(), *const c_void, f32, f32, UInteger>(
                 self.as_ptr(),
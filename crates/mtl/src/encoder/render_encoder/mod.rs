@@ -92,9 +92,16 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setLabel(const NS::String*)`
     pub fn set_label(&self, label: &str) {
         if let Some(ns_label) = mtl_foundation::String::from_str(label) {
-            unsafe {
-                msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setLabel:), ns_label.as_ptr());
-            }
+            self.set_label_ns(&ns_label);
+        }
+    }
+
+    /// Set the label for this encoder from an existing `NSString`.
+    ///
+    /// C++ equivalent: `void setLabel(const NS::String*)`
+    pub fn set_label_ns(&self, label: &mtl_foundation::String) {
+        unsafe {
+            msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setLabel:), label.as_ptr());
         }
     }
 
@@ -103,6 +110,8 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void endEncoding()`
     #[inline]
     pub fn end_encoding(&self) {
+        #[cfg(feature = "validation")]
+        crate::validation::end_encoding(self.as_ptr(), "RenderCommandEncoder::end_encoding");
         unsafe {
             msg_send_0::<()>(self.as_ptr(), sel!(endEncoding));
         }
@@ -113,13 +122,20 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void insertDebugSignpost(const NS::String*)`
     pub fn insert_debug_signpost(&self, string: &str) {
         if let Some(ns_string) = mtl_foundation::String::from_str(string) {
-            unsafe {
-                msg_send_1::<(), *const c_void>(
-                    self.as_ptr(),
-                    sel!(insertDebugSignpost:),
-                    ns_string.as_ptr(),
-                );
-            }
+            self.insert_debug_signpost_ns(&ns_string);
+        }
+    }
+
+    /// Insert a debug signpost from an existing `NSString`.
+    ///
+    /// C++ equivalent: `void insertDebugSignpost(const NS::String*)`
+    pub fn insert_debug_signpost_ns(&self, string: &mtl_foundation::String) {
+        unsafe {
+            msg_send_1::<(), *const c_void>(
+                self.as_ptr(),
+                sel!(insertDebugSignpost:),
+                string.as_ptr(),
+            );
         }
     }
 
@@ -128,13 +144,16 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void pushDebugGroup(const NS::String*)`
     pub fn push_debug_group(&self, string: &str) {
         if let Some(ns_string) = mtl_foundation::String::from_str(string) {
-            unsafe {
-                msg_send_1::<(), *const c_void>(
-                    self.as_ptr(),
-                    sel!(pushDebugGroup:),
-                    ns_string.as_ptr(),
-                );
-            }
+            self.push_debug_group_ns(&ns_string);
+        }
+    }
+
+    /// Push a debug group from an existing `NSString`.
+    ///
+    /// C++ equivalent: `void pushDebugGroup(const NS::String*)`
+    pub fn push_debug_group_ns(&self, string: &mtl_foundation::String) {
+        unsafe {
+            msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(pushDebugGroup:), string.as_ptr());
         }
     }
 
@@ -176,6 +195,8 @@ impl RenderCommandEncoder {
     /// C++ equivalent: `void setRenderPipelineState(const RenderPipelineState*)`
     #[inline]
     pub fn set_render_pipeline_state(&self, state: &crate::RenderPipelineState) {
+        #[cfg(feature = "validation")]
+        crate::validation::set_render_pipeline_state(self.as_ptr(), state.attachment_formats());
         unsafe {
             msg_send_1::<(), *const c_void>(
                 self.as_ptr(),
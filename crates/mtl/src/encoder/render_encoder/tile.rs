@@ -10,6 +10,7 @@ use mtl_sys::{msg_send_0, sel};
 
 use crate::Buffer;
 use crate::Texture;
+use crate::object_ptrs::with_object_ptrs;
 
 use super::RenderCommandEncoder;
 
@@ -112,12 +113,7 @@ impl RenderCommandEncoder {
     ) {
         let range = mtl_foundation::Range::new(range_location, range_length);
         unsafe {
-            mtl_sys::msg_send_3::<
-                (),
-                *const *const c_void,
-                *const UInteger,
-                mtl_foundation::Range,
-            >(
+            mtl_sys::msg_send_3::<(), *const *const c_void, *const UInteger, mtl_foundation::Range>(
                 self.as_ptr(),
                 sel!(setTileBuffers: offsets: withRange:),
                 buffers,
@@ -127,6 +123,32 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set tile buffers at consecutive indices starting at `start_index`.
+    ///
+    /// `offsets` holds one offset per buffer.
+    ///
+    /// C++ equivalent: `void setTileBuffers(const Buffer* const*, const NS::UInteger*, NS::Range)`
+    pub fn set_tile_buffers(
+        &self,
+        buffers: &[&Buffer],
+        offsets: &[UInteger],
+        start_index: UInteger,
+    ) {
+        assert_eq!(
+            buffers.len(),
+            offsets.len(),
+            "RenderCommandEncoder::set_tile_buffers needs one offset per buffer"
+        );
+        with_object_ptrs(buffers, |ptrs| unsafe {
+            self.set_tile_buffers_ptr(
+                ptrs.as_ptr(),
+                offsets.as_ptr(),
+                start_index,
+                ptrs.len() as UInteger,
+            )
+        })
+    }
+
     /// Set multiple tile textures at a range of indices (raw pointer version).
     ///
     /// C++ equivalent: `void setTileTextures(const Texture* const*, NS::Range)`
@@ -152,6 +174,15 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set tile textures at consecutive indices starting at `start_index`.
+    ///
+    /// C++ equivalent: `void setTileTextures(const Texture* const*, NS::Range)`
+    pub fn set_tile_textures(&self, textures: &[&Texture], start_index: UInteger) {
+        with_object_ptrs(textures, |ptrs| unsafe {
+            self.set_tile_textures_ptr(ptrs.as_ptr(), start_index, ptrs.len() as UInteger)
+        })
+    }
+
     /// Set a tile sampler state with LOD clamps.
     ///
     /// C++ equivalent: `void setTileSamplerState(const SamplerState*, float, float, NS::UInteger)`
@@ -200,6 +231,19 @@ impl RenderCommandEncoder {
         }
     }
 
+    /// Set tile sampler states at consecutive indices starting at `start_index`.
+    ///
+    /// C++ equivalent: `void setTileSamplerStates(const SamplerState* const*, NS::Range)`
+    pub fn set_tile_sampler_states(
+        &self,
+        samplers: &[&crate::SamplerState],
+        start_index: UInteger,
+    ) {
+        with_object_ptrs(samplers, |ptrs| unsafe {
+            self.set_tile_sampler_states_ptr(ptrs.as_ptr(), start_index, ptrs.len() as UInteger)
+        })
+    }
+
     /// Set multiple tile sampler states with LOD clamps at a range of indices (raw pointer version).
     ///
     /// C++ equivalent: `void setTileSamplerStates(const SamplerState* const*, const float*, const float*, NS::Range)`
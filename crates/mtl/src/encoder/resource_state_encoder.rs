@@ -10,7 +10,7 @@ use mtl_sys::{msg_send_0, msg_send_1, sel};
 
 use crate::Buffer;
 use crate::Texture;
-use crate::enums::SparseTextureMappingMode;
+use crate::enums::{SparseTextureMappingMode, SparseTextureRegionAlignmentMode};
 use crate::sync::Fence;
 use crate::types::{Origin, Region, Size};
 
@@ -38,6 +38,119 @@ pub struct MapIndirectArguments {
     pub slice_id: u32,
 }
 
+/// A list of sparse texture regions to map or unmap in one call.
+///
+/// Regions are in tiles, as expected by
+/// [`ResourceStateCommandEncoder::update_texture_mapping_batch`]; use
+/// [`push_pixels`](Self::push_pixels) to add a region given in pixels.
+///
+/// # Example
+///
+/// ```ignore
+/// let tile_size = device.sparse_tile_size_for_texture(&texture);
+/// let mut batch = SparseTextureMappingBatch::new();
+/// for &(region, mip) in &newly_visible {
+///     batch.push_pixels(region, tile_size, mip, 0, SparseTextureRegionAlignmentMode::OUTWARD);
+/// }
+/// encoder.update_texture_mapping_batch(&texture, SparseTextureMappingMode::MAP, &batch);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SparseTextureMappingBatch {
+    regions: Vec<Region>,
+    mip_levels: Vec<UInteger>,
+    slices: Vec<UInteger>,
+}
+
+impl SparseTextureMappingBatch {
+    /// Create an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a region of tiles in `mip_level` of `slice`.
+    pub fn push(&mut self, tile_region: Region, mip_level: UInteger, slice: UInteger) {
+        self.regions.push(tile_region);
+        self.mip_levels.push(mip_level);
+        self.slices.push(slice);
+    }
+
+    /// Add a region given in pixels, converted to tiles of `tile_size`.
+    ///
+    /// With [`OUTWARD`](SparseTextureRegionAlignmentMode::OUTWARD) alignment
+    /// every tile touching the region is included; with
+    /// [`INWARD`](SparseTextureRegionAlignmentMode::INWARD) only tiles
+    /// entirely inside it are, and nothing is added if there are none.
+    pub fn push_pixels(
+        &mut self,
+        pixel_region: Region,
+        tile_size: Size,
+        mip_level: UInteger,
+        slice: UInteger,
+        mode: SparseTextureRegionAlignmentMode,
+    ) {
+        let tile_region = pixel_region_to_tiles(pixel_region, tile_size, mode);
+        if tile_region.size.width > 0 && tile_region.size.height > 0 && tile_region.size.depth > 0 {
+            self.push(tile_region, mip_level, slice);
+        }
+    }
+
+    /// Get the number of regions in the batch.
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Check if the batch has no regions.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Remove all regions, keeping the allocations.
+    pub fn clear(&mut self) {
+        self.regions.clear();
+        self.mip_levels.clear();
+        self.slices.clear();
+    }
+
+    /// Get the tile regions.
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Get the mip level of each region.
+    pub fn mip_levels(&self) -> &[UInteger] {
+        &self.mip_levels
+    }
+
+    /// Get the slice of each region.
+    pub fn slices(&self) -> &[UInteger] {
+        &self.slices
+    }
+}
+
+/// Convert a pixel region to the tile region covering it, matching
+/// `Device::convert_sparse_pixel_regions`.
+fn pixel_region_to_tiles(
+    region: Region,
+    tile_size: Size,
+    mode: SparseTextureRegionAlignmentMode,
+) -> Region {
+    let inward = mode == SparseTextureRegionAlignmentMode::INWARD;
+    let axis = |origin: UInteger, size: UInteger, tile: UInteger| {
+        let tile = tile.max(1);
+        let end = origin + size;
+        let (first, last) = if inward {
+            (origin.div_ceil(tile), end / tile)
+        } else {
+            (origin / tile, end.div_ceil(tile))
+        };
+        (first, last.saturating_sub(first))
+    };
+    let (x, width) = axis(region.origin.x, region.size.width, tile_size.width);
+    let (y, height) = axis(region.origin.y, region.size.height, tile_size.height);
+    let (z, depth) = axis(region.origin.z, region.size.depth, tile_size.depth);
+    Region::new(Origin::new(x, y, z), Size::new(width, height, depth))
+}
+
 /// A command encoder for resource state operations.
 ///
 /// C++ equivalent: `MTL::ResourceStateCommandEncoder`
@@ -125,6 +238,8 @@ impl ResourceStateCommandEncoder {
     /// C++ equivalent: `void endEncoding()`
     #[inline]
     pub fn end_encoding(&self) {
+        #[cfg(feature = "validation")]
+        crate::validation::end_encoding(self.as_ptr(), "ResourceStateCommandEncoder::end_encoding");
         unsafe {
             msg_send_0::<()>(self.as_ptr(), sel!(endEncoding));
         }
@@ -290,6 +405,30 @@ impl ResourceStateCommandEncoder {
         }
     }
 
+    /// Update sparse texture mappings for every region in `batch`.
+    ///
+    /// Does nothing if the batch is empty.
+    pub fn update_texture_mapping_batch(
+        &self,
+        texture: &Texture,
+        mode: SparseTextureMappingMode,
+        batch: &SparseTextureMappingBatch,
+    ) {
+        if batch.is_empty() {
+            return;
+        }
+        unsafe {
+            self.update_texture_mappings(
+                texture,
+                mode,
+                batch.regions.as_ptr(),
+                batch.mip_levels.as_ptr(),
+                batch.slices.as_ptr(),
+                batch.len(),
+            );
+        }
+    }
+
     /// Move texture mappings from one texture to another.
     ///
     /// C++ equivalent: `void moveTextureMappingsFromTexture(...)`
@@ -411,4 +550,41 @@ mod tests {
         // 8 u32 fields = 32 bytes
         assert_eq!(std::mem::size_of::<MapIndirectArguments>(), 32);
     }
+
+    #[test]
+    fn test_sparse_texture_mapping_batch() {
+        let tile_size = Size::new(128, 128, 1);
+        let mut batch = SparseTextureMappingBatch::new();
+
+        batch.push_pixels(
+            Region::new_2d(100, 0, 200, 128),
+            tile_size,
+            0,
+            0,
+            SparseTextureRegionAlignmentMode::OUTWARD,
+        );
+        batch.push_pixels(
+            Region::new_2d(100, 0, 200, 128),
+            tile_size,
+            1,
+            2,
+            SparseTextureRegionAlignmentMode::INWARD,
+        );
+        batch.push_pixels(
+            Region::new_2d(10, 10, 100, 100),
+            tile_size,
+            0,
+            0,
+            SparseTextureRegionAlignmentMode::INWARD,
+        );
+
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch.regions()[0], Region::new_2d(0, 0, 3, 1));
+        assert_eq!(batch.regions()[1], Region::new_2d(1, 0, 1, 1));
+        assert_eq!(batch.mip_levels(), [0, 1]);
+        assert_eq!(batch.slices(), [0, 2]);
+
+        batch.clear();
+        assert!(batch.is_empty());
+    }
 }
@@ -27,6 +27,8 @@
 //! | [`capture`] | `MTLCaptureManager.hpp` |
 //! | [`counter`] | `MTLCounters.hpp` |
 //! | [`function`] | `MTLFunctionDescriptor.hpp` |
+//!
+//! The major enumerations also have string names; see [`names`].
 
 pub mod acceleration;
 pub mod argument;
@@ -43,6 +45,7 @@ pub mod indirect_command;
 pub mod io;
 pub mod library;
 pub mod log;
+pub mod names;
 pub mod pipeline;
 pub mod pixel_format;
 pub mod render;
@@ -68,6 +71,7 @@ pub use indirect_command::*;
 pub use io::*;
 pub use library::*;
 pub use log::*;
+pub use names::ParseEnumError;
 pub use pipeline::*;
 pub use pixel_format::*;
 pub use render::*;
@@ -0,0 +1,379 @@
+//! Names for enumeration values.
+//!
+//! The major enumerations implement [`Display`](fmt::Display) and
+//! [`FromStr`] using the Rust constant names, so configuration files and
+//! debug output can round-trip Metal settings:
+//!
+//! ```
+//! use mtl_gpu::{PixelFormat, StorageMode};
+//!
+//! assert_eq!(PixelFormat::RGBA8_UNORM.to_string(), "RGBA8_UNORM");
+//! assert_eq!("private".parse::<StorageMode>(), Ok(StorageMode::PRIVATE));
+//! ```
+//!
+//! Names are matched case-insensitively. Values without a named constant
+//! display as `Type(raw)`, which parses back, as does a bare integer.
+//!
+//! With the `serde` feature, the same types serialize as their name in
+//! human-readable formats and as the raw integer otherwise.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::*;
+
+/// Error returned when parsing an enumeration value from a string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseEnumError {
+    type_name: &'static str,
+    input: String,
+}
+
+impl ParseEnumError {
+    /// Get the name of the type being parsed, such as `"PixelFormat"`.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Get the string that failed to parse.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+impl fmt::Display for ParseEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown {} `{}`", self.type_name, self.input)
+    }
+}
+
+impl Error for ParseEnumError {}
+
+/// Parse the `Type(raw)` form written by `Display` for unnamed values, or a
+/// bare integer.
+fn parse_raw<T: FromStr>(type_name: &str, s: &str) -> Option<T> {
+    let raw = s
+        .strip_prefix(type_name)
+        .and_then(|rest| rest.strip_prefix('('))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .unwrap_or(s);
+    raw.trim().parse().ok()
+}
+
+macro_rules! impl_enum_names {
+    ($($ty:ident { $($name:ident),* $(,)? })*) => {$(
+        impl $ty {
+            /// Every named value, in declaration order.
+            pub const VALUES: &'static [Self] = &[$(Self::$name),*];
+
+            /// Get the name of the constant this value equals, or `None` if
+            /// there isn't one.
+            pub fn name(&self) -> Option<&'static str> {
+                match *self {
+                    $(Self::$name => Some(stringify!($name)),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self.name() {
+                    Some(name) => f.write_str(name),
+                    None => write!(f, "{}({})", stringify!($ty), self.0),
+                }
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = ParseEnumError;
+
+            fn from_str(s: &str) -> Result<Self, ParseEnumError> {
+                let trimmed = s.trim();
+                Self::VALUES
+                    .iter()
+                    .copied()
+                    .find(|value| {
+                        value
+                            .name()
+                            .is_some_and(|name| name.eq_ignore_ascii_case(trimmed))
+                    })
+                    .or_else(|| parse_raw(stringify!($ty), trimmed).map(Self))
+                    .ok_or_else(|| ParseEnumError {
+                        type_name: stringify!($ty),
+                        input: s.to_string(),
+                    })
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self.name() {
+                    Some(name) if serializer.is_human_readable() => serializer.serialize_str(name),
+                    _ => serde::Serialize::serialize(&self.0, serializer),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if deserializer.is_human_readable() {
+                    let visitor = serde_impl::NameVisitor::<Self>::new(stringify!($ty));
+                    deserializer.deserialize_any(visitor)
+                } else {
+                    serde::Deserialize::deserialize(deserializer).map(Self)
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde_impl::FromRaw for $ty {
+            fn from_u64(raw: u64) -> Option<Self> {
+                raw.try_into().ok().map(Self)
+            }
+
+            fn from_i64(raw: i64) -> Option<Self> {
+                raw.try_into().ok().map(Self)
+            }
+        }
+    )*};
+}
+
+impl_enum_names! {
+    PixelFormat {
+        INVALID, A8_UNORM, R8_UNORM, R8_UNORM_SRGB, R8_SNORM, R8_UINT, R8_SINT, R16_UNORM,
+        R16_SNORM, R16_UINT, R16_SINT, R16_FLOAT, RG8_UNORM, RG8_UNORM_SRGB, RG8_SNORM, RG8_UINT,
+        RG8_SINT, B5G6R5_UNORM, A1BGR5_UNORM, ABGR4_UNORM, BGR5A1_UNORM, R32_UINT, R32_SINT,
+        R32_FLOAT, RG16_UNORM, RG16_SNORM, RG16_UINT, RG16_SINT, RG16_FLOAT, RGBA8_UNORM,
+        RGBA8_UNORM_SRGB, RGBA8_SNORM, RGBA8_UINT, RGBA8_SINT, BGRA8_UNORM, BGRA8_UNORM_SRGB,
+        RGB10A2_UNORM, RGB10A2_UINT, RG11B10_FLOAT, RGB9E5_FLOAT, BGR10A2_UNORM, RG32_UINT,
+        RG32_SINT, RG32_FLOAT, RGBA16_UNORM, RGBA16_SNORM, RGBA16_UINT, RGBA16_SINT, RGBA16_FLOAT,
+        RGBA32_UINT, RGBA32_SINT, RGBA32_FLOAT, BC1_RGBA, BC1_RGBA_SRGB, BC2_RGBA, BC2_RGBA_SRGB,
+        BC3_RGBA, BC3_RGBA_SRGB, BC4_R_UNORM, BC4_R_SNORM, BC5_RG_UNORM, BC5_RG_SNORM,
+        BC6H_RGB_FLOAT, BC6H_RGB_UFLOAT, BC7_RGBA_UNORM, BC7_RGBA_UNORM_SRGB, PVRTC_RGB_2BPP,
+        PVRTC_RGB_2BPP_SRGB, PVRTC_RGB_4BPP, PVRTC_RGB_4BPP_SRGB, PVRTC_RGBA_2BPP,
+        PVRTC_RGBA_2BPP_SRGB, PVRTC_RGBA_4BPP, PVRTC_RGBA_4BPP_SRGB, EAC_R11_UNORM, EAC_R11_SNORM,
+        EAC_RG11_UNORM, EAC_RG11_SNORM, EAC_RGBA8, EAC_RGBA8_SRGB, ETC2_RGB8, ETC2_RGB8_SRGB,
+        ETC2_RGB8A1, ETC2_RGB8A1_SRGB, ASTC_4X4_SRGB, ASTC_5X4_SRGB, ASTC_5X5_SRGB, ASTC_6X5_SRGB,
+        ASTC_6X6_SRGB, ASTC_8X5_SRGB, ASTC_8X6_SRGB, ASTC_8X8_SRGB, ASTC_10X5_SRGB, ASTC_10X6_SRGB,
+        ASTC_10X8_SRGB, ASTC_10X10_SRGB, ASTC_12X10_SRGB, ASTC_12X12_SRGB, ASTC_4X4_LDR,
+        ASTC_5X4_LDR, ASTC_5X5_LDR, ASTC_6X5_LDR, ASTC_6X6_LDR, ASTC_8X5_LDR, ASTC_8X6_LDR,
+        ASTC_8X8_LDR, ASTC_10X5_LDR, ASTC_10X6_LDR, ASTC_10X8_LDR, ASTC_10X10_LDR, ASTC_12X10_LDR,
+        ASTC_12X12_LDR, ASTC_4X4_HDR, ASTC_5X4_HDR, ASTC_5X5_HDR, ASTC_6X5_HDR, ASTC_6X6_HDR,
+        ASTC_8X5_HDR, ASTC_8X6_HDR, ASTC_8X8_HDR, ASTC_10X5_HDR, ASTC_10X6_HDR, ASTC_10X8_HDR,
+        ASTC_10X10_HDR, ASTC_12X10_HDR, ASTC_12X12_HDR, GBGR422, BGRG422, DEPTH16_UNORM,
+        DEPTH32_FLOAT, STENCIL8, DEPTH24_UNORM_STENCIL8, DEPTH32_FLOAT_STENCIL8, X32_STENCIL8,
+        X24_STENCIL8, BGRA10_XR, BGRA10_XR_SRGB, BGR10_XR, BGR10_XR_SRGB, UNSPECIALIZED,
+    }
+    VertexFormat {
+        INVALID, UCHAR2, UCHAR3, UCHAR4, CHAR2, CHAR3, CHAR4, UCHAR2_NORMALIZED, UCHAR3_NORMALIZED,
+        UCHAR4_NORMALIZED, CHAR2_NORMALIZED, CHAR3_NORMALIZED, CHAR4_NORMALIZED, USHORT2, USHORT3,
+        USHORT4, SHORT2, SHORT3, SHORT4, USHORT2_NORMALIZED, USHORT3_NORMALIZED,
+        USHORT4_NORMALIZED, SHORT2_NORMALIZED, SHORT3_NORMALIZED, SHORT4_NORMALIZED, HALF2, HALF3,
+        HALF4, FLOAT, FLOAT2, FLOAT3, FLOAT4, INT, INT2, INT3, INT4, UINT, UINT2, UINT3, UINT4,
+        INT1010102_NORMALIZED, UINT1010102_NORMALIZED, UCHAR4_NORMALIZED_BGRA, UCHAR, CHAR,
+        UCHAR_NORMALIZED, CHAR_NORMALIZED, USHORT, SHORT, USHORT_NORMALIZED, SHORT_NORMALIZED,
+        HALF, FLOAT_RG11B10, FLOAT_RGB9E5,
+    }
+    VertexStepFunction {
+        CONSTANT, PER_VERTEX, PER_INSTANCE, PER_PATCH, PER_PATCH_CONTROL_POINT,
+    }
+    AttributeFormat {
+        INVALID, UCHAR2, UCHAR3, UCHAR4, CHAR2, CHAR3, CHAR4, UCHAR2_NORMALIZED, UCHAR3_NORMALIZED,
+        UCHAR4_NORMALIZED, CHAR2_NORMALIZED, CHAR3_NORMALIZED, CHAR4_NORMALIZED, USHORT2, USHORT3,
+        USHORT4, SHORT2, SHORT3, SHORT4, USHORT2_NORMALIZED, USHORT3_NORMALIZED,
+        USHORT4_NORMALIZED, SHORT2_NORMALIZED, SHORT3_NORMALIZED, SHORT4_NORMALIZED, HALF2, HALF3,
+        HALF4, FLOAT, FLOAT2, FLOAT3, FLOAT4, INT, INT2, INT3, INT4, UINT, UINT2, UINT3, UINT4,
+        INT1010102_NORMALIZED, UINT1010102_NORMALIZED, UCHAR4_NORMALIZED_BGRA, UCHAR, CHAR,
+        UCHAR_NORMALIZED, CHAR_NORMALIZED, USHORT, SHORT, USHORT_NORMALIZED, SHORT_NORMALIZED,
+        HALF, FLOAT_RG11B10, FLOAT_RGB9E5,
+    }
+    CPUCacheMode {
+        DEFAULT_CACHE, WRITE_COMBINED,
+    }
+    StorageMode {
+        SHARED, MANAGED, PRIVATE, MEMORYLESS,
+    }
+    HazardTrackingMode {
+        DEFAULT, UNTRACKED, TRACKED,
+    }
+    TextureType {
+        TYPE_1D, TYPE_1D_ARRAY, TYPE_2D, TYPE_2D_ARRAY, TYPE_2D_MULTISAMPLE, TYPE_CUBE,
+        TYPE_CUBE_ARRAY, TYPE_3D, TYPE_2D_MULTISAMPLE_ARRAY, TYPE_TEXTURE_BUFFER,
+    }
+    SamplerMinMagFilter {
+        NEAREST, LINEAR,
+    }
+    SamplerMipFilter {
+        NOT_MIPMAPPED, NEAREST, LINEAR,
+    }
+    SamplerAddressMode {
+        CLAMP_TO_EDGE, MIRROR_CLAMP_TO_EDGE, REPEAT, MIRROR_REPEAT, CLAMP_TO_ZERO,
+        CLAMP_TO_BORDER_COLOR,
+    }
+    SamplerBorderColor {
+        TRANSPARENT_BLACK, OPAQUE_BLACK, OPAQUE_WHITE,
+    }
+    CompareFunction {
+        NEVER, LESS, EQUAL, LESS_EQUAL, GREATER, NOT_EQUAL, GREATER_EQUAL, ALWAYS,
+    }
+    StencilOperation {
+        KEEP, ZERO, REPLACE, INCREMENT_CLAMP, DECREMENT_CLAMP, INVERT, INCREMENT_WRAP,
+        DECREMENT_WRAP,
+    }
+    PrimitiveType {
+        POINT, LINE, LINE_STRIP, TRIANGLE, TRIANGLE_STRIP,
+    }
+    CullMode {
+        NONE, FRONT, BACK,
+    }
+    Winding {
+        CLOCKWISE, COUNTER_CLOCKWISE,
+    }
+    TriangleFillMode {
+        FILL, LINES,
+    }
+    LoadAction {
+        DONT_CARE, LOAD, CLEAR,
+    }
+    StoreAction {
+        DONT_CARE, STORE, MULTISAMPLE_RESOLVE, STORE_AND_MULTISAMPLE_RESOLVE, UNKNOWN,
+        CUSTOM_SAMPLE_DEPTH_STORE,
+    }
+    BlendFactor {
+        ZERO, ONE, SOURCE_COLOR, ONE_MINUS_SOURCE_COLOR, SOURCE_ALPHA, ONE_MINUS_SOURCE_ALPHA,
+        DESTINATION_COLOR, ONE_MINUS_DESTINATION_COLOR, DESTINATION_ALPHA,
+        ONE_MINUS_DESTINATION_ALPHA, SOURCE_ALPHA_SATURATED, BLEND_COLOR, ONE_MINUS_BLEND_COLOR,
+        BLEND_ALPHA, ONE_MINUS_BLEND_ALPHA, SOURCE1_COLOR, ONE_MINUS_SOURCE1_COLOR, SOURCE1_ALPHA,
+        ONE_MINUS_SOURCE1_ALPHA, UNSPECIALIZED,
+    }
+    BlendOperation {
+        ADD, SUBTRACT, REVERSE_SUBTRACT, MIN, MAX, UNSPECIALIZED,
+    }
+    IndexType {
+        UINT16, UINT32,
+    }
+    DataType {
+        NONE, STRUCT, ARRAY, FLOAT, FLOAT2, FLOAT3, FLOAT4, FLOAT2X2, FLOAT2X3, FLOAT2X4, FLOAT3X2,
+        FLOAT3X3, FLOAT3X4, FLOAT4X2, FLOAT4X3, FLOAT4X4, HALF, HALF2, HALF3, HALF4, HALF2X2,
+        HALF2X3, HALF2X4, HALF3X2, HALF3X3, HALF3X4, HALF4X2, HALF4X3, HALF4X4, INT, INT2, INT3,
+        INT4, UINT, UINT2, UINT3, UINT4, SHORT, SHORT2, SHORT3, SHORT4, USHORT, USHORT2, USHORT3,
+        USHORT4, CHAR, CHAR2, CHAR3, CHAR4, UCHAR, UCHAR2, UCHAR3, UCHAR4, BOOL, BOOL2, BOOL3,
+        BOOL4, TEXTURE, SAMPLER, POINTER, R8_UNORM, R8_SNORM, R16_UNORM, R16_SNORM, RG8_UNORM,
+        RG8_SNORM, RG16_UNORM, RG16_SNORM, RGBA8_UNORM, RGBA8_UNORM_SRGB, RGBA8_SNORM,
+        RGBA16_UNORM, RGBA16_SNORM, RGB10A2_UNORM, RG11B10_FLOAT, RGB9E5_FLOAT, RENDER_PIPELINE,
+        COMPUTE_PIPELINE, INDIRECT_COMMAND_BUFFER, LONG, LONG2, LONG3, LONG4, ULONG, ULONG2,
+        ULONG3, ULONG4, VISIBLE_FUNCTION_TABLE, INTERSECTION_FUNCTION_TABLE,
+        PRIMITIVE_ACCELERATION_STRUCTURE, INSTANCE_ACCELERATION_STRUCTURE, BFLOAT, BFLOAT2,
+        BFLOAT3, BFLOAT4, DEPTH_STENCIL_STATE, TENSOR,
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::fmt;
+    use std::marker::PhantomData;
+    use std::str::FromStr;
+
+    use serde::de::{self, Visitor};
+
+    use super::ParseEnumError;
+
+    /// Construction from a raw integer, for deserializing unnamed values.
+    pub(super) trait FromRaw: Sized {
+        fn from_u64(raw: u64) -> Option<Self>;
+        fn from_i64(raw: i64) -> Option<Self>;
+    }
+
+    /// Accepts a name, the `Type(raw)` form, or an integer.
+    pub(super) struct NameVisitor<T> {
+        type_name: &'static str,
+        marker: PhantomData<T>,
+    }
+
+    impl<T> NameVisitor<T> {
+        pub(super) fn new(type_name: &'static str) -> Self {
+            Self {
+                type_name,
+                marker: PhantomData,
+            }
+        }
+    }
+
+    impl<T> Visitor<'_> for NameVisitor<T>
+    where
+        T: FromStr<Err = ParseEnumError> + FromRaw,
+    {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a {} name or integer", self.type_name)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+            v.parse().map_err(E::custom)
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+            T::from_u64(v).ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+            T::from_i64(v).ok_or_else(|| E::invalid_value(de::Unexpected::Signed(v), &self))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            PixelFormat::BGRA8_UNORM_SRGB.to_string(),
+            "BGRA8_UNORM_SRGB"
+        );
+        assert_eq!(StorageMode::SHARED.to_string(), "SHARED");
+        assert_eq!(PixelFormat(9999).to_string(), "PixelFormat(9999)");
+    }
+
+    #[test]
+    fn test_from_str_round_trip() {
+        for &format in PixelFormat::VALUES.iter().chain([PixelFormat(9999)].iter()) {
+            assert_eq!(format.to_string().parse::<PixelFormat>(), Ok(format));
+        }
+        for &format in VertexFormat::VALUES {
+            assert_eq!(format.to_string().parse::<VertexFormat>(), Ok(format));
+        }
+        assert_eq!(" float3 ".parse::<VertexFormat>(), Ok(VertexFormat::FLOAT3));
+        assert_eq!("2".parse::<StorageMode>(), Ok(StorageMode::PRIVATE));
+    }
+
+    #[test]
+    fn test_from_str_error() {
+        let err = "PURPLE".parse::<PixelFormat>().unwrap_err();
+        assert_eq!(err.type_name(), "PixelFormat");
+        assert_eq!(err.input(), "PURPLE");
+        assert_eq!(err.to_string(), "unknown PixelFormat `PURPLE`");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde::de::IntoDeserializer;
+        use serde::de::value::Error;
+
+        let deserializer: serde::de::value::StrDeserializer<'_, Error> =
+            "rgba16_float".into_deserializer();
+        let format: PixelFormat = serde::Deserialize::deserialize(deserializer).unwrap();
+        assert_eq!(format, PixelFormat::RGBA16_FLOAT);
+
+        let deserializer: serde::de::value::U64Deserializer<Error> = 3u64.into_deserializer();
+        let mode: StorageMode = serde::Deserialize::deserialize(deserializer).unwrap();
+        assert_eq!(mode, StorageMode::MEMORYLESS);
+    }
+}
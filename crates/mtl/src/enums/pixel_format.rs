@@ -203,6 +203,326 @@ impl PixelFormat {
     pub const UNSPECIALIZED: Self = Self(263);
 }
 
+impl PixelFormat {
+    /// Get the size in bytes of a single pixel.
+    ///
+    /// Returns `None` for compressed, packed YUV, and combined depth/stencil
+    /// formats, which have no single per-pixel size.
+    pub const fn bytes_per_pixel(self) -> Option<UInteger> {
+        match self {
+            Self::A8_UNORM
+            | Self::R8_UNORM
+            | Self::R8_UNORM_SRGB
+            | Self::R8_SNORM
+            | Self::R8_UINT
+            | Self::R8_SINT
+            | Self::STENCIL8 => Some(1),
+
+            Self::R16_UNORM
+            | Self::R16_SNORM
+            | Self::R16_UINT
+            | Self::R16_SINT
+            | Self::R16_FLOAT
+            | Self::RG8_UNORM
+            | Self::RG8_UNORM_SRGB
+            | Self::RG8_SNORM
+            | Self::RG8_UINT
+            | Self::RG8_SINT
+            | Self::B5G6R5_UNORM
+            | Self::A1BGR5_UNORM
+            | Self::ABGR4_UNORM
+            | Self::BGR5A1_UNORM
+            | Self::DEPTH16_UNORM => Some(2),
+
+            Self::R32_UINT
+            | Self::R32_SINT
+            | Self::R32_FLOAT
+            | Self::RG16_UNORM
+            | Self::RG16_SNORM
+            | Self::RG16_UINT
+            | Self::RG16_SINT
+            | Self::RG16_FLOAT
+            | Self::RGBA8_UNORM
+            | Self::RGBA8_UNORM_SRGB
+            | Self::RGBA8_SNORM
+            | Self::RGBA8_UINT
+            | Self::RGBA8_SINT
+            | Self::BGRA8_UNORM
+            | Self::BGRA8_UNORM_SRGB
+            | Self::RGB10A2_UNORM
+            | Self::RGB10A2_UINT
+            | Self::RG11B10_FLOAT
+            | Self::RGB9E5_FLOAT
+            | Self::BGR10A2_UNORM
+            | Self::BGR10_XR
+            | Self::BGR10_XR_SRGB
+            | Self::DEPTH32_FLOAT => Some(4),
+
+            Self::RG32_UINT
+            | Self::RG32_SINT
+            | Self::RG32_FLOAT
+            | Self::RGBA16_UNORM
+            | Self::RGBA16_SNORM
+            | Self::RGBA16_UINT
+            | Self::RGBA16_SINT
+            | Self::RGBA16_FLOAT
+            | Self::BGRA10_XR
+            | Self::BGRA10_XR_SRGB => Some(8),
+
+            Self::RGBA32_UINT | Self::RGBA32_SINT | Self::RGBA32_FLOAT => Some(16),
+
+            _ => None,
+        }
+    }
+
+    /// Check if the format is block-compressed (BC, PVRTC, EAC, ETC2 or
+    /// ASTC).
+    pub const fn is_compressed(self) -> bool {
+        self.0 >= Self::BC1_RGBA.0 && self.0 <= Self::ASTC_12X12_HDR.0
+    }
+
+    /// Get the width and height in pixels of one block of the format.
+    ///
+    /// Returns `(1, 1)` for formats that store each pixel separately.
+    pub const fn block_size(self) -> (UInteger, UInteger) {
+        match self.block_layout() {
+            Some((width, height, _)) => (width, height),
+            None => (1, 1),
+        }
+    }
+
+    /// Get the size in bytes of one block of the format.
+    ///
+    /// For formats with a 1x1 block this is the same as
+    /// [`bytes_per_pixel`](Self::bytes_per_pixel).
+    pub const fn bytes_per_block(self) -> Option<UInteger> {
+        match self.block_layout() {
+            Some((_, _, bytes)) => Some(bytes),
+            None => self.bytes_per_pixel(),
+        }
+    }
+
+    /// Get the number of bytes in a tightly packed row of `width` pixels.
+    ///
+    /// For block formats a row is a row of blocks, so the width is rounded
+    /// up to a whole number of blocks.
+    pub const fn bytes_per_row(self, width: UInteger) -> Option<UInteger> {
+        let (block_width, _) = self.block_size();
+        match self.bytes_per_block() {
+            Some(bytes) => Some(width.div_ceil(block_width) * bytes),
+            None => None,
+        }
+    }
+
+    /// Get the number of bytes in a tightly packed `width` x `height` image.
+    pub const fn bytes_per_image(self, width: UInteger, height: UInteger) -> Option<UInteger> {
+        let (_, block_height) = self.block_size();
+        match self.bytes_per_row(width) {
+            Some(row) => Some(height.div_ceil(block_height) * row),
+            None => None,
+        }
+    }
+
+    /// Get `(block width, block height, bytes per block)` for formats whose
+    /// block is larger than one pixel.
+    const fn block_layout(self) -> Option<(UInteger, UInteger, UInteger)> {
+        match self {
+            Self::BC1_RGBA | Self::BC1_RGBA_SRGB | Self::BC4_R_UNORM | Self::BC4_R_SNORM => {
+                Some((4, 4, 8))
+            }
+            Self::BC2_RGBA
+            | Self::BC2_RGBA_SRGB
+            | Self::BC3_RGBA
+            | Self::BC3_RGBA_SRGB
+            | Self::BC5_RG_UNORM
+            | Self::BC5_RG_SNORM
+            | Self::BC6H_RGB_FLOAT
+            | Self::BC6H_RGB_UFLOAT
+            | Self::BC7_RGBA_UNORM
+            | Self::BC7_RGBA_UNORM_SRGB => Some((4, 4, 16)),
+
+            Self::PVRTC_RGB_2BPP
+            | Self::PVRTC_RGB_2BPP_SRGB
+            | Self::PVRTC_RGBA_2BPP
+            | Self::PVRTC_RGBA_2BPP_SRGB => Some((8, 4, 8)),
+            Self::PVRTC_RGB_4BPP
+            | Self::PVRTC_RGB_4BPP_SRGB
+            | Self::PVRTC_RGBA_4BPP
+            | Self::PVRTC_RGBA_4BPP_SRGB => Some((4, 4, 8)),
+
+            Self::EAC_R11_UNORM
+            | Self::EAC_R11_SNORM
+            | Self::ETC2_RGB8
+            | Self::ETC2_RGB8_SRGB
+            | Self::ETC2_RGB8A1
+            | Self::ETC2_RGB8A1_SRGB => Some((4, 4, 8)),
+            Self::EAC_RG11_UNORM
+            | Self::EAC_RG11_SNORM
+            | Self::EAC_RGBA8
+            | Self::EAC_RGBA8_SRGB => Some((4, 4, 16)),
+
+            Self::ASTC_4X4_SRGB | Self::ASTC_4X4_LDR | Self::ASTC_4X4_HDR => Some((4, 4, 16)),
+            Self::ASTC_5X4_SRGB | Self::ASTC_5X4_LDR | Self::ASTC_5X4_HDR => Some((5, 4, 16)),
+            Self::ASTC_5X5_SRGB | Self::ASTC_5X5_LDR | Self::ASTC_5X5_HDR => Some((5, 5, 16)),
+            Self::ASTC_6X5_SRGB | Self::ASTC_6X5_LDR | Self::ASTC_6X5_HDR => Some((6, 5, 16)),
+            Self::ASTC_6X6_SRGB | Self::ASTC_6X6_LDR | Self::ASTC_6X6_HDR => Some((6, 6, 16)),
+            Self::ASTC_8X5_SRGB | Self::ASTC_8X5_LDR | Self::ASTC_8X5_HDR => Some((8, 5, 16)),
+            Self::ASTC_8X6_SRGB | Self::ASTC_8X6_LDR | Self::ASTC_8X6_HDR => Some((8, 6, 16)),
+            Self::ASTC_8X8_SRGB | Self::ASTC_8X8_LDR | Self::ASTC_8X8_HDR => Some((8, 8, 16)),
+            Self::ASTC_10X5_SRGB | Self::ASTC_10X5_LDR | Self::ASTC_10X5_HDR => Some((10, 5, 16)),
+            Self::ASTC_10X6_SRGB | Self::ASTC_10X6_LDR | Self::ASTC_10X6_HDR => Some((10, 6, 16)),
+            Self::ASTC_10X8_SRGB | Self::ASTC_10X8_LDR | Self::ASTC_10X8_HDR => Some((10, 8, 16)),
+            Self::ASTC_10X10_SRGB | Self::ASTC_10X10_LDR | Self::ASTC_10X10_HDR => {
+                Some((10, 10, 16))
+            }
+            Self::ASTC_12X10_SRGB | Self::ASTC_12X10_LDR | Self::ASTC_12X10_HDR => {
+                Some((12, 10, 16))
+            }
+            Self::ASTC_12X12_SRGB | Self::ASTC_12X12_LDR | Self::ASTC_12X12_HDR => {
+                Some((12, 12, 16))
+            }
+
+            Self::GBGR422 | Self::BGRG422 => Some((2, 1, 4)),
+
+            _ => None,
+        }
+    }
+
+    /// Check if the format is a depth, stencil or combined depth/stencil
+    /// format.
+    pub const fn is_depth_stencil(self) -> bool {
+        self.is_depth() || self.has_stencil()
+    }
+
+    /// Check if the format stores sRGB-encoded color.
+    pub const fn is_srgb(self) -> bool {
+        self.linear_variant().0 != self.0
+    }
+
+    /// Get the sRGB-encoded counterpart of the format.
+    ///
+    /// Returns the format itself if it is already sRGB, and `None` if it has
+    /// no sRGB counterpart.
+    pub const fn srgb_variant(self) -> Option<Self> {
+        let format = match self {
+            Self::R8_UNORM => Self::R8_UNORM_SRGB,
+            Self::RG8_UNORM => Self::RG8_UNORM_SRGB,
+            Self::RGBA8_UNORM => Self::RGBA8_UNORM_SRGB,
+            Self::BGRA8_UNORM => Self::BGRA8_UNORM_SRGB,
+            Self::BGRA10_XR => Self::BGRA10_XR_SRGB,
+            Self::BGR10_XR => Self::BGR10_XR_SRGB,
+            Self::BC1_RGBA => Self::BC1_RGBA_SRGB,
+            Self::BC2_RGBA => Self::BC2_RGBA_SRGB,
+            Self::BC3_RGBA => Self::BC3_RGBA_SRGB,
+            Self::BC7_RGBA_UNORM => Self::BC7_RGBA_UNORM_SRGB,
+            Self::PVRTC_RGB_2BPP => Self::PVRTC_RGB_2BPP_SRGB,
+            Self::PVRTC_RGB_4BPP => Self::PVRTC_RGB_4BPP_SRGB,
+            Self::PVRTC_RGBA_2BPP => Self::PVRTC_RGBA_2BPP_SRGB,
+            Self::PVRTC_RGBA_4BPP => Self::PVRTC_RGBA_4BPP_SRGB,
+            Self::EAC_RGBA8 => Self::EAC_RGBA8_SRGB,
+            Self::ETC2_RGB8 => Self::ETC2_RGB8_SRGB,
+            Self::ETC2_RGB8A1 => Self::ETC2_RGB8A1_SRGB,
+            format if format.0 >= Self::ASTC_4X4_LDR.0 && format.0 <= Self::ASTC_12X12_LDR.0 => {
+                Self(format.0 - (Self::ASTC_4X4_LDR.0 - Self::ASTC_4X4_SRGB.0))
+            }
+            format if format.is_srgb() => format,
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// Get the linear (non-sRGB) counterpart of the format.
+    ///
+    /// Returns the format itself if it is not sRGB.
+    pub const fn linear_variant(self) -> Self {
+        match self {
+            Self::R8_UNORM_SRGB => Self::R8_UNORM,
+            Self::RG8_UNORM_SRGB => Self::RG8_UNORM,
+            Self::RGBA8_UNORM_SRGB => Self::RGBA8_UNORM,
+            Self::BGRA8_UNORM_SRGB => Self::BGRA8_UNORM,
+            Self::BGRA10_XR_SRGB => Self::BGRA10_XR,
+            Self::BGR10_XR_SRGB => Self::BGR10_XR,
+            Self::BC1_RGBA_SRGB => Self::BC1_RGBA,
+            Self::BC2_RGBA_SRGB => Self::BC2_RGBA,
+            Self::BC3_RGBA_SRGB => Self::BC3_RGBA,
+            Self::BC7_RGBA_UNORM_SRGB => Self::BC7_RGBA_UNORM,
+            Self::PVRTC_RGB_2BPP_SRGB => Self::PVRTC_RGB_2BPP,
+            Self::PVRTC_RGB_4BPP_SRGB => Self::PVRTC_RGB_4BPP,
+            Self::PVRTC_RGBA_2BPP_SRGB => Self::PVRTC_RGBA_2BPP,
+            Self::PVRTC_RGBA_4BPP_SRGB => Self::PVRTC_RGBA_4BPP,
+            Self::EAC_RGBA8_SRGB => Self::EAC_RGBA8,
+            Self::ETC2_RGB8_SRGB => Self::ETC2_RGB8,
+            Self::ETC2_RGB8A1_SRGB => Self::ETC2_RGB8A1,
+            format if format.0 >= Self::ASTC_4X4_SRGB.0 && format.0 <= Self::ASTC_12X12_SRGB.0 => {
+                Self(format.0 + (Self::ASTC_4X4_LDR.0 - Self::ASTC_4X4_SRGB.0))
+            }
+            format => format,
+        }
+    }
+
+    /// Check if the format has a depth component.
+    pub const fn is_depth(self) -> bool {
+        matches!(
+            self,
+            Self::DEPTH16_UNORM
+                | Self::DEPTH32_FLOAT
+                | Self::DEPTH24_UNORM_STENCIL8
+                | Self::DEPTH32_FLOAT_STENCIL8
+        )
+    }
+
+    /// Check if the format stores unnormalized integers, read in shaders as
+    /// `uint` or `int` rather than `float`.
+    pub const fn is_integer(self) -> bool {
+        matches!(
+            self,
+            Self::R8_UINT
+                | Self::R8_SINT
+                | Self::R16_UINT
+                | Self::R16_SINT
+                | Self::RG8_UINT
+                | Self::RG8_SINT
+                | Self::R32_UINT
+                | Self::R32_SINT
+                | Self::RG16_UINT
+                | Self::RG16_SINT
+                | Self::RGBA8_UINT
+                | Self::RGBA8_SINT
+                | Self::RGB10A2_UINT
+                | Self::RG32_UINT
+                | Self::RG32_SINT
+                | Self::RGBA16_UINT
+                | Self::RGBA16_SINT
+                | Self::RGBA32_UINT
+                | Self::RGBA32_SINT
+        )
+    }
+
+    /// Check if the format has a stencil component.
+    pub const fn has_stencil(self) -> bool {
+        matches!(
+            self,
+            Self::STENCIL8
+                | Self::DEPTH24_UNORM_STENCIL8
+                | Self::DEPTH32_FLOAT_STENCIL8
+                | Self::X24_STENCIL8
+                | Self::X32_STENCIL8
+        )
+    }
+
+    /// Get the format of a view that reads only the stencil component.
+    ///
+    /// Returns `None` for formats without a stencil component.
+    pub const fn stencil_view_format(self) -> Option<Self> {
+        match self {
+            Self::DEPTH32_FLOAT_STENCIL8 | Self::X32_STENCIL8 => Some(Self::X32_STENCIL8),
+            Self::DEPTH24_UNORM_STENCIL8 | Self::X24_STENCIL8 => Some(Self::X24_STENCIL8),
+            Self::STENCIL8 => Some(Self::STENCIL8),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,4 +542,93 @@ mod tests {
             std::mem::size_of::<UInteger>()
         );
     }
+
+    #[test]
+    fn test_bytes_per_pixel() {
+        assert_eq!(PixelFormat::R8_UNORM.bytes_per_pixel(), Some(1));
+        assert_eq!(PixelFormat::RG8_UNORM.bytes_per_pixel(), Some(2));
+        assert_eq!(PixelFormat::BGRA8_UNORM.bytes_per_pixel(), Some(4));
+        assert_eq!(PixelFormat::RGBA16_FLOAT.bytes_per_pixel(), Some(8));
+        assert_eq!(PixelFormat::RGBA32_FLOAT.bytes_per_pixel(), Some(16));
+        assert_eq!(PixelFormat::BC1_RGBA.bytes_per_pixel(), None);
+        assert_eq!(PixelFormat::DEPTH32_FLOAT_STENCIL8.bytes_per_pixel(), None);
+        assert_eq!(PixelFormat::INVALID.bytes_per_pixel(), None);
+    }
+
+    #[test]
+    fn test_depth_stencil_queries() {
+        assert!(PixelFormat::DEPTH32_FLOAT.is_depth());
+        assert!(!PixelFormat::DEPTH32_FLOAT.has_stencil());
+        assert!(PixelFormat::DEPTH32_FLOAT_STENCIL8.is_depth());
+        assert!(PixelFormat::DEPTH32_FLOAT_STENCIL8.has_stencil());
+        assert!(!PixelFormat::STENCIL8.is_depth());
+        assert!(!PixelFormat::BGRA8_UNORM.is_depth());
+        assert_eq!(
+            PixelFormat::DEPTH32_FLOAT_STENCIL8.stencil_view_format(),
+            Some(PixelFormat::X32_STENCIL8)
+        );
+        assert_eq!(PixelFormat::DEPTH32_FLOAT.stencil_view_format(), None);
+    }
+
+    #[test]
+    fn test_is_integer() {
+        assert!(PixelFormat::R32_UINT.is_integer());
+        assert!(PixelFormat::RGBA8_SINT.is_integer());
+        assert!(!PixelFormat::RGBA8_UNORM.is_integer());
+        assert!(!PixelFormat::R32_FLOAT.is_integer());
+        assert!(!PixelFormat::STENCIL8.is_integer());
+    }
+
+    #[test]
+    fn test_block_queries() {
+        assert!(PixelFormat::BC1_RGBA.is_compressed());
+        assert!(PixelFormat::ASTC_12X12_HDR.is_compressed());
+        assert!(!PixelFormat::RGBA8_UNORM.is_compressed());
+        assert!(!PixelFormat::GBGR422.is_compressed());
+
+        assert_eq!(PixelFormat::RGBA8_UNORM.block_size(), (1, 1));
+        assert_eq!(PixelFormat::BC7_RGBA_UNORM.block_size(), (4, 4));
+        assert_eq!(PixelFormat::PVRTC_RGB_2BPP.block_size(), (8, 4));
+        assert_eq!(PixelFormat::ASTC_10X6_LDR.block_size(), (10, 6));
+
+        assert_eq!(PixelFormat::BC1_RGBA.bytes_per_block(), Some(8));
+        assert_eq!(PixelFormat::BC3_RGBA.bytes_per_block(), Some(16));
+        assert_eq!(PixelFormat::RGBA16_FLOAT.bytes_per_block(), Some(8));
+        assert_eq!(PixelFormat::INVALID.bytes_per_block(), None);
+
+        assert_eq!(PixelFormat::RGBA8_UNORM.bytes_per_row(10), Some(40));
+        assert_eq!(PixelFormat::BC1_RGBA.bytes_per_row(10), Some(24));
+        assert_eq!(PixelFormat::BC1_RGBA.bytes_per_image(10, 5), Some(48));
+        assert_eq!(PixelFormat::GBGR422.bytes_per_row(3), Some(8));
+    }
+
+    #[test]
+    fn test_srgb_variants() {
+        assert!(PixelFormat::BGRA8_UNORM_SRGB.is_srgb());
+        assert!(!PixelFormat::BGRA8_UNORM.is_srgb());
+        assert_eq!(
+            PixelFormat::BGRA8_UNORM.srgb_variant(),
+            Some(PixelFormat::BGRA8_UNORM_SRGB)
+        );
+        assert_eq!(
+            PixelFormat::BGRA8_UNORM_SRGB.srgb_variant(),
+            Some(PixelFormat::BGRA8_UNORM_SRGB)
+        );
+        assert_eq!(PixelFormat::RGBA16_FLOAT.srgb_variant(), None);
+        assert_eq!(
+            PixelFormat::ASTC_8X8_LDR.srgb_variant(),
+            Some(PixelFormat::ASTC_8X8_SRGB)
+        );
+        assert_eq!(
+            PixelFormat::ASTC_8X8_SRGB.linear_variant(),
+            PixelFormat::ASTC_8X8_LDR
+        );
+        assert_eq!(
+            PixelFormat::RGBA16_FLOAT.linear_variant(),
+            PixelFormat::RGBA16_FLOAT
+        );
+        assert!(PixelFormat::DEPTH32_FLOAT.is_depth_stencil());
+        assert!(PixelFormat::STENCIL8.is_depth_stencil());
+        assert!(!PixelFormat::R32_FLOAT.is_depth_stencil());
+    }
 }
@@ -22,6 +22,16 @@ impl TensorDataType {
     pub const UINT16: Self = Self(41);
     pub const INT32: Self = Self(29);
     pub const UINT32: Self = Self(33);
+
+    /// Get the size in bytes of one element, or `None` for [`NONE`](Self::NONE).
+    pub const fn size_in_bytes(self) -> Option<usize> {
+        match self {
+            Self::INT8 | Self::UINT8 => Some(1),
+            Self::FLOAT16 | Self::BFLOAT16 | Self::INT16 | Self::UINT16 => Some(2),
+            Self::FLOAT32 | Self::INT32 | Self::UINT32 => Some(4),
+            _ => None,
+        }
+    }
 }
 
 /// Tensor error codes.
@@ -88,6 +98,14 @@ mod tests {
         assert_eq!(TensorDataType::UINT32.0, 33);
     }
 
+    #[test]
+    fn test_tensor_data_type_size() {
+        assert_eq!(TensorDataType::UINT8.size_in_bytes(), Some(1));
+        assert_eq!(TensorDataType::BFLOAT16.size_in_bytes(), Some(2));
+        assert_eq!(TensorDataType::FLOAT32.size_in_bytes(), Some(4));
+        assert_eq!(TensorDataType::NONE.size_in_bytes(), None);
+    }
+
     #[test]
     fn test_tensor_error_values() {
         assert_eq!(TensorError::NONE.0, 0);
@@ -164,12 +164,64 @@ impl TextureSwizzleChannels {
     /// C++ equivalent: `static TextureSwizzleChannels Default()`
     #[inline]
     pub const fn default_channels() -> Self {
-        Self {
-            red: TextureSwizzle::RED,
-            green: TextureSwizzle::GREEN,
-            blue: TextureSwizzle::BLUE,
-            alpha: TextureSwizzle::ALPHA,
-        }
+        Self::IDENTITY
+    }
+
+    /// Each channel reads itself.
+    pub const IDENTITY: Self = Self::new(
+        TextureSwizzle::RED,
+        TextureSwizzle::GREEN,
+        TextureSwizzle::BLUE,
+        TextureSwizzle::ALPHA,
+    );
+
+    /// Swap red and blue, reading BGRA data as RGBA and vice versa.
+    pub const SWAP_RED_BLUE: Self = Self::new(
+        TextureSwizzle::BLUE,
+        TextureSwizzle::GREEN,
+        TextureSwizzle::RED,
+        TextureSwizzle::ALPHA,
+    );
+
+    /// Broadcast red to the color channels with opaque alpha, so
+    /// single-channel textures sample as grayscale.
+    pub const RED_AS_GRAYSCALE: Self = Self::new(
+        TextureSwizzle::RED,
+        TextureSwizzle::RED,
+        TextureSwizzle::RED,
+        TextureSwizzle::ONE,
+    );
+
+    /// Read red as alpha over white, so single-channel masks sample like
+    /// `A8Unorm` textures.
+    pub const RED_AS_ALPHA: Self = Self::new(
+        TextureSwizzle::ONE,
+        TextureSwizzle::ONE,
+        TextureSwizzle::ONE,
+        TextureSwizzle::RED,
+    );
+
+    /// Keep the color channels and force alpha to one.
+    pub const OPAQUE: Self = Self::new(
+        TextureSwizzle::RED,
+        TextureSwizzle::GREEN,
+        TextureSwizzle::BLUE,
+        TextureSwizzle::ONE,
+    );
+
+    /// Check if every channel reads itself.
+    #[inline]
+    pub const fn is_identity(&self) -> bool {
+        let Self {
+            red,
+            green,
+            blue,
+            alpha,
+        } = *self;
+        red.0 == TextureSwizzle::RED.0
+            && green.0 == TextureSwizzle::GREEN.0
+            && blue.0 == TextureSwizzle::BLUE.0
+            && alpha.0 == TextureSwizzle::ALPHA.0
     }
 }
 
@@ -202,6 +254,20 @@ mod tests {
         assert_eq!(std::mem::size_of::<TextureSwizzleChannels>(), 4);
     }
 
+    #[test]
+    fn test_texture_swizzle_channels_presets() {
+        assert!(TextureSwizzleChannels::default().is_identity());
+        assert_eq!(
+            TextureSwizzleChannels::default_channels(),
+            TextureSwizzleChannels::IDENTITY
+        );
+        assert!(!TextureSwizzleChannels::SWAP_RED_BLUE.is_identity());
+        assert_eq!(
+            { TextureSwizzleChannels::RED_AS_ALPHA.alpha },
+            TextureSwizzle::RED
+        );
+    }
+
     #[test]
     fn test_texture_usage_bitor() {
         let usage = TextureUsage::SHADER_READ | TextureUsage::RENDER_TARGET;
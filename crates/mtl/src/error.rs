@@ -24,6 +24,12 @@ pub enum ValidationError {
     /// The raster sample count is not supported by the device.
     UnsupportedRasterSampleCount(UInteger),
 
+    /// Mesh render pipeline descriptor is missing a required mesh function.
+    MissingMeshFunction,
+
+    /// Tile render pipeline descriptor is missing a required tile function.
+    MissingTileFunction,
+
     // =========================================================================
     // Compute Pipeline Errors
     // =========================================================================
@@ -89,6 +95,19 @@ impl fmt::Display for ValidationError {
                 )
             }
 
+            ValidationError::MissingMeshFunction => {
+                write!(
+                    f,
+                    "mesh render pipeline descriptor requires a mesh function"
+                )
+            }
+            ValidationError::MissingTileFunction => {
+                write!(
+                    f,
+                    "tile render pipeline descriptor requires a tile function"
+                )
+            }
+
             // Compute Pipeline
             ValidationError::MissingComputeFunction => {
                 write!(f, "compute pipeline descriptor requires a compute function")
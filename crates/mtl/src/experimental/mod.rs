@@ -0,0 +1,20 @@
+//! Unstable API surface.
+//!
+//! Enabled by the `experimental` feature. Everything under this module is
+//! exempt from semver: it can change or be removed in a minor release, so
+//! large subsystems can iterate without forcing a major version bump of
+//! the stable bindings.
+//!
+//! New subsystems land here first. Once an API has settled it moves to its
+//! permanent module, and the path here stays as a `#[deprecated]`
+//! re-export for one minor release so callers get a warning pointing at
+//! the new location instead of a build failure.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Cargo.toml: mtl-gpu = { version = "...", features = ["experimental"] }
+//! use mtl_gpu::experimental::text::{MsdfFont, TextRenderer};
+//! ```
+
+pub mod text;
@@ -0,0 +1,561 @@
+//! Text rendering with multi-channel signed distance field fonts.
+//!
+//! An MSDF atlas stores, for every glyph, three distance fields whose
+//! median is the distance to the glyph's outline, so text stays sharp with
+//! corners intact at any scale from one small texture. Atlases and glyph
+//! metrics come from an offline generator such as `msdf-atlas-gen`
+//! (`-type msdf -format rgba -yorigin top`); [`MsdfFont`] uploads the
+//! atlas and lays out strings with the metrics, and [`TextRenderer`] draws
+//! them with the shaders in [`MSDF_TEXT_SOURCE`].
+//!
+//! Positions are in pixels with the origin at the top left of the
+//! viewport. Strings are laid out left to right with `\n` starting a new
+//! line; there is no kerning, shaping or bidirectional text.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let font = MsdfFont::new(&device, &queue, &atlas_rgba, 512, 512, metrics, glyphs)?;
+//! let mut text = TextRenderer::new(&device, PixelFormat::BGRA8_UNORM)?;
+//!
+//! // In a render pass:
+//! text.set_viewport_size([width as f32, height as f32]);
+//! text.draw_text(&encoder, &font, "Frame 42\n16.6 ms", [8.0, 8.0], &TextStyle::new(18.0));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use crate::enums::{
+    BlendFactor, PixelFormat, PrimitiveType, ResourceOptions, StorageMode, TextureUsage,
+};
+use crate::error::ValidationError;
+use crate::texture::UploadError;
+use crate::{
+    CommandQueue, Device, RenderCommandEncoder, RenderPipelineDescriptor, RenderPipelineState,
+    Texture, TextureDescriptor,
+};
+
+/// Largest vertex data passed with `setVertexBytes` instead of a buffer.
+const MAX_INLINE_VERTEX_BYTES: usize = 4096;
+
+/// Vertex and fragment functions for MSDF text.
+///
+/// `msdf_text_vertex` reads `TextVertex` positions in pixels from buffer 0
+/// and `TextUniforms` from buffer 1; `msdf_text_fragment` samples the atlas
+/// at texture 0 with the same uniforms at fragment buffer 0.
+pub const MSDF_TEXT_SOURCE: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct TextVertex {
+    float2 position;
+    float2 uv;
+};
+
+struct TextUniforms {
+    float2 viewport_size;
+    float distance_range;
+    float padding;
+    float4 color;
+};
+
+struct TextFragmentIn {
+    float4 position [[position]];
+    float2 uv;
+};
+
+constexpr sampler msdf_sampler(coord::normalized, filter::linear, address::clamp_to_edge);
+
+vertex TextFragmentIn msdf_text_vertex(uint vid [[vertex_id]],
+                                       const device TextVertex* vertices [[buffer(0)]],
+                                       constant TextUniforms& uniforms [[buffer(1)]]) {
+    TextVertex v = vertices[vid];
+    float2 ndc = v.position / uniforms.viewport_size * 2.0 - 1.0;
+    TextFragmentIn out;
+    out.position = float4(ndc.x, -ndc.y, 0.0, 1.0);
+    out.uv = v.uv;
+    return out;
+}
+
+static float msdf_median(float3 d) {
+    return max(min(d.r, d.g), min(max(d.r, d.g), d.b));
+}
+
+fragment float4 msdf_text_fragment(TextFragmentIn in [[stage_in]],
+                                   texture2d<float> atlas [[texture(0)]],
+                                   constant TextUniforms& uniforms [[buffer(0)]]) {
+    float distance = msdf_median(atlas.sample(msdf_sampler, in.uv).rgb);
+    float2 unit_range = uniforms.distance_range / float2(atlas.get_width(), atlas.get_height());
+    float2 screen_texture_size = 1.0 / fwidth(in.uv);
+    float screen_range = max(0.5 * dot(unit_range, screen_texture_size), 1.0);
+    float opacity = saturate(screen_range * (distance - 0.5) + 0.5);
+    return float4(uniforms.color.rgb, uniforms.color.a * opacity);
+}
+"#;
+
+// ============================================================================
+// TextError
+// ============================================================================
+
+/// Error produced while creating fonts or the text renderer.
+#[derive(Debug)]
+pub enum TextError {
+    /// The text shaders failed to compile.
+    Library(mtl_foundation::Error),
+    /// The text render pipeline could not be created.
+    Pipeline(ValidationError),
+    /// The atlas could not be uploaded.
+    Upload(UploadError),
+    /// A texture, function or descriptor could not be created.
+    Allocation,
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextError::Library(err) => write!(f, "failed to compile text shaders: {}", err),
+            TextError::Pipeline(err) => write!(f, "failed to build text pipeline: {}", err),
+            TextError::Upload(err) => write!(f, "failed to upload font atlas: {}", err),
+            TextError::Allocation => write!(f, "failed to allocate text resources"),
+        }
+    }
+}
+
+impl std::error::Error for TextError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextError::Library(err) => Some(err),
+            TextError::Pipeline(err) => Some(err),
+            TextError::Upload(err) => Some(err),
+            TextError::Allocation => None,
+        }
+    }
+}
+
+// ============================================================================
+// MsdfFont
+// ============================================================================
+
+/// A rectangle given by its edges.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct GlyphBounds {
+    /// Left edge.
+    pub left: f32,
+    /// Bottom edge.
+    pub bottom: f32,
+    /// Right edge.
+    pub right: f32,
+    /// Top edge.
+    pub top: f32,
+}
+
+/// Metrics of one glyph in an MSDF atlas.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MsdfGlyph {
+    /// Horizontal advance in ems.
+    pub advance: f32,
+    /// The glyph's quad relative to the pen position on the baseline, in
+    /// ems with y up. `None` for glyphs with no outline, such as spaces.
+    pub plane_bounds: Option<GlyphBounds>,
+    /// The glyph's rectangle in the atlas, in pixels with y down.
+    pub atlas_bounds: Option<GlyphBounds>,
+}
+
+/// Font-wide metrics of an MSDF atlas.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MsdfFontMetrics {
+    /// Distance between baselines, in ems.
+    pub line_height: f32,
+    /// Height of the ascender above the baseline, in ems.
+    pub ascender: f32,
+    /// Width of the distance field in atlas pixels, as passed to the
+    /// generator's `-pxrange`.
+    pub distance_range: f32,
+}
+
+/// A vertex of a laid-out glyph quad; matches `TextVertex` in
+/// [`MSDF_TEXT_SOURCE`].
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TextVertex {
+    /// Position in pixels from the top left of the viewport.
+    pub position: [f32; 2],
+    /// Normalized atlas coordinate.
+    pub uv: [f32; 2],
+}
+
+/// An MSDF atlas on the GPU and the metrics to lay out text with it.
+pub struct MsdfFont {
+    atlas: Texture,
+    metrics: MsdfFontMetrics,
+    glyphs: HashMap<char, MsdfGlyph>,
+}
+
+impl MsdfFont {
+    /// Upload a `width` x `height` RGBA8 atlas and create a font with its
+    /// metrics.
+    ///
+    /// `pixels` holds tightly packed rows, top row first. Blocks until the
+    /// upload finishes.
+    pub fn new(
+        device: &Device,
+        queue: &CommandQueue,
+        pixels: &[u8],
+        width: UInteger,
+        height: UInteger,
+        metrics: MsdfFontMetrics,
+        glyphs: impl IntoIterator<Item = (char, MsdfGlyph)>,
+    ) -> Result<Self, TextError> {
+        let descriptor = TextureDescriptor::texture_2d_descriptor(
+            PixelFormat::RGBA8_UNORM,
+            width,
+            height,
+            false,
+        )
+        .ok_or(TextError::Allocation)?;
+        descriptor.set_storage_mode(StorageMode::PRIVATE);
+        descriptor.set_usage(TextureUsage::SHADER_READ);
+        let atlas = device
+            .new_texture_with_descriptor(&descriptor)
+            .map_err(|_| TextError::Allocation)?;
+        atlas
+            .upload_from_bytes(queue, pixels, width * 4)
+            .map_err(TextError::Upload)?;
+        Ok(Self {
+            atlas,
+            metrics,
+            glyphs: glyphs.into_iter().collect(),
+        })
+    }
+
+    /// Get the atlas texture.
+    pub fn atlas(&self) -> &Texture {
+        &self.atlas
+    }
+
+    /// Get the font-wide metrics.
+    pub fn metrics(&self) -> MsdfFontMetrics {
+        self.metrics
+    }
+
+    /// Get the metrics of `ch`.
+    pub fn glyph(&self, ch: char) -> Option<&MsdfGlyph> {
+        self.glyphs.get(&ch)
+    }
+
+    /// Get the width and height in pixels of `text` set at `size` pixels
+    /// per em.
+    pub fn measure(&self, text: &str, size: f32) -> [f32; 2] {
+        let mut width: f32 = 0.0;
+        let mut lines = 0;
+        for line in text.split('\n') {
+            let advance: f32 = line
+                .chars()
+                .filter_map(|ch| self.glyph(ch))
+                .map(|glyph| glyph.advance)
+                .sum();
+            width = width.max(advance * size);
+            lines += 1;
+        }
+        [width, lines as f32 * self.metrics.line_height * size]
+    }
+
+    /// Append two triangles per visible glyph of `text` to `vertices`.
+    ///
+    /// `position` is the top left of the first line in pixels and `size`
+    /// the font size in pixels per em. Characters missing from the font
+    /// are skipped.
+    pub fn layout(
+        &self,
+        text: &str,
+        position: [f32; 2],
+        size: f32,
+        vertices: &mut Vec<TextVertex>,
+    ) {
+        let [atlas_width, atlas_height] = [self.atlas.width() as f32, self.atlas.height() as f32];
+        let mut x = position[0];
+        let mut baseline = position[1] + self.metrics.ascender * size;
+        for ch in text.chars() {
+            if ch == '\n' {
+                x = position[0];
+                baseline += self.metrics.line_height * size;
+                continue;
+            }
+            let Some(glyph) = self.glyph(ch) else {
+                continue;
+            };
+            if let (Some(plane), Some(atlas)) = (glyph.plane_bounds, glyph.atlas_bounds) {
+                let left = x + plane.left * size;
+                let right = x + plane.right * size;
+                let top = baseline - plane.top * size;
+                let bottom = baseline - plane.bottom * size;
+                let u0 = atlas.left / atlas_width;
+                let u1 = atlas.right / atlas_width;
+                let v0 = atlas.top / atlas_height;
+                let v1 = atlas.bottom / atlas_height;
+                let corner = |position, uv| TextVertex { position, uv };
+                let top_left = corner([left, top], [u0, v0]);
+                let top_right = corner([right, top], [u1, v0]);
+                let bottom_left = corner([left, bottom], [u0, v1]);
+                let bottom_right = corner([right, bottom], [u1, v1]);
+                vertices.extend_from_slice(&[
+                    top_left,
+                    bottom_left,
+                    top_right,
+                    top_right,
+                    bottom_left,
+                    bottom_right,
+                ]);
+            }
+            x += glyph.advance * size;
+        }
+    }
+}
+
+impl fmt::Debug for MsdfFont {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MsdfFont")
+            .field("atlas", &self.atlas)
+            .field("metrics", &self.metrics)
+            .field("glyphs", &self.glyphs.len())
+            .finish()
+    }
+}
+
+// ============================================================================
+// TextRenderer
+// ============================================================================
+
+/// Size and color of drawn text.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextStyle {
+    /// Font size in pixels per em.
+    pub size: f32,
+    /// Straight-alpha RGBA color.
+    pub color: [f32; 4],
+}
+
+impl TextStyle {
+    /// White text of `size` pixels per em.
+    pub fn new(size: f32) -> Self {
+        Self {
+            size,
+            color: [1.0; 4],
+        }
+    }
+
+    /// Set the color.
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self::new(16.0)
+    }
+}
+
+/// Uniforms shared by the text shaders; matches `TextUniforms`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct TextUniforms {
+    viewport_size: [f32; 2],
+    distance_range: f32,
+    padding: f32,
+    color: [f32; 4],
+}
+
+/// Draws MSDF text into a render pass.
+pub struct TextRenderer {
+    device: Device,
+    pipeline: RenderPipelineState,
+    viewport_size: [f32; 2],
+    vertices: Vec<TextVertex>,
+}
+
+impl TextRenderer {
+    /// Create a renderer for passes whose color attachment 0 is in
+    /// `color_format`, blending text over it with straight alpha.
+    ///
+    /// Compiles the text shaders, so create it once rather than per frame.
+    pub fn new(device: &Device, color_format: PixelFormat) -> Result<Self, TextError> {
+        let library = device
+            .new_library_with_source(MSDF_TEXT_SOURCE, None)
+            .map_err(TextError::Library)?;
+        let function = |name: &str| {
+            library
+                .new_function_with_name(name)
+                .ok_or(TextError::Allocation)
+        };
+        let descriptor = RenderPipelineDescriptor::new().ok_or(TextError::Allocation)?;
+        descriptor.set_label("MSDF text");
+        descriptor.set_vertex_function(Some(&function("msdf_text_vertex")?));
+        descriptor.set_fragment_function(Some(&function("msdf_text_fragment")?));
+        let color = descriptor
+            .color_attachments()
+            .object(0)
+            .ok_or(TextError::Allocation)?;
+        color.set_pixel_format(color_format);
+        color.set_blending_enabled(true);
+        color.set_source_rgb_blend_factor(BlendFactor::SOURCE_ALPHA);
+        color.set_destination_rgb_blend_factor(BlendFactor::ONE_MINUS_SOURCE_ALPHA);
+        color.set_source_alpha_blend_factor(BlendFactor::ONE);
+        color.set_destination_alpha_blend_factor(BlendFactor::ONE_MINUS_SOURCE_ALPHA);
+        let pipeline = device
+            .new_render_pipeline_state_with_descriptor(&descriptor)
+            .map_err(TextError::Pipeline)?;
+
+        Ok(Self {
+            device: device.clone(),
+            pipeline,
+            viewport_size: [1.0, 1.0],
+            vertices: Vec::new(),
+        })
+    }
+
+    /// Get the viewport size in pixels that positions are relative to.
+    pub fn viewport_size(&self) -> [f32; 2] {
+        self.viewport_size
+    }
+
+    /// Set the viewport size in pixels; call when the render target is
+    /// resized.
+    pub fn set_viewport_size(&mut self, size: [f32; 2]) {
+        self.viewport_size = [size[0].max(1.0), size[1].max(1.0)];
+    }
+
+    /// Draw `text` with its first line's top left at `position` pixels.
+    ///
+    /// Binds the text pipeline, vertex buffers 0 and 1, fragment buffer 0
+    /// and fragment texture 0 on `encoder`; rebind your own state
+    /// afterwards.
+    pub fn draw_text(
+        &mut self,
+        encoder: &RenderCommandEncoder,
+        font: &MsdfFont,
+        text: &str,
+        position: [f32; 2],
+        style: &TextStyle,
+    ) {
+        self.vertices.clear();
+        font.layout(text, position, style.size, &mut self.vertices);
+        if self.vertices.is_empty() {
+            return;
+        }
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                self.vertices.as_ptr() as *const u8,
+                std::mem::size_of_val(self.vertices.as_slice()),
+            )
+        };
+        if bytes.len() <= MAX_INLINE_VERTEX_BYTES {
+            encoder.set_vertex_bytes(bytes, 0);
+        } else if let Some(buffer) = self
+            .device
+            .new_buffer_with_bytes(bytes, ResourceOptions::STORAGE_MODE_SHARED)
+        {
+            encoder.set_vertex_buffer(&buffer, 0, 0);
+        } else {
+            return;
+        }
+
+        let uniforms = TextUniforms {
+            viewport_size: self.viewport_size,
+            distance_range: font.metrics().distance_range,
+            padding: 0.0,
+            color: style.color,
+        };
+        encoder.set_render_pipeline_state(&self.pipeline);
+        encoder.set_vertex_value(&uniforms, 1);
+        encoder.set_fragment_value(&uniforms, 0);
+        encoder.set_fragment_texture(font.atlas(), 0);
+        encoder.draw_primitives(PrimitiveType::TRIANGLE, 0, self.vertices.len());
+    }
+}
+
+impl fmt::Debug for TextRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextRenderer")
+            .field("viewport_size", &self.viewport_size)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_font() -> MsdfFont {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().unwrap();
+        let metrics = MsdfFontMetrics {
+            line_height: 1.25,
+            ascender: 1.0,
+            distance_range: 4.0,
+        };
+        let glyphs = [
+            (
+                'A',
+                MsdfGlyph {
+                    advance: 0.5,
+                    plane_bounds: Some(GlyphBounds {
+                        left: 0.0,
+                        bottom: 0.0,
+                        right: 0.5,
+                        top: 1.0,
+                    }),
+                    atlas_bounds: Some(GlyphBounds {
+                        left: 0.0,
+                        bottom: 16.0,
+                        right: 8.0,
+                        top: 0.0,
+                    }),
+                },
+            ),
+            (
+                ' ',
+                MsdfGlyph {
+                    advance: 0.25,
+                    ..MsdfGlyph::default()
+                },
+            ),
+        ];
+        MsdfFont::new(
+            &device,
+            &queue,
+            &[255; 16 * 16 * 4],
+            16,
+            16,
+            metrics,
+            glyphs,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_measure_and_layout() {
+        let font = test_font();
+        assert_eq!(font.measure("A A\nA", 20.0), [25.0, 50.0]);
+
+        let mut vertices = Vec::new();
+        font.layout("A A?", [10.0, 5.0], 20.0, &mut vertices);
+        assert_eq!(vertices.len(), 12);
+        assert_eq!(vertices[0].position, [10.0, 5.0]);
+        assert_eq!(vertices[5].position, [20.0, 25.0]);
+        assert_eq!(vertices[5].uv, [0.5, 1.0]);
+        assert_eq!(vertices[6].position, [25.0, 5.0]);
+    }
+
+    #[test]
+    fn test_text_renderer_pipeline() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let mut renderer = TextRenderer::new(&device, PixelFormat::BGRA8_UNORM).unwrap();
+        renderer.set_viewport_size([0.0, 720.0]);
+        assert_eq!(renderer.viewport_size(), [1.0, 720.0]);
+    }
+}
@@ -8,7 +8,7 @@
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
-use mtl_foundation::{Referencing, UInteger};
+use mtl_foundation::{FastEnumerationState, Referencing, UInteger};
 use mtl_sys::{msg_send_0, sel};
 
 use crate::enums::FunctionLogType;
@@ -40,6 +40,38 @@ impl LogContainer {
     pub fn as_raw(&self) -> *mut c_void {
         self.0.as_ptr()
     }
+
+    /// Get the log entries.
+    ///
+    /// C++ equivalent: `NS::UInteger countByEnumerating(NS::FastEnumerationState*, NS::Object**, NS::UInteger)`
+    pub fn logs(&self) -> Vec<FunctionLog> {
+        const BATCH: usize = 16;
+        let mut state = FastEnumerationState::default();
+        let mut buffer = [std::ptr::null_mut::<c_void>(); BATCH];
+        let mut logs = Vec::new();
+        loop {
+            let count: UInteger = unsafe {
+                mtl_sys::msg_send_3(
+                    self.as_ptr(),
+                    sel!(countByEnumeratingWithState: objects: count:),
+                    &mut state as *mut FastEnumerationState,
+                    buffer.as_mut_ptr(),
+                    BATCH as UInteger,
+                )
+            };
+            if count == 0 {
+                return logs;
+            }
+            let items = state.items_ptr as *const *mut c_void;
+            for index in 0..count {
+                unsafe {
+                    let ptr = *items.add(index);
+                    let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+                    logs.extend(FunctionLog::from_raw(ptr));
+                }
+            }
+        }
+    }
 }
 
 impl Clone for LogContainer {
@@ -291,6 +323,15 @@ impl Referencing for FunctionLog {
 unsafe impl Send for FunctionLog {}
 unsafe impl Sync for FunctionLog {}
 
+impl std::fmt::Debug for FunctionLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FunctionLog")
+            .field("log_type", &self.log_type())
+            .field("encoder_label", &self.encoder_label())
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
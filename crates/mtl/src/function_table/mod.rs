@@ -14,6 +14,7 @@ use mtl_sys::{Class, msg_send_0, msg_send_1, msg_send_2, msg_send_3, sel};
 
 use crate::Buffer;
 use crate::enums::{FunctionType, IntersectionFunctionSignature};
+use crate::object_ptrs::with_object_ptrs;
 use crate::types::ResourceID;
 
 // ============================================================================
@@ -324,15 +325,14 @@ impl VisibleFunctionTable {
     ///
     /// C++ equivalent: `void setFunctions(const MTL::FunctionHandle* const functions[], NS::Range range)`
     pub fn set_functions(&self, functions: &[&FunctionHandle], range: Range) {
-        let ptrs: Vec<*const c_void> = functions.iter().map(|f| f.as_ptr()).collect();
-        unsafe {
+        with_object_ptrs(functions, |ptrs| unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(setFunctions:withRange:),
                 ptrs.as_ptr(),
                 range,
             );
-        }
+        })
     }
 }
 
@@ -550,8 +550,7 @@ impl IntersectionFunctionTable {
     ///
     /// C++ equivalent: `void setBuffers(const MTL::Buffer* const buffers[], const NS::UInteger offsets[], NS::Range range)`
     pub fn set_buffers(&self, buffers: &[&Buffer], offsets: &[UInteger], range: Range) {
-        let ptrs: Vec<*const c_void> = buffers.iter().map(|b| b.as_ptr()).collect();
-        unsafe {
+        with_object_ptrs(buffers, |ptrs| unsafe {
             let _: () = msg_send_3(
                 self.as_ptr(),
                 sel!(setBuffers:offsets:withRange:),
@@ -559,7 +558,7 @@ impl IntersectionFunctionTable {
                 offsets.as_ptr(),
                 range,
             );
-        }
+        })
     }
 
     // =========================================================================
@@ -584,15 +583,14 @@ impl IntersectionFunctionTable {
     ///
     /// C++ equivalent: `void setFunctions(const MTL::FunctionHandle* const functions[], NS::Range range)`
     pub fn set_functions(&self, functions: &[&FunctionHandle], range: Range) {
-        let ptrs: Vec<*const c_void> = functions.iter().map(|f| f.as_ptr()).collect();
-        unsafe {
+        with_object_ptrs(functions, |ptrs| unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(setFunctions:withRange:),
                 ptrs.as_ptr(),
                 range,
             );
-        }
+        })
     }
 
     // =========================================================================
@@ -701,15 +699,14 @@ impl IntersectionFunctionTable {
         function_tables: &[&VisibleFunctionTable],
         buffer_range: Range,
     ) {
-        let ptrs: Vec<*const c_void> = function_tables.iter().map(|t| t.as_ptr()).collect();
-        unsafe {
+        with_object_ptrs(function_tables, |ptrs| unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(setVisibleFunctionTables:withBufferRange:),
                 ptrs.as_ptr(),
                 buffer_range,
             );
-        }
+        })
     }
 }
 
@@ -0,0 +1,450 @@
+//! A growable, CPU-writable GPU buffer.
+//!
+//! [`GpuVec`] keeps a typed length and capacity over a [`Buffer`]. When a
+//! push doesn't fit, it allocates a buffer with at least twice the capacity
+//! and encodes a blit of the existing contents into the new buffer on the
+//! caller's command buffer, so the copy is ordered with the rest of the
+//! frame's GPU work and picks up anything the GPU wrote to the old buffer.
+//!
+//! Until that blit has run, CPU writes into the copied elements would be
+//! overwritten by it. While the command buffer is still being encoded,
+//! such writes also go to the blit's source buffers, so the blit carries
+//! them over; once it is committed, they wait for it to complete.
+//!
+//! The replaced buffer may still be read by command buffers in flight. It
+//! is retired with the current epoch (a frame index or a [`SharedEvent`]
+//! value set with [`GpuVec::set_epoch`]) and released by
+//! [`GpuVec::collect`] once the GPU has passed that epoch.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut instances: GpuVec<[f32; 4]> = GpuVec::new(&device, ResourceOptions::STORAGE_MODE_SHARED)?;
+//!
+//! // Each frame:
+//! instances.collect(completed_frame);
+//! instances.set_epoch(frame);
+//! instances.clear();
+//! instances.extend_from_slice(&command_buffer, &visible_instances)?;
+//! encoder.set_vertex_buffer(instances.buffer(), 0, 1);
+//! ```
+//!
+//! [`SharedEvent`]: crate::SharedEvent
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use mtl_foundation::{Referencing, UInteger};
+
+use crate::enums::{CommandBufferStatus, ResourceOptions};
+use crate::{BlitCommandEncoder, Buffer, BufferView, CommandBuffer, Device};
+
+/// Smallest capacity, in elements, a [`GpuVec`] allocates.
+pub const MIN_CAPACITY: usize = 16;
+
+// ============================================================================
+// Pod
+// ============================================================================
+
+/// Plain-old-data types that can be copied into and out of GPU buffers.
+///
+/// # Safety
+///
+/// Implementors must be `repr(C)` or `repr(transparent)` (or primitives),
+/// contain no padding, pointers or references, and be valid for every bit
+/// pattern, since the GPU may write arbitrary bytes into the buffer.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),* $(,)?) => {
+        $(unsafe impl Pod for $ty {})*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+impl_pod!(
+    crate::types::PackedFloat3,
+    crate::types::PackedFloat4x3,
+    crate::types::PackedFloatQuaternion,
+    crate::types::AxisAlignedBoundingBox,
+    crate::types::DrawPrimitivesIndirectArguments,
+    crate::types::DrawIndexedPrimitivesIndirectArguments,
+    crate::types::DrawPatchIndirectArguments,
+    crate::DispatchThreadgroupsIndirectArguments,
+    crate::DispatchThreadsIndirectArguments,
+);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+// ============================================================================
+// GpuVecError
+// ============================================================================
+
+/// Error produced by a [`GpuVec`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GpuVecError {
+    /// A buffer or blit encoder could not be created.
+    Allocation,
+    /// The resource options select a storage mode the CPU can't write.
+    NotCpuAccessible,
+    /// The requested capacity overflows `usize` bytes.
+    CapacityOverflow,
+    /// The vector grew on another command buffer that hasn't been
+    /// committed yet, so the two blits can't be ordered.
+    GrowthPending,
+}
+
+impl fmt::Display for GpuVecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuVecError::Allocation => write!(f, "failed to allocate GPU buffer"),
+            GpuVecError::NotCpuAccessible => {
+                write!(f, "GpuVec requires shared or managed storage")
+            }
+            GpuVecError::CapacityOverflow => write!(f, "GpuVec capacity overflow"),
+            GpuVecError::GrowthPending => {
+                write!(f, "GpuVec growth pending on an uncommitted command buffer")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GpuVecError {}
+
+// ============================================================================
+// GpuVec
+// ============================================================================
+
+/// A growable array of `T` stored in a GPU buffer.
+///
+/// Writes go through the buffer's CPU mapping. As with any shared buffer,
+/// don't overwrite elements a command buffer in flight is still reading;
+/// clear and refill a `GpuVec` only after the frame that used it completed,
+/// or keep one per frame in flight.
+pub struct GpuVec<T: Pod> {
+    device: Device,
+    buffer: Buffer,
+    options: ResourceOptions,
+    len: usize,
+    capacity: usize,
+    epoch: u64,
+    retired: Vec<(u64, Buffer)>,
+    growth: Option<Growth>,
+    _marker: PhantomData<T>,
+}
+
+/// Blits encoded by growth that may not have run yet.
+struct Growth {
+    command_buffer: CommandBuffer,
+    /// Each blit's source buffer and the number of elements it copies, in
+    /// encoding order.
+    sources: Vec<(Buffer, usize)>,
+}
+
+impl<T: Pod> GpuVec<T> {
+    /// Create an empty vector with [`MIN_CAPACITY`] elements of storage.
+    pub fn new(device: &Device, options: ResourceOptions) -> Result<Self, GpuVecError> {
+        Self::with_capacity(device, MIN_CAPACITY, options)
+    }
+
+    /// Create an empty vector with room for at least `capacity` elements.
+    ///
+    /// `options` must select shared or managed storage.
+    pub fn with_capacity(
+        device: &Device,
+        capacity: usize,
+        options: ResourceOptions,
+    ) -> Result<Self, GpuVecError> {
+        let capacity = capacity.max(MIN_CAPACITY);
+        let buffer = allocate::<T>(device, capacity, options)?;
+        if buffer.contents().is_none() {
+            return Err(GpuVecError::NotCpuAccessible);
+        }
+        Ok(Self {
+            device: device.clone(),
+            buffer,
+            options,
+            len: 0,
+            capacity,
+            epoch: 0,
+            retired: Vec::new(),
+            growth: None,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Get the current buffer.
+    ///
+    /// The buffer changes when the vector grows, so bind it after the last
+    /// push of the frame.
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Get the number of elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the vector has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the number of elements the current buffer holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the length of the elements in bytes.
+    pub fn byte_len(&self) -> UInteger {
+        self.len * size_of::<T>()
+    }
+
+    /// Get the resource options used for every allocation.
+    pub fn resource_options(&self) -> ResourceOptions {
+        self.options
+    }
+
+    /// Get a CPU view of the elements.
+    ///
+    /// After a growth, the elements copied by the blit are stale until the
+    /// command buffer that encoded it completes.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(self.contents(), self.len) }
+    }
+
+    /// Append one element, growing the buffer on `command_buffer` if needed.
+    pub fn push(&mut self, command_buffer: &CommandBuffer, value: T) -> Result<(), GpuVecError> {
+        self.extend_from_slice(command_buffer, std::slice::from_ref(&value))
+    }
+
+    /// Append `values`, growing the buffer on `command_buffer` if needed.
+    pub fn extend_from_slice(
+        &mut self,
+        command_buffer: &CommandBuffer,
+        values: &[T],
+    ) -> Result<(), GpuVecError> {
+        self.reserve(command_buffer, values.len())?;
+        self.store(self.len, values);
+        self.len += values.len();
+        Ok(())
+    }
+
+    /// Overwrite the elements starting at `index` with `values`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range extends past [`len`](Self::len).
+    pub fn write(&mut self, index: usize, values: &[T]) {
+        assert!(
+            index
+                .checked_add(values.len())
+                .is_some_and(|end| end <= self.len),
+            "write out of bounds"
+        );
+        self.store(index, values);
+    }
+
+    /// Ensure room for `additional` more elements.
+    ///
+    /// If the buffer is too small, allocates a larger one, encodes a blit
+    /// of the current elements into it on `command_buffer`, and retires the
+    /// old buffer with the current epoch.
+    ///
+    /// Growing again on a different command buffer waits for the previous
+    /// one if it is committed, and fails with
+    /// [`GpuVecError::GrowthPending`] if it isn't.
+    pub fn reserve(
+        &mut self,
+        command_buffer: &CommandBuffer,
+        additional: usize,
+    ) -> Result<(), GpuVecError> {
+        let required = self
+            .len
+            .checked_add(additional)
+            .ok_or(GpuVecError::CapacityOverflow)?;
+        if required <= self.capacity {
+            return Ok(());
+        }
+
+        self.settle_growth();
+        if let Some(growth) = &self.growth
+            && growth.command_buffer.as_ptr() != command_buffer.as_ptr()
+        {
+            return Err(GpuVecError::GrowthPending);
+        }
+
+        let capacity = grown_capacity(self.capacity, required);
+        let buffer = allocate::<T>(&self.device, capacity, self.options)?;
+        if self.len > 0 {
+            let blit =
+                unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+                    .ok_or(GpuVecError::Allocation)?;
+            blit.copy_from_buffer_to_buffer(&self.buffer, 0, &buffer, 0, self.byte_len());
+            blit.end_encoding();
+            self.growth
+                .get_or_insert_with(|| Growth {
+                    command_buffer: command_buffer.clone(),
+                    sources: Vec::new(),
+                })
+                .sources
+                .push((self.buffer.clone(), self.len));
+        }
+
+        let old = std::mem::replace(&mut self.buffer, buffer);
+        self.retired.push((self.epoch, old));
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    /// Shorten the vector to `len` elements. Has no effect if it's shorter.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Remove all elements, keeping the buffer.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Get the epoch buffers replaced by growth are retired with.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Set the epoch buffers replaced by growth are retired with.
+    ///
+    /// Typically the index of the frame being encoded.
+    pub fn set_epoch(&mut self, epoch: u64) {
+        self.epoch = epoch;
+    }
+
+    /// Release retired buffers whose epoch is `<= completed`.
+    ///
+    /// Returns the number of buffers released.
+    pub fn collect(&mut self, completed: u64) -> usize {
+        let before = self.retired.len();
+        self.retired.retain(|(epoch, _)| *epoch > completed);
+        before - self.retired.len()
+    }
+
+    /// Get the number of retired buffers waiting for [`collect`](Self::collect).
+    pub fn retired_count(&self) -> usize {
+        self.retired.len()
+    }
+
+    fn contents(&self) -> *mut T {
+        // Checked to be CPU-accessible when the first buffer was created.
+        self.buffer.contents().unwrap() as *mut T
+    }
+
+    /// Forget growth blits that have run, waiting for them if their
+    /// command buffer is committed.
+    fn settle_growth(&mut self) {
+        let Some(growth) = &self.growth else {
+            return;
+        };
+        let status = growth.command_buffer.status();
+        if status == CommandBufferStatus::NOT_ENQUEUED || status == CommandBufferStatus::ENQUEUED {
+            return;
+        }
+        if status == CommandBufferStatus::COMMITTED || status == CommandBufferStatus::SCHEDULED {
+            growth.command_buffer.wait_until_completed();
+        }
+        self.growth = None;
+    }
+
+    /// Copy `values` to `index`, keeping them ahead of pending growth blits.
+    fn store(&mut self, index: usize, values: &[T]) {
+        self.settle_growth();
+        if let Some(growth) = &self.growth {
+            // The command buffer hasn't been committed, so no blit has run:
+            // writing each source makes every blit copy the new values.
+            for (source, copied) in &growth.sources {
+                let count = copied.saturating_sub(index).min(values.len());
+                if count > 0 {
+                    write_elements(source, self.options, index, &values[..count]);
+                }
+            }
+        }
+        write_elements(&self.buffer, self.options, index, values);
+    }
+}
+
+impl<T: Pod> crate::buffer::sealed::Sealed for GpuVec<T> {}
+
+impl<T: Pod> BufferView for GpuVec<T> {
+    fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    fn offset(&self) -> UInteger {
+        0
+    }
+
+    /// Get the length of the elements in bytes, not of the buffer.
+    fn length(&self) -> UInteger {
+        self.len * size_of::<T>()
+    }
+}
+
+impl<T: Pod> fmt::Debug for GpuVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpuVec")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity)
+            .field("epoch", &self.epoch)
+            .field("retired", &self.retired.len())
+            .finish()
+    }
+}
+
+/// Capacity to grow to: at least double the current one.
+fn grown_capacity(current: usize, required: usize) -> usize {
+    required.max(current.saturating_mul(2)).max(MIN_CAPACITY)
+}
+
+/// Copy `values` into `buffer` at element `index` through its CPU mapping.
+fn write_elements<T: Pod>(buffer: &Buffer, options: ResourceOptions, index: usize, values: &[T]) {
+    // Every buffer is allocated with the same CPU-accessible options.
+    let contents = buffer.contents().unwrap() as *mut T;
+    unsafe {
+        std::ptr::copy_nonoverlapping(values.as_ptr(), contents.add(index), values.len());
+    }
+    let storage = options.bits() & (0xf << 4);
+    if storage == ResourceOptions::STORAGE_MODE_MANAGED.bits() && !values.is_empty() {
+        buffer.did_modify_range(index * size_of::<T>(), size_of_val(values));
+    }
+}
+
+fn allocate<T>(
+    device: &Device,
+    capacity: usize,
+    options: ResourceOptions,
+) -> Result<Buffer, GpuVecError> {
+    let length = capacity
+        .checked_mul(size_of::<T>())
+        .ok_or(GpuVecError::CapacityOverflow)?;
+    device
+        .new_buffer(length.max(1), options)
+        .ok_or(GpuVecError::Allocation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grown_capacity_doubles() {
+        assert_eq!(grown_capacity(16, 17), 32);
+        assert_eq!(grown_capacity(32, 33), 64);
+    }
+
+    #[test]
+    fn test_grown_capacity_covers_large_requests() {
+        assert_eq!(grown_capacity(16, 100), 100);
+        assert_eq!(grown_capacity(0, 1), MIN_CAPACITY);
+        assert_eq!(grown_capacity(usize::MAX / 2 + 1, usize::MAX), usize::MAX);
+    }
+}
@@ -0,0 +1,269 @@
+//! Tracking of resources that share heap memory.
+//!
+//! Transient render targets in a frame graph live for a few passes each,
+//! so resources whose lifetimes don't overlap can occupy the same heap
+//! memory. Metal lets them alias in two ways: placing resources at
+//! explicit, overlapping offsets in a placement heap, or calling
+//! `makeAliasable` so an automatic heap may reuse a resource's memory for
+//! later allocations. Either way Metal doesn't report which resources
+//! ended up sharing memory. [`AliasGroup`] records each resource's byte
+//! range in its heap, answers which resources alias each other, and flags
+//! overlapping resources that are both still in use.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut group = AliasGroup::new(placement_heap);
+//! let (gbuffer, gbuffer_id) = group.place_texture(&gbuffer_desc, 0).unwrap();
+//! let (bloom, bloom_id) = group.place_texture(&bloom_desc, 0).unwrap();
+//!
+//! // Passes writing `bloom` must wait on a fence signalled after the last
+//! // pass reading any of its aliases.
+//! for alias in group.aliases(bloom_id) {
+//!     // ...
+//! }
+//! group.make_aliasable(gbuffer_id);
+//! assert!(group.conflicts().is_empty());
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+use mtl_foundation::{Referencing, UInteger};
+
+use super::Heap;
+use crate::buffer::Buffer;
+use crate::enums::ResourceOptions;
+use crate::resource::Resource;
+use crate::texture::{Texture, TextureDescriptor};
+
+/// Identifies a resource tracked by an [`AliasGroup`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AliasId(u64);
+
+struct AliasEntry {
+    id: AliasId,
+    resource: Box<dyn Resource + Send + Sync>,
+    range: Range<UInteger>,
+    aliasable: bool,
+}
+
+/// The resources allocated from one heap and the memory they share.
+pub struct AliasGroup {
+    heap: Heap,
+    entries: Vec<AliasEntry>,
+    next_id: u64,
+}
+
+impl AliasGroup {
+    /// Track resources allocated from `heap`.
+    pub fn new(heap: Heap) -> Self {
+        Self {
+            heap,
+            entries: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Get the heap.
+    pub fn heap(&self) -> &Heap {
+        &self.heap
+    }
+
+    /// Start tracking `resource`, which must have been allocated from the
+    /// group's heap.
+    ///
+    /// Returns `None` if the resource belongs to a different heap or none.
+    pub fn insert<R>(&mut self, resource: &R) -> Option<AliasId>
+    where
+        R: Resource + Clone + Send + Sync + 'static,
+    {
+        if resource.heap()?.as_raw() != self.heap.as_raw() {
+            return None;
+        }
+        let offset = resource.heap_offset();
+        let id = AliasId(self.next_id);
+        self.next_id += 1;
+        self.entries.push(AliasEntry {
+            id,
+            resource: Box::new(resource.clone()),
+            range: offset..offset + resource.allocated_size(),
+            aliasable: resource.is_aliasable(),
+        });
+        Some(id)
+    }
+
+    /// Allocate a buffer at byte `offset` of a placement heap and track it.
+    pub fn place_buffer(
+        &mut self,
+        length: UInteger,
+        options: ResourceOptions,
+        offset: UInteger,
+    ) -> Option<(Buffer, AliasId)> {
+        let buffer = self.heap.new_buffer_with_offset(length, options, offset)?;
+        let id = self.insert(&buffer)?;
+        Some((buffer, id))
+    }
+
+    /// Allocate a texture at byte `offset` of a placement heap and track it.
+    pub fn place_texture(
+        &mut self,
+        descriptor: &TextureDescriptor,
+        offset: UInteger,
+    ) -> Option<(Texture, AliasId)> {
+        let texture = unsafe {
+            self.heap
+                .new_texture_with_offset(descriptor.as_ptr(), offset)?
+        };
+        let id = self.insert(&texture)?;
+        Some((texture, id))
+    }
+
+    /// Stop tracking a resource.
+    pub fn remove(&mut self, id: AliasId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.len() != len
+    }
+
+    /// Get the number of tracked resources.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no resources are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the byte range a resource occupies in the heap.
+    pub fn range(&self, id: AliasId) -> Option<Range<UInteger>> {
+        self.entry(id).map(|entry| entry.range.clone())
+    }
+
+    /// Get the tracked resource.
+    pub fn resource(&self, id: AliasId) -> Option<&dyn Resource> {
+        self.entry(id)
+            .map(|entry| &*entry.resource as &dyn Resource)
+    }
+
+    /// Mark a resource's memory as reusable and call `makeAliasable` on it.
+    ///
+    /// Its contents are undefined from then on. The resource stays tracked
+    /// so later allocations over its range still report it as an alias.
+    pub fn make_aliasable(&mut self, id: AliasId) -> bool {
+        let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) else {
+            return false;
+        };
+        entry.resource.make_aliasable();
+        entry.aliasable = true;
+        true
+    }
+
+    /// Check if a resource's memory has been marked reusable.
+    pub fn is_aliasable(&self, id: AliasId) -> bool {
+        self.entry(id).is_some_and(|entry| entry.aliasable)
+    }
+
+    /// Check if two resources share any heap memory.
+    pub fn overlaps(&self, a: AliasId, b: AliasId) -> bool {
+        match (self.entry(a), self.entry(b)) {
+            (Some(a), Some(b)) => a.id != b.id && ranges_overlap(&a.range, &b.range),
+            _ => false,
+        }
+    }
+
+    /// Get the other resources that share heap memory with `id`.
+    pub fn aliases(&self, id: AliasId) -> Vec<AliasId> {
+        let Some(range) = self.range(id) else {
+            return Vec::new();
+        };
+        self.entries
+            .iter()
+            .filter(|entry| entry.id != id && ranges_overlap(&entry.range, &range))
+            .map(|entry| entry.id)
+            .collect()
+    }
+
+    /// Get the resources overlapping `range` of the heap.
+    pub fn resources_in(&self, range: Range<UInteger>) -> Vec<AliasId> {
+        self.entries
+            .iter()
+            .filter(|entry| ranges_overlap(&entry.range, &range))
+            .map(|entry| entry.id)
+            .collect()
+    }
+
+    /// Get the pairs of overlapping resources that are both still in use,
+    /// that is neither has been made aliasable.
+    ///
+    /// A non-empty result means one of each pair will read the other's
+    /// data.
+    pub fn conflicts(&self) -> Vec<(AliasId, AliasId)> {
+        let live: Vec<&AliasEntry> = self.entries.iter().filter(|e| !e.aliasable).collect();
+        let mut conflicts = Vec::new();
+        for (i, a) in live.iter().enumerate() {
+            for b in &live[i + 1..] {
+                if ranges_overlap(&a.range, &b.range) {
+                    conflicts.push((a.id, b.id));
+                }
+            }
+        }
+        conflicts
+    }
+
+    fn entry(&self, id: AliasId) -> Option<&AliasEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+}
+
+impl fmt::Debug for AliasGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AliasGroup")
+            .field("heap", &self.heap)
+            .field("resources", &self.entries.len())
+            .finish()
+    }
+}
+
+fn ranges_overlap(a: &Range<UInteger>, b: &Range<UInteger>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::{HeapType, StorageMode};
+    use crate::heap::HeapDescriptor;
+
+    #[test]
+    fn test_alias_group_placement() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let descriptor = HeapDescriptor::new().unwrap();
+        descriptor.set_heap_type(HeapType::PLACEMENT);
+        descriptor.set_storage_mode(StorageMode::PRIVATE);
+        descriptor.set_size(1 << 20);
+        let heap = device.new_heap(&descriptor).unwrap();
+
+        let mut group = AliasGroup::new(heap);
+        let options = ResourceOptions::STORAGE_MODE_PRIVATE;
+        let (_, a) = group.place_buffer(4096, options, 0).unwrap();
+        let (_, b) = group.place_buffer(4096, options, 0).unwrap();
+        let (_, c) = group.place_buffer(4096, options, 1 << 16).unwrap();
+
+        assert!(group.overlaps(a, b));
+        assert!(!group.overlaps(a, c));
+        assert_eq!(group.aliases(a), [b]);
+        assert_eq!(group.conflicts(), [(a, b)]);
+
+        assert!(group.make_aliasable(a));
+        assert!(group.is_aliasable(a));
+        assert!(group.conflicts().is_empty());
+        assert_eq!(group.aliases(b), [a]);
+
+        let unrelated = device.new_buffer(16, options).unwrap();
+        assert!(group.insert(&unrelated).is_none());
+        assert!(group.remove(a));
+        assert!(group.aliases(b).is_empty());
+    }
+}
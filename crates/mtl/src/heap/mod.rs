@@ -3,6 +3,7 @@
 //! Corresponds to `Metal/MTLHeap.hpp`.
 //!
 //! Heaps allow you to allocate multiple resources from a single memory allocation.
+//! [`AliasGroup`] tracks which resources in a heap share memory.
 
 use std::ffi::c_void;
 use std::ptr::NonNull;
@@ -15,6 +16,10 @@ use crate::enums::{
     StorageMode,
 };
 
+mod alias;
+
+pub use alias::{AliasGroup, AliasId};
+
 /// A memory pool from which resources can be allocated.
 ///
 /// C++ equivalent: `MTL::Heap`
@@ -0,0 +1,297 @@
+//! Builder for indirect command buffers and typed access to their commands.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let icb = IndirectCommandBufferBuilder::new(1024)
+//!     .unwrap()
+//!     .command_types(IndirectCommandType::DRAW_INDEXED)
+//!     .inherit_pipeline_state(true)
+//!     .max_vertex_buffer_bind_count(2)
+//!     .max_fragment_buffer_bind_count(1)
+//!     .build(&device)?;
+//!
+//! for (command, mesh) in icb.render_commands().zip(&meshes) {
+//!     command.set_vertex_buffer(&mesh.vertices, 0, 0);
+//!     command.draw_indexed_primitives(/* ... */);
+//! }
+//!
+//! // Next frame, before re-encoding the first `visible` commands:
+//! icb.reset_range(0..visible);
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+use mtl_foundation::UInteger;
+
+use super::{
+    IndirectCommandBuffer, IndirectCommandBufferDescriptor, IndirectCommandBufferExecutionRange,
+    IndirectComputeCommand, IndirectRenderCommand,
+};
+use crate::Device;
+use crate::enums::{IndirectCommandType, ResourceOptions};
+
+/// Command types encoded by an `IndirectRenderCommand`.
+const RENDER_COMMAND_TYPES: IndirectCommandType = IndirectCommandType(
+    IndirectCommandType::DRAW.0
+        | IndirectCommandType::DRAW_INDEXED.0
+        | IndirectCommandType::DRAW_PATCHES.0
+        | IndirectCommandType::DRAW_INDEXED_PATCHES.0
+        | IndirectCommandType::DRAW_MESH_THREADGROUPS.0
+        | IndirectCommandType::DRAW_MESH_THREADS.0,
+);
+
+/// Command types encoded by an `IndirectComputeCommand`.
+const COMPUTE_COMMAND_TYPES: IndirectCommandType = IndirectCommandType(
+    IndirectCommandType::CONCURRENT_DISPATCH.0 | IndirectCommandType::CONCURRENT_DISPATCH_THREADS.0,
+);
+
+/// Error produced by [`IndirectCommandBufferBuilder::build`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IndirectCommandBufferError {
+    /// No command types were set.
+    NoCommandTypes,
+    /// Render and compute command types were both set; an indirect command
+    /// buffer holds one or the other.
+    MixedCommandTypes,
+    /// The device could not create the indirect command buffer.
+    Allocation,
+}
+
+impl fmt::Display for IndirectCommandBufferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndirectCommandBufferError::NoCommandTypes => {
+                write!(f, "indirect command buffer has no command types")
+            }
+            IndirectCommandBufferError::MixedCommandTypes => write!(
+                f,
+                "indirect command buffer cannot mix render and compute command types"
+            ),
+            IndirectCommandBufferError::Allocation => {
+                write!(f, "failed to create indirect command buffer")
+            }
+        }
+    }
+}
+
+impl std::error::Error for IndirectCommandBufferError {}
+
+// ============================================================================
+// IndirectCommandBufferBuilder
+// ============================================================================
+
+/// Configures and creates an [`IndirectCommandBuffer`].
+///
+/// Nothing is inherited from the parent encoder by default, so every
+/// command sets its own pipeline and buffers. Set the `inherit_*` options
+/// to bind those on the encoder that executes the buffer instead.
+pub struct IndirectCommandBufferBuilder {
+    descriptor: IndirectCommandBufferDescriptor,
+    max_command_count: UInteger,
+    options: ResourceOptions,
+}
+
+impl IndirectCommandBufferBuilder {
+    /// Start building a buffer that holds `max_command_count` commands.
+    pub fn new(max_command_count: UInteger) -> Option<Self> {
+        let descriptor = IndirectCommandBufferDescriptor::new()?;
+        descriptor.set_inherit_pipeline_state(false);
+        descriptor.set_inherit_buffers(false);
+        Some(Self {
+            descriptor,
+            max_command_count,
+            options: ResourceOptions::default(),
+        })
+    }
+
+    /// Set the command types the buffer may contain.
+    pub fn command_types(self, types: IndirectCommandType) -> Self {
+        self.descriptor.set_command_types(types);
+        self
+    }
+
+    /// Set whether commands inherit the executing encoder's pipeline state.
+    pub fn inherit_pipeline_state(self, inherit: bool) -> Self {
+        self.descriptor.set_inherit_pipeline_state(inherit);
+        self
+    }
+
+    /// Set whether commands inherit the executing encoder's buffer bindings.
+    pub fn inherit_buffers(self, inherit: bool) -> Self {
+        self.descriptor.set_inherit_buffers(inherit);
+        self
+    }
+
+    /// Set the number of vertex buffer slots each render command can bind.
+    pub fn max_vertex_buffer_bind_count(self, count: UInteger) -> Self {
+        self.descriptor.set_max_vertex_buffer_bind_count(count);
+        self
+    }
+
+    /// Set the number of fragment buffer slots each render command can bind.
+    pub fn max_fragment_buffer_bind_count(self, count: UInteger) -> Self {
+        self.descriptor.set_max_fragment_buffer_bind_count(count);
+        self
+    }
+
+    /// Set the number of kernel buffer slots each compute command can bind.
+    pub fn max_kernel_buffer_bind_count(self, count: UInteger) -> Self {
+        self.descriptor.set_max_kernel_buffer_bind_count(count);
+        self
+    }
+
+    /// Set the number of mesh buffer slots each render command can bind.
+    pub fn max_mesh_buffer_bind_count(self, count: UInteger) -> Self {
+        self.descriptor.set_max_mesh_buffer_bind_count(count);
+        self
+    }
+
+    /// Set the number of object buffer slots each render command can bind.
+    pub fn max_object_buffer_bind_count(self, count: UInteger) -> Self {
+        self.descriptor.set_max_object_buffer_bind_count(count);
+        self
+    }
+
+    /// Set the resource options for the buffer's storage.
+    pub fn resource_options(mut self, options: ResourceOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Get the descriptor, for settings without a builder method.
+    pub fn descriptor(&self) -> &IndirectCommandBufferDescriptor {
+        &self.descriptor
+    }
+
+    /// Create the indirect command buffer on `device`.
+    pub fn build(
+        &self,
+        device: &Device,
+    ) -> Result<IndirectCommandBuffer, IndirectCommandBufferError> {
+        let types = self.descriptor.command_types();
+        validate_command_types(types)?;
+        device
+            .new_indirect_command_buffer(&self.descriptor, self.max_command_count, self.options)
+            .ok_or(IndirectCommandBufferError::Allocation)
+    }
+}
+
+impl fmt::Debug for IndirectCommandBufferBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndirectCommandBufferBuilder")
+            .field("command_types", &self.descriptor.command_types())
+            .field("max_command_count", &self.max_command_count)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+fn validate_command_types(types: IndirectCommandType) -> Result<(), IndirectCommandBufferError> {
+    let render = (types & RENDER_COMMAND_TYPES).bits() != 0;
+    let compute = (types & COMPUTE_COMMAND_TYPES).bits() != 0;
+    match (render, compute) {
+        (false, false) => Err(IndirectCommandBufferError::NoCommandTypes),
+        (true, true) => Err(IndirectCommandBufferError::MixedCommandTypes),
+        _ => Ok(()),
+    }
+}
+
+// ============================================================================
+// Command Slots
+// ============================================================================
+
+impl IndirectCommandBuffer {
+    /// Iterate over every slot as a render command.
+    pub fn render_commands(&self) -> impl Iterator<Item = IndirectRenderCommand> + '_ {
+        self.render_commands_in(0..self.size())
+    }
+
+    /// Iterate over the slots in `range` as render commands.
+    ///
+    /// The range is clamped to [`size`](Self::size).
+    pub fn render_commands_in(
+        &self,
+        range: Range<UInteger>,
+    ) -> impl Iterator<Item = IndirectRenderCommand> + '_ {
+        self.clamp(range)
+            .filter_map(|index| self.indirect_render_command(index))
+    }
+
+    /// Iterate over every slot as a compute command.
+    pub fn compute_commands(&self) -> impl Iterator<Item = IndirectComputeCommand> + '_ {
+        self.compute_commands_in(0..self.size())
+    }
+
+    /// Iterate over the slots in `range` as compute commands.
+    ///
+    /// The range is clamped to [`size`](Self::size).
+    pub fn compute_commands_in(
+        &self,
+        range: Range<UInteger>,
+    ) -> impl Iterator<Item = IndirectComputeCommand> + '_ {
+        self.clamp(range)
+            .filter_map(|index| self.indirect_compute_command(index))
+    }
+
+    /// Reset the commands in `range`, clamped to [`size`](Self::size).
+    pub fn reset_range(&self, range: Range<UInteger>) {
+        let range = self.clamp(range);
+        if !range.is_empty() {
+            self.reset(range.start, range.end - range.start);
+        }
+    }
+
+    /// Reset every command.
+    pub fn reset_all(&self) {
+        self.reset(0, self.size());
+    }
+
+    fn clamp(&self, range: Range<UInteger>) -> Range<UInteger> {
+        let size = self.size();
+        range.start.min(size)..range.end.min(size)
+    }
+}
+
+impl IndirectCommandBufferExecutionRange {
+    /// Create an execution range covering `range`.
+    #[inline]
+    pub const fn from_range(range: Range<u32>) -> Self {
+        Self::new(range.start, range.end.saturating_sub(range.start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_command_types() {
+        assert_eq!(
+            validate_command_types(IndirectCommandType::NONE),
+            Err(IndirectCommandBufferError::NoCommandTypes)
+        );
+        assert_eq!(
+            validate_command_types(IndirectCommandType::DRAW | IndirectCommandType::DRAW_INDEXED),
+            Ok(())
+        );
+        assert_eq!(
+            validate_command_types(IndirectCommandType::CONCURRENT_DISPATCH),
+            Ok(())
+        );
+        assert_eq!(
+            validate_command_types(
+                IndirectCommandType::DRAW | IndirectCommandType::CONCURRENT_DISPATCH_THREADS
+            ),
+            Err(IndirectCommandBufferError::MixedCommandTypes)
+        );
+    }
+
+    #[test]
+    fn test_execution_range_from_range() {
+        let range = IndirectCommandBufferExecutionRange::from_range(4..10);
+        assert_eq!({ range.location }, 4);
+        assert_eq!({ range.length }, 6);
+    }
+}
@@ -18,14 +18,19 @@
 //!
 //! let icb = device.new_indirect_command_buffer(&desc, 100, ResourceOptions::default());
 //! ```
+//!
+//! [`IndirectCommandBufferBuilder`] wraps the same steps, validates the
+//! command types, and adds iterators over an ICB's command slots.
 
 mod buffer;
 mod buffer_descriptor;
+mod builder;
 mod compute_command;
 mod render_command;
 
 pub use buffer::IndirectCommandBuffer;
 pub use buffer_descriptor::IndirectCommandBufferDescriptor;
+pub use builder::{IndirectCommandBufferBuilder, IndirectCommandBufferError};
 pub use compute_command::IndirectComputeCommand;
 pub use render_command::IndirectRenderCommand;
 
@@ -0,0 +1,549 @@
+//! File streaming on top of an IO command queue.
+//!
+//! [`AssetLoader`] owns an [`IOCommandQueue`] and a [`SharedEvent`]. Loads
+//! are grouped into an [`AssetBatch`], which schedules buffer and texture
+//! loads from file offsets and, when committed, signals the next value of
+//! the event. GPU command buffers can wait on that value with
+//! `encode_wait_for_event` instead of blocking the CPU; the CPU can wait on
+//! or poll the returned [`AssetLoad`].
+//!
+//! Files written with [`write_compressed`] are read back by opening them
+//! with the same compression method.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let loader = AssetLoader::new(&device)?;
+//!
+//! // One call for a raw texture file:
+//! let albedo = loader.load_texture_async("albedo.raw", &descriptor)?.await?;
+//!
+//! // Or several loads sharing one signal:
+//! let file = loader.open("level.pack", Some(IOCompressionMethod::LZ4))?;
+//! let load = loader
+//!     .batch()?
+//!     .buffer(&vertices, 0, vertex_bytes, &file, 0)
+//!     .buffer(&indices, 0, index_bytes, &file, vertex_bytes)
+//!     .commit();
+//! command_buffer.encode_wait_for_event(loader.event().as_event(), load.value());
+//! ```
+
+use std::ffi::c_void;
+use std::fmt;
+#[cfg(feature = "blocks")]
+use std::future::Future;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "blocks")]
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "blocks")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "blocks")]
+use std::task::{Context, Poll, Waker};
+
+use mtl_foundation::UInteger;
+
+use super::{
+    IOCommandBuffer, IOCommandQueue, IOCommandQueueDescriptor, IOFileHandle,
+    io_compression_context_append_data, io_compression_context_default_chunk_size,
+    io_create_compression_context, io_flush_and_destroy_compression_context,
+};
+use crate::cancel::{CancelRegistration, CancellationToken};
+use crate::enums::device::IOCompressionMethod;
+use crate::enums::{IOCompressionStatus, IOStatus, PixelFormat};
+use crate::error::ValidationError;
+use crate::sync::SharedEvent;
+use crate::types::{Origin, Size};
+use crate::{Buffer, Device, Texture};
+
+/// Error produced by [`AssetLoader`].
+#[derive(Debug)]
+pub enum AssetLoadError {
+    /// The path is not valid UTF-8 or could not be turned into a URL.
+    InvalidPath(PathBuf),
+    /// The file could not be opened.
+    Open(mtl_foundation::Error),
+    /// The IO command queue could not be created.
+    Queue(mtl_foundation::Error),
+    /// A command buffer or shared event could not be created.
+    Allocation,
+    /// The texture could not be created.
+    Texture(ValidationError),
+    /// The texture's pixel format has no fixed row pitch.
+    UnsupportedPixelFormat(PixelFormat),
+    /// Writing a compressed file failed.
+    Compression,
+    /// The IO command buffer was cancelled.
+    Cancelled,
+    /// The IO command buffer failed, usually because the file is shorter
+    /// than a load.
+    Load(Option<mtl_foundation::Error>),
+}
+
+impl fmt::Display for AssetLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetLoadError::InvalidPath(path) => write!(f, "invalid asset path {:?}", path),
+            AssetLoadError::Open(err) => write!(f, "failed to open asset file: {}", err),
+            AssetLoadError::Queue(err) => write!(f, "failed to create IO command queue: {}", err),
+            AssetLoadError::Allocation => write!(f, "failed to allocate IO resources"),
+            AssetLoadError::Texture(err) => write!(f, "failed to create texture: {}", err),
+            AssetLoadError::UnsupportedPixelFormat(format) => {
+                write!(f, "pixel format {:?} cannot be streamed", format)
+            }
+            AssetLoadError::Compression => write!(f, "failed to write compressed file"),
+            AssetLoadError::Cancelled => write!(f, "asset load was cancelled"),
+            AssetLoadError::Load(Some(err)) => write!(f, "asset load failed: {}", err),
+            AssetLoadError::Load(None) => write!(f, "asset load failed"),
+        }
+    }
+}
+
+impl std::error::Error for AssetLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AssetLoadError::Open(err)
+            | AssetLoadError::Queue(err)
+            | AssetLoadError::Load(Some(err)) => Some(err),
+            AssetLoadError::Texture(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+fn finish(command_buffer: &IOCommandBuffer) -> Result<(), AssetLoadError> {
+    match command_buffer.status() {
+        IOStatus::CANCELLED => Err(AssetLoadError::Cancelled),
+        IOStatus::ERROR => Err(AssetLoadError::Load(command_buffer.error())),
+        _ => Ok(()),
+    }
+}
+
+/// Write `data` to `path` compressed with `method`, in chunks of
+/// [`io_compression_context_default_chunk_size`] bytes.
+///
+/// Open the file with [`AssetLoader::open`] and the same method to read it
+/// back; file offsets refer to the uncompressed data.
+pub fn write_compressed(
+    path: impl AsRef<Path>,
+    data: &[u8],
+    method: IOCompressionMethod,
+) -> Result<(), AssetLoadError> {
+    let path = path.as_ref();
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| AssetLoadError::InvalidPath(path.to_path_buf()))?;
+    let context = io_create_compression_context(
+        path_str,
+        method,
+        io_compression_context_default_chunk_size(),
+    )
+    .ok_or(AssetLoadError::Compression)?;
+    unsafe {
+        io_compression_context_append_data(context, data.as_ptr() as *const c_void, data.len());
+    }
+    match io_flush_and_destroy_compression_context(context) {
+        IOCompressionStatus::COMPLETE => Ok(()),
+        _ => Err(AssetLoadError::Compression),
+    }
+}
+
+// ============================================================================
+// AssetLoader
+// ============================================================================
+
+/// Streams file contents into buffers and textures through an IO command
+/// queue.
+pub struct AssetLoader {
+    device: Device,
+    queue: IOCommandQueue,
+    event: SharedEvent,
+    next_value: AtomicU64,
+}
+
+impl AssetLoader {
+    /// Create a loader with a concurrent, normal priority IO queue.
+    pub fn new(device: &Device) -> Result<Self, AssetLoadError> {
+        let descriptor = IOCommandQueueDescriptor::new().ok_or(AssetLoadError::Allocation)?;
+        let queue = device
+            .new_io_command_queue(&descriptor)
+            .map_err(AssetLoadError::Queue)?;
+        Self::with_queue(device, queue)
+    }
+
+    /// Create a loader that submits to `queue`.
+    pub fn with_queue(device: &Device, queue: IOCommandQueue) -> Result<Self, AssetLoadError> {
+        let event = device
+            .new_shared_event()
+            .ok_or(AssetLoadError::Allocation)?;
+        Ok(Self {
+            device: device.clone(),
+            queue,
+            event,
+            next_value: AtomicU64::new(1),
+        })
+    }
+
+    /// Get the IO command queue.
+    pub fn queue(&self) -> &IOCommandQueue {
+        &self.queue
+    }
+
+    /// Get the event signaled as each batch completes.
+    pub fn event(&self) -> &SharedEvent {
+        &self.event
+    }
+
+    /// Open a file for loading.
+    ///
+    /// Pass the compression method the file was written with, or `None`
+    /// for an uncompressed file.
+    pub fn open(
+        &self,
+        path: impl AsRef<Path>,
+        compression: Option<IOCompressionMethod>,
+    ) -> Result<IOFileHandle, AssetLoadError> {
+        let path = path.as_ref();
+        let url = mtl_foundation::Url::from_path(path)
+            .ok_or_else(|| AssetLoadError::InvalidPath(path.to_path_buf()))?;
+        match compression {
+            Some(method) => self
+                .device
+                .new_io_file_handle_with_compression(&url, method),
+            None => self.device.new_io_file_handle(&url),
+        }
+        .map_err(AssetLoadError::Open)
+    }
+
+    /// Start a batch of loads.
+    pub fn batch(&self) -> Result<AssetBatch<'_>, AssetLoadError> {
+        let command_buffer = self
+            .queue
+            .command_buffer()
+            .ok_or(AssetLoadError::Allocation)?;
+        Ok(AssetBatch {
+            loader: self,
+            command_buffer,
+        })
+    }
+
+    /// Create a texture from `descriptor` and fill mipmap level 0 of its
+    /// first slice from the uncompressed file at `path`.
+    ///
+    /// The file holds tightly packed rows (3D textures: depth images back to
+    /// back) starting at offset 0.
+    #[cfg(feature = "blocks")]
+    pub fn load_texture_async(
+        &self,
+        path: impl AsRef<Path>,
+        descriptor: &crate::TextureDescriptor,
+    ) -> Result<TextureLoad, AssetLoadError> {
+        let texture = self
+            .device
+            .new_texture_with_descriptor(descriptor)
+            .map_err(AssetLoadError::Texture)?;
+        let file = self.open(path, None)?;
+        let load = self.batch()?.texture(&texture, 0, 0, &file, 0)?.commit();
+        Ok(TextureLoad { texture, load })
+    }
+}
+
+impl fmt::Debug for AssetLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetLoader")
+            .field("queue", &self.queue.label())
+            .field("signaled_value", &self.event.signaled_value())
+            .finish()
+    }
+}
+
+// ============================================================================
+// AssetBatch
+// ============================================================================
+
+/// Loads scheduled on one IO command buffer, returned by
+/// [`AssetLoader::batch`].
+pub struct AssetBatch<'a> {
+    loader: &'a AssetLoader,
+    command_buffer: IOCommandBuffer,
+}
+
+impl AssetBatch<'_> {
+    /// Load `size` bytes at `file_offset` into `buffer` at `offset`.
+    pub fn buffer(
+        self,
+        buffer: &Buffer,
+        offset: UInteger,
+        size: UInteger,
+        file: &IOFileHandle,
+        file_offset: UInteger,
+    ) -> Self {
+        self.command_buffer
+            .load_buffer(buffer, offset, size, file, file_offset);
+        self
+    }
+
+    /// Load a whole mipmap level of one slice of `texture`, stored tightly
+    /// packed at `file_offset`.
+    pub fn texture(
+        self,
+        texture: &Texture,
+        slice: UInteger,
+        level: UInteger,
+        file: &IOFileHandle,
+        file_offset: UInteger,
+    ) -> Result<Self, AssetLoadError> {
+        let mip = |extent: UInteger| (extent >> level).max(1);
+        let size = Size::new(
+            mip(texture.width()),
+            mip(texture.height()),
+            mip(texture.depth()),
+        );
+        let format = texture.pixel_format();
+        let bytes_per_row = format
+            .bytes_per_row(size.width)
+            .ok_or(AssetLoadError::UnsupportedPixelFormat(format))?;
+        let bytes_per_image = format
+            .bytes_per_image(size.width, size.height)
+            .ok_or(AssetLoadError::UnsupportedPixelFormat(format))?;
+        self.command_buffer.load_texture(
+            texture,
+            slice,
+            level,
+            size,
+            bytes_per_row,
+            bytes_per_image,
+            Origin::new(0, 0, 0),
+            file,
+            file_offset,
+        );
+        Ok(self)
+    }
+
+    /// Get the IO command buffer, for commands without a batch method.
+    pub fn command_buffer(&self) -> &IOCommandBuffer {
+        &self.command_buffer
+    }
+
+    /// Signal the loader's event after the loads and submit the batch.
+    pub fn commit(self) -> AssetLoad {
+        let value = self.loader.next_value.fetch_add(1, Ordering::Relaxed);
+        self.command_buffer.signal_event(&self.loader.event, value);
+
+        #[cfg(feature = "blocks")]
+        let state = {
+            let state = Arc::new(Mutex::new(LoadState::default()));
+            let notified = Arc::clone(&state);
+            self.command_buffer
+                .add_completed_handler(move |command_buffer| {
+                    let waker = {
+                        let mut state = notified.lock().unwrap();
+                        state.done = true;
+                        state.result = Some(finish(command_buffer));
+                        state.waker.take()
+                    };
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                });
+            state
+        };
+
+        self.command_buffer.commit();
+        AssetLoad {
+            command_buffer: self.command_buffer,
+            value,
+            cancellation: None,
+            #[cfg(feature = "blocks")]
+            state,
+        }
+    }
+}
+
+impl fmt::Debug for AssetBatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetBatch")
+            .field("label", &self.command_buffer.label())
+            .finish()
+    }
+}
+
+// ============================================================================
+// AssetLoad
+// ============================================================================
+
+#[cfg(feature = "blocks")]
+#[derive(Default)]
+struct LoadState {
+    done: bool,
+    result: Option<Result<(), AssetLoadError>>,
+    waker: Option<Waker>,
+}
+
+/// A committed batch. With the `blocks` feature it is also a future that
+/// resolves when the loads finish.
+pub struct AssetLoad {
+    command_buffer: IOCommandBuffer,
+    value: u64,
+    cancellation: Option<CancelRegistration>,
+    #[cfg(feature = "blocks")]
+    state: Arc<Mutex<LoadState>>,
+}
+
+impl AssetLoad {
+    /// Get the value the loader's event is signaled with when the batch
+    /// completes.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Check if the batch has finished, successfully or not.
+    pub fn is_complete(&self) -> bool {
+        self.command_buffer.status() != IOStatus::PENDING
+    }
+
+    /// Block until the batch finishes.
+    pub fn wait(&self) -> Result<(), AssetLoadError> {
+        self.command_buffer.wait_until_completed();
+        finish(&self.command_buffer)
+    }
+
+    /// Try to cancel the loads that have not started.
+    pub fn cancel(&self) {
+        self.command_buffer.try_cancel();
+    }
+
+    /// Try to cancel the loads that have not started when `token` is
+    /// cancelled, for as long as this load is alive.
+    ///
+    /// Loads already running finish; the batch then resolves with
+    /// [`AssetLoadError::Cancelled`].
+    pub fn with_cancellation(mut self, token: &CancellationToken) -> Self {
+        let command_buffer = self.command_buffer.clone();
+        self.cancellation = Some(token.on_cancel(move || command_buffer.try_cancel()));
+        self
+    }
+}
+
+#[cfg(feature = "blocks")]
+impl Future for AssetLoad {
+    type Output = Result<(), AssetLoadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            return Poll::Ready(result);
+        }
+        if state.done {
+            drop(state);
+            return Poll::Ready(finish(&self.command_buffer));
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl fmt::Debug for AssetLoad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetLoad")
+            .field("value", &self.value)
+            .field("complete", &self.is_complete())
+            .finish()
+    }
+}
+
+/// Future returned by [`AssetLoader::load_texture_async`].
+#[cfg(feature = "blocks")]
+pub struct TextureLoad {
+    texture: Texture,
+    load: AssetLoad,
+}
+
+#[cfg(feature = "blocks")]
+impl TextureLoad {
+    /// Get the texture being loaded.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Get the underlying batch, for its event value.
+    pub fn load(&self) -> &AssetLoad {
+        &self.load
+    }
+
+    /// Try to cancel the load when `token` is cancelled.
+    ///
+    /// See [`AssetLoad::with_cancellation`].
+    pub fn with_cancellation(mut self, token: &CancellationToken) -> Self {
+        self.load = self.load.with_cancellation(token);
+        self
+    }
+}
+
+#[cfg(feature = "blocks")]
+impl Future for TextureLoad {
+    type Output = Result<Texture, AssetLoadError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.load).poll(cx) {
+            Poll::Ready(result) => Poll::Ready(result.map(|()| self.texture.clone())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(feature = "blocks")]
+impl fmt::Debug for TextureLoad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextureLoad")
+            .field("texture", &self.texture)
+            .field("load", &self.load)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::ResourceOptions;
+
+    fn read_back(buffer: &Buffer, len: usize) -> Vec<u8> {
+        let contents = buffer.contents().unwrap() as *const u8;
+        unsafe { std::slice::from_raw_parts(contents, len) }.to_vec()
+    }
+
+    #[test]
+    fn test_asset_loader_buffers() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let loader = AssetLoader::new(&device).unwrap();
+        let data: Vec<u8> = (0..4096u32).map(|i| i as u8).collect();
+
+        let dir = std::env::temp_dir();
+        let raw_path = dir.join("mtl_asset_loader_raw.bin");
+        let lz4_path = dir.join("mtl_asset_loader_lz4.bin");
+        std::fs::write(&raw_path, &data).unwrap();
+        write_compressed(&lz4_path, &data, IOCompressionMethod::LZ4).unwrap();
+
+        let raw = loader.open(&raw_path, None).unwrap();
+        let lz4 = loader
+            .open(&lz4_path, Some(IOCompressionMethod::LZ4))
+            .unwrap();
+        let a = device.new_buffer(1024, ResourceOptions::default()).unwrap();
+        let b = device.new_buffer(1024, ResourceOptions::default()).unwrap();
+        let load = loader
+            .batch()
+            .unwrap()
+            .buffer(&a, 0, 1024, &raw, 256)
+            .buffer(&b, 0, 1024, &lz4, 512)
+            .commit();
+        load.wait().unwrap();
+
+        assert!(load.is_complete());
+        assert!(loader.event().signaled_value() >= load.value());
+        assert_eq!(read_back(&a, 1024), &data[256..1280]);
+        assert_eq!(read_back(&b, 1024), &data[512..1536]);
+
+        let _ = std::fs::remove_file(raw_path);
+        let _ = std::fs::remove_file(lz4_path);
+    }
+}
@@ -314,6 +314,7 @@ impl IOCommandBuffer {
     /// C++ equivalent: `void addCompletedHandler(const IOCommandBufferHandler)`
     ///
     /// The handler is called with a reference to the completed IO command buffer.
+    #[cfg(feature = "blocks")]
     pub fn add_completed_handler<F>(&self, handler: F)
     where
         F: Fn(&IOCommandBuffer) + Send + 'static,
@@ -10,6 +10,7 @@ use std::ffi::c_void;
 use crate::enums::IOCompressionStatus;
 use crate::enums::device::IOCompressionMethod;
 
+mod asset_loader;
 mod command_buffer;
 mod command_queue;
 mod command_queue_descriptor;
@@ -17,6 +18,9 @@ mod file_handle;
 mod scratch_buffer;
 mod scratch_buffer_allocator;
 
+#[cfg(feature = "blocks")]
+pub use asset_loader::TextureLoad;
+pub use asset_loader::{AssetBatch, AssetLoad, AssetLoadError, AssetLoader, write_compressed};
 pub use command_buffer::IOCommandBuffer;
 pub use command_queue::IOCommandQueue;
 pub use command_queue_descriptor::IOCommandQueueDescriptor;
@@ -31,7 +35,7 @@ pub use scratch_buffer_allocator::IOScratchBufferAllocator;
 /// Opaque compression context handle.
 pub type IOCompressionContext = *mut c_void;
 
-#[link(name = "Metal", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "Metal", kind = "framework"))]
 unsafe extern "C" {
     fn MTLIOCompressionContextDefaultChunkSize() -> usize;
     fn MTLIOCreateCompressionContext(
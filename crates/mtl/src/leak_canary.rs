@@ -0,0 +1,305 @@
+//! Diagnostics for resources that stay retained after their GPU work.
+//!
+//! A command buffer retains every resource it references and releases them
+//! once it is done with them. If a resource's retain count is still above
+//! its pre-submission value long after the command buffer completed,
+//! something (the bindings, a cached command buffer, or the app) is holding
+//! an extra reference. [`LeakCanary`] records each watched resource's
+//! retain count before encoding, notes when its command buffer completes,
+//! and after a grace period reports the resources whose count never came
+//! back down, by label.
+//!
+//! Retain counts are a heuristic: objects handed to autorelease pools or
+//! retained by the system on purpose can show up as false positives. The
+//! canary is meant for debug builds and leak hunts, not production.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let canary = LeakCanary::new(Duration::from_secs(2));
+//!
+//! // Each frame, before encoding:
+//! canary.watch(&command_buffer, &vertex_buffer);
+//! canary.watch(&command_buffer, &shadow_map);
+//! // ... encode and commit ...
+//!
+//! // Periodically:
+//! for leak in canary.check() {
+//!     eprintln!("possible leak: {leak}");
+//! }
+//! ```
+
+use std::collections::HashSet;
+use std::ffi::c_void;
+use std::fmt;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use mtl_foundation::{Referencing, UInteger};
+use mtl_sys::{msg_send_0, sel};
+
+use crate::enums::CommandBufferStatus;
+use crate::{Allocation, CommandBuffer};
+
+/// A resource whose retain count stayed elevated after its command buffer
+/// completed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeakReport {
+    /// The resource's label, if it has one.
+    pub label: Option<String>,
+    /// Address of the resource object, to tell unlabeled resources apart.
+    pub address: usize,
+    /// Retain count when the resource was watched.
+    pub baseline: UInteger,
+    /// Retain count when the leak was reported.
+    pub retain_count: UInteger,
+    /// Time between command buffer completion and the report.
+    pub since_completion: Duration,
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({:#x}): retain count {} (expected {}) {:.1} s after completion",
+            self.label.as_deref().unwrap_or("<unlabeled>"),
+            self.address,
+            self.retain_count,
+            self.baseline,
+            self.since_completion.as_secs_f64()
+        )
+    }
+}
+
+/// A retained resource and the retain count it is expected to return to.
+struct Watched {
+    object: NonNull<c_void>,
+    command_buffer: usize,
+    baseline: UInteger,
+    completed_at: Option<Instant>,
+}
+
+// SAFETY: Metal resources may be retained, released and queried from any
+// thread.
+unsafe impl Send for Watched {}
+
+impl Watched {
+    fn retain_count(&self) -> UInteger {
+        unsafe { msg_send_0(self.object.as_ptr(), sel!(retainCount)) }
+    }
+
+    fn label(&self) -> Option<String> {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.object.as_ptr(), sel!(label));
+            if ptr.is_null() {
+                return None;
+            }
+            let utf8_ptr: *const std::ffi::c_char =
+                msg_send_0(ptr as *const c_void, sel!(UTF8String));
+            if utf8_ptr.is_null() {
+                return None;
+            }
+            let c_str = std::ffi::CStr::from_ptr(utf8_ptr);
+            Some(c_str.to_string_lossy().into_owned())
+        }
+    }
+}
+
+impl Drop for Watched {
+    fn drop(&mut self) {
+        unsafe {
+            msg_send_0::<()>(self.object.as_ptr(), sel!(release));
+        }
+    }
+}
+
+#[derive(Default)]
+struct CanaryState {
+    watched: Vec<Watched>,
+    /// Command buffers with a completed handler installed.
+    command_buffers: HashSet<usize>,
+}
+
+// ============================================================================
+// LeakCanary
+// ============================================================================
+
+/// Tracks retain counts of resources referenced by committed command
+/// buffers and reports those that stay elevated.
+///
+/// The canary holds one reference to each watched resource until it is
+/// checked; that reference is part of the baseline.
+pub struct LeakCanary {
+    enabled: AtomicBool,
+    grace: Duration,
+    state: Arc<Mutex<CanaryState>>,
+}
+
+impl LeakCanary {
+    /// Create an enabled canary that reports resources still retained
+    /// `grace` after their command buffer completed.
+    pub fn new(grace: Duration) -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            grace,
+            state: Arc::new(Mutex::new(CanaryState::default())),
+        }
+    }
+
+    /// Get the grace period.
+    pub fn grace(&self) -> Duration {
+        self.grace
+    }
+
+    /// Enable or disable watching. Resources already watched are still
+    /// checked.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Check if watching is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Watch `resource` until `command_buffer` completes.
+    ///
+    /// Call this before the resource is encoded into the command buffer, so
+    /// the baseline does not include the command buffer's own reference,
+    /// and before the command buffer is committed. Returns `false` if the
+    /// canary is disabled or the command buffer was already committed.
+    pub fn watch<R: Allocation + Referencing>(
+        &self,
+        command_buffer: &CommandBuffer,
+        resource: &R,
+    ) -> bool {
+        if !self.is_enabled() || command_buffer.status() != CommandBufferStatus::NOT_ENQUEUED {
+            return false;
+        }
+        let Some(object) = NonNull::new(resource.as_ptr() as *mut c_void) else {
+            return false;
+        };
+        let key = command_buffer.as_ptr() as usize;
+
+        let mut state = self.state.lock().unwrap();
+        if state.command_buffers.insert(key) {
+            let notified = Arc::clone(&self.state);
+            command_buffer.add_completed_handler(move |command_buffer| {
+                let key = command_buffer.as_ptr() as usize;
+                let now = Instant::now();
+                let mut state = notified.lock().unwrap();
+                state.command_buffers.remove(&key);
+                for watched in &mut state.watched {
+                    if watched.command_buffer == key && watched.completed_at.is_none() {
+                        watched.completed_at = Some(now);
+                    }
+                }
+            });
+        }
+
+        unsafe {
+            let _: *mut c_void = msg_send_0(object.as_ptr(), sel!(retain));
+        }
+        let mut watched = Watched {
+            object,
+            command_buffer: key,
+            baseline: 0,
+            completed_at: None,
+        };
+        watched.baseline = watched.retain_count();
+        state.watched.push(watched);
+        true
+    }
+
+    /// Report resources still retained after the grace period.
+    ///
+    /// Every resource whose grace period has passed is released by the
+    /// canary, whether or not it was reported.
+    pub fn check(&self) -> Vec<LeakReport> {
+        let now = Instant::now();
+        let expired = {
+            let mut state = self.state.lock().unwrap();
+            let (expired, pending) = std::mem::take(&mut state.watched)
+                .into_iter()
+                .partition::<Vec<_>, _>(|watched| {
+                    watched
+                        .completed_at
+                        .is_some_and(|completed| now.duration_since(completed) >= self.grace)
+                });
+            state.watched = pending;
+            expired
+        };
+        expired
+            .into_iter()
+            .filter_map(|watched| {
+                let retain_count = watched.retain_count();
+                (retain_count > watched.baseline).then(|| LeakReport {
+                    label: watched.label(),
+                    address: watched.object.as_ptr() as usize,
+                    baseline: watched.baseline,
+                    retain_count,
+                    since_completion: watched
+                        .completed_at
+                        .map_or(Duration::ZERO, |completed| now.duration_since(completed)),
+                })
+            })
+            .collect()
+    }
+
+    /// Get the number of resources still being watched.
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap().watched.len()
+    }
+
+    /// Stop watching every resource.
+    pub fn clear(&self) {
+        self.state.lock().unwrap().watched.clear();
+    }
+}
+
+impl fmt::Debug for LeakCanary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeakCanary")
+            .field("enabled", &self.is_enabled())
+            .field("grace", &self.grace)
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::ResourceOptions;
+
+    #[test]
+    fn test_leak_canary() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().unwrap();
+        let canary = LeakCanary::new(Duration::ZERO);
+
+        let clean = device.new_buffer(256, ResourceOptions::default()).unwrap();
+        let leaked = device.new_buffer(256, ResourceOptions::default()).unwrap();
+        leaked.set_label("leaked");
+
+        let command_buffer = queue.command_buffer().unwrap();
+        assert!(canary.watch(&command_buffer, &clean));
+        assert!(canary.watch(&command_buffer, &leaked));
+        std::mem::forget(leaked.clone());
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+        assert!(!canary.watch(&command_buffer, &clean));
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut reports = Vec::new();
+        while canary.pending() > 0 && Instant::now() < deadline {
+            reports.extend(canary.check());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(canary.pending(), 0);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].label.as_deref(), Some("leaked"));
+    }
+}
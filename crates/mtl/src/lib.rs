@@ -9,7 +9,7 @@
 //! Metal framework bindings for Rust.
 //!
 //! This crate provides safe, idiomatic Rust bindings to Apple's Metal graphics API.
-//! It is a 1:1 translation of the official metal-cpp library with zero external dependencies.
+//! It is a 1:1 translation of the official metal-cpp library with no required external dependencies.
 //!
 //! # Module Organization
 //!
@@ -136,66 +136,145 @@ pub mod function_log;
 // Texture view pool modules
 pub mod texture_view_pool;
 
+// Resource handle modules
+pub mod resource_store;
+
+// Growable buffer modules
+pub mod gpu_vec;
+
+// Material parameter block modules
+pub mod material_blocks;
+
+// Debug view modules
+pub mod debug_viewer;
+
+// Checkpointed compute job modules
+pub mod compute_job;
+
+// Device abstraction modules
+pub mod device_like;
+
+// Cross-queue timeline modules
+pub mod timeline;
+
+// Synthetic workload modules
+pub mod stress;
+
+// Queue set modules
+pub mod queue_set;
+
+// Procedural mesh modules
+pub mod mesh;
+
+// MSL layout modules
+pub mod msl_layout;
+
+// Validation modules
+#[cfg(feature = "validation")]
+pub mod validation;
+
+// Leak diagnostics modules
+#[cfg(feature = "blocks")]
+pub mod leak_canary;
+
 // Metal 4 modules
 pub mod mtl4;
 
+// Cancellation of async operations
+pub mod cancel;
+
+// Internal helpers
+mod object_ptrs;
+
+// Unstable API modules
+#[cfg(feature = "experimental")]
+pub mod experimental;
+
+// C API modules
+#[cfg(feature = "capi")]
+pub mod capi;
+
+// Core Video interop modules
+#[cfg(feature = "core-video")]
+pub mod core_video;
+
 // Re-export commonly used types at crate root
 pub use enums::*;
 pub use error::ValidationError;
 pub use types::*;
 
 // Re-export Device and creation functions for convenience
-pub use device::{Architecture, Device};
+#[cfg(target_os = "macos")]
+pub use device::PeerGroup;
+pub use device::{Architecture, Device, FormatCaps, SourcePipelineError};
+#[cfg(feature = "memory-report")]
+pub use device::{MemoryReport, MemoryReportEntry, MemoryTracker, ResourceKind};
 
 // Re-export resource types
-pub use buffer::Buffer;
-pub use heap::{Heap, HeapDescriptor};
-pub use texture::{SharedTextureHandle, Texture, TextureDescriptor, TextureViewDescriptor};
+pub use buffer::{Buffer, BufferReadbackError, BufferSlice, BufferView};
+pub use heap::{AliasGroup, AliasId, Heap, HeapDescriptor};
+#[cfg(feature = "blocks")]
+pub use texture::TextureUpload;
+pub use texture::{
+    DecompressError, ReadbackError, SharedTextureHandle, Texture, TextureData, TextureDescriptor,
+    TextureViewDescriptor, UploadError, UploadOptions,
+};
 
 // Re-export command types
-pub use command_buffer::{CommandBuffer, CommandBufferDescriptor};
+pub use command_buffer::{CommandBuffer, CommandBufferDescriptor, Timeout};
 pub use command_queue::{CommandQueue, CommandQueueDescriptor};
 
 // Re-export state types
-pub use depth_stencil::{DepthStencilDescriptor, DepthStencilState, StencilDescriptor};
-pub use sampler::{SamplerDescriptor, SamplerState};
+pub use depth_stencil::{
+    DepthStencilCache, DepthStencilDescriptor, DepthStencilKey, DepthStencilState,
+    StencilDescriptor, StencilKey,
+};
+pub use sampler::{SamplerCache, SamplerDescriptor, SamplerKey, SamplerState};
 
 // Re-export library types
 pub use library::{
-    Attribute, CompileOptions, DynamicLibrary, Function, FunctionConstant, FunctionConstantValues,
-    FunctionDescriptor, FunctionReflection, IntersectionFunctionDescriptor, Library,
-    LinkedFunctions, VertexAttribute,
+    Attribute, CompileOptions, DynamicLibrary, EmbeddedMetallib, Function, FunctionConstant,
+    FunctionConstantValues, FunctionDescriptor, FunctionReflection, IntersectionFunctionDescriptor,
+    Library, LinkedFunctions, VertexAttribute,
 };
 
 // Re-export pipeline types
 pub use pipeline::{
-    ComputePipelineDescriptor, ComputePipelineState, LogicalToPhysicalColorAttachmentMap,
-    MeshRenderPipelineDescriptor, PipelineBufferDescriptor, PipelineBufferDescriptorArray,
-    RenderPipelineColorAttachmentDescriptor, RenderPipelineColorAttachmentDescriptorArray,
-    RenderPipelineDescriptor, RenderPipelineFunctionsDescriptor, RenderPipelineReflection,
+    BindingChange, BindingInterface, BindingSignature, BindingStage, ColorAttachmentConfig,
+    ColorBlending, ComputePipelineDescriptor, ComputePipelineReflection, ComputePipelineState,
+    LogicalToPhysicalColorAttachmentMap, MeshRenderPipelineDescriptor, PipelineBufferDescriptor,
+    PipelineBufferDescriptorArray, PipelineSwap, PipelineSwapError, RayTracingPipeline,
+    RayTracingPipelineBuildError, RayTracingPipelineBuilder, ReflectedPipeline,
+    RenderPipelineBuildError, RenderPipelineColorAttachmentDescriptor,
+    RenderPipelineColorAttachmentDescriptorArray, RenderPipelineConfig, RenderPipelineDescriptor,
+    RenderPipelineDescriptorBuilder, RenderPipelineFunctionsDescriptor, RenderPipelineReflection,
     RenderPipelineState, TileRenderPipelineColorAttachmentDescriptor,
-    TileRenderPipelineColorAttachmentDescriptorArray, TileRenderPipelineDescriptor,
+    TileRenderPipelineColorAttachmentDescriptorArray, TileRenderPipelineDescriptor, VertexCapture,
+    VertexCaptureError,
 };
 
 // Re-export sync types
-pub use sync::{Event, Fence, SharedEvent, SharedEventHandle, SharedEventListener};
+#[cfg(feature = "blocks")]
+pub use sync::GpuFenceWait;
+pub use sync::{Event, Fence, GpuFence, SharedEvent, SharedEventHandle, SharedEventListener};
 
 // Re-export pass descriptor types
 pub use pass::{
     BlitPassDescriptor, BlitPassSampleBufferAttachmentDescriptor,
     BlitPassSampleBufferAttachmentDescriptorArray, ComputePassDescriptor,
     ComputePassSampleBufferAttachmentDescriptor, ComputePassSampleBufferAttachmentDescriptorArray,
-    RenderPassColorAttachmentDescriptor, RenderPassColorAttachmentDescriptorArray,
-    RenderPassDepthAttachmentDescriptor, RenderPassDescriptor,
-    RenderPassSampleBufferAttachmentDescriptor, RenderPassSampleBufferAttachmentDescriptorArray,
-    RenderPassStencilAttachmentDescriptor, ResourceStatePassDescriptor,
-    ResourceStatePassSampleBufferAttachmentDescriptor,
-    ResourceStatePassSampleBufferAttachmentDescriptorArray,
+    CubeShadowPass, DepthBias, LayeredRenderError, RenderPassColorAttachmentDescriptor,
+    RenderPassColorAttachmentDescriptorArray, RenderPassDepthAttachmentDescriptor,
+    RenderPassDescriptor, RenderPassSampleBufferAttachmentDescriptor,
+    RenderPassSampleBufferAttachmentDescriptorArray, RenderPassStencilAttachmentDescriptor,
+    ResourceStatePassDescriptor, ResourceStatePassSampleBufferAttachmentDescriptor,
+    ResourceStatePassSampleBufferAttachmentDescriptorArray, ShadowPass, ShadowPassError,
 };
 
 // Re-export acceleration structure types
 pub use acceleration::{
     AccelerationStructure, AccelerationStructureBoundingBoxGeometryDescriptor,
+    AccelerationStructureBuildError, AccelerationStructureBuilder,
     AccelerationStructureCommandEncoder, AccelerationStructureCurveGeometryDescriptor,
     AccelerationStructureDescriptor, AccelerationStructureGeometryDescriptor,
     AccelerationStructureMotionBoundingBoxGeometryDescriptor,
@@ -210,23 +289,29 @@ pub use acceleration::{
 
 // Re-export encoder types
 pub use encoder::{
-    BlitCommandEncoder, ComputeCommandEncoder, DispatchThreadgroupsIndirectArguments,
-    DispatchThreadsIndirectArguments, MapIndirectArguments, ParallelRenderCommandEncoder,
-    RenderCommandEncoder, ResourceStateCommandEncoder, StageInRegionIndirectArguments,
+    BlitCommandEncoder, BlitError, CUBE_FACE_COUNT, ComputeCommandEncoder, ComputePass,
+    DEFAULT_ENCODE_HISTORY, DispatchThreadgroupsIndirectArguments,
+    DispatchThreadsIndirectArguments, EncodeSpan, EncodeTimings, EncoderKind, FrameEncodeTimes,
+    MAX_INLINE_BYTES, MapIndirectArguments, ParallelRenderCommandEncoder, PassEncodeRecord,
+    PassEncodeTime, RenderCommandEncoder, ResourceStateCommandEncoder, SparseTextureMappingBatch,
+    StageInRegionIndirectArguments, TimedEncoder, cube_array_slice,
 };
 
 // Re-export IO types
+#[cfg(feature = "blocks")]
+pub use io::TextureLoad;
 pub use io::{
-    IOCommandBuffer, IOCommandQueue, IOCommandQueueDescriptor, IOCompressionContext, IOFileHandle,
-    IOScratchBuffer, IOScratchBufferAllocator, io_compression_context_append_data,
+    AssetBatch, AssetLoad, AssetLoadError, AssetLoader, IOCommandBuffer, IOCommandQueue,
+    IOCommandQueueDescriptor, IOCompressionContext, IOFileHandle, IOScratchBuffer,
+    IOScratchBufferAllocator, io_compression_context_append_data,
     io_compression_context_default_chunk_size, io_create_compression_context,
     io_flush_and_destroy_compression_context,
 };
 
 // Re-export argument types
 pub use argument::{
-    ATTRIBUTE_STRIDE_STATIC, Argument, ArgumentEncoder, ArrayType, Binding, BufferBinding,
-    ObjectPayloadBinding, PointerType, StructMember, StructType, TensorBinding,
+    ATTRIBUTE_STRIDE_STATIC, Argument, ArgumentEncoder, ArrayType, Binding, BindingKind, Bindings,
+    BufferBinding, ObjectPayloadBinding, PointerType, StructMember, StructType, TensorBinding,
     TensorReferenceType, TextureBinding, TextureReferenceType, ThreadgroupBinding, Type,
 };
 pub use argument_descriptor::ArgumentDescriptor;
@@ -245,16 +330,24 @@ pub use function_stitching::{
 };
 
 // Re-export residency set types
-pub use residency_set::{ResidencySet, ResidencySetDescriptor};
+pub use residency_set::{
+    ResidencyReport, ResidencySet, ResidencySetBuilder, ResidencySetDescriptor,
+};
 
 // Re-export tensor types
-pub use tensor::{Tensor, TensorDescriptor, TensorExtents};
+pub use tensor::{Tensor, TensorDataError, TensorDescriptor, TensorElement, TensorExtents};
 
 // Re-export drawable types
 pub use drawable::{Drawable, TimeInterval};
 
 // Re-export capture types
-pub use capture::{CaptureDescriptor, CaptureManager, CaptureScope};
+#[cfg(feature = "blocks")]
+pub use capture::FailureWatch;
+pub use capture::{
+    CaptureDescriptor, CaptureGuard, CaptureManager, CaptureScope, CaptureSet, CaptureTrigger,
+    CapturedImage, DiffOptions, EncoderFailure, FailureReport, FrameCapture, FrameDiffError,
+    FrameDiffReport, ImageDiff, RenderTargetCapture, TargetDiff,
+};
 
 // Re-export counter types
 pub use counter::{
@@ -265,14 +358,17 @@ pub use counter::{
 
 // Re-export indirect command types
 pub use indirect::{
-    IndirectCommandBuffer, IndirectCommandBufferDescriptor, IndirectCommandBufferExecutionRange,
-    IndirectComputeCommand, IndirectRenderCommand,
+    IndirectCommandBuffer, IndirectCommandBufferBuilder, IndirectCommandBufferDescriptor,
+    IndirectCommandBufferError, IndirectCommandBufferExecutionRange, IndirectComputeCommand,
+    IndirectRenderCommand,
 };
 
 // Re-export vertex descriptor types
 pub use vertex::{
-    BUFFER_LAYOUT_STRIDE_DYNAMIC, VertexAttributeDescriptor, VertexAttributeDescriptorArray,
+    BUFFER_LAYOUT_STRIDE_DYNAMIC, Normalized, VertexAttributeConfig, VertexAttributeDescriptor,
+    VertexAttributeDescriptorArray, VertexAttributeType, VertexBufferLayoutConfig,
     VertexBufferLayoutDescriptor, VertexBufferLayoutDescriptorArray, VertexDescriptor,
+    VertexDescriptorConfig, VertexField, VertexLayout,
 };
 
 // Re-export function table types
@@ -283,8 +379,9 @@ pub use function_table::{
 
 // Re-export rasterization rate types
 pub use rasterization_rate::{
-    RasterizationRateLayerArray, RasterizationRateLayerDescriptor, RasterizationRateMap,
-    RasterizationRateMapDescriptor, RasterizationRateSampleArray,
+    FoveationController, FoveationError, FoveationParams, RasterizationRateLayerArray,
+    RasterizationRateLayerDescriptor, RasterizationRateMap, RasterizationRateMapDescriptor,
+    RasterizationRateSampleArray,
 };
 
 // Re-export log state types
@@ -296,6 +393,52 @@ pub use function_log::{FunctionLog, FunctionLogDebugLocation, LogContainer};
 // Re-export texture view pool types
 pub use texture_view_pool::{ResourceViewPoolDescriptor, TextureViewPool};
 
+// Re-export resource handle types
+pub use resource_store::{BufferHandle, Handle, ResourceStore, TextureHandle};
+
+// Re-export growable buffer types
+pub use gpu_vec::{GpuVec, GpuVecError, Pod};
+
+// Re-export material block types
+pub use material_blocks::{MaterialBlockAllocator, MaterialBlockError};
+
+// Re-export debug viewer types
+pub use debug_viewer::{DebugViewChannels, DebugViewer, DebugViewerError};
+
+// Re-export compute job types
+pub use compute_job::{ComputeJobError, ComputeJobRunner, JobCheckpoint};
+
+// Re-export device abstraction types
+#[cfg(feature = "mock")]
+pub use device_like::mock::{
+    MockBuffer, MockCall, MockCommandBuffer, MockCommandQueue, MockDevice,
+};
+pub use device_like::{BufferLike, CommandBufferLike, CommandQueueLike, DeviceLike};
+
+// Re-export timeline types
+pub use timeline::{
+    DEFAULT_TIMELINE_HISTORY, FrameTimeline, QueueSpan, QueueTimelines, TimestampCalibration,
+};
+
+// Re-export stress test types
+pub use stress::{StressConfig, StressError, StressKind, StressResult, StressTest};
+
+// Re-export queue set types
+pub use queue_set::{QueueRole, QueueSet, QueueStats, WorkKind};
+
+// Re-export procedural mesh types
+pub use mesh::{MESH_VERTEX_BUFFER_INDEX, Mesh, MeshData, MeshVertex};
+
+// Re-export MSL layout types
+pub use msl_layout::{MslArray, MslField, MslLayout};
+
+// Re-export cancellation types
+pub use cancel::{CancelRegistration, CancellationToken};
+
+// Re-export leak diagnostics types
+#[cfg(feature = "blocks")]
+pub use leak_canary::{LeakCanary, LeakReport};
+
 // Re-export foundation types for convenience
 pub use mtl_foundation::{Integer, UInteger};
 
@@ -1,11 +1,14 @@
 //! A dynamic Metal library that can be loaded at runtime.
 
 use std::ffi::c_void;
+use std::path::Path;
 use std::ptr::NonNull;
 
-use mtl_foundation::Referencing;
+use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, sel};
 
+use crate::object_ptrs::with_object_ptrs;
+
 /// A dynamic Metal library that can be loaded at runtime.
 ///
 /// C++ equivalent: `MTL::DynamicLibrary`
@@ -124,6 +127,84 @@ impl DynamicLibrary {
             }
         }
     }
+
+    /// Serialize the dynamic library to a `.metallib` file at `path`.
+    ///
+    /// Load it again with
+    /// [`Device::new_dynamic_library_with_path`](crate::Device::new_dynamic_library_with_path).
+    pub fn serialize(&self, path: impl AsRef<Path>) -> Result<(), mtl_foundation::Error> {
+        let url = file_url(path.as_ref())?;
+        self.serialize_to_url(&url)
+    }
+}
+
+/// `NSFileReadInvalidFileNameError`, the Cocoa error code for unusable paths.
+const INVALID_FILE_NAME_ERROR: mtl_foundation::Integer = 258;
+
+/// Create a file URL for `path`.
+///
+/// Paths that aren't UTF-8 or contain a NUL byte can't be passed to
+/// Foundation; they fail with a Cocoa error naming the path.
+pub(crate) fn file_url(path: &Path) -> Result<mtl_foundation::Url, mtl_foundation::Error> {
+    mtl_foundation::Url::from_path(path).ok_or_else(|| {
+        use mtl_foundation::{Dictionary, String};
+
+        let message = format!(
+            "file path is not valid UTF-8 or contains a NUL byte: {}",
+            path.display()
+        );
+        let user_info = String::from_str(&message.replace('\0', "\\0")).and_then(|message| {
+            Dictionary::<String, String>::dictionary_with_object(
+                message.as_ptr().cast(),
+                mtl_foundation::localized_description_key(),
+            )
+        });
+        mtl_foundation::Error::error(
+            mtl_foundation::cocoa_error_domain(),
+            INVALID_FILE_NAME_ERROR,
+            user_info.map_or(std::ptr::null_mut(), |info| info.as_ptr() as *mut _),
+        )
+        .expect("failed to create error object")
+    })
+}
+
+/// Call `f` with an NSArray of `libraries`, or null if there are none.
+pub(crate) fn with_library_array<R>(
+    libraries: &[&DynamicLibrary],
+    f: impl FnOnce(*const c_void) -> R,
+) -> R {
+    if libraries.is_empty() {
+        return f(std::ptr::null());
+    }
+    with_object_ptrs(libraries, |ptrs| {
+        let array = mtl_foundation::Array::<c_void>::array_with_objects(
+            ptrs.as_ptr(),
+            ptrs.len() as UInteger,
+        );
+        f(array.map_or(std::ptr::null(), |array| array.as_ptr()))
+    })
+}
+
+/// Get the dynamic libraries in an NSArray, retained.
+///
+/// # Safety
+///
+/// `array` must be null or a valid NSArray of dynamic libraries.
+pub(crate) unsafe fn libraries_from_array(array: *mut c_void) -> Vec<DynamicLibrary> {
+    if array.is_null() {
+        return Vec::new();
+    }
+    unsafe {
+        let count: UInteger = msg_send_0(array, sel!(count));
+        (0..count)
+            .filter_map(|index| {
+                let ptr: *mut c_void =
+                    msg_send_1(array as *const c_void, sel!(objectAtIndex:), index);
+                let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+                DynamicLibrary::from_raw(ptr)
+            })
+            .collect()
+    }
 }
 
 impl Clone for DynamicLibrary {
@@ -161,3 +242,44 @@ impl std::fmt::Debug for DynamicLibrary {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::enums::LibraryType;
+
+    #[test]
+    fn test_dynamic_library_round_trip() {
+        let device = crate::device::system_default().expect("no Metal device");
+        if !device.supports_dynamic_libraries() {
+            return;
+        }
+        let options = crate::CompileOptions::new().unwrap();
+        options.set_library_type(LibraryType::DYNAMIC);
+        options.set_install_name("@executable_path/scale.metallib");
+        let library = device
+            .new_library_with_source("float scale(float x) { return x * 2.0; }", Some(&options))
+            .unwrap();
+        let dynamic = device.new_dynamic_library(&library).unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("mtl-gpu-{}-scale.metallib", std::process::id()));
+        dynamic.serialize(&path).unwrap();
+        let loaded = device.new_dynamic_library_with_path(&path);
+        let _ = std::fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.install_name(), dynamic.install_name());
+
+        let descriptor = crate::ComputePipelineDescriptor::new().unwrap();
+        descriptor.set_preloaded_libraries(&[&loaded]);
+        assert_eq!(descriptor.preloaded_libraries().len(), 1);
+        descriptor.set_preloaded_libraries(&[]);
+        assert!(descriptor.preloaded_libraries().is_empty());
+    }
+
+    #[test]
+    fn test_file_url_rejects_nul_path() {
+        let error = super::file_url(std::path::Path::new("shaders\0.metallib")).unwrap_err();
+        assert_eq!(error.code(), super::INVALID_FILE_NAME_ERROR);
+        assert!(!error.localized_description().is_null());
+    }
+}
@@ -0,0 +1,138 @@
+//! Metallibs embedded in the binary.
+//!
+//! [`include_metallib!`](crate::include_metallib) embeds a `.metallib`
+//! file with `include_bytes!` and [`Library::from_embedded`] loads it
+//! through `newLibraryWithData:`, so an app can ship as a single binary
+//! without looking up shader files at run time.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Built by build.rs, for example with MetalCompiler.
+//! static SHADERS: EmbeddedMetallib =
+//!     mtl_gpu::include_metallib!(concat!(env!("OUT_DIR"), "/shaders.metallib"));
+//!
+//! let library = Library::from_embedded(&device, &SHADERS)?;
+//! let blur = library.new_function_with_name("blur").unwrap();
+//! ```
+
+use std::ffi::c_void;
+
+use super::Library;
+use crate::Device;
+
+unsafe extern "C" {
+    fn dispatch_data_create(
+        buffer: *const c_void,
+        size: usize,
+        queue: *mut c_void,
+        destructor: *const c_void,
+    ) -> *mut c_void;
+    fn dispatch_release(object: *mut c_void);
+}
+
+/// The first bytes of every `.metallib` file.
+const METALLIB_MAGIC: &[u8; 4] = b"MTLB";
+
+/// The bytes of a `.metallib` file compiled into the binary.
+///
+/// Create one with [`include_metallib!`](crate::include_metallib).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EmbeddedMetallib {
+    name: &'static str,
+    bytes: &'static [u8],
+}
+
+impl EmbeddedMetallib {
+    /// Wrap metallib bytes, with `name` identifying them in errors.
+    pub const fn new(name: &'static str, bytes: &'static [u8]) -> Self {
+        Self { name, bytes }
+    }
+
+    /// Get the name, the path given to `include_metallib!`.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Get the metallib bytes.
+    pub const fn bytes(&self) -> &'static [u8] {
+        self.bytes
+    }
+
+    /// Check if the bytes start with the metallib file signature.
+    pub fn has_metallib_header(&self) -> bool {
+        self.bytes.starts_with(METALLIB_MAGIC)
+    }
+}
+
+/// Embed a `.metallib` file in the binary as an
+/// [`EmbeddedMetallib`](crate::EmbeddedMetallib).
+///
+/// The path is resolved like `include_bytes!`, relative to the current
+/// file; use `concat!(env!("OUT_DIR"), ...)` for files written by a build
+/// script.
+///
+/// ```ignore
+/// static SHADERS: mtl_gpu::EmbeddedMetallib = mtl_gpu::include_metallib!("shaders.metallib");
+/// ```
+#[macro_export]
+macro_rules! include_metallib {
+    ($path:expr $(,)?) => {
+        $crate::EmbeddedMetallib::new($path, include_bytes!($path))
+    };
+}
+
+impl Library {
+    /// Load a library from an embedded metallib.
+    ///
+    /// The bytes are copied into a `dispatch_data_t` and passed to
+    /// [`Device::new_library_with_data`].
+    pub fn from_embedded(
+        device: &Device,
+        metallib: &EmbeddedMetallib,
+    ) -> Result<Library, mtl_foundation::Error> {
+        let failure = || {
+            mtl_foundation::Error::error(std::ptr::null_mut(), -1, std::ptr::null_mut())
+                .expect("failed to create error object")
+        };
+        if !metallib.has_metallib_header() {
+            return Err(failure());
+        }
+        unsafe {
+            // A null destructor makes dispatch copy the bytes.
+            let data = dispatch_data_create(
+                metallib.bytes.as_ptr().cast(),
+                metallib.bytes.len(),
+                std::ptr::null_mut(),
+                std::ptr::null(),
+            );
+            if data.is_null() {
+                return Err(failure());
+            }
+            let library = device.new_library_with_data(data);
+            dispatch_release(data);
+            library
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metallib_header() {
+        static VALID: EmbeddedMetallib = EmbeddedMetallib::new("valid", b"MTLB\x01\x00");
+        static INVALID: EmbeddedMetallib = EmbeddedMetallib::new("invalid", b"not a metallib");
+        assert!(VALID.has_metallib_header());
+        assert!(!INVALID.has_metallib_header());
+        assert_eq!(INVALID.name(), "invalid");
+    }
+
+    #[test]
+    fn test_from_embedded_rejects_invalid_data() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let metallib = EmbeddedMetallib::new("invalid", b"not a metallib");
+        assert!(Library::from_embedded(&device, &metallib).is_err());
+    }
+}
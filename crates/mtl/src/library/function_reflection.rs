@@ -6,6 +6,8 @@ use std::ptr::NonNull;
 use mtl_foundation::Referencing;
 use mtl_sys::{msg_send_0, sel};
 
+use crate::argument::Bindings;
+
 /// Function reflection information.
 ///
 /// C++ equivalent: `MTL::FunctionReflection`
@@ -63,6 +65,13 @@ impl FunctionReflection {
     pub fn bindings_raw(&self) -> *mut c_void {
         unsafe { msg_send_0(self.as_ptr(), sel!(bindings)) }
     }
+
+    /// Iterate over the function bindings.
+    ///
+    /// C++ equivalent: `NS::Array* bindings() const`
+    pub fn bindings(&self) -> Bindings {
+        unsafe { Bindings::from_array(self.bindings_raw()) }
+    }
 }
 
 impl Clone for FunctionReflection {
@@ -0,0 +1,304 @@
+//! Shader hot-reloading.
+//!
+//! [`HotReload`] watches a `.metal` source file or a precompiled `.metallib`
+//! on disk and rebuilds the [`Library`] whenever the file's modification time
+//! changes. Each successful reload reports a [`FunctionDiff`] describing which
+//! functions were added, removed, or kept, so callers can rebuild only the
+//! pipelines that need it. Rebuilt pipelines can be installed through a
+//! [`PipelineSwap`](crate::pipeline::PipelineSwap), which catches changed
+//! argument bindings before they render garbage.
+//!
+//! Change detection polls the file's modification time; call
+//! [`HotReload::poll`] once per frame, or use [`HotReload::watch`] to poll on a
+//! background thread.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use mtl_gpu::library::HotReload;
+//!
+//! let mut reload = HotReload::new(&device, "shaders/blur.metal");
+//! loop {
+//!     reload.poll(|result| match result {
+//!         Ok((library, diff)) => rebuild_pipelines(&library, &diff),
+//!         Err(err) => eprintln!("shader reload failed: {err}"),
+//!     });
+//!     // ... render frame ...
+//! }
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use mtl_foundation::Referencing;
+
+use super::{CompileOptions, Library};
+use crate::Device;
+
+/// Function-level changes between two reloads of a library.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FunctionDiff {
+    /// Functions present in the new library but not the previous one.
+    pub added: Vec<String>,
+    /// Functions present in the previous library but not the new one.
+    pub removed: Vec<String>,
+    /// Functions present in both libraries. Their code may have changed.
+    pub retained: Vec<String>,
+}
+
+impl FunctionDiff {
+    /// Compute the diff between two sets of function names.
+    pub fn between(old: &[String], new: &[String]) -> Self {
+        let old: BTreeSet<&String> = old.iter().collect();
+        let new: BTreeSet<&String> = new.iter().collect();
+        Self {
+            added: new.difference(&old).map(|s| s.to_string()).collect(),
+            removed: old.difference(&new).map(|s| s.to_string()).collect(),
+            retained: new.intersection(&old).map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Check if any functions were added or removed.
+    pub fn has_interface_changes(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty()
+    }
+}
+
+/// Error produced by a failed reload.
+#[derive(Debug)]
+pub enum HotReloadError {
+    /// The watched file could not be read.
+    Io(std::io::Error),
+    /// The shader source failed to compile or the metallib failed to load.
+    Compile(mtl_foundation::Error),
+}
+
+impl fmt::Display for HotReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotReloadError::Io(err) => write!(f, "failed to read shader file: {}", err),
+            HotReloadError::Compile(err) => write!(f, "failed to build library: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for HotReloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HotReloadError::Io(err) => Some(err),
+            HotReloadError::Compile(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for HotReloadError {
+    fn from(err: std::io::Error) -> Self {
+        HotReloadError::Io(err)
+    }
+}
+
+impl From<mtl_foundation::Error> for HotReloadError {
+    fn from(err: mtl_foundation::Error) -> Self {
+        HotReloadError::Compile(err)
+    }
+}
+
+/// Watches a shader file and rebuilds its [`Library`] on change.
+pub struct HotReload {
+    device: Device,
+    path: PathBuf,
+    options: Option<CompileOptions>,
+    modified: Option<SystemTime>,
+    function_names: Vec<String>,
+}
+
+impl HotReload {
+    /// Watch a `.metal` source file or `.metallib` binary.
+    ///
+    /// Files with a `.metallib` extension are loaded as precompiled libraries;
+    /// anything else is compiled from source. The first call to
+    /// [`poll`](Self::poll) always builds the library.
+    pub fn new(device: &Device, path: impl AsRef<Path>) -> Self {
+        Self {
+            device: device.clone(),
+            path: path.as_ref().to_path_buf(),
+            options: None,
+            modified: None,
+            function_names: Vec::new(),
+        }
+    }
+
+    /// Set the compile options used when building from source.
+    pub fn with_compile_options(mut self, options: CompileOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Get the watched path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the function names of the most recently loaded library.
+    pub fn function_names(&self) -> &[String] {
+        &self.function_names
+    }
+
+    /// Check if the watched file has changed since the last reload.
+    pub fn is_stale(&self) -> bool {
+        match std::fs::metadata(&self.path).and_then(|m| m.modified()) {
+            Ok(modified) => self.modified != Some(modified),
+            Err(_) => false,
+        }
+    }
+
+    /// Rebuild the library unconditionally.
+    pub fn reload(&mut self) -> Result<(Library, FunctionDiff), HotReloadError> {
+        let modified = std::fs::metadata(&self.path)?.modified()?;
+        let library = if self.is_metallib() {
+            let url = mtl_foundation::Url::from_path(&self.path).ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "path is not valid UTF-8")
+            })?;
+            unsafe { self.device.new_library_with_url(url.as_ptr())? }
+        } else {
+            let source = std::fs::read_to_string(&self.path)?;
+            self.device
+                .new_library_with_source(&source, self.options.as_ref())?
+        };
+
+        // Only record the timestamp once the build succeeded; `poll` decides
+        // what to do with failed builds.
+        self.modified = Some(modified);
+        let names = library.function_names();
+        let diff = FunctionDiff::between(&self.function_names, &names);
+        self.function_names = names;
+        Ok((library, diff))
+    }
+
+    /// Rebuild the library if the file changed and pass the result to `callback`.
+    ///
+    /// A failed build is reported once per file modification; the watcher
+    /// then waits for the next change before trying again.
+    ///
+    /// Returns `true` if a reload was attempted.
+    pub fn poll<F>(&mut self, callback: F) -> bool
+    where
+        F: FnOnce(Result<(Library, FunctionDiff), HotReloadError>),
+    {
+        if !self.is_stale() {
+            return false;
+        }
+        let result = self.reload();
+        if result.is_err() {
+            // Don't retry the same broken file every poll.
+            self.modified = std::fs::metadata(&self.path)
+                .and_then(|m| m.modified())
+                .ok();
+        }
+        callback(result);
+        true
+    }
+
+    /// Poll for changes on a background thread every `interval`.
+    ///
+    /// The callback runs on the watcher thread. The watcher stops when the
+    /// returned handle is dropped or [`HotReloadHandle::stop`] is called.
+    pub fn watch<F>(mut self, interval: Duration, mut callback: F) -> HotReloadHandle
+    where
+        F: FnMut(Result<(Library, FunctionDiff), HotReloadError>) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = Arc::clone(&running);
+        let thread = std::thread::spawn(move || {
+            while thread_running.load(Ordering::Acquire) {
+                self.poll(&mut callback);
+                std::thread::sleep(interval);
+            }
+        });
+        HotReloadHandle {
+            running,
+            thread: Some(thread),
+        }
+    }
+
+    fn is_metallib(&self) -> bool {
+        self.path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("metallib"))
+    }
+}
+
+impl fmt::Debug for HotReload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HotReload")
+            .field("path", &self.path)
+            .field("function_names", &self.function_names)
+            .finish()
+    }
+}
+
+/// Handle to a background [`HotReload`] watcher.
+///
+/// Dropping the handle stops the watcher thread.
+pub struct HotReloadHandle {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HotReloadHandle {
+    /// Stop the watcher and wait for its thread to exit.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for HotReloadHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl fmt::Debug for HotReloadHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HotReloadHandle")
+            .field("running", &self.running.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_function_diff() {
+        let diff = FunctionDiff::between(&names(&["a", "b"]), &names(&["b", "c"]));
+        assert_eq!(diff.added, names(&["c"]));
+        assert_eq!(diff.removed, names(&["a"]));
+        assert_eq!(diff.retained, names(&["b"]));
+        assert!(diff.has_interface_changes());
+    }
+
+    #[test]
+    fn test_function_diff_unchanged() {
+        let diff = FunctionDiff::between(&names(&["a"]), &names(&["a"]));
+        assert!(!diff.has_interface_changes());
+        assert_eq!(diff.retained, names(&["a"]));
+    }
+}
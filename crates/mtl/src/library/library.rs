@@ -8,10 +8,9 @@ use mtl_sys::{msg_send_0, msg_send_1, msg_send_2, sel};
 
 use crate::enums::LibraryType;
 
-use super::{
-    Function, FunctionConstantValues, FunctionDescriptor, FunctionReflection,
-    IntersectionFunctionDescriptor,
-};
+#[cfg(feature = "blocks")]
+use super::FunctionConstantValues;
+use super::{Function, FunctionDescriptor, FunctionReflection, IntersectionFunctionDescriptor};
 
 /// A collection of compiled shader functions.
 ///
@@ -200,6 +199,17 @@ impl Library {
         }
     }
 
+    /// Iterate over every function in the library.
+    ///
+    /// Each function is created on demand from
+    /// [`function_names`](Self::function_names); use
+    /// [`Function::name`] and [`Function::function_type`] to tell them apart.
+    pub fn functions(&self) -> impl Iterator<Item = Function> + '_ {
+        self.function_names()
+            .into_iter()
+            .filter_map(|name| self.new_function_with_name(&name))
+    }
+
     /// Create a function with a descriptor.
     ///
     /// C++ equivalent: `Function* newFunction(const FunctionDescriptor*, NS::Error**)`
@@ -294,6 +304,7 @@ impl Library {
     /// Create a function with constant values asynchronously.
     ///
     /// C++ equivalent: `void newFunction(const NS::String*, const FunctionConstantValues*, void (^)(Function*, Error*))`
+    #[cfg(feature = "blocks")]
     pub fn new_function_with_name_and_constants_async<F>(
         &self,
         name: &str,
@@ -343,6 +354,7 @@ impl Library {
     /// Create a function with a descriptor asynchronously.
     ///
     /// C++ equivalent: `void newFunction(const FunctionDescriptor*, void (^)(Function*, Error*))`
+    #[cfg(feature = "blocks")]
     pub fn new_function_with_descriptor_async<F>(
         &self,
         descriptor: &FunctionDescriptor,
@@ -382,6 +394,7 @@ impl Library {
     /// Create an intersection function with a descriptor asynchronously.
     ///
     /// C++ equivalent: `void newIntersectionFunction(const IntersectionFunctionDescriptor*, void (^)(Function*, Error*))`
+    #[cfg(feature = "blocks")]
     pub fn new_intersection_function_async<F>(
         &self,
         descriptor: &IntersectionFunctionDescriptor,
@@ -0,0 +1,327 @@
+//! Offline `.metallib` compilation.
+//!
+//! [`compile_to_metallib`] compiles Metal source with the device's runtime
+//! compiler and serializes the result as a dynamic library, so a build
+//! script running on a Mac can produce `.metallib` files without the Xcode
+//! command line tools. Load the output with
+//! [`Device::new_library_with_url`](crate::Device::new_library_with_url),
+//! or embed it with [`include_metallib!`](crate::include_metallib).
+//!
+//! With the `xcrun` feature, [`MetalCompiler`] runs `xcrun metal` instead,
+//! producing a regular executable metallib with the full set of compiler
+//! flags (language standard, macros, include paths).
+//!
+//! # Example
+//!
+//! ```ignore
+//! // build.rs
+//! let source = std::fs::read_to_string("shaders/blur.metal")?;
+//! let bytes = MetalCompiler::new()
+//!     .define("RADIUS", Some("4"))
+//!     .compile(&source)?;
+//! std::fs::write(out_dir.join("blur.metallib"), bytes)?;
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "xcrun")]
+use std::ffi::OsString;
+
+use super::CompileOptions;
+use crate::Device;
+use crate::enums::LibraryType;
+
+/// Error produced while compiling a metallib.
+#[derive(Debug)]
+pub enum MetallibError {
+    /// A temporary file could not be written or read, or the tool could not
+    /// be launched.
+    Io(std::io::Error),
+    /// The runtime compiler rejected the source or could not serialize it.
+    Compile(mtl_foundation::Error),
+    /// An external tool exited with an error.
+    Tool {
+        /// The command that failed.
+        command: String,
+        /// The exit code, if the tool was not killed by a signal.
+        status: Option<i32>,
+        /// The tool's diagnostic output.
+        stderr: String,
+    },
+}
+
+impl fmt::Display for MetallibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetallibError::Io(err) => write!(f, "metallib I/O error: {}", err),
+            MetallibError::Compile(err) => write!(f, "failed to compile metallib: {}", err),
+            MetallibError::Tool {
+                command,
+                status: Some(code),
+                stderr,
+            } => write!(f, "`{}` exited with status {}: {}", command, code, stderr),
+            MetallibError::Tool {
+                command, stderr, ..
+            } => write!(f, "`{}` was terminated: {}", command, stderr),
+        }
+    }
+}
+
+impl std::error::Error for MetallibError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MetallibError::Io(err) => Some(err),
+            MetallibError::Compile(err) => Some(err),
+            MetallibError::Tool { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for MetallibError {
+    fn from(err: std::io::Error) -> Self {
+        MetallibError::Io(err)
+    }
+}
+
+impl From<mtl_foundation::Error> for MetallibError {
+    fn from(err: mtl_foundation::Error) -> Self {
+        MetallibError::Compile(err)
+    }
+}
+
+/// A file in the temporary directory that is removed on drop.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(extension: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "mtl-gpu-{}-{}.{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            extension
+        );
+        Self(std::env::temp_dir().join(name))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Compile Metal source into the bytes of a `.metallib` file using the
+/// device's runtime compiler.
+///
+/// The library is built as a [`LibraryType::DYNAMIC`] library with the given
+/// `install_name` (for example `@executable_path/blur.metallib`), which is
+/// how other libraries will refer to it when linking. If `options` are
+/// given, their library type and install name are overwritten.
+///
+/// Requires [`Device::supports_dynamic_libraries`].
+pub fn compile_to_metallib(
+    device: &Device,
+    source: &str,
+    install_name: &str,
+    options: Option<&CompileOptions>,
+) -> Result<Vec<u8>, MetallibError> {
+    let owned;
+    let options = match options {
+        Some(options) => options,
+        None => {
+            owned = CompileOptions::new()
+                .ok_or_else(|| std::io::Error::other("failed to create compile options"))?;
+            &owned
+        }
+    };
+    options.set_library_type(LibraryType::DYNAMIC);
+    options.set_install_name(install_name);
+
+    let library = device.new_library_with_source(source, Some(options))?;
+    let dynamic = device.new_dynamic_library(&library)?;
+
+    let output = TempFile::new("metallib");
+    let url = mtl_foundation::Url::from_path(output.path())
+        .ok_or_else(|| std::io::Error::other("failed to create file URL"))?;
+    dynamic.serialize_to_url(&url)?;
+    Ok(std::fs::read(output.path())?)
+}
+
+// ============================================================================
+// MetalCompiler
+// ============================================================================
+
+/// Compiles Metal source to a `.metallib` with `xcrun metal`.
+///
+/// Requires the Xcode command line tools.
+#[cfg(feature = "xcrun")]
+#[derive(Clone, Debug)]
+pub struct MetalCompiler {
+    sdk: String,
+    std: Option<String>,
+    defines: Vec<(String, Option<String>)>,
+    include_dirs: Vec<PathBuf>,
+    fast_math: bool,
+    debug_info: bool,
+}
+
+#[cfg(feature = "xcrun")]
+impl MetalCompiler {
+    /// Create a compiler targeting the `macosx` SDK with default flags.
+    pub fn new() -> Self {
+        Self {
+            sdk: "macosx".to_string(),
+            std: None,
+            defines: Vec::new(),
+            include_dirs: Vec::new(),
+            fast_math: true,
+            debug_info: false,
+        }
+    }
+
+    /// Set the SDK passed to `xcrun -sdk` (for example `iphoneos`).
+    pub fn sdk(mut self, sdk: &str) -> Self {
+        self.sdk = sdk.to_string();
+        self
+    }
+
+    /// Set the language standard (for example `metal3.1`).
+    pub fn std(mut self, std: &str) -> Self {
+        self.std = Some(std.to_string());
+        self
+    }
+
+    /// Define a preprocessor macro, with an optional value.
+    pub fn define(mut self, name: &str, value: Option<&str>) -> Self {
+        self.defines
+            .push((name.to_string(), value.map(str::to_string)));
+        self
+    }
+
+    /// Add a directory to the `#include` search path.
+    pub fn include_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.include_dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Enable or disable fast math. Enabled by default.
+    pub fn fast_math(mut self, enabled: bool) -> Self {
+        self.fast_math = enabled;
+        self
+    }
+
+    /// Embed debug information and sources for GPU frame capture.
+    pub fn debug_info(mut self, enabled: bool) -> Self {
+        self.debug_info = enabled;
+        self
+    }
+
+    /// Compile `source` and return the metallib's bytes.
+    pub fn compile(&self, source: &str) -> Result<Vec<u8>, MetallibError> {
+        let input = TempFile::new("metal");
+        std::fs::write(input.path(), source)?;
+        let output = TempFile::new("metallib");
+        self.compile_file_to(input.path(), output.path())?;
+        Ok(std::fs::read(output.path())?)
+    }
+
+    /// Compile the source file at `input` into a metallib at `output`.
+    pub fn compile_file_to(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> Result<(), MetallibError> {
+        let args = self.args(input.as_ref(), output.as_ref());
+        let result = std::process::Command::new("xcrun").args(&args).output()?;
+        if result.status.success() {
+            return Ok(());
+        }
+        let command = std::iter::once("xcrun".into())
+            .chain(args)
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Err(MetallibError::Tool {
+            command,
+            status: result.status.code(),
+            stderr: String::from_utf8_lossy(&result.stderr).into_owned(),
+        })
+    }
+
+    /// Build the `xcrun` argument list.
+    fn args(&self, input: &Path, output: &Path) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["-sdk".into(), self.sdk.clone().into(), "metal".into()];
+        if let Some(std) = &self.std {
+            args.push(format!("-std={}", std).into());
+        }
+        for (name, value) in &self.defines {
+            args.push(match value {
+                Some(value) => format!("-D{}={}", name, value).into(),
+                None => format!("-D{}", name).into(),
+            });
+        }
+        for dir in &self.include_dirs {
+            args.push("-I".into());
+            args.push(dir.into());
+        }
+        if !self.fast_math {
+            args.push("-fno-fast-math".into());
+        }
+        if self.debug_info {
+            args.push("-frecord-sources".into());
+            args.push("-gline-tables-only".into());
+        }
+        args.push("-o".into());
+        args.push(output.into());
+        args.push(input.into());
+        args
+    }
+}
+
+#[cfg(feature = "xcrun")]
+impl Default for MetalCompiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, feature = "xcrun"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compiler_args() {
+        let args = MetalCompiler::new()
+            .std("metal3.1")
+            .define("RADIUS", Some("4"))
+            .define("HDR", None)
+            .include_dir("shaders/include")
+            .fast_math(false)
+            .args(Path::new("in.metal"), Path::new("out.metallib"));
+        let args: Vec<_> = args.iter().map(|arg| arg.to_str().unwrap()).collect();
+        assert_eq!(
+            args,
+            [
+                "-sdk",
+                "macosx",
+                "metal",
+                "-std=metal3.1",
+                "-DRADIUS=4",
+                "-DHDR",
+                "-I",
+                "shaders/include",
+                "-fno-fast-math",
+                "-o",
+                "out.metallib",
+                "in.metal",
+            ]
+        );
+    }
+}
@@ -7,27 +7,36 @@
 mod attribute;
 mod compile_options;
 mod dynamic_library;
+mod embedded;
 mod function;
 mod function_constant;
 mod function_constant_values;
 mod function_descriptor;
 mod function_reflection;
+mod hot_reload;
 mod intersection_function_descriptor;
 mod library;
 mod linked_functions;
+mod metallib;
 mod vertex_attribute;
 
 pub use attribute::Attribute;
 pub use compile_options::CompileOptions;
 pub use dynamic_library::DynamicLibrary;
+pub(crate) use dynamic_library::{file_url, libraries_from_array, with_library_array};
+pub use embedded::EmbeddedMetallib;
 pub use function::Function;
 pub use function_constant::FunctionConstant;
 pub use function_constant_values::FunctionConstantValues;
 pub use function_descriptor::FunctionDescriptor;
 pub use function_reflection::FunctionReflection;
+pub use hot_reload::{FunctionDiff, HotReload, HotReloadError, HotReloadHandle};
 pub use intersection_function_descriptor::IntersectionFunctionDescriptor;
 pub use library::Library;
 pub use linked_functions::LinkedFunctions;
+#[cfg(feature = "xcrun")]
+pub use metallib::MetalCompiler;
+pub use metallib::{MetallibError, compile_to_metallib};
 pub use vertex_attribute::VertexAttribute;
 
 #[cfg(test)]
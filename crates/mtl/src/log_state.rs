@@ -201,6 +201,7 @@ impl LogState {
     /// - `category` - The category of the log message
     /// - `level` - The severity level of the log message
     /// - `message` - The actual log message content
+    #[cfg(feature = "blocks")]
     pub fn add_log_handler<F>(&self, handler: F)
     where
         F: Fn(&str, &str, LogLevel, &str) + Send + 'static,
@@ -231,6 +232,45 @@ impl LogState {
         // Transfer ownership to Metal
         std::mem::forget(block);
     }
+
+    /// Forward shader log messages to the [`log`] crate.
+    ///
+    /// Messages are logged with the target `mtl_gpu::shader` as
+    /// `subsystem/category: message`. Debug messages map to
+    /// [`log::Level::Debug`], info and notice to [`log::Level::Info`], and
+    /// error and fault to [`log::Level::Error`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let log_state = device.new_log_state(&LogStateDescriptor::new().unwrap())?;
+    /// log_state.add_rust_log_handler();
+    /// queue_descriptor.set_log_state(&log_state);
+    /// ```
+    #[cfg(feature = "log")]
+    pub fn add_rust_log_handler(&self) {
+        self.add_log_handler(|subsystem, category, level, message| {
+            log::log!(
+                target: "mtl_gpu::shader",
+                rust_log_level(level),
+                "{}/{}: {}",
+                subsystem,
+                category,
+                message
+            );
+        });
+    }
+}
+
+/// Map a shader log level to a [`log`] crate level.
+#[cfg(feature = "log")]
+fn rust_log_level(level: LogLevel) -> log::Level {
+    match level {
+        LogLevel::DEBUG => log::Level::Debug,
+        LogLevel::INFO | LogLevel::NOTICE => log::Level::Info,
+        LogLevel::ERROR | LogLevel::FAULT => log::Level::Error,
+        _ => log::Level::Trace,
+    }
 }
 
 /// Convert an NSString pointer to a Rust String.
@@ -238,6 +278,7 @@ impl LogState {
 /// # Safety
 ///
 /// The pointer must be a valid NSString or null.
+#[cfg(feature = "blocks")]
 unsafe fn nsstring_to_str(ns_string: *mut c_void) -> String {
     if ns_string.is_null() {
         return String::new();
@@ -0,0 +1,277 @@
+//! Fixed-size material parameter blocks in a single GPU buffer.
+//!
+//! Data-driven material systems keep every material's parameters in one
+//! large buffer and have shaders index it, instead of binding a buffer per
+//! draw. [`MaterialBlockAllocator`] hands out a stable slot per material,
+//! keeps a CPU copy of each block, and tracks the range of slots changed
+//! since the last [`flush`](MaterialBlockAllocator::flush) so only that
+//! range is uploaded.
+//!
+//! Slots are addressed with the same generational [`Handle`] used by
+//! [`ResourceStore`], so a freed material's handle can't alias the next
+//! material placed in its slot. [`Handle::index`] is the element index the
+//! shader uses.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // struct Material { float4 albedo; float roughness; float metallic; uint albedo_texture; uint _pad; };
+//! // fragment float4 shade(..., constant Material* materials [[buffer(2)]], ...)
+//! let mut materials: MaterialBlockAllocator<Material> =
+//!     MaterialBlockAllocator::new(&device, 4096, ResourceOptions::STORAGE_MODE_PRIVATE)?;
+//!
+//! let brick = materials.allocate(Material { albedo: [0.6, 0.3, 0.2, 1.0], ..Default::default() })?;
+//! materials.update(brick, |m| m.roughness = 0.9);
+//!
+//! // Once per frame, before encoding draws:
+//! materials.flush(&command_buffer)?;
+//! encoder.set_fragment_buffer(materials.buffer(), 0, 2);
+//! encoder.set_fragment_value(&brick.index(), 3);
+//! ```
+//!
+//! [`ResourceStore`]: crate::ResourceStore
+
+use std::fmt;
+use std::ops::Range;
+
+use mtl_foundation::UInteger;
+
+use crate::enums::ResourceOptions;
+use crate::gpu_vec::Pod;
+use crate::resource_store::{Arena, Handle};
+use crate::{BlitCommandEncoder, Buffer, CommandBuffer, Device};
+
+/// Error produced by a [`MaterialBlockAllocator`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MaterialBlockError {
+    /// The block buffer, a staging buffer, or a blit encoder could not be
+    /// created.
+    Allocation,
+    /// Every slot is in use.
+    Full { capacity: u32 },
+    /// The requested capacity overflows `usize` bytes.
+    CapacityOverflow,
+}
+
+impl fmt::Display for MaterialBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaterialBlockError::Allocation => {
+                write!(f, "failed to allocate material block resources")
+            }
+            MaterialBlockError::Full { capacity } => {
+                write!(f, "all {} material blocks are in use", capacity)
+            }
+            MaterialBlockError::CapacityOverflow => {
+                write!(f, "material block capacity overflow")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MaterialBlockError {}
+
+/// A buffer of fixed-size `T` blocks with stable, generational slots.
+pub struct MaterialBlockAllocator<T: Pod> {
+    device: Device,
+    buffer: Buffer,
+    options: ResourceOptions,
+    capacity: u32,
+    blocks: Arena<T>,
+    dirty: Option<Range<u32>>,
+}
+
+impl<T: Pod> MaterialBlockAllocator<T> {
+    /// Create an allocator with room for `capacity` blocks.
+    ///
+    /// Any storage mode works: CPU-visible buffers are written directly on
+    /// [`flush`](Self::flush), private buffers through a staging blit.
+    pub fn new(
+        device: &Device,
+        capacity: u32,
+        options: ResourceOptions,
+    ) -> Result<Self, MaterialBlockError> {
+        let length = (capacity as usize)
+            .checked_mul(size_of::<T>())
+            .ok_or(MaterialBlockError::CapacityOverflow)?;
+        let buffer = device
+            .new_buffer(length.max(1), options)
+            .ok_or(MaterialBlockError::Allocation)?;
+        Ok(Self {
+            device: device.clone(),
+            buffer,
+            options,
+            capacity,
+            blocks: Arena::new(),
+            dirty: None,
+        })
+    }
+
+    /// Get the buffer holding every block.
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Get the GPU address of the block buffer, for storing in an argument
+    /// buffer or bindless table.
+    pub fn gpu_address(&self) -> u64 {
+        self.buffer.gpu_address()
+    }
+
+    /// Get the size of one block in bytes.
+    pub fn stride(&self) -> UInteger {
+        size_of::<T>()
+    }
+
+    /// Get the byte offset of `handle`'s block within the buffer.
+    pub fn offset(&self, handle: Handle<T>) -> UInteger {
+        handle.index() as UInteger * self.stride()
+    }
+
+    /// Get the number of slots.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Get the number of allocated blocks.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Check if no blocks are allocated.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.len() == 0
+    }
+
+    /// Store `value` in a free slot and return its handle.
+    pub fn allocate(&mut self, value: T) -> Result<Handle<T>, MaterialBlockError> {
+        if self.blocks.len() >= self.capacity as usize {
+            return Err(MaterialBlockError::Full {
+                capacity: self.capacity,
+            });
+        }
+        let handle = self.blocks.insert(value);
+        self.mark_dirty(handle.index());
+        Ok(handle)
+    }
+
+    /// Get the CPU copy of a block.
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.blocks.get(handle)
+    }
+
+    /// Replace a block's value. Returns `false` for a stale handle.
+    pub fn set(&mut self, handle: Handle<T>, value: T) -> bool {
+        self.update(handle, |block| *block = value)
+    }
+
+    /// Modify a block in place. Returns `false` for a stale handle.
+    pub fn update<F>(&mut self, handle: Handle<T>, f: F) -> bool
+    where
+        F: FnOnce(&mut T),
+    {
+        let Some(block) = self.blocks.get_mut(handle) else {
+            return false;
+        };
+        f(block);
+        self.mark_dirty(handle.index());
+        true
+    }
+
+    /// Free a block's slot for reuse and return its last value.
+    ///
+    /// The GPU copy isn't cleared; draws already encoded with the handle's
+    /// index keep reading the old parameters until the slot is reused.
+    pub fn free(&mut self, handle: Handle<T>) -> Option<T> {
+        self.blocks.remove(handle)
+    }
+
+    /// Iterate over allocated blocks.
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.blocks.iter()
+    }
+
+    /// Get the range of slot indices changed since the last flush.
+    pub fn dirty_range(&self) -> Option<Range<u32>> {
+        self.dirty.clone()
+    }
+
+    /// Upload the dirty range to the GPU buffer.
+    ///
+    /// CPU-visible buffers are written immediately (with `didModifyRange`
+    /// for managed storage) and `command_buffer` is unused. Private buffers
+    /// get a staging buffer and a blit encoded on `command_buffer`, which
+    /// must be committed before draws that read the blocks.
+    ///
+    /// Returns the byte range uploaded, or `None` if nothing was dirty.
+    pub fn flush(
+        &mut self,
+        command_buffer: &CommandBuffer,
+    ) -> Result<Option<Range<UInteger>>, MaterialBlockError> {
+        let Some(dirty) = self.dirty.clone() else {
+            return Ok(None);
+        };
+        let staged: Vec<T> = dirty
+            .clone()
+            .map(|index| {
+                // Free slots are uploaded as zeroes; any bit pattern is a valid `T`.
+                self.blocks
+                    .get_index(index)
+                    .copied()
+                    .unwrap_or_else(|| unsafe { std::mem::zeroed() })
+            })
+            .collect();
+        let offset = dirty.start as UInteger * self.stride();
+        let length = staged.len() * self.stride();
+
+        match self.buffer.contents() {
+            Some(contents) => {
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        staged.as_ptr(),
+                        (contents as *mut T).add(dirty.start as usize),
+                        staged.len(),
+                    );
+                }
+                let storage = self.options.bits() & (0xf << 4);
+                if storage == ResourceOptions::STORAGE_MODE_MANAGED.bits() {
+                    self.buffer.did_modify_range(offset, length);
+                }
+            }
+            None => {
+                let bytes =
+                    unsafe { std::slice::from_raw_parts(staged.as_ptr() as *const u8, length) };
+                let staging = self
+                    .device
+                    .new_buffer_with_bytes(bytes, ResourceOptions::STORAGE_MODE_SHARED)
+                    .ok_or(MaterialBlockError::Allocation)?;
+                let blit =
+                    unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+                        .ok_or(MaterialBlockError::Allocation)?;
+                blit.copy_from_buffer_to_buffer(&staging, 0, &self.buffer, offset, length);
+                blit.end_encoding();
+            }
+        }
+
+        self.dirty = None;
+        Ok(Some(offset..offset + length))
+    }
+
+    fn mark_dirty(&mut self, index: u32) {
+        self.dirty = Some(match self.dirty.take() {
+            Some(range) => range.start.min(index)..range.end.max(index + 1),
+            None => index..index + 1,
+        });
+    }
+}
+
+impl<T: Pod> fmt::Debug for MaterialBlockAllocator<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaterialBlockAllocator")
+            .field("stride", &self.stride())
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .field("dirty", &self.dirty)
+            .finish()
+    }
+}
@@ -0,0 +1,342 @@
+//! Procedural meshes for tests, examples and prototypes.
+//!
+//! [`cube`], [`sphere`] and [`plane`] build indexed triangle lists of
+//! [`MeshVertex`], which interleaves position, normal and texture
+//! coordinates in one buffer. [`MeshData::vertex_descriptor`] describes that
+//! layout; a matching shader input is:
+//!
+//! ```text
+//! struct MeshVertex {
+//!     float3 position [[attribute(0)]];
+//!     float3 normal   [[attribute(1)]];
+//!     float2 uv       [[attribute(2)]];
+//! };
+//! ```
+//!
+//! Triangles wind counter-clockwise when seen from outside (or, for the
+//! plane, from +Y). Metal treats clockwise triangles as front-facing by
+//! default, so set `Winding::COUNTER_CLOCKWISE` on the encoder before
+//! enabling back-face culling.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let descriptor = MeshData::vertex_descriptor(MESH_VERTEX_BUFFER_INDEX).unwrap();
+//! pipeline_descriptor.set_vertex_descriptor(Some(&descriptor));
+//!
+//! let sphere = mesh::sphere(1.0, 32, 16).upload(&device, ResourceOptions::default()).unwrap();
+//! encoder.set_front_facing_winding(Winding::COUNTER_CLOCKWISE);
+//! sphere.draw(&encoder);
+//! ```
+
+use std::f32::consts::PI;
+
+use mtl_foundation::UInteger;
+
+use crate::enums::{IndexType, PrimitiveType, ResourceOptions, VertexFormat};
+use crate::{Buffer, Device, Pod, RenderCommandEncoder, VertexDescriptor};
+
+/// Vertex buffer index used by [`Mesh::draw`].
+pub const MESH_VERTEX_BUFFER_INDEX: UInteger = 0;
+
+/// An interleaved vertex: position, normal and texture coordinates.
+///
+/// The layout is 32 bytes with no padding; see
+/// [`MeshData::vertex_descriptor`].
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MeshVertex {
+    /// Position in object space.
+    pub position: [f32; 3],
+    /// Unit-length normal.
+    pub normal: [f32; 3],
+    /// Texture coordinates, with `v` increasing downwards.
+    pub uv: [f32; 2],
+}
+
+unsafe impl Pod for MeshVertex {}
+
+impl MeshVertex {
+    /// Byte distance between consecutive vertices.
+    pub const STRIDE: UInteger = size_of::<MeshVertex>();
+
+    const fn new(position: [f32; 3], normal: [f32; 3], uv: [f32; 2]) -> Self {
+        Self {
+            position,
+            normal,
+            uv,
+        }
+    }
+}
+
+/// CPU-side vertices and triangle indices.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshData {
+    /// Vertices.
+    pub vertices: Vec<MeshVertex>,
+    /// Three indices per triangle.
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    /// Describe the [`MeshVertex`] layout read from vertex buffer
+    /// `buffer_index`.
+    pub fn vertex_descriptor(buffer_index: UInteger) -> Option<VertexDescriptor> {
+        let descriptor = VertexDescriptor::new()?;
+        let attributes = descriptor.attributes();
+        for (index, (format, offset)) in [
+            (VertexFormat::FLOAT3, 0),
+            (VertexFormat::FLOAT3, 12),
+            (VertexFormat::FLOAT2, 24),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let attribute = attributes.object(index as UInteger)?;
+            attribute.set_format(format);
+            attribute.set_offset(offset);
+            attribute.set_buffer_index(buffer_index);
+        }
+        descriptor
+            .layouts()
+            .object(buffer_index)?
+            .set_stride(MeshVertex::STRIDE);
+        Some(descriptor)
+    }
+
+    /// Get the number of triangles.
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Copy the vertices and indices into new buffers.
+    pub fn upload(&self, device: &Device, options: ResourceOptions) -> Option<Mesh> {
+        let vertex_buffer = device.new_buffer_with_bytes(as_bytes(&self.vertices), options)?;
+        let index_buffer = device.new_buffer_with_bytes(as_bytes(&self.indices), options)?;
+        Some(Mesh {
+            vertex_buffer,
+            index_buffer,
+            vertex_count: self.vertices.len() as UInteger,
+            index_count: self.indices.len() as UInteger,
+        })
+    }
+}
+
+fn as_bytes<T: Pod>(data: &[T]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, size_of_val(data)) }
+}
+
+/// A mesh uploaded to GPU buffers by [`MeshData::upload`].
+#[derive(Clone, Debug)]
+pub struct Mesh {
+    /// Interleaved [`MeshVertex`] data.
+    pub vertex_buffer: Buffer,
+    /// `u32` triangle indices.
+    pub index_buffer: Buffer,
+    /// Number of vertices.
+    pub vertex_count: UInteger,
+    /// Number of indices.
+    pub index_count: UInteger,
+}
+
+impl Mesh {
+    /// Get the index type of [`index_buffer`](Self::index_buffer).
+    pub fn index_type(&self) -> IndexType {
+        IndexType::UINT32
+    }
+
+    /// Bind the vertex buffer at [`MESH_VERTEX_BUFFER_INDEX`] and draw the
+    /// triangles.
+    pub fn draw(&self, encoder: &RenderCommandEncoder) {
+        encoder.set_vertex_buffer(&self.vertex_buffer, 0, MESH_VERTEX_BUFFER_INDEX);
+        encoder.draw_indexed_primitives(
+            PrimitiveType::TRIANGLE,
+            self.index_count,
+            self.index_type(),
+            &self.index_buffer,
+            0,
+        );
+    }
+}
+
+// ============================================================================
+// Generators
+// ============================================================================
+
+/// An axis-aligned cube centered on the origin with edges `size` long.
+///
+/// Each face has its own four vertices so normals and texture coordinates
+/// are not shared across edges.
+pub fn cube(size: f32) -> MeshData {
+    let h = size * 0.5;
+    // Each face: normal, then the axes that map to u and v.
+    let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+        ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+        ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+        ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+        ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ];
+
+    let mut mesh = MeshData::default();
+    for (normal, u_axis, v_axis) in faces {
+        let base = mesh.vertices.len() as u32;
+        for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            let position = std::array::from_fn(|i| {
+                (normal[i] + (u * 2.0 - 1.0) * u_axis[i] + (v * 2.0 - 1.0) * v_axis[i]) * h
+            });
+            mesh.vertices
+                .push(MeshVertex::new(position, normal, [u, v]));
+        }
+        // u x v points against the normal, so (0, 2, 1) winds
+        // counter-clockwise seen from outside.
+        mesh.indices
+            .extend([base, base + 2, base + 1, base, base + 3, base + 2]);
+    }
+    mesh
+}
+
+/// A UV sphere centered on the origin.
+///
+/// `segments` is the number of divisions around the Y axis (at least 3)
+/// and `rings` the number from pole to pole (at least 2).
+pub fn sphere(radius: f32, segments: u32, rings: u32) -> MeshData {
+    let segments = segments.max(3);
+    let rings = rings.max(2);
+
+    let mut mesh = MeshData::default();
+    for ring in 0..=rings {
+        let v = ring as f32 / rings as f32;
+        let (sin_theta, cos_theta) = (v * PI).sin_cos();
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let (sin_phi, cos_phi) = (u * 2.0 * PI).sin_cos();
+            let normal = [sin_theta * cos_phi, cos_theta, -sin_theta * sin_phi];
+            let position = normal.map(|n| n * radius);
+            mesh.vertices
+                .push(MeshVertex::new(position, normal, [u, v]));
+        }
+    }
+
+    let row = segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let a = ring * row + segment;
+            let b = a + row;
+            // Skip the degenerate triangles at the poles.
+            if ring != 0 {
+                mesh.indices.extend([a, b, a + 1]);
+            }
+            if ring != rings - 1 {
+                mesh.indices.extend([a + 1, b, b + 1]);
+            }
+        }
+    }
+    mesh
+}
+
+/// A plane in the XZ plane, centered on the origin and facing +Y, split
+/// into `segments x segments` quads.
+pub fn plane(width: f32, depth: f32, segments: u32) -> MeshData {
+    let segments = segments.max(1);
+
+    let mut mesh = MeshData::default();
+    for row in 0..=segments {
+        let v = row as f32 / segments as f32;
+        for column in 0..=segments {
+            let u = column as f32 / segments as f32;
+            let position = [(u - 0.5) * width, 0.0, (v - 0.5) * depth];
+            mesh.vertices
+                .push(MeshVertex::new(position, [0.0, 1.0, 0.0], [u, v]));
+        }
+    }
+
+    let row = segments + 1;
+    for z in 0..segments {
+        for x in 0..segments {
+            let a = z * row + x;
+            let b = a + row;
+            mesh.indices.extend([a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+        [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+    }
+
+    fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+    }
+
+    fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+    }
+
+    /// Check indices are in range, normals are unit length and every
+    /// triangle winds counter-clockwise around its vertex normals.
+    fn check(mesh: &MeshData) {
+        assert_eq!(mesh.indices.len() % 3, 0);
+        for vertex in &mesh.vertices {
+            assert!((dot(vertex.normal, vertex.normal) - 1.0).abs() < 1e-4);
+        }
+        for triangle in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+            let face = cross(sub(b.position, a.position), sub(c.position, a.position));
+            assert!(
+                dot(face, a.normal) > 0.0,
+                "triangle {:?} is wound clockwise",
+                triangle
+            );
+        }
+    }
+
+    #[test]
+    fn test_generators() {
+        let cube = cube(2.0);
+        assert_eq!(cube.vertices.len(), 24);
+        assert_eq!(cube.triangle_count(), 12);
+        assert!(
+            cube.vertices
+                .iter()
+                .all(|v| v.position.iter().all(|p| p.abs() == 1.0))
+        );
+        check(&cube);
+
+        let sphere = sphere(1.0, 8, 4);
+        assert_eq!(sphere.vertices.len(), 9 * 5);
+        assert_eq!(sphere.triangle_count(), 2 * 8 * 4 - 2 * 8);
+        check(&sphere);
+
+        let plane = plane(4.0, 2.0, 3);
+        assert_eq!(plane.vertices.len(), 16);
+        assert_eq!(plane.triangle_count(), 18);
+        check(&plane);
+    }
+
+    #[test]
+    fn test_upload() {
+        assert_eq!(MeshVertex::STRIDE, 32);
+        let device = crate::device::system_default().expect("no Metal device");
+        let mesh = cube(1.0)
+            .upload(&device, ResourceOptions::default())
+            .unwrap();
+        assert_eq!(mesh.vertex_buffer.length(), 24 * MeshVertex::STRIDE);
+        assert_eq!(mesh.index_count, 36);
+
+        let descriptor = MeshData::vertex_descriptor(MESH_VERTEX_BUFFER_INDEX).unwrap();
+        let normal = descriptor.attributes().object(1).unwrap();
+        assert_eq!(normal.format(), VertexFormat::FLOAT3);
+        assert_eq!(normal.offset(), 12);
+    }
+}
@@ -0,0 +1,407 @@
+//! Metal Shading Language constant buffer layout for Rust structs.
+//!
+//! A `#[repr(C)]` Rust struct does not match the MSL struct with the same
+//! fields: MSL's `float3` is 16 bytes with 16-byte alignment, while
+//! `[f32; 3]` is 12 bytes with 4-byte alignment, so every field after a
+//! `float3` ends up at the wrong offset. [`MslLayout`] computes the MSL size,
+//! alignment and field offsets of a type and serializes it with the padding
+//! MSL expects.
+//!
+//! Rust types map to MSL types as follows:
+//!
+//! | Rust | MSL | Size | Alignment |
+//! |------|-----|------|-----------|
+//! | `bool`, `u8`, `i8` | `bool`, `uchar`, `char` | 1 | 1 |
+//! | `u16`, `i16` | `ushort`, `short` | 2 | 2 |
+//! | `f32`, `u32`, `i32` | `float`, `uint`, `int` | 4 | 4 |
+//! | `[f32; 2]` | `float2` | 8 | 8 |
+//! | `[f32; 3]` | `float3` | 16 | 16 |
+//! | `[f32; 4]` | `float4` | 16 | 16 |
+//! | `[[f32; R]; C]` | `floatCxR` (column-major) | `C` × size of `floatR` | alignment of `floatR` |
+//! | [`PackedFloat3`] | `packed_float3` | 12 | 4 |
+//! | [`MslArray<T, N>`] | `T[N]` | `N` × size of `T` | alignment of `T` |
+//!
+//! `u32` and `i32` vectors map to `uintN` and `intN` the same way. Structs
+//! declared with [`msl_struct!`](crate::msl_struct) lay out their fields in
+//! order, each at the next multiple of its alignment, and round their size
+//! up to their largest field alignment.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // struct Uniforms { float4x4 model; float3 light_direction; float intensity; };
+//! mtl_gpu::msl_struct! {
+//!     #[derive(Copy, Clone, Debug, Default)]
+//!     pub struct Uniforms {
+//!         pub model: [[f32; 4]; 4],
+//!         pub light_direction: [f32; 3],
+//!         pub intensity: f32,
+//!     }
+//! }
+//!
+//! assert_eq!(Uniforms::SIZE, 96);
+//! encoder.set_vertex_bytes(&uniforms.as_bytes(), 1);
+//! ```
+//!
+//! [`PackedFloat3`]: crate::types::PackedFloat3
+
+use crate::types::PackedFloat3;
+
+/// Round `offset` up to the next multiple of `align`.
+///
+/// `align` must be a power of two.
+pub const fn align_up(offset: usize, align: usize) -> usize {
+    (offset + align - 1) & !(align - 1)
+}
+
+/// Compute the MSL size of a struct from the `(size, alignment)` of its
+/// fields in declaration order.
+pub const fn struct_size(fields: &[(usize, usize)]) -> usize {
+    let mut offset = 0;
+    let mut align = 1;
+    let mut i = 0;
+    while i < fields.len() {
+        let (field_size, field_align) = fields[i];
+        offset = align_up(offset, field_align) + field_size;
+        if field_align > align {
+            align = field_align;
+        }
+        i += 1;
+    }
+    align_up(offset, align)
+}
+
+/// Compute the MSL alignment of a struct from the alignments of its fields.
+pub const fn struct_align(fields: &[usize]) -> usize {
+    let mut align = 1;
+    let mut i = 0;
+    while i < fields.len() {
+        if fields[i] > align {
+            align = fields[i];
+        }
+        i += 1;
+    }
+    align
+}
+
+// ============================================================================
+// MslLayout
+// ============================================================================
+
+/// Position of a struct field in its MSL layout.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MslField {
+    /// The field name.
+    pub name: &'static str,
+    /// Byte offset from the start of the struct.
+    pub offset: usize,
+    /// Size in bytes, without trailing padding.
+    pub size: usize,
+}
+
+/// Types with a known layout in MSL constant and device buffers.
+///
+/// Implement it for structs with [`msl_struct!`](crate::msl_struct).
+pub trait MslLayout {
+    /// Size in bytes, including trailing padding.
+    const SIZE: usize;
+    /// Alignment in bytes.
+    const ALIGN: usize;
+
+    /// Write the value in MSL layout to the first [`SIZE`](Self::SIZE)
+    /// bytes of `out`.
+    ///
+    /// Padding bytes are left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is shorter than [`SIZE`](Self::SIZE).
+    fn write_to(&self, out: &mut [u8]);
+
+    /// Get the offset and size of each field, for structs.
+    fn fields() -> Vec<MslField> {
+        Vec::new()
+    }
+
+    /// Serialize the value in MSL layout, with zeroed padding.
+    fn as_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0; Self::SIZE];
+        self.write_to(&mut bytes);
+        bytes
+    }
+}
+
+macro_rules! impl_msl_scalar {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl MslLayout for $ty {
+                const SIZE: usize = size_of::<$ty>();
+                const ALIGN: usize = size_of::<$ty>();
+
+                fn write_to(&self, out: &mut [u8]) {
+                    out[..Self::SIZE].copy_from_slice(&self.to_ne_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_msl_scalar!(u8, i8, u16, i16, u32, i32, f32);
+
+impl MslLayout for bool {
+    const SIZE: usize = 1;
+    const ALIGN: usize = 1;
+
+    fn write_to(&self, out: &mut [u8]) {
+        out[0] = *self as u8;
+    }
+}
+
+macro_rules! impl_msl_vector {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl_msl_vector!(@impl $ty, 2);
+            impl_msl_vector!(@impl $ty, 3);
+            impl_msl_vector!(@impl $ty, 4);
+        )*
+    };
+    (@impl $ty:ty, $n:literal) => {
+        impl MslLayout for [$ty; $n] {
+            // Three-component vectors are padded to four.
+            const SIZE: usize = if $n == 3 { 4 } else { $n } * size_of::<$ty>();
+            const ALIGN: usize = Self::SIZE;
+
+            fn write_to(&self, out: &mut [u8]) {
+                let stride = size_of::<$ty>();
+                for (i, component) in self.iter().enumerate() {
+                    component.write_to(&mut out[i * stride..(i + 1) * stride]);
+                }
+            }
+        }
+    };
+}
+
+impl_msl_vector!(f32, u32, i32);
+
+macro_rules! impl_msl_matrix {
+    ($($columns:literal x $rows:literal),* $(,)?) => {
+        $(
+            impl MslLayout for [[f32; $rows]; $columns] {
+                const SIZE: usize = $columns * <[f32; $rows]>::SIZE;
+                const ALIGN: usize = <[f32; $rows]>::ALIGN;
+
+                fn write_to(&self, out: &mut [u8]) {
+                    let stride = <[f32; $rows]>::SIZE;
+                    for (i, column) in self.iter().enumerate() {
+                        column.write_to(&mut out[i * stride..(i + 1) * stride]);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_msl_matrix!(2 x 2, 2 x 3, 2 x 4, 3 x 2, 3 x 3, 3 x 4, 4 x 2, 4 x 3, 4 x 4);
+
+impl MslLayout for PackedFloat3 {
+    const SIZE: usize = 12;
+    const ALIGN: usize = 4;
+
+    fn write_to(&self, out: &mut [u8]) {
+        for (i, component) in self.to_array().iter().enumerate() {
+            component.write_to(&mut out[i * 4..(i + 1) * 4]);
+        }
+    }
+}
+
+/// A fixed-size MSL array, `T[N]`.
+///
+/// Plain Rust arrays of `f32`, `u32` and `i32` map to vectors and matrices,
+/// so arrays are spelled with this wrapper instead.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MslArray<T, const N: usize>(pub [T; N]);
+
+impl<T: Default, const N: usize> Default for MslArray<T, N> {
+    fn default() -> Self {
+        Self(std::array::from_fn(|_| T::default()))
+    }
+}
+
+impl<T: MslLayout, const N: usize> MslLayout for MslArray<T, N> {
+    const SIZE: usize = N * align_up(T::SIZE, T::ALIGN);
+    const ALIGN: usize = T::ALIGN;
+
+    fn write_to(&self, out: &mut [u8]) {
+        let stride = align_up(T::SIZE, T::ALIGN);
+        for (i, element) in self.0.iter().enumerate() {
+            element.write_to(&mut out[i * stride..i * stride + T::SIZE]);
+        }
+    }
+}
+
+/// Declare a struct and implement [`MslLayout`](crate::MslLayout) for it.
+///
+/// Every field type must implement `MslLayout`. The Rust struct itself
+/// keeps Rust's layout; only [`MslLayout::as_bytes`](crate::MslLayout::as_bytes)
+/// and [`MslLayout::write_to`](crate::MslLayout::write_to) produce the MSL
+/// layout.
+///
+/// ```ignore
+/// mtl_gpu::msl_struct! {
+///     #[derive(Copy, Clone, Debug, Default)]
+///     pub struct Light {
+///         pub position: [f32; 3],
+///         pub color: [f32; 3],
+///         pub radius: f32,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! msl_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $crate::MslLayout for $name {
+            const SIZE: usize = $crate::msl_layout::struct_size(&[
+                $((
+                    <$ty as $crate::MslLayout>::SIZE,
+                    <$ty as $crate::MslLayout>::ALIGN,
+                )),*
+            ]);
+            const ALIGN: usize = $crate::msl_layout::struct_align(&[
+                $(<$ty as $crate::MslLayout>::ALIGN),*
+            ]);
+
+            #[allow(unused_assignments, unused_variables)]
+            fn write_to(&self, out: &mut [u8]) {
+                let mut offset = 0;
+                $(
+                    offset = $crate::msl_layout::align_up(
+                        offset,
+                        <$ty as $crate::MslLayout>::ALIGN,
+                    );
+                    let end = offset + <$ty as $crate::MslLayout>::SIZE;
+                    $crate::MslLayout::write_to(&self.$field, &mut out[offset..end]);
+                    offset = end;
+                )*
+            }
+
+            #[allow(unused_assignments, unused_mut)]
+            fn fields() -> Vec<$crate::MslField> {
+                let mut fields = Vec::new();
+                let mut offset = 0;
+                $(
+                    offset = $crate::msl_layout::align_up(
+                        offset,
+                        <$ty as $crate::MslLayout>::ALIGN,
+                    );
+                    fields.push($crate::MslField {
+                        name: stringify!($field),
+                        offset,
+                        size: <$ty as $crate::MslLayout>::SIZE,
+                    });
+                    offset += <$ty as $crate::MslLayout>::SIZE;
+                )*
+                fields
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::msl_struct! {
+        #[derive(Copy, Clone, Debug, Default)]
+        struct Light {
+            position: [f32; 3],
+            intensity: f32,
+            color: [f32; 3],
+        }
+    }
+
+    crate::msl_struct! {
+        #[derive(Copy, Clone, Debug, Default)]
+        struct Uniforms {
+            model: [[f32; 4]; 4],
+            normal_matrix: [[f32; 3]; 3],
+            light_count: u32,
+            lights: MslArray<Light, 2>,
+            enabled: bool,
+        }
+    }
+
+    #[test]
+    fn test_primitive_layouts() {
+        assert_eq!((<[f32; 2]>::SIZE, <[f32; 2]>::ALIGN), (8, 8));
+        assert_eq!((<[f32; 3]>::SIZE, <[f32; 3]>::ALIGN), (16, 16));
+        assert_eq!((<[u32; 4]>::SIZE, <[u32; 4]>::ALIGN), (16, 16));
+        assert_eq!((<[[f32; 3]; 3]>::SIZE, <[[f32; 3]; 3]>::ALIGN), (48, 16));
+        assert_eq!((<[[f32; 2]; 4]>::SIZE, <[[f32; 2]; 4]>::ALIGN), (32, 8));
+        assert_eq!((PackedFloat3::SIZE, PackedFloat3::ALIGN), (12, 4));
+        assert_eq!(<MslArray<[f32; 3], 4>>::SIZE, 64);
+    }
+
+    #[test]
+    fn test_struct_layout() {
+        // The float3 after `intensity` starts at 32, not 16 as in repr(C).
+        assert_eq!(
+            Light::fields()
+                .iter()
+                .map(|field| field.offset)
+                .collect::<Vec<_>>(),
+            [0, 16, 32]
+        );
+        assert_eq!((Light::SIZE, Light::ALIGN), (48, 16));
+
+        let offsets: Vec<_> = Uniforms::fields()
+            .iter()
+            .map(|field| (field.name, field.offset))
+            .collect();
+        assert_eq!(
+            offsets,
+            [
+                ("model", 0),
+                ("normal_matrix", 64),
+                ("light_count", 112),
+                ("lights", 128),
+                ("enabled", 224),
+            ]
+        );
+        assert_eq!(Uniforms::SIZE, 240);
+    }
+
+    #[test]
+    fn test_as_bytes() {
+        let light = Light {
+            position: [1.0, 2.0, 3.0],
+            intensity: 4.0,
+            color: [5.0, 6.0, 7.0],
+        };
+        let bytes = light.as_bytes();
+        assert_eq!(bytes.len(), Light::SIZE);
+
+        let float_at =
+            |offset: usize| f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        assert_eq!(float_at(8), 3.0);
+        assert_eq!(float_at(12), 0.0);
+        assert_eq!(float_at(16), 4.0);
+        assert_eq!(float_at(32), 5.0);
+        assert!(bytes[44..].iter().all(|&b| b == 0));
+    }
+}
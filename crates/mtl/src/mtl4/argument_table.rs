@@ -8,7 +8,7 @@ use std::ptr::NonNull;
 use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, msg_send_2, msg_send_3, sel};
 
-use crate::Device;
+use crate::{Buffer, Device, ResourceID};
 
 // ============================================================
 // ArgumentTableDescriptor
@@ -238,6 +238,10 @@ impl ArgumentTable {
     pub fn device(&self) -> Option<Device> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(device));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             Device::from_raw(ptr)
         }
     }
@@ -297,10 +301,18 @@ impl ArgumentTable {
         }
     }
 
+    /// Bind a buffer at the specified binding index.
+    ///
+    /// Convenience for [`set_address`](Self::set_address) with the buffer's
+    /// GPU address plus `offset`.
+    pub fn set_buffer(&self, buffer: &Buffer, offset: UInteger, binding_index: UInteger) {
+        self.set_address(buffer.gpu_address() + offset as u64, binding_index);
+    }
+
     /// Set a resource at the specified buffer index.
     ///
     /// C++ equivalent: `void setResource(MTL::ResourceID, NS::UInteger)`
-    pub fn set_resource(&self, resource_id: u64, binding_index: UInteger) {
+    pub fn set_resource(&self, resource_id: ResourceID, binding_index: UInteger) {
         unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
@@ -313,8 +325,10 @@ impl ArgumentTable {
 
     /// Set a texture at the specified binding index.
     ///
+    /// Pass [`Texture::gpu_resource_id`](crate::Texture::gpu_resource_id).
+    ///
     /// C++ equivalent: `void setTexture(MTL::ResourceID, NS::UInteger)`
-    pub fn set_texture(&self, resource_id: u64, binding_index: UInteger) {
+    pub fn set_texture(&self, resource_id: ResourceID, binding_index: UInteger) {
         unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
@@ -327,8 +341,11 @@ impl ArgumentTable {
 
     /// Set a sampler state at the specified binding index.
     ///
+    /// Pass [`SamplerState::gpu_resource_id`](crate::SamplerState::gpu_resource_id).
+    /// The sampler must have been created with argument buffer support.
+    ///
     /// C++ equivalent: `void setSamplerState(MTL::ResourceID, NS::UInteger)`
-    pub fn set_sampler_state(&self, resource_id: u64, binding_index: UInteger) {
+    pub fn set_sampler_state(&self, resource_id: ResourceID, binding_index: UInteger) {
         unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
@@ -11,6 +11,7 @@ use mtl_sys::{msg_send_0, msg_send_1, msg_send_2, msg_send_5, sel};
 use super::acceleration_structure::BufferRange;
 
 use super::CommandAllocator;
+use crate::object_ptrs::with_object_ptrs;
 use crate::{Device, ResidencySet};
 
 // ============================================================
@@ -128,6 +129,10 @@ impl CommandBuffer {
     pub fn device(&self) -> Option<Device> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(device));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             Device::from_raw(ptr)
         }
     }
@@ -221,15 +226,14 @@ impl CommandBuffer {
     ///
     /// C++ equivalent: `void useResidencySets(const MTL::ResidencySet* const[], NS::UInteger count)`
     pub fn use_residency_sets(&self, residency_sets: &[&ResidencySet]) {
-        let ptrs: Vec<*const c_void> = residency_sets.iter().map(|r| r.as_ptr()).collect();
-        unsafe {
+        with_object_ptrs(residency_sets, |ptrs| unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(useResidencySets:count:),
                 ptrs.as_ptr(),
                 ptrs.len() as UInteger,
             );
-        }
+        })
     }
 
     /// Push a debug group.
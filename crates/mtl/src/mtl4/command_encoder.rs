@@ -43,6 +43,10 @@ impl CommandEncoder {
     pub fn device(&self) -> Option<Device> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(device));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             Device::from_raw(ptr)
         }
     }
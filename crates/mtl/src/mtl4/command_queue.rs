@@ -8,8 +8,11 @@ use std::ptr::NonNull;
 use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, msg_send_2, msg_send_3, msg_send_4, sel};
 
-use super::{CommandBuffer, CommitFeedback};
-use crate::{Device, Drawable, Event, ResidencySet};
+use super::CommandBuffer;
+#[cfg(feature = "blocks")]
+use super::CommitFeedback;
+use crate::object_ptrs::with_object_ptrs;
+use crate::{Device, Drawable, Event, ResidencySet, SharedEvent};
 
 /// Dispatch queue type (opaque).
 pub type DispatchQueue = *mut c_void;
@@ -55,6 +58,7 @@ impl CommitOptions {
     /// C++ equivalent: `void addFeedbackHandler(void (^)(MTL4::CommitFeedback*))`
     ///
     /// The handler is called with the commit feedback when the commit completes.
+    #[cfg(feature = "blocks")]
     pub fn add_feedback_handler<F>(&self, handler: F)
     where
         F: Fn(&CommitFeedback) + Send + 'static,
@@ -249,6 +253,10 @@ impl CommandQueue {
     pub fn device(&self) -> Option<Device> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(device));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             Device::from_raw(ptr)
         }
     }
@@ -293,15 +301,14 @@ impl CommandQueue {
     ///
     /// C++ equivalent: `void addResidencySets(const MTL::ResidencySet* const[], NS::UInteger count)`
     pub fn add_residency_sets(&self, residency_sets: &[&ResidencySet]) {
-        let ptrs: Vec<*const c_void> = residency_sets.iter().map(|r| r.as_ptr()).collect();
-        unsafe {
+        with_object_ptrs(residency_sets, |ptrs| unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(addResidencySets:count:),
                 ptrs.as_ptr(),
                 ptrs.len() as UInteger,
             );
-        }
+        })
     }
 
     /// Remove a residency set.
@@ -321,34 +328,27 @@ impl CommandQueue {
     ///
     /// C++ equivalent: `void removeResidencySets(const MTL::ResidencySet* const[], NS::UInteger count)`
     pub fn remove_residency_sets(&self, residency_sets: &[&ResidencySet]) {
-        let ptrs: Vec<*const c_void> = residency_sets.iter().map(|r| r.as_ptr()).collect();
-        unsafe {
+        with_object_ptrs(residency_sets, |ptrs| unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(removeResidencySets:count:),
                 ptrs.as_ptr(),
                 ptrs.len() as UInteger,
             );
-        }
+        })
     }
 
     // ========== Command Buffer Creation ==========
 
-    /// Create a new MTL4 command buffer.
+    /// Create a new MTL4 command buffer on this queue's device.
     ///
-    /// C++ equivalent: `MTL4::CommandBuffer* newCommandBuffer()`
+    /// `MTL4CommandQueue` has no factory of its own; this forwards to
+    /// [`Device::new_command_buffer`].
     ///
     /// The returned command buffer must be used with `begin_command_buffer()`
     /// and `end_command_buffer()` to record commands.
     pub fn new_command_buffer(&self) -> Option<CommandBuffer> {
-        unsafe {
-            let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(newCommandBuffer));
-            if ptr.is_null() {
-                None
-            } else {
-                CommandBuffer::from_raw(ptr)
-            }
-        }
+        self.device()?.new_command_buffer()
     }
 
     // ========== Commit ==========
@@ -357,23 +357,21 @@ impl CommandQueue {
     ///
     /// C++ equivalent: `void commit(const MTL4::CommandBuffer* const[], NS::UInteger count)`
     pub fn commit(&self, command_buffers: &[&CommandBuffer]) {
-        let ptrs: Vec<*const c_void> = command_buffers.iter().map(|c| c.as_ptr()).collect();
-        unsafe {
+        with_object_ptrs(command_buffers, |ptrs| unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(commit:count:),
                 ptrs.as_ptr(),
                 ptrs.len() as UInteger,
             );
-        }
+        })
     }
 
     /// Commit command buffers with options.
     ///
     /// C++ equivalent: `void commit(const MTL4::CommandBuffer* const[], NS::UInteger, const MTL4::CommitOptions*)`
     pub fn commit_with_options(&self, command_buffers: &[&CommandBuffer], options: &CommitOptions) {
-        let ptrs: Vec<*const c_void> = command_buffers.iter().map(|c| c.as_ptr()).collect();
-        unsafe {
+        with_object_ptrs(command_buffers, |ptrs| unsafe {
             let _: () = msg_send_3(
                 self.as_ptr(),
                 sel!(commit:count:options:),
@@ -381,7 +379,38 @@ impl CommandQueue {
                 ptrs.len() as UInteger,
                 options.as_ptr(),
             );
-        }
+        })
+    }
+
+    /// Commit command buffers and signal `event` with `value` once they finish.
+    ///
+    /// Metal 4 command buffers have no `waitUntilCompleted`; completion is
+    /// tracked through events instead. Pair with
+    /// [`SharedEvent::wait_until_signaled_value`] or
+    /// [`SharedEvent::signaled_value`] to know when the work is done.
+    pub fn commit_and_signal(
+        &self,
+        command_buffers: &[&CommandBuffer],
+        event: &SharedEvent,
+        value: u64,
+    ) {
+        self.commit(command_buffers);
+        self.signal_event(event.as_event(), value);
+    }
+
+    /// Commit command buffers and block until they finish.
+    ///
+    /// Signals `event` with `value` after the commit and waits on the CPU.
+    /// Returns `false` if `timeout_ms` elapsed before the GPU reached `value`.
+    pub fn commit_and_wait(
+        &self,
+        command_buffers: &[&CommandBuffer],
+        event: &SharedEvent,
+        value: u64,
+        timeout_ms: u64,
+    ) -> bool {
+        self.commit_and_signal(command_buffers, event, value);
+        event.wait_until_signaled_value(value, timeout_ms)
     }
 
     // ========== Synchronization ==========
@@ -7,12 +7,14 @@ use mtl_foundation::Referencing;
 use mtl_sys::{msg_send_0, msg_send_2, msg_send_3, msg_send_4, sel};
 
 use crate::mtl4::{
-    BinaryFunction, BinaryFunctionDescriptor, CompilerTask, ComputePipelineDescriptor,
-    LibraryDescriptor, MachineLearningPipelineDescriptor, MachineLearningPipelineState,
-    PipelineDataSetSerializer, PipelineDescriptor, PipelineStageDynamicLinkingDescriptor,
+    BinaryFunction, BinaryFunctionDescriptor, ComputePipelineDescriptor, LibraryDescriptor,
+    MachineLearningPipelineDescriptor, MachineLearningPipelineState, PipelineDataSetSerializer,
+    PipelineDescriptor, PipelineStageDynamicLinkingDescriptor,
     RenderPipelineDynamicLinkingDescriptor,
 };
-use crate::{ComputePipelineState, Device, DynamicLibrary, Library, RenderPipelineState};
+use crate::{ComputePipelineState, Device, Library, RenderPipelineState};
+#[cfg(feature = "blocks")]
+use crate::{DynamicLibrary, mtl4::CompilerTask};
 
 use super::CompilerTaskOptions;
 
@@ -50,6 +52,10 @@ impl Compiler {
     pub fn device(&self) -> Option<Device> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(device));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             Device::from_raw(ptr)
         }
     }
@@ -343,6 +349,7 @@ impl Compiler {
     /// Create a new library asynchronously with a completion handler.
     ///
     /// C++ equivalent: `CompilerTask* newLibrary(const LibraryDescriptor*, NewLibraryCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_library_async<F>(
         &self,
         descriptor: &LibraryDescriptor,
@@ -386,6 +393,7 @@ impl Compiler {
     /// Create a new binary function asynchronously with a completion handler.
     ///
     /// C++ equivalent: `CompilerTask* newBinaryFunction(const BinaryFunctionDescriptor*, CompilerTaskOptions*, NewBinaryFunctionCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_binary_function_async<F>(
         &self,
         descriptor: &BinaryFunctionDescriptor,
@@ -433,6 +441,7 @@ impl Compiler {
     /// Create a new compute pipeline state asynchronously with a completion handler.
     ///
     /// C++ equivalent: `CompilerTask* newComputePipelineState(const ComputePipelineDescriptor*, CompilerTaskOptions*, NewComputePipelineStateCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_compute_pipeline_state_async<F>(
         &self,
         descriptor: &ComputePipelineDescriptor,
@@ -480,6 +489,7 @@ impl Compiler {
     /// Create a new render pipeline state asynchronously with a completion handler.
     ///
     /// C++ equivalent: `CompilerTask* newRenderPipelineState(const PipelineDescriptor*, CompilerTaskOptions*, NewRenderPipelineStateCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_render_pipeline_state_async<F>(
         &self,
         descriptor: &PipelineDescriptor,
@@ -525,6 +535,7 @@ impl Compiler {
     /// Create a new render pipeline state by specialization asynchronously.
     ///
     /// C++ equivalent: `CompilerTask* newRenderPipelineStateBySpecialization(..., NewRenderPipelineStateCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_render_pipeline_state_by_specialization_async<F>(
         &self,
         descriptor: &PipelineDescriptor,
@@ -570,6 +581,7 @@ impl Compiler {
     /// Create a new dynamic library from a library asynchronously.
     ///
     /// C++ equivalent: `CompilerTask* newDynamicLibrary(const Library*, NewDynamicLibraryCompletionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_dynamic_library_from_library_async<F>(
         &self,
         library: &Library,
@@ -615,6 +627,7 @@ impl Compiler {
     /// # Safety
     ///
     /// The URL pointer must be valid.
+    #[cfg(feature = "blocks")]
     pub unsafe fn new_dynamic_library_from_url_async<F>(
         &self,
         url: *const c_void,
@@ -658,6 +671,7 @@ impl Compiler {
     /// Create a new compute pipeline state with dynamic linking asynchronously.
     ///
     /// C++ equivalent: `CompilerTask* newComputePipelineState(..., dynamicLinkingDescriptor, ..., completionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_compute_pipeline_state_with_dynamic_linking_async<F>(
         &self,
         descriptor: &ComputePipelineDescriptor,
@@ -705,6 +719,7 @@ impl Compiler {
     /// Create a new render pipeline state with dynamic linking asynchronously.
     ///
     /// C++ equivalent: `CompilerTask* newRenderPipelineState(..., dynamicLinkingDescriptor, ..., completionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_render_pipeline_state_with_dynamic_linking_async<F>(
         &self,
         descriptor: &PipelineDescriptor,
@@ -781,6 +796,7 @@ impl Compiler {
     /// Create a new machine learning pipeline state asynchronously.
     ///
     /// C++ equivalent: `CompilerTask* newMachineLearningPipelineState(..., completionHandler)`
+    #[cfg(feature = "blocks")]
     pub fn new_machine_learning_pipeline_state_async<F>(
         &self,
         descriptor: &MachineLearningPipelineDescriptor,
@@ -788,9 +804,7 @@ impl Compiler {
         completion_handler: F,
     ) -> Option<CompilerTask>
     where
-        F: Fn(Option<MachineLearningPipelineState>, Option<mtl_foundation::Error>)
-            + Send
-            + 'static,
+        F: Fn(Option<MachineLearningPipelineState>, Option<mtl_foundation::Error>) + Send + 'static,
     {
         let block =
             mtl_sys::TwoArgBlock::from_fn(move |state_ptr: *mut c_void, err_ptr: *mut c_void| {
@@ -8,6 +8,7 @@ use std::ptr::NonNull;
 use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, msg_send_2, msg_send_3, msg_send_4, msg_send_5, sel};
 
+use super::ArgumentTable;
 use super::enums::VisibilityOptions;
 use crate::{ComputePipelineState, Device, Size};
 
@@ -42,6 +43,10 @@ impl ComputeCommandEncoder {
     pub fn device(&self) -> Option<Device> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(device));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             Device::from_raw(ptr)
         }
     }
@@ -98,9 +103,14 @@ impl ComputeCommandEncoder {
     /// Set the argument table at index.
     ///
     /// C++ equivalent: `void setArgumentTable(const MTL4::ArgumentTable*, NS::UInteger)`
-    pub fn set_argument_table(&self, table: *const c_void, index: UInteger) {
+    pub fn set_argument_table(&self, table: &ArgumentTable, index: UInteger) {
         unsafe {
-            let _: () = msg_send_2(self.as_ptr(), sel!(setArgumentTable:atIndex:), table, index);
+            let _: () = msg_send_2(
+                self.as_ptr(),
+                sel!(setArgumentTable:atIndex:),
+                table.as_ptr(),
+                index,
+            );
         }
     }
 
@@ -248,6 +248,22 @@ impl CounterHeap {
         }
     }
 
+    /// Resolve a range of counters and return the data.
+    ///
+    /// The returned data is autoreleased; read it in place with
+    /// [`Data::bytes`](mtl_foundation::Data::bytes) before the enclosing
+    /// autorelease pool drains, and copy it only if it must outlive the
+    /// pool.
+    ///
+    /// C++ equivalent: `NS::Data* resolveCounterRange(NS::Range)`
+    pub fn resolve_counter_range(
+        &self,
+        location: UInteger,
+        length: UInteger,
+    ) -> Option<mtl_foundation::Data> {
+        unsafe { mtl_foundation::Data::from_ptr(self.resolve_counter_range_raw(location, length)) }
+    }
+
     /// Resolve a range of counters and return the data.
     ///
     /// C++ equivalent: `NS::Data* resolveCounterRange(NS::Range)`
@@ -10,6 +10,7 @@ use mtl_sys::{
     msg_send_0, msg_send_1, msg_send_2, msg_send_3, msg_send_4, msg_send_5, msg_send_6, sel,
 };
 
+use super::ArgumentTable;
 use super::enums::VisibilityOptions;
 use crate::{
     CullMode, DepthClipMode, DepthStencilState, Device, PrimitiveType, RenderPipelineState,
@@ -48,6 +49,10 @@ impl RenderCommandEncoder {
     pub fn device(&self) -> Option<Device> {
         unsafe {
             let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(device));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
             Device::from_raw(ptr)
         }
     }
@@ -104,12 +109,12 @@ impl RenderCommandEncoder {
     /// Set vertex argument table at index.
     ///
     /// C++ equivalent: `void setVertexArgumentTable(const MTL4::ArgumentTable*, NS::UInteger)`
-    pub fn set_vertex_argument_table(&self, table: *const c_void, index: UInteger) {
+    pub fn set_vertex_argument_table(&self, table: &ArgumentTable, index: UInteger) {
         unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(setVertexArgumentTable:atIndex:),
-                table,
+                table.as_ptr(),
                 index,
             );
         }
@@ -118,12 +123,12 @@ impl RenderCommandEncoder {
     /// Set fragment argument table at index.
     ///
     /// C++ equivalent: `void setFragmentArgumentTable(const MTL4::ArgumentTable*, NS::UInteger)`
-    pub fn set_fragment_argument_table(&self, table: *const c_void, index: UInteger) {
+    pub fn set_fragment_argument_table(&self, table: &ArgumentTable, index: UInteger) {
         unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(setFragmentArgumentTable:atIndex:),
-                table,
+                table.as_ptr(),
                 index,
             );
         }
@@ -132,12 +137,12 @@ impl RenderCommandEncoder {
     /// Set tile argument table at index.
     ///
     /// C++ equivalent: `void setTileArgumentTable(const MTL4::ArgumentTable*, NS::UInteger)`
-    pub fn set_tile_argument_table(&self, table: *const c_void, index: UInteger) {
+    pub fn set_tile_argument_table(&self, table: &ArgumentTable, index: UInteger) {
         unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(setTileArgumentTable:atIndex:),
-                table,
+                table.as_ptr(),
                 index,
             );
         }
@@ -146,12 +151,12 @@ impl RenderCommandEncoder {
     /// Set object argument table at index.
     ///
     /// C++ equivalent: `void setObjectArgumentTable(const MTL4::ArgumentTable*, NS::UInteger)`
-    pub fn set_object_argument_table(&self, table: *const c_void, index: UInteger) {
+    pub fn set_object_argument_table(&self, table: &ArgumentTable, index: UInteger) {
         unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(setObjectArgumentTable:atIndex:),
-                table,
+                table.as_ptr(),
                 index,
             );
         }
@@ -160,12 +165,12 @@ impl RenderCommandEncoder {
     /// Set mesh argument table at index.
     ///
     /// C++ equivalent: `void setMeshArgumentTable(const MTL4::ArgumentTable*, NS::UInteger)`
-    pub fn set_mesh_argument_table(&self, table: *const c_void, index: UInteger) {
+    pub fn set_mesh_argument_table(&self, table: &ArgumentTable, index: UInteger) {
         unsafe {
             let _: () = msg_send_2(
                 self.as_ptr(),
                 sel!(setMeshArgumentTable:atIndex:),
-                table,
+                table.as_ptr(),
                 index,
             );
         }
@@ -0,0 +1,74 @@
+//! Object pointer arrays for methods that take C arrays of objects.
+//!
+//! Methods like `setBuffers:offsets:withRange:` take a C array of object
+//! pointers while the safe wrappers take `&[&Buffer]`. Collecting the
+//! pointers into a `Vec` costs a heap allocation per call, which adds up on
+//! per-frame paths, so [`with_object_ptrs`] builds the array on the stack
+//! and only allocates for more than [`INLINE_CAPACITY`] objects.
+
+use std::ffi::c_void;
+
+use mtl_foundation::Referencing;
+
+use crate::resource::Resource;
+
+/// Number of pointers [`with_object_ptrs`] keeps on the stack.
+pub(crate) const INLINE_CAPACITY: usize = 32;
+
+/// Call `f` with the pointers of `objects` as a contiguous array.
+pub(crate) fn with_object_ptrs<T: Referencing, R>(
+    objects: &[&T],
+    f: impl FnOnce(&[*const c_void]) -> R,
+) -> R {
+    with_ptrs(objects, T::as_ptr, f)
+}
+
+/// Call `f` with the pointers of `resources` as a contiguous array.
+pub(crate) fn with_resource_ptrs<R>(
+    resources: &[&dyn Resource],
+    f: impl FnOnce(&[*const c_void]) -> R,
+) -> R {
+    with_ptrs(resources, |resource| resource.as_resource_ptr(), f)
+}
+
+fn with_ptrs<T: ?Sized, R>(
+    objects: &[&T],
+    ptr_of: impl Fn(&T) -> *const c_void,
+    f: impl FnOnce(&[*const c_void]) -> R,
+) -> R {
+    if objects.len() <= INLINE_CAPACITY {
+        let mut ptrs = [std::ptr::null::<c_void>(); INLINE_CAPACITY];
+        for (ptr, object) in ptrs.iter_mut().zip(objects) {
+            *ptr = ptr_of(object);
+        }
+        f(&ptrs[..objects.len()])
+    } else {
+        let ptrs: Vec<*const c_void> = objects.iter().map(|object| ptr_of(object)).collect();
+        f(&ptrs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fake(usize);
+
+    impl Referencing for Fake {
+        fn as_ptr(&self) -> *const c_void {
+            self.0 as *const c_void
+        }
+    }
+
+    #[test]
+    fn test_with_object_ptrs() {
+        for count in [0, 3, INLINE_CAPACITY, INLINE_CAPACITY + 5] {
+            let objects: Vec<Fake> = (1..=count).map(Fake).collect();
+            let refs: Vec<&Fake> = objects.iter().collect();
+            let addresses = with_object_ptrs(&refs, |ptrs| {
+                ptrs.iter().map(|ptr| *ptr as usize).collect::<Vec<_>>()
+            });
+            assert_eq!(addresses, (1..=count).collect::<Vec<_>>());
+        }
+    }
+}
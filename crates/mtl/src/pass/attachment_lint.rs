@@ -0,0 +1,455 @@
+//! Frame analysis for render pass attachment usage.
+//!
+//! On tile-based GPUs, storing an attachment that nobody reads costs memory
+//! bandwidth, and a texture that only lives in tile memory doesn't need
+//! backing storage at all. [`AttachmentLint`] records the render passes and
+//! texture reads of one frame and reports attachments whose store action can
+//! become [`StoreAction::DONT_CARE`] or whose texture can use
+//! [`StorageMode::MEMORYLESS`].
+//!
+//! The analysis only sees what is recorded. Textures read by a later frame
+//! (history buffers, for example) must be recorded with
+//! [`record_texture_read`](AttachmentLint::record_texture_read) or they will
+//! be reported as unused.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut lint = AttachmentLint::new();
+//!
+//! lint.record_render_pass(&gbuffer_pass);
+//! lint.record_texture_read(&albedo); // sampled by the lighting pass
+//! lint.record_render_pass(&lighting_pass);
+//!
+//! for hint in lint.finish_frame() {
+//!     eprintln!("{hint}");
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use super::RenderPassDescriptor;
+use crate::Texture;
+use crate::enums::{LoadAction, StorageMode, StoreAction};
+
+/// Maximum number of color attachments inspected per render pass.
+const MAX_COLOR_ATTACHMENTS: UInteger = 8;
+
+/// Which attachment of a render pass a hint refers to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AttachmentSlot {
+    /// Color attachment at the given index.
+    Color(UInteger),
+    /// The depth attachment.
+    Depth,
+    /// The stencil attachment.
+    Stencil,
+}
+
+impl fmt::Display for AttachmentSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttachmentSlot::Color(index) => write!(f, "color[{}]", index),
+            AttachmentSlot::Depth => write!(f, "depth"),
+            AttachmentSlot::Stencil => write!(f, "stencil"),
+        }
+    }
+}
+
+/// The change an [`AttachmentHint`] recommends.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AttachmentSuggestion {
+    /// Replace the store action with this one; the stored contents are
+    /// never read.
+    StoreAction(StoreAction),
+    /// Create the texture with [`StorageMode::MEMORYLESS`]; it never leaves
+    /// tile memory.
+    Memoryless,
+}
+
+/// A single recommendation produced by [`AttachmentLint`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttachmentHint {
+    /// Index of the render pass within the frame, in recording order.
+    pub pass_index: usize,
+    /// The attachment the hint applies to.
+    pub slot: AttachmentSlot,
+    /// Label of the attachment's texture, if it has one.
+    pub texture_label: Option<String>,
+    /// The recommended change.
+    pub suggestion: AttachmentSuggestion,
+}
+
+impl fmt::Display for AttachmentHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pass {} {}", self.pass_index, self.slot)?;
+        if let Some(label) = &self.texture_label {
+            write!(f, " ({})", label)?;
+        }
+        match self.suggestion {
+            AttachmentSuggestion::StoreAction(action) if action == StoreAction::DONT_CARE => {
+                write!(
+                    f,
+                    ": stored but never read later in the frame; use StoreAction::DONT_CARE"
+                )
+            }
+            AttachmentSuggestion::StoreAction(_) => write!(
+                f,
+                ": multisample contents stored but never read; use StoreAction::MULTISAMPLE_RESOLVE"
+            ),
+            AttachmentSuggestion::Memoryless => write!(
+                f,
+                ": texture is only used inside render passes; create it with StorageMode::MEMORYLESS"
+            ),
+        }
+    }
+}
+
+/// One attachment of a recorded render pass.
+#[derive(Clone, Debug)]
+struct AttachmentUse {
+    pass_index: usize,
+    slot: AttachmentSlot,
+    texture: usize,
+    label: Option<String>,
+    load: LoadAction,
+    store: StoreAction,
+    storage: StorageMode,
+}
+
+#[derive(Clone, Debug)]
+enum FrameEvent {
+    Attachment(AttachmentUse),
+    Read(usize),
+}
+
+/// Records one frame of attachment usage and suggests cheaper configurations.
+///
+/// Intended as a debugging aid; it costs a few Objective-C calls per recorded
+/// attachment.
+#[derive(Debug, Default)]
+pub struct AttachmentLint {
+    events: Vec<FrameEvent>,
+    pass_count: usize,
+    /// Textures recorded this frame, retained so their addresses stay unique
+    /// until the frame is reset.
+    textures: HashMap<usize, Texture>,
+}
+
+impl AttachmentLint {
+    /// Create an empty analyzer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a render pass in encoding order.
+    pub fn record_render_pass(&mut self, descriptor: &RenderPassDescriptor) {
+        let pass_index = self.pass_count;
+        self.pass_count += 1;
+
+        if let Some(colors) = descriptor.color_attachments() {
+            for index in 0..MAX_COLOR_ATTACHMENTS {
+                let Some(attachment) = colors.object_at(index) else {
+                    continue;
+                };
+                if let Some(texture) = attachment.texture() {
+                    self.push_attachment(
+                        pass_index,
+                        AttachmentSlot::Color(index),
+                        &texture,
+                        attachment.load_action(),
+                        attachment.store_action(),
+                    );
+                }
+            }
+        }
+        if let Some(depth) = descriptor.depth_attachment()
+            && let Some(texture) = depth.texture()
+        {
+            self.push_attachment(
+                pass_index,
+                AttachmentSlot::Depth,
+                &texture,
+                depth.load_action(),
+                depth.store_action(),
+            );
+        }
+        if let Some(stencil) = descriptor.stencil_attachment()
+            && let Some(texture) = stencil.texture()
+        {
+            self.push_attachment(
+                pass_index,
+                AttachmentSlot::Stencil,
+                &texture,
+                stencil.load_action(),
+                stencil.store_action(),
+            );
+        }
+    }
+
+    /// Record that `texture` is read outside a render pass attachment.
+    ///
+    /// Call this for sampling, blits, compute reads, presentation, and reads
+    /// by later frames.
+    pub fn record_texture_read(&mut self, texture: &Texture) {
+        let key = self.retain_texture(texture);
+        self.events.push(FrameEvent::Read(key));
+    }
+
+    /// Get the number of render passes recorded this frame.
+    pub fn pass_count(&self) -> usize {
+        self.pass_count
+    }
+
+    /// Analyze the recorded frame without clearing it.
+    pub fn hints(&self) -> Vec<AttachmentHint> {
+        let mut hints = Vec::new();
+        let mut memoryless_checked = HashSet::new();
+
+        for (position, event) in self.events.iter().enumerate() {
+            let FrameEvent::Attachment(usage) = event else {
+                continue;
+            };
+
+            if is_store(usage.store) && !self.is_read_after(usage.texture, position) {
+                let replacement = if usage.store == StoreAction::STORE_AND_MULTISAMPLE_RESOLVE {
+                    StoreAction::MULTISAMPLE_RESOLVE
+                } else {
+                    StoreAction::DONT_CARE
+                };
+                hints.push(AttachmentHint {
+                    pass_index: usage.pass_index,
+                    slot: usage.slot,
+                    texture_label: usage.label.clone(),
+                    suggestion: AttachmentSuggestion::StoreAction(replacement),
+                });
+            }
+
+            if usage.storage != StorageMode::MEMORYLESS
+                && memoryless_checked.insert(usage.texture)
+                && self.is_tile_only(usage.texture)
+            {
+                hints.push(AttachmentHint {
+                    pass_index: usage.pass_index,
+                    slot: usage.slot,
+                    texture_label: usage.label.clone(),
+                    suggestion: AttachmentSuggestion::Memoryless,
+                });
+            }
+        }
+        hints
+    }
+
+    /// Analyze the recorded frame and reset for the next one.
+    pub fn finish_frame(&mut self) -> Vec<AttachmentHint> {
+        let hints = self.hints();
+        self.reset();
+        hints
+    }
+
+    /// Discard everything recorded this frame.
+    pub fn reset(&mut self) {
+        self.events.clear();
+        self.pass_count = 0;
+        self.textures.clear();
+    }
+
+    /// Retain `texture` for the rest of the frame and return its key.
+    fn retain_texture(&mut self, texture: &Texture) -> usize {
+        let key = texture.as_raw() as usize;
+        self.textures.entry(key).or_insert_with(|| texture.clone());
+        key
+    }
+
+    fn push_attachment(
+        &mut self,
+        pass_index: usize,
+        slot: AttachmentSlot,
+        texture: &Texture,
+        load: LoadAction,
+        store: StoreAction,
+    ) {
+        let key = self.retain_texture(texture);
+        self.events.push(FrameEvent::Attachment(AttachmentUse {
+            pass_index,
+            slot,
+            texture: key,
+            label: texture.label(),
+            load,
+            store,
+            storage: texture.storage_mode(),
+        }));
+    }
+
+    /// Check if the texture's contents are consumed after event `position`.
+    fn is_read_after(&self, texture: usize, position: usize) -> bool {
+        self.events[position + 1..].iter().any(|event| match event {
+            FrameEvent::Read(key) => *key == texture,
+            FrameEvent::Attachment(usage) => {
+                usage.texture == texture && usage.load == LoadAction::LOAD
+            }
+        })
+    }
+
+    /// Check if the texture never needs to leave tile memory: it is never
+    /// read, never loaded, and none of its stores are consumed.
+    fn is_tile_only(&self, texture: usize) -> bool {
+        self.events
+            .iter()
+            .enumerate()
+            .all(|(position, event)| match event {
+                FrameEvent::Read(key) => *key != texture,
+                FrameEvent::Attachment(usage) => {
+                    usage.texture != texture
+                        || (usage.load != LoadAction::LOAD
+                            && (!is_store(usage.store) || !self.is_read_after(texture, position)))
+                }
+            })
+    }
+}
+
+/// Check if a store action writes the attachment's own contents to memory.
+fn is_store(action: StoreAction) -> bool {
+    action == StoreAction::STORE
+        || action == StoreAction::STORE_AND_MULTISAMPLE_RESOLVE
+        || action == StoreAction::CUSTOM_SAMPLE_DEPTH_STORE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(
+        pass_index: usize,
+        texture: usize,
+        load: LoadAction,
+        store: StoreAction,
+    ) -> FrameEvent {
+        FrameEvent::Attachment(AttachmentUse {
+            pass_index,
+            slot: AttachmentSlot::Color(0),
+            texture,
+            label: None,
+            load,
+            store,
+            storage: StorageMode::PRIVATE,
+        })
+    }
+
+    fn lint(events: Vec<FrameEvent>) -> Vec<AttachmentSuggestion> {
+        let lint = AttachmentLint {
+            events,
+            ..Default::default()
+        };
+        lint.hints().into_iter().map(|h| h.suggestion).collect()
+    }
+
+    #[test]
+    fn test_stored_and_never_read() {
+        let hints = lint(vec![attachment(
+            0,
+            1,
+            LoadAction::CLEAR,
+            StoreAction::STORE,
+        )]);
+        assert_eq!(
+            hints,
+            vec![
+                AttachmentSuggestion::StoreAction(StoreAction::DONT_CARE),
+                AttachmentSuggestion::Memoryless,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stored_and_sampled_later() {
+        let hints = lint(vec![
+            attachment(0, 1, LoadAction::CLEAR, StoreAction::STORE),
+            FrameEvent::Read(1),
+        ]);
+        assert!(hints.is_empty());
+    }
+
+    #[test]
+    fn test_stored_and_loaded_by_later_pass() {
+        let hints = lint(vec![
+            attachment(0, 1, LoadAction::CLEAR, StoreAction::STORE),
+            attachment(1, 1, LoadAction::LOAD, StoreAction::DONT_CARE),
+        ]);
+        assert!(hints.is_empty());
+    }
+
+    #[test]
+    fn test_read_before_store_does_not_count() {
+        let hints = lint(vec![
+            FrameEvent::Read(1),
+            attachment(0, 1, LoadAction::CLEAR, StoreAction::STORE),
+        ]);
+        assert_eq!(
+            hints,
+            vec![AttachmentSuggestion::StoreAction(StoreAction::DONT_CARE)]
+        );
+    }
+
+    #[test]
+    fn test_msaa_store_and_resolve() {
+        let hints = lint(vec![attachment(
+            0,
+            1,
+            LoadAction::CLEAR,
+            StoreAction::STORE_AND_MULTISAMPLE_RESOLVE,
+        )]);
+        assert_eq!(
+            hints[0],
+            AttachmentSuggestion::StoreAction(StoreAction::MULTISAMPLE_RESOLVE)
+        );
+    }
+
+    #[test]
+    fn test_already_memoryless() {
+        let mut event = attachment(0, 1, LoadAction::CLEAR, StoreAction::DONT_CARE);
+        if let FrameEvent::Attachment(usage) = &mut event {
+            usage.storage = StorageMode::MEMORYLESS;
+        }
+        assert!(lint(vec![event]).is_empty());
+    }
+
+    #[test]
+    fn test_textures_retained_until_reset() {
+        use crate::TextureDescriptor;
+        use crate::enums::PixelFormat;
+
+        let device = crate::device::system_default().expect("no Metal device");
+        let descriptor =
+            TextureDescriptor::texture_2d_descriptor(PixelFormat::RGBA8_UNORM, 4, 4, false)
+                .unwrap();
+        let texture = device.new_texture_with_descriptor(&descriptor).unwrap();
+
+        let mut lint = AttachmentLint::new();
+        lint.record_texture_read(&texture);
+        lint.record_texture_read(&texture);
+        let key = texture.as_raw() as usize;
+        drop(texture);
+        assert_eq!(lint.textures.len(), 1);
+        assert_eq!(lint.textures[&key].as_raw() as usize, key);
+
+        lint.finish_frame();
+        assert!(lint.textures.is_empty());
+    }
+
+    #[test]
+    fn test_hint_display() {
+        let hint = AttachmentHint {
+            pass_index: 2,
+            slot: AttachmentSlot::Depth,
+            texture_label: Some("shadow depth".into()),
+            suggestion: AttachmentSuggestion::Memoryless,
+        };
+        let text = hint.to_string();
+        assert!(text.starts_with("pass 2 depth (shadow depth):"));
+        assert!(text.contains("MEMORYLESS"));
+    }
+}
@@ -127,6 +127,20 @@ impl RenderPassColorAttachmentDescriptor {
             msg_send_1::<(), UInteger>(self.as_ptr(), sel!(setSlice:), slice);
         }
     }
+
+    /// Get the depth plane for this attachment.
+    #[inline]
+    pub fn depth_plane(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(depthPlane)) }
+    }
+
+    /// Set the depth plane for this attachment.
+    #[inline]
+    pub fn set_depth_plane(&self, depth_plane: UInteger) {
+        unsafe {
+            msg_send_1::<(), UInteger>(self.as_ptr(), sel!(setDepthPlane:), depth_plane);
+        }
+    }
 }
 
 impl Referencing for RenderPassColorAttachmentDescriptor {
@@ -3,7 +3,7 @@
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
-use mtl_foundation::Referencing;
+use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, sel};
 
 use crate::Texture;
@@ -120,6 +120,166 @@ impl RenderPassDepthAttachmentDescriptor {
             msg_send_1::<(), StoreAction>(self.as_ptr(), sel!(setStoreAction:), store_action);
         }
     }
+
+    /// Get the mipmap level for this attachment.
+    ///
+    /// C++ equivalent: `NS::UInteger level() const`
+    #[inline]
+    pub fn level(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(level)) }
+    }
+
+    /// Set the mipmap level for this attachment.
+    ///
+    /// C++ equivalent: `void setLevel(NS::UInteger)`
+    #[inline]
+    pub fn set_level(&self, level: UInteger) {
+        unsafe {
+            msg_send_1::<(), UInteger>(self.as_ptr(), sel!(setLevel:), level);
+        }
+    }
+
+    /// Get the texture slice for this attachment.
+    ///
+    /// C++ equivalent: `NS::UInteger slice() const`
+    #[inline]
+    pub fn slice(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(slice)) }
+    }
+
+    /// Set the texture slice for this attachment.
+    ///
+    /// C++ equivalent: `void setSlice(NS::UInteger)`
+    #[inline]
+    pub fn set_slice(&self, slice: UInteger) {
+        unsafe {
+            msg_send_1::<(), UInteger>(self.as_ptr(), sel!(setSlice:), slice);
+        }
+    }
+
+    /// Get the depth plane for this attachment.
+    ///
+    /// C++ equivalent: `NS::UInteger depthPlane() const`
+    #[inline]
+    pub fn depth_plane(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(depthPlane)) }
+    }
+
+    /// Set the depth plane for this attachment.
+    ///
+    /// C++ equivalent: `void setDepthPlane(NS::UInteger)`
+    #[inline]
+    pub fn set_depth_plane(&self, depth_plane: UInteger) {
+        unsafe {
+            msg_send_1::<(), UInteger>(self.as_ptr(), sel!(setDepthPlane:), depth_plane);
+        }
+    }
+
+    /// Get the multisample resolve texture for this attachment.
+    ///
+    /// C++ equivalent: `Texture* resolveTexture() const`
+    pub fn resolve_texture(&self) -> Option<Texture> {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.as_ptr(), sel!(resolveTexture));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            Texture::from_raw(ptr)
+        }
+    }
+
+    /// Set the multisample resolve texture for this attachment.
+    ///
+    /// C++ equivalent: `void setResolveTexture(const Texture*)`
+    pub fn set_resolve_texture(&self, texture: Option<&Texture>) {
+        unsafe {
+            let ptr = texture.map_or(std::ptr::null(), |t| t.as_ptr());
+            msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setResolveTexture:), ptr);
+        }
+    }
+
+    /// Get the resolve mipmap level for this attachment.
+    ///
+    /// C++ equivalent: `NS::UInteger resolveLevel() const`
+    #[inline]
+    pub fn resolve_level(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(resolveLevel)) }
+    }
+
+    /// Set the resolve mipmap level for this attachment.
+    ///
+    /// C++ equivalent: `void setResolveLevel(NS::UInteger)`
+    #[inline]
+    pub fn set_resolve_level(&self, level: UInteger) {
+        unsafe {
+            msg_send_1::<(), UInteger>(self.as_ptr(), sel!(setResolveLevel:), level);
+        }
+    }
+
+    /// Get the resolve texture slice for this attachment.
+    ///
+    /// C++ equivalent: `NS::UInteger resolveSlice() const`
+    #[inline]
+    pub fn resolve_slice(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(resolveSlice)) }
+    }
+
+    /// Set the resolve texture slice for this attachment.
+    ///
+    /// C++ equivalent: `void setResolveSlice(NS::UInteger)`
+    #[inline]
+    pub fn set_resolve_slice(&self, slice: UInteger) {
+        unsafe {
+            msg_send_1::<(), UInteger>(self.as_ptr(), sel!(setResolveSlice:), slice);
+        }
+    }
+
+    /// Get the resolve depth plane for this attachment.
+    ///
+    /// C++ equivalent: `NS::UInteger resolveDepthPlane() const`
+    #[inline]
+    pub fn resolve_depth_plane(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_ptr(), sel!(resolveDepthPlane)) }
+    }
+
+    /// Set the resolve depth plane for this attachment.
+    ///
+    /// C++ equivalent: `void setResolveDepthPlane(NS::UInteger)`
+    #[inline]
+    pub fn set_resolve_depth_plane(&self, depth_plane: UInteger) {
+        unsafe {
+            msg_send_1::<(), UInteger>(self.as_ptr(), sel!(setResolveDepthPlane:), depth_plane);
+        }
+    }
+
+    // =========================================================================
+    // Multisample Resolve
+    // =========================================================================
+
+    /// Configure this attachment to resolve into `resolve_texture` at the end
+    /// of the pass.
+    ///
+    /// Sets the resolve texture and depth resolve filter, and picks the store
+    /// action: `STORE_AND_MULTISAMPLE_RESOLVE` if `keep_multisample` is true
+    /// (the multisample texture is read again later), otherwise
+    /// `MULTISAMPLE_RESOLVE`. The attachment's own texture must be a
+    /// multisample texture and `resolve_texture` a single-sample texture of
+    /// the same pixel format.
+    pub fn set_multisample_resolve(
+        &self,
+        resolve_texture: &Texture,
+        filter: MultisampleDepthResolveFilter,
+        keep_multisample: bool,
+    ) {
+        self.set_resolve_texture(Some(resolve_texture));
+        self.set_depth_resolve_filter(filter);
+        self.set_store_action(if keep_multisample {
+            StoreAction::STORE_AND_MULTISAMPLE_RESOLVE
+        } else {
+            StoreAction::MULTISAMPLE_RESOLVE
+        });
+    }
 }
 
 impl Referencing for RenderPassDepthAttachmentDescriptor {
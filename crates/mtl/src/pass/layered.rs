@@ -0,0 +1,244 @@
+//! Layered rendering checks.
+//!
+//! A layered render pass sends each primitive to the array slice, cube face
+//! or 3D depth plane picked by the vertex function's
+//! `[[render_target_array_index]]` output, so a cube map or a pair of eye
+//! views can be drawn in one pass. It needs
+//! [`Device::supports_layered_rendering`](crate::Device::supports_layered_rendering),
+//! a [`render_target_array_length`](RenderPassDescriptor::render_target_array_length)
+//! on the pass that every attachment has enough layers for, and an
+//! [`input_primitive_topology`](RenderPipelineDescriptor::input_primitive_topology)
+//! on the pipeline. Metal reports the last two with a validation abort or
+//! not at all; the checks here report them as errors.
+//!
+//! # Example
+//!
+//! ```ignore
+//! pass.set_render_target_array_length(2);
+//! pass.validate_layered()?;
+//!
+//! pipeline_descriptor.set_input_primitive_topology(PrimitiveTopologyClass::TRIANGLE);
+//! pipeline_descriptor.validate_layered()?;
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use super::{AttachmentSlot, RenderPassDescriptor};
+use crate::Texture;
+use crate::enums::{PrimitiveTopologyClass, TextureType};
+use crate::pipeline::RenderPipelineDescriptor;
+
+/// Number of color attachments checked.
+const MAX_COLOR_ATTACHMENTS: UInteger = 8;
+
+/// Error produced by the layered rendering checks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayeredRenderError {
+    /// The render pass's `render_target_array_length` is 0.
+    MissingArrayLength,
+    /// An attachment's texture is not an array, cube or 3D texture.
+    NotLayered(AttachmentSlot),
+    /// An attachment has fewer layers from its slice or depth plane than
+    /// the render pass's `render_target_array_length`.
+    TooFewLayers {
+        /// The attachment.
+        slot: AttachmentSlot,
+        /// Layers the attachment has.
+        layers: UInteger,
+        /// The pass's `render_target_array_length`.
+        array_length: UInteger,
+    },
+    /// The pipeline's `input_primitive_topology` is unspecified.
+    UnspecifiedInputTopology,
+}
+
+impl fmt::Display for LayeredRenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayeredRenderError::MissingArrayLength => {
+                write!(f, "render pass has no render target array length")
+            }
+            LayeredRenderError::NotLayered(slot) => {
+                write!(f, "{} attachment is not an array, cube or 3D texture", slot)
+            }
+            LayeredRenderError::TooFewLayers {
+                slot,
+                layers,
+                array_length,
+            } => write!(
+                f,
+                "{} attachment has {} layers but the render target array length is {}",
+                slot, layers, array_length
+            ),
+            LayeredRenderError::UnspecifiedInputTopology => write!(
+                f,
+                "layered render pipeline needs an input primitive topology"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayeredRenderError {}
+
+/// Get the number of layers of `texture` from `slice` or `depth_plane`
+/// onwards, or `None` if it cannot be rendered to in layers.
+fn available_layers(
+    texture: &Texture,
+    level: UInteger,
+    slice: UInteger,
+    depth_plane: UInteger,
+) -> Option<UInteger> {
+    let texture_type = texture.texture_type();
+    let layers = if texture_type == TextureType::TYPE_1D_ARRAY
+        || texture_type == TextureType::TYPE_2D_ARRAY
+        || texture_type == TextureType::TYPE_2D_MULTISAMPLE_ARRAY
+    {
+        texture.array_length().saturating_sub(slice)
+    } else if texture_type == TextureType::TYPE_CUBE {
+        6usize.saturating_sub(slice)
+    } else if texture_type == TextureType::TYPE_CUBE_ARRAY {
+        (6 * texture.array_length()).saturating_sub(slice)
+    } else if texture_type == TextureType::TYPE_3D {
+        (texture.depth() >> level)
+            .max(1)
+            .saturating_sub(depth_plane)
+    } else {
+        return None;
+    };
+    Some(layers)
+}
+
+fn check_attachment(
+    slot: AttachmentSlot,
+    layers: Option<UInteger>,
+    array_length: UInteger,
+) -> Result<(), LayeredRenderError> {
+    match layers {
+        None => Err(LayeredRenderError::NotLayered(slot)),
+        Some(layers) if layers < array_length => Err(LayeredRenderError::TooFewLayers {
+            slot,
+            layers,
+            array_length,
+        }),
+        Some(_) => Ok(()),
+    }
+}
+
+impl RenderPassDescriptor {
+    /// Check that the pass can be rendered in layers.
+    ///
+    /// The pass needs a nonzero
+    /// [`render_target_array_length`](Self::render_target_array_length), and
+    /// every attachment must be an array, cube or 3D texture with at least
+    /// that many layers from its slice or depth plane.
+    pub fn validate_layered(&self) -> Result<(), LayeredRenderError> {
+        let array_length = self.render_target_array_length();
+        if array_length == 0 {
+            return Err(LayeredRenderError::MissingArrayLength);
+        }
+
+        if let Some(colors) = self.color_attachments() {
+            for index in 0..MAX_COLOR_ATTACHMENTS {
+                let Some(attachment) = colors.object_at(index) else {
+                    continue;
+                };
+                if let Some(texture) = attachment.texture() {
+                    let layers = available_layers(
+                        &texture,
+                        attachment.level(),
+                        attachment.slice(),
+                        attachment.depth_plane(),
+                    );
+                    check_attachment(AttachmentSlot::Color(index), layers, array_length)?;
+                }
+            }
+        }
+        if let Some(depth) = self.depth_attachment()
+            && let Some(texture) = depth.texture()
+        {
+            let layers =
+                available_layers(&texture, depth.level(), depth.slice(), depth.depth_plane());
+            check_attachment(AttachmentSlot::Depth, layers, array_length)?;
+        }
+        if let Some(stencil) = self.stencil_attachment()
+            && let Some(texture) = stencil.texture()
+        {
+            let layers = available_layers(
+                &texture,
+                stencil.level(),
+                stencil.slice(),
+                stencil.depth_plane(),
+            );
+            check_attachment(AttachmentSlot::Stencil, layers, array_length)?;
+        }
+        Ok(())
+    }
+}
+
+impl RenderPipelineDescriptor {
+    /// Check that the pipeline can be used in a layered render pass.
+    ///
+    /// A vertex function that writes `[[render_target_array_index]]` needs
+    /// an [`input_primitive_topology`](Self::input_primitive_topology) other
+    /// than `UNSPECIFIED`.
+    pub fn validate_layered(&self) -> Result<(), LayeredRenderError> {
+        if self.input_primitive_topology() == PrimitiveTopologyClass::UNSPECIFIED {
+            return Err(LayeredRenderError::UnspecifiedInputTopology);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextureDescriptor;
+    use crate::enums::PixelFormat;
+
+    #[test]
+    fn test_validate_layered_pass() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let pass = RenderPassDescriptor::new().unwrap();
+        assert_eq!(
+            pass.validate_layered(),
+            Err(LayeredRenderError::MissingArrayLength)
+        );
+
+        let flat = TextureDescriptor::depth_2d(PixelFormat::DEPTH32_FLOAT, 16, 16).unwrap();
+        let flat = device.new_texture_with_descriptor(&flat).unwrap();
+        let depth = pass.depth_attachment().unwrap();
+        depth.set_texture(Some(&flat));
+        pass.set_render_target_array_length(6);
+        assert_eq!(
+            pass.validate_layered(),
+            Err(LayeredRenderError::NotLayered(AttachmentSlot::Depth))
+        );
+
+        let cube = TextureDescriptor::depth_cube(PixelFormat::DEPTH32_FLOAT, 16).unwrap();
+        let cube = device.new_texture_with_descriptor(&cube).unwrap();
+        depth.set_texture(Some(&cube));
+        assert_eq!(pass.validate_layered(), Ok(()));
+        depth.set_slice(2);
+        assert_eq!(
+            pass.validate_layered(),
+            Err(LayeredRenderError::TooFewLayers {
+                slot: AttachmentSlot::Depth,
+                layers: 4,
+                array_length: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_layered_pipeline() {
+        let descriptor = RenderPipelineDescriptor::new().unwrap();
+        assert_eq!(
+            descriptor.validate_layered(),
+            Err(LayeredRenderError::UnspecifiedInputTopology)
+        );
+        descriptor.set_input_primitive_topology(PrimitiveTopologyClass::TRIANGLE);
+        assert_eq!(descriptor.validate_layered(), Ok(()));
+    }
+}
@@ -6,18 +6,22 @@
 //! Pass descriptors configure the state and resources for command encoders.
 
 mod attachment;
+mod attachment_lint;
 mod blit_pass;
 mod blit_sample_buffer;
 mod color_attachment;
 mod compute_pass;
 mod compute_sample_buffer;
 mod depth_attachment;
+mod layered;
 mod render_pass;
 mod render_sample_buffer;
 mod resource_state;
+mod shadow_pass;
 mod stencil_attachment;
 
 pub use attachment::RenderPassAttachmentDescriptor;
+pub use attachment_lint::{AttachmentHint, AttachmentLint, AttachmentSlot, AttachmentSuggestion};
 pub use blit_pass::BlitPassDescriptor;
 pub use blit_sample_buffer::{
     BlitPassSampleBufferAttachmentDescriptor, BlitPassSampleBufferAttachmentDescriptorArray,
@@ -30,6 +34,7 @@ pub use compute_sample_buffer::{
     ComputePassSampleBufferAttachmentDescriptor, ComputePassSampleBufferAttachmentDescriptorArray,
 };
 pub use depth_attachment::RenderPassDepthAttachmentDescriptor;
+pub use layered::LayeredRenderError;
 pub use render_pass::RenderPassDescriptor;
 pub use render_sample_buffer::{
     RenderPassSampleBufferAttachmentDescriptor, RenderPassSampleBufferAttachmentDescriptorArray,
@@ -38,6 +43,7 @@ pub use resource_state::{
     ResourceStatePassDescriptor, ResourceStatePassSampleBufferAttachmentDescriptor,
     ResourceStatePassSampleBufferAttachmentDescriptorArray,
 };
+pub use shadow_pass::{CubeShadowPass, DepthBias, ShadowPass, ShadowPassError};
 pub use stencil_attachment::RenderPassStencilAttachmentDescriptor;
 
 #[cfg(test)]
@@ -68,4 +74,47 @@ mod tests {
             std::mem::size_of::<*mut c_void>()
         );
     }
+
+    #[test]
+    fn test_render_pass_tile_memory() {
+        let pass = RenderPassDescriptor::new().unwrap();
+        pass.set_tile_width(16);
+        pass.set_tile_height(32);
+        pass.set_threadgroup_memory_length(1024);
+        pass.set_imageblock_sample_length(64);
+        assert_eq!(pass.tile_width(), 16);
+        assert_eq!(pass.tile_height(), 32);
+        assert_eq!(pass.threadgroup_memory_length(), 1024);
+        assert_eq!(pass.imageblock_sample_length(), 64);
+    }
+
+    #[test]
+    fn test_render_pass_for_texture() {
+        use crate::TextureDescriptor;
+        use crate::enums::{LoadAction, PixelFormat, StorageMode, StoreAction, TextureUsage};
+        use crate::types::ClearColor;
+
+        let device = crate::device::system_default().expect("no Metal device");
+        let new_target = |format| {
+            let descriptor =
+                TextureDescriptor::texture_2d_descriptor(format, 16, 16, false).unwrap();
+            descriptor.set_storage_mode(StorageMode::PRIVATE);
+            descriptor.set_usage(TextureUsage::RENDER_TARGET);
+            device.new_texture_with_descriptor(&descriptor).unwrap()
+        };
+        let color = new_target(PixelFormat::BGRA8_UNORM);
+        let depth = new_target(PixelFormat::DEPTH32_FLOAT_STENCIL8);
+
+        let clear = ClearColor::new(0.1, 0.2, 0.3, 1.0);
+        let pass = RenderPassDescriptor::for_texture(&color, clear, Some(&depth)).unwrap();
+        let attachment = pass.color_attachments().unwrap().object_at(0).unwrap();
+        assert_eq!(attachment.load_action(), LoadAction::CLEAR);
+        assert_eq!(attachment.store_action(), StoreAction::STORE);
+        assert_eq!(attachment.clear_color(), clear);
+        assert!(pass.depth_attachment().unwrap().texture().is_some());
+        assert!(pass.stencil_attachment().unwrap().texture().is_some());
+
+        let pass = RenderPassDescriptor::for_texture(&color, clear, None).unwrap();
+        assert!(pass.depth_attachment().unwrap().texture().is_none());
+    }
 }
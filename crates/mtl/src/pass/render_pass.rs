@@ -6,8 +6,9 @@ use std::ptr::NonNull;
 use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, sel};
 
-use crate::enums::VisibilityResultType;
-use crate::types::SamplePosition;
+use crate::Texture;
+use crate::enums::{LoadAction, StoreAction, VisibilityResultType};
+use crate::types::{ClearColor, SamplePosition};
 
 use super::{
     RenderPassColorAttachmentDescriptorArray, RenderPassDepthAttachmentDescriptor,
@@ -52,6 +53,56 @@ impl RenderPassDescriptor {
         }
     }
 
+    /// Create the usual pass for drawing a frame into `color`, such as a
+    /// drawable's texture.
+    ///
+    /// Color attachment 0 is cleared to `clear_color` and stored. If `depth`
+    /// is given, it is cleared to 1.0 and discarded at the end of the pass,
+    /// and formats with a stencil component also become the stencil
+    /// attachment, cleared to 0.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let pass = RenderPassDescriptor::for_texture(
+    ///     &drawable.texture().unwrap(),
+    ///     ClearColor::new(0.0, 0.0, 0.0, 1.0),
+    ///     Some(&depth_texture),
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn for_texture(
+        color: &Texture,
+        clear_color: ClearColor,
+        depth: Option<&Texture>,
+    ) -> Option<Self> {
+        let pass = Self::new()?;
+        let attachment = pass.color_attachments()?.object_at(0)?;
+        attachment.set_texture(Some(color));
+        attachment.set_load_action(LoadAction::CLEAR);
+        attachment.set_store_action(StoreAction::STORE);
+        attachment.set_clear_color(clear_color);
+
+        if let Some(depth) = depth {
+            let format = depth.pixel_format();
+            if format.is_depth() {
+                let attachment = pass.depth_attachment()?;
+                attachment.set_texture(Some(depth));
+                attachment.set_load_action(LoadAction::CLEAR);
+                attachment.set_store_action(StoreAction::DONT_CARE);
+                attachment.set_clear_depth(1.0);
+            }
+            if format.has_stencil() {
+                let attachment = pass.stencil_attachment()?;
+                attachment.set_texture(Some(depth));
+                attachment.set_load_action(LoadAction::CLEAR);
+                attachment.set_store_action(StoreAction::DONT_CARE);
+                attachment.set_clear_stencil(0);
+            }
+        }
+        Some(pass)
+    }
+
     // =========================================================================
     // Color Attachments
     // =========================================================================
@@ -343,6 +394,10 @@ impl RenderPassDescriptor {
 
     /// Set the imageblock sample length.
     ///
+    /// Explicit imageblock layouts declared by tile shaders need at least
+    /// the pipeline's [`imageblock_sample_length`](crate::RenderPipelineState::imageblock_sample_length)
+    /// bytes per sample; see [`reserve_imageblock_for`](Self::reserve_imageblock_for).
+    ///
     /// C++ equivalent: `void setImageblockSampleLength(NS::UInteger)`
     #[inline]
     pub fn set_imageblock_sample_length(&self, length: UInteger) {
@@ -351,6 +406,29 @@ impl RenderPassDescriptor {
         }
     }
 
+    /// Grow the imageblock sample length to fit `pipeline`'s imageblock.
+    ///
+    /// Call once for each tile or fragment pipeline with an explicit
+    /// imageblock layout that the pass will bind, before creating the
+    /// encoder. The length never shrinks, so the pass fits the largest.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let pass = RenderPassDescriptor::new().unwrap();
+    /// pass.set_tile_width(32);
+    /// pass.set_tile_height(32);
+    /// pass.reserve_imageblock_for(&gbuffer_pipeline);
+    /// pass.reserve_imageblock_for(&lighting_tile_pipeline);
+    /// pass.set_threadgroup_memory_length(32 * 32 * 4);
+    /// ```
+    pub fn reserve_imageblock_for(&self, pipeline: &crate::RenderPipelineState) {
+        let length = pipeline.imageblock_sample_length();
+        if length > self.imageblock_sample_length() {
+            self.set_imageblock_sample_length(length);
+        }
+    }
+
     // =========================================================================
     // Rasterization Rate Map
     // =========================================================================
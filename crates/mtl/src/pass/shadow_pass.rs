@@ -0,0 +1,514 @@
+//! Depth-only render pass setup for shadow maps.
+//!
+//! [`ShadowPass`] owns a shadow map texture together with the render pass
+//! descriptor, depth/stencil state, and comparison sampler needed to render
+//! and sample it, and applies depth bias and depth clamping when encoding.
+//! [`CubeShadowPass`] does the same for a point light's cube map, rendering
+//! all six faces in one layered pass.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let shadow = ShadowPass::new(&device, 2048, 2048, PixelFormat::DEPTH32_FLOAT)?
+//!     .with_bias(DepthBias::new(0.015, 7.0, 0.02));
+//! let desc = shadow.pipeline_descriptor(&shadow_vertex_fn).unwrap();
+//! let pipeline = device.new_render_pipeline_state_with_descriptor(&desc)?;
+//!
+//! // Each frame:
+//! let encoder = shadow.begin(&command_buffer).unwrap();
+//! encoder.set_render_pipeline_state(&pipeline);
+//! // ... draw shadow casters ...
+//! encoder.end_encoding();
+//!
+//! // In the lighting pass:
+//! encoder.set_fragment_texture(shadow.depth_texture(), 0);
+//! encoder.set_fragment_sampler_state(shadow.sampler(), 0);
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use super::RenderPassDescriptor;
+use crate::depth_stencil::{DepthStencilDescriptor, DepthStencilState};
+use crate::enums::{
+    CompareFunction, CullMode, DepthClipMode, LoadAction, PixelFormat, PrimitiveTopologyClass,
+    StoreAction,
+};
+use crate::pipeline::RenderPipelineDescriptor;
+use crate::sampler::{SamplerDescriptor, SamplerState};
+use crate::texture::{Texture, TextureDescriptor};
+use crate::types::Viewport;
+use crate::{CommandBuffer, Device, Function, RenderCommandEncoder};
+
+/// Depth bias applied while rendering shadow casters.
+///
+/// C++ equivalent: arguments of `RenderCommandEncoder::setDepthBias`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DepthBias {
+    /// Constant bias, in units of the minimum resolvable depth.
+    pub constant: f32,
+    /// Bias scaled by the depth slope of each triangle.
+    pub slope_scale: f32,
+    /// Maximum absolute bias; `0.0` disables clamping.
+    pub clamp: f32,
+}
+
+impl DepthBias {
+    /// Create a depth bias.
+    pub const fn new(constant: f32, slope_scale: f32, clamp: f32) -> Self {
+        Self {
+            constant,
+            slope_scale,
+            clamp,
+        }
+    }
+
+    /// No depth bias.
+    pub const fn none() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+}
+
+impl Default for DepthBias {
+    fn default() -> Self {
+        Self::new(0.015, 7.0, 0.02)
+    }
+}
+
+/// Error produced when creating a [`ShadowPass`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShadowPassError {
+    /// The pixel format has no depth component.
+    UnsupportedPixelFormat(PixelFormat),
+    /// The shadow map or one of its state objects could not be created.
+    Allocation,
+    /// The device cannot render all cube faces in one pass.
+    LayeredRenderingUnsupported,
+}
+
+impl fmt::Display for ShadowPassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShadowPassError::UnsupportedPixelFormat(format) => {
+                write!(f, "pixel format {:?} has no depth component", format)
+            }
+            ShadowPassError::Allocation => write!(f, "failed to allocate shadow pass resources"),
+            ShadowPassError::LayeredRenderingUnsupported => {
+                write!(f, "device does not support layered rendering")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShadowPassError {}
+
+/// A shadow map and the state needed to render into and sample it.
+pub struct ShadowPass {
+    depth_texture: Texture,
+    render_pass: RenderPassDescriptor,
+    depth_stencil_state: DepthStencilState,
+    sampler: SamplerState,
+    bias: DepthBias,
+    depth_clamp: bool,
+    cull_mode: CullMode,
+}
+
+impl ShadowPass {
+    /// Create a `width` x `height` shadow map in `depth_format`.
+    ///
+    /// The pass clears depth to 1.0, stores it for later sampling, tests
+    /// with `LESS_EQUAL`, clamps depth instead of clipping, and applies
+    /// [`DepthBias::default`].
+    pub fn new(
+        device: &Device,
+        width: UInteger,
+        height: UInteger,
+        depth_format: PixelFormat,
+    ) -> Result<Self, ShadowPassError> {
+        if !depth_format.is_depth() {
+            return Err(ShadowPassError::UnsupportedPixelFormat(depth_format));
+        }
+
+        let texture_descriptor = TextureDescriptor::depth_2d(depth_format, width, height)
+            .ok_or(ShadowPassError::Allocation)?;
+        let depth_texture = device
+            .new_texture_with_descriptor(&texture_descriptor)
+            .map_err(|_| ShadowPassError::Allocation)?;
+
+        let render_pass = depth_render_pass(&depth_texture)?;
+        let (depth_stencil_state, sampler) = shadow_states(device)?;
+
+        Ok(Self {
+            depth_texture,
+            render_pass,
+            depth_stencil_state,
+            sampler,
+            bias: DepthBias::default(),
+            depth_clamp: true,
+            cull_mode: CullMode::NONE,
+        })
+    }
+
+    /// Set the depth bias applied by [`begin`](Self::begin).
+    pub fn with_bias(mut self, bias: DepthBias) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Set whether geometry outside the light's near/far planes is clamped
+    /// instead of clipped. Enabled by default so casters behind the near
+    /// plane still cast shadows.
+    pub fn with_depth_clamp(mut self, enabled: bool) -> Self {
+        self.depth_clamp = enabled;
+        self
+    }
+
+    /// Set the cull mode applied by [`begin`](Self::begin).
+    ///
+    /// Culling front faces is a common alternative to a large depth bias
+    /// for closed meshes.
+    pub fn with_cull_mode(mut self, mode: CullMode) -> Self {
+        self.cull_mode = mode;
+        self
+    }
+
+    /// Get the shadow map.
+    pub fn depth_texture(&self) -> &Texture {
+        &self.depth_texture
+    }
+
+    /// Get the shadow map's pixel format.
+    pub fn depth_format(&self) -> PixelFormat {
+        self.depth_texture.pixel_format()
+    }
+
+    /// Get the comparison sampler for reading the shadow map.
+    pub fn sampler(&self) -> &SamplerState {
+        &self.sampler
+    }
+
+    /// Get the depth/stencil state used while rendering casters.
+    pub fn depth_stencil_state(&self) -> &DepthStencilState {
+        &self.depth_stencil_state
+    }
+
+    /// Get the render pass descriptor targeting the shadow map.
+    pub fn render_pass_descriptor(&self) -> &RenderPassDescriptor {
+        &self.render_pass
+    }
+
+    /// Get the depth bias.
+    pub fn bias(&self) -> DepthBias {
+        self.bias
+    }
+
+    /// Create a depth-only pipeline descriptor matching the shadow map.
+    pub fn pipeline_descriptor(
+        &self,
+        vertex_function: &Function,
+    ) -> Option<RenderPipelineDescriptor> {
+        RenderPipelineDescriptor::depth_only(vertex_function, self.depth_format())
+    }
+
+    /// Begin rendering into the shadow map.
+    ///
+    /// Creates a render encoder on `command_buffer` and sets the viewport,
+    /// depth/stencil state, depth bias, depth clip mode, and cull mode. The
+    /// caller binds a pipeline, draws, and ends encoding.
+    pub fn begin(&self, command_buffer: &CommandBuffer) -> Option<RenderCommandEncoder> {
+        begin_shadow_encoder(
+            command_buffer,
+            &self.render_pass,
+            &self.depth_texture,
+            &self.depth_stencil_state,
+            self.bias,
+            self.depth_clamp,
+            self.cull_mode,
+        )
+    }
+}
+
+impl fmt::Debug for ShadowPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShadowPass")
+            .field("width", &self.depth_texture.width())
+            .field("height", &self.depth_texture.height())
+            .field("depth_format", &self.depth_format())
+            .field("bias", &self.bias)
+            .field("depth_clamp", &self.depth_clamp)
+            .field("cull_mode", &self.cull_mode)
+            .finish()
+    }
+}
+
+/// Create a render pass that clears `depth_texture` and stores it.
+fn depth_render_pass(depth_texture: &Texture) -> Result<RenderPassDescriptor, ShadowPassError> {
+    let render_pass = RenderPassDescriptor::new().ok_or(ShadowPassError::Allocation)?;
+    let depth = render_pass
+        .depth_attachment()
+        .ok_or(ShadowPassError::Allocation)?;
+    depth.set_texture(Some(depth_texture));
+    depth.set_load_action(LoadAction::CLEAR);
+    depth.set_store_action(StoreAction::STORE);
+    depth.set_clear_depth(1.0);
+    if depth_texture.pixel_format().has_stencil() {
+        let stencil = render_pass
+            .stencil_attachment()
+            .ok_or(ShadowPassError::Allocation)?;
+        stencil.set_texture(Some(depth_texture));
+        stencil.set_load_action(LoadAction::CLEAR);
+        stencil.set_store_action(StoreAction::DONT_CARE);
+    }
+    Ok(render_pass)
+}
+
+/// Create the depth/stencil state for rendering casters and the sampler
+/// for reading the shadow map.
+fn shadow_states(device: &Device) -> Result<(DepthStencilState, SamplerState), ShadowPassError> {
+    let depth_stencil_descriptor =
+        DepthStencilDescriptor::depth_test(CompareFunction::LESS_EQUAL, true)
+            .ok_or(ShadowPassError::Allocation)?;
+    let depth_stencil_state = device
+        .new_depth_stencil_state(&depth_stencil_descriptor)
+        .ok_or(ShadowPassError::Allocation)?;
+
+    let sampler_descriptor =
+        SamplerDescriptor::shadow_comparison().ok_or(ShadowPassError::Allocation)?;
+    let sampler = device
+        .new_sampler_state(&sampler_descriptor)
+        .ok_or(ShadowPassError::Allocation)?;
+    Ok((depth_stencil_state, sampler))
+}
+
+/// Create a render encoder for `render_pass` with the shadow pass state
+/// applied.
+fn begin_shadow_encoder(
+    command_buffer: &CommandBuffer,
+    render_pass: &RenderPassDescriptor,
+    depth_texture: &Texture,
+    depth_stencil_state: &DepthStencilState,
+    bias: DepthBias,
+    depth_clamp: bool,
+    cull_mode: CullMode,
+) -> Option<RenderCommandEncoder> {
+    let encoder = unsafe {
+        RenderCommandEncoder::from_raw(command_buffer.render_command_encoder(render_pass))
+    }?;
+    encoder.set_viewport(Viewport::new(
+        0.0,
+        0.0,
+        depth_texture.width() as f64,
+        depth_texture.height() as f64,
+        0.0,
+        1.0,
+    ));
+    encoder.set_depth_stencil_state(depth_stencil_state);
+    encoder.set_depth_bias(bias.constant, bias.slope_scale, bias.clamp);
+    encoder.set_depth_clip_mode(if depth_clamp {
+        DepthClipMode::CLAMP
+    } else {
+        DepthClipMode::CLIP
+    });
+    encoder.set_cull_mode(cull_mode);
+    Some(encoder)
+}
+
+// ============================================================================
+// Cube Shadow Maps
+// ============================================================================
+
+/// A cube shadow map for a point light, rendered in a single layered pass.
+///
+/// Instead of six passes, draw each caster with six times its instance
+/// count; the vertex function picks the face as `instance_id % 6`,
+/// transforms by that face's view-projection matrix, and writes the face to
+/// a `[[render_target_array_index]]` output.
+///
+/// # Example
+///
+/// ```ignore
+/// let shadow = CubeShadowPass::new(&device, 1024, PixelFormat::DEPTH32_FLOAT)?;
+/// let desc = shadow.pipeline_descriptor(&cube_shadow_vertex_fn).unwrap();
+/// let pipeline = device.new_render_pipeline_state_with_descriptor(&desc)?;
+///
+/// let encoder = shadow.begin(&command_buffer).unwrap();
+/// encoder.set_render_pipeline_state(&pipeline);
+/// encoder.set_vertex_bytes(&face_matrices, 1);
+/// encoder.draw_primitives_instanced(PrimitiveType::TRIANGLE, 0, count, CubeShadowPass::FACES);
+/// encoder.end_encoding();
+/// ```
+pub struct CubeShadowPass {
+    depth_texture: Texture,
+    render_pass: RenderPassDescriptor,
+    depth_stencil_state: DepthStencilState,
+    sampler: SamplerState,
+    bias: DepthBias,
+    depth_clamp: bool,
+    cull_mode: CullMode,
+}
+
+impl CubeShadowPass {
+    /// Number of cube faces rendered per pass.
+    pub const FACES: UInteger = 6;
+
+    /// Create a cube shadow map with `size` x `size` faces in
+    /// `depth_format`.
+    ///
+    /// Uses the same defaults as [`ShadowPass::new`]. Fails with
+    /// [`ShadowPassError::LayeredRenderingUnsupported`] on devices without
+    /// [`supports_layered_rendering`](Device::supports_layered_rendering).
+    pub fn new(
+        device: &Device,
+        size: UInteger,
+        depth_format: PixelFormat,
+    ) -> Result<Self, ShadowPassError> {
+        if !depth_format.is_depth() {
+            return Err(ShadowPassError::UnsupportedPixelFormat(depth_format));
+        }
+        if !device.supports_layered_rendering() {
+            return Err(ShadowPassError::LayeredRenderingUnsupported);
+        }
+
+        let texture_descriptor =
+            TextureDescriptor::depth_cube(depth_format, size).ok_or(ShadowPassError::Allocation)?;
+        let depth_texture = device
+            .new_texture_with_descriptor(&texture_descriptor)
+            .map_err(|_| ShadowPassError::Allocation)?;
+
+        let render_pass = depth_render_pass(&depth_texture)?;
+        render_pass.set_render_target_array_length(Self::FACES);
+        let (depth_stencil_state, sampler) = shadow_states(device)?;
+
+        Ok(Self {
+            depth_texture,
+            render_pass,
+            depth_stencil_state,
+            sampler,
+            bias: DepthBias::default(),
+            depth_clamp: true,
+            cull_mode: CullMode::NONE,
+        })
+    }
+
+    /// Set the depth bias applied by [`begin`](Self::begin).
+    pub fn with_bias(mut self, bias: DepthBias) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Set whether geometry outside the light's near/far planes is clamped
+    /// instead of clipped.
+    pub fn with_depth_clamp(mut self, enabled: bool) -> Self {
+        self.depth_clamp = enabled;
+        self
+    }
+
+    /// Set the cull mode applied by [`begin`](Self::begin).
+    pub fn with_cull_mode(mut self, mode: CullMode) -> Self {
+        self.cull_mode = mode;
+        self
+    }
+
+    /// Get the cube shadow map.
+    pub fn depth_texture(&self) -> &Texture {
+        &self.depth_texture
+    }
+
+    /// Get the shadow map's pixel format.
+    pub fn depth_format(&self) -> PixelFormat {
+        self.depth_texture.pixel_format()
+    }
+
+    /// Get the width and height of each face.
+    pub fn size(&self) -> UInteger {
+        self.depth_texture.width()
+    }
+
+    /// Get the comparison sampler for reading the shadow map.
+    pub fn sampler(&self) -> &SamplerState {
+        &self.sampler
+    }
+
+    /// Get the depth/stencil state used while rendering casters.
+    pub fn depth_stencil_state(&self) -> &DepthStencilState {
+        &self.depth_stencil_state
+    }
+
+    /// Get the layered render pass descriptor targeting all six faces.
+    pub fn render_pass_descriptor(&self) -> &RenderPassDescriptor {
+        &self.render_pass
+    }
+
+    /// Get the depth bias.
+    pub fn bias(&self) -> DepthBias {
+        self.bias
+    }
+
+    /// Create a depth-only pipeline descriptor for layered rendering into
+    /// the shadow map, with a triangle input primitive topology.
+    pub fn pipeline_descriptor(
+        &self,
+        vertex_function: &Function,
+    ) -> Option<RenderPipelineDescriptor> {
+        let descriptor =
+            RenderPipelineDescriptor::depth_only(vertex_function, self.depth_format())?;
+        descriptor.set_input_primitive_topology(PrimitiveTopologyClass::TRIANGLE);
+        Some(descriptor)
+    }
+
+    /// Begin rendering into all six faces of the shadow map.
+    ///
+    /// Sets the same state as [`ShadowPass::begin`].
+    pub fn begin(&self, command_buffer: &CommandBuffer) -> Option<RenderCommandEncoder> {
+        begin_shadow_encoder(
+            command_buffer,
+            &self.render_pass,
+            &self.depth_texture,
+            &self.depth_stencil_state,
+            self.bias,
+            self.depth_clamp,
+            self.cull_mode,
+        )
+    }
+}
+
+impl fmt::Debug for CubeShadowPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CubeShadowPass")
+            .field("size", &self.size())
+            .field("depth_format", &self.depth_format())
+            .field("bias", &self.bias)
+            .field("depth_clamp", &self.depth_clamp)
+            .field("cull_mode", &self.cull_mode)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cube_shadow_pass() {
+        let device = crate::device::system_default().expect("no Metal device");
+        match CubeShadowPass::new(&device, 64, PixelFormat::DEPTH32_FLOAT) {
+            Ok(shadow) => {
+                assert_eq!(shadow.size(), 64);
+                assert_eq!(shadow.render_pass_descriptor().validate_layered(), Ok(()));
+            }
+            Err(error) => {
+                assert!(!device.supports_layered_rendering());
+                assert_eq!(error, ShadowPassError::LayeredRenderingUnsupported);
+            }
+        }
+    }
+
+    #[test]
+    fn test_depth_bias_default() {
+        let bias = DepthBias::default();
+        assert!(bias.constant > 0.0);
+        assert!(bias.slope_scale > 0.0);
+        assert_eq!(DepthBias::none(), DepthBias::new(0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,445 @@
+//! Pipeline compile time and binary size tracking.
+//!
+//! Metal has no built-in way to see which pipeline permutations are expensive
+//! to build. [`PipelineCompileStats`] records the wall time of each pipeline
+//! creation (and, where known, the size of the compiled binary), keyed by
+//! pipeline name. The map can be saved to disk and loaded again on the next
+//! run so regressions can be spotted with [`PipelineCompileStats::regressions`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use mtl_gpu::pipeline::PipelineCompileStats;
+//!
+//! let stats = PipelineCompileStats::load("pipeline_stats.tsv").unwrap_or_default();
+//! let pipeline = stats.new_compute_pipeline_state(&device, &descriptor, PipelineOption::NONE)?;
+//! println!("{}", stats.report());
+//! stats.save("pipeline_stats.tsv")?;
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use mtl_foundation::Referencing;
+
+use crate::Device;
+use crate::enums::PipelineOption;
+
+use super::{
+    ComputePipelineDescriptor, ComputePipelineState, RenderPipelineDescriptor, RenderPipelineState,
+};
+
+/// Header line written at the top of saved stats files.
+const FILE_HEADER: &str = "# name\tkind\tcount\ttotal_ns\tmax_ns\tlast_ns\tbinary_size";
+
+/// The kind of pipeline a record describes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PipelineKind {
+    Render,
+    Compute,
+    Tile,
+    Mesh,
+}
+
+impl PipelineKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            PipelineKind::Render => "render",
+            PipelineKind::Compute => "compute",
+            PipelineKind::Tile => "tile",
+            PipelineKind::Mesh => "mesh",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "render" => Some(PipelineKind::Render),
+            "compute" => Some(PipelineKind::Compute),
+            "tile" => Some(PipelineKind::Tile),
+            "mesh" => Some(PipelineKind::Mesh),
+            _ => None,
+        }
+    }
+}
+
+/// Accumulated compile statistics for a single named pipeline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipelineCompileRecord {
+    /// Pipeline name (descriptor label or function name).
+    pub name: String,
+    /// The kind of pipeline.
+    pub kind: PipelineKind,
+    /// Number of times the pipeline was compiled.
+    pub count: u64,
+    /// Total compile time across all compilations.
+    pub total_time: Duration,
+    /// Slowest single compilation.
+    pub max_time: Duration,
+    /// Most recent compilation time.
+    pub last_time: Duration,
+    /// Size of the compiled binary in bytes, if known.
+    pub binary_size: Option<u64>,
+}
+
+impl PipelineCompileRecord {
+    /// Average compile time across all compilations.
+    pub fn mean_time(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos((self.total_time.as_nanos() / self.count as u128) as u64)
+    }
+}
+
+/// A pipeline whose compile time grew relative to a baseline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineCompileRegression {
+    /// Pipeline name.
+    pub name: String,
+    /// Mean compile time in the baseline.
+    pub baseline: Duration,
+    /// Mean compile time in the current stats.
+    pub current: Duration,
+    /// `current / baseline`.
+    pub ratio: f64,
+}
+
+/// Thread-safe map of pipeline compile times and sizes.
+#[derive(Debug, Default)]
+pub struct PipelineCompileStats {
+    records: Mutex<BTreeMap<String, PipelineCompileRecord>>,
+}
+
+impl PipelineCompileStats {
+    /// Create an empty stats map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one compilation of the named pipeline.
+    pub fn record(&self, name: &str, kind: PipelineKind, time: Duration) {
+        let mut records = self.records.lock().unwrap();
+        let record = records
+            .entry(name.to_string())
+            .or_insert_with(|| PipelineCompileRecord {
+                name: name.to_string(),
+                kind,
+                count: 0,
+                total_time: Duration::ZERO,
+                max_time: Duration::ZERO,
+                last_time: Duration::ZERO,
+                binary_size: None,
+            });
+        record.kind = kind;
+        record.count += 1;
+        record.total_time += time;
+        record.max_time = record.max_time.max(time);
+        record.last_time = time;
+    }
+
+    /// Set the compiled binary size of a previously recorded pipeline.
+    ///
+    /// Returns `false` if no pipeline with that name has been recorded.
+    pub fn set_binary_size(&self, name: &str, size: u64) -> bool {
+        match self.records.lock().unwrap().get_mut(name) {
+            Some(record) => {
+                record.binary_size = Some(size);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Time `f` and record it under `name` if it succeeds.
+    pub fn time<T, E>(
+        &self,
+        name: &str,
+        kind: PipelineKind,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let start = Instant::now();
+        let result = f();
+        if result.is_ok() {
+            self.record(name, kind, start.elapsed());
+        }
+        result
+    }
+
+    /// Create a compute pipeline state and record its compile time.
+    ///
+    /// The record is keyed by the descriptor label, falling back to the
+    /// compute function name.
+    pub fn new_compute_pipeline_state(
+        &self,
+        device: &Device,
+        descriptor: &ComputePipelineDescriptor,
+        options: PipelineOption,
+    ) -> Result<ComputePipelineState, mtl_foundation::Error> {
+        let name = descriptor
+            .label()
+            .or_else(|| descriptor.compute_function().and_then(|f| f.name()))
+            .unwrap_or_else(|| "<unnamed compute>".to_string());
+        self.time(&name, PipelineKind::Compute, || unsafe {
+            device.new_compute_pipeline_state_with_descriptor(
+                descriptor.as_ptr(),
+                options,
+                std::ptr::null_mut(),
+            )
+        })
+    }
+
+    /// Create a render pipeline state and record its compile time.
+    ///
+    /// The record is keyed by the descriptor label, falling back to the
+    /// vertex function name.
+    pub fn new_render_pipeline_state(
+        &self,
+        device: &Device,
+        descriptor: &RenderPipelineDescriptor,
+        options: PipelineOption,
+    ) -> Result<RenderPipelineState, mtl_foundation::Error> {
+        let name = descriptor
+            .label()
+            .or_else(|| descriptor.vertex_function().and_then(|f| f.name()))
+            .unwrap_or_else(|| "<unnamed render>".to_string());
+        self.time(&name, PipelineKind::Render, || unsafe {
+            device.new_render_pipeline_state_with_reflection(
+                descriptor.as_ptr(),
+                options,
+                std::ptr::null_mut(),
+            )
+        })
+    }
+
+    /// Get the record for a named pipeline.
+    pub fn get(&self, name: &str) -> Option<PipelineCompileRecord> {
+        self.records.lock().unwrap().get(name).cloned()
+    }
+
+    /// Get all records, slowest (by total compile time) first.
+    pub fn records(&self) -> Vec<PipelineCompileRecord> {
+        let mut records: Vec<_> = self.records.lock().unwrap().values().cloned().collect();
+        records.sort_by_key(|r| std::cmp::Reverse(r.total_time));
+        records
+    }
+
+    /// Number of distinct pipelines recorded.
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    /// Check if no pipelines have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all records.
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+
+    /// Merge another stats map into this one.
+    pub fn merge(&self, other: &PipelineCompileStats) {
+        let other = other.records.lock().unwrap().clone();
+        let mut records = self.records.lock().unwrap();
+        for (name, theirs) in other {
+            match records.get_mut(&name) {
+                Some(ours) => {
+                    ours.count += theirs.count;
+                    ours.total_time += theirs.total_time;
+                    ours.max_time = ours.max_time.max(theirs.max_time);
+                    ours.last_time = theirs.last_time;
+                    ours.binary_size = theirs.binary_size.or(ours.binary_size);
+                }
+                None => {
+                    records.insert(name, theirs);
+                }
+            }
+        }
+    }
+
+    /// Find pipelines whose mean compile time grew by more than `threshold`
+    /// (e.g. `1.5` for 50% slower) relative to `baseline`.
+    pub fn regressions(
+        &self,
+        baseline: &PipelineCompileStats,
+        threshold: f64,
+    ) -> Vec<PipelineCompileRegression> {
+        let baseline = baseline.records.lock().unwrap();
+        let mut regressions: Vec<_> = self
+            .records
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|current| {
+                let base = baseline.get(&current.name)?;
+                let base_time = base.mean_time();
+                let current_time = current.mean_time();
+                if base_time.is_zero() {
+                    return None;
+                }
+                let ratio = current_time.as_secs_f64() / base_time.as_secs_f64();
+                (ratio > threshold).then(|| PipelineCompileRegression {
+                    name: current.name.clone(),
+                    baseline: base_time,
+                    current: current_time,
+                    ratio,
+                })
+            })
+            .collect();
+        regressions.sort_by(|a, b| b.ratio.total_cmp(&a.ratio));
+        regressions
+    }
+
+    /// Render a human-readable report, slowest pipelines first.
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:<40} {:>8} {:>6} {:>12} {:>12} {:>12}",
+            "pipeline", "kind", "count", "mean (ms)", "max (ms)", "size (B)"
+        );
+        for record in self.records() {
+            let size = record
+                .binary_size
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let _ = writeln!(
+                out,
+                "{:<40} {:>8} {:>6} {:>12.3} {:>12.3} {:>12}",
+                record.name,
+                record.kind.as_str(),
+                record.count,
+                record.mean_time().as_secs_f64() * 1000.0,
+                record.max_time.as_secs_f64() * 1000.0,
+                size
+            );
+        }
+        out
+    }
+
+    /// Serialize the stats as tab-separated text.
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::from(FILE_HEADER);
+        out.push('\n');
+        for record in self.records.lock().unwrap().values() {
+            let size = record
+                .binary_size
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                record.name.replace(['\t', '\n'], " "),
+                record.kind.as_str(),
+                record.count,
+                record.total_time.as_nanos(),
+                record.max_time.as_nanos(),
+                record.last_time.as_nanos(),
+                size
+            );
+        }
+        out
+    }
+
+    /// Parse stats previously produced by [`to_tsv`](Self::to_tsv).
+    ///
+    /// Malformed lines are skipped.
+    pub fn from_tsv(text: &str) -> Self {
+        let mut records = BTreeMap::new();
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(record) = parse_line(line) {
+                records.insert(record.name.clone(), record);
+            }
+        }
+        Self {
+            records: Mutex::new(records),
+        }
+    }
+
+    /// Save the stats to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_tsv())
+    }
+
+    /// Load stats from a file written by [`save`](Self::save).
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::from_tsv(&std::fs::read_to_string(path)?))
+    }
+}
+
+fn parse_line(line: &str) -> Option<PipelineCompileRecord> {
+    let mut fields = line.split('\t');
+    let name = fields.next()?.to_string();
+    let kind = PipelineKind::parse(fields.next()?)?;
+    let count = fields.next()?.parse().ok()?;
+    let total_time = Duration::from_nanos(fields.next()?.parse().ok()?);
+    let max_time = Duration::from_nanos(fields.next()?.parse().ok()?);
+    let last_time = Duration::from_nanos(fields.next()?.parse().ok()?);
+    let binary_size = match fields.next()? {
+        "-" => None,
+        s => Some(s.parse().ok()?),
+    };
+    Some(PipelineCompileRecord {
+        name,
+        kind,
+        count,
+        total_time,
+        max_time,
+        last_time,
+        binary_size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_accumulates() {
+        let stats = PipelineCompileStats::new();
+        stats.record("blur", PipelineKind::Compute, Duration::from_millis(10));
+        stats.record("blur", PipelineKind::Compute, Duration::from_millis(30));
+
+        let record = stats.get("blur").unwrap();
+        assert_eq!(record.count, 2);
+        assert_eq!(record.total_time, Duration::from_millis(40));
+        assert_eq!(record.max_time, Duration::from_millis(30));
+        assert_eq!(record.last_time, Duration::from_millis(30));
+        assert_eq!(record.mean_time(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_tsv_round_trip() {
+        let stats = PipelineCompileStats::new();
+        stats.record("blur", PipelineKind::Compute, Duration::from_millis(10));
+        stats.record("forward", PipelineKind::Render, Duration::from_millis(25));
+        assert!(stats.set_binary_size("forward", 4096));
+        assert!(!stats.set_binary_size("missing", 1));
+
+        let loaded = PipelineCompileStats::from_tsv(&stats.to_tsv());
+        assert_eq!(loaded.records(), stats.records());
+    }
+
+    #[test]
+    fn test_regressions() {
+        let baseline = PipelineCompileStats::new();
+        baseline.record("a", PipelineKind::Compute, Duration::from_millis(10));
+        baseline.record("b", PipelineKind::Compute, Duration::from_millis(10));
+
+        let current = PipelineCompileStats::new();
+        current.record("a", PipelineKind::Compute, Duration::from_millis(30));
+        current.record("b", PipelineKind::Compute, Duration::from_millis(11));
+        current.record("c", PipelineKind::Compute, Duration::from_millis(50));
+
+        let regressions = current.regressions(&baseline, 1.5);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].name, "a");
+    }
+}
@@ -420,6 +420,23 @@ impl ComputePipelineDescriptor {
         }
     }
 
+    /// Get the dynamic libraries loaded with the pipeline.
+    ///
+    /// C++ equivalent: `NS::Array* preloadedLibraries() const`
+    pub fn preloaded_libraries(&self) -> Vec<crate::DynamicLibrary> {
+        unsafe { crate::library::libraries_from_array(self.preloaded_libraries_raw()) }
+    }
+
+    /// Set the dynamic libraries loaded with the pipeline, so its functions
+    /// can call functions exported by them.
+    ///
+    /// C++ equivalent: `void setPreloadedLibraries(const NS::Array*)`
+    pub fn set_preloaded_libraries(&self, libraries: &[&crate::DynamicLibrary]) {
+        crate::library::with_library_array(libraries, |array| unsafe {
+            self.set_preloaded_libraries_raw(array)
+        });
+    }
+
     // =========================================================================
     // Insert Libraries
     // =========================================================================
@@ -443,6 +460,22 @@ impl ComputePipelineDescriptor {
             msg_send_1::<(), *const c_void>(self.as_ptr(), sel!(setInsertLibraries:), libraries);
         }
     }
+
+    /// Get the dynamic libraries linked into the pipeline.
+    ///
+    /// C++ equivalent: `NS::Array* insertLibraries() const`
+    pub fn insert_libraries(&self) -> Vec<crate::DynamicLibrary> {
+        unsafe { crate::library::libraries_from_array(self.insert_libraries_raw()) }
+    }
+
+    /// Set the dynamic libraries linked into the pipeline.
+    ///
+    /// C++ equivalent: `void setInsertLibraries(const NS::Array*)`
+    pub fn set_insert_libraries(&self, libraries: &[&crate::DynamicLibrary]) {
+        crate::library::with_library_array(libraries, |array| unsafe {
+            self.set_insert_libraries_raw(array)
+        });
+    }
 }
 
 impl Clone for ComputePipelineDescriptor {
@@ -140,6 +140,20 @@ impl ComputePipelineState {
         unsafe { msg_send_0(self.as_ptr(), sel!(requiredThreadsPerThreadgroup)) }
     }
 
+    /// Pick a threadgroup size for dispatching over `grid`.
+    ///
+    /// Uses the thread execution width (SIMD group size) for the X dimension
+    /// and fills the remaining threads up to `maxTotalThreadsPerThreadgroup`
+    /// in Y, clamping each dimension to the grid so small grids don't launch
+    /// idle threads. 1D grids use the full threadgroup width in X.
+    pub fn threadgroup_size_for_grid(&self, grid: Size) -> Size {
+        threadgroup_size_for_grid(
+            self.max_total_threads_per_threadgroup(),
+            self.thread_execution_width(),
+            grid,
+        )
+    }
+
     // =========================================================================
     // Function Handles
     // =========================================================================
@@ -353,3 +367,59 @@ impl std::fmt::Debug for ComputePipelineState {
             .finish()
     }
 }
+
+/// Compute a threadgroup size from pipeline limits and grid dimensions.
+pub(crate) fn threadgroup_size_for_grid(
+    max_total_threads: UInteger,
+    execution_width: UInteger,
+    grid: Size,
+) -> Size {
+    let max_total = max_total_threads.max(1);
+    let grid_width = grid.width.max(1);
+    let grid_height = grid.height.max(1);
+    let grid_depth = grid.depth.max(1);
+
+    if grid_height == 1 && grid_depth == 1 {
+        return Size::new(max_total.min(grid_width), 1, 1);
+    }
+
+    let width = execution_width.clamp(1, max_total).min(grid_width);
+    let height = (max_total / width).min(grid_height).max(1);
+    let depth = (max_total / (width * height)).min(grid_depth).max(1);
+    Size::new(width, height, depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threadgroup_size_1d() {
+        let size = threadgroup_size_for_grid(1024, 32, Size::new(100_000, 1, 1));
+        assert_eq!(size, Size::new(1024, 1, 1));
+
+        let size = threadgroup_size_for_grid(1024, 32, Size::new(10, 1, 1));
+        assert_eq!(size, Size::new(10, 1, 1));
+    }
+
+    #[test]
+    fn test_threadgroup_size_2d() {
+        let size = threadgroup_size_for_grid(1024, 32, Size::new(1920, 1080, 1));
+        assert_eq!(size, Size::new(32, 32, 1));
+
+        let size = threadgroup_size_for_grid(512, 32, Size::new(1920, 4, 1));
+        assert_eq!(size, Size::new(32, 4, 1));
+    }
+
+    #[test]
+    fn test_threadgroup_size_3d() {
+        let size = threadgroup_size_for_grid(1024, 32, Size::new(64, 8, 64));
+        assert_eq!(size, Size::new(32, 8, 4));
+    }
+
+    #[test]
+    fn test_threadgroup_size_never_exceeds_limit() {
+        let size = threadgroup_size_for_grid(256, 64, Size::new(7, 7, 7));
+        assert!(size.width * size.height * size.depth <= 256);
+    }
+}
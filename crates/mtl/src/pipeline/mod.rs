@@ -6,30 +6,51 @@
 
 mod buffer_descriptor;
 mod color_attachment;
+mod compile_stats;
 mod compute_descriptor;
 mod compute_state;
 mod functions_descriptor;
 mod mesh_pipeline;
+mod ray_tracing;
 mod reflection;
+mod render_builder;
+mod render_config;
 mod render_descriptor;
 mod render_state;
+mod swap;
 mod tile_pipeline;
+mod vertex_capture;
 
 // Re-export all public types
 pub use buffer_descriptor::{PipelineBufferDescriptor, PipelineBufferDescriptorArray};
 pub use color_attachment::{
     RenderPipelineColorAttachmentDescriptor, RenderPipelineColorAttachmentDescriptorArray,
 };
+pub use compile_stats::{
+    PipelineCompileRecord, PipelineCompileRegression, PipelineCompileStats, PipelineKind,
+};
 pub use compute_descriptor::ComputePipelineDescriptor;
 pub use compute_state::ComputePipelineState;
 pub use functions_descriptor::{
     LogicalToPhysicalColorAttachmentMap, RenderPipelineFunctionsDescriptor,
 };
 pub use mesh_pipeline::MeshRenderPipelineDescriptor;
+pub use ray_tracing::{
+    RayTracingPipeline, RayTracingPipelineBuildError, RayTracingPipelineBuilder,
+};
 pub use reflection::{ComputePipelineReflection, RenderPipelineReflection};
+pub use render_builder::{
+    ColorBlending, MAX_COLOR_ATTACHMENTS, RenderPipelineBuildError, RenderPipelineDescriptorBuilder,
+};
+pub use render_config::{ColorAttachmentConfig, RenderPipelineConfig};
 pub use render_descriptor::RenderPipelineDescriptor;
 pub use render_state::RenderPipelineState;
+pub use swap::{
+    BindingChange, BindingInterface, BindingSignature, BindingStage, PipelineSwap,
+    PipelineSwapError, ReflectedPipeline,
+};
 pub use tile_pipeline::{
     TileRenderPipelineColorAttachmentDescriptor, TileRenderPipelineColorAttachmentDescriptorArray,
     TileRenderPipelineDescriptor,
 };
+pub use vertex_capture::{VertexCapture, VertexCaptureError};
@@ -0,0 +1,447 @@
+//! Compute pipelines with function tables for ray tracing.
+//!
+//! Calling visible or intersection functions from a kernel takes four steps
+//! across as many modules: linking the functions into the
+//! [`ComputePipelineDescriptor`], compiling the pipeline, getting a
+//! [`FunctionHandle`] for each function from the pipeline, and sizing and
+//! filling a [`VisibleFunctionTable`] and an [`IntersectionFunctionTable`].
+//! [`RayTracingPipelineBuilder`] does all four and returns a
+//! [`RayTracingPipeline`] ready to bind.
+//!
+//! Functions are stored in their table in the order they were added, so
+//! index `i` in the shader calls the `i`th visible or intersection function.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let pipeline = RayTracingPipelineBuilder::new(&kernel)
+//!     .unwrap()
+//!     .label("raytrace")
+//!     .visible_function(&shade_diffuse)
+//!     .visible_function(&shade_mirror)
+//!     .intersection_function(&sphere_intersection)
+//!     .build(&device)?;
+//!
+//! pipeline.bind(&encoder, 1, 2);
+//! encoder.set_acceleration_structure(&scene, 0);
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::{Range, Referencing, UInteger};
+
+use super::{ComputePipelineDescriptor, ComputePipelineState};
+use crate::enums::{FunctionType, PipelineOption};
+use crate::{
+    ComputeCommandEncoder, Device, Function, FunctionHandle, IntersectionFunctionTable,
+    IntersectionFunctionTableDescriptor, LinkedFunctions, VisibleFunctionTable,
+    VisibleFunctionTableDescriptor,
+};
+
+/// Error produced by [`RayTracingPipelineBuilder::build`].
+#[derive(Clone, Debug)]
+pub enum RayTracingPipelineBuildError {
+    /// No kernel function was set.
+    MissingKernelFunction,
+    /// A function has the wrong type for where it was added.
+    WrongFunctionType {
+        /// The function's name.
+        name: String,
+        /// The type it needs.
+        expected: FunctionType,
+        /// The type it has.
+        found: FunctionType,
+    },
+    /// The device can't call functions through function tables.
+    Unsupported,
+    /// Metal failed to compile the pipeline.
+    Compile(mtl_foundation::Error),
+    /// The compiled pipeline has no handle for a linked function.
+    MissingFunctionHandle(String),
+    /// Metal failed to create a function table.
+    TableCreation,
+}
+
+impl fmt::Display for RayTracingPipelineBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RayTracingPipelineBuildError::MissingKernelFunction => {
+                write!(f, "ray tracing pipeline requires a kernel function")
+            }
+            RayTracingPipelineBuildError::WrongFunctionType {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "function {} has type {:?} but {:?} is required",
+                name, found, expected
+            ),
+            RayTracingPipelineBuildError::Unsupported => {
+                write!(f, "device does not support function tables")
+            }
+            RayTracingPipelineBuildError::Compile(err) => {
+                write!(f, "ray tracing pipeline compilation failed: {}", err)
+            }
+            RayTracingPipelineBuildError::MissingFunctionHandle(name) => {
+                write!(f, "pipeline has no handle for function {}", name)
+            }
+            RayTracingPipelineBuildError::TableCreation => {
+                write!(f, "failed to create function table")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RayTracingPipelineBuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RayTracingPipelineBuildError::Compile(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// RayTracingPipelineBuilder
+// ============================================================================
+
+/// Links visible and intersection functions into a compute pipeline and
+/// creates the function tables that call them.
+pub struct RayTracingPipelineBuilder {
+    descriptor: ComputePipelineDescriptor,
+    visible_functions: Vec<Function>,
+    intersection_functions: Vec<Function>,
+}
+
+impl RayTracingPipelineBuilder {
+    /// Start from an empty descriptor with `kernel` as the compute function.
+    pub fn new(kernel: &Function) -> Option<Self> {
+        let descriptor = ComputePipelineDescriptor::new()?;
+        descriptor.set_compute_function(Some(kernel));
+        Some(Self::with_descriptor(descriptor))
+    }
+
+    /// Start from an existing descriptor.
+    ///
+    /// The descriptor's linked functions are replaced by
+    /// [`build`](Self::build).
+    pub fn with_descriptor(descriptor: ComputePipelineDescriptor) -> Self {
+        Self {
+            descriptor,
+            visible_functions: Vec::new(),
+            intersection_functions: Vec::new(),
+        }
+    }
+
+    /// Set the pipeline's label.
+    pub fn label(self, label: &str) -> Self {
+        self.descriptor.set_label(label);
+        self
+    }
+
+    /// Set the deepest chain of function calls the kernel makes.
+    pub fn max_call_stack_depth(self, depth: UInteger) -> Self {
+        self.descriptor.set_max_call_stack_depth(depth);
+        self
+    }
+
+    /// Add a `[[visible]]` function at the next visible function table index.
+    pub fn visible_function(mut self, function: &Function) -> Self {
+        self.visible_functions.push(function.clone());
+        self
+    }
+
+    /// Add an `[[intersection(...)]]` function at the next intersection
+    /// function table index.
+    pub fn intersection_function(mut self, function: &Function) -> Self {
+        self.intersection_functions.push(function.clone());
+        self
+    }
+
+    /// Get the descriptor being configured.
+    pub fn descriptor(&self) -> &ComputePipelineDescriptor {
+        &self.descriptor
+    }
+
+    /// Check the functions against `device` without compiling.
+    pub fn validate(&self, device: &Device) -> Result<(), RayTracingPipelineBuildError> {
+        let Some(kernel) = self.descriptor.compute_function() else {
+            return Err(RayTracingPipelineBuildError::MissingKernelFunction);
+        };
+        check_function_type(&kernel, FunctionType::KERNEL)?;
+        for function in &self.visible_functions {
+            check_function_type(function, FunctionType::VISIBLE)?;
+        }
+        for function in &self.intersection_functions {
+            check_function_type(function, FunctionType::INTERSECTION)?;
+        }
+        if !self.visible_functions.is_empty() && !device.supports_function_pointers() {
+            return Err(RayTracingPipelineBuildError::Unsupported);
+        }
+        if !self.intersection_functions.is_empty() && !device.supports_raytracing() {
+            return Err(RayTracingPipelineBuildError::Unsupported);
+        }
+        Ok(())
+    }
+
+    /// Validate the functions, compile the pipeline on `device` and fill its
+    /// function tables.
+    pub fn build(
+        &self,
+        device: &Device,
+    ) -> Result<RayTracingPipeline, RayTracingPipelineBuildError> {
+        self.validate(device)?;
+        self.link_functions()?;
+        let pipeline = unsafe {
+            device.new_compute_pipeline_state_with_descriptor(
+                self.descriptor.as_ptr(),
+                PipelineOption::NONE,
+                std::ptr::null_mut(),
+            )
+        }
+        .map_err(RayTracingPipelineBuildError::Compile)?;
+
+        let visible_handles = function_handles(&pipeline, &self.visible_functions)?;
+        let visible_function_table = if visible_handles.is_empty() {
+            None
+        } else {
+            let descriptor = VisibleFunctionTableDescriptor::new()
+                .ok_or(RayTracingPipelineBuildError::TableCreation)?;
+            descriptor.set_function_count(visible_handles.len() as UInteger);
+            let table = pipeline
+                .new_visible_function_table(&descriptor)
+                .ok_or(RayTracingPipelineBuildError::TableCreation)?;
+            let handles: Vec<_> = visible_handles.iter().collect();
+            table.set_functions(&handles, Range::new(0, handles.len() as UInteger));
+            Some(table)
+        };
+
+        let intersection_handles = function_handles(&pipeline, &self.intersection_functions)?;
+        let intersection_function_table = if intersection_handles.is_empty() {
+            None
+        } else {
+            let descriptor = IntersectionFunctionTableDescriptor::new()
+                .ok_or(RayTracingPipelineBuildError::TableCreation)?;
+            descriptor.set_function_count(intersection_handles.len() as UInteger);
+            let table = pipeline
+                .new_intersection_function_table(&descriptor)
+                .ok_or(RayTracingPipelineBuildError::TableCreation)?;
+            let handles: Vec<_> = intersection_handles.iter().collect();
+            table.set_functions(&handles, Range::new(0, handles.len() as UInteger));
+            Some(table)
+        };
+
+        Ok(RayTracingPipeline {
+            pipeline,
+            visible_function_names: self.visible_functions.iter().map(Function::name).collect(),
+            intersection_function_names: self
+                .intersection_functions
+                .iter()
+                .map(Function::name)
+                .collect(),
+            visible_function_table,
+            intersection_function_table,
+        })
+    }
+
+    /// Point the descriptor's linked functions at the added functions.
+    fn link_functions(&self) -> Result<(), RayTracingPipelineBuildError> {
+        let objects: Vec<*const std::ffi::c_void> = self
+            .visible_functions
+            .iter()
+            .chain(&self.intersection_functions)
+            .map(Function::as_ptr)
+            .collect();
+        if objects.is_empty() {
+            self.descriptor.set_linked_functions(None);
+            return Ok(());
+        }
+        let linked = LinkedFunctions::new().ok_or(RayTracingPipelineBuildError::TableCreation)?;
+        // The array retains each function and the linked functions retain the array.
+        let array = mtl_foundation::Array::<std::ffi::c_void>::array_with_objects(
+            objects.as_ptr(),
+            objects.len(),
+        )
+        .ok_or(RayTracingPipelineBuildError::TableCreation)?;
+        linked.set_functions_raw(array.as_ptr());
+        self.descriptor.set_linked_functions(Some(&linked));
+        Ok(())
+    }
+}
+
+impl fmt::Debug for RayTracingPipelineBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RayTracingPipelineBuilder")
+            .field("label", &self.descriptor.label())
+            .field("visible_functions", &self.visible_functions.len())
+            .field("intersection_functions", &self.intersection_functions.len())
+            .finish()
+    }
+}
+
+fn check_function_type(
+    function: &Function,
+    expected: FunctionType,
+) -> Result<(), RayTracingPipelineBuildError> {
+    let found = function.function_type();
+    if found != expected {
+        return Err(RayTracingPipelineBuildError::WrongFunctionType {
+            name: function.name().unwrap_or_default(),
+            expected,
+            found,
+        });
+    }
+    Ok(())
+}
+
+fn function_handles(
+    pipeline: &ComputePipelineState,
+    functions: &[Function],
+) -> Result<Vec<FunctionHandle>, RayTracingPipelineBuildError> {
+    functions
+        .iter()
+        .map(|function| {
+            pipeline
+                .function_handle_with_function(function)
+                .ok_or_else(|| {
+                    RayTracingPipelineBuildError::MissingFunctionHandle(
+                        function.name().unwrap_or_default(),
+                    )
+                })
+        })
+        .collect()
+}
+
+// ============================================================================
+// RayTracingPipeline
+// ============================================================================
+
+/// A compute pipeline with filled function tables.
+///
+/// Created by [`RayTracingPipelineBuilder::build`].
+pub struct RayTracingPipeline {
+    pipeline: ComputePipelineState,
+    visible_function_names: Vec<Option<String>>,
+    intersection_function_names: Vec<Option<String>>,
+    visible_function_table: Option<VisibleFunctionTable>,
+    intersection_function_table: Option<IntersectionFunctionTable>,
+}
+
+impl RayTracingPipeline {
+    /// Get the compute pipeline state.
+    pub fn pipeline(&self) -> &ComputePipelineState {
+        &self.pipeline
+    }
+
+    /// Get the visible function table, or `None` if no visible functions
+    /// were added.
+    pub fn visible_function_table(&self) -> Option<&VisibleFunctionTable> {
+        self.visible_function_table.as_ref()
+    }
+
+    /// Get the intersection function table, or `None` if no intersection
+    /// functions were added.
+    pub fn intersection_function_table(&self) -> Option<&IntersectionFunctionTable> {
+        self.intersection_function_table.as_ref()
+    }
+
+    /// Get the visible function table index of the function named `name`.
+    pub fn visible_function_index(&self, name: &str) -> Option<UInteger> {
+        find_index(&self.visible_function_names, name)
+    }
+
+    /// Get the intersection function table index of the function named
+    /// `name`.
+    pub fn intersection_function_index(&self, name: &str) -> Option<UInteger> {
+        find_index(&self.intersection_function_names, name)
+    }
+
+    /// Set the pipeline state on `encoder` and bind each function table
+    /// that exists at its buffer index.
+    pub fn bind(
+        &self,
+        encoder: &ComputeCommandEncoder,
+        visible_table_index: UInteger,
+        intersection_table_index: UInteger,
+    ) {
+        encoder.set_compute_pipeline_state(&self.pipeline);
+        if let Some(table) = &self.visible_function_table {
+            encoder.set_visible_function_table(table, visible_table_index);
+        }
+        if let Some(table) = &self.intersection_function_table {
+            encoder.set_intersection_function_table(table, intersection_table_index);
+        }
+    }
+}
+
+impl fmt::Debug for RayTracingPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RayTracingPipeline")
+            .field("pipeline", &self.pipeline)
+            .field("visible_functions", &self.visible_function_names)
+            .field("intersection_functions", &self.intersection_function_names)
+            .finish()
+    }
+}
+
+fn find_index(names: &[Option<String>], name: &str) -> Option<UInteger> {
+    names
+        .iter()
+        .position(|candidate| candidate.as_deref() == Some(name))
+        .map(|index| index as UInteger)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+        #include <metal_stdlib>
+        using namespace metal;
+
+        [[visible]] float half_of(float x) { return x * 0.5; }
+        [[visible]] float double_of(float x) { return x * 2.0; }
+
+        kernel void apply(device float *data [[buffer(0)]],
+                          visible_function_table<float(float)> table [[buffer(1)]],
+                          uint id [[thread_position_in_grid]]) {
+            data[id] = table[id % 2](data[id]);
+        }
+    "#;
+
+    #[test]
+    fn test_find_index() {
+        let names = vec![Some("a".to_string()), None, Some("b".to_string())];
+        assert_eq!(find_index(&names, "b"), Some(2));
+        assert_eq!(find_index(&names, "c"), None);
+    }
+
+    #[test]
+    fn test_build_visible_function_table() {
+        let device = crate::device::system_default().expect("no Metal device");
+        if !device.supports_function_pointers() {
+            return;
+        }
+        let library = device.new_library_with_source(SOURCE, None).unwrap();
+        let kernel = library.new_function_with_name("apply").unwrap();
+        let half = library.new_function_with_name("half_of").unwrap();
+        let double = library.new_function_with_name("double_of").unwrap();
+
+        let builder = RayTracingPipelineBuilder::new(&half).unwrap();
+        assert!(matches!(
+            builder.validate(&device),
+            Err(RayTracingPipelineBuildError::WrongFunctionType { .. })
+        ));
+
+        let pipeline = RayTracingPipelineBuilder::new(&kernel)
+            .unwrap()
+            .visible_function(&half)
+            .visible_function(&double)
+            .build(&device)
+            .unwrap();
+        assert!(pipeline.visible_function_table().is_some());
+        assert!(pipeline.intersection_function_table().is_none());
+        assert_eq!(pipeline.visible_function_index("double_of"), Some(1));
+    }
+}
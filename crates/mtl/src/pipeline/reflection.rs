@@ -8,6 +8,8 @@ use std::ptr::NonNull;
 use mtl_foundation::Referencing;
 use mtl_sys::{msg_send_0, sel};
 
+use crate::argument::Bindings;
+
 pub struct ComputePipelineReflection(pub(crate) NonNull<c_void>);
 
 impl ComputePipelineReflection {
@@ -63,6 +65,13 @@ impl ComputePipelineReflection {
     pub fn bindings_raw(&self) -> *mut c_void {
         unsafe { msg_send_0(self.as_ptr(), sel!(bindings)) }
     }
+
+    /// Iterate over the bindings.
+    ///
+    /// C++ equivalent: `NS::Array* bindings() const`
+    pub fn bindings(&self) -> Bindings {
+        unsafe { Bindings::from_array(self.bindings_raw()) }
+    }
 }
 
 impl Clone for ComputePipelineReflection {
@@ -176,6 +185,13 @@ impl RenderPipelineReflection {
         unsafe { msg_send_0(self.as_ptr(), sel!(vertexBindings)) }
     }
 
+    /// Iterate over the vertex bindings.
+    ///
+    /// C++ equivalent: `NS::Array* vertexBindings() const`
+    pub fn vertex_bindings(&self) -> Bindings {
+        unsafe { Bindings::from_array(self.vertex_bindings_raw()) }
+    }
+
     /// Get the fragment bindings array.
     ///
     /// C++ equivalent: `NS::Array* fragmentBindings() const`
@@ -183,6 +199,13 @@ impl RenderPipelineReflection {
         unsafe { msg_send_0(self.as_ptr(), sel!(fragmentBindings)) }
     }
 
+    /// Iterate over the fragment bindings.
+    ///
+    /// C++ equivalent: `NS::Array* fragmentBindings() const`
+    pub fn fragment_bindings(&self) -> Bindings {
+        unsafe { Bindings::from_array(self.fragment_bindings_raw()) }
+    }
+
     /// Get the tile bindings array.
     ///
     /// C++ equivalent: `NS::Array* tileBindings() const`
@@ -190,6 +213,13 @@ impl RenderPipelineReflection {
         unsafe { msg_send_0(self.as_ptr(), sel!(tileBindings)) }
     }
 
+    /// Iterate over the tile bindings.
+    ///
+    /// C++ equivalent: `NS::Array* tileBindings() const`
+    pub fn tile_bindings(&self) -> Bindings {
+        unsafe { Bindings::from_array(self.tile_bindings_raw()) }
+    }
+
     /// Get the object bindings array.
     ///
     /// C++ equivalent: `NS::Array* objectBindings() const`
@@ -197,12 +227,26 @@ impl RenderPipelineReflection {
         unsafe { msg_send_0(self.as_ptr(), sel!(objectBindings)) }
     }
 
+    /// Iterate over the object bindings.
+    ///
+    /// C++ equivalent: `NS::Array* objectBindings() const`
+    pub fn object_bindings(&self) -> Bindings {
+        unsafe { Bindings::from_array(self.object_bindings_raw()) }
+    }
+
     /// Get the mesh bindings array.
     ///
     /// C++ equivalent: `NS::Array* meshBindings() const`
     pub fn mesh_bindings_raw(&self) -> *mut c_void {
         unsafe { msg_send_0(self.as_ptr(), sel!(meshBindings)) }
     }
+
+    /// Iterate over the mesh bindings.
+    ///
+    /// C++ equivalent: `NS::Array* meshBindings() const`
+    pub fn mesh_bindings(&self) -> Bindings {
+        unsafe { Bindings::from_array(self.mesh_bindings_raw()) }
+    }
 }
 
 impl Clone for RenderPipelineReflection {
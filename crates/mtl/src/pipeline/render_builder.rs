@@ -0,0 +1,351 @@
+//! Builder for render pipeline states.
+//!
+//! Filling in a [`RenderPipelineDescriptor`] by hand takes a setter call per
+//! field and leaves mistakes to be caught by the Metal validation layer,
+//! which often aborts the process. [`RenderPipelineDescriptorBuilder`] sets
+//! the common fields in one chain, checks them before compiling, and reports
+//! compiler errors with their message.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let pipeline = RenderPipelineDescriptorBuilder::new()
+//!     .unwrap()
+//!     .label("sprites")
+//!     .vertex_function(&vertex_fn)
+//!     .fragment_function(&fragment_fn)
+//!     .color_attachment(0, PixelFormat::BGRA8_UNORM, ColorBlending::Alpha)
+//!     .depth_format(PixelFormat::DEPTH32_FLOAT)
+//!     .sample_count(4)
+//!     .build(&device)?;
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::{Referencing, UInteger};
+
+use super::{RenderPipelineDescriptor, RenderPipelineState};
+use crate::enums::{BlendFactor, BlendOperation, PixelFormat};
+use crate::{Device, Function, VertexDescriptor};
+
+/// Number of color attachments a render pipeline can have.
+pub const MAX_COLOR_ATTACHMENTS: UInteger = 8;
+
+/// How a color attachment combines new fragments with stored values.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColorBlending {
+    /// Blending disabled; fragments overwrite the stored color.
+    #[default]
+    Opaque,
+    /// `src * src.a + dst * (1 - src.a)`, for straight alpha.
+    Alpha,
+    /// `src + dst * (1 - src.a)`, for premultiplied alpha.
+    PremultipliedAlpha,
+    /// `src + dst`.
+    Additive,
+}
+
+impl ColorBlending {
+    /// Get the source and destination factors, or `None` if blending is
+    /// disabled.
+    fn factors(self) -> Option<(BlendFactor, BlendFactor)> {
+        match self {
+            ColorBlending::Opaque => None,
+            ColorBlending::Alpha => Some((
+                BlendFactor::SOURCE_ALPHA,
+                BlendFactor::ONE_MINUS_SOURCE_ALPHA,
+            )),
+            ColorBlending::PremultipliedAlpha => {
+                Some((BlendFactor::ONE, BlendFactor::ONE_MINUS_SOURCE_ALPHA))
+            }
+            ColorBlending::Additive => Some((BlendFactor::ONE, BlendFactor::ONE)),
+        }
+    }
+}
+
+/// Error produced by [`RenderPipelineDescriptorBuilder::build`].
+#[derive(Clone, Debug)]
+pub enum RenderPipelineBuildError {
+    /// No vertex function was set.
+    MissingVertexFunction,
+    /// A color attachment index is not below [`MAX_COLOR_ATTACHMENTS`].
+    InvalidColorAttachment(UInteger),
+    /// A color attachment has a depth, stencil or compressed format.
+    InvalidColorFormat {
+        /// The attachment index.
+        index: UInteger,
+        /// The format set on it.
+        format: PixelFormat,
+    },
+    /// Blending was enabled on an attachment with an integer format.
+    BlendingUnsupported {
+        /// The attachment index.
+        index: UInteger,
+        /// The format set on it.
+        format: PixelFormat,
+    },
+    /// The depth format has no depth or stencil component.
+    InvalidDepthFormat(PixelFormat),
+    /// The device does not support the sample count.
+    UnsupportedSampleCount(UInteger),
+    /// Metal failed to compile the pipeline.
+    Compile(mtl_foundation::Error),
+}
+
+impl fmt::Display for RenderPipelineBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderPipelineBuildError::MissingVertexFunction => {
+                write!(f, "render pipeline requires a vertex function")
+            }
+            RenderPipelineBuildError::InvalidColorAttachment(index) => write!(
+                f,
+                "color attachment {} is out of range (maximum {})",
+                index,
+                MAX_COLOR_ATTACHMENTS - 1
+            ),
+            RenderPipelineBuildError::InvalidColorFormat { index, format } => write!(
+                f,
+                "pixel format {:?} cannot be used for color attachment {}",
+                format, index
+            ),
+            RenderPipelineBuildError::BlendingUnsupported { index, format } => write!(
+                f,
+                "color attachment {} has integer format {:?} and cannot blend",
+                index, format
+            ),
+            RenderPipelineBuildError::InvalidDepthFormat(format) => {
+                write!(f, "pixel format {:?} is not a depth format", format)
+            }
+            RenderPipelineBuildError::UnsupportedSampleCount(count) => {
+                write!(f, "sample count {} is not supported by device", count)
+            }
+            RenderPipelineBuildError::Compile(err) => {
+                write!(f, "render pipeline compilation failed: {}", err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RenderPipelineBuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderPipelineBuildError::Compile(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// RenderPipelineDescriptorBuilder
+// ============================================================================
+
+/// Configures a [`RenderPipelineDescriptor`] and creates the pipeline state.
+///
+/// Invalid arguments are remembered and reported by
+/// [`build`](Self::build), so the chain never needs to be interrupted.
+pub struct RenderPipelineDescriptorBuilder {
+    descriptor: RenderPipelineDescriptor,
+    error: Option<RenderPipelineBuildError>,
+}
+
+impl RenderPipelineDescriptorBuilder {
+    /// Start from an empty descriptor.
+    pub fn new() -> Option<Self> {
+        Some(Self {
+            descriptor: RenderPipelineDescriptor::new()?,
+            error: None,
+        })
+    }
+
+    /// Set the label used in debugging tools.
+    pub fn label(self, label: &str) -> Self {
+        self.descriptor.set_label(label);
+        self
+    }
+
+    /// Set the vertex function.
+    pub fn vertex_function(self, function: &Function) -> Self {
+        self.descriptor.set_vertex_function(Some(function));
+        self
+    }
+
+    /// Set the fragment function.
+    pub fn fragment_function(self, function: &Function) -> Self {
+        self.descriptor.set_fragment_function(Some(function));
+        self
+    }
+
+    /// Set the pixel format and blending of color attachment `index`.
+    pub fn color_attachment(
+        mut self,
+        index: UInteger,
+        format: PixelFormat,
+        blending: ColorBlending,
+    ) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        if let Err(err) = validate_color_attachment(index, format, blending) {
+            self.error = Some(err);
+            return self;
+        }
+        let Some(attachment) = self.descriptor.color_attachments().object(index) else {
+            self.error = Some(RenderPipelineBuildError::InvalidColorAttachment(index));
+            return self;
+        };
+        attachment.set_pixel_format(format);
+        match blending.factors() {
+            Some((source, destination)) => {
+                attachment.set_blending_enabled(true);
+                attachment.set_rgb_blend_operation(BlendOperation::ADD);
+                attachment.set_alpha_blend_operation(BlendOperation::ADD);
+                attachment.set_source_rgb_blend_factor(source);
+                attachment.set_source_alpha_blend_factor(source);
+                attachment.set_destination_rgb_blend_factor(destination);
+                attachment.set_destination_alpha_blend_factor(destination);
+            }
+            None => attachment.set_blending_enabled(false),
+        }
+        self
+    }
+
+    /// Set the depth attachment format.
+    ///
+    /// Formats with a stencil component also set the stencil attachment
+    /// format.
+    pub fn depth_format(mut self, format: PixelFormat) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        if !format.is_depth_stencil() {
+            self.error = Some(RenderPipelineBuildError::InvalidDepthFormat(format));
+            return self;
+        }
+        if format.is_depth() {
+            self.descriptor.set_depth_attachment_pixel_format(format);
+        }
+        if format.has_stencil() {
+            self.descriptor.set_stencil_attachment_pixel_format(format);
+        }
+        self
+    }
+
+    /// Set the raster sample count.
+    pub fn sample_count(self, count: UInteger) -> Self {
+        self.descriptor.set_raster_sample_count(count);
+        self
+    }
+
+    /// Set the vertex descriptor.
+    pub fn vertex_descriptor(self, descriptor: &VertexDescriptor) -> Self {
+        self.descriptor.set_vertex_descriptor(Some(descriptor));
+        self
+    }
+
+    /// Get the descriptor, for settings without a builder method.
+    pub fn descriptor(&self) -> &RenderPipelineDescriptor {
+        &self.descriptor
+    }
+
+    /// Check the configuration against `device` without compiling.
+    pub fn validate(&self, device: &Device) -> Result<(), RenderPipelineBuildError> {
+        if let Some(err) = &self.error {
+            return Err(err.clone());
+        }
+        if self.descriptor.vertex_function().is_none() {
+            return Err(RenderPipelineBuildError::MissingVertexFunction);
+        }
+        let count = self.descriptor.raster_sample_count();
+        if count > 1 && !device.supports_texture_sample_count(count) {
+            return Err(RenderPipelineBuildError::UnsupportedSampleCount(count));
+        }
+        Ok(())
+    }
+
+    /// Validate the configuration and compile the pipeline on `device`.
+    pub fn build(&self, device: &Device) -> Result<RenderPipelineState, RenderPipelineBuildError> {
+        self.validate(device)?;
+        unsafe { device.new_render_pipeline_state(self.descriptor.as_ptr()) }
+            .map_err(RenderPipelineBuildError::Compile)
+    }
+}
+
+impl fmt::Debug for RenderPipelineDescriptorBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenderPipelineDescriptorBuilder")
+            .field("label", &self.descriptor.label())
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+fn validate_color_attachment(
+    index: UInteger,
+    format: PixelFormat,
+    blending: ColorBlending,
+) -> Result<(), RenderPipelineBuildError> {
+    if index >= MAX_COLOR_ATTACHMENTS {
+        return Err(RenderPipelineBuildError::InvalidColorAttachment(index));
+    }
+    if format.is_depth_stencil() || format.is_compressed() {
+        return Err(RenderPipelineBuildError::InvalidColorFormat { index, format });
+    }
+    if blending != ColorBlending::Opaque && format.is_integer() {
+        return Err(RenderPipelineBuildError::BlendingUnsupported { index, format });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_color_attachment() {
+        assert!(
+            validate_color_attachment(0, PixelFormat::BGRA8_UNORM, ColorBlending::Alpha).is_ok()
+        );
+        assert!(matches!(
+            validate_color_attachment(8, PixelFormat::BGRA8_UNORM, ColorBlending::Opaque),
+            Err(RenderPipelineBuildError::InvalidColorAttachment(8))
+        ));
+        assert!(matches!(
+            validate_color_attachment(1, PixelFormat::DEPTH32_FLOAT, ColorBlending::Opaque),
+            Err(RenderPipelineBuildError::InvalidColorFormat { index: 1, .. })
+        ));
+        assert!(matches!(
+            validate_color_attachment(0, PixelFormat::R32_UINT, ColorBlending::Additive),
+            Err(RenderPipelineBuildError::BlendingUnsupported { .. })
+        ));
+        assert!(validate_color_attachment(0, PixelFormat::R32_UINT, ColorBlending::Opaque).is_ok());
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let builder = RenderPipelineDescriptorBuilder::new()
+            .unwrap()
+            .color_attachment(
+                0,
+                PixelFormat::BGRA8_UNORM,
+                ColorBlending::PremultipliedAlpha,
+            );
+        assert!(matches!(
+            builder.build(&device),
+            Err(RenderPipelineBuildError::MissingVertexFunction)
+        ));
+
+        let attachment = builder.descriptor().color_attachments().object(0).unwrap();
+        assert!(attachment.is_blending_enabled());
+        assert_eq!(attachment.source_rgb_blend_factor(), BlendFactor::ONE);
+
+        let builder = RenderPipelineDescriptorBuilder::new()
+            .unwrap()
+            .depth_format(PixelFormat::RGBA8_UNORM);
+        assert!(matches!(
+            builder.validate(&device),
+            Err(RenderPipelineBuildError::InvalidDepthFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,423 @@
+//! Plain-value snapshots of render pipeline descriptors.
+//!
+//! A [`RenderPipelineDescriptor`] is a mutable Objective-C object, so using
+//! one as a cache key races with any thread that still holds it. A
+//! [`RenderPipelineConfig`] reads every field into plain Rust values once;
+//! it is `Clone`, `Eq` and `Hash`, and can create a fresh descriptor
+//! whenever the pipeline has to be compiled.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let config = RenderPipelineConfig::from_descriptor(&descriptor);
+//! let pipeline = match cache.get(&config) {
+//!     Some(pipeline) => pipeline.clone(),
+//!     None => {
+//!         let descriptor = config.descriptor().unwrap();
+//!         let pipeline = unsafe { device.new_render_pipeline_state(descriptor.as_ptr()) }?;
+//!         cache.insert(config, pipeline.clone());
+//!         pipeline
+//!     }
+//! };
+//! ```
+
+use std::hash::{Hash, Hasher};
+
+use mtl_foundation::UInteger;
+
+use super::{
+    MAX_COLOR_ATTACHMENTS, RenderPipelineColorAttachmentDescriptor, RenderPipelineDescriptor,
+};
+use crate::enums::{
+    BlendFactor, BlendOperation, ColorWriteMask, PixelFormat, PrimitiveTopologyClass,
+    ShaderValidation, TessellationControlPointIndexType, TessellationFactorFormat,
+    TessellationFactorStepFunction, TessellationPartitionMode, Winding,
+};
+use crate::{Function, VertexDescriptorConfig};
+
+// ============================================================================
+// ColorAttachmentConfig
+// ============================================================================
+
+/// The parameters of a [`RenderPipelineColorAttachmentDescriptor`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ColorAttachmentConfig {
+    /// The attachment's pixel format.
+    pub pixel_format: PixelFormat,
+    /// The channels written.
+    pub write_mask: ColorWriteMask,
+    /// Whether blending is enabled.
+    pub blending_enabled: bool,
+    /// Source factor for the RGB channels.
+    pub source_rgb_blend_factor: BlendFactor,
+    /// Destination factor for the RGB channels.
+    pub destination_rgb_blend_factor: BlendFactor,
+    /// Operation combining the RGB channels.
+    pub rgb_blend_operation: BlendOperation,
+    /// Source factor for the alpha channel.
+    pub source_alpha_blend_factor: BlendFactor,
+    /// Destination factor for the alpha channel.
+    pub destination_alpha_blend_factor: BlendFactor,
+    /// Operation combining the alpha channel.
+    pub alpha_blend_operation: BlendOperation,
+}
+
+impl Default for ColorAttachmentConfig {
+    /// Metal's defaults: no pixel format, all channels written, blending
+    /// disabled.
+    fn default() -> Self {
+        Self {
+            pixel_format: PixelFormat::INVALID,
+            write_mask: ColorWriteMask::ALL,
+            blending_enabled: false,
+            source_rgb_blend_factor: BlendFactor::ONE,
+            destination_rgb_blend_factor: BlendFactor::ZERO,
+            rgb_blend_operation: BlendOperation::ADD,
+            source_alpha_blend_factor: BlendFactor::ONE,
+            destination_alpha_blend_factor: BlendFactor::ZERO,
+            alpha_blend_operation: BlendOperation::ADD,
+        }
+    }
+}
+
+impl ColorAttachmentConfig {
+    /// An attachment with `pixel_format` and blending disabled.
+    pub fn opaque(pixel_format: PixelFormat) -> Self {
+        Self {
+            pixel_format,
+            ..Self::default()
+        }
+    }
+
+    /// Read the parameters of `descriptor`.
+    pub fn from_descriptor(descriptor: &RenderPipelineColorAttachmentDescriptor) -> Self {
+        Self {
+            pixel_format: descriptor.pixel_format(),
+            write_mask: descriptor.write_mask(),
+            blending_enabled: descriptor.is_blending_enabled(),
+            source_rgb_blend_factor: descriptor.source_rgb_blend_factor(),
+            destination_rgb_blend_factor: descriptor.destination_rgb_blend_factor(),
+            rgb_blend_operation: descriptor.rgb_blend_operation(),
+            source_alpha_blend_factor: descriptor.source_alpha_blend_factor(),
+            destination_alpha_blend_factor: descriptor.destination_alpha_blend_factor(),
+            alpha_blend_operation: descriptor.alpha_blend_operation(),
+        }
+    }
+
+    /// Write these parameters to `descriptor`.
+    pub fn apply(&self, descriptor: &RenderPipelineColorAttachmentDescriptor) {
+        descriptor.set_pixel_format(self.pixel_format);
+        descriptor.set_write_mask(self.write_mask);
+        descriptor.set_blending_enabled(self.blending_enabled);
+        descriptor.set_source_rgb_blend_factor(self.source_rgb_blend_factor);
+        descriptor.set_destination_rgb_blend_factor(self.destination_rgb_blend_factor);
+        descriptor.set_rgb_blend_operation(self.rgb_blend_operation);
+        descriptor.set_source_alpha_blend_factor(self.source_alpha_blend_factor);
+        descriptor.set_destination_alpha_blend_factor(self.destination_alpha_blend_factor);
+        descriptor.set_alpha_blend_operation(self.alpha_blend_operation);
+    }
+}
+
+// ============================================================================
+// RenderPipelineConfig
+// ============================================================================
+
+/// The contents of a [`RenderPipelineDescriptor`] as plain values.
+///
+/// Functions are compared by identity: two configs are equal only if they
+/// refer to the same [`Function`] objects, which are immutable. The label
+/// is not part of the comparison or hash.
+///
+/// Linked functions, preloaded libraries, binary archives and per-buffer
+/// mutability are not recorded.
+#[derive(Clone, Debug)]
+pub struct RenderPipelineConfig {
+    /// The pipeline's label.
+    pub label: Option<String>,
+    /// The vertex function.
+    pub vertex_function: Option<Function>,
+    /// The fragment function.
+    pub fragment_function: Option<Function>,
+    /// The vertex descriptor, or `None` if vertex data is fetched manually.
+    pub vertex_descriptor: Option<VertexDescriptorConfig>,
+    /// The color attachments by index, without trailing unused ones.
+    pub color_attachments: Vec<ColorAttachmentConfig>,
+    /// The depth attachment's pixel format.
+    pub depth_attachment_pixel_format: PixelFormat,
+    /// The stencil attachment's pixel format.
+    pub stencil_attachment_pixel_format: PixelFormat,
+    /// Samples per pixel.
+    pub raster_sample_count: UInteger,
+    /// Whether primitives are rasterized.
+    pub rasterization_enabled: bool,
+    /// The primitive class, for layered rendering.
+    pub input_primitive_topology: PrimitiveTopologyClass,
+    /// Whether alpha-to-coverage is enabled.
+    pub alpha_to_coverage_enabled: bool,
+    /// Whether alpha is forced to one.
+    pub alpha_to_one_enabled: bool,
+    /// Largest tessellation factor applied to a patch.
+    pub max_tessellation_factor: UInteger,
+    /// Whether tessellation factors are scaled.
+    pub tessellation_factor_scale_enabled: bool,
+    /// The tessellation factor buffer format.
+    pub tessellation_factor_format: TessellationFactorFormat,
+    /// The control point index type.
+    pub tessellation_control_point_index_type: TessellationControlPointIndexType,
+    /// How tessellation factors are fetched.
+    pub tessellation_factor_step_function: TessellationFactorStepFunction,
+    /// Winding of tessellated triangles.
+    pub tessellation_output_winding_order: Winding,
+    /// How patch edges are partitioned.
+    pub tessellation_partition_mode: TessellationPartitionMode,
+    /// Whether the pipeline can be used in indirect command buffers.
+    pub support_indirect_command_buffers: bool,
+    /// Largest vertex amplification count.
+    pub max_vertex_amplification_count: UInteger,
+    /// Shader validation setting.
+    pub shader_validation: ShaderValidation,
+}
+
+impl Default for RenderPipelineConfig {
+    /// Metal's defaults for a new descriptor.
+    fn default() -> Self {
+        Self {
+            label: None,
+            vertex_function: None,
+            fragment_function: None,
+            vertex_descriptor: None,
+            color_attachments: Vec::new(),
+            depth_attachment_pixel_format: PixelFormat::INVALID,
+            stencil_attachment_pixel_format: PixelFormat::INVALID,
+            raster_sample_count: 1,
+            rasterization_enabled: true,
+            input_primitive_topology: PrimitiveTopologyClass::UNSPECIFIED,
+            alpha_to_coverage_enabled: false,
+            alpha_to_one_enabled: false,
+            max_tessellation_factor: 16,
+            tessellation_factor_scale_enabled: false,
+            tessellation_factor_format: TessellationFactorFormat::default(),
+            tessellation_control_point_index_type: TessellationControlPointIndexType::default(),
+            tessellation_factor_step_function: TessellationFactorStepFunction::default(),
+            tessellation_output_winding_order: Winding::default(),
+            tessellation_partition_mode: TessellationPartitionMode::default(),
+            support_indirect_command_buffers: false,
+            max_vertex_amplification_count: 1,
+            shader_validation: ShaderValidation::DEFAULT,
+        }
+    }
+}
+
+impl RenderPipelineConfig {
+    /// Read the contents of `descriptor`.
+    pub fn from_descriptor(descriptor: &RenderPipelineDescriptor) -> Self {
+        let attachments = descriptor.color_attachments();
+        let mut color_attachments: Vec<_> = (0..MAX_COLOR_ATTACHMENTS)
+            .map(|index| {
+                attachments
+                    .object(index)
+                    .map(|attachment| ColorAttachmentConfig::from_descriptor(&attachment))
+                    .unwrap_or_default()
+            })
+            .collect();
+        while color_attachments.last() == Some(&ColorAttachmentConfig::default()) {
+            color_attachments.pop();
+        }
+        Self {
+            label: descriptor.label(),
+            vertex_function: descriptor.vertex_function(),
+            fragment_function: descriptor.fragment_function(),
+            vertex_descriptor: descriptor
+                .vertex_descriptor()
+                .map(|vertex_descriptor| {
+                    VertexDescriptorConfig::from_descriptor(&vertex_descriptor)
+                })
+                .filter(|config| !config.is_empty()),
+            color_attachments,
+            depth_attachment_pixel_format: descriptor.depth_attachment_pixel_format(),
+            stencil_attachment_pixel_format: descriptor.stencil_attachment_pixel_format(),
+            raster_sample_count: descriptor.raster_sample_count(),
+            rasterization_enabled: descriptor.is_rasterization_enabled(),
+            input_primitive_topology: descriptor.input_primitive_topology(),
+            alpha_to_coverage_enabled: descriptor.is_alpha_to_coverage_enabled(),
+            alpha_to_one_enabled: descriptor.is_alpha_to_one_enabled(),
+            max_tessellation_factor: descriptor.max_tessellation_factor(),
+            tessellation_factor_scale_enabled: descriptor.is_tessellation_factor_scale_enabled(),
+            tessellation_factor_format: descriptor.tessellation_factor_format(),
+            tessellation_control_point_index_type: descriptor
+                .tessellation_control_point_index_type(),
+            tessellation_factor_step_function: descriptor.tessellation_factor_step_function(),
+            tessellation_output_winding_order: descriptor.tessellation_output_winding_order(),
+            tessellation_partition_mode: descriptor.tessellation_partition_mode(),
+            support_indirect_command_buffers: descriptor.support_indirect_command_buffers(),
+            max_vertex_amplification_count: descriptor.max_vertex_amplification_count(),
+            shader_validation: descriptor.shader_validation(),
+        }
+    }
+
+    /// Reset `descriptor` and write these contents to it.
+    pub fn apply(&self, descriptor: &RenderPipelineDescriptor) {
+        descriptor.reset();
+        if let Some(label) = &self.label {
+            descriptor.set_label(label);
+        }
+        descriptor.set_vertex_function(self.vertex_function.as_ref());
+        descriptor.set_fragment_function(self.fragment_function.as_ref());
+        let vertex_descriptor = self
+            .vertex_descriptor
+            .as_ref()
+            .and_then(VertexDescriptorConfig::descriptor);
+        descriptor.set_vertex_descriptor(vertex_descriptor.as_ref());
+        let attachments = descriptor.color_attachments();
+        for (index, config) in self.color_attachments.iter().enumerate() {
+            if let Some(attachment) = attachments.object(index as UInteger) {
+                config.apply(&attachment);
+            }
+        }
+        descriptor.set_depth_attachment_pixel_format(self.depth_attachment_pixel_format);
+        descriptor.set_stencil_attachment_pixel_format(self.stencil_attachment_pixel_format);
+        descriptor.set_raster_sample_count(self.raster_sample_count);
+        descriptor.set_rasterization_enabled(self.rasterization_enabled);
+        descriptor.set_input_primitive_topology(self.input_primitive_topology);
+        descriptor.set_alpha_to_coverage_enabled(self.alpha_to_coverage_enabled);
+        descriptor.set_alpha_to_one_enabled(self.alpha_to_one_enabled);
+        descriptor.set_max_tessellation_factor(self.max_tessellation_factor);
+        descriptor.set_tessellation_factor_scale_enabled(self.tessellation_factor_scale_enabled);
+        descriptor.set_tessellation_factor_format(self.tessellation_factor_format);
+        descriptor
+            .set_tessellation_control_point_index_type(self.tessellation_control_point_index_type);
+        descriptor.set_tessellation_factor_step_function(self.tessellation_factor_step_function);
+        descriptor.set_tessellation_output_winding_order(self.tessellation_output_winding_order);
+        descriptor.set_tessellation_partition_mode(self.tessellation_partition_mode);
+        descriptor.set_support_indirect_command_buffers(self.support_indirect_command_buffers);
+        descriptor.set_max_vertex_amplification_count(self.max_vertex_amplification_count);
+        descriptor.set_shader_validation(self.shader_validation);
+    }
+
+    /// Create a descriptor with these contents.
+    pub fn descriptor(&self) -> Option<RenderPipelineDescriptor> {
+        let descriptor = RenderPipelineDescriptor::new()?;
+        self.apply(&descriptor);
+        Some(descriptor)
+    }
+}
+
+/// Identify a function by its object address.
+fn function_id(function: &Option<Function>) -> usize {
+    function
+        .as_ref()
+        .map_or(0, |function| function.as_raw() as usize)
+}
+
+impl PartialEq for RenderPipelineConfig {
+    fn eq(&self, other: &Self) -> bool {
+        function_id(&self.vertex_function) == function_id(&other.vertex_function)
+            && function_id(&self.fragment_function) == function_id(&other.fragment_function)
+            && self.vertex_descriptor == other.vertex_descriptor
+            && self.color_attachments == other.color_attachments
+            && self.depth_attachment_pixel_format == other.depth_attachment_pixel_format
+            && self.stencil_attachment_pixel_format == other.stencil_attachment_pixel_format
+            && self.raster_sample_count == other.raster_sample_count
+            && self.rasterization_enabled == other.rasterization_enabled
+            && self.input_primitive_topology == other.input_primitive_topology
+            && self.alpha_to_coverage_enabled == other.alpha_to_coverage_enabled
+            && self.alpha_to_one_enabled == other.alpha_to_one_enabled
+            && self.max_tessellation_factor == other.max_tessellation_factor
+            && self.tessellation_factor_scale_enabled == other.tessellation_factor_scale_enabled
+            && self.tessellation_factor_format == other.tessellation_factor_format
+            && self.tessellation_control_point_index_type
+                == other.tessellation_control_point_index_type
+            && self.tessellation_factor_step_function == other.tessellation_factor_step_function
+            && self.tessellation_output_winding_order == other.tessellation_output_winding_order
+            && self.tessellation_partition_mode == other.tessellation_partition_mode
+            && self.support_indirect_command_buffers == other.support_indirect_command_buffers
+            && self.max_vertex_amplification_count == other.max_vertex_amplification_count
+            && self.shader_validation == other.shader_validation
+    }
+}
+
+impl Eq for RenderPipelineConfig {}
+
+impl Hash for RenderPipelineConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        function_id(&self.vertex_function).hash(state);
+        function_id(&self.fragment_function).hash(state);
+        self.vertex_descriptor.hash(state);
+        self.color_attachments.hash(state);
+        self.depth_attachment_pixel_format.hash(state);
+        self.stencil_attachment_pixel_format.hash(state);
+        self.raster_sample_count.hash(state);
+        self.rasterization_enabled.hash(state);
+        self.input_primitive_topology.hash(state);
+        self.alpha_to_coverage_enabled.hash(state);
+        self.alpha_to_one_enabled.hash(state);
+        self.max_tessellation_factor.hash(state);
+        self.tessellation_factor_scale_enabled.hash(state);
+        self.tessellation_factor_format.hash(state);
+        self.tessellation_control_point_index_type.hash(state);
+        self.tessellation_factor_step_function.hash(state);
+        self.tessellation_output_winding_order.hash(state);
+        self.tessellation_partition_mode.hash(state);
+        self.support_indirect_command_buffers.hash(state);
+        self.max_vertex_amplification_count.hash(state);
+        self.shader_validation.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VertexAttributeConfig;
+    use crate::VertexBufferLayoutConfig;
+    use crate::enums::{VertexFormat, VertexStepFunction};
+
+    #[test]
+    fn test_render_pipeline_config_round_trip() {
+        let config = RenderPipelineConfig {
+            label: Some("sprites".to_string()),
+            vertex_descriptor: Some(VertexDescriptorConfig {
+                attributes: vec![VertexAttributeConfig {
+                    index: 0,
+                    format: VertexFormat::FLOAT2,
+                    offset: 0,
+                    buffer_index: 0,
+                }],
+                layouts: vec![VertexBufferLayoutConfig {
+                    buffer_index: 0,
+                    stride: 8,
+                    step_function: VertexStepFunction::PER_VERTEX,
+                    step_rate: 1,
+                }],
+            }),
+            color_attachments: vec![
+                ColorAttachmentConfig::opaque(PixelFormat::BGRA8_UNORM),
+                ColorAttachmentConfig::default(),
+                ColorAttachmentConfig {
+                    blending_enabled: true,
+                    ..ColorAttachmentConfig::opaque(PixelFormat::RGBA16_FLOAT)
+                },
+            ],
+            depth_attachment_pixel_format: PixelFormat::DEPTH32_FLOAT,
+            raster_sample_count: 4,
+            ..RenderPipelineConfig::default()
+        };
+        let descriptor = config.descriptor().unwrap();
+        let read = RenderPipelineConfig::from_descriptor(&descriptor);
+        assert_eq!(read, config);
+        assert_eq!(read.label.as_deref(), Some("sprites"));
+
+        // Changing the descriptor afterwards doesn't affect the snapshot.
+        descriptor.set_raster_sample_count(1);
+        assert_eq!(read.raster_sample_count, 4);
+        assert_ne!(RenderPipelineConfig::from_descriptor(&descriptor), read);
+    }
+
+    #[test]
+    fn test_default_matches_new_descriptor() {
+        let descriptor = RenderPipelineDescriptor::new().unwrap();
+        assert_eq!(
+            RenderPipelineConfig::from_descriptor(&descriptor),
+            RenderPipelineConfig::default()
+        );
+    }
+}
@@ -48,6 +48,66 @@ impl RenderPipelineDescriptor {
         Self::alloc().and_then(|d| d.init())
     }
 
+    /// Create a descriptor for a depth-only pipeline, such as a shadow map
+    /// pass.
+    ///
+    /// Sets the vertex function and depth format and leaves the fragment
+    /// function and all color attachments unset, so only depth is written.
+    /// If `depth_format` has a stencil component it is also set as the
+    /// stencil format.
+    pub fn depth_only(
+        vertex_function: &crate::Function,
+        depth_format: PixelFormat,
+    ) -> Option<Self> {
+        if !depth_format.is_depth() {
+            return None;
+        }
+        let descriptor = Self::new()?;
+        descriptor.set_vertex_function(Some(vertex_function));
+        descriptor.set_depth_attachment_pixel_format(depth_format);
+        if depth_format.has_stencil() {
+            descriptor.set_stencil_attachment_pixel_format(depth_format);
+        }
+        Some(descriptor)
+    }
+
+    /// Create a descriptor for a vertex-only pipeline with rasterization
+    /// disabled.
+    ///
+    /// Used when the vertex function writes its results to device memory
+    /// instead of producing fragments. See
+    /// [`VertexCapture`](super::VertexCapture).
+    pub fn vertex_only(vertex_function: &crate::Function) -> Option<Self> {
+        let descriptor = Self::new()?;
+        descriptor.set_vertex_function(Some(vertex_function));
+        descriptor.set_rasterization_enabled(false);
+        Some(descriptor)
+    }
+
+    /// Create a descriptor for a stencil-only pipeline, such as a stencil
+    /// mask or portal pass.
+    ///
+    /// Like [`depth_only`](Self::depth_only), but sets only the stencil
+    /// format. `fragment_function` may be used for `discard_fragment`-based
+    /// masking; pass `None` to write stencil for every covered pixel.
+    pub fn stencil_only(
+        vertex_function: &crate::Function,
+        fragment_function: Option<&crate::Function>,
+        stencil_format: PixelFormat,
+    ) -> Option<Self> {
+        if !stencil_format.has_stencil() {
+            return None;
+        }
+        let descriptor = Self::new()?;
+        descriptor.set_vertex_function(Some(vertex_function));
+        descriptor.set_fragment_function(fragment_function);
+        descriptor.set_stencil_attachment_pixel_format(stencil_format);
+        if stencil_format.is_depth() {
+            descriptor.set_depth_attachment_pixel_format(stencil_format);
+        }
+        Some(descriptor)
+    }
+
     /// Create from a raw pointer.
     ///
     /// # Safety
@@ -828,6 +888,38 @@ impl RenderPipelineDescriptor {
         }
     }
 
+    /// Get the dynamic libraries loaded with the vertex function.
+    ///
+    /// C++ equivalent: `NS::Array* vertexPreloadedLibraries() const`
+    pub fn vertex_preloaded_libraries(&self) -> Vec<crate::DynamicLibrary> {
+        unsafe { crate::library::libraries_from_array(self.vertex_preloaded_libraries_raw()) }
+    }
+
+    /// Set the dynamic libraries loaded with the vertex function.
+    ///
+    /// C++ equivalent: `void setVertexPreloadedLibraries(const NS::Array* vertexPreloadedLibraries)`
+    pub fn set_vertex_preloaded_libraries(&self, libraries: &[&crate::DynamicLibrary]) {
+        crate::library::with_library_array(libraries, |array| unsafe {
+            self.set_vertex_preloaded_libraries_raw(array)
+        });
+    }
+
+    /// Get the dynamic libraries loaded with the fragment function.
+    ///
+    /// C++ equivalent: `NS::Array* fragmentPreloadedLibraries() const`
+    pub fn fragment_preloaded_libraries(&self) -> Vec<crate::DynamicLibrary> {
+        unsafe { crate::library::libraries_from_array(self.fragment_preloaded_libraries_raw()) }
+    }
+
+    /// Set the dynamic libraries loaded with the fragment function.
+    ///
+    /// C++ equivalent: `void setFragmentPreloadedLibraries(const NS::Array* fragmentPreloadedLibraries)`
+    pub fn set_fragment_preloaded_libraries(&self, libraries: &[&crate::DynamicLibrary]) {
+        crate::library::with_library_array(libraries, |array| unsafe {
+            self.set_fragment_preloaded_libraries_raw(array)
+        });
+    }
+
     // =========================================================================
     // Binary Archives (Raw)
     // =========================================================================
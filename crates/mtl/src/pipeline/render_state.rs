@@ -17,8 +17,14 @@ use super::RenderPipelineReflection;
 /// A compiled render pipeline configuration.
 ///
 /// C++ equivalent: `MTL::RenderPipelineState`
-#[repr(transparent)]
-pub struct RenderPipelineState(pub(crate) NonNull<c_void>);
+#[cfg_attr(not(feature = "validation"), repr(transparent))]
+pub struct RenderPipelineState {
+    ptr: NonNull<c_void>,
+    /// Attachment formats of the descriptor the pipeline was created from,
+    /// checked against the render pass by the `validation` feature.
+    #[cfg(feature = "validation")]
+    formats: Option<std::sync::Arc<crate::validation::AttachmentFormats>>,
+}
 
 impl RenderPipelineState {
     /// Create a RenderPipelineState from a raw pointer.
@@ -28,13 +34,44 @@ impl RenderPipelineState {
     /// The pointer must be a valid Metal render pipeline state object.
     #[inline]
     pub unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
-        NonNull::new(ptr).map(Self)
+        NonNull::new(ptr).map(|ptr| Self {
+            ptr,
+            #[cfg(feature = "validation")]
+            formats: None,
+        })
     }
 
     /// Get the raw pointer.
     #[inline]
     pub fn as_raw(&self) -> *mut c_void {
-        self.0.as_ptr()
+        self.ptr.as_ptr()
+    }
+
+    /// Record the attachment formats of `descriptor`, a render or mesh
+    /// render pipeline descriptor the pipeline was created from.
+    ///
+    /// # Safety
+    ///
+    /// `descriptor` must be a valid render or mesh render pipeline
+    /// descriptor.
+    #[cfg(feature = "validation")]
+    pub(crate) unsafe fn with_descriptor_formats(mut self, descriptor: *const c_void) -> Self {
+        let formats = unsafe { crate::validation::render_pipeline_formats(descriptor) };
+        self.formats = Some(std::sync::Arc::new(formats));
+        self
+    }
+
+    /// Copy the attachment formats recorded for `other`.
+    #[cfg(feature = "validation")]
+    fn with_formats_of(mut self, other: &Self) -> Self {
+        self.formats = other.formats.clone();
+        self
+    }
+
+    /// Get the attachment formats recorded when the pipeline was created.
+    #[cfg(feature = "validation")]
+    pub(crate) fn attachment_formats(&self) -> Option<&crate::validation::AttachmentFormats> {
+        self.formats.as_deref()
     }
 
     // =========================================================================
@@ -334,7 +371,12 @@ impl RenderPipelineState {
                 )
                 .expect("failed to create error"));
             }
-            Ok(RenderPipelineState::from_raw(ptr).expect("failed to create pipeline state"))
+            let state =
+                RenderPipelineState::from_raw(ptr).expect("failed to create pipeline state");
+            // The attachments are the same as this pipeline's.
+            #[cfg(feature = "validation")]
+            let state = state.with_formats_of(self);
+            Ok(state)
         }
     }
 
@@ -362,7 +404,11 @@ impl Clone for RenderPipelineState {
         unsafe {
             msg_send_0::<*mut c_void>(self.as_ptr(), sel!(retain));
         }
-        Self(self.0)
+        Self {
+            ptr: self.ptr,
+            #[cfg(feature = "validation")]
+            formats: self.formats.clone(),
+        }
     }
 }
 
@@ -377,7 +423,7 @@ impl Drop for RenderPipelineState {
 impl Referencing for RenderPipelineState {
     #[inline]
     fn as_ptr(&self) -> *const c_void {
-        self.0.as_ptr()
+        self.ptr.as_ptr()
     }
 }
 
@@ -0,0 +1,460 @@
+//! Checked pipeline replacement for shader hot reload.
+//!
+//! A shader edit can rename, remove or renumber a `[[buffer(n)]]` or
+//! `[[texture(n)]]` argument while the host code keeps binding resources
+//! to the old slots, and the first sign is garbage on screen.
+//! [`PipelineSwap`] holds the live pipeline with its [`BindingInterface`];
+//! each replacement is compared against it and the differences are
+//! reported as [`BindingChange`]s. In strict mode a replacement that
+//! changes the interface is refused and the old pipeline stays in use.
+//!
+//! The interface comes from the pipeline's reflection, so pipelines should
+//! be created with [`PipelineOption::BINDING_INFO`](crate::PipelineOption::BINDING_INFO)
+//! or have their interface passed in with
+//! [`swap_with_interface`](PipelineSwap::swap_with_interface).
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut blur = PipelineSwap::new(build_blur(&device, &library)?).with_strict(true);
+//!
+//! reload.poll(|result| {
+//!     if let Ok((library, _)) = result {
+//!         let pipeline = build_blur(&device, &library).unwrap();
+//!         match blur.swap(pipeline) {
+//!             Ok(changes) => assert!(changes.is_empty()),
+//!             Err(err) => eprintln!("kept the old blur pipeline: {err}"),
+//!         }
+//!     }
+//! });
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use super::{
+    ComputePipelineReflection, ComputePipelineState, RenderPipelineReflection, RenderPipelineState,
+};
+use crate::argument::Bindings;
+use crate::enums::BindingType;
+
+/// The shader stage a binding belongs to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BindingStage {
+    /// The vertex function.
+    Vertex,
+    /// The fragment function.
+    Fragment,
+    /// The tile function.
+    Tile,
+    /// The object function.
+    Object,
+    /// The mesh function.
+    Mesh,
+    /// The compute kernel.
+    Compute,
+}
+
+impl fmt::Display for BindingStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BindingStage::Vertex => "vertex",
+            BindingStage::Fragment => "fragment",
+            BindingStage::Tile => "tile",
+            BindingStage::Object => "object",
+            BindingStage::Mesh => "mesh",
+            BindingStage::Compute => "compute",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One binding of a pipeline's interface.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BindingSignature {
+    /// The stage the binding belongs to.
+    pub stage: BindingStage,
+    /// The argument name in the shader.
+    pub name: String,
+    /// The binding type.
+    pub binding_type: BindingType,
+    /// The index in the stage's argument table for the binding type.
+    pub index: UInteger,
+}
+
+impl BindingSignature {
+    fn same_slot(&self, other: &BindingSignature) -> bool {
+        self.stage == other.stage
+            && self.binding_type == other.binding_type
+            && self.index == other.index
+    }
+}
+
+/// A change to a pipeline's bindings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BindingChange {
+    /// A binding exists only in the new pipeline.
+    Added(BindingSignature),
+    /// A binding exists only in the old pipeline.
+    Removed(BindingSignature),
+    /// A slot is still bound but its argument has a different name.
+    Renamed {
+        /// The binding in the old pipeline.
+        old: BindingSignature,
+        /// The new argument name.
+        new_name: String,
+    },
+    /// An argument kept its name but moved to a different index or type.
+    Rebound {
+        /// The binding in the old pipeline.
+        old: BindingSignature,
+        /// The binding in the new pipeline.
+        new: BindingSignature,
+    },
+}
+
+impl fmt::Display for BindingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingChange::Added(binding) => write!(
+                f,
+                "added {} argument '{}' ({:?} {})",
+                binding.stage, binding.name, binding.binding_type, binding.index
+            ),
+            BindingChange::Removed(binding) => write!(
+                f,
+                "removed {} argument '{}' ({:?} {})",
+                binding.stage, binding.name, binding.binding_type, binding.index
+            ),
+            BindingChange::Renamed { old, new_name } => write!(
+                f,
+                "renamed {} argument '{}' to '{}' ({:?} {})",
+                old.stage, old.name, new_name, old.binding_type, old.index
+            ),
+            BindingChange::Rebound { old, new } => write!(
+                f,
+                "moved {} argument '{}' from {:?} {} to {:?} {}",
+                old.stage, old.name, old.binding_type, old.index, new.binding_type, new.index
+            ),
+        }
+    }
+}
+
+// ============================================================================
+// BindingInterface
+// ============================================================================
+
+/// The bindings a pipeline expects the host to provide.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BindingInterface {
+    bindings: Vec<BindingSignature>,
+}
+
+impl BindingInterface {
+    /// Create an interface from a list of bindings.
+    pub fn new(bindings: Vec<BindingSignature>) -> Self {
+        Self { bindings }
+    }
+
+    /// Collect the bindings of a compute pipeline.
+    pub fn from_compute_reflection(reflection: &ComputePipelineReflection) -> Self {
+        let mut interface = Self::default();
+        interface.extend(BindingStage::Compute, reflection.bindings());
+        interface
+    }
+
+    /// Collect the bindings of every stage of a render pipeline.
+    pub fn from_render_reflection(reflection: &RenderPipelineReflection) -> Self {
+        let mut interface = Self::default();
+        interface.extend(BindingStage::Vertex, reflection.vertex_bindings());
+        interface.extend(BindingStage::Fragment, reflection.fragment_bindings());
+        interface.extend(BindingStage::Tile, reflection.tile_bindings());
+        interface.extend(BindingStage::Object, reflection.object_bindings());
+        interface.extend(BindingStage::Mesh, reflection.mesh_bindings());
+        interface
+    }
+
+    fn extend(&mut self, stage: BindingStage, bindings: Bindings) {
+        self.bindings
+            .extend(bindings.map(|binding| BindingSignature {
+                stage,
+                name: binding.name().unwrap_or_default(),
+                binding_type: binding.binding_type(),
+                index: binding.index(),
+            }));
+    }
+
+    /// Get the bindings.
+    pub fn bindings(&self) -> &[BindingSignature] {
+        &self.bindings
+    }
+
+    /// List the changes from `self` to `new`.
+    ///
+    /// Bindings are matched by slot (stage, type and index) first, then by
+    /// stage and name, so a renamed argument shows up as
+    /// [`Renamed`](BindingChange::Renamed) and a renumbered one as
+    /// [`Rebound`](BindingChange::Rebound) rather than as a removal and an
+    /// addition.
+    pub fn diff(&self, new: &BindingInterface) -> Vec<BindingChange> {
+        let mut unmatched: Vec<&BindingSignature> = new.bindings.iter().collect();
+        let mut changes = Vec::new();
+        let mut moved = Vec::new();
+
+        for old in &self.bindings {
+            match unmatched.iter().position(|binding| binding.same_slot(old)) {
+                Some(position) => {
+                    let binding = unmatched.remove(position);
+                    if binding.name != old.name {
+                        changes.push(BindingChange::Renamed {
+                            old: old.clone(),
+                            new_name: binding.name.clone(),
+                        });
+                    }
+                }
+                None => moved.push(old),
+            }
+        }
+        for old in moved {
+            let position = unmatched
+                .iter()
+                .position(|binding| binding.stage == old.stage && binding.name == old.name);
+            changes.push(match position {
+                Some(position) => BindingChange::Rebound {
+                    old: old.clone(),
+                    new: unmatched.remove(position).clone(),
+                },
+                None => BindingChange::Removed(old.clone()),
+            });
+        }
+        changes.extend(
+            unmatched
+                .into_iter()
+                .map(|binding| BindingChange::Added(binding.clone())),
+        );
+        changes
+    }
+}
+
+/// A pipeline state whose binding interface can be read from its
+/// reflection.
+pub trait ReflectedPipeline {
+    /// Get the pipeline's bindings, or `None` if it has no reflection.
+    fn binding_interface(&self) -> Option<BindingInterface>;
+}
+
+impl ReflectedPipeline for ComputePipelineState {
+    fn binding_interface(&self) -> Option<BindingInterface> {
+        self.reflection()
+            .map(|reflection| BindingInterface::from_compute_reflection(&reflection))
+    }
+}
+
+impl ReflectedPipeline for RenderPipelineState {
+    fn binding_interface(&self) -> Option<BindingInterface> {
+        self.reflection()
+            .map(|reflection| BindingInterface::from_render_reflection(&reflection))
+    }
+}
+
+// ============================================================================
+// PipelineSwap
+// ============================================================================
+
+/// Error produced by a refused [`PipelineSwap`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PipelineSwapError {
+    /// The new pipeline's bindings differ from the old one's.
+    InterfaceChanged(Vec<BindingChange>),
+    /// The old or new pipeline has no reflection to compare.
+    ReflectionUnavailable,
+}
+
+impl fmt::Display for PipelineSwapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineSwapError::InterfaceChanged(changes) => {
+                write!(f, "pipeline binding interface changed: ")?;
+                for (i, change) in changes.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", change)?;
+                }
+                Ok(())
+            }
+            PipelineSwapError::ReflectionUnavailable => {
+                write!(f, "pipeline has no reflection to compare bindings with")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PipelineSwapError {}
+
+/// A live pipeline that is replaced only after its bindings are compared.
+pub struct PipelineSwap<P> {
+    pipeline: P,
+    interface: Option<BindingInterface>,
+    strict: bool,
+}
+
+impl<P: ReflectedPipeline> PipelineSwap<P> {
+    /// Wrap `pipeline`, reading its interface from its reflection.
+    pub fn new(pipeline: P) -> Self {
+        let interface = pipeline.binding_interface();
+        Self::with_interface(pipeline, interface)
+    }
+
+    /// Replace the pipeline, reading the new interface from its
+    /// reflection.
+    ///
+    /// See [`swap_with_interface`](Self::swap_with_interface).
+    pub fn swap(&mut self, pipeline: P) -> Result<Vec<BindingChange>, PipelineSwapError> {
+        let interface = pipeline.binding_interface();
+        self.swap_with_interface(pipeline, interface)
+    }
+}
+
+impl<P> PipelineSwap<P> {
+    /// Wrap `pipeline` with an interface obtained elsewhere, such as the
+    /// reflection returned when the pipeline was created.
+    pub fn with_interface(pipeline: P, interface: Option<BindingInterface>) -> Self {
+        Self {
+            pipeline,
+            interface,
+            strict: false,
+        }
+    }
+
+    /// Refuse replacements that change the binding interface or cannot be
+    /// compared. Off by default, where the change list is only reported.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Check if interface changes refuse the swap.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Get the live pipeline.
+    pub fn pipeline(&self) -> &P {
+        &self.pipeline
+    }
+
+    /// Get the live pipeline's interface, if known.
+    pub fn interface(&self) -> Option<&BindingInterface> {
+        self.interface.as_ref()
+    }
+
+    /// Replace the pipeline with `pipeline`, whose bindings are
+    /// `interface`.
+    ///
+    /// Returns the binding changes. In strict mode any change, or a missing
+    /// interface on either side, is returned as an error and the old
+    /// pipeline is kept.
+    pub fn swap_with_interface(
+        &mut self,
+        pipeline: P,
+        interface: Option<BindingInterface>,
+    ) -> Result<Vec<BindingChange>, PipelineSwapError> {
+        let changes = match (&self.interface, &interface) {
+            (Some(old), Some(new)) => old.diff(new),
+            _ if self.strict => return Err(PipelineSwapError::ReflectionUnavailable),
+            _ => Vec::new(),
+        };
+        if self.strict && !changes.is_empty() {
+            return Err(PipelineSwapError::InterfaceChanged(changes));
+        }
+        self.pipeline = pipeline;
+        self.interface = interface;
+        Ok(changes)
+    }
+
+    /// Unwrap the live pipeline.
+    pub fn into_inner(self) -> P {
+        self.pipeline
+    }
+}
+
+impl<P: fmt::Debug> fmt::Debug for PipelineSwap<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipelineSwap")
+            .field("pipeline", &self.pipeline)
+            .field("interface", &self.interface)
+            .field("strict", &self.strict)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(name: &str, binding_type: BindingType, index: UInteger) -> BindingSignature {
+        BindingSignature {
+            stage: BindingStage::Compute,
+            name: name.to_string(),
+            binding_type,
+            index,
+        }
+    }
+
+    #[test]
+    fn test_binding_interface_diff() {
+        let old = BindingInterface::new(vec![
+            binding("input", BindingType::BUFFER, 0),
+            binding("output", BindingType::BUFFER, 1),
+            binding("params", BindingType::BUFFER, 2),
+            binding("source", BindingType::TEXTURE, 0),
+        ]);
+        let new = BindingInterface::new(vec![
+            binding("input", BindingType::BUFFER, 0),
+            binding("result", BindingType::BUFFER, 1),
+            binding("params", BindingType::BUFFER, 3),
+            binding("lut", BindingType::TEXTURE, 1),
+        ]);
+
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(
+            old.diff(&new),
+            vec![
+                BindingChange::Renamed {
+                    old: binding("output", BindingType::BUFFER, 1),
+                    new_name: "result".into(),
+                },
+                BindingChange::Rebound {
+                    old: binding("params", BindingType::BUFFER, 2),
+                    new: binding("params", BindingType::BUFFER, 3),
+                },
+                BindingChange::Removed(binding("source", BindingType::TEXTURE, 0)),
+                BindingChange::Added(binding("lut", BindingType::TEXTURE, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pipeline_swap_strict() {
+        let v1 = BindingInterface::new(vec![binding("input", BindingType::BUFFER, 0)]);
+        let v2 = BindingInterface::new(vec![binding("input", BindingType::BUFFER, 1)]);
+
+        let mut swap = PipelineSwap::with_interface(1, Some(v1.clone())).with_strict(true);
+        assert_eq!(swap.swap_with_interface(2, Some(v1.clone())), Ok(vec![]));
+        assert!(matches!(
+            swap.swap_with_interface(3, Some(v2.clone())),
+            Err(PipelineSwapError::InterfaceChanged(_))
+        ));
+        assert_eq!(
+            swap.swap_with_interface(3, None),
+            Err(PipelineSwapError::ReflectionUnavailable)
+        );
+        assert_eq!(*swap.pipeline(), 2);
+
+        let mut swap = swap.with_strict(false);
+        assert_eq!(swap.swap_with_interface(3, Some(v2)).unwrap().len(), 1);
+        assert_eq!(swap.into_inner(), 3);
+    }
+}
@@ -0,0 +1,251 @@
+//! Capture of post-transform vertex data.
+//!
+//! Metal has no transform feedback stage. The equivalent is a vertex-only
+//! pipeline with rasterization disabled whose vertex function writes its
+//! outputs to a device buffer indexed by `vertex_id`. [`VertexCapture`]
+//! builds that pipeline, owns the output buffer, and encodes the draw.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // struct Out { float4 position; float3 normal; };
+//! // vertex void skin(uint vid [[vertex_id]],
+//! //                  const device In* in [[buffer(0)]],
+//! //                  device Out* out [[buffer(1)]]) {
+//! //     out[vid] = transform(in[vid]);
+//! // }
+//! let capture = VertexCapture::new(&device, &skin_fn, 32, vertex_count, 1)?;
+//!
+//! let command_buffer = queue.command_buffer().unwrap();
+//! capture.encode(&command_buffer, vertex_count, |encoder| {
+//!     encoder.set_vertex_buffer(&input, 0, 0);
+//! })?;
+//! command_buffer.commit();
+//! command_buffer.wait_until_completed();
+//!
+//! let vertices: Vec<Out> = unsafe { capture.read(vertex_count)? };
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use super::{RenderPipelineDescriptor, RenderPipelineState};
+use crate::enums::{PrimitiveType, ResourceOptions};
+use crate::error::ValidationError;
+use crate::pass::RenderPassDescriptor;
+use crate::{Buffer, CommandBuffer, Device, Function, RenderCommandEncoder};
+
+/// Error produced by a [`VertexCapture`].
+#[derive(Debug, Clone)]
+pub enum VertexCaptureError {
+    /// The vertex-only pipeline could not be created.
+    Pipeline(ValidationError),
+    /// The output buffer, render pass, or encoder could not be created.
+    Allocation,
+    /// More vertices were requested than the output buffer holds.
+    CapacityExceeded {
+        requested: UInteger,
+        capacity: UInteger,
+    },
+    /// The element type read back doesn't match the capture stride.
+    StrideMismatch { stride: UInteger, element: usize },
+    /// `stride * capacity` overflows the size of a buffer.
+    SizeOverflow {
+        stride: UInteger,
+        capacity: UInteger,
+    },
+}
+
+impl fmt::Display for VertexCaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VertexCaptureError::Pipeline(err) => {
+                write!(f, "failed to create vertex capture pipeline: {}", err)
+            }
+            VertexCaptureError::Allocation => {
+                write!(f, "failed to allocate vertex capture resources")
+            }
+            VertexCaptureError::CapacityExceeded {
+                requested,
+                capacity,
+            } => write!(
+                f,
+                "requested {} vertices but the capture buffer holds {}",
+                requested, capacity
+            ),
+            VertexCaptureError::StrideMismatch { stride, element } => write!(
+                f,
+                "element size {} does not match capture stride {}",
+                element, stride
+            ),
+            VertexCaptureError::SizeOverflow { stride, capacity } => write!(
+                f,
+                "{} vertices of {} bytes overflow the capture buffer size",
+                capacity, stride
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VertexCaptureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VertexCaptureError::Pipeline(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// A vertex-only pipeline that writes transformed vertices to a buffer.
+pub struct VertexCapture {
+    pipeline: RenderPipelineState,
+    output: Buffer,
+    stride: UInteger,
+    capacity: UInteger,
+    output_index: UInteger,
+}
+
+impl VertexCapture {
+    /// Create a capture for `vertex_function`.
+    ///
+    /// The function must write one `stride`-byte record per vertex to the
+    /// buffer bound at `output_index`. The output buffer holds `capacity`
+    /// vertices in shared memory.
+    ///
+    /// Fails with [`VertexCaptureError::SizeOverflow`] if the buffer size
+    /// `stride * capacity` overflows.
+    pub fn new(
+        device: &Device,
+        vertex_function: &Function,
+        stride: UInteger,
+        capacity: UInteger,
+        output_index: UInteger,
+    ) -> Result<Self, VertexCaptureError> {
+        let length = stride
+            .checked_mul(capacity)
+            .ok_or(VertexCaptureError::SizeOverflow { stride, capacity })?;
+        let descriptor = RenderPipelineDescriptor::vertex_only(vertex_function)
+            .ok_or(VertexCaptureError::Allocation)?;
+        let pipeline = device
+            .new_render_pipeline_state_with_descriptor(&descriptor)
+            .map_err(VertexCaptureError::Pipeline)?;
+        let output = device
+            .new_buffer(length.max(1), ResourceOptions::STORAGE_MODE_SHARED)
+            .ok_or(VertexCaptureError::Allocation)?;
+        Ok(Self {
+            pipeline,
+            output,
+            stride,
+            capacity,
+            output_index,
+        })
+    }
+
+    /// Get the vertex-only pipeline.
+    pub fn pipeline(&self) -> &RenderPipelineState {
+        &self.pipeline
+    }
+
+    /// Get the buffer the captured vertices are written to.
+    pub fn output_buffer(&self) -> &Buffer {
+        &self.output
+    }
+
+    /// Get the size of one captured vertex in bytes.
+    pub fn stride(&self) -> UInteger {
+        self.stride
+    }
+
+    /// Get the number of vertices the output buffer holds.
+    pub fn capacity(&self) -> UInteger {
+        self.capacity
+    }
+
+    /// Get the vertex buffer index the output buffer is bound to.
+    pub fn output_index(&self) -> UInteger {
+        self.output_index
+    }
+
+    /// Encode a capture of `vertex_count` vertices into `command_buffer`.
+    ///
+    /// `bind` is called with the encoder after the pipeline and output
+    /// buffer are bound, to bind the function's inputs. Vertices are drawn
+    /// as points so every `vertex_id` runs exactly once.
+    pub fn encode<F>(
+        &self,
+        command_buffer: &CommandBuffer,
+        vertex_count: UInteger,
+        bind: F,
+    ) -> Result<(), VertexCaptureError>
+    where
+        F: FnOnce(&RenderCommandEncoder),
+    {
+        self.check_capacity(vertex_count)?;
+
+        // A pass without attachments still needs a render target size.
+        let pass = RenderPassDescriptor::new().ok_or(VertexCaptureError::Allocation)?;
+        pass.set_render_target_width(1);
+        pass.set_render_target_height(1);
+        pass.set_default_raster_sample_count(1);
+
+        let encoder =
+            unsafe { RenderCommandEncoder::from_raw(command_buffer.render_command_encoder(&pass)) }
+                .ok_or(VertexCaptureError::Allocation)?;
+        encoder.set_render_pipeline_state(&self.pipeline);
+        encoder.set_vertex_buffer(&self.output, 0, self.output_index);
+        bind(&encoder);
+        encoder.draw_primitives(PrimitiveType::POINT, 0, vertex_count);
+        encoder.end_encoding();
+        Ok(())
+    }
+
+    /// Copy the first `vertex_count` captured vertices out of the buffer.
+    ///
+    /// Call only after the command buffer from [`encode`](Self::encode) has
+    /// completed. Fails if `T` isn't exactly `stride` bytes.
+    ///
+    /// # Safety
+    ///
+    /// `T` must match the layout the vertex function writes, and every bit
+    /// pattern it writes must be a valid `T`.
+    pub unsafe fn read<T: Copy>(
+        &self,
+        vertex_count: UInteger,
+    ) -> Result<Vec<T>, VertexCaptureError> {
+        if std::mem::size_of::<T>() != self.stride {
+            return Err(VertexCaptureError::StrideMismatch {
+                stride: self.stride,
+                element: std::mem::size_of::<T>(),
+            });
+        }
+        self.check_capacity(vertex_count)?;
+        let ptr = self
+            .output
+            .contents()
+            .ok_or(VertexCaptureError::Allocation)?;
+        let vertices =
+            unsafe { std::slice::from_raw_parts(ptr as *const T, vertex_count) }.to_vec();
+        Ok(vertices)
+    }
+
+    fn check_capacity(&self, vertex_count: UInteger) -> Result<(), VertexCaptureError> {
+        if vertex_count > self.capacity {
+            return Err(VertexCaptureError::CapacityExceeded {
+                requested: vertex_count,
+                capacity: self.capacity,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for VertexCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VertexCapture")
+            .field("stride", &self.stride)
+            .field("capacity", &self.capacity)
+            .field("output_index", &self.output_index)
+            .finish()
+    }
+}
@@ -0,0 +1,318 @@
+//! Graphics, async compute and IO queues with per-queue time budgets.
+//!
+//! Most renderers settle on the same three queues: one for rendering, one
+//! for compute and copies that should overlap it, and an IO queue for
+//! asset streaming. [`QueueSet`] creates and labels them, routes work to
+//! the right one with [`queue_for`](QueueSet::queue_for), and accounts the
+//! GPU time each queue spends per frame against an optional budget, so a
+//! frame loop can see which queue is overrunning and scale back the work
+//! routed to it.
+//!
+//! Time is taken from command buffer `GPUStartTime`/`GPUEndTime` for the
+//! command queues and from host time between creation and completion for
+//! the IO queue. It is attributed to the frame in which the command buffer
+//! completes.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let queues = QueueSet::new(&device).unwrap();
+//! queues.set_budget(QueueRole::AsyncCompute, Some(Duration::from_millis(4)));
+//!
+//! // Each frame:
+//! let graphics = queues.command_buffer(WorkKind::Render).unwrap();
+//! let compute = queues.command_buffer(WorkKind::Compute).unwrap();
+//! // ... encode and commit ...
+//! queues.end_frame();
+//! if queues.stats(QueueRole::AsyncCompute).is_over_budget() {
+//!     // halve the simulation step count
+//! }
+//! ```
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::io::{IOCommandBuffer, IOCommandQueue, IOCommandQueueDescriptor};
+use crate::{CommandBuffer, CommandQueue, Device};
+
+// ============================================================================
+// QueueRole / WorkKind
+// ============================================================================
+
+/// One of the queues in a [`QueueSet`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum QueueRole {
+    /// The queue for rendering.
+    Graphics,
+    /// The queue for compute and copies that overlap rendering.
+    AsyncCompute,
+    /// The IO queue for loading from files.
+    Io,
+}
+
+impl QueueRole {
+    /// All roles, in index order.
+    pub const ALL: [QueueRole; 3] = [QueueRole::Graphics, QueueRole::AsyncCompute, QueueRole::Io];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The kind of GPU work to route with [`QueueSet::queue_for`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WorkKind {
+    /// Render passes and work that feeds them within the frame.
+    Render,
+    /// Compute that can run alongside rendering.
+    Compute,
+    /// Blits such as uploads and readbacks.
+    Transfer,
+}
+
+impl WorkKind {
+    /// Get the queue that runs this kind of work.
+    ///
+    /// Compute and transfers go to the async compute queue so they overlap
+    /// rendering instead of queueing behind it.
+    pub fn role(self) -> QueueRole {
+        match self {
+            WorkKind::Render => QueueRole::Graphics,
+            WorkKind::Compute | WorkKind::Transfer => QueueRole::AsyncCompute,
+        }
+    }
+}
+
+// ============================================================================
+// QueueStats
+// ============================================================================
+
+/// Time accounting for one queue, reported by [`QueueSet::stats`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Command buffers that completed successfully.
+    pub completed: u64,
+    /// Command buffers that completed with an error.
+    pub failed: u64,
+    /// Time spent over the queue's lifetime.
+    pub total_time: Duration,
+    /// Time spent so far in the current frame.
+    pub frame_time: Duration,
+    /// Time spent in the last finished frame.
+    pub last_frame_time: Duration,
+    /// The per-frame budget, if set.
+    pub budget: Option<Duration>,
+    /// Finished frames whose time exceeded the budget.
+    pub frames_over_budget: u64,
+}
+
+impl QueueStats {
+    /// Check if the last finished frame exceeded the budget.
+    pub fn is_over_budget(&self) -> bool {
+        self.budget
+            .is_some_and(|budget| self.last_frame_time > budget)
+    }
+
+    /// Get the budget left in the current frame, or `None` if no budget is
+    /// set.
+    pub fn remaining_budget(&self) -> Option<Duration> {
+        self.budget
+            .map(|budget| budget.saturating_sub(self.frame_time))
+    }
+
+    fn record(&mut self, time: Duration, failed: bool) {
+        if failed {
+            self.failed += 1;
+        } else {
+            self.completed += 1;
+        }
+        self.total_time += time;
+        self.frame_time += time;
+    }
+
+    fn end_frame(&mut self) {
+        self.last_frame_time = std::mem::take(&mut self.frame_time);
+        if self.is_over_budget() {
+            self.frames_over_budget += 1;
+        }
+    }
+}
+
+// ============================================================================
+// QueueSet
+// ============================================================================
+
+/// A graphics queue, an async compute queue and an optional IO queue.
+pub struct QueueSet {
+    graphics: CommandQueue,
+    async_compute: CommandQueue,
+    io: Option<IOCommandQueue>,
+    stats: Arc<Mutex<[QueueStats; 3]>>,
+}
+
+impl QueueSet {
+    /// Create and label the three queues on `device`.
+    ///
+    /// The IO queue is `None` where the device can't create one.
+    pub fn new(device: &Device) -> Option<Self> {
+        let graphics = device.new_command_queue()?;
+        graphics.set_label("Graphics");
+        let async_compute = device.new_command_queue()?;
+        async_compute.set_label("Async Compute");
+        let io = IOCommandQueueDescriptor::new()
+            .and_then(|descriptor| device.new_io_command_queue(&descriptor).ok());
+        if let Some(io) = &io {
+            io.set_label("IO");
+        }
+        Some(Self::from_queues(graphics, async_compute, io))
+    }
+
+    /// Group existing queues.
+    pub fn from_queues(
+        graphics: CommandQueue,
+        async_compute: CommandQueue,
+        io: Option<IOCommandQueue>,
+    ) -> Self {
+        Self {
+            graphics,
+            async_compute,
+            io,
+            stats: Arc::new(Mutex::new([QueueStats::default(); 3])),
+        }
+    }
+
+    /// Get the graphics queue.
+    pub fn graphics(&self) -> &CommandQueue {
+        &self.graphics
+    }
+
+    /// Get the async compute queue.
+    pub fn async_compute(&self) -> &CommandQueue {
+        &self.async_compute
+    }
+
+    /// Get the IO queue.
+    pub fn io(&self) -> Option<&IOCommandQueue> {
+        self.io.as_ref()
+    }
+
+    /// Get the command queue that runs `work`.
+    pub fn queue_for(&self, work: WorkKind) -> &CommandQueue {
+        match work.role() {
+            QueueRole::Graphics => &self.graphics,
+            _ => &self.async_compute,
+        }
+    }
+
+    /// Create a command buffer for `work` on its queue.
+    ///
+    /// With the `blocks` feature, its GPU time is added to the queue's
+    /// stats when it completes.
+    pub fn command_buffer(&self, work: WorkKind) -> Option<CommandBuffer> {
+        let command_buffer = self.queue_for(work).command_buffer()?;
+        #[cfg(feature = "blocks")]
+        {
+            let stats = Arc::clone(&self.stats);
+            let index = work.role().index();
+            command_buffer.add_completed_handler(move |command_buffer| {
+                let seconds = command_buffer.gpu_end_time() - command_buffer.gpu_start_time();
+                let time = Duration::from_secs_f64(seconds.max(0.0));
+                let failed = command_buffer.error().is_some();
+                stats.lock().unwrap()[index].record(time, failed);
+            });
+        }
+        Some(command_buffer)
+    }
+
+    /// Create a command buffer on the IO queue.
+    ///
+    /// With the `blocks` feature, the time from creation to completion is
+    /// added to the IO queue's stats when it completes.
+    pub fn io_command_buffer(&self) -> Option<IOCommandBuffer> {
+        let command_buffer = self.io.as_ref()?.command_buffer()?;
+        #[cfg(feature = "blocks")]
+        {
+            let stats = Arc::clone(&self.stats);
+            let created = std::time::Instant::now();
+            command_buffer.add_completed_handler(move |command_buffer| {
+                let failed = command_buffer.error().is_some();
+                stats.lock().unwrap()[QueueRole::Io.index()].record(created.elapsed(), failed);
+            });
+        }
+        Some(command_buffer)
+    }
+
+    /// Add `time` spent by work on `role`'s queue that wasn't submitted
+    /// through this set.
+    pub fn record_time(&self, role: QueueRole, time: Duration) {
+        self.stats.lock().unwrap()[role.index()].record(time, false);
+    }
+
+    /// Set the per-frame time budget of `role`'s queue, or `None` to stop
+    /// tracking overruns.
+    pub fn set_budget(&self, role: QueueRole, budget: Option<Duration>) {
+        self.stats.lock().unwrap()[role.index()].budget = budget;
+    }
+
+    /// Finish the current frame's accounting on every queue.
+    pub fn end_frame(&self) {
+        for stats in self.stats.lock().unwrap().iter_mut() {
+            stats.end_frame();
+        }
+    }
+
+    /// Get the time accounting of `role`'s queue.
+    pub fn stats(&self, role: QueueRole) -> QueueStats {
+        self.stats.lock().unwrap()[role.index()]
+    }
+}
+
+impl fmt::Debug for QueueSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueueSet")
+            .field("graphics", &self.graphics)
+            .field("async_compute", &self.async_compute)
+            .field("has_io", &self.io.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_set_budget_accounting() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queues = QueueSet::new(&device).unwrap();
+        assert_eq!(
+            queues.queue_for(WorkKind::Transfer).as_raw(),
+            queues.async_compute().as_raw()
+        );
+        assert_eq!(
+            queues.queue_for(WorkKind::Render).as_raw(),
+            queues.graphics().as_raw()
+        );
+
+        let budget = Duration::from_millis(4);
+        queues.set_budget(QueueRole::AsyncCompute, Some(budget));
+        queues.record_time(QueueRole::AsyncCompute, Duration::from_millis(3));
+        assert_eq!(
+            queues.stats(QueueRole::AsyncCompute).remaining_budget(),
+            Some(Duration::from_millis(1))
+        );
+        queues.record_time(QueueRole::AsyncCompute, Duration::from_millis(3));
+        queues.end_frame();
+
+        let stats = queues.stats(QueueRole::AsyncCompute);
+        assert!(stats.is_over_budget());
+        assert_eq!(stats.frames_over_budget, 1);
+        assert_eq!(stats.last_frame_time, Duration::from_millis(6));
+        assert_eq!(stats.frame_time, Duration::ZERO);
+
+        queues.end_frame();
+        assert!(!queues.stats(QueueRole::AsyncCompute).is_over_budget());
+        assert!(!queues.stats(QueueRole::Graphics).is_over_budget());
+    }
+}
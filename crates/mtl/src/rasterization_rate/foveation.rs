@@ -0,0 +1,324 @@
+//! Foveated rendering on top of rasterization rate maps.
+//!
+//! [`FoveationController`] turns a small set of [`FoveationParams`] (a focus
+//! point and a radial falloff) into a [`RasterizationRateMap`] plus the
+//! parameter buffer shaders need to map between screen and physical
+//! coordinates. Call [`FoveationController::update`] every frame; the map is
+//! only rebuilt when the parameters move by more than the configured
+//! tolerance.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut foveation = FoveationController::new(&device, Size::new(width, height, 0));
+//!
+//! // Each frame:
+//! foveation.update(FoveationParams::gaze(eye_x, eye_y))?;
+//! let map = foveation.map().unwrap();
+//! unsafe { pass.set_rasterization_rate_map(map.as_raw()) };
+//! encoder.set_fragment_buffer(foveation.parameter_buffer().unwrap(), 0, 1);
+//! ```
+
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use super::{
+    RasterizationRateLayerDescriptor, RasterizationRateMap, RasterizationRateMapDescriptor,
+};
+use crate::enums::ResourceOptions;
+use crate::types::Size;
+use crate::{Buffer, Device};
+
+/// Default number of rate samples along each axis.
+const DEFAULT_GRID: UInteger = 8;
+
+/// Default tolerance for [`FoveationParams::differs_from`].
+const DEFAULT_TOLERANCE: f32 = 0.02;
+
+/// Radial falloff parameters for foveated rendering.
+///
+/// Positions and radii are normalized to the screen, `0.0..=1.0` on each
+/// axis. Samples within `inner_radius` of the center render at full rate;
+/// the rate falls smoothly to `min_rate` at `outer_radius`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FoveationParams {
+    /// Focus point, normalized.
+    pub center: [f32; 2],
+    /// Radius of the full-rate region.
+    pub inner_radius: f32,
+    /// Radius beyond which the minimum rate applies.
+    pub outer_radius: f32,
+    /// Lowest rasterization rate, in `(0.0, 1.0]`.
+    pub min_rate: f32,
+}
+
+impl FoveationParams {
+    /// Center-weighted foveation for displays without eye tracking.
+    pub const fn centered() -> Self {
+        Self::gaze(0.5, 0.5)
+    }
+
+    /// Foveation around a normalized gaze point.
+    pub const fn gaze(x: f32, y: f32) -> Self {
+        Self {
+            center: [x, y],
+            inner_radius: 0.2,
+            outer_radius: 0.6,
+            min_rate: 0.25,
+        }
+    }
+
+    /// Set the full-rate and minimum-rate radii.
+    pub const fn with_radii(mut self, inner: f32, outer: f32) -> Self {
+        self.inner_radius = inner;
+        self.outer_radius = outer;
+        self
+    }
+
+    /// Set the minimum rasterization rate.
+    pub const fn with_min_rate(mut self, min_rate: f32) -> Self {
+        self.min_rate = min_rate;
+        self
+    }
+
+    /// Check if any parameter differs from `other` by more than `tolerance`.
+    pub fn differs_from(&self, other: &Self, tolerance: f32) -> bool {
+        let pairs = [
+            (self.center[0], other.center[0]),
+            (self.center[1], other.center[1]),
+            (self.inner_radius, other.inner_radius),
+            (self.outer_radius, other.outer_radius),
+            (self.min_rate, other.min_rate),
+        ];
+        pairs.iter().any(|(a, b)| (a - b).abs() > tolerance)
+    }
+
+    /// Compute per-axis rate samples for a `columns` x `rows` grid.
+    ///
+    /// Returns `(horizontal, vertical)` sample arrays.
+    pub fn samples(&self, columns: usize, rows: usize) -> (Vec<f32>, Vec<f32>) {
+        (
+            self.axis_samples(columns, self.center[0]),
+            self.axis_samples(rows, self.center[1]),
+        )
+    }
+
+    fn axis_samples(&self, count: usize, center: f32) -> Vec<f32> {
+        let min_rate = self.min_rate.clamp(f32::EPSILON, 1.0);
+        let inner = self.inner_radius.max(0.0);
+        let outer = self.outer_radius.max(inner);
+        (0..count)
+            .map(|i| {
+                let position = (i as f32 + 0.5) / count as f32;
+                let distance = (position - center).abs();
+                if distance <= inner {
+                    1.0
+                } else if distance >= outer {
+                    min_rate
+                } else {
+                    let t = (distance - inner) / (outer - inner);
+                    let smooth = t * t * (3.0 - 2.0 * t);
+                    1.0 + (min_rate - 1.0) * smooth
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for FoveationParams {
+    fn default() -> Self {
+        Self::centered()
+    }
+}
+
+/// Error produced when building a foveation rate map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FoveationError {
+    /// The device doesn't support rasterization rate maps.
+    Unsupported,
+    /// A descriptor, map, or parameter buffer could not be created.
+    Allocation,
+}
+
+impl fmt::Display for FoveationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FoveationError::Unsupported => {
+                write!(f, "device does not support rasterization rate maps")
+            }
+            FoveationError::Allocation => write!(f, "failed to allocate rasterization rate map"),
+        }
+    }
+}
+
+impl std::error::Error for FoveationError {}
+
+/// Maintains a foveation [`RasterizationRateMap`] and its parameter buffer.
+pub struct FoveationController {
+    device: Device,
+    screen_size: Size,
+    grid: (UInteger, UInteger),
+    tolerance: f32,
+    params: Option<FoveationParams>,
+    map: Option<RasterizationRateMap>,
+    parameter_buffer: Option<Buffer>,
+}
+
+impl FoveationController {
+    /// Create a controller for a render target of `screen_size`.
+    ///
+    /// No map is built until the first [`update`](Self::update).
+    pub fn new(device: &Device, screen_size: Size) -> Self {
+        Self {
+            device: device.clone(),
+            screen_size,
+            grid: (DEFAULT_GRID, DEFAULT_GRID),
+            tolerance: DEFAULT_TOLERANCE,
+            params: None,
+            map: None,
+            parameter_buffer: None,
+        }
+    }
+
+    /// Set the number of rate samples along each axis.
+    pub fn with_grid(mut self, columns: UInteger, rows: UInteger) -> Self {
+        self.grid = (columns.max(1), rows.max(1));
+        self.params = None;
+        self
+    }
+
+    /// Set how far parameters must move before the map is rebuilt.
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Change the render target size. The next update rebuilds the map.
+    pub fn set_screen_size(&mut self, screen_size: Size) {
+        if self.screen_size != screen_size {
+            self.screen_size = screen_size;
+            self.params = None;
+        }
+    }
+
+    /// Get the render target size.
+    pub fn screen_size(&self) -> Size {
+        self.screen_size
+    }
+
+    /// Get the parameters the current map was built from.
+    pub fn params(&self) -> Option<FoveationParams> {
+        self.params
+    }
+
+    /// Get the current rate map.
+    pub fn map(&self) -> Option<&RasterizationRateMap> {
+        self.map.as_ref()
+    }
+
+    /// Get the buffer holding the map's shader-side parameter data.
+    ///
+    /// Bind it to shaders that convert between screen and physical
+    /// coordinates with `rasterization_rate_map_data`. Every rebuilt map
+    /// gets a new buffer, so command buffers still using the previous one
+    /// keep reading the parameters they were encoded with.
+    pub fn parameter_buffer(&self) -> Option<&Buffer> {
+        self.parameter_buffer.as_ref()
+    }
+
+    /// Get the physical render target size the map requires.
+    pub fn physical_size(&self) -> Option<Size> {
+        self.map.as_ref().map(|map| map.physical_size(0))
+    }
+
+    /// Rebuild the map if `params` differ materially from the current ones.
+    ///
+    /// Returns `Ok(true)` if a new map was built.
+    pub fn update(&mut self, params: FoveationParams) -> Result<bool, FoveationError> {
+        if let Some(current) = &self.params
+            && !current.differs_from(&params, self.tolerance)
+        {
+            return Ok(false);
+        }
+        if !self.device.supports_rasterization_rate_map(1) {
+            return Err(FoveationError::Unsupported);
+        }
+
+        let (columns, rows) = self.grid;
+        let (horizontal, vertical) = params.samples(columns, rows);
+        let layer = RasterizationRateLayerDescriptor::with_sample_count_and_data(
+            Size::new(columns, rows, 0),
+            horizontal.as_ptr(),
+            vertical.as_ptr(),
+        )
+        .ok_or(FoveationError::Allocation)?;
+        let descriptor =
+            RasterizationRateMapDescriptor::with_screen_size_and_layer(self.screen_size, &layer)
+                .ok_or(FoveationError::Allocation)?;
+        let map = self
+            .device
+            .new_rasterization_rate_map(&descriptor)
+            .ok_or(FoveationError::Allocation)?;
+
+        // Frames in flight may still read the previous buffer, so each map
+        // gets its own.
+        let required = map.parameter_buffer_size_and_align().size;
+        let buffer = self
+            .device
+            .new_buffer(required, ResourceOptions::STORAGE_MODE_SHARED)
+            .ok_or(FoveationError::Allocation)?;
+        map.copy_parameter_data_to_buffer(&buffer, 0);
+
+        self.map = Some(map);
+        self.parameter_buffer = Some(buffer);
+        self.params = Some(params);
+        Ok(true)
+    }
+}
+
+impl fmt::Debug for FoveationController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FoveationController")
+            .field("screen_size", &self.screen_size)
+            .field("grid", &self.grid)
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_centered_samples_are_symmetric() {
+        let (horizontal, vertical) = FoveationParams::centered().samples(8, 8);
+        assert_eq!(horizontal.len(), 8);
+        assert_eq!(horizontal, vertical);
+        for i in 0..4 {
+            assert_eq!(horizontal[i], horizontal[7 - i]);
+        }
+        assert_eq!(horizontal[3], 1.0);
+    }
+
+    #[test]
+    fn test_samples_fall_off_to_min_rate() {
+        let params = FoveationParams::gaze(0.0, 0.0)
+            .with_radii(0.1, 0.5)
+            .with_min_rate(0.25);
+        let (horizontal, _) = params.samples(10, 10);
+        assert_eq!(horizontal[0], 1.0);
+        assert_eq!(horizontal[9], 0.25);
+        assert!(horizontal.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn test_differs_from() {
+        let a = FoveationParams::gaze(0.5, 0.5);
+        let b = FoveationParams::gaze(0.51, 0.5);
+        let c = FoveationParams::gaze(0.6, 0.5);
+        assert!(!a.differs_from(&b, 0.02));
+        assert!(a.differs_from(&c, 0.02));
+    }
+}
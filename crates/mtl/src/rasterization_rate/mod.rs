@@ -14,6 +14,10 @@ use mtl_sys::{Class, msg_send_0, msg_send_1, msg_send_2, sel};
 use crate::Buffer;
 use crate::types::{Coordinate2D, Size, SizeAndAlign};
 
+mod foveation;
+
+pub use foveation::{FoveationController, FoveationError, FoveationParams};
+
 // ============================================================================
 // RasterizationRateSampleArray
 // ============================================================================
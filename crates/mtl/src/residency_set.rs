@@ -3,14 +3,35 @@
 //! Corresponds to `Metal/MTLResidencySet.hpp`.
 //!
 //! Residency sets manage which allocations are resident in GPU memory.
+//!
+//! [`ResidencySetBuilder`] collects buffers, textures and heaps, creates and
+//! commits the set in one step, and reports how much memory it made
+//! resident.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let (set, report) = ResidencySetBuilder::new(&device)
+//!     .label("level")
+//!     .allocation(&vertex_buffer)
+//!     .allocation(&albedo)
+//!     .allocation(&heap)
+//!     .build()?;
+//! println!("{} bytes resident", report.allocated_size);
+//!
+//! queue.add_residency_set(&set);
+//! set.request_residency();
+//! ```
 
 use std::ffi::c_void;
+use std::marker::PhantomData;
 use std::ptr::NonNull;
 
 use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, msg_send_2, sel};
 
 use crate::Device;
+use crate::allocation::Allocation;
 
 // ============================================================================
 // ResidencySetDescriptor
@@ -334,6 +355,122 @@ impl Referencing for ResidencySet {
 unsafe impl Send for ResidencySet {}
 unsafe impl Sync for ResidencySet {}
 
+// ============================================================================
+// ResidencySetBuilder
+// ============================================================================
+
+/// Sizes reported by [`ResidencySetBuilder::build`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ResidencyReport {
+    /// Number of allocations in the committed set.
+    pub allocation_count: UInteger,
+    /// Sum of the [`Allocation::allocated_size`] of every allocation added
+    /// to the builder.
+    pub requested_size: u64,
+    /// The committed set's [`ResidencySet::allocated_size`].
+    pub allocated_size: u64,
+}
+
+/// Collects allocations and creates a committed [`ResidencySet`].
+///
+/// Allocations are borrowed until [`build`](Self::build), and adding the
+/// same allocation twice has no effect.
+pub struct ResidencySetBuilder<'a> {
+    device: &'a Device,
+    label: Option<String>,
+    allocations: Vec<*const c_void>,
+    requested_size: u64,
+    _allocations: PhantomData<&'a ()>,
+}
+
+impl<'a> ResidencySetBuilder<'a> {
+    /// Create a builder for a set on `device`.
+    pub fn new(device: &'a Device) -> Self {
+        Self {
+            device,
+            label: None,
+            allocations: Vec::new(),
+            requested_size: 0,
+            _allocations: PhantomData,
+        }
+    }
+
+    /// Set the label of the residency set.
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Add a buffer, texture or heap to the set.
+    pub fn allocation<A: Allocation + Referencing>(mut self, allocation: &'a A) -> Self {
+        let ptr = allocation.as_ptr();
+        if !self.allocations.contains(&ptr) {
+            self.allocations.push(ptr);
+            self.requested_size += allocation.allocated_size() as u64;
+        }
+        self
+    }
+
+    /// Add every allocation in `allocations` to the set.
+    pub fn allocations<A: Allocation + Referencing + 'a>(
+        self,
+        allocations: impl IntoIterator<Item = &'a A>,
+    ) -> Self {
+        allocations
+            .into_iter()
+            .fold(self, |builder, allocation| builder.allocation(allocation))
+    }
+
+    /// Get the number of allocations added so far.
+    pub fn len(&self) -> usize {
+        self.allocations.len()
+    }
+
+    /// Check if no allocations have been added.
+    pub fn is_empty(&self) -> bool {
+        self.allocations.is_empty()
+    }
+
+    /// Create the residency set, add the allocations and commit it.
+    ///
+    /// Residency is not requested; call
+    /// [`ResidencySet::request_residency`] or attach the set to a queue.
+    pub fn build(self) -> Result<(ResidencySet, ResidencyReport), mtl_foundation::Error> {
+        let descriptor = ResidencySetDescriptor::new().ok_or_else(|| {
+            mtl_foundation::Error::error(std::ptr::null_mut(), -1, std::ptr::null_mut())
+                .expect("failed to create error object")
+        })?;
+        descriptor.set_initial_capacity(self.allocations.len() as UInteger);
+        if let Some(label) = &self.label {
+            descriptor.set_label(label);
+        }
+        let set = self.device.new_residency_set(&descriptor)?;
+        if !self.allocations.is_empty() {
+            set.add_allocations_ptr(
+                self.allocations.as_ptr(),
+                self.allocations.len() as UInteger,
+            );
+        }
+        set.commit();
+        let report = ResidencyReport {
+            allocation_count: set.allocation_count(),
+            requested_size: self.requested_size,
+            allocated_size: set.allocated_size(),
+        };
+        Ok((set, report))
+    }
+}
+
+impl std::fmt::Debug for ResidencySetBuilder<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResidencySetBuilder")
+            .field("label", &self.label)
+            .field("allocations", &self.allocations.len())
+            .field("requested_size", &self.requested_size)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -366,4 +503,40 @@ mod tests {
         descriptor.set_initial_capacity(100);
         assert_eq!(descriptor.initial_capacity(), 100);
     }
+
+    #[test]
+    fn test_residency_set_builder() {
+        use crate::enums::ResourceOptions;
+
+        let device = crate::device::system_default().expect("no Metal device");
+        let a = device
+            .new_buffer(4096, ResourceOptions::STORAGE_MODE_PRIVATE)
+            .unwrap();
+        let b = device
+            .new_buffer(65536, ResourceOptions::STORAGE_MODE_PRIVATE)
+            .unwrap();
+
+        let builder = ResidencySetBuilder::new(&device)
+            .label("builder")
+            .allocations([&a, &b])
+            .allocation(&a);
+        assert_eq!(builder.len(), 2);
+
+        let (set, report) = builder.build().expect("failed to build residency set");
+        assert_eq!(set.label().as_deref(), Some("builder"));
+        assert_eq!(report.allocation_count, 2);
+        assert_eq!(
+            report.requested_size,
+            (a.allocated_size() + b.allocated_size()) as u64
+        );
+        assert!(report.allocated_size >= 65536);
+
+        let queue = device.new_command_queue().unwrap();
+        queue.add_residency_set(&set);
+        let command_buffer = queue.command_buffer().unwrap();
+        command_buffer.use_residency_set(&set);
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+        queue.remove_residency_set(&set);
+    }
 }
@@ -4,23 +4,32 @@
 //!
 //! This module defines the Resource trait which corresponds to the
 //! `MTL::Resource` protocol in metal-cpp. Resource types like Buffer
-//! and Texture already have these methods as inherent methods, so this
-//! trait serves primarily for documentation and generic programming.
+//! and Texture already have most of these methods as inherent methods; the
+//! trait is implemented for them so they can be handled generically, as
+//! the encoders' `use_resource` and `use_resources` do.
+
+use std::ffi::c_void;
+
+use mtl_foundation::{Referencing, UInteger};
+use mtl_sys::{msg_send_0, msg_send_1, sel};
 
-use crate::Device;
 use crate::enums::{
     CPUCacheMode, HazardTrackingMode, PurgeableState, ResourceOptions, StorageMode,
 };
 use crate::heap::Heap;
-use mtl_foundation::UInteger;
+use crate::{
+    AccelerationStructure, Buffer, Device, IndirectCommandBuffer, IntersectionFunctionTable,
+    Texture, VisibleFunctionTable,
+};
 
 /// Protocol for GPU-accessible resources.
 ///
 /// C++ equivalent: `MTL::Resource`
 ///
 /// This trait corresponds to the Objective-C protocol that resource types
-/// conform to. In Rust, concrete types like [`Buffer`](crate::Buffer) and
-/// [`Texture`](crate::Texture) implement these methods directly.
+/// conform to. [`Buffer`], [`Texture`], [`AccelerationStructure`],
+/// [`IndirectCommandBuffer`] and the function tables implement it; only
+/// [`as_resource_ptr`](Self::as_resource_ptr) needs to be provided.
 ///
 /// # Example
 ///
@@ -30,75 +39,157 @@ use mtl_foundation::UInteger;
 /// println!("Storage mode: {:?}", buffer.storage_mode());
 /// ```
 pub trait Resource {
+    /// Get the raw pointer to the resource object.
+    fn as_resource_ptr(&self) -> *const c_void;
+
     /// Get the allocated size of this resource in bytes.
     ///
     /// C++ equivalent: `NS::UInteger allocatedSize() const`
-    fn allocated_size(&self) -> UInteger;
+    fn allocated_size(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_resource_ptr(), sel!(allocatedSize)) }
+    }
+
     /// Get the CPU cache mode for this resource.
     ///
     /// C++ equivalent: `CPUCacheMode cpuCacheMode() const`
-    fn cpu_cache_mode(&self) -> CPUCacheMode;
+    fn cpu_cache_mode(&self) -> CPUCacheMode {
+        unsafe { msg_send_0(self.as_resource_ptr(), sel!(cpuCacheMode)) }
+    }
 
     /// Get the device that created this resource.
     ///
     /// C++ equivalent: `Device* device() const`
-    fn device(&self) -> Device;
+    fn device(&self) -> Device {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.as_resource_ptr(), sel!(device));
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            Device::from_raw(ptr).expect("resource has no device")
+        }
+    }
 
     /// Get the hazard tracking mode for this resource.
     ///
     /// C++ equivalent: `HazardTrackingMode hazardTrackingMode() const`
-    fn hazard_tracking_mode(&self) -> HazardTrackingMode;
+    fn hazard_tracking_mode(&self) -> HazardTrackingMode {
+        unsafe { msg_send_0(self.as_resource_ptr(), sel!(hazardTrackingMode)) }
+    }
 
     /// Get the heap this resource was allocated from.
     ///
     /// Returns `None` if the resource was not allocated from a heap.
     ///
     /// C++ equivalent: `Heap* heap() const`
-    fn heap(&self) -> Option<Heap>;
+    fn heap(&self) -> Option<Heap> {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.as_resource_ptr(), sel!(heap));
+            if ptr.is_null() {
+                return None;
+            }
+            let _: *mut c_void = msg_send_0(ptr, sel!(retain));
+            Heap::from_raw(ptr)
+        }
+    }
 
     /// Get the offset within the heap where this resource is allocated.
     ///
     /// C++ equivalent: `NS::UInteger heapOffset() const`
-    fn heap_offset(&self) -> UInteger;
+    fn heap_offset(&self) -> UInteger {
+        unsafe { msg_send_0(self.as_resource_ptr(), sel!(heapOffset)) }
+    }
 
     /// Check if this resource can be aliased with other resources.
     ///
     /// C++ equivalent: `bool isAliasable()`
-    fn is_aliasable(&self) -> bool;
+    fn is_aliasable(&self) -> bool {
+        unsafe { msg_send_0(self.as_resource_ptr(), sel!(isAliasable)) }
+    }
 
     /// Get the debug label for this resource.
     ///
     /// C++ equivalent: `NS::String* label() const`
-    fn label(&self) -> Option<String>;
+    fn label(&self) -> Option<String> {
+        unsafe {
+            let ptr: *mut c_void = msg_send_0(self.as_resource_ptr(), sel!(label));
+            if ptr.is_null() {
+                return None;
+            }
+            let utf8_ptr: *const std::ffi::c_char = msg_send_0(ptr, sel!(UTF8String));
+            if utf8_ptr.is_null() {
+                return None;
+            }
+            let c_str = std::ffi::CStr::from_ptr(utf8_ptr);
+            Some(c_str.to_string_lossy().into_owned())
+        }
+    }
 
     /// Make this resource aliasable with other resources.
     ///
     /// C++ equivalent: `void makeAliasable()`
-    fn make_aliasable(&self);
+    fn make_aliasable(&self) {
+        unsafe { msg_send_0::<()>(self.as_resource_ptr(), sel!(makeAliasable)) }
+    }
 
     /// Get the resource options used to create this resource.
     ///
     /// C++ equivalent: `ResourceOptions resourceOptions() const`
-    fn resource_options(&self) -> ResourceOptions;
+    fn resource_options(&self) -> ResourceOptions {
+        unsafe { msg_send_0(self.as_resource_ptr(), sel!(resourceOptions)) }
+    }
 
     /// Set the debug label for this resource.
     ///
     /// C++ equivalent: `void setLabel(const NS::String*)`
-    fn set_label(&self, label: &str);
+    fn set_label(&self, label: &str) {
+        if let Some(ns_label) = mtl_foundation::String::from_str(label) {
+            unsafe {
+                msg_send_1::<(), *const c_void>(
+                    self.as_resource_ptr(),
+                    sel!(setLabel:),
+                    ns_label.as_ptr(),
+                );
+            }
+        }
+    }
 
     /// Set the purgeable state for this resource.
     ///
     /// Returns the previous purgeable state.
     ///
     /// C++ equivalent: `PurgeableState setPurgeableState(PurgeableState)`
-    fn set_purgeable_state(&self, state: PurgeableState) -> PurgeableState;
+    fn set_purgeable_state(&self, state: PurgeableState) -> PurgeableState {
+        unsafe { msg_send_1(self.as_resource_ptr(), sel!(setPurgeableState:), state) }
+    }
 
     /// Get the storage mode for this resource.
     ///
     /// C++ equivalent: `StorageMode storageMode() const`
-    fn storage_mode(&self) -> StorageMode;
+    fn storage_mode(&self) -> StorageMode {
+        unsafe { msg_send_0(self.as_resource_ptr(), sel!(storageMode)) }
+    }
 }
 
+macro_rules! impl_resource {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Resource for $ty {
+                #[inline]
+                fn as_resource_ptr(&self) -> *const c_void {
+                    self.as_ptr()
+                }
+            }
+        )*
+    };
+}
+
+impl_resource!(
+    Buffer,
+    Texture,
+    AccelerationStructure,
+    IndirectCommandBuffer,
+    VisibleFunctionTable,
+    IntersectionFunctionTable,
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,4 +199,18 @@ mod tests {
         // Verify the trait is object-safe
         fn _check_object_safety(_: &dyn Resource) {}
     }
+
+    #[test]
+    fn test_resource_trait_objects() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let buffer = device
+            .new_buffer(256, ResourceOptions::STORAGE_MODE_PRIVATE)
+            .unwrap();
+        let resource: &dyn Resource = &buffer;
+        resource.set_label("scratch");
+        assert_eq!(buffer.label().as_deref(), Some("scratch"));
+        assert_eq!(resource.storage_mode(), StorageMode::PRIVATE);
+        assert!(resource.allocated_size() >= 256);
+        assert!(resource.heap().is_none());
+    }
 }
@@ -0,0 +1,421 @@
+//! Copyable handles to resources owned by a [`ResourceStore`].
+//!
+//! ECS-style architectures want plain-old-data components. A
+//! [`BufferHandle`] or [`TextureHandle`] is a `Copy` index plus generation
+//! that can live in a component, while the [`ResourceStore`] keeps the
+//! retained Metal object. Removing a resource bumps its slot's generation, so
+//! stale handles resolve to `None` instead of aliasing a newer resource.
+//!
+//! Resources still referenced by in-flight command buffers can be retired
+//! with a timeline value (a frame index or a [`SharedEvent`] value) and are
+//! released by [`ResourceStore::collect`] once the GPU has passed it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut store = ResourceStore::new();
+//! let mesh = store.insert_buffer(vertex_buffer);
+//!
+//! // In a system:
+//! encoder.set_vertex_buffer(store.buffer(mesh).unwrap(), 0, 0);
+//!
+//! // When the entity despawns during frame `n`:
+//! store.retire_buffer(mesh, n);
+//!
+//! // Once frame `completed` has finished on the GPU:
+//! store.collect(completed);
+//! ```
+//!
+//! [`SharedEvent`]: crate::SharedEvent
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use crate::{Buffer, Texture};
+
+/// A generational handle to a resource of type `T`.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Handle to a [`Buffer`] owned by a [`ResourceStore`].
+pub type BufferHandle = Handle<Buffer>;
+
+/// Handle to a [`Texture`] owned by a [`ResourceStore`].
+pub type TextureHandle = Handle<Texture>;
+
+impl<T> Handle<T> {
+    pub(crate) const fn new(index: u32, generation: u32) -> Self {
+        Self {
+            index,
+            generation,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the slot index.
+    #[inline]
+    pub const fn index(self) -> u32 {
+        self.index
+    }
+
+    /// Get the slot generation.
+    #[inline]
+    pub const fn generation(self) -> u32 {
+        self.generation
+    }
+
+    /// Pack the handle into a `u64`.
+    #[inline]
+    pub const fn to_bits(self) -> u64 {
+        ((self.generation as u64) << 32) | self.index as u64
+    }
+
+    /// Unpack a handle produced by [`to_bits`](Self::to_bits).
+    #[inline]
+    pub const fn from_bits(bits: u64) -> Self {
+        Self::new(bits as u32, (bits >> 32) as u32)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bits().hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+// =============================================================================
+// Arena
+// =============================================================================
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Generational arena backing each resource kind.
+pub(crate) struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> Arena<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, value: T) -> Handle<T> {
+        self.len += 1;
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return Handle::new(index, slot.generation);
+        }
+        let index = u32::try_from(self.slots.len()).expect("resource store is full");
+        self.slots.push(Slot {
+            generation: 0,
+            value: Some(value),
+        });
+        Handle::new(index, 0)
+    }
+
+    pub(crate) fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub(crate) fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.value.as_mut())
+    }
+
+    /// Get the value in slot `index`, regardless of generation.
+    pub(crate) fn get_index(&self, index: u32) -> Option<&T> {
+        self.slots
+            .get(index as usize)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let value = slot.value.as_ref()?;
+            Some((Handle::new(index as u32, slot.generation), value))
+        })
+    }
+
+    pub(crate) fn clear(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.value.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index as u32);
+            }
+        }
+        self.len = 0;
+    }
+}
+
+// =============================================================================
+// ResourceStore
+// =============================================================================
+
+enum Retired {
+    Buffer(#[allow(dead_code)] Buffer),
+    Texture(#[allow(dead_code)] Texture),
+}
+
+/// Owns retained buffers and textures and hands out copyable handles.
+pub struct ResourceStore {
+    buffers: Arena<Buffer>,
+    textures: Arena<Texture>,
+    retired: Vec<(u64, Retired)>,
+}
+
+impl ResourceStore {
+    /// Create an empty store.
+    pub const fn new() -> Self {
+        Self {
+            buffers: Arena::new(),
+            textures: Arena::new(),
+            retired: Vec::new(),
+        }
+    }
+
+    // =========================================================================
+    // Buffers
+    // =========================================================================
+
+    /// Take ownership of a buffer and return its handle.
+    pub fn insert_buffer(&mut self, buffer: Buffer) -> BufferHandle {
+        self.buffers.insert(buffer)
+    }
+
+    /// Look up a buffer. Returns `None` for stale handles.
+    pub fn buffer(&self, handle: BufferHandle) -> Option<&Buffer> {
+        self.buffers.get(handle)
+    }
+
+    /// Remove a buffer and return it, invalidating the handle.
+    pub fn remove_buffer(&mut self, handle: BufferHandle) -> Option<Buffer> {
+        self.buffers.remove(handle)
+    }
+
+    /// Invalidate a buffer's handle but keep the buffer alive until
+    /// [`collect`](Self::collect) is called with a value of at least `until`.
+    ///
+    /// Returns `false` if the handle was stale.
+    pub fn retire_buffer(&mut self, handle: BufferHandle, until: u64) -> bool {
+        match self.buffers.remove(handle) {
+            Some(buffer) => {
+                self.retired.push((until, Retired::Buffer(buffer)));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Iterate over live buffers and their handles.
+    pub fn buffers(&self) -> impl Iterator<Item = (BufferHandle, &Buffer)> {
+        self.buffers.iter()
+    }
+
+    /// Get the number of live buffers.
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.len
+    }
+
+    // =========================================================================
+    // Textures
+    // =========================================================================
+
+    /// Take ownership of a texture and return its handle.
+    pub fn insert_texture(&mut self, texture: Texture) -> TextureHandle {
+        self.textures.insert(texture)
+    }
+
+    /// Look up a texture. Returns `None` for stale handles.
+    pub fn texture(&self, handle: TextureHandle) -> Option<&Texture> {
+        self.textures.get(handle)
+    }
+
+    /// Remove a texture and return it, invalidating the handle.
+    pub fn remove_texture(&mut self, handle: TextureHandle) -> Option<Texture> {
+        self.textures.remove(handle)
+    }
+
+    /// Invalidate a texture's handle but keep the texture alive until
+    /// [`collect`](Self::collect) is called with a value of at least `until`.
+    ///
+    /// Returns `false` if the handle was stale.
+    pub fn retire_texture(&mut self, handle: TextureHandle, until: u64) -> bool {
+        match self.textures.remove(handle) {
+            Some(texture) => {
+                self.retired.push((until, Retired::Texture(texture)));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Iterate over live textures and their handles.
+    pub fn textures(&self) -> impl Iterator<Item = (TextureHandle, &Texture)> {
+        self.textures.iter()
+    }
+
+    /// Get the number of live textures.
+    pub fn texture_count(&self) -> usize {
+        self.textures.len
+    }
+
+    // =========================================================================
+    // Deferred Destruction
+    // =========================================================================
+
+    /// Release retired resources whose timeline value is `<= completed`.
+    ///
+    /// `completed` is typically the last finished frame index or the
+    /// [`SharedEvent::signaled_value`](crate::SharedEvent::signaled_value) of
+    /// a per-frame event. Returns the number of resources released.
+    pub fn collect(&mut self, completed: u64) -> usize {
+        let before = self.retired.len();
+        self.retired.retain(|(until, _)| *until > completed);
+        before - self.retired.len()
+    }
+
+    /// Get the number of retired resources waiting for [`collect`](Self::collect).
+    pub fn retired_count(&self) -> usize {
+        self.retired.len()
+    }
+
+    /// Release every resource, live and retired, invalidating all handles.
+    ///
+    /// Only call this once the GPU is idle.
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+        self.textures.clear();
+        self.retired.clear();
+    }
+}
+
+impl Default for ResourceStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ResourceStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceStore")
+            .field("buffers", &self.buffers.len)
+            .field("textures", &self.textures.len)
+            .field("retired", &self.retired.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_size() {
+        assert_eq!(std::mem::size_of::<BufferHandle>(), 8);
+        assert_eq!(std::mem::size_of::<TextureHandle>(), 8);
+    }
+
+    #[test]
+    fn test_handle_bits_round_trip() {
+        let handle = BufferHandle::new(7, 3);
+        assert_eq!(BufferHandle::from_bits(handle.to_bits()), handle);
+    }
+
+    #[test]
+    fn test_arena_insert_get_remove() {
+        let mut arena = Arena::new();
+        let a = arena.insert(1u32);
+        let b = arena.insert(2u32);
+        assert_eq!(arena.get(a), Some(&1));
+        assert_eq!(arena.get(b), Some(&2));
+        assert_eq!(arena.len, 2);
+
+        assert_eq!(arena.remove(a), Some(1));
+        assert_eq!(arena.get(a), None);
+        assert_eq!(arena.remove(a), None);
+        assert_eq!(arena.len, 1);
+    }
+
+    #[test]
+    fn test_arena_stale_handle_after_reuse() {
+        let mut arena = Arena::new();
+        let old = arena.insert(1u32);
+        arena.remove(old);
+        let new = arena.insert(2u32);
+        assert_eq!(new.index(), old.index());
+        assert_ne!(new.generation(), old.generation());
+        assert_eq!(arena.get(old), None);
+        assert_eq!(arena.get(new), Some(&2));
+    }
+
+    #[test]
+    fn test_arena_clear_invalidates_handles() {
+        let mut arena = Arena::new();
+        let a = arena.insert(1u32);
+        arena.clear();
+        assert_eq!(arena.get(a), None);
+        assert_eq!(arena.iter().count(), 0);
+        let b = arena.insert(2u32);
+        assert_eq!(arena.get(b), Some(&2));
+    }
+}
@@ -0,0 +1,289 @@
+//! Deduplicating cache of sampler states.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use mtl_foundation::UInteger;
+
+use super::{SamplerDescriptor, SamplerState};
+use crate::Device;
+use crate::enums::{
+    CompareFunction, SamplerAddressMode, SamplerBorderColor, SamplerMinMagFilter, SamplerMipFilter,
+    SamplerReductionMode,
+};
+
+// ============================================================================
+// SamplerKey
+// ============================================================================
+
+/// The parameters of a [`SamplerDescriptor`], used as a cache key.
+///
+/// Floating-point fields compare by bit pattern. The label is not part of
+/// the key.
+#[derive(Copy, Clone, Debug)]
+pub struct SamplerKey {
+    /// Filter used when the texture is minified.
+    pub min_filter: SamplerMinMagFilter,
+    /// Filter used when the texture is magnified.
+    pub mag_filter: SamplerMinMagFilter,
+    /// Filter used between mipmap levels.
+    pub mip_filter: SamplerMipFilter,
+    /// Address mode for the width coordinate.
+    pub s_address_mode: SamplerAddressMode,
+    /// Address mode for the height coordinate.
+    pub t_address_mode: SamplerAddressMode,
+    /// Address mode for the depth coordinate.
+    pub r_address_mode: SamplerAddressMode,
+    /// Color returned by [`SamplerAddressMode::CLAMP_TO_BORDER_COLOR`].
+    pub border_color: SamplerBorderColor,
+    /// Lowest mipmap level of detail.
+    pub lod_min_clamp: f32,
+    /// Highest mipmap level of detail.
+    pub lod_max_clamp: f32,
+    /// Bias added to the computed level of detail.
+    pub lod_bias: f32,
+    /// Whether the level of detail is averaged over a quad.
+    pub lod_average: bool,
+    /// Maximum anisotropic filtering ratio.
+    pub max_anisotropy: UInteger,
+    /// Comparison for `sample_compare`.
+    pub compare_function: CompareFunction,
+    /// How filtered texels are combined.
+    pub reduction_mode: SamplerReductionMode,
+    /// Whether coordinates are normalized to `[0, 1]`.
+    pub normalized_coordinates: bool,
+    /// Whether the sampler can be placed in an argument buffer.
+    pub support_argument_buffers: bool,
+}
+
+impl Default for SamplerKey {
+    /// Metal's defaults: nearest filtering without mipmaps, clamped to the
+    /// edge.
+    fn default() -> Self {
+        Self {
+            min_filter: SamplerMinMagFilter::NEAREST,
+            mag_filter: SamplerMinMagFilter::NEAREST,
+            mip_filter: SamplerMipFilter::NOT_MIPMAPPED,
+            s_address_mode: SamplerAddressMode::CLAMP_TO_EDGE,
+            t_address_mode: SamplerAddressMode::CLAMP_TO_EDGE,
+            r_address_mode: SamplerAddressMode::CLAMP_TO_EDGE,
+            border_color: SamplerBorderColor::TRANSPARENT_BLACK,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: f32::MAX,
+            lod_bias: 0.0,
+            lod_average: false,
+            max_anisotropy: 1,
+            compare_function: CompareFunction::NEVER,
+            reduction_mode: SamplerReductionMode::WEIGHTED_AVERAGE,
+            normalized_coordinates: true,
+            support_argument_buffers: false,
+        }
+    }
+}
+
+impl SamplerKey {
+    /// Trilinear filtering with `address_mode` on every axis.
+    pub fn linear(address_mode: SamplerAddressMode) -> Self {
+        Self {
+            min_filter: SamplerMinMagFilter::LINEAR,
+            mag_filter: SamplerMinMagFilter::LINEAR,
+            mip_filter: SamplerMipFilter::LINEAR,
+            ..Self::default()
+        }
+        .with_address_mode(address_mode)
+    }
+
+    /// Use `address_mode` for every axis.
+    pub fn with_address_mode(mut self, address_mode: SamplerAddressMode) -> Self {
+        self.s_address_mode = address_mode;
+        self.t_address_mode = address_mode;
+        self.r_address_mode = address_mode;
+        self
+    }
+
+    /// Read the parameters of `descriptor`.
+    pub fn from_descriptor(descriptor: &SamplerDescriptor) -> Self {
+        Self {
+            min_filter: descriptor.min_filter(),
+            mag_filter: descriptor.mag_filter(),
+            mip_filter: descriptor.mip_filter(),
+            s_address_mode: descriptor.s_address_mode(),
+            t_address_mode: descriptor.t_address_mode(),
+            r_address_mode: descriptor.r_address_mode(),
+            border_color: descriptor.border_color(),
+            lod_min_clamp: descriptor.lod_min_clamp(),
+            lod_max_clamp: descriptor.lod_max_clamp(),
+            lod_bias: descriptor.lod_bias(),
+            lod_average: descriptor.lod_average(),
+            max_anisotropy: descriptor.max_anisotropy(),
+            compare_function: descriptor.compare_function(),
+            reduction_mode: descriptor.reduction_mode(),
+            normalized_coordinates: descriptor.normalized_coordinates(),
+            support_argument_buffers: descriptor.support_argument_buffers(),
+        }
+    }
+
+    /// Create a descriptor with these parameters.
+    pub fn descriptor(&self) -> Option<SamplerDescriptor> {
+        let descriptor = SamplerDescriptor::new()?;
+        self.apply(&descriptor);
+        Some(descriptor)
+    }
+
+    /// Write these parameters to `descriptor`, leaving its label unchanged.
+    pub fn apply(&self, descriptor: &SamplerDescriptor) {
+        descriptor.set_min_filter(self.min_filter);
+        descriptor.set_mag_filter(self.mag_filter);
+        descriptor.set_mip_filter(self.mip_filter);
+        descriptor.set_s_address_mode(self.s_address_mode);
+        descriptor.set_t_address_mode(self.t_address_mode);
+        descriptor.set_r_address_mode(self.r_address_mode);
+        descriptor.set_border_color(self.border_color);
+        descriptor.set_lod_min_clamp(self.lod_min_clamp);
+        descriptor.set_lod_max_clamp(self.lod_max_clamp);
+        descriptor.set_lod_bias(self.lod_bias);
+        descriptor.set_lod_average(self.lod_average);
+        descriptor.set_max_anisotropy(self.max_anisotropy);
+        descriptor.set_compare_function(self.compare_function);
+        descriptor.set_reduction_mode(self.reduction_mode);
+        descriptor.set_normalized_coordinates(self.normalized_coordinates);
+        descriptor.set_support_argument_buffers(self.support_argument_buffers);
+    }
+
+    /// The fields as plain integers, for comparison and hashing.
+    fn bits(&self) -> [UInteger; 16] {
+        [
+            self.min_filter.0,
+            self.mag_filter.0,
+            self.mip_filter.0,
+            self.s_address_mode.0,
+            self.t_address_mode.0,
+            self.r_address_mode.0,
+            self.border_color.0,
+            self.lod_min_clamp.to_bits() as UInteger,
+            self.lod_max_clamp.to_bits() as UInteger,
+            self.lod_bias.to_bits() as UInteger,
+            self.lod_average as UInteger,
+            self.max_anisotropy,
+            self.compare_function.0,
+            self.reduction_mode.0,
+            self.normalized_coordinates as UInteger,
+            self.support_argument_buffers as UInteger,
+        ]
+    }
+}
+
+impl PartialEq for SamplerKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits() == other.bits()
+    }
+}
+
+impl Eq for SamplerKey {}
+
+impl Hash for SamplerKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits().hash(state);
+    }
+}
+
+// ============================================================================
+// SamplerCache
+// ============================================================================
+
+/// Returns one shared [`SamplerState`] per distinct set of parameters.
+///
+/// The cache is safe to share between threads.
+pub struct SamplerCache {
+    device: Device,
+    states: Mutex<HashMap<SamplerKey, SamplerState>>,
+}
+
+impl SamplerCache {
+    /// Create an empty cache for `device`.
+    pub fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the sampler for `key`, creating it on first use.
+    ///
+    /// Returns `None` if the sampler cannot be created.
+    pub fn get(&self, key: &SamplerKey) -> Option<SamplerState> {
+        let mut states = self.states.lock().unwrap();
+        if let Some(state) = states.get(key) {
+            return Some(state.clone());
+        }
+        let state = self.device.new_sampler_state(&key.descriptor()?)?;
+        states.insert(*key, state.clone());
+        Some(state)
+    }
+
+    /// Get the sampler matching the parameters of `descriptor`.
+    pub fn get_for_descriptor(&self, descriptor: &SamplerDescriptor) -> Option<SamplerState> {
+        self.get(&SamplerKey::from_descriptor(descriptor))
+    }
+
+    /// Get the number of cached samplers.
+    pub fn len(&self) -> usize {
+        self.states.lock().unwrap().len()
+    }
+
+    /// Check if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Release every cached sampler.
+    pub fn clear(&self) {
+        self.states.lock().unwrap().clear();
+    }
+}
+
+impl fmt::Debug for SamplerCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SamplerCache")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::system_default;
+
+    #[test]
+    fn test_sampler_key_round_trip() {
+        let key = SamplerKey {
+            max_anisotropy: 8,
+            lod_bias: -0.5,
+            ..SamplerKey::linear(SamplerAddressMode::REPEAT)
+        };
+        let descriptor = key.descriptor().unwrap();
+        assert_eq!(SamplerKey::from_descriptor(&descriptor), key);
+        assert_ne!(key, SamplerKey::default());
+    }
+
+    #[test]
+    fn test_sampler_cache() {
+        let device = system_default().expect("no Metal device");
+        let cache = SamplerCache::new(&device);
+
+        let key = SamplerKey::linear(SamplerAddressMode::CLAMP_TO_EDGE);
+        let a = cache.get(&key).unwrap();
+        let b = cache
+            .get_for_descriptor(&key.descriptor().unwrap())
+            .unwrap();
+        assert_eq!(a.as_raw(), b.as_raw());
+
+        cache.get(&SamplerKey::default()).unwrap();
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
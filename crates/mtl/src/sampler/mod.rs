@@ -4,6 +4,10 @@
 //!
 //! Sampler states define how textures are sampled in shaders.
 
+mod cache;
+
+pub use cache::{SamplerCache, SamplerKey};
+
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
@@ -143,6 +147,22 @@ impl SamplerDescriptor {
         }
     }
 
+    /// Create a descriptor for sampling shadow maps with
+    /// `sample_compare`.
+    ///
+    /// Uses linear filtering for hardware percentage-closer filtering,
+    /// clamps to the edge, and passes when the reference depth is less than
+    /// or equal to the stored depth.
+    pub fn shadow_comparison() -> Option<Self> {
+        let descriptor = Self::new()?;
+        descriptor.set_min_filter(SamplerMinMagFilter::LINEAR);
+        descriptor.set_mag_filter(SamplerMinMagFilter::LINEAR);
+        descriptor.set_s_address_mode(SamplerAddressMode::CLAMP_TO_EDGE);
+        descriptor.set_t_address_mode(SamplerAddressMode::CLAMP_TO_EDGE);
+        descriptor.set_compare_function(CompareFunction::LESS_EQUAL);
+        Some(descriptor)
+    }
+
     /// Create a SamplerDescriptor from a raw pointer.
     ///
     /// # Safety
@@ -0,0 +1,453 @@
+//! Synthetic GPU workloads for throughput and pacing tests.
+//!
+//! [`StressTest`] runs kernels that saturate one part of the GPU at a
+//! time: arithmetic ([`StressKind::Alu`]), memory bandwidth
+//! ([`StressKind::Bandwidth`]) or texture sampling
+//! ([`StressKind::TextureSample`]). A single [`run`](StressTest::run)
+//! measures the device's throughput for that kind of work; repeated runs
+//! with [`run_for`](StressTest::run_for) show throughput dropping as the
+//! device heats up; and [`encode`](StressTest::encode) adds a fixed load
+//! to an app's own command buffers to check how its frame pacing holds up
+//! on a busy GPU.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut stress = StressTest::new(&device)?;
+//! let result = stress.run(&StressConfig::alu())?;
+//! println!("{:.1} GFLOP/s", result.throughput() / 1e9);
+//!
+//! for result in stress.run_for(&StressConfig::bandwidth(), Duration::from_secs(60))? {
+//!     println!("{:.1} GB/s", result.throughput() / 1e9);
+//! }
+//! ```
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use mtl_foundation::UInteger;
+
+use crate::device::SourcePipelineError;
+use crate::enums::{CommandBufferStatus, PixelFormat, ResourceOptions, StorageMode, TextureUsage};
+use crate::types::Size;
+use crate::{
+    Buffer, CommandBuffer, CommandQueue, ComputeCommandEncoder, ComputePipelineState, Device,
+    Texture, TextureDescriptor,
+};
+
+/// Kernels for the stress workloads.
+///
+/// Every kernel takes `StressParams` at buffer 0 and writes one value per
+/// thread to buffer 1 so the work can't be optimized away.
+pub const STRESS_SOURCE: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct StressParams {
+    uint iterations;
+    uint element_count;
+};
+
+kernel void stress_alu(constant StressParams& params [[buffer(0)]],
+                       device float4* out [[buffer(1)]],
+                       uint gid [[thread_position_in_grid]]) {
+    float4 a = float4(gid) * 1e-6;
+    float4 b = a + 0.5;
+    float4 c = float4(0.999, 0.998, 0.997, 0.996);
+    for (uint i = 0; i < params.iterations; i++) {
+        a = fma(a, c, b);
+        b = fma(b, c, a);
+        a = fma(a, c, b);
+        b = fma(b, c, a);
+    }
+    out[gid] = a + b;
+}
+
+kernel void stress_bandwidth(constant StressParams& params [[buffer(0)]],
+                             device float4* dst [[buffer(1)]],
+                             const device float4* src [[buffer(2)]],
+                             uint gid [[thread_position_in_grid]],
+                             uint grid [[threads_per_grid]]) {
+    for (uint i = 0; i < params.iterations; i++) {
+        for (uint index = gid; index < params.element_count; index += grid) {
+            dst[index] = src[index] + float4(i);
+        }
+    }
+}
+
+kernel void stress_texture_sample(constant StressParams& params [[buffer(0)]],
+                                  device float4* out [[buffer(1)]],
+                                  texture2d<float> texture [[texture(0)]],
+                                  uint gid [[thread_position_in_grid]]) {
+    constexpr sampler linear(filter::linear, address::repeat);
+    float4 sum = 0.0;
+    float2 uv = float2(gid % 1021, gid / 1021) * (1.0 / 1021.0);
+    for (uint i = 0; i < params.iterations; i++) {
+        uv = fract(uv * 1.618 + float2(0.013, 0.029) + sum.xy * 1e-3);
+        sum += texture.sample(linear, uv);
+    }
+    out[gid] = sum;
+}
+"#;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct StressParams {
+    iterations: u32,
+    element_count: u32,
+}
+
+// ============================================================================
+// StressError
+// ============================================================================
+
+/// Error produced by a [`StressTest`].
+#[derive(Debug)]
+pub enum StressError {
+    /// A stress kernel failed to build.
+    Pipeline(SourcePipelineError),
+    /// A command queue, command buffer, encoder, buffer or texture could
+    /// not be created.
+    Allocation,
+    /// A stress command buffer failed on the GPU.
+    CommandBuffer(Option<mtl_foundation::Error>),
+}
+
+impl fmt::Display for StressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StressError::Pipeline(err) => write!(f, "failed to build stress kernel: {}", err),
+            StressError::Allocation => write!(f, "failed to allocate stress resources"),
+            StressError::CommandBuffer(Some(err)) => {
+                write!(f, "stress command buffer failed: {}", err)
+            }
+            StressError::CommandBuffer(None) => write!(f, "stress command buffer failed"),
+        }
+    }
+}
+
+impl std::error::Error for StressError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StressError::Pipeline(err) => Some(err),
+            StressError::CommandBuffer(Some(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// StressConfig
+// ============================================================================
+
+/// The part of the GPU a stress workload saturates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StressKind {
+    /// Fused multiply-adds on registers; measured in floating-point
+    /// operations.
+    Alu,
+    /// Copies between two device buffers; measured in bytes read and
+    /// written.
+    Bandwidth,
+    /// Bilinear samples from an RGBA8 texture; measured in samples.
+    TextureSample,
+}
+
+/// Size of a stress workload.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StressConfig {
+    /// The kind of work.
+    pub kind: StressKind,
+    /// Threads dispatched.
+    pub threads: UInteger,
+    /// Loop iterations per thread, or passes over the buffer for
+    /// [`StressKind::Bandwidth`].
+    pub iterations: u32,
+    /// Size in bytes of each buffer for [`StressKind::Bandwidth`], or the
+    /// width and height in pixels of the texture for
+    /// [`StressKind::TextureSample`].
+    pub working_set: UInteger,
+}
+
+impl StressConfig {
+    /// About 34 GFLOP of arithmetic.
+    pub fn alu() -> Self {
+        Self {
+            kind: StressKind::Alu,
+            threads: 1 << 20,
+            iterations: 1024,
+            working_set: 0,
+        }
+    }
+
+    /// About 4 GB copied between two 64 MiB buffers.
+    pub fn bandwidth() -> Self {
+        Self {
+            kind: StressKind::Bandwidth,
+            threads: 1 << 18,
+            iterations: 32,
+            working_set: 64 << 20,
+        }
+    }
+
+    /// About 268 million samples from a 2048 x 2048 texture.
+    pub fn texture_sample() -> Self {
+        Self {
+            kind: StressKind::TextureSample,
+            threads: 1 << 20,
+            iterations: 256,
+            working_set: 2048,
+        }
+    }
+
+    /// Set the number of threads.
+    pub fn with_threads(mut self, threads: UInteger) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Set the iterations per thread.
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Set the working set size.
+    pub fn with_working_set(mut self, working_set: UInteger) -> Self {
+        self.working_set = working_set;
+        self
+    }
+
+    /// Get the operations the workload performs: floating-point operations,
+    /// bytes or samples depending on the kind.
+    pub fn operations(&self) -> u64 {
+        let iterations = u64::from(self.iterations);
+        match self.kind {
+            // 4 fma per iteration on float4, 2 operations each.
+            StressKind::Alu => self.threads as u64 * iterations * 32,
+            StressKind::Bandwidth => {
+                let bytes = (self.working_set / 16 * 16) as u64;
+                bytes * 2 * iterations
+            }
+            StressKind::TextureSample => self.threads as u64 * iterations,
+        }
+    }
+}
+
+/// The measured result of one stress run.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StressResult {
+    /// The workload that ran.
+    pub config: StressConfig,
+    /// GPU time the command buffer took.
+    pub gpu_time: Duration,
+}
+
+impl StressResult {
+    /// Get the operations per second; see [`StressConfig::operations`].
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.gpu_time.as_secs_f64();
+        if seconds > 0.0 {
+            self.config.operations() as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+// ============================================================================
+// StressTest
+// ============================================================================
+
+/// Builds and runs stress workloads on a device.
+pub struct StressTest {
+    device: Device,
+    queue: CommandQueue,
+    alu: ComputePipelineState,
+    bandwidth: ComputePipelineState,
+    texture_sample: ComputePipelineState,
+    output: Option<Buffer>,
+    buffers: Option<(Buffer, Buffer)>,
+    texture: Option<Texture>,
+}
+
+impl StressTest {
+    /// Compile the stress kernels for `device`.
+    pub fn new(device: &Device) -> Result<Self, StressError> {
+        let pipeline = |name| {
+            device
+                .new_compute_pipeline_from_source(STRESS_SOURCE, name, None)
+                .map_err(StressError::Pipeline)
+        };
+        Ok(Self {
+            device: device.clone(),
+            queue: device.new_command_queue().ok_or(StressError::Allocation)?,
+            alu: pipeline("stress_alu")?,
+            bandwidth: pipeline("stress_bandwidth")?,
+            texture_sample: pipeline("stress_texture_sample")?,
+            output: None,
+            buffers: None,
+            texture: None,
+        })
+    }
+
+    /// Run `config` in its own command buffer and wait for the result.
+    pub fn run(&mut self, config: &StressConfig) -> Result<StressResult, StressError> {
+        let command_buffer = self.queue.command_buffer().ok_or(StressError::Allocation)?;
+        command_buffer.set_label("Stress test");
+        self.encode(&command_buffer, config)?;
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+
+        if command_buffer.status() == CommandBufferStatus::ERROR {
+            return Err(StressError::CommandBuffer(command_buffer.error()));
+        }
+        let seconds = command_buffer.gpu_end_time() - command_buffer.gpu_start_time();
+        Ok(StressResult {
+            config: *config,
+            gpu_time: Duration::from_secs_f64(seconds.max(0.0)),
+        })
+    }
+
+    /// Run `config` back to back until `duration` has passed, returning
+    /// each run's result in order.
+    pub fn run_for(
+        &mut self,
+        config: &StressConfig,
+        duration: Duration,
+    ) -> Result<Vec<StressResult>, StressError> {
+        let start = Instant::now();
+        let mut results = Vec::new();
+        while start.elapsed() < duration {
+            results.push(self.run(config)?);
+        }
+        Ok(results)
+    }
+
+    /// Encode `config` into `command_buffer` as a compute pass.
+    ///
+    /// Buffers and textures are allocated on first use and reused while
+    /// large enough.
+    pub fn encode(
+        &mut self,
+        command_buffer: &CommandBuffer,
+        config: &StressConfig,
+    ) -> Result<(), StressError> {
+        let element_count = config.working_set / 16;
+        let params = StressParams {
+            iterations: config.iterations,
+            element_count: element_count.min(u32::MAX as UInteger) as u32,
+        };
+        let output = self.output(config.threads * 16)?;
+        let encoder =
+            unsafe { ComputeCommandEncoder::from_raw(command_buffer.compute_command_encoder()) }
+                .ok_or(StressError::Allocation)?;
+        encoder.set_value(&params, 0);
+        encoder.set_buffer(&output, 0, 1);
+        let pipeline = match config.kind {
+            StressKind::Alu => &self.alu,
+            StressKind::Bandwidth => {
+                let (dst, src) = self.bandwidth_buffers(element_count * 16)?;
+                encoder.set_buffer(&dst, 0, 1);
+                encoder.set_buffer(&src, 0, 2);
+                &self.bandwidth
+            }
+            StressKind::TextureSample => {
+                let texture = self.texture(config.working_set)?;
+                encoder.set_texture(&texture, 0);
+                &self.texture_sample
+            }
+        };
+        encoder.set_compute_pipeline_state(pipeline);
+        let width = pipeline.thread_execution_width().max(1);
+        encoder.dispatch_threads(Size::new(config.threads, 1, 1), Size::new(width, 1, 1));
+        encoder.end_encoding();
+        Ok(())
+    }
+
+    fn output(&mut self, length: UInteger) -> Result<Buffer, StressError> {
+        if let Some(buffer) = &self.output
+            && buffer.length() >= length
+        {
+            return Ok(buffer.clone());
+        }
+        let buffer = self
+            .device
+            .new_buffer(length.max(16), ResourceOptions::STORAGE_MODE_PRIVATE)
+            .ok_or(StressError::Allocation)?;
+        self.output = Some(buffer.clone());
+        Ok(buffer)
+    }
+
+    fn bandwidth_buffers(&mut self, length: UInteger) -> Result<(Buffer, Buffer), StressError> {
+        if let Some((dst, src)) = &self.buffers
+            && dst.length() >= length
+        {
+            return Ok((dst.clone(), src.clone()));
+        }
+        let new_buffer = || {
+            self.device
+                .new_buffer(length.max(16), ResourceOptions::STORAGE_MODE_PRIVATE)
+                .ok_or(StressError::Allocation)
+        };
+        let buffers = (new_buffer()?, new_buffer()?);
+        self.buffers = Some(buffers.clone());
+        Ok(buffers)
+    }
+
+    fn texture(&mut self, size: UInteger) -> Result<Texture, StressError> {
+        let size = size.max(1);
+        if let Some(texture) = &self.texture
+            && texture.width() == size
+        {
+            return Ok(texture.clone());
+        }
+        let descriptor =
+            TextureDescriptor::texture_2d_descriptor(PixelFormat::RGBA8_UNORM, size, size, false)
+                .ok_or(StressError::Allocation)?;
+        descriptor.set_storage_mode(StorageMode::PRIVATE);
+        descriptor.set_usage(TextureUsage::SHADER_READ);
+        let texture = self
+            .device
+            .new_texture_with_descriptor(&descriptor)
+            .map_err(|_| StressError::Allocation)?;
+        self.texture = Some(texture.clone());
+        Ok(texture)
+    }
+}
+
+impl fmt::Debug for StressTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StressTest")
+            .field("device", &self.device.name())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stress_runs() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let mut stress = StressTest::new(&device).unwrap();
+        for config in [
+            StressConfig::alu(),
+            StressConfig::bandwidth().with_working_set(1 << 20),
+            StressConfig::texture_sample().with_working_set(256),
+        ] {
+            let config = config.with_threads(4096).with_iterations(4);
+            let result = stress.run(&config).unwrap();
+            assert_eq!(result.config, config);
+            assert!(result.throughput() >= 0.0);
+        }
+        assert_eq!(
+            StressConfig::alu()
+                .with_threads(10)
+                .with_iterations(2)
+                .operations(),
+            640
+        );
+    }
+}
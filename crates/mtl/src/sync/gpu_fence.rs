@@ -0,0 +1,210 @@
+//! CPU/GPU synchronization on a shared event's timeline.
+//!
+//! [`GpuFence`] wraps a [`SharedEvent`] whose signaled value only grows.
+//! Command buffers signal values on it as they complete, and the CPU waits
+//! for a value either by blocking with [`GpuFence::wait_cpu`] or by
+//! awaiting the future returned by [`GpuFence::wait`], which is woken from
+//! a [`SharedEventListener`] without tying up a thread.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let fence = GpuFence::new(&device).unwrap();
+//!
+//! // Frame pacing: don't encode frame N until frame N - 3 has completed.
+//! fence.wait_cpu(frame.saturating_sub(3), Duration::from_secs(1));
+//! // ... encode ...
+//! fence.signal_on_gpu(&command_buffer, frame);
+//! command_buffer.commit();
+//!
+//! // Readback without blocking an executor thread:
+//! let value = fence.signal_next(&command_buffer);
+//! command_buffer.commit();
+//! fence.wait(value).await;
+//! ```
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+#[cfg(feature = "blocks")]
+use std::future::Future;
+#[cfg(feature = "blocks")]
+use std::pin::Pin;
+#[cfg(feature = "blocks")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "blocks")]
+use std::task::{Context, Poll, Waker};
+
+use super::SharedEvent;
+#[cfg(feature = "blocks")]
+use super::SharedEventListener;
+use crate::{CommandBuffer, Device};
+
+// ============================================================================
+// GpuFence
+// ============================================================================
+
+/// A monotonically increasing timeline shared by the CPU and GPU.
+pub struct GpuFence {
+    event: SharedEvent,
+    #[cfg(feature = "blocks")]
+    listener: SharedEventListener,
+    next_value: AtomicU64,
+}
+
+impl GpuFence {
+    /// Create a fence with a new shared event on `device`.
+    pub fn new(device: &Device) -> Option<Self> {
+        Self::from_event(device.new_shared_event()?)
+    }
+
+    /// Create a fence around an existing shared event.
+    ///
+    /// [`signal_next`](Self::signal_next) continues from the event's
+    /// current value.
+    pub fn from_event(event: SharedEvent) -> Option<Self> {
+        let next_value = AtomicU64::new(event.signaled_value() + 1);
+        Some(Self {
+            event,
+            #[cfg(feature = "blocks")]
+            listener: SharedEventListener::new()?,
+            next_value,
+        })
+    }
+
+    /// Get the underlying shared event.
+    pub fn event(&self) -> &SharedEvent {
+        &self.event
+    }
+
+    /// Get the highest value signaled so far.
+    pub fn completed_value(&self) -> u64 {
+        self.event.signaled_value()
+    }
+
+    /// Check if the fence has reached `value`.
+    pub fn is_signaled(&self, value: u64) -> bool {
+        self.event.signaled_value() >= value
+    }
+
+    /// Encode a signal of `value` when the GPU reaches this point in
+    /// `command_buffer`.
+    pub fn signal_on_gpu(&self, command_buffer: &CommandBuffer, value: u64) {
+        command_buffer.encode_signal_event(self.event.as_event(), value);
+        self.next_value.fetch_max(value + 1, Ordering::Relaxed);
+    }
+
+    /// Encode a signal of the next unused value and return it.
+    pub fn signal_next(&self, command_buffer: &CommandBuffer) -> u64 {
+        let value = self.next_value.fetch_add(1, Ordering::Relaxed);
+        command_buffer.encode_signal_event(self.event.as_event(), value);
+        value
+    }
+
+    /// Encode a wait in `command_buffer` until the fence reaches `value`.
+    pub fn wait_on_gpu(&self, command_buffer: &CommandBuffer, value: u64) {
+        command_buffer.encode_wait_for_event(self.event.as_event(), value);
+    }
+
+    /// Signal `value` from the CPU, releasing GPU work waiting on it.
+    pub fn signal_cpu(&self, value: u64) {
+        self.event.set_signaled_value(value);
+        self.next_value.fetch_max(value + 1, Ordering::Relaxed);
+    }
+
+    /// Block until the fence reaches `value`.
+    ///
+    /// Returns `false` if `timeout` elapsed first.
+    pub fn wait_cpu(&self, value: u64, timeout: Duration) -> bool {
+        if self.is_signaled(value) {
+            return true;
+        }
+        let timeout_ms = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+        self.event.wait_until_signaled_value(value, timeout_ms)
+    }
+
+    /// Get a future that resolves once the fence reaches `value`.
+    ///
+    /// The listener is registered immediately, so the future may be
+    /// awaited later without missing the signal.
+    #[cfg(feature = "blocks")]
+    pub fn wait(&self, value: u64) -> GpuFenceWait {
+        let state = Arc::new(Mutex::new(WaitState::default()));
+        if self.is_signaled(value) {
+            state.lock().unwrap().done = true;
+        } else {
+            let notified = Arc::clone(&state);
+            self.event.notify(&self.listener, value, move |_| {
+                let waker = {
+                    let mut state = notified.lock().unwrap();
+                    state.done = true;
+                    state.waker.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            });
+        }
+        GpuFenceWait { value, state }
+    }
+}
+
+impl fmt::Debug for GpuFence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpuFence")
+            .field("completed_value", &self.completed_value())
+            .field("next_value", &self.next_value.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+// ============================================================================
+// GpuFenceWait
+// ============================================================================
+
+#[cfg(feature = "blocks")]
+#[derive(Default)]
+struct WaitState {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+/// Future returned by [`GpuFence::wait`].
+#[cfg(feature = "blocks")]
+pub struct GpuFenceWait {
+    value: u64,
+    state: Arc<Mutex<WaitState>>,
+}
+
+#[cfg(feature = "blocks")]
+impl GpuFenceWait {
+    /// Get the value being waited for.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+#[cfg(feature = "blocks")]
+impl Future for GpuFenceWait {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.done {
+            return Poll::Ready(());
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "blocks")]
+impl fmt::Debug for GpuFenceWait {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpuFenceWait")
+            .field("value", &self.value)
+            .field("done", &self.state.lock().unwrap().done)
+            .finish()
+    }
+}
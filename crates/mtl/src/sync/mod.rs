@@ -2,6 +2,12 @@
 //!
 //! Corresponds to `Metal/MTLEvent.hpp` and `Metal/MTLFence.hpp`.
 
+mod gpu_fence;
+
+pub use gpu_fence::GpuFence;
+#[cfg(feature = "blocks")]
+pub use gpu_fence::GpuFenceWait;
+
 use std::ffi::c_void;
 use std::ptr::NonNull;
 
@@ -198,6 +204,33 @@ impl SharedEvent {
         }
     }
 
+    /// Block the calling thread until the event reaches `value`.
+    ///
+    /// Returns `false` if `timeout_ms` elapsed first.
+    ///
+    /// C++ equivalent: `bool waitUntilSignaledValue(uint64_t, uint64_t)`
+    pub fn wait_until_signaled_value(&self, value: u64, timeout_ms: u64) -> bool {
+        unsafe {
+            mtl_sys::msg_send_2::<bool, u64, u64>(
+                self.as_ptr(),
+                sel!(waitUntilSignaledValue: timeoutMS:),
+                value,
+                timeout_ms,
+            )
+        }
+    }
+
+    /// View this shared event as an [`Event`].
+    ///
+    /// `MTLSharedEvent` refines `MTLEvent`, so a shared event can be passed
+    /// anywhere an event is expected.
+    #[inline]
+    pub fn as_event(&self) -> &Event {
+        // SAFETY: both types are transparent wrappers around the same object
+        // pointer, and every shared event is an event.
+        unsafe { &*(self as *const SharedEvent as *const Event) }
+    }
+
     /// Create a handle for sharing across processes.
     ///
     /// C++ equivalent: `SharedEventHandle* newSharedEventHandle()`
@@ -215,6 +248,7 @@ impl SharedEvent {
     /// # Safety
     ///
     /// The listener pointer must be valid.
+    #[cfg(feature = "blocks")]
     pub unsafe fn notify_listener<F>(&self, listener: *const c_void, value: u64, block: F)
     where
         F: Fn(*mut c_void, u64) + Send + 'static,
@@ -231,6 +265,32 @@ impl SharedEvent {
         }
         std::mem::forget(block);
     }
+
+    /// Call `handler` on `listener`'s dispatch queue once the event reaches
+    /// `value`.
+    ///
+    /// The handler receives the signaled value and runs at most once; it is
+    /// dropped, along with everything it captured, as soon as it returns.
+    /// If the event already holds `value`, the handler is scheduled
+    /// immediately.
+    ///
+    /// C++ equivalent: `void notifyListener(SharedEventListener*, uint64_t, SharedEventNotificationHandlerFunction&)`
+    #[cfg(feature = "blocks")]
+    pub fn notify<F>(&self, listener: &SharedEventListener, value: u64, handler: F)
+    where
+        F: FnOnce(u64) + Send + 'static,
+    {
+        let handler = std::sync::Mutex::new(Some(handler));
+        // SAFETY: the listener is a live `MTLSharedEventListener`.
+        unsafe {
+            self.notify_listener(listener.as_ptr(), value, move |_, signaled| {
+                let handler = handler.lock().ok().and_then(|mut handler| handler.take());
+                if let Some(handler) = handler {
+                    handler(signaled);
+                }
+            });
+        }
+    }
 }
 
 impl Clone for SharedEvent {
@@ -375,11 +435,42 @@ impl SharedEventListener {
         }
     }
 
+    /// Initialize an allocated listener that runs notifications on
+    /// `dispatch_queue`.
+    ///
+    /// C++ equivalent: `SharedEventListener* init(const dispatch_queue_t dispatchQueue)`
+    ///
+    /// # Safety
+    ///
+    /// `dispatch_queue` must be a valid `dispatch_queue_t`. The listener
+    /// retains it.
+    pub unsafe fn init_with_dispatch_queue(&self, dispatch_queue: *mut c_void) -> Option<Self> {
+        unsafe {
+            let ptr: *mut c_void =
+                msg_send_1(self.as_ptr(), sel!(initWithDispatchQueue:), dispatch_queue);
+            Self::from_raw(ptr)
+        }
+    }
+
     /// Create a new shared event listener with the default dispatch queue.
     pub fn new() -> Option<Self> {
         Self::alloc()?.init()
     }
 
+    /// Create a new shared event listener that runs notifications on
+    /// `dispatch_queue`.
+    ///
+    /// Use a serial queue to receive notifications in order, or the queue
+    /// of the thread that consumes them to avoid a hop.
+    ///
+    /// # Safety
+    ///
+    /// `dispatch_queue` must be a valid `dispatch_queue_t`. The listener
+    /// retains it.
+    pub unsafe fn with_dispatch_queue(dispatch_queue: *mut c_void) -> Option<Self> {
+        unsafe { Self::alloc()?.init_with_dispatch_queue(dispatch_queue) }
+    }
+
     /// Get the shared (global) listener singleton.
     ///
     /// C++ equivalent: `static SharedEventListener* sharedListener()`
@@ -594,4 +685,31 @@ mod tests {
             std::mem::size_of::<*mut c_void>()
         );
     }
+
+    #[cfg(feature = "blocks")]
+    #[test]
+    fn test_notify_on_listener_queue() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let device = crate::device::system_default().expect("no Metal device");
+        let event = device.new_shared_event().expect("no shared event");
+        let default_listener = SharedEventListener::new().expect("no listener");
+        let listener = unsafe {
+            SharedEventListener::with_dispatch_queue(default_listener.dispatch_queue_raw())
+        }
+        .expect("no listener");
+        assert_eq!(
+            listener.dispatch_queue_raw(),
+            default_listener.dispatch_queue_raw()
+        );
+
+        let (sender, receiver) = mpsc::channel();
+        event.notify(&listener, 2, move |value| sender.send(value).unwrap());
+        event.set_signaled_value(2);
+        let value = receiver
+            .recv_timeout(Duration::from_secs(5))
+            .expect("notification not delivered");
+        assert_eq!(value, 2);
+    }
 }
@@ -99,6 +99,13 @@ impl TensorExtents {
             )
         }
     }
+
+    /// Collect all extents, dimension 0 first.
+    pub fn to_vec(&self) -> Vec<Integer> {
+        (0..self.rank())
+            .map(|i| self.extent_at_dimension_index(i))
+            .collect()
+    }
 }
 
 impl Clone for TensorExtents {
@@ -154,6 +161,19 @@ impl TensorDescriptor {
         }
     }
 
+    /// Create a descriptor for a tensor of the given shape and data type.
+    ///
+    /// `shape` is in row-major order, outermost dimension first, as in
+    /// ndarray. Metal orders extents innermost first, so the shape is
+    /// reversed when stored.
+    pub fn for_shape(shape: &[usize], data_type: TensorDataType) -> Option<Self> {
+        let descriptor = Self::new()?;
+        let extents: Vec<Integer> = shape.iter().rev().map(|&d| d as Integer).collect();
+        descriptor.set_dimensions(&TensorExtents::with_values(&extents)?);
+        descriptor.set_data_type(data_type);
+        Some(descriptor)
+    }
+
     /// Create from a raw pointer.
     ///
     /// # Safety
@@ -502,6 +522,238 @@ impl Tensor {
     }
 }
 
+// ============================================================================
+// Tensor Data Access
+// ============================================================================
+
+/// A Rust type that can be copied in and out of a tensor element-wise.
+///
+/// # Safety
+///
+/// Implementors must be plain data with the same size and layout as the
+/// tensor elements they are compatible with, valid for any bit pattern.
+pub unsafe trait TensorElement: Copy {
+    /// The data type this element maps to.
+    const DATA_TYPE: TensorDataType;
+
+    /// Check if this type can represent elements of `data_type`.
+    fn is_compatible(data_type: TensorDataType) -> bool {
+        data_type == Self::DATA_TYPE
+    }
+}
+
+unsafe impl TensorElement for f32 {
+    const DATA_TYPE: TensorDataType = TensorDataType::FLOAT32;
+}
+
+unsafe impl TensorElement for i8 {
+    const DATA_TYPE: TensorDataType = TensorDataType::INT8;
+}
+
+unsafe impl TensorElement for u8 {
+    const DATA_TYPE: TensorDataType = TensorDataType::UINT8;
+}
+
+unsafe impl TensorElement for i16 {
+    const DATA_TYPE: TensorDataType = TensorDataType::INT16;
+}
+
+unsafe impl TensorElement for u16 {
+    const DATA_TYPE: TensorDataType = TensorDataType::UINT16;
+
+    /// Also accepts `FLOAT16` and `BFLOAT16` as raw bit patterns.
+    fn is_compatible(data_type: TensorDataType) -> bool {
+        data_type == TensorDataType::UINT16
+            || data_type == TensorDataType::FLOAT16
+            || data_type == TensorDataType::BFLOAT16
+    }
+}
+
+unsafe impl TensorElement for i32 {
+    const DATA_TYPE: TensorDataType = TensorDataType::INT32;
+}
+
+unsafe impl TensorElement for u32 {
+    const DATA_TYPE: TensorDataType = TensorDataType::UINT32;
+}
+
+/// Error produced when copying data in or out of a tensor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TensorDataError {
+    /// The element type doesn't match the tensor's data type.
+    DataTypeMismatch(TensorDataType),
+    /// The number of strides doesn't match the tensor's rank.
+    RankMismatch { expected: usize, found: usize },
+    /// The slice is too short for the tensor's shape and strides.
+    LengthMismatch { expected: usize, found: usize },
+    /// The tensor's extents could not be read or created.
+    Extents,
+}
+
+impl std::fmt::Display for TensorDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TensorDataError::DataTypeMismatch(data_type) => {
+                write!(
+                    f,
+                    "element type does not match tensor data type {:?}",
+                    data_type
+                )
+            }
+            TensorDataError::RankMismatch { expected, found } => {
+                write!(f, "expected {} strides, found {}", expected, found)
+            }
+            TensorDataError::LengthMismatch { expected, found } => {
+                write!(
+                    f,
+                    "expected at least {} elements, found {}",
+                    expected, found
+                )
+            }
+            TensorDataError::Extents => write!(f, "failed to access tensor extents"),
+        }
+    }
+}
+
+impl std::error::Error for TensorDataError {}
+
+/// Compute row-major contiguous strides, in elements, for `shape`.
+pub(crate) fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
+
+/// Compute the number of elements a strided layout spans.
+pub(crate) fn strided_len(shape: &[usize], strides: &[usize]) -> usize {
+    if shape.contains(&0) {
+        return 0;
+    }
+    1 + shape
+        .iter()
+        .zip(strides)
+        .map(|(&dim, &stride)| (dim - 1) * stride)
+        .sum::<usize>()
+}
+
+impl Tensor {
+    /// Get the shape in row-major order, outermost dimension first.
+    pub fn shape(&self) -> Vec<usize> {
+        self.dimensions()
+            .map(|d| d.to_vec().iter().rev().map(|&e| e as usize).collect())
+            .unwrap_or_default()
+    }
+
+    /// Copy contiguous row-major data into the whole tensor.
+    ///
+    /// The tensor must be CPU-accessible.
+    pub fn write_from_slice<T: TensorElement>(&self, data: &[T]) -> Result<(), TensorDataError> {
+        let strides = contiguous_strides(&self.shape());
+        self.write_from_slice_with_strides(data, &strides)
+    }
+
+    /// Copy strided data into the whole tensor.
+    ///
+    /// `strides` are in elements and row-major order, matching
+    /// [`shape`](Self::shape).
+    pub fn write_from_slice_with_strides<T: TensorElement>(
+        &self,
+        data: &[T],
+        strides: &[usize],
+    ) -> Result<(), TensorDataError> {
+        let (origin, dimensions, metal_strides) = self.prepare_access::<T>(data.len(), strides)?;
+        self.replace_slice_origin(
+            &origin,
+            &dimensions,
+            data.as_ptr() as *const c_void,
+            &metal_strides,
+        );
+        Ok(())
+    }
+
+    /// Copy the whole tensor out as contiguous row-major data.
+    ///
+    /// The tensor must be CPU-accessible.
+    pub fn read_to_vec<T: TensorElement>(&self) -> Result<Vec<T>, TensorDataError> {
+        let shape = self.shape();
+        let strides = contiguous_strides(&shape);
+        let len = strided_len(&shape, &strides);
+        let mut data = Vec::with_capacity(len);
+        self.read_with_strides(&mut data.spare_capacity_mut()[..len], &strides)?;
+        // SAFETY: `read_with_strides` filled every element of the contiguous
+        // layout, and `TensorElement` types are valid for any bit pattern.
+        unsafe { data.set_len(len) };
+        Ok(data)
+    }
+
+    /// Copy the whole tensor into `data` using the given row-major strides.
+    ///
+    /// Elements of `data` not covered by the strided layout are left as-is.
+    pub fn read_into_slice_with_strides<T: TensorElement>(
+        &self,
+        data: &mut [T],
+        strides: &[usize],
+    ) -> Result<(), TensorDataError> {
+        // SAFETY: `MaybeUninit<T>` has the layout of `T`, and the tensor only
+        // writes initialized elements into the slice.
+        let data = unsafe { &mut *(data as *mut [T] as *mut [std::mem::MaybeUninit<T>]) };
+        self.read_with_strides(data, strides)
+    }
+
+    /// Copy the whole tensor into possibly uninitialized `data` using the
+    /// given row-major strides.
+    fn read_with_strides<T: TensorElement>(
+        &self,
+        data: &mut [std::mem::MaybeUninit<T>],
+        strides: &[usize],
+    ) -> Result<(), TensorDataError> {
+        let (origin, dimensions, metal_strides) = self.prepare_access::<T>(data.len(), strides)?;
+        self.get_bytes(
+            data.as_mut_ptr() as *mut c_void,
+            &metal_strides,
+            &origin,
+            &dimensions,
+        );
+        Ok(())
+    }
+
+    /// Validate an element-wise copy and build the extents Metal expects.
+    fn prepare_access<T: TensorElement>(
+        &self,
+        len: usize,
+        strides: &[usize],
+    ) -> Result<(TensorExtents, TensorExtents, TensorExtents), TensorDataError> {
+        let data_type = self.data_type();
+        if !T::is_compatible(data_type) {
+            return Err(TensorDataError::DataTypeMismatch(data_type));
+        }
+        let dimensions = self.dimensions().ok_or(TensorDataError::Extents)?;
+        let shape = self.shape();
+        if strides.len() != shape.len() {
+            return Err(TensorDataError::RankMismatch {
+                expected: shape.len(),
+                found: strides.len(),
+            });
+        }
+        let required = strided_len(&shape, strides);
+        if len < required {
+            return Err(TensorDataError::LengthMismatch {
+                expected: required,
+                found: len,
+            });
+        }
+
+        let origin =
+            TensorExtents::with_values(&vec![0; shape.len()]).ok_or(TensorDataError::Extents)?;
+        let reversed: Vec<Integer> = strides.iter().rev().map(|&s| s as Integer).collect();
+        let metal_strides =
+            TensorExtents::with_values(&reversed).ok_or(TensorDataError::Extents)?;
+        Ok((origin, dimensions, metal_strides))
+    }
+}
+
 impl Clone for Tensor {
     fn clone(&self) -> Self {
         unsafe {
@@ -533,6 +785,29 @@ unsafe impl Sync for Tensor {}
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_contiguous_strides() {
+        assert_eq!(contiguous_strides(&[2, 3, 4]), vec![12, 4, 1]);
+        assert_eq!(contiguous_strides(&[5]), vec![1]);
+        assert!(contiguous_strides(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_strided_len() {
+        assert_eq!(strided_len(&[2, 3, 4], &[12, 4, 1]), 24);
+        // Rows padded to 8 elements.
+        assert_eq!(strided_len(&[2, 4], &[8, 1]), 12);
+        assert_eq!(strided_len(&[0, 4], &[4, 1]), 0);
+    }
+
+    #[test]
+    fn test_tensor_element_compatibility() {
+        assert!(f32::is_compatible(TensorDataType::FLOAT32));
+        assert!(!f32::is_compatible(TensorDataType::INT32));
+        assert!(u16::is_compatible(TensorDataType::FLOAT16));
+        assert!(u16::is_compatible(TensorDataType::BFLOAT16));
+    }
+
     #[test]
     fn test_tensor_extents_creation() {
         // TensorExtents may not be available on all systems
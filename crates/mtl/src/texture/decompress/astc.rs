@@ -0,0 +1,786 @@
+//! ASTC LDR block decoder.
+//!
+//! Follows the decoding procedure of the Khronos Data Format
+//! Specification. Blocks using HDR endpoint modes, and other blocks that
+//! can't be decoded to LDR colors, decode to the error color (opaque
+//! magenta) as on hardware.
+
+const ERROR_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+/// An integer sequence encoding: values with `bits` low bits and an
+/// optional trit or quint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Range {
+    trits: bool,
+    quints: bool,
+    bits: u32,
+}
+
+const fn range(trits: bool, quints: bool, bits: u32) -> Range {
+    Range {
+        trits,
+        quints,
+        bits,
+    }
+}
+
+impl Range {
+    fn bit_count(self, count: u32) -> u32 {
+        let mut total = count * self.bits;
+        if self.trits {
+            total += (8 * count).div_ceil(5);
+        }
+        if self.quints {
+            total += (7 * count).div_ceil(3);
+        }
+        total
+    }
+}
+
+/// Weight ranges indexed by `(high precision << 3) | R`.
+const WEIGHT_RANGES: [Option<Range>; 16] = [
+    None,
+    None,
+    Some(range(false, false, 1)),
+    Some(range(true, false, 0)),
+    Some(range(false, false, 2)),
+    Some(range(false, true, 0)),
+    Some(range(true, false, 1)),
+    Some(range(false, false, 3)),
+    None,
+    None,
+    Some(range(false, true, 1)),
+    Some(range(true, false, 2)),
+    Some(range(false, false, 4)),
+    Some(range(false, true, 2)),
+    Some(range(true, false, 3)),
+    Some(range(false, false, 5)),
+];
+
+/// Color endpoint ranges with at least six levels, largest first.
+const COLOR_RANGES: [Range; 17] = [
+    range(false, false, 8),
+    range(true, false, 6),
+    range(false, true, 5),
+    range(false, false, 7),
+    range(true, false, 5),
+    range(false, true, 4),
+    range(false, false, 6),
+    range(true, false, 4),
+    range(false, true, 3),
+    range(false, false, 5),
+    range(true, false, 3),
+    range(false, true, 2),
+    range(false, false, 4),
+    range(true, false, 2),
+    range(false, true, 1),
+    range(false, false, 3),
+    range(true, false, 1),
+];
+
+/// Reads bits of a block, least significant first.
+struct BitReader {
+    bits: u128,
+    position: u32,
+}
+
+impl BitReader {
+    fn new(bits: u128, position: u32) -> Self {
+        Self { bits, position }
+    }
+
+    fn read(&mut self, count: u32) -> u32 {
+        let value = if count == 0 || self.position >= 128 {
+            0
+        } else {
+            ((self.bits >> self.position) & ((1u128 << count) - 1)) as u32
+        };
+        self.position += count;
+        value
+    }
+}
+
+fn field(bits: u128, start: u32, count: u32) -> u32 {
+    BitReader::new(bits, start).read(count)
+}
+
+fn bit(value: u32, index: u32) -> u32 {
+    (value >> index) & 1
+}
+
+/// Decode `count` values encoded with `range` starting at `position`.
+fn decode_ise(bits: u128, position: u32, count: usize, range: Range) -> Vec<(u32, u32)> {
+    let mut reader = BitReader::new(bits, position);
+    let mut values = Vec::with_capacity(count);
+    let low = |reader: &mut BitReader| reader.read(range.bits);
+
+    while values.len() < count {
+        if range.trits {
+            let mut m = [0; 5];
+            m[0] = low(&mut reader);
+            let mut t = reader.read(2);
+            m[1] = low(&mut reader);
+            t |= reader.read(2) << 2;
+            m[2] = low(&mut reader);
+            t |= reader.read(1) << 4;
+            m[3] = low(&mut reader);
+            t |= reader.read(2) << 5;
+            m[4] = low(&mut reader);
+            t |= reader.read(1) << 7;
+            for (m, trit) in m.into_iter().zip(decode_trits(t)) {
+                values.push((m, trit));
+            }
+        } else if range.quints {
+            let mut m = [0; 3];
+            m[0] = low(&mut reader);
+            let mut q = reader.read(3);
+            m[1] = low(&mut reader);
+            q |= reader.read(2) << 3;
+            m[2] = low(&mut reader);
+            q |= reader.read(2) << 5;
+            for (m, quint) in m.into_iter().zip(decode_quints(q)) {
+                values.push((m, quint));
+            }
+        } else {
+            values.push((low(&mut reader), 0));
+        }
+    }
+    values.truncate(count);
+    values
+}
+
+fn decode_trits(t: u32) -> [u32; 5] {
+    let (c, t4, t3);
+    if (t >> 2) & 7 == 7 {
+        c = ((t >> 5) << 2) | (t & 3);
+        t4 = 2;
+        t3 = 2;
+    } else {
+        c = t & 0x1f;
+        if (t >> 5) & 3 == 3 {
+            t4 = 2;
+            t3 = bit(t, 7);
+        } else {
+            t4 = bit(t, 7);
+            t3 = (t >> 5) & 3;
+        }
+    }
+    let (t2, t1, t0);
+    if c & 3 == 3 {
+        t2 = 2;
+        t1 = bit(c, 4);
+        t0 = (bit(c, 3) << 1) | (bit(c, 2) & !bit(c, 3) & 1);
+    } else if (c >> 2) & 3 == 3 {
+        t2 = 2;
+        t1 = 2;
+        t0 = c & 3;
+    } else {
+        t2 = bit(c, 4);
+        t1 = (c >> 2) & 3;
+        t0 = (bit(c, 1) << 1) | (bit(c, 0) & !bit(c, 1) & 1);
+    }
+    [t0, t1, t2, t3, t4]
+}
+
+fn decode_quints(q: u32) -> [u32; 3] {
+    if (q >> 1) & 3 == 3 && (q >> 5) & 3 == 0 {
+        let q2 =
+            (bit(q, 0) << 2) | ((bit(q, 4) & !bit(q, 0) & 1) << 1) | (bit(q, 3) & !bit(q, 0) & 1);
+        return [4, 4, q2];
+    }
+    let (q2, c);
+    if (q >> 1) & 3 == 3 {
+        q2 = 4;
+        c = (((q >> 3) & 3) << 3) | ((!(q >> 5) & 3) << 1) | bit(q, 0);
+    } else {
+        q2 = (q >> 5) & 3;
+        c = q & 0x1f;
+    }
+    let (q1, q0) = if c & 7 == 5 {
+        (4, (c >> 3) & 3)
+    } else {
+        ((c >> 3) & 3, c & 7)
+    };
+    [q0, q1, q2]
+}
+
+/// Unquantize a color endpoint value to `0..=255`.
+fn unquantize_color(range: Range, (m, d): (u32, u32)) -> u8 {
+    if !range.trits && !range.quints {
+        // Replicate the bits to fill eight
+        let mut value = m << (8 - range.bits);
+        let mut shift = range.bits;
+        while shift < 8 {
+            value |= value >> shift;
+            shift *= 2;
+        }
+        return value as u8;
+    }
+
+    let a = if m & 1 != 0 { 0x1ff } else { 0 };
+    let b = bit(m, 1);
+    let (pattern, multiplier) = match (range.trits, range.bits) {
+        (true, 1) => (0, 204),
+        (true, 2) => ((b << 8) | (b << 4) | (b << 2) | (b << 1), 93),
+        (true, 3) => {
+            let cb = m >> 1 & 3;
+            ((cb << 7) | (cb << 2) | cb, 44)
+        }
+        (true, 4) => {
+            let dcb = m >> 1 & 7;
+            ((dcb << 6) | dcb, 22)
+        }
+        (true, 5) => {
+            let edcb = m >> 1 & 0xf;
+            ((edcb << 5) | (edcb >> 2), 11)
+        }
+        (true, _) => {
+            let fedcb = m >> 1 & 0x1f;
+            ((fedcb << 4) | (fedcb >> 4), 5)
+        }
+        (false, 1) => (0, 113),
+        (false, 2) => ((b << 8) | (b << 3) | (b << 2), 54),
+        (false, 3) => {
+            let cb = m >> 1 & 3;
+            ((cb << 7) | (cb << 1) | (cb >> 1), 26)
+        }
+        (false, 4) => {
+            let dcb = m >> 1 & 7;
+            ((dcb << 6) | (dcb >> 1), 13)
+        }
+        (false, _) => {
+            let edcb = m >> 1 & 0xf;
+            ((edcb << 5) | (edcb >> 3), 6)
+        }
+    };
+    let t = (d * multiplier + pattern) ^ a;
+    ((a & 0x80) | (t >> 2)) as u8
+}
+
+/// Unquantize a weight to `0..=64`.
+fn unquantize_weight(range: Range, (m, d): (u32, u32)) -> u32 {
+    let value = if !range.trits && !range.quints {
+        let mut value = m << (6 - range.bits);
+        let mut shift = range.bits;
+        while shift < 6 {
+            value |= value >> shift;
+            shift *= 2;
+        }
+        value
+    } else if range.bits == 0 {
+        if range.trits {
+            [0, 32, 63][d as usize]
+        } else {
+            [0, 16, 32, 47, 63][d as usize]
+        }
+    } else {
+        let a = if m & 1 != 0 { 0x7f } else { 0 };
+        let b = bit(m, 1);
+        let (pattern, multiplier) = match (range.trits, range.bits) {
+            (true, 1) => (0, 50),
+            (true, 2) => ((b << 6) | (b << 2) | b, 23),
+            (true, _) => {
+                let cb = m >> 1 & 3;
+                ((cb << 5) | cb, 11)
+            }
+            (false, 1) => (0, 28),
+            (false, _) => ((b << 6) | (b << 1), 13),
+        };
+        let t = (d * multiplier + pattern) ^ a;
+        (a & 0x20) | (t >> 2)
+    };
+    if value > 32 { value + 1 } else { value }
+}
+
+/// Weight grid layout decoded from the block mode.
+struct BlockMode {
+    width: u32,
+    height: u32,
+    dual_plane: bool,
+    range: Range,
+}
+
+fn decode_block_mode(mode: u32) -> Option<BlockMode> {
+    let a = (mode >> 5) & 3;
+    let b = (mode >> 7) & 3;
+    let (width, height, r, high_precision, dual_plane);
+    if mode & 3 != 0 {
+        r = ((mode & 3) << 1) | bit(mode, 4);
+        high_precision = bit(mode, 9);
+        dual_plane = bit(mode, 10) != 0;
+        (width, height) = match (mode >> 2) & 3 {
+            0 => (b + 4, a + 2),
+            1 => (b + 8, a + 2),
+            2 => (a + 2, b + 8),
+            _ if bit(mode, 8) == 0 => (a + 2, bit(mode, 7) + 6),
+            _ => (bit(mode, 7) + 2, a + 2),
+        };
+    } else {
+        r = (((mode >> 2) & 3) << 1) | bit(mode, 4);
+        if r == 0 {
+            return None;
+        }
+        match (mode >> 7) & 3 {
+            0 => {
+                (width, height) = (12, a + 2);
+                high_precision = bit(mode, 9);
+                dual_plane = bit(mode, 10) != 0;
+            }
+            1 => {
+                (width, height) = (a + 2, 12);
+                high_precision = bit(mode, 9);
+                dual_plane = bit(mode, 10) != 0;
+            }
+            2 => {
+                (width, height) = (a + 6, ((mode >> 9) & 3) + 6);
+                high_precision = 0;
+                dual_plane = false;
+            }
+            _ => {
+                (width, height) = match a {
+                    0 => (6, 10),
+                    1 => (10, 6),
+                    _ => return None,
+                };
+                high_precision = bit(mode, 9);
+                dual_plane = bit(mode, 10) != 0;
+            }
+        }
+    }
+    let range = WEIGHT_RANGES[((high_precision << 3) | r) as usize]?;
+    Some(BlockMode {
+        width,
+        height,
+        dual_plane,
+        range,
+    })
+}
+
+fn hash52(mut p: u32) -> u32 {
+    p ^= p >> 15;
+    p = p.wrapping_sub(p << 17);
+    p = p.wrapping_add(p << 7);
+    p = p.wrapping_add(p << 4);
+    p ^= p >> 5;
+    p = p.wrapping_add(p << 16);
+    p ^= p >> 7;
+    p ^= p >> 3;
+    p ^= p << 6;
+    p ^= p >> 17;
+    p
+}
+
+/// Select the partition of texel `(x, y)`.
+fn select_partition(seed: u32, x: u32, y: u32, partitions: u32, small_block: bool) -> usize {
+    let (x, y, z) = if small_block {
+        (x << 1, y << 1, 0)
+    } else {
+        (x, y, 0)
+    };
+    let seed = seed + (partitions - 1) * 1024;
+    let rnum = hash52(seed);
+
+    let mut seeds = [
+        rnum & 0xf,
+        (rnum >> 4) & 0xf,
+        (rnum >> 8) & 0xf,
+        (rnum >> 12) & 0xf,
+        (rnum >> 16) & 0xf,
+        (rnum >> 20) & 0xf,
+        (rnum >> 24) & 0xf,
+        (rnum >> 28) & 0xf,
+        (rnum >> 18) & 0xf,
+        (rnum >> 22) & 0xf,
+        (rnum >> 26) & 0xf,
+        rnum.rotate_right(30) & 0xf,
+    ];
+    for s in &mut seeds {
+        *s *= *s;
+    }
+
+    let (sh1, sh2) = if seed & 1 != 0 {
+        (
+            if seed & 2 != 0 { 4 } else { 5 },
+            if partitions == 3 { 6 } else { 5 },
+        )
+    } else {
+        (
+            if partitions == 3 { 6 } else { 5 },
+            if seed & 2 != 0 { 4 } else { 5 },
+        )
+    };
+    let sh3 = if seed & 0x10 != 0 { sh1 } else { sh2 };
+    for (i, s) in seeds.iter_mut().enumerate() {
+        *s >>= match i {
+            0..8 if i % 2 == 0 => sh1,
+            0..8 => sh2,
+            _ => sh3,
+        };
+    }
+
+    let a = (seeds[0] * x + seeds[1] * y + seeds[10] * z + (rnum >> 14)) & 0x3f;
+    let b = (seeds[2] * x + seeds[3] * y + seeds[11] * z + (rnum >> 10)) & 0x3f;
+    let mut c = (seeds[4] * x + seeds[5] * y + seeds[8] * z + (rnum >> 6)) & 0x3f;
+    let mut d = (seeds[6] * x + seeds[7] * y + seeds[9] * z + (rnum >> 2)) & 0x3f;
+    if partitions < 4 {
+        d = 0;
+    }
+    if partitions < 3 {
+        c = 0;
+    }
+
+    if a >= b && a >= c && a >= d {
+        0
+    } else if b >= c && b >= d {
+        1
+    } else if c >= d {
+        2
+    } else {
+        3
+    }
+}
+
+fn bit_transfer_signed(a: i32, b: i32) -> (i32, i32) {
+    let b = (b >> 1) | (a & 0x80);
+    let mut a = (a >> 1) & 0x3f;
+    if a & 0x20 != 0 {
+        a -= 0x40;
+    }
+    (a, b)
+}
+
+fn blue_contract([r, g, b, a]: [i32; 4]) -> [i32; 4] {
+    [(r + b) >> 1, (g + b) >> 1, b, a]
+}
+
+/// Decode the endpoints of an LDR color endpoint mode. Returns `None` for
+/// HDR modes.
+fn decode_endpoints(mode: u32, v: &[i32]) -> Option<([i32; 4], [i32; 4])> {
+    let (e0, e1) = match mode {
+        0 => ([v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]),
+        1 => {
+            let l0 = (v[0] >> 2) | (v[1] & 0xc0);
+            let l1 = (l0 + (v[1] & 0x3f)).min(255);
+            ([l0, l0, l0, 255], [l1, l1, l1, 255])
+        }
+        4 => ([v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]),
+        5 => {
+            let (d0, l0) = bit_transfer_signed(v[1], v[0]);
+            let (d1, a0) = bit_transfer_signed(v[3], v[2]);
+            let l1 = l0 + d0;
+            ([l0, l0, l0, a0], [l1, l1, l1, a0 + d1])
+        }
+        6 => (
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                255,
+            ],
+            [v[0], v[1], v[2], 255],
+        ),
+        8 | 12 => {
+            let (a0, a1) = if mode == 12 { (v[6], v[7]) } else { (255, 255) };
+            if v[1] + v[3] + v[5] >= v[0] + v[2] + v[4] {
+                ([v[0], v[2], v[4], a0], [v[1], v[3], v[5], a1])
+            } else {
+                (
+                    blue_contract([v[1], v[3], v[5], a1]),
+                    blue_contract([v[0], v[2], v[4], a0]),
+                )
+            }
+        }
+        9 | 13 => {
+            let (d0, r) = bit_transfer_signed(v[1], v[0]);
+            let (d1, g) = bit_transfer_signed(v[3], v[2]);
+            let (d2, b) = bit_transfer_signed(v[5], v[4]);
+            let (d3, a) = if mode == 13 {
+                bit_transfer_signed(v[7], v[6])
+            } else {
+                (0, 255)
+            };
+            if d0 + d1 + d2 >= 0 {
+                ([r, g, b, a], [r + d0, g + d1, b + d2, a + d3])
+            } else {
+                (
+                    blue_contract([r + d0, g + d1, b + d2, a + d3]),
+                    blue_contract([r, g, b, a]),
+                )
+            }
+        }
+        10 => (
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                v[4],
+            ],
+            [v[0], v[1], v[2], v[5]],
+        ),
+        _ => return None,
+    };
+    Some((e0.map(|c| c.clamp(0, 255)), e1.map(|c| c.clamp(0, 255))))
+}
+
+/// Decode a block of `block_width` x `block_height` texels, writing rows
+/// of RGBA8 texels to `out`.
+pub(super) fn decode_astc(
+    block: &[u8],
+    block_width: u32,
+    block_height: u32,
+    srgb: bool,
+    out: &mut [[u8; 4]],
+) {
+    let texel_count = (block_width * block_height) as usize;
+    let out = &mut out[..texel_count];
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&block[..16]);
+    let bits = u128::from_le_bytes(bytes);
+
+    if decode_block(bits, block_width, block_height, srgb, out).is_none() {
+        out.fill(ERROR_COLOR);
+    }
+}
+
+fn decode_block(
+    bits: u128,
+    block_width: u32,
+    block_height: u32,
+    srgb: bool,
+    out: &mut [[u8; 4]],
+) -> Option<()> {
+    let mode = field(bits, 0, 11);
+
+    // Void-extent block: one constant color
+    if mode & 0x1ff == 0x1fc {
+        if mode & 0x200 != 0 {
+            // HDR constant color
+            return None;
+        }
+        let color = [
+            (field(bits, 64, 16) >> 8) as u8,
+            (field(bits, 80, 16) >> 8) as u8,
+            (field(bits, 96, 16) >> 8) as u8,
+            (field(bits, 112, 16) >> 8) as u8,
+        ];
+        out.fill(color);
+        return Some(());
+    }
+
+    let grid = decode_block_mode(mode)?;
+    if grid.width > block_width || grid.height > block_height {
+        return None;
+    }
+    let planes = if grid.dual_plane { 2 } else { 1 };
+    let weight_count = grid.width * grid.height * planes;
+    if weight_count > 64 {
+        return None;
+    }
+    let weight_bits = grid.range.bit_count(weight_count);
+    if !(24..=96).contains(&weight_bits) {
+        return None;
+    }
+
+    let partitions = field(bits, 11, 2) + 1;
+    if partitions == 4 && grid.dual_plane {
+        return None;
+    }
+
+    // Endpoint modes
+    let mut below_weights = 128 - weight_bits;
+    let mut modes = [0u32; 4];
+    let color_start;
+    if partitions == 1 {
+        modes[0] = field(bits, 13, 4);
+        color_start = 17;
+    } else {
+        color_start = 29;
+        let encoded = field(bits, 23, 6);
+        if encoded & 3 == 0 {
+            modes = [encoded >> 2; 4];
+        } else {
+            let extra_bits = 3 * partitions - 4;
+            below_weights -= extra_bits;
+            let encoded = encoded | (field(bits, below_weights, extra_bits) << 6);
+            let base_class = (encoded & 3) - 1;
+            for (i, mode) in modes.iter_mut().take(partitions as usize).enumerate() {
+                let class = base_class + bit(encoded, 2 + i as u32);
+                let low = (encoded >> (2 + partitions + 2 * i as u32)) & 3;
+                *mode = (class << 2) | low;
+            }
+        }
+    }
+    let component_selector = if grid.dual_plane {
+        below_weights -= 2;
+        Some(field(bits, below_weights, 2) as usize)
+    } else {
+        None
+    };
+
+    // Endpoint values
+    let value_count: u32 = modes
+        .iter()
+        .take(partitions as usize)
+        .map(|mode| ((mode >> 2) + 1) * 2)
+        .sum();
+    if value_count > 18 || below_weights < color_start {
+        return None;
+    }
+    let color_bits = below_weights - color_start;
+    let color_range = COLOR_RANGES
+        .into_iter()
+        .find(|range| range.bit_count(value_count) <= color_bits)?;
+    let values: Vec<i32> = decode_ise(bits, color_start, value_count as usize, color_range)
+        .into_iter()
+        .map(|value| unquantize_color(color_range, value) as i32)
+        .collect();
+
+    let mut endpoints = [([0i32; 4], [0i32; 4]); 4];
+    let mut offset = 0;
+    for (endpoint, &mode) in endpoints.iter_mut().zip(&modes).take(partitions as usize) {
+        let count = (((mode >> 2) + 1) * 2) as usize;
+        *endpoint = decode_endpoints(mode, &values[offset..offset + count])?;
+        offset += count;
+    }
+
+    // Weights, stored bit-reversed from the top of the block
+    let weights: Vec<u32> = decode_ise(bits.reverse_bits(), 0, weight_count as usize, grid.range)
+        .into_iter()
+        .map(|value| unquantize_weight(grid.range, value))
+        .collect();
+
+    let seed = field(bits, 13, 10);
+    let small_block = block_width * block_height < 31;
+    let ds = (1024 + block_width / 2) / (block_width - 1);
+    let dt = (1024 + block_height / 2) / (block_height - 1);
+    let grid_weight = |plane: u32, s: u32, t: u32| -> u32 {
+        let gs = (ds * s * (grid.width - 1) + 32) >> 6;
+        let gt = (dt * t * (grid.height - 1) + 32) >> 6;
+        let (js, fs) = (gs >> 4, gs & 0xf);
+        let (jt, ft) = (gt >> 4, gt & 0xf);
+        let w11 = (fs * ft + 8) >> 4;
+        let w10 = ft - w11;
+        let w01 = fs - w11;
+        let w00 = 16 - fs - ft + w11;
+        let at = |x: u32, y: u32| -> u32 {
+            if x >= grid.width || y >= grid.height {
+                return 0;
+            }
+            weights[((y * grid.width + x) * planes + plane) as usize]
+        };
+        (at(js, jt) * w00
+            + at(js + 1, jt) * w01
+            + at(js, jt + 1) * w10
+            + at(js + 1, jt + 1) * w11
+            + 8)
+            >> 4
+    };
+
+    for t in 0..block_height {
+        for s in 0..block_width {
+            let partition = if partitions > 1 {
+                select_partition(seed, s, t, partitions, small_block)
+            } else {
+                0
+            };
+            let (e0, e1) = endpoints[partition];
+            let w0 = grid_weight(0, s, t);
+            let w1 = component_selector.map(|_| grid_weight(1, s, t));
+
+            let texel = &mut out[(t * block_width + s) as usize];
+            for c in 0..4 {
+                let w = match (component_selector, w1) {
+                    (Some(selector), Some(w1)) if selector == c => w1,
+                    _ => w0,
+                } as i32;
+                let (c0, c1) = if srgb {
+                    ((e0[c] << 8) | 0x80, (e1[c] << 8) | 0x80)
+                } else {
+                    (e0[c] * 257, e1[c] * 257)
+                };
+                let value = (c0 * (64 - w) + c1 * w + 32) >> 6;
+                texel[c] = (value >> 8) as u8;
+            }
+        }
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_void_extent_block() {
+        let mut bits: u128 = 0x1fc | (0b11 << 10) | (0x1fff << 12) | (0x1fff << 25);
+        bits |= (0x1fff << 38) | (0x1fff << 51);
+        for (i, value) in [0xff00u128, 0x8000, 0x0000, 0xffff].into_iter().enumerate() {
+            bits |= value << (64 + 16 * i);
+        }
+        let mut out = [[0; 4]; 16];
+        decode_astc(&bits.to_le_bytes(), 4, 4, false, &mut out);
+        assert!(out.iter().all(|texel| *texel == [255, 128, 0, 255]));
+    }
+
+    #[test]
+    fn test_reserved_block_is_error_color() {
+        let mut out = [[0; 4]; 36];
+        decode_astc(&[0; 16], 6, 6, false, &mut out);
+        assert!(out.iter().all(|texel| *texel == ERROR_COLOR));
+    }
+
+    #[test]
+    fn test_ise_round_trip() {
+        // Trit blocks: every 5-trit combination decodes from some T.
+        let mut seen = std::collections::HashSet::new();
+        for t in 0..256 {
+            seen.insert(decode_trits(t));
+        }
+        assert_eq!(seen.len(), 243);
+        let mut seen = std::collections::HashSet::new();
+        for q in 0..128 {
+            seen.insert(decode_quints(q));
+        }
+        assert_eq!(seen.len(), 125);
+    }
+
+    #[test]
+    fn test_unquantize_extremes() {
+        for range in COLOR_RANGES {
+            let levels = (1u32 << range.bits)
+                * if range.trits {
+                    3
+                } else if range.quints {
+                    5
+                } else {
+                    1
+                };
+            let values: Vec<u8> = (0..levels)
+                .map(|i| unquantize_color(range, (i & ((1 << range.bits) - 1), i >> range.bits)))
+                .collect();
+            assert!(values.contains(&0) && values.contains(&255), "{range:?}");
+        }
+        let range = range(false, false, 1);
+        assert_eq!(unquantize_weight(range, (0, 0)), 0);
+        assert_eq!(unquantize_weight(range, (1, 0)), 64);
+    }
+
+    #[test]
+    fn test_solid_block() {
+        // 4x4 block, 2x2 grid of 1-bit weights is under 24 bits, so use a
+        // 4x4 grid of 2-bit weights (32 bits), one partition, CEM 8 (RGB
+        // direct) with 8-bit endpoints, all weights zero.
+        //
+        // Block mode: R = 4 (bits 1:0 = 10, bit 4 = 0), bits 3:2 = 0,
+        // W = B + 4 = 4, H = A + 2 = 4 => A = 2, B = 0.
+        let mode: u128 = 0b10 | (2 << 5);
+        let mut bits = mode | (8 << 13);
+        let endpoints = [10u128, 10, 20, 20, 30, 30];
+        for (i, value) in endpoints.iter().enumerate() {
+            bits |= value << (17 + 8 * i);
+        }
+        let mut out = [[0; 4]; 16];
+        decode_astc(&bits.to_le_bytes(), 4, 4, false, &mut out);
+        assert!(out.iter().all(|texel| *texel == [10, 20, 30, 255]));
+    }
+}
@@ -0,0 +1,509 @@
+//! BC1–BC5 and BC7 block decoders.
+//!
+//! Each decoder takes one block and writes its 4x4 texels as RGBA8, row
+//! by row.
+
+/// Decoded texels of a 4x4 block.
+pub(super) type Texels = [[u8; 4]; 16];
+
+fn expand_565(color: u16) -> [u8; 3] {
+    let r = (color >> 11) as u8 & 0x1f;
+    let g = (color >> 5) as u8 & 0x3f;
+    let b = color as u8 & 0x1f;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+/// Decode the BC1 color half of a block. `opaque` forces the four-color
+/// mode used by BC2 and BC3.
+fn decode_color(block: &[u8], opaque: bool, out: &mut Texels) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (e0, e1) = (expand_565(c0), expand_565(c1));
+
+    let mut palette = [[0u8; 4]; 4];
+    palette[0] = [e0[0], e0[1], e0[2], 255];
+    palette[1] = [e1[0], e1[1], e1[2], 255];
+    for channel in 0..3 {
+        let (a, b) = (e0[channel] as u32, e1[channel] as u32);
+        if c0 > c1 || opaque {
+            palette[2][channel] = ((2 * a + b) / 3) as u8;
+            palette[3][channel] = ((a + 2 * b) / 3) as u8;
+        } else {
+            palette[2][channel] = ((a + b) / 2) as u8;
+        }
+    }
+    palette[2][3] = 255;
+    palette[3][3] = if c0 > c1 || opaque { 255 } else { 0 };
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, texel) in out.iter_mut().enumerate() {
+        *texel = palette[(indices >> (2 * i)) as usize & 3];
+    }
+}
+
+/// Decode a BC4 channel block into eight-bit values.
+///
+/// Signed blocks produce two's-complement values.
+fn decode_channel(block: &[u8], signed: bool) -> [u8; 16] {
+    let mut palette = [0i32; 8];
+    let (a, b) = if signed {
+        (
+            (block[0] as i8).max(-127) as i32,
+            (block[1] as i8).max(-127) as i32,
+        )
+    } else {
+        (block[0] as i32, block[1] as i32)
+    };
+    let (min, max) = if signed { (-127, 127) } else { (0, 255) };
+    palette[0] = a;
+    palette[1] = b;
+    if a > b {
+        for (i, value) in (2..).zip(&mut palette[2..]) {
+            *value = ((8 - i) * a + (i - 1) * b) / 7;
+        }
+    } else {
+        for (i, value) in (2..).zip(&mut palette[2..6]) {
+            *value = ((6 - i) * a + (i - 1) * b) / 5;
+        }
+        palette[6] = min;
+        palette[7] = max;
+    }
+
+    let mut bits = 0u64;
+    for (i, &byte) in block[2..8].iter().enumerate() {
+        bits |= (byte as u64) << (8 * i);
+    }
+    let mut out = [0u8; 16];
+    for (i, value) in out.iter_mut().enumerate() {
+        *value = palette[(bits >> (3 * i)) as usize & 7] as u8;
+    }
+    out
+}
+
+pub(super) fn decode_bc1(block: &[u8], out: &mut Texels) {
+    decode_color(block, false, out);
+}
+
+pub(super) fn decode_bc2(block: &[u8], out: &mut Texels) {
+    decode_color(&block[8..], true, out);
+    for (i, texel) in out.iter_mut().enumerate() {
+        let alpha = (block[i / 2] >> (4 * (i % 2))) & 0xf;
+        texel[3] = alpha * 17;
+    }
+}
+
+pub(super) fn decode_bc3(block: &[u8], out: &mut Texels) {
+    decode_color(&block[8..], true, out);
+    let alpha = decode_channel(block, false);
+    for (texel, alpha) in out.iter_mut().zip(alpha) {
+        texel[3] = alpha;
+    }
+}
+
+/// Decode BC4 into the red channel, with opaque alpha.
+pub(super) fn decode_bc4(block: &[u8], signed: bool, out: &mut Texels) {
+    let red = decode_channel(block, signed);
+    let alpha = if signed { 127 } else { 255 };
+    for (texel, red) in out.iter_mut().zip(red) {
+        *texel = [red, 0, 0, alpha];
+    }
+}
+
+/// Decode BC5 into the red and green channels, with opaque alpha.
+pub(super) fn decode_bc5(block: &[u8], signed: bool, out: &mut Texels) {
+    let red = decode_channel(block, signed);
+    let green = decode_channel(&block[8..], signed);
+    let alpha = if signed { 127 } else { 255 };
+    for (i, texel) in out.iter_mut().enumerate() {
+        *texel = [red[i], green[i], 0, alpha];
+    }
+}
+
+// ============================================================================
+// BC7
+// ============================================================================
+
+/// Per-mode layout: subsets, partition bits, rotation bits, index selection
+/// bits, color bits, alpha bits, endpoint p-bits, shared p-bits, index bits,
+/// secondary index bits.
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index2_bits: u32,
+}
+
+const fn mode(
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index2_bits: u32,
+) -> Bc7Mode {
+    Bc7Mode {
+        subsets,
+        partition_bits,
+        rotation_bits,
+        index_selection_bits,
+        color_bits,
+        alpha_bits,
+        endpoint_pbits,
+        shared_pbits,
+        index_bits,
+        index2_bits,
+    }
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    mode(3, 4, 0, 0, 4, 0, true, false, 3, 0),
+    mode(2, 6, 0, 0, 6, 0, false, true, 3, 0),
+    mode(3, 6, 0, 0, 5, 0, false, false, 2, 0),
+    mode(2, 6, 0, 0, 7, 0, true, false, 2, 0),
+    mode(1, 0, 2, 1, 5, 6, false, false, 2, 3),
+    mode(1, 0, 2, 0, 7, 8, false, false, 2, 2),
+    mode(1, 0, 0, 0, 7, 7, true, false, 4, 0),
+    mode(2, 6, 0, 0, 5, 5, true, false, 2, 0),
+];
+
+/// Two-subset partitions; bit `i` is the subset of texel `i`.
+const PARTITIONS2: [u16; 64] = [
+    0xcccc, 0x8888, 0xeeee, 0xecc8, 0xc880, 0xfeec, 0xfec8, 0xec80, 0xc800, 0xffec, 0xfe80, 0xe800,
+    0xffe8, 0xff00, 0xfff0, 0xf000, 0xf710, 0x008e, 0x7100, 0x08ce, 0x008c, 0x7310, 0x3100, 0x8cce,
+    0x088c, 0x3110, 0x6666, 0x366c, 0x17e8, 0x0ff0, 0x718e, 0x399c, 0xaaaa, 0xf0f0, 0x5a5a, 0x33cc,
+    0x3c3c, 0x55aa, 0x9696, 0xa55a, 0x73ce, 0x13c8, 0x324c, 0x3bdc, 0x6996, 0xc33c, 0x9966, 0x0660,
+    0x0272, 0x04e4, 0x4e40, 0x2720, 0xc936, 0x936c, 0x39c6, 0x639c, 0x9336, 0x9cc9, 0x817e, 0xe718,
+    0xccf0, 0x0fcc, 0x7744, 0xee22,
+];
+
+/// Three-subset partitions, as the subset of each texel.
+const PARTITIONS3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2],
+    [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0],
+    [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0],
+    [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2],
+    [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0],
+    [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0],
+    [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1],
+    [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1],
+    [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2],
+    [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2],
+    [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1],
+    [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+/// Anchor texel of the second subset of two-subset partitions.
+const ANCHORS2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8,
+    2, 2, 8, 8, 2, 2, 15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2,
+    2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+/// Anchor texel of the second subset of three-subset partitions.
+const ANCHORS3_SECOND: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, 3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5,
+    15, 15, 8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15, 3, 15, 5, 5, 5, 8, 5, 10, 5,
+    10, 8, 13, 15, 12, 3, 3,
+];
+
+/// Anchor texel of the third subset of three-subset partitions.
+const ANCHORS3_THIRD: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, 15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6,
+    10, 15, 15, 10, 8, 15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, 15, 3, 15, 15, 15,
+    15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
+
+const WEIGHTS2: [u32; 4] = [0, 21, 43, 64];
+const WEIGHTS3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const WEIGHTS4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// Reads bits of a block, least significant first.
+struct BitReader {
+    bits: u128,
+}
+
+impl BitReader {
+    fn read(&mut self, count: u32) -> u32 {
+        if count == 0 {
+            return 0;
+        }
+        let value = (self.bits & ((1u128 << count) - 1)) as u32;
+        self.bits >>= count;
+        value
+    }
+}
+
+fn subset_of(subsets: usize, partition: usize, texel: usize) -> usize {
+    match subsets {
+        2 => (PARTITIONS2[partition] >> texel) as usize & 1,
+        3 => PARTITIONS3[partition][texel] as usize,
+        _ => 0,
+    }
+}
+
+fn is_anchor(subsets: usize, partition: usize, texel: usize) -> bool {
+    texel == 0
+        || match subsets {
+            2 => texel == ANCHORS2[partition] as usize,
+            3 => {
+                texel == ANCHORS3_SECOND[partition] as usize
+                    || texel == ANCHORS3_THIRD[partition] as usize
+            }
+            _ => false,
+        }
+}
+
+fn interpolate(a: u8, b: u8, weight: u32) -> u8 {
+    (((64 - weight) * a as u32 + weight * b as u32 + 32) >> 6) as u8
+}
+
+fn weight(bits: u32, index: u32) -> u32 {
+    match bits {
+        2 => WEIGHTS2[index as usize],
+        3 => WEIGHTS3[index as usize],
+        _ => WEIGHTS4[index as usize],
+    }
+}
+
+pub(super) fn decode_bc7(block: &[u8], out: &mut Texels) {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&block[..16]);
+    let mut reader = BitReader {
+        bits: u128::from_le_bytes(bytes),
+    };
+
+    let mode_index = reader.bits.trailing_zeros() as usize;
+    if mode_index >= 8 {
+        // Reserved mode: transparent black
+        *out = [[0; 4]; 16];
+        return;
+    }
+    reader.read(mode_index as u32 + 1);
+    let mode = &BC7_MODES[mode_index];
+
+    let partition = reader.read(mode.partition_bits) as usize;
+    let rotation = reader.read(mode.rotation_bits);
+    let index_selection = reader.read(mode.index_selection_bits);
+
+    // endpoints[subset * 2 + end][channel]
+    let mut endpoints = [[0u8; 4]; 6];
+    let endpoint_count = mode.subsets * 2;
+    for channel in 0..3 {
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            endpoint[channel] = reader.read(mode.color_bits) as u8;
+        }
+    }
+    if mode.alpha_bits > 0 {
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            endpoint[3] = reader.read(mode.alpha_bits) as u8;
+        }
+    }
+
+    let mut pbits = [0u8; 6];
+    if mode.endpoint_pbits {
+        for pbit in pbits.iter_mut().take(endpoint_count) {
+            *pbit = reader.read(1) as u8;
+        }
+    } else if mode.shared_pbits {
+        for subset in 0..mode.subsets {
+            let pbit = reader.read(1) as u8;
+            pbits[subset * 2] = pbit;
+            pbits[subset * 2 + 1] = pbit;
+        }
+    }
+
+    let has_pbits = mode.endpoint_pbits || mode.shared_pbits;
+    for (endpoint, &pbit) in endpoints.iter_mut().zip(&pbits).take(endpoint_count) {
+        for (channel, component) in endpoint.iter_mut().enumerate() {
+            let mut bits = if channel == 3 {
+                mode.alpha_bits
+            } else {
+                mode.color_bits
+            };
+            if bits == 0 {
+                *component = 255;
+                continue;
+            }
+            let mut value = *component as u32;
+            if has_pbits {
+                value = (value << 1) | pbit as u32;
+                bits += 1;
+            }
+            value <<= 8 - bits;
+            *component = (value | (value >> bits)) as u8;
+        }
+    }
+
+    let mut indices = [0u32; 16];
+    for (texel, index) in indices.iter_mut().enumerate() {
+        let anchor = is_anchor(mode.subsets, partition, texel);
+        *index = reader.read(mode.index_bits - anchor as u32);
+    }
+    let mut indices2 = [0u32; 16];
+    if mode.index2_bits > 0 {
+        for (texel, index) in indices2.iter_mut().enumerate() {
+            *index = reader.read(mode.index2_bits - (texel == 0) as u32);
+        }
+    }
+
+    for (texel, out) in out.iter_mut().enumerate() {
+        let subset = subset_of(mode.subsets, partition, texel);
+        let (e0, e1) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+
+        let (color_weight, alpha_weight) = if mode.index2_bits == 0 {
+            let w = weight(mode.index_bits, indices[texel]);
+            (w, w)
+        } else if index_selection == 0 {
+            (
+                weight(mode.index_bits, indices[texel]),
+                weight(mode.index2_bits, indices2[texel]),
+            )
+        } else {
+            (
+                weight(mode.index2_bits, indices2[texel]),
+                weight(mode.index_bits, indices[texel]),
+            )
+        };
+
+        let mut color = [
+            interpolate(e0[0], e1[0], color_weight),
+            interpolate(e0[1], e1[1], color_weight),
+            interpolate(e0[2], e1[2], color_weight),
+            interpolate(e0[3], e1[3], alpha_weight),
+        ];
+        match rotation {
+            1 => color.swap(0, 3),
+            2 => color.swap(1, 3),
+            3 => color.swap(2, 3),
+            _ => {}
+        }
+        *out = color;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bc1_modes() {
+        let mut out = [[0; 4]; 16];
+        // Red and blue endpoints, texels cycling through the palette.
+        decode_bc1(&[0x00, 0xf8, 0x1f, 0x00, 0xe4, 0xe4, 0xe4, 0xe4], &mut out);
+        assert_eq!(out[0], [255, 0, 0, 255]);
+        assert_eq!(out[1], [0, 0, 255, 255]);
+        assert_eq!(out[2], [170, 0, 85, 255]);
+
+        // Swapped endpoints select the three-color mode with transparency.
+        decode_bc1(&[0x1f, 0x00, 0x00, 0xf8, 0xff, 0, 0, 0], &mut out);
+        assert_eq!(out[0], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_bc4_palette() {
+        let red = decode_channel(&[200, 100, 0b001, 0, 0, 0, 0, 0], false);
+        assert_eq!(red[0], 100);
+        assert_eq!(red[1], 200);
+        let red = decode_channel(&[0x80, 0x7f, 0, 0, 0, 0, 0, 0], true);
+        assert_eq!(red[0] as i8, -127);
+    }
+
+    #[test]
+    fn test_bc7_anchors_lie_in_their_subsets() {
+        for partition in 0..64 {
+            assert_eq!(subset_of(2, partition, ANCHORS2[partition] as usize), 1);
+            assert_eq!(
+                subset_of(3, partition, ANCHORS3_SECOND[partition] as usize),
+                1
+            );
+            assert_eq!(
+                subset_of(3, partition, ANCHORS3_THIRD[partition] as usize),
+                2
+            );
+        }
+    }
+
+    #[test]
+    fn test_bc7_mode6_solid() {
+        // Mode 6 with both endpoints 0x7f and p-bits set: opaque white.
+        let mut bits: u128 = 1 << 6;
+        let mut pos = 7;
+        for _ in 0..8 {
+            bits |= 0x7f << pos;
+            pos += 7;
+        }
+        bits |= 0b11 << pos;
+        let mut out = [[0; 4]; 16];
+        decode_bc7(&bits.to_le_bytes(), &mut out);
+        assert!(out.iter().all(|texel| *texel == [255; 4]));
+
+        decode_bc7(&[0; 16], &mut out);
+        assert!(out.iter().all(|texel| *texel == [0; 4]));
+    }
+}
@@ -0,0 +1,356 @@
+//! ETC2 and EAC block decoders.
+//!
+//! Blocks are big-endian and their texel indices are stored column by
+//! column; the decoders write texels row by row like the BC decoders.
+
+use super::bc::Texels;
+
+const MODIFIERS: [[i32; 2]; 8] = [
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+
+const DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+fn clamp8(value: i32) -> u8 {
+    value.clamp(0, 255) as u8
+}
+
+fn expand4(value: u8) -> i32 {
+    (value as i32 & 0xf) * 17
+}
+
+fn expand5(value: i32) -> i32 {
+    (value << 3) | (value >> 2)
+}
+
+/// Get the 2-bit index of the texel at row `y`, column `x`.
+fn texel_index(block: &[u8], x: usize, y: usize) -> usize {
+    let bit = x * 4 + y;
+    let msb = (u16::from_be_bytes([block[4], block[5]]) >> bit) & 1;
+    let lsb = (u16::from_be_bytes([block[6], block[7]]) >> bit) & 1;
+    (msb * 2 + lsb) as usize
+}
+
+fn add(color: [i32; 3], offset: i32) -> [u8; 4] {
+    [
+        clamp8(color[0] + offset),
+        clamp8(color[1] + offset),
+        clamp8(color[2] + offset),
+        255,
+    ]
+}
+
+/// Decode an ETC2 RGB block. `punchthrough` selects the RGB8A1 variant,
+/// where the differential bit is the opaque flag.
+pub(super) fn decode_etc2(block: &[u8], punchthrough: bool, out: &mut Texels) {
+    let differential = block[3] & 2 != 0;
+    let opaque = !punchthrough || differential;
+
+    if !differential && !punchthrough {
+        let base = [
+            [
+                expand4(block[0] >> 4),
+                expand4(block[1] >> 4),
+                expand4(block[2] >> 4),
+            ],
+            [expand4(block[0]), expand4(block[1]), expand4(block[2])],
+        ];
+        decode_subblocks(block, base, true, out);
+        return;
+    }
+
+    let r = (block[0] >> 3) as i32;
+    let g = (block[1] >> 3) as i32;
+    let b = (block[2] >> 3) as i32;
+    let dr = ((block[0] as i8) << 5 >> 5) as i32;
+    let dg = ((block[1] as i8) << 5 >> 5) as i32;
+    let db = ((block[2] as i8) << 5 >> 5) as i32;
+
+    if !(0..32).contains(&(r + dr)) {
+        decode_t_mode(block, opaque, out);
+    } else if !(0..32).contains(&(g + dg)) {
+        decode_h_mode(block, opaque, out);
+    } else if !(0..32).contains(&(b + db)) {
+        decode_planar(block, out);
+    } else {
+        let base = [
+            [expand5(r), expand5(g), expand5(b)],
+            [expand5(r + dr), expand5(g + dg), expand5(b + db)],
+        ];
+        decode_subblocks(block, base, opaque, out);
+    }
+}
+
+fn decode_subblocks(block: &[u8], base: [[i32; 3]; 2], opaque: bool, out: &mut Texels) {
+    let tables = [(block[3] >> 5) as usize & 7, (block[3] >> 2) as usize & 7];
+    let flip = block[3] & 1 != 0;
+    for y in 0..4 {
+        for x in 0..4 {
+            let subblock = if flip { y / 2 } else { x / 2 };
+            let [a, b] = MODIFIERS[tables[subblock]];
+            let texel = &mut out[y * 4 + x];
+            *texel = match (texel_index(block, x, y), opaque) {
+                (0, true) => add(base[subblock], a),
+                (0, false) => add(base[subblock], 0),
+                (1, _) => add(base[subblock], b),
+                (2, true) => add(base[subblock], -a),
+                (2, false) => [0; 4],
+                _ => add(base[subblock], -b),
+            };
+        }
+    }
+}
+
+fn decode_paint(block: &[u8], paint: [[u8; 4]; 4], opaque: bool, out: &mut Texels) {
+    for y in 0..4 {
+        for x in 0..4 {
+            let index = texel_index(block, x, y);
+            out[y * 4 + x] = if index == 2 && !opaque {
+                [0; 4]
+            } else {
+                paint[index]
+            };
+        }
+    }
+}
+
+fn decode_t_mode(block: &[u8], opaque: bool, out: &mut Texels) {
+    let c1 = [
+        expand4(((block[0] >> 1) & 0xc) | (block[0] & 3)),
+        expand4(block[1] >> 4),
+        expand4(block[1]),
+    ];
+    let c2 = [
+        expand4(block[2] >> 4),
+        expand4(block[2]),
+        expand4(block[3] >> 4),
+    ];
+    let distance = DISTANCES[(((block[3] >> 1) & 6) | (block[3] & 1)) as usize];
+    let paint = [
+        add(c1, 0),
+        add(c2, distance),
+        add(c2, 0),
+        add(c2, -distance),
+    ];
+    decode_paint(block, paint, opaque, out);
+}
+
+fn decode_h_mode(block: &[u8], opaque: bool, out: &mut Texels) {
+    let r1 = (block[0] >> 3) & 0xf;
+    let g1 = ((block[0] << 1) & 0xe) | ((block[1] >> 4) & 1);
+    let b1 = (block[1] & 8) | ((block[1] << 1) & 6) | (block[2] >> 7);
+    let r2 = (block[2] >> 3) & 0xf;
+    let g2 = ((block[2] << 1) & 0xe) | (block[3] >> 7);
+    let b2 = (block[3] >> 3) & 0xf;
+
+    let key1 = ((r1 as u32) << 8) | ((g1 as u32) << 4) | b1 as u32;
+    let key2 = ((r2 as u32) << 8) | ((g2 as u32) << 4) | b2 as u32;
+    let index = (block[3] & 4) | ((block[3] << 1) & 2) | (key1 >= key2) as u8;
+    let distance = DISTANCES[index as usize];
+
+    let c1 = [expand4(r1), expand4(g1), expand4(b1)];
+    let c2 = [expand4(r2), expand4(g2), expand4(b2)];
+    let paint = [
+        add(c1, distance),
+        add(c1, -distance),
+        add(c2, distance),
+        add(c2, -distance),
+    ];
+    decode_paint(block, paint, opaque, out);
+}
+
+fn decode_planar(block: &[u8], out: &mut Texels) {
+    let b = |i: usize| block[i] as i32;
+    let expand6 = |v: i32| (v << 2) | (v >> 4);
+    let expand7 = |v: i32| (v << 1) | (v >> 6);
+
+    let origin = [
+        expand6((b(0) >> 1) & 0x3f),
+        expand7(((b(0) & 1) << 6) | ((b(1) >> 1) & 0x3f)),
+        expand6(((b(1) & 1) << 5) | (b(2) & 0x18) | ((b(2) & 3) << 1) | ((b(3) >> 7) & 1)),
+    ];
+    let horizontal = [
+        expand6(((b(3) >> 1) & 0x3e) | (b(3) & 1)),
+        expand7(b(4) >> 1),
+        expand6(((b(4) & 1) << 5) | (b(5) >> 3)),
+    ];
+    let vertical = [
+        expand6(((b(5) & 7) << 3) | (b(6) >> 5)),
+        expand7(((b(6) & 0x1f) << 2) | (b(7) >> 6)),
+        expand6(b(7) & 0x3f),
+    ];
+
+    for y in 0..4 {
+        for x in 0..4 {
+            let texel = &mut out[y * 4 + x];
+            for c in 0..3 {
+                let value = (x as i32 * (horizontal[c] - origin[c])
+                    + y as i32 * (vertical[c] - origin[c])
+                    + 4 * origin[c]
+                    + 2)
+                    >> 2;
+                texel[c] = clamp8(value);
+            }
+            texel[3] = 255;
+        }
+    }
+}
+
+/// Decode an EAC block into 11-bit values, in `0..2048` for unsigned blocks
+/// and `-1023..1024` for signed ones.
+fn decode_eac(block: &[u8], signed: bool) -> [i32; 16] {
+    let base = if signed {
+        (block[0] as i8).max(-127) as i32
+    } else {
+        block[0] as i32
+    };
+    let multiplier = (block[1] >> 4) as i32;
+    let modifiers = EAC_MODIFIERS[(block[1] & 0xf) as usize];
+    let mut indices = 0u64;
+    for &byte in &block[2..8] {
+        indices = (indices << 8) | byte as u64;
+    }
+
+    let mut out = [0i32; 16];
+    for y in 0..4 {
+        for x in 0..4 {
+            let index = (indices >> (45 - 3 * (x * 4 + y))) as usize & 7;
+            let modifier = if multiplier == 0 {
+                modifiers[index]
+            } else {
+                modifiers[index] * multiplier * 8
+            };
+            out[y * 4 + x] = if signed {
+                (base * 8 + modifier).clamp(-1023, 1023)
+            } else {
+                (base * 8 + 4 + modifier).clamp(0, 2047)
+            };
+        }
+    }
+    out
+}
+
+/// Decode an EAC block at eight-bit precision, matching the RGBA8 EAC alpha
+/// block.
+fn decode_eac8(block: &[u8]) -> [u8; 16] {
+    let base = block[0] as i32;
+    let multiplier = (block[1] >> 4) as i32;
+    let modifiers = EAC_MODIFIERS[(block[1] & 0xf) as usize];
+    let mut indices = 0u64;
+    for &byte in &block[2..8] {
+        indices = (indices << 8) | byte as u64;
+    }
+
+    let mut out = [0u8; 16];
+    for y in 0..4 {
+        for x in 0..4 {
+            let index = (indices >> (45 - 3 * (x * 4 + y))) as usize & 7;
+            out[y * 4 + x] = clamp8(base + modifiers[index] * multiplier);
+        }
+    }
+    out
+}
+
+fn eac_to_8bit(value: i32, signed: bool) -> u8 {
+    if signed {
+        (value >> 3).max(-127) as i8 as u8
+    } else {
+        (value >> 3) as u8
+    }
+}
+
+pub(super) fn decode_eac_rgba8(block: &[u8], out: &mut Texels) {
+    decode_etc2(&block[8..], false, out);
+    for (texel, alpha) in out.iter_mut().zip(decode_eac8(block)) {
+        texel[3] = alpha;
+    }
+}
+
+/// Decode EAC R11 into the red channel, with opaque alpha.
+pub(super) fn decode_eac_r11(block: &[u8], signed: bool, out: &mut Texels) {
+    let alpha = if signed { 127 } else { 255 };
+    for (texel, red) in out.iter_mut().zip(decode_eac(block, signed)) {
+        *texel = [eac_to_8bit(red, signed), 0, 0, alpha];
+    }
+}
+
+/// Decode EAC RG11 into the red and green channels, with opaque alpha.
+pub(super) fn decode_eac_rg11(block: &[u8], signed: bool, out: &mut Texels) {
+    let red = decode_eac(block, signed);
+    let green = decode_eac(&block[8..], signed);
+    let alpha = if signed { 127 } else { 255 };
+    for (i, texel) in out.iter_mut().enumerate() {
+        *texel = [
+            eac_to_8bit(red[i], signed),
+            eac_to_8bit(green[i], signed),
+            0,
+            alpha,
+        ];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etc2_individual_mode() {
+        // Base colors 0x8 and 0x4 (expanded to 136 and 68) side by side,
+        // table 0, all indices 0 (+2).
+        let block = [0x84, 0x84, 0x84, 0x00, 0, 0, 0, 0];
+        let mut out = [[0; 4]; 16];
+        decode_etc2(&block, false, &mut out);
+        assert_eq!(out[0], [138, 138, 138, 255]);
+        assert_eq!(out[3], [70, 70, 70, 255]);
+    }
+
+    #[test]
+    fn test_etc2_punchthrough_transparency() {
+        // Differential block without the opaque flag; texel (0, 0) uses
+        // index 2, which is transparent.
+        let block = [0x80, 0x80, 0x80, 0x00, 0x00, 0x01, 0x00, 0x00];
+        let mut out = [[0; 4]; 16];
+        decode_etc2(&block, true, &mut out);
+        assert_eq!(out[0], [0; 4]);
+        assert_eq!(out[1], [132, 132, 132, 255]);
+    }
+
+    #[test]
+    fn test_eac_alpha() {
+        // Base 100, multiplier 1, table 13 index 4 (+0) everywhere.
+        let mut block = [100, 0x1d, 0, 0, 0, 0, 0, 0];
+        let mut indices = 0u64;
+        for _ in 0..16 {
+            indices = (indices << 3) | 4;
+        }
+        block[2..8].copy_from_slice(&indices.to_be_bytes()[2..]);
+        assert!(decode_eac8(&block).iter().all(|&a| a == 100));
+        assert!(decode_eac(&block, false).iter().all(|&v| v == 804));
+    }
+}
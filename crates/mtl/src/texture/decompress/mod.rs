@@ -0,0 +1,249 @@
+//! CPU decoding of block-compressed textures.
+//!
+//! Not every GPU samples every compressed format: BC formats need
+//! [`Device::supports_bc_texture_compression`](crate::Device::supports_bc_texture_compression),
+//! and ASTC, ETC2 and EAC need an Apple GPU family.
+//! [`Device::check_and_fallback`](crate::Device::check_and_fallback) passes
+//! supported data through and decodes the rest to RGBA8, so one set of
+//! assets renders everywhere at four times the memory of BC1 or ASTC 4x4.
+//!
+//! Decoded formats: BC1–BC5, BC7, ETC2, EAC and LDR ASTC. BC6H, HDR ASTC
+//! and PVRTC are not decoded.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let image = device.check_and_fallback(PixelFormat::BC7_RGBA_UNORM, &bytes, 1024, 1024)?;
+//! let descriptor = TextureDescriptor::texture_2d_descriptor(image.pixel_format, 1024, 1024, false).unwrap();
+//! let texture = device.new_texture_with_descriptor(&descriptor)?;
+//! texture.upload_from_bytes(&queue, &image.data, image.bytes_per_row)?;
+//! ```
+
+mod astc;
+mod bc;
+mod etc;
+
+use std::borrow::Cow;
+use std::fmt;
+
+use mtl_foundation::UInteger;
+
+use crate::enums::PixelFormat;
+
+/// Error produced by [`TextureData::decompress`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecompressError {
+    /// The format is not one the CPU decoder handles.
+    UnsupportedFormat(PixelFormat),
+    /// The data is shorter than a `width` x `height` image of the format.
+    DataTooShort {
+        /// Bytes needed for the image.
+        required: usize,
+        /// Bytes passed in.
+        length: usize,
+    },
+}
+
+impl fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecompressError::UnsupportedFormat(format) => {
+                write!(f, "pixel format {:?} cannot be decompressed", format)
+            }
+            DecompressError::DataTooShort { required, length } => write!(
+                f,
+                "data is {} bytes but the image needs {}",
+                length, required
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecompressError {}
+
+/// Image data ready to upload, in the format it should be uploaded as.
+///
+/// Returned by [`Device::check_and_fallback`](crate::Device::check_and_fallback)
+/// and [`TextureData::decompress`].
+#[derive(Clone, Debug)]
+pub struct TextureData<'a> {
+    /// The pixel format to create the texture with.
+    pub pixel_format: PixelFormat,
+    /// The image bytes, tightly packed.
+    pub data: Cow<'a, [u8]>,
+    /// The row pitch of `data`.
+    pub bytes_per_row: UInteger,
+}
+
+impl<'a> TextureData<'a> {
+    /// Wrap `data` of `format` without decoding it.
+    pub(crate) fn borrowed(format: PixelFormat, data: &'a [u8], width: UInteger) -> Self {
+        Self {
+            pixel_format: format,
+            data: Cow::Borrowed(data),
+            bytes_per_row: format.bytes_per_row(width).unwrap_or(0),
+        }
+    }
+
+    /// Check if the data was decoded on the CPU.
+    pub fn is_decompressed(&self) -> bool {
+        matches!(self.data, Cow::Owned(_))
+    }
+
+    /// Get the format `format` decodes to: `RGBA8_UNORM`, `RGBA8_UNORM_SRGB`
+    /// for sRGB formats, or `RGBA8_SNORM` for signed formats.
+    ///
+    /// Returns `None` if the format is not decoded on the CPU.
+    pub fn decompressed_format(format: PixelFormat) -> Option<PixelFormat> {
+        use PixelFormat as F;
+        let format = match format {
+            F::BC4_R_SNORM | F::BC5_RG_SNORM | F::EAC_R11_SNORM | F::EAC_RG11_SNORM => {
+                F::RGBA8_SNORM
+            }
+            F::BC1_RGBA
+            | F::BC2_RGBA
+            | F::BC3_RGBA
+            | F::BC4_R_UNORM
+            | F::BC5_RG_UNORM
+            | F::BC7_RGBA_UNORM
+            | F::ETC2_RGB8
+            | F::ETC2_RGB8A1
+            | F::EAC_RGBA8
+            | F::EAC_R11_UNORM
+            | F::EAC_RG11_UNORM => F::RGBA8_UNORM,
+            F::BC1_RGBA_SRGB
+            | F::BC2_RGBA_SRGB
+            | F::BC3_RGBA_SRGB
+            | F::BC7_RGBA_UNORM_SRGB
+            | F::ETC2_RGB8_SRGB
+            | F::ETC2_RGB8A1_SRGB
+            | F::EAC_RGBA8_SRGB => F::RGBA8_UNORM_SRGB,
+            f if f.0 >= F::ASTC_4X4_SRGB.0 && f.0 <= F::ASTC_12X12_SRGB.0 => F::RGBA8_UNORM_SRGB,
+            f if f.0 >= F::ASTC_4X4_LDR.0 && f.0 <= F::ASTC_12X12_LDR.0 => F::RGBA8_UNORM,
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// Decode a `width` x `height` image of the compressed `format` to four
+    /// bytes per pixel.
+    ///
+    /// The result's format is [`decompressed_format`](Self::decompressed_format).
+    pub fn decompress(
+        format: PixelFormat,
+        data: &[u8],
+        width: UInteger,
+        height: UInteger,
+    ) -> Result<TextureData<'static>, DecompressError> {
+        let pixel_format =
+            Self::decompressed_format(format).ok_or(DecompressError::UnsupportedFormat(format))?;
+        let (block_width, block_height) = format.block_size();
+        let block_bytes = format.bytes_per_block().unwrap_or(0);
+        let required = format.bytes_per_image(width, height).unwrap_or(0);
+        if data.len() < required {
+            return Err(DecompressError::DataTooShort {
+                required,
+                length: data.len(),
+            });
+        }
+
+        let bytes_per_row = width * 4;
+        let mut pixels = vec![0u8; bytes_per_row * height];
+        let mut texels = [[0u8; 4]; 144];
+        let blocks_wide = width.div_ceil(block_width);
+        for (index, block) in data[..required].chunks_exact(block_bytes).enumerate() {
+            decode_block(format, block, &mut texels);
+            let x0 = (index % blocks_wide) * block_width;
+            let y0 = (index / blocks_wide) * block_height;
+            for y in 0..block_height.min(height - y0) {
+                for x in 0..block_width.min(width - x0) {
+                    let offset = (y0 + y) * bytes_per_row + (x0 + x) * 4;
+                    pixels[offset..offset + 4].copy_from_slice(&texels[y * block_width + x]);
+                }
+            }
+        }
+
+        Ok(TextureData {
+            pixel_format,
+            data: Cow::Owned(pixels),
+            bytes_per_row,
+        })
+    }
+}
+
+/// Decode one block of a format accepted by
+/// [`TextureData::decompressed_format`] into rows of texels.
+fn decode_block(format: PixelFormat, block: &[u8], out: &mut [[u8; 4]; 144]) {
+    use PixelFormat as F;
+    let texels: &mut bc::Texels = (&mut out[..16]).try_into().unwrap();
+    match format.linear_variant() {
+        F::BC1_RGBA => bc::decode_bc1(block, texels),
+        F::BC2_RGBA => bc::decode_bc2(block, texels),
+        F::BC3_RGBA => bc::decode_bc3(block, texels),
+        F::BC4_R_UNORM => bc::decode_bc4(block, false, texels),
+        F::BC4_R_SNORM => bc::decode_bc4(block, true, texels),
+        F::BC5_RG_UNORM => bc::decode_bc5(block, false, texels),
+        F::BC5_RG_SNORM => bc::decode_bc5(block, true, texels),
+        F::BC7_RGBA_UNORM => bc::decode_bc7(block, texels),
+        F::ETC2_RGB8 => etc::decode_etc2(block, false, texels),
+        F::ETC2_RGB8A1 => etc::decode_etc2(block, true, texels),
+        F::EAC_RGBA8 => etc::decode_eac_rgba8(block, texels),
+        F::EAC_R11_UNORM => etc::decode_eac_r11(block, false, texels),
+        F::EAC_R11_SNORM => etc::decode_eac_r11(block, true, texels),
+        F::EAC_RG11_UNORM => etc::decode_eac_rg11(block, false, texels),
+        F::EAC_RG11_SNORM => etc::decode_eac_rg11(block, true, texels),
+        _ => {
+            let (width, height) = format.block_size();
+            astc::decode_astc(block, width as u32, height as u32, format.is_srgb(), out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompress_clips_partial_blocks() {
+        // Two BC1 blocks of solid red for a 6x3 image.
+        let block = [0x00, 0xf8, 0x00, 0xf8, 0, 0, 0, 0];
+        let data = [block, block].concat();
+        let image = TextureData::decompress(PixelFormat::BC1_RGBA_SRGB, &data, 6, 3).unwrap();
+        assert_eq!(image.pixel_format, PixelFormat::RGBA8_UNORM_SRGB);
+        assert_eq!(image.bytes_per_row, 24);
+        assert_eq!(image.data.len(), 72);
+        assert!(image.data.chunks(4).all(|texel| texel == [255, 0, 0, 255]));
+        assert!(image.is_decompressed());
+
+        assert_eq!(
+            TextureData::decompress(PixelFormat::BC1_RGBA, &data, 12, 4).unwrap_err(),
+            DecompressError::DataTooShort {
+                required: 24,
+                length: 16
+            }
+        );
+        assert_eq!(
+            TextureData::decompress(PixelFormat::BC6H_RGB_FLOAT, &data, 4, 4).unwrap_err(),
+            DecompressError::UnsupportedFormat(PixelFormat::BC6H_RGB_FLOAT)
+        );
+    }
+
+    #[test]
+    fn test_check_and_fallback() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let data = [0u8; 16];
+        let image = device
+            .check_and_fallback(PixelFormat::ASTC_4X4_LDR, &data, 4, 4)
+            .unwrap();
+        if device
+            .format_capabilities(PixelFormat::ASTC_4X4_LDR)
+            .supported
+        {
+            assert_eq!(image.pixel_format, PixelFormat::ASTC_4X4_LDR);
+            assert!(!image.is_decompressed());
+        } else {
+            assert_eq!(image.pixel_format, PixelFormat::RGBA8_UNORM);
+            assert_eq!(image.data.len(), 64);
+        }
+    }
+}
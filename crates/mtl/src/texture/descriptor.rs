@@ -115,6 +115,37 @@ impl TextureDescriptor {
         }
     }
 
+    /// Create a descriptor for a 2D depth or stencil render target that
+    /// shaders can also sample, such as a shadow map.
+    ///
+    /// The texture is private, single-level, and usable as both a render
+    /// target and a shader input. Returns `None` if `pixel_format` has
+    /// neither a depth nor a stencil component.
+    pub fn depth_2d(pixel_format: PixelFormat, width: UInteger, height: UInteger) -> Option<Self> {
+        if !pixel_format.is_depth() && !pixel_format.has_stencil() {
+            return None;
+        }
+        let descriptor = Self::texture_2d_descriptor(pixel_format, width, height, false)?;
+        descriptor.set_storage_mode(StorageMode::PRIVATE);
+        descriptor.set_usage(TextureUsage::RENDER_TARGET | TextureUsage::SHADER_READ);
+        Some(descriptor)
+    }
+
+    /// Create a descriptor for a cube depth or stencil render target that
+    /// shaders can also sample, such as a point light shadow map.
+    ///
+    /// Like [`depth_2d`](Self::depth_2d), but with six `size` x `size`
+    /// faces.
+    pub fn depth_cube(pixel_format: PixelFormat, size: UInteger) -> Option<Self> {
+        if !pixel_format.is_depth() && !pixel_format.has_stencil() {
+            return None;
+        }
+        let descriptor = Self::texture_cube_descriptor(pixel_format, size, false)?;
+        descriptor.set_storage_mode(StorageMode::PRIVATE);
+        descriptor.set_usage(TextureUsage::RENDER_TARGET | TextureUsage::SHADER_READ);
+        Some(descriptor)
+    }
+
     /// Create a TextureDescriptor from a raw pointer.
     ///
     /// # Safety
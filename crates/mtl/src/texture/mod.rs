@@ -4,14 +4,22 @@
 //!
 //! Textures store formatted image data for shader access.
 
+mod decompress;
 mod descriptor;
+mod readback;
 mod shared_handle;
 mod texture;
+mod upload;
 mod view_descriptor;
 
+pub use decompress::{DecompressError, TextureData};
 pub use descriptor::TextureDescriptor;
+pub use readback::ReadbackError;
 pub use shared_handle::SharedTextureHandle;
 pub use texture::Texture;
+#[cfg(feature = "blocks")]
+pub use upload::TextureUpload;
+pub use upload::{UploadError, UploadOptions};
 pub use view_descriptor::TextureViewDescriptor;
 
 #[cfg(test)]
@@ -69,6 +77,30 @@ mod tests {
         assert_eq!(desc.texture_type(), crate::enums::TextureType::TYPE_2D);
     }
 
+    #[test]
+    fn test_swizzled_view() {
+        use crate::enums::{TextureSwizzleChannels, TextureUsage};
+
+        let device = crate::device::system_default().expect("no Metal device");
+        let desc = TextureDescriptor::texture_2d_descriptor(PixelFormat::BGRA8_UNORM, 16, 16, true)
+            .unwrap();
+        desc.set_usage(TextureUsage::SHADER_READ | TextureUsage::PIXEL_FORMAT_VIEW);
+        desc.set_swizzle(TextureSwizzleChannels::OPAQUE);
+        assert_eq!(desc.swizzle(), TextureSwizzleChannels::OPAQUE);
+        desc.set_swizzle(TextureSwizzleChannels::IDENTITY);
+        let texture = device
+            .new_texture_with_descriptor(&desc)
+            .expect("failed to create texture");
+        assert_eq!(texture.slice_count(), 1);
+
+        let view = texture
+            .new_swizzled_view(TextureSwizzleChannels::SWAP_RED_BLUE)
+            .expect("failed to create view");
+        assert_eq!(view.swizzle(), TextureSwizzleChannels::SWAP_RED_BLUE);
+        assert_eq!(view.pixel_format(), PixelFormat::BGRA8_UNORM);
+        assert_eq!(view.mipmap_level_count(), texture.mipmap_level_count());
+    }
+
     #[test]
     fn test_shared_texture_handle_size() {
         assert_eq!(
@@ -0,0 +1,217 @@
+//! Blit-based texture readback.
+//!
+//! Private and memoryless textures can't be read from the CPU directly. The
+//! helpers here encode a blit from the texture into a CPU-visible staging
+//! buffer, wait for the GPU, and copy the tightly packed pixel rows out.
+
+use std::fmt;
+#[cfg(feature = "blocks")]
+use std::sync::Mutex;
+
+use mtl_foundation::UInteger;
+
+use super::Texture;
+use crate::enums::{CommandBufferStatus, PixelFormat, ResourceOptions};
+use crate::types::{Origin, Region, Size};
+use crate::{BlitCommandEncoder, Buffer, CommandBuffer, CommandQueue};
+
+/// Error produced by a texture readback.
+#[derive(Debug)]
+pub enum ReadbackError {
+    /// The texture's pixel format has no fixed per-pixel size (compressed,
+    /// packed YUV, or combined depth/stencil).
+    UnsupportedPixelFormat(PixelFormat),
+    /// The region or mipmap level lies outside the texture.
+    InvalidRegion,
+    /// The slice is not below the texture's slice count.
+    InvalidSlice { slice: UInteger, count: UInteger },
+    /// A command buffer, encoder, or staging buffer could not be created.
+    Allocation,
+    /// The command buffer failed on the GPU.
+    CommandBuffer(Option<mtl_foundation::Error>),
+}
+
+impl fmt::Display for ReadbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadbackError::UnsupportedPixelFormat(format) => {
+                write!(f, "pixel format {:?} cannot be read back", format)
+            }
+            ReadbackError::InvalidRegion => write!(f, "region is outside the texture"),
+            ReadbackError::InvalidSlice { slice, count } => write!(
+                f,
+                "slice {} is outside the texture's {} slices",
+                slice, count
+            ),
+            ReadbackError::Allocation => write!(f, "failed to allocate readback resources"),
+            ReadbackError::CommandBuffer(Some(err)) => {
+                write!(f, "readback command buffer failed: {}", err)
+            }
+            ReadbackError::CommandBuffer(None) => write!(f, "readback command buffer failed"),
+        }
+    }
+}
+
+impl std::error::Error for ReadbackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadbackError::CommandBuffer(Some(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// A committed-but-unfinished readback.
+struct PendingReadback {
+    command_buffer: CommandBuffer,
+    staging: Buffer,
+    length: usize,
+}
+
+impl PendingReadback {
+    fn finish(&self) -> Result<Vec<u8>, ReadbackError> {
+        if self.command_buffer.status() == CommandBufferStatus::ERROR {
+            return Err(ReadbackError::CommandBuffer(self.command_buffer.error()));
+        }
+        let ptr = self.staging.contents().ok_or(ReadbackError::Allocation)?;
+        let bytes = unsafe { std::slice::from_raw_parts(ptr as *const u8, self.length) };
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Texture {
+    /// Read back a region of one mipmap level and slice as tightly packed rows.
+    ///
+    /// Encodes a blit into a staging buffer on `queue`, commits it, and blocks
+    /// until the GPU finishes. Works for every storage mode, including
+    /// private textures. For 3D textures, `region` may span several depth
+    /// images and `slice` must be 0. For cube textures, `slice` selects the
+    /// face, and for cube arrays it is `6 * index + face`.
+    ///
+    /// The result holds `width * height * depth * bytes_per_pixel` bytes.
+    pub fn read_region(
+        &self,
+        queue: &CommandQueue,
+        region: Region,
+        level: UInteger,
+        slice: UInteger,
+    ) -> Result<Vec<u8>, ReadbackError> {
+        let pending = self.encode_readback(queue, region, level, slice)?;
+        pending.command_buffer.commit();
+        pending.command_buffer.wait_until_completed();
+        pending.finish()
+    }
+
+    /// Read back a region without blocking.
+    ///
+    /// Like [`read_region`](Self::read_region), but `handler` is called from
+    /// the command buffer's completion handler once the data is available.
+    /// Errors detected before encoding are returned immediately and the
+    /// handler is not called.
+    #[cfg(feature = "blocks")]
+    pub fn read_region_async<F>(
+        &self,
+        queue: &CommandQueue,
+        region: Region,
+        level: UInteger,
+        slice: UInteger,
+        handler: F,
+    ) -> Result<(), ReadbackError>
+    where
+        F: FnOnce(Result<Vec<u8>, ReadbackError>) + Send + 'static,
+    {
+        let pending = self.encode_readback(queue, region, level, slice)?;
+        let command_buffer = pending.command_buffer.clone();
+        // The completion block may be invoked through a `Fn`, so hand the
+        // one-shot state over through a slot.
+        let slot = Mutex::new(Some((pending, handler)));
+        command_buffer.add_completed_handler(move |_| {
+            let taken = slot.lock().ok().and_then(|mut slot| slot.take());
+            if let Some((pending, handler)) = taken {
+                handler(pending.finish());
+            }
+        });
+        command_buffer.commit();
+        Ok(())
+    }
+
+    fn encode_readback(
+        &self,
+        queue: &CommandQueue,
+        region: Region,
+        level: UInteger,
+        slice: UInteger,
+    ) -> Result<PendingReadback, ReadbackError> {
+        let format = self.pixel_format();
+        let bytes_per_pixel = format
+            .bytes_per_pixel()
+            .ok_or(ReadbackError::UnsupportedPixelFormat(format))?;
+
+        let origin: Origin = region.origin;
+        let size: Size = region.size;
+        if !self.contains_region(origin, size, level) {
+            return Err(ReadbackError::InvalidRegion);
+        }
+        let count = self.slice_count();
+        if slice >= count {
+            return Err(ReadbackError::InvalidSlice { slice, count });
+        }
+
+        let bytes_per_row = size.width * bytes_per_pixel;
+        let bytes_per_image = bytes_per_row * size.height;
+        let length = bytes_per_image * size.depth;
+
+        // Discrete GPUs write faster into managed memory; unified memory
+        // devices read shared memory directly.
+        let device = self.device();
+        let unified = device.has_unified_memory();
+        let options = if unified {
+            ResourceOptions::STORAGE_MODE_SHARED
+        } else {
+            ResourceOptions::STORAGE_MODE_MANAGED
+        };
+        let staging = device
+            .new_buffer(length, options)
+            .ok_or(ReadbackError::Allocation)?;
+
+        let command_buffer = queue.command_buffer().ok_or(ReadbackError::Allocation)?;
+        let encoder =
+            unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+                .ok_or(ReadbackError::Allocation)?;
+        encoder.copy_from_texture_to_buffer(
+            self,
+            slice,
+            level,
+            origin,
+            size,
+            &staging,
+            0,
+            bytes_per_row,
+            bytes_per_image,
+        );
+        if !unified {
+            encoder.synchronize_buffer(&staging);
+        }
+        encoder.end_encoding();
+
+        Ok(PendingReadback {
+            command_buffer,
+            staging,
+            length,
+        })
+    }
+
+    pub(crate) fn contains_region(&self, origin: Origin, size: Size, level: UInteger) -> bool {
+        if level >= self.mipmap_level_count() || size.width == 0 || size.height == 0 {
+            return false;
+        }
+        let mip = |extent: UInteger| (extent >> level).max(1);
+        let fits = |start: UInteger, len: UInteger, extent: UInteger| {
+            start.checked_add(len).is_some_and(|end| end <= mip(extent))
+        };
+        size.depth != 0
+            && fits(origin.x, size.width, self.width())
+            && fits(origin.y, size.height, self.height())
+            && fits(origin.z, size.depth, self.depth())
+    }
+}
@@ -7,7 +7,7 @@ use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{msg_send_0, msg_send_1, sel};
 
 use crate::enums::{
-    PixelFormat, ResourceOptions, TextureCompressionType, TextureSparseTier,
+    PixelFormat, ResourceOptions, StorageMode, TextureCompressionType, TextureSparseTier,
     TextureSwizzleChannels, TextureType, TextureUsage,
 };
 use crate::types::ResourceID;
@@ -370,6 +370,28 @@ impl Texture {
         }
     }
 
+    /// Create a view of every level and slice that reads through `swizzle`.
+    ///
+    /// The view keeps the texture's pixel format and type.
+    pub fn new_swizzled_view(&self, swizzle: TextureSwizzleChannels) -> Option<Texture> {
+        self.new_texture_view_with_swizzle(
+            self.pixel_format(),
+            self.texture_type(),
+            mtl_foundation::Range::new(0, self.mipmap_level_count()),
+            mtl_foundation::Range::new(0, self.slice_count()),
+            swizzle,
+        )
+    }
+
+    /// Get the number of slices a view can address: six per cube, one per
+    /// array element, and one for 3D textures.
+    pub fn slice_count(&self) -> UInteger {
+        match self.texture_type() {
+            TextureType::TYPE_CUBE | TextureType::TYPE_CUBE_ARRAY => self.array_length() * 6,
+            _ => self.array_length(),
+        }
+    }
+
     /// Create a shared texture handle for cross-process sharing.
     ///
     /// C++ equivalent: `SharedTextureHandle* newSharedTextureHandle()`
@@ -397,6 +419,16 @@ impl Texture {
         }
     }
 
+    /// Create a view that reads only the stencil component.
+    ///
+    /// Maps combined depth/stencil formats to `X32_STENCIL8` or
+    /// `X24_STENCIL8` so shaders can sample stencil values as unsigned
+    /// integers. Returns `None` if the texture has no stencil component.
+    pub fn new_stencil_view(&self) -> Option<Texture> {
+        let format = self.pixel_format().stencil_view_format()?;
+        self.new_texture_view_with_pixel_format(format)
+    }
+
     // =========================================================================
     // Texture Data Operations
     // =========================================================================
@@ -582,6 +614,14 @@ impl Texture {
         unsafe { msg_send_0(self.as_ptr(), sel!(resourceOptions)) }
     }
 
+    /// Get the storage mode.
+    ///
+    /// C++ equivalent: `StorageMode storageMode() const`
+    #[inline]
+    pub fn storage_mode(&self) -> StorageMode {
+        unsafe { msg_send_0(self.as_ptr(), sel!(storageMode)) }
+    }
+
     /// Get the allocated size.
     ///
     /// C++ equivalent: `NS::UInteger allocatedSize() const`
@@ -0,0 +1,339 @@
+//! Staged texture uploads.
+//!
+//! Private textures can't be written from the CPU, and even for shared
+//! textures the row and image pitch of a buffer copy are easy to get wrong.
+//! The helpers here copy the caller's bytes into a CPU-visible staging
+//! buffer, encode a blit into the texture, optionally generate the remaining
+//! mipmap levels, and either block until the GPU finishes or return a
+//! future.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let texture = device.new_texture_with_descriptor(&private_descriptor)?;
+//! texture.upload_from_bytes(&queue, &pixels, width * 4)?;
+//!
+//! // Or, with mipmaps and without blocking:
+//! let options = UploadOptions { generate_mipmaps: true, ..Default::default() };
+//! texture.upload_from_bytes_async(&queue, &pixels, width * 4, options)?.await?;
+//! ```
+
+use std::fmt;
+#[cfg(feature = "blocks")]
+use std::future::Future;
+#[cfg(feature = "blocks")]
+use std::pin::Pin;
+#[cfg(feature = "blocks")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "blocks")]
+use std::task::{Context, Poll, Waker};
+
+use mtl_foundation::UInteger;
+
+use super::Texture;
+#[cfg(feature = "blocks")]
+use crate::cancel::{CancelRegistration, CancellationToken};
+use crate::enums::{CommandBufferStatus, PixelFormat, ResourceOptions, TextureType};
+use crate::types::{Origin, Size};
+use crate::{BlitCommandEncoder, CUBE_FACE_COUNT, CommandBuffer, CommandQueue};
+
+/// Error produced by a texture upload.
+#[derive(Debug)]
+pub enum UploadError {
+    /// The texture's pixel format has no fixed row pitch (packed YUV or
+    /// combined depth/stencil).
+    UnsupportedPixelFormat(PixelFormat),
+    /// `bytes_per_row` is smaller than one row of the texture.
+    InvalidBytesPerRow {
+        /// The smallest valid row pitch.
+        minimum: UInteger,
+        /// The row pitch passed in.
+        bytes_per_row: UInteger,
+    },
+    /// The data is shorter than the texture at the given row pitch.
+    DataTooShort {
+        /// Bytes needed to fill the texture.
+        required: usize,
+        /// Bytes passed in.
+        length: usize,
+    },
+    /// The slice lies outside the texture.
+    InvalidSlice(UInteger),
+    /// A command buffer, encoder, or staging buffer could not be created.
+    Allocation,
+    /// The command buffer failed on the GPU.
+    CommandBuffer(Option<mtl_foundation::Error>),
+    /// The upload's cancellation token was cancelled before it finished.
+    Cancelled,
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UploadError::UnsupportedPixelFormat(format) => {
+                write!(f, "pixel format {:?} cannot be uploaded", format)
+            }
+            UploadError::InvalidBytesPerRow {
+                minimum,
+                bytes_per_row,
+            } => write!(
+                f,
+                "bytes per row {} is less than the row size {}",
+                bytes_per_row, minimum
+            ),
+            UploadError::DataTooShort { required, length } => write!(
+                f,
+                "upload needs {} bytes but only {} were given",
+                required, length
+            ),
+            UploadError::InvalidSlice(slice) => write!(f, "slice {} is outside the texture", slice),
+            UploadError::Allocation => write!(f, "failed to allocate upload resources"),
+            UploadError::CommandBuffer(Some(err)) => {
+                write!(f, "upload command buffer failed: {}", err)
+            }
+            UploadError::CommandBuffer(None) => write!(f, "upload command buffer failed"),
+            UploadError::Cancelled => write!(f, "upload was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UploadError::CommandBuffer(Some(err)) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Options for [`Texture::upload_from_bytes_with_options`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct UploadOptions {
+    /// The array slice or cube face to write. For cube arrays this is
+    /// `cube * 6 + face`.
+    pub slice: UInteger,
+    /// Regenerate every mipmap level below level 0 after the copy.
+    ///
+    /// The pixel format must support filtering and rendering.
+    pub generate_mipmaps: bool,
+}
+
+fn finish(command_buffer: &CommandBuffer) -> Result<(), UploadError> {
+    if command_buffer.status() == CommandBufferStatus::ERROR {
+        return Err(UploadError::CommandBuffer(command_buffer.error()));
+    }
+    Ok(())
+}
+
+impl Texture {
+    /// Upload `data` to mipmap level 0 of the first slice.
+    ///
+    /// `data` holds rows `bytes_per_row` bytes apart; 3D textures take their
+    /// depth images back to back. Blocks until the GPU finishes. Works for
+    /// every storage mode except memoryless.
+    pub fn upload_from_bytes(
+        &self,
+        queue: &CommandQueue,
+        data: &[u8],
+        bytes_per_row: UInteger,
+    ) -> Result<(), UploadError> {
+        self.upload_from_bytes_with_options(queue, data, bytes_per_row, UploadOptions::default())
+    }
+
+    /// Upload `data` to mipmap level 0 of a slice, optionally generating
+    /// mipmaps.
+    ///
+    /// Like [`upload_from_bytes`](Self::upload_from_bytes), with the slice
+    /// and mipmap generation chosen by `options`.
+    pub fn upload_from_bytes_with_options(
+        &self,
+        queue: &CommandQueue,
+        data: &[u8],
+        bytes_per_row: UInteger,
+        options: UploadOptions,
+    ) -> Result<(), UploadError> {
+        let command_buffer = self.encode_upload(queue, data, bytes_per_row, options)?;
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+        finish(&command_buffer)
+    }
+
+    /// Upload without blocking.
+    ///
+    /// Like [`upload_from_bytes_with_options`](Self::upload_from_bytes_with_options),
+    /// but returns a future that resolves when the GPU has finished. The data
+    /// is copied to the staging buffer before this returns. Errors detected
+    /// before encoding are returned immediately.
+    #[cfg(feature = "blocks")]
+    pub fn upload_from_bytes_async(
+        &self,
+        queue: &CommandQueue,
+        data: &[u8],
+        bytes_per_row: UInteger,
+        options: UploadOptions,
+    ) -> Result<TextureUpload, UploadError> {
+        let command_buffer = self.encode_upload(queue, data, bytes_per_row, options)?;
+        let state = Arc::new(Mutex::new(UploadState::default()));
+        let notified = Arc::clone(&state);
+        command_buffer.add_completed_handler(move |command_buffer| {
+            let waker = {
+                let mut state = notified.lock().unwrap();
+                state.done = true;
+                if !state.cancelled {
+                    state.result = Some(finish(command_buffer));
+                }
+                state.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        });
+        command_buffer.commit();
+        Ok(TextureUpload {
+            state,
+            cancellation: None,
+        })
+    }
+
+    /// Validate the upload, fill a staging buffer and encode the copy.
+    fn encode_upload(
+        &self,
+        queue: &CommandQueue,
+        data: &[u8],
+        bytes_per_row: UInteger,
+        options: UploadOptions,
+    ) -> Result<CommandBuffer, UploadError> {
+        let format = self.pixel_format();
+        let minimum = format
+            .bytes_per_row(self.width())
+            .ok_or(UploadError::UnsupportedPixelFormat(format))?;
+        if bytes_per_row < minimum {
+            return Err(UploadError::InvalidBytesPerRow {
+                minimum,
+                bytes_per_row,
+            });
+        }
+
+        let slices = match self.texture_type() {
+            TextureType::TYPE_CUBE | TextureType::TYPE_CUBE_ARRAY => {
+                self.array_length() * CUBE_FACE_COUNT
+            }
+            _ => self.array_length(),
+        };
+        if options.slice >= slices {
+            return Err(UploadError::InvalidSlice(options.slice));
+        }
+
+        // Block-compressed formats are uploaded one row of blocks at a time.
+        let (_, block_height) = format.block_size();
+        let rows = self.height().div_ceil(block_height);
+        let bytes_per_image = bytes_per_row * rows;
+        let required = bytes_per_image * self.depth();
+        if data.len() < required {
+            return Err(UploadError::DataTooShort {
+                required,
+                length: data.len(),
+            });
+        }
+
+        let device = self.device();
+        let staging = device
+            .new_buffer_with_bytes(&data[..required], ResourceOptions::STORAGE_MODE_SHARED)
+            .ok_or(UploadError::Allocation)?;
+
+        let command_buffer = queue.command_buffer().ok_or(UploadError::Allocation)?;
+        let encoder =
+            unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+                .ok_or(UploadError::Allocation)?;
+        encoder.copy_from_buffer_to_texture(
+            &staging,
+            0,
+            bytes_per_row,
+            bytes_per_image,
+            Size::new(self.width(), self.height(), self.depth()),
+            self,
+            options.slice,
+            0,
+            Origin::new(0, 0, 0),
+        );
+        if options.generate_mipmaps && self.mipmap_level_count() > 1 {
+            encoder.generate_mipmaps(self);
+        }
+        encoder.end_encoding();
+        Ok(command_buffer)
+    }
+}
+
+// ============================================================================
+// TextureUpload
+// ============================================================================
+
+#[cfg(feature = "blocks")]
+#[derive(Default)]
+struct UploadState {
+    done: bool,
+    cancelled: bool,
+    result: Option<Result<(), UploadError>>,
+    waker: Option<Waker>,
+}
+
+/// Future returned by [`Texture::upload_from_bytes_async`].
+#[cfg(feature = "blocks")]
+pub struct TextureUpload {
+    state: Arc<Mutex<UploadState>>,
+    cancellation: Option<CancelRegistration>,
+}
+
+#[cfg(feature = "blocks")]
+impl TextureUpload {
+    /// Check if the GPU has finished the upload.
+    pub fn is_complete(&self) -> bool {
+        self.state.lock().unwrap().done
+    }
+
+    /// Resolve with [`UploadError::Cancelled`] as soon as `token` is
+    /// cancelled, instead of waiting for the GPU.
+    ///
+    /// The committed copy still runs; only the wait is dropped.
+    pub fn with_cancellation(mut self, token: &CancellationToken) -> Self {
+        let state = Arc::clone(&self.state);
+        self.cancellation = Some(token.on_cancel(move || {
+            let waker = {
+                let mut state = state.lock().unwrap();
+                if state.done {
+                    return;
+                }
+                state.cancelled = true;
+                state.result = Some(Err(UploadError::Cancelled));
+                state.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }));
+        self
+    }
+}
+
+#[cfg(feature = "blocks")]
+impl Future for TextureUpload {
+    type Output = Result<(), UploadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(result) = state.result.take() {
+            return Poll::Ready(result);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "blocks")]
+impl fmt::Debug for TextureUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextureUpload")
+            .field("complete", &self.is_complete())
+            .finish()
+    }
+}
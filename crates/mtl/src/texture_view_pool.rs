@@ -12,7 +12,7 @@ use mtl_foundation::{Referencing, UInteger};
 use mtl_sys::{Class, msg_send_0, msg_send_1, msg_send_2, msg_send_3, sel};
 
 use crate::types::ResourceID;
-use crate::{Buffer, Texture, TextureDescriptor};
+use crate::{Buffer, Texture, TextureDescriptor, TextureViewDescriptor};
 
 // ============================================================================
 // ResourceViewPoolDescriptor
@@ -270,41 +270,47 @@ impl TextureViewPool {
 
     /// Set a texture view at the specified index.
     ///
-    /// C++ equivalent: `void setTextureView(const Texture*, NS::UInteger)`
-    pub fn set_texture_view(&self, texture: &Texture, index: UInteger) {
+    /// Returns the resource ID of the view.
+    ///
+    /// C++ equivalent: `ResourceID setTextureView(const Texture*, NS::UInteger)`
+    pub fn set_texture_view(&self, texture: &Texture, index: UInteger) -> ResourceID {
         unsafe {
-            msg_send_2::<(), *const c_void, UInteger>(
+            msg_send_2::<ResourceID, *const c_void, UInteger>(
                 self.as_ptr(),
                 sel!(setTextureView:atIndex:),
                 texture.as_ptr(),
                 index,
-            );
+            )
         }
     }
 
     /// Set a texture view with a descriptor at the specified index.
     ///
-    /// C++ equivalent: `void setTextureView(const Texture*, const TextureViewDescriptor*, NS::UInteger)`
+    /// Returns the resource ID of the view.
+    ///
+    /// C++ equivalent: `ResourceID setTextureView(const Texture*, const TextureViewDescriptor*, NS::UInteger)`
     pub fn set_texture_view_with_descriptor(
         &self,
         texture: &Texture,
-        descriptor: &crate::TextureViewDescriptor,
+        descriptor: &TextureViewDescriptor,
         index: UInteger,
-    ) {
+    ) -> ResourceID {
         unsafe {
-            msg_send_3::<(), *const c_void, *const c_void, UInteger>(
+            msg_send_3::<ResourceID, *const c_void, *const c_void, UInteger>(
                 self.as_ptr(),
                 sel!(setTextureView:descriptor:atIndex:),
                 texture.as_ptr(),
                 descriptor.as_ptr(),
                 index,
-            );
+            )
         }
     }
 
     /// Set a texture view from a buffer.
     ///
-    /// C++ equivalent: `void setTextureViewFromBuffer(const Buffer*, const TextureDescriptor*, NS::UInteger, NS::UInteger, NS::UInteger)`
+    /// Returns the resource ID of the view.
+    ///
+    /// C++ equivalent: `ResourceID setTextureViewFromBuffer(const Buffer*, const TextureDescriptor*, NS::UInteger, NS::UInteger, NS::UInteger)`
     pub fn set_texture_view_from_buffer(
         &self,
         buffer: &Buffer,
@@ -312,9 +318,16 @@ impl TextureViewPool {
         offset: UInteger,
         bytes_per_row: UInteger,
         index: UInteger,
-    ) {
+    ) -> ResourceID {
         unsafe {
-            mtl_sys::msg_send_5::<(), *const c_void, *const c_void, UInteger, UInteger, UInteger>(
+            mtl_sys::msg_send_5::<
+                ResourceID,
+                *const c_void,
+                *const c_void,
+                UInteger,
+                UInteger,
+                UInteger,
+            >(
                 self.as_ptr(),
                 sel!(setTextureViewFromBuffer:descriptor:offset:bytesPerRow:atIndex:),
                 buffer.as_ptr(),
@@ -322,13 +335,13 @@ impl TextureViewPool {
                 offset,
                 bytes_per_row,
                 index,
-            );
+            )
         }
     }
 
     /// Set a texture view from a buffer (raw pointer version).
     ///
-    /// C++ equivalent: `void setTextureViewFromBuffer(const Buffer*, const TextureDescriptor*, NS::UInteger, NS::UInteger, NS::UInteger)`
+    /// C++ equivalent: `ResourceID setTextureViewFromBuffer(const Buffer*, const TextureDescriptor*, NS::UInteger, NS::UInteger, NS::UInteger)`
     ///
     /// # Safety
     ///
@@ -340,9 +353,16 @@ impl TextureViewPool {
         offset: UInteger,
         bytes_per_row: UInteger,
         index: UInteger,
-    ) {
+    ) -> ResourceID {
         unsafe {
-            mtl_sys::msg_send_5::<(), *const c_void, *const c_void, UInteger, UInteger, UInteger>(
+            mtl_sys::msg_send_5::<
+                ResourceID,
+                *const c_void,
+                *const c_void,
+                UInteger,
+                UInteger,
+                UInteger,
+            >(
                 self.as_ptr(),
                 sel!(setTextureViewFromBuffer:descriptor:offset:bytesPerRow:atIndex:),
                 buffer,
@@ -350,9 +370,59 @@ impl TextureViewPool {
                 offset,
                 bytes_per_row,
                 index,
-            );
+            )
         }
     }
+
+    // =========================================================================
+    // Typed Views
+    // =========================================================================
+
+    /// Get the number of views the pool holds.
+    ///
+    /// Same as [`resource_view_count`](Self::resource_view_count).
+    #[inline]
+    pub fn capacity(&self) -> UInteger {
+        self.resource_view_count()
+    }
+
+    /// Create a view of `texture` described by `descriptor` at `index` and
+    /// return its resource ID, for writing into argument buffers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below [`capacity`](Self::capacity).
+    pub fn set_view(
+        &self,
+        index: UInteger,
+        texture: &Texture,
+        descriptor: &TextureViewDescriptor,
+    ) -> ResourceID {
+        let capacity = self.capacity();
+        assert!(
+            index < capacity,
+            "view index {index} out of range for a pool of {capacity} views"
+        );
+        self.set_texture_view_with_descriptor(texture, descriptor, index)
+    }
+
+    /// Get the resource ID of the view at `index`, or `None` if `index` is
+    /// not below [`capacity`](Self::capacity).
+    ///
+    /// Views in a pool have consecutive IDs starting at
+    /// [`base_resource_id`](Self::base_resource_id), so this is valid
+    /// before the view is set.
+    pub fn view_resource_id(&self, index: UInteger) -> Option<ResourceID> {
+        (index < self.capacity())
+            .then(|| ResourceID::new(self.base_resource_id()._impl + index as u64))
+    }
+
+    /// Iterate over the resource IDs of every view in the pool, in index
+    /// order.
+    pub fn resource_ids(&self) -> impl Iterator<Item = ResourceID> + '_ {
+        let base = self.base_resource_id()._impl;
+        (0..self.capacity() as u64).map(move |index| ResourceID::new(base + index))
+    }
 }
 
 impl Clone for TextureViewPool {
@@ -403,6 +473,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_texture_view_pool_views() {
+        use crate::enums::PixelFormat;
+
+        let device = crate::device::system_default().expect("no Metal device");
+        let pool_descriptor = ResourceViewPoolDescriptor::new().unwrap();
+        pool_descriptor.set_resource_view_count(4);
+        let Ok(pool) = device.new_texture_view_pool(&pool_descriptor) else {
+            return;
+        };
+        assert_eq!(pool.capacity(), 4);
+
+        let ids: Vec<_> = pool.resource_ids().collect();
+        assert_eq!(ids.len(), 4);
+        assert_eq!(ids[0], pool.base_resource_id());
+        assert_eq!(pool.view_resource_id(3), Some(ids[3]));
+        assert_eq!(pool.view_resource_id(4), None);
+
+        let descriptor =
+            TextureDescriptor::texture_2d_descriptor(PixelFormat::RGBA8_UNORM, 8, 8, false)
+                .unwrap();
+        let texture = device.new_texture_with_descriptor(&descriptor).unwrap();
+        let view = TextureViewDescriptor::new().unwrap();
+        view.set_pixel_format(PixelFormat::RGBA8_UNORM);
+        assert_eq!(pool.set_view(2, &texture, &view), ids[2]);
+    }
+
     #[test]
     fn test_texture_view_pool_size() {
         assert_eq!(
@@ -0,0 +1,397 @@
+//! Cross-queue GPU timeline alignment for profiling.
+//!
+//! [`QueueTimelines`] tracks several command queues at once. At the end of
+//! each frame, every queue marks its last command buffer with
+//! [`mark_frame_boundary`](QueueTimelines::mark_frame_boundary), which
+//! encodes a signal of the frame number on a [`SharedEvent`] owned by that
+//! queue. Once every queue has passed the boundary,
+//! [`poll`](QueueTimelines::poll) turns the marked command buffers' GPU
+//! start and end times into a [`FrameTimeline`], with each queue's span on
+//! the shared host clock and its offset from the first queue.
+//!
+//! Counter sample buffers report raw GPU ticks rather than host time.
+//! [`TimestampCalibration`] maps those ticks onto the same clock using pairs
+//! of [`Device::sample_timestamps`] readings, so counter samples can be drawn
+//! on the same overlay as the queue spans.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut timelines = QueueTimelines::new(&device, 2).unwrap();
+//!
+//! // Each frame:
+//! timelines.mark_frame_boundary(0, frame, &graphics_cb);
+//! timelines.mark_frame_boundary(1, frame, &compute_cb);
+//! graphics_cb.commit();
+//! compute_cb.commit();
+//!
+//! timelines.poll();
+//! if let Some(timeline) = timelines.latest() {
+//!     overlay.draw_offset("compute", timeline.offset(1));
+//! }
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::time::Duration;
+
+use mtl_foundation::UInteger;
+
+use crate::counter::{COUNTER_ERROR_VALUE, CounterResultTimestamp, CounterSampleBuffer};
+use crate::enums::{CommandBufferStatus, CounterSampleBufferError};
+use crate::sync::SharedEvent;
+use crate::{CommandBuffer, Device};
+
+/// Default number of completed frames kept by [`QueueTimelines`].
+pub const DEFAULT_TIMELINE_HISTORY: usize = 64;
+
+// ============================================================================
+// TimestampCalibration
+// ============================================================================
+
+/// A linear mapping from GPU timestamp ticks to host time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimestampCalibration {
+    cpu_origin: u64,
+    gpu_origin: u64,
+    nanos_per_tick: f64,
+}
+
+impl TimestampCalibration {
+    /// Build a calibration from two `(cpu, gpu)` pairs returned by
+    /// [`Device::sample_timestamps`].
+    ///
+    /// Returns `None` if the GPU clock did not advance between the samples.
+    pub fn from_samples(first: (u64, u64), second: (u64, u64)) -> Option<Self> {
+        let (cpu0, gpu0) = first;
+        let (cpu1, gpu1) = second;
+        if gpu1 <= gpu0 {
+            return None;
+        }
+        Some(Self {
+            cpu_origin: cpu0,
+            gpu_origin: gpu0,
+            nanos_per_tick: (cpu1 as f64 - cpu0 as f64) / (gpu1 - gpu0) as f64,
+        })
+    }
+
+    /// Sample the device's clocks twice, `interval` apart, and build a
+    /// calibration from the pair.
+    ///
+    /// Blocks the calling thread for `interval`; a few milliseconds keeps
+    /// the rounding error of the tick rate small. Clocks drift apart over
+    /// time, so long-running tools should recalibrate periodically.
+    pub fn measure(device: &Device, interval: Duration) -> Option<Self> {
+        let first = device.sample_timestamps();
+        std::thread::sleep(interval);
+        Self::from_samples(first, device.sample_timestamps())
+    }
+
+    /// Get the number of host nanoseconds per GPU tick.
+    pub fn nanos_per_tick(&self) -> f64 {
+        self.nanos_per_tick
+    }
+
+    /// Convert a GPU timestamp to host nanoseconds.
+    pub fn gpu_to_cpu_nanos(&self, gpu_timestamp: u64) -> f64 {
+        let ticks = gpu_timestamp as f64 - self.gpu_origin as f64;
+        self.cpu_origin as f64 + ticks * self.nanos_per_tick
+    }
+
+    /// Convert a GPU timestamp to host seconds, the clock used by
+    /// [`CommandBuffer::gpu_start_time`].
+    pub fn gpu_to_seconds(&self, gpu_timestamp: u64) -> f64 {
+        self.gpu_to_cpu_nanos(gpu_timestamp) / 1e9
+    }
+
+    /// Resolve `length` timestamp samples starting at `location` and
+    /// convert them to host nanoseconds.
+    ///
+    /// `sample_buffer` must sample the timestamp counter set. Samples that
+    /// Metal couldn't take are `None`.
+    pub fn resolve_timestamps(
+        &self,
+        sample_buffer: &CounterSampleBuffer,
+        location: UInteger,
+        length: UInteger,
+    ) -> Result<Vec<Option<f64>>, CounterSampleBufferError> {
+        let data = sample_buffer.resolve_counter_range(location, length)?;
+        Ok(data
+            .bytes()
+            .chunks_exact(std::mem::size_of::<CounterResultTimestamp>())
+            .map(|chunk| u64::from_ne_bytes(chunk.try_into().unwrap()))
+            .map(|timestamp| {
+                (timestamp != COUNTER_ERROR_VALUE as u64).then(|| self.gpu_to_cpu_nanos(timestamp))
+            })
+            .collect())
+    }
+}
+
+// ============================================================================
+// FrameTimeline
+// ============================================================================
+
+/// The GPU execution span of one queue's marked command buffer, in host
+/// seconds.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct QueueSpan {
+    /// When the GPU started executing the command buffer.
+    pub gpu_start: f64,
+    /// When the GPU finished executing the command buffer; the frame
+    /// boundary for this queue.
+    pub gpu_end: f64,
+}
+
+/// The aligned spans of every queue for one frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameTimeline {
+    /// The frame number passed to
+    /// [`mark_frame_boundary`](QueueTimelines::mark_frame_boundary).
+    pub frame: u64,
+    /// One entry per queue. `None` if the queue's command buffer failed.
+    pub spans: Vec<Option<QueueSpan>>,
+}
+
+impl FrameTimeline {
+    /// Get the earliest start time across all queues.
+    pub fn start(&self) -> Option<f64> {
+        self.spans
+            .iter()
+            .flatten()
+            .map(|span| span.gpu_start)
+            .reduce(f64::min)
+    }
+
+    /// Get the latest end time across all queues.
+    pub fn end(&self) -> Option<f64> {
+        self.spans
+            .iter()
+            .flatten()
+            .map(|span| span.gpu_end)
+            .reduce(f64::max)
+    }
+
+    /// Get how far `queue`'s frame boundary lags queue 0's, in seconds.
+    ///
+    /// Negative if `queue` finished first.
+    pub fn offset(&self, queue: usize) -> Option<f64> {
+        let reference = self.spans.first().copied().flatten()?;
+        let span = self.spans.get(queue).copied().flatten()?;
+        Some(span.gpu_end - reference.gpu_end)
+    }
+}
+
+// ============================================================================
+// QueueTimelines
+// ============================================================================
+
+struct Track {
+    event: SharedEvent,
+    pending: VecDeque<(u64, CommandBuffer)>,
+}
+
+/// Collects per-frame GPU spans from several queues and aligns them.
+pub struct QueueTimelines {
+    device: Device,
+    tracks: Vec<Track>,
+    partial: BTreeMap<u64, Vec<Option<Option<QueueSpan>>>>,
+    completed: VecDeque<FrameTimeline>,
+    history: usize,
+    first_sample: (u64, u64),
+    calibration: Option<TimestampCalibration>,
+}
+
+impl QueueTimelines {
+    /// Create a tracker for `queue_count` queues.
+    ///
+    /// Returns `None` if the shared events cannot be created.
+    pub fn new(device: &Device, queue_count: usize) -> Option<Self> {
+        let tracks = (0..queue_count)
+            .map(|_| {
+                Some(Track {
+                    event: device.new_shared_event()?,
+                    pending: VecDeque::new(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            device: device.clone(),
+            tracks,
+            partial: BTreeMap::new(),
+            completed: VecDeque::new(),
+            history: DEFAULT_TIMELINE_HISTORY,
+            first_sample: device.sample_timestamps(),
+            calibration: None,
+        })
+    }
+
+    /// Set how many completed frames to keep.
+    pub fn with_history(mut self, history: usize) -> Self {
+        self.history = history.max(1);
+        self
+    }
+
+    /// Get the number of tracked queues.
+    pub fn queue_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Get the shared event `queue` signals at each frame boundary.
+    ///
+    /// Its signaled value is the last frame the queue has finished.
+    pub fn event(&self, queue: usize) -> &SharedEvent {
+        &self.tracks[queue].event
+    }
+
+    /// Mark `command_buffer` as the last work `queue` submits for `frame`.
+    ///
+    /// Encodes a signal of `frame` on the queue's event, so frame numbers
+    /// must increase per queue. Call before committing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `queue` is out of range.
+    pub fn mark_frame_boundary(
+        &mut self,
+        queue: usize,
+        frame: u64,
+        command_buffer: &CommandBuffer,
+    ) {
+        let track = &mut self.tracks[queue];
+        command_buffer.encode_signal_event(track.event.as_event(), frame);
+        track.pending.push_back((frame, command_buffer.clone()));
+    }
+
+    /// Make `command_buffer` wait on the GPU until `queue` has passed the
+    /// boundary of `frame`.
+    ///
+    /// Use this to start queues' frames together so their spans line up.
+    pub fn wait_for_boundary(&self, command_buffer: &CommandBuffer, queue: usize, frame: u64) {
+        command_buffer.encode_wait_for_event(self.tracks[queue].event.as_event(), frame);
+    }
+
+    /// Collect finished command buffers and assemble completed frames.
+    ///
+    /// Also refreshes the [`calibration`](Self::calibration). Returns the
+    /// number of frames completed by this call.
+    pub fn poll(&mut self) -> usize {
+        let sample = self.device.sample_timestamps();
+        if let Some(calibration) = TimestampCalibration::from_samples(self.first_sample, sample) {
+            self.calibration = Some(calibration);
+        }
+
+        let queue_count = self.tracks.len();
+        for (queue, track) in self.tracks.iter_mut().enumerate() {
+            while let Some((frame, command_buffer)) = track.pending.front() {
+                let status = command_buffer.status();
+                let span = if status == CommandBufferStatus::COMPLETED {
+                    Some(QueueSpan {
+                        gpu_start: command_buffer.gpu_start_time(),
+                        gpu_end: command_buffer.gpu_end_time(),
+                    })
+                } else if status == CommandBufferStatus::ERROR {
+                    None
+                } else {
+                    break;
+                };
+                self.partial
+                    .entry(*frame)
+                    .or_insert_with(|| vec![None; queue_count])[queue] = Some(span);
+                track.pending.pop_front();
+            }
+        }
+
+        let mut count = 0;
+        while let Some(entry) = self.partial.first_entry() {
+            if entry.get().iter().any(Option::is_none) {
+                break;
+            }
+            let (frame, spans) = entry.remove_entry();
+            self.completed.push_back(FrameTimeline {
+                frame,
+                spans: spans.into_iter().flatten().collect(),
+            });
+            if self.completed.len() > self.history {
+                self.completed.pop_front();
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Get the most recent completed frame.
+    pub fn latest(&self) -> Option<&FrameTimeline> {
+        self.completed.back()
+    }
+
+    /// Iterate over completed frames, oldest first.
+    pub fn frames(&self) -> impl Iterator<Item = &FrameTimeline> {
+        self.completed.iter()
+    }
+
+    /// Get the mapping from GPU counter timestamps to host time, once
+    /// [`poll`](Self::poll) has taken a second timestamp sample.
+    pub fn calibration(&self) -> Option<TimestampCalibration> {
+        self.calibration
+    }
+}
+
+impl fmt::Debug for QueueTimelines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueueTimelines")
+            .field("queue_count", &self.tracks.len())
+            .field("pending_frames", &self.partial.len())
+            .field("completed_frames", &self.completed.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibration() {
+        let calibration = TimestampCalibration::from_samples((1_000, 100), (3_000, 600)).unwrap();
+        assert_eq!(calibration.nanos_per_tick(), 4.0);
+        assert_eq!(calibration.gpu_to_cpu_nanos(100), 1_000.0);
+        assert_eq!(calibration.gpu_to_cpu_nanos(350), 2_000.0);
+        assert_eq!(calibration.gpu_to_cpu_nanos(50), 800.0);
+        assert_eq!(calibration.gpu_to_seconds(600), 3e-6);
+        assert!(TimestampCalibration::from_samples((0, 5), (10, 5)).is_none());
+    }
+
+    #[test]
+    fn test_measure_calibration() {
+        let device = crate::device::system_default().expect("no Metal device");
+        let calibration = TimestampCalibration::measure(&device, Duration::from_millis(5)).unwrap();
+        assert!(calibration.nanos_per_tick() > 0.0);
+        let (cpu, gpu) = device.sample_timestamps();
+        let error = (calibration.gpu_to_cpu_nanos(gpu) - cpu as f64).abs();
+        assert!(error < 1e6, "calibration off by {} ns", error);
+    }
+
+    #[test]
+    fn test_frame_timeline() {
+        let timeline = FrameTimeline {
+            frame: 7,
+            spans: vec![
+                Some(QueueSpan {
+                    gpu_start: 1.0,
+                    gpu_end: 1.5,
+                }),
+                None,
+                Some(QueueSpan {
+                    gpu_start: 0.5,
+                    gpu_end: 1.25,
+                }),
+            ],
+        };
+        assert_eq!(timeline.start(), Some(0.5));
+        assert_eq!(timeline.end(), Some(1.5));
+        assert_eq!(timeline.offset(0), Some(0.0));
+        assert_eq!(timeline.offset(1), None);
+        assert_eq!(timeline.offset(2), Some(-0.25));
+        assert_eq!(timeline.offset(3), None);
+    }
+}
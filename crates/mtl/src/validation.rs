@@ -0,0 +1,419 @@
+//! Rust-side validation of encoder and command buffer usage.
+//!
+//! With the `validation` feature enabled, command buffers and encoders
+//! track their state and panic with a description of the mistake before
+//! making a call that Metal would reject with an abort (or silently
+//! ignore without its validation layer):
+//!
+//! - committing a command buffer twice, or with an encoder still open
+//! - creating an encoder after commit, or while another encoder on the
+//!   same command buffer hasn't ended
+//! - encoding with an encoder after [`end_encoding`](crate::RenderCommandEncoder::end_encoding)
+//! - binding buffers, textures or samplers beyond the argument table limits
+//! - setting a render pipeline whose color, depth or stencil attachment
+//!   formats don't match the render pass
+//!
+//! Encoder state is kept per command buffer and discarded when it is
+//! committed. Pipeline formats are stored in the
+//! [`RenderPipelineState`](crate::RenderPipelineState) wrappers returned by
+//! [`Device::new_render_pipeline_state`](crate::Device::new_render_pipeline_state)
+//! and the other synchronous constructors; pipelines created asynchronously
+//! skip the attachment check. Without the feature none of this state is
+//! kept and the checks compile to nothing.
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Cargo.toml: mtl-gpu = { version = "...", features = ["validation"] }
+//! let encoder = unsafe {
+//!     RenderCommandEncoder::from_raw(command_buffer.render_command_encoder(&pass))
+//! }
+//! .unwrap();
+//! encoder.end_encoding();
+//! encoder.draw_primitives(PrimitiveType::TRIANGLE, 0, 3);
+//! // panics: "RenderCommandEncoder::draw_primitives called after end_encoding"
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::c_void;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use mtl_foundation::UInteger;
+use mtl_sys::{msg_send_0, msg_send_1, sel};
+
+use crate::enums::{CommandBufferStatus, PixelFormat};
+
+/// Maximum number of buffers in a stage's argument table.
+pub const MAX_BUFFER_BINDINGS: UInteger = 31;
+
+/// Maximum number of textures in a stage's argument table.
+pub const MAX_TEXTURE_BINDINGS: UInteger = 128;
+
+/// Maximum number of samplers in a stage's argument table.
+pub const MAX_SAMPLER_BINDINGS: UInteger = 16;
+
+/// Number of color attachments checked against the render pass.
+const MAX_COLOR_ATTACHMENTS: UInteger = 8;
+
+/// A kind of argument table entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Binding {
+    Buffer,
+    Texture,
+    Sampler,
+}
+
+/// Pixel formats of a render pass or render pipeline's attachments.
+///
+/// Render pipeline states created with validation enabled keep theirs in
+/// the wrapper, see [`render_pipeline_formats`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AttachmentFormats {
+    color: [PixelFormat; MAX_COLOR_ATTACHMENTS],
+    depth: PixelFormat,
+    stencil: PixelFormat,
+}
+
+/// The encoder a command buffer is currently encoding with.
+#[derive(Debug)]
+struct OpenEncoder {
+    encoder: usize,
+    pass: Option<AttachmentFormats>,
+}
+
+/// Encoding state of a command buffer that hasn't been committed.
+///
+/// The entry is removed on commit, so everything it records lives only as
+/// long as the command buffer can still encode.
+#[derive(Debug, Default)]
+struct CommandBufferState {
+    /// The encoder that hasn't ended yet, if any.
+    open: Option<OpenEncoder>,
+    /// Addresses of encoders that have ended.
+    ended: HashSet<usize>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Command buffers that created an encoder, by address.
+    command_buffers: HashMap<usize, CommandBufferState>,
+}
+
+impl State {
+    /// Get the open encoder record for `encoder`.
+    fn open_encoder(&self, encoder: *const c_void) -> Option<&OpenEncoder> {
+        self.command_buffers
+            .values()
+            .filter_map(|command_buffer| command_buffer.open.as_ref())
+            .find(|open| open.encoder == encoder as usize)
+    }
+
+    /// Check if `encoder` has ended on a command buffer that is still
+    /// encoding.
+    fn has_ended(&self, encoder: *const c_void) -> bool {
+        self.command_buffers
+            .values()
+            .any(|command_buffer| command_buffer.ended.contains(&(encoder as usize)))
+    }
+}
+
+fn state() -> MutexGuard<'static, State> {
+    static STATE: OnceLock<Mutex<State>> = OnceLock::new();
+    STATE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn command_buffer_status(command_buffer: *const c_void) -> CommandBufferStatus {
+    unsafe { msg_send_0(command_buffer, sel!(status)) }
+}
+
+/// Check that the command buffer is still encoding and has no open encoder.
+fn check_no_open_encoder(state: &State, command_buffer: *const c_void, method: &str) {
+    let open = state
+        .command_buffers
+        .get(&(command_buffer as usize))
+        .and_then(|command_buffer| command_buffer.open.as_ref());
+    if let Some(open) = open {
+        panic!(
+            "CommandBuffer::{method} called while an encoder (0x{:x}) is still \
+             encoding; call end_encoding on it first",
+            open.encoder
+        );
+    }
+}
+
+// ============================================================================
+// Command Buffers
+// ============================================================================
+
+/// Forget any state left at the address of a newly created command buffer.
+pub(crate) fn command_buffer_created(command_buffer: *const c_void) {
+    state().command_buffers.remove(&(command_buffer as usize));
+}
+
+/// Validate a call to `CommandBuffer::commit`.
+pub(crate) fn commit(command_buffer: *const c_void) {
+    let status = command_buffer_status(command_buffer);
+    if status.0 >= CommandBufferStatus::COMMITTED.0 {
+        panic!("CommandBuffer::commit called on a command buffer that was already committed");
+    }
+    let mut state = state();
+    check_no_open_encoder(&state, command_buffer, "commit");
+    state.command_buffers.remove(&(command_buffer as usize));
+}
+
+/// Validate creating an encoder with `CommandBuffer::{method}`.
+pub(crate) fn begin_encoder(command_buffer: *const c_void, method: &str) {
+    let status = command_buffer_status(command_buffer);
+    if status.0 >= CommandBufferStatus::COMMITTED.0 {
+        panic!("CommandBuffer::{method} called after the command buffer was committed");
+    }
+    check_no_open_encoder(&state(), command_buffer, method);
+}
+
+/// Record an encoder created by a command buffer, with the render pass
+/// descriptor it was created from if any.
+pub(crate) fn encoder_created(
+    command_buffer: *const c_void,
+    encoder: *mut c_void,
+    render_pass: *const c_void,
+) {
+    if encoder.is_null() {
+        return;
+    }
+    let pass = (!render_pass.is_null()).then(|| unsafe { render_pass_formats(render_pass) });
+    let mut state = state();
+    // An ended encoder at the same address has been freed.
+    for command_buffer in state.command_buffers.values_mut() {
+        command_buffer.ended.remove(&(encoder as usize));
+    }
+    state
+        .command_buffers
+        .entry(command_buffer as usize)
+        .or_default()
+        .open = Some(OpenEncoder {
+        encoder: encoder as usize,
+        pass,
+    });
+}
+
+// ============================================================================
+// Encoders
+// ============================================================================
+
+/// Validate that `encoder` can still encode commands.
+pub(crate) fn check_encoding(encoder: *const c_void, method: &str) {
+    if state().has_ended(encoder) {
+        panic!("{method} called after end_encoding");
+    }
+}
+
+/// Validate binding an argument table entry at `index`.
+pub(crate) fn check_binding(
+    encoder: *const c_void,
+    method: &str,
+    binding: Binding,
+    index: UInteger,
+) {
+    check_encoding(encoder, method);
+    let (kind, limit) = match binding {
+        Binding::Buffer => ("buffer", MAX_BUFFER_BINDINGS),
+        Binding::Texture => ("texture", MAX_TEXTURE_BINDINGS),
+        Binding::Sampler => ("sampler", MAX_SAMPLER_BINDINGS),
+    };
+    if index >= limit {
+        panic!("{method}: {kind} index {index} is out of range (the limit is {limit} {kind}s)");
+    }
+}
+
+/// Validate and record a call to `end_encoding`.
+pub(crate) fn end_encoding(encoder: *const c_void, method: &str) {
+    let mut state = state();
+    if state.has_ended(encoder) {
+        panic!("{method} called twice");
+    }
+    let command_buffer = state.command_buffers.values_mut().find(|command_buffer| {
+        command_buffer
+            .open
+            .as_ref()
+            .is_some_and(|open| open.encoder == encoder as usize)
+    });
+    if let Some(command_buffer) = command_buffer {
+        command_buffer.open = None;
+        command_buffer.ended.insert(encoder as usize);
+    }
+}
+
+// ============================================================================
+// Render Pipelines
+// ============================================================================
+
+/// Validate setting a render pipeline with attachment `formats` on a render
+/// command encoder.
+///
+/// Pipelines without recorded formats skip the attachment check.
+pub(crate) fn set_render_pipeline_state(
+    encoder: *const c_void,
+    pipeline: Option<&AttachmentFormats>,
+) {
+    const METHOD: &str = "RenderCommandEncoder::set_render_pipeline_state";
+    check_encoding(encoder, METHOD);
+    let Some(pipeline) = pipeline else {
+        return;
+    };
+    let state = state();
+    let Some(pass) = state.open_encoder(encoder).and_then(|open| open.pass) else {
+        return;
+    };
+    for (index, (expected, actual)) in pass.color.iter().zip(&pipeline.color).enumerate() {
+        if expected != actual {
+            panic!(
+                "{METHOD}: pipeline color attachment {index} format {actual:?} does not \
+                 match the render pass format {expected:?}"
+            );
+        }
+    }
+    if pass.depth != pipeline.depth {
+        panic!(
+            "{METHOD}: pipeline depth attachment format {:?} does not match the render pass \
+             format {:?}",
+            pipeline.depth, pass.depth
+        );
+    }
+    if pass.stencil != pipeline.stencil {
+        panic!(
+            "{METHOD}: pipeline stencil attachment format {:?} does not match the render \
+             pass format {:?}",
+            pipeline.stencil, pass.stencil
+        );
+    }
+}
+
+/// Read the pixel formats of a render pass descriptor's attachment textures.
+///
+/// # Safety
+///
+/// `descriptor` must be a valid MTLRenderPassDescriptor.
+unsafe fn render_pass_formats(descriptor: *const c_void) -> AttachmentFormats {
+    unsafe fn texture_format(attachment: *const c_void) -> PixelFormat {
+        if attachment.is_null() {
+            return PixelFormat::INVALID;
+        }
+        unsafe {
+            let texture: *const c_void = msg_send_0(attachment, sel!(texture));
+            if texture.is_null() {
+                PixelFormat::INVALID
+            } else {
+                msg_send_0(texture, sel!(pixelFormat))
+            }
+        }
+    }
+
+    unsafe {
+        let attachments: *const c_void = msg_send_0(descriptor, sel!(colorAttachments));
+        let color = std::array::from_fn(|index| {
+            let attachment: *const c_void = msg_send_1(
+                attachments,
+                sel!(objectAtIndexedSubscript:),
+                index as UInteger,
+            );
+            texture_format(attachment)
+        });
+        AttachmentFormats {
+            color,
+            depth: texture_format(msg_send_0(descriptor, sel!(depthAttachment))),
+            stencil: texture_format(msg_send_0(descriptor, sel!(stencilAttachment))),
+        }
+    }
+}
+
+/// Read the attachment pixel formats of a render pipeline descriptor.
+///
+/// # Safety
+///
+/// `descriptor` must be a valid MTLRenderPipelineDescriptor or
+/// MTLMeshRenderPipelineDescriptor.
+pub(crate) unsafe fn render_pipeline_formats(descriptor: *const c_void) -> AttachmentFormats {
+    unsafe {
+        let attachments: *const c_void = msg_send_0(descriptor, sel!(colorAttachments));
+        let color = std::array::from_fn(|index| {
+            let attachment: *const c_void = msg_send_1(
+                attachments,
+                sel!(objectAtIndexedSubscript:),
+                index as UInteger,
+            );
+            if attachment.is_null() {
+                PixelFormat::INVALID
+            } else {
+                msg_send_0(attachment, sel!(pixelFormat))
+            }
+        });
+        AttachmentFormats {
+            color,
+            depth: msg_send_0(descriptor, sel!(depthAttachmentPixelFormat)),
+            stencil: msg_send_0(descriptor, sel!(stencilAttachmentPixelFormat)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mtl_foundation::Referencing;
+
+    use crate::BlitCommandEncoder;
+
+    fn command_buffer() -> crate::CommandBuffer {
+        let device = crate::device::system_default().expect("no Metal device");
+        let queue = device.new_command_queue().unwrap();
+        queue.command_buffer().unwrap()
+    }
+
+    #[test]
+    #[should_panic(expected = "BlitCommandEncoder::fill_buffer called after end_encoding")]
+    fn test_encoding_after_end_encoding() {
+        let command_buffer = command_buffer();
+        let buffer = command_buffer
+            .device()
+            .new_buffer(16, crate::ResourceOptions::STORAGE_MODE_SHARED)
+            .unwrap();
+        let encoder =
+            unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }.unwrap();
+        encoder.end_encoding();
+        encoder.fill_buffer(&buffer, 0, 16, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "still encoding")]
+    fn test_encoder_left_open() {
+        let command_buffer = command_buffer();
+        let _encoder =
+            unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }.unwrap();
+        command_buffer.commit();
+    }
+
+    #[test]
+    #[should_panic(expected = "already committed")]
+    fn test_double_commit() {
+        let command_buffer = command_buffer();
+        command_buffer.commit();
+        command_buffer.commit();
+    }
+    #[test]
+    fn test_encoders_in_sequence() {
+        let command_buffer = command_buffer();
+        for _ in 0..2 {
+            let encoder =
+                unsafe { BlitCommandEncoder::from_raw(command_buffer.blit_command_encoder()) }
+                    .unwrap();
+            encoder.end_encoding();
+        }
+        command_buffer.commit();
+        assert!(
+            !super::state()
+                .command_buffers
+                .contains_key(&(command_buffer.as_ptr() as usize))
+        );
+    }
+}
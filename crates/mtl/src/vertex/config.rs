@@ -0,0 +1,116 @@
+//! Plain-value snapshots of vertex descriptors.
+
+use mtl_foundation::UInteger;
+
+use super::VertexDescriptor;
+use crate::enums::{VertexFormat, VertexStepFunction};
+
+/// Number of attribute and buffer layout slots a vertex descriptor has.
+const MAX_VERTEX_SLOTS: UInteger = 31;
+
+/// The parameters of one vertex attribute.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexAttributeConfig {
+    /// The attribute index.
+    pub index: UInteger,
+    /// The data format.
+    pub format: VertexFormat,
+    /// Byte offset from the start of the vertex.
+    pub offset: UInteger,
+    /// The buffer the attribute is read from.
+    pub buffer_index: UInteger,
+}
+
+/// The parameters of one vertex buffer layout.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexBufferLayoutConfig {
+    /// The buffer index.
+    pub buffer_index: UInteger,
+    /// Bytes between consecutive vertices.
+    pub stride: UInteger,
+    /// How the vertex fetch advances through the buffer.
+    pub step_function: VertexStepFunction,
+    /// How many instances or patches share one element.
+    pub step_rate: UInteger,
+}
+
+/// The contents of a [`VertexDescriptor`] as plain values.
+///
+/// Unlike the descriptor, a config can't change after it is read, so it
+/// can be shared between threads and used as part of a cache key. Only
+/// attributes with a format and layouts with a stride are recorded, in
+/// index order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct VertexDescriptorConfig {
+    /// The attributes in use.
+    pub attributes: Vec<VertexAttributeConfig>,
+    /// The buffer layouts in use.
+    pub layouts: Vec<VertexBufferLayoutConfig>,
+}
+
+impl VertexDescriptorConfig {
+    /// Read the contents of `descriptor`.
+    pub fn from_descriptor(descriptor: &VertexDescriptor) -> Self {
+        let attributes = descriptor.attributes();
+        let layouts = descriptor.layouts();
+        Self {
+            attributes: (0..MAX_VERTEX_SLOTS)
+                .filter_map(|index| {
+                    let attribute = attributes.object(index)?;
+                    let format = attribute.format();
+                    (format != VertexFormat::INVALID).then(|| VertexAttributeConfig {
+                        index,
+                        format,
+                        offset: attribute.offset(),
+                        buffer_index: attribute.buffer_index(),
+                    })
+                })
+                .collect(),
+            layouts: (0..MAX_VERTEX_SLOTS)
+                .filter_map(|buffer_index| {
+                    let layout = layouts.object(buffer_index)?;
+                    let stride = layout.stride();
+                    (stride != 0).then(|| VertexBufferLayoutConfig {
+                        buffer_index,
+                        stride,
+                        step_function: layout.step_function(),
+                        step_rate: layout.step_rate(),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Check if no attributes or layouts are in use.
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty() && self.layouts.is_empty()
+    }
+
+    /// Reset `descriptor` and write these contents to it.
+    pub fn apply(&self, descriptor: &VertexDescriptor) {
+        descriptor.reset();
+        let attributes = descriptor.attributes();
+        for config in &self.attributes {
+            if let Some(attribute) = attributes.object(config.index) {
+                attribute.set_format(config.format);
+                attribute.set_offset(config.offset);
+                attribute.set_buffer_index(config.buffer_index);
+            }
+        }
+        let layouts = descriptor.layouts();
+        for config in &self.layouts {
+            if let Some(layout) = layouts.object(config.buffer_index) {
+                layout.set_stride(config.stride);
+                layout.set_step_function(config.step_function);
+                layout.set_step_rate(config.step_rate);
+            }
+        }
+    }
+
+    /// Create a descriptor with these contents.
+    pub fn descriptor(&self) -> Option<VertexDescriptor> {
+        let descriptor = VertexDescriptor::new()?;
+        self.apply(&descriptor);
+        Some(descriptor)
+    }
+}
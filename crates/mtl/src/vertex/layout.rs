@@ -0,0 +1,261 @@
+//! Vertex descriptors generated from Rust vertex structs.
+//!
+//! Writing a [`VertexDescriptor`] by hand repeats every field's offset and
+//! format, and nothing notices when the struct changes. A struct declared
+//! with [`vertex_struct!`](crate::vertex_struct) is `#[repr(C)]` and
+//! implements [`VertexLayout`], which takes the offsets from the compiler
+//! and infers each attribute's format from its field type:
+//!
+//! | Rust | Vertex format |
+//! |------|---------------|
+//! | `f32`, `[f32; N]` | `FLOAT`, `FLOATN` |
+//! | `u32`, `[u32; N]` / `i32`, `[i32; N]` | `UINT`, `UINTN` / `INT`, `INTN` |
+//! | `u16`, `[u16; N]` / `i16`, `[i16; N]` | `USHORT`, `USHORTN` / `SHORT`, `SHORTN` |
+//! | `u8`, `[u8; N]` / `i8`, `[i8; N]` | `UCHAR`, `UCHARN` / `CHAR`, `CHARN` |
+//! | [`PackedFloat3`] | `FLOAT3` |
+//! | [`Normalized<T>`] | the `_NORMALIZED` format of `T` |
+//!
+//! Attributes are numbered in field order.
+//!
+//! # Example
+//!
+//! ```ignore
+//! mtl_gpu::vertex_struct! {
+//!     #[derive(Copy, Clone, Debug, Default)]
+//!     pub struct Vertex {
+//!         pub position: [f32; 3],
+//!         pub normal: [f32; 3],
+//!         pub uv: [f32; 2],
+//!         pub color: Normalized<[u8; 4]>,
+//!     }
+//! }
+//!
+//! let descriptor = Vertex::vertex_descriptor(0).unwrap();
+//! pipeline_descriptor.set_vertex_descriptor(Some(&descriptor));
+//! ```
+//!
+//! [`PackedFloat3`]: crate::types::PackedFloat3
+
+use mtl_foundation::UInteger;
+
+use super::VertexDescriptor;
+use crate::enums::{VertexFormat, VertexStepFunction};
+use crate::types::PackedFloat3;
+
+// ============================================================================
+// VertexAttributeType
+// ============================================================================
+
+/// Field types with a vertex attribute format.
+pub trait VertexAttributeType {
+    /// The attribute format for the type.
+    const FORMAT: VertexFormat;
+}
+
+macro_rules! impl_vertex_attribute_type {
+    ($($ty:ty => $format:ident),* $(,)?) => {
+        $(
+            impl VertexAttributeType for $ty {
+                const FORMAT: VertexFormat = VertexFormat::$format;
+            }
+        )*
+    };
+}
+
+impl_vertex_attribute_type!(
+    f32 => FLOAT, [f32; 2] => FLOAT2, [f32; 3] => FLOAT3, [f32; 4] => FLOAT4,
+    u32 => UINT, [u32; 2] => UINT2, [u32; 3] => UINT3, [u32; 4] => UINT4,
+    i32 => INT, [i32; 2] => INT2, [i32; 3] => INT3, [i32; 4] => INT4,
+    u16 => USHORT, [u16; 2] => USHORT2, [u16; 3] => USHORT3, [u16; 4] => USHORT4,
+    i16 => SHORT, [i16; 2] => SHORT2, [i16; 3] => SHORT3, [i16; 4] => SHORT4,
+    u8 => UCHAR, [u8; 2] => UCHAR2, [u8; 3] => UCHAR3, [u8; 4] => UCHAR4,
+    i8 => CHAR, [i8; 2] => CHAR2, [i8; 3] => CHAR3, [i8; 4] => CHAR4,
+    PackedFloat3 => FLOAT3,
+);
+
+/// An integer vertex attribute that the vertex fetch normalizes to
+/// `[0, 1]` (unsigned) or `[-1, 1]` (signed) floats.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Normalized<T>(pub T);
+
+macro_rules! impl_normalized {
+    ($($ty:ty => $format:ident),* $(,)?) => {
+        $(
+            impl VertexAttributeType for Normalized<$ty> {
+                const FORMAT: VertexFormat = VertexFormat::$format;
+            }
+        )*
+    };
+}
+
+impl_normalized!(
+    u16 => USHORT_NORMALIZED, [u16; 2] => USHORT2_NORMALIZED,
+    [u16; 3] => USHORT3_NORMALIZED, [u16; 4] => USHORT4_NORMALIZED,
+    i16 => SHORT_NORMALIZED, [i16; 2] => SHORT2_NORMALIZED,
+    [i16; 3] => SHORT3_NORMALIZED, [i16; 4] => SHORT4_NORMALIZED,
+    u8 => UCHAR_NORMALIZED, [u8; 2] => UCHAR2_NORMALIZED,
+    [u8; 3] => UCHAR3_NORMALIZED, [u8; 4] => UCHAR4_NORMALIZED,
+    i8 => CHAR_NORMALIZED, [i8; 2] => CHAR2_NORMALIZED,
+    [i8; 3] => CHAR3_NORMALIZED, [i8; 4] => CHAR4_NORMALIZED,
+);
+
+// ============================================================================
+// VertexLayout
+// ============================================================================
+
+/// A vertex attribute generated from a struct field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexField {
+    /// The field name.
+    pub name: &'static str,
+    /// The attribute format.
+    pub format: VertexFormat,
+    /// Byte offset from the start of the struct.
+    pub offset: UInteger,
+}
+
+/// Vertex structs that can describe their own vertex descriptor.
+///
+/// Implement it with [`vertex_struct!`](crate::vertex_struct).
+pub trait VertexLayout {
+    /// Size of one vertex in bytes, used as the buffer layout's stride.
+    const STRIDE: UInteger;
+
+    /// Get the attribute generated for each field, in field order.
+    fn fields() -> Vec<VertexField>;
+
+    /// Add the struct's attributes and buffer layout to `descriptor`.
+    ///
+    /// The attributes are numbered from `first_attribute` and read from the
+    /// buffer at `buffer_index`, stepping by `step_function`. Returns the
+    /// index after the last attribute, so several structs, such as
+    /// per-vertex and per-instance data, can share one descriptor.
+    fn describe(
+        descriptor: &VertexDescriptor,
+        buffer_index: UInteger,
+        first_attribute: UInteger,
+        step_function: VertexStepFunction,
+    ) -> UInteger {
+        let attributes = descriptor.attributes();
+        let mut index = first_attribute;
+        for field in Self::fields() {
+            if let Some(attribute) = attributes.object(index) {
+                attribute.set_format(field.format);
+                attribute.set_offset(field.offset);
+                attribute.set_buffer_index(buffer_index);
+            }
+            index += 1;
+        }
+        if let Some(layout) = descriptor.layouts().object(buffer_index) {
+            layout.set_stride(Self::STRIDE);
+            layout.set_step_function(step_function);
+            layout.set_step_rate(1);
+        }
+        index
+    }
+
+    /// Create a vertex descriptor with the struct's attributes, numbered
+    /// from 0, read per vertex from the buffer at `buffer_index`.
+    fn vertex_descriptor(buffer_index: UInteger) -> Option<VertexDescriptor> {
+        let descriptor = VertexDescriptor::new()?;
+        Self::describe(&descriptor, buffer_index, 0, VertexStepFunction::PER_VERTEX);
+        Some(descriptor)
+    }
+}
+
+/// Declare a `#[repr(C)]` vertex struct and implement
+/// [`VertexLayout`](crate::VertexLayout) for it.
+///
+/// Every field type must implement
+/// [`VertexAttributeType`](crate::VertexAttributeType).
+///
+/// ```ignore
+/// mtl_gpu::vertex_struct! {
+///     #[derive(Copy, Clone, Debug, Default)]
+///     pub struct Instance {
+///         pub offset: [f32; 2],
+///         pub tint: Normalized<[u8; 4]>,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! vertex_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $crate::VertexLayout for $name {
+            const STRIDE: usize = ::core::mem::size_of::<$name>();
+
+            fn fields() -> Vec<$crate::VertexField> {
+                vec![
+                    $(
+                        $crate::VertexField {
+                            name: stringify!($field),
+                            format: <$ty as $crate::VertexAttributeType>::FORMAT,
+                            offset: ::core::mem::offset_of!($name, $field),
+                        }
+                    ),*
+                ]
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::vertex_struct! {
+        #[derive(Copy, Clone, Debug, Default)]
+        struct Vertex {
+            position: [f32; 3],
+            uv: [f32; 2],
+            color: Normalized<[u8; 4]>,
+            material: u16,
+        }
+    }
+
+    #[test]
+    fn test_vertex_fields() {
+        let fields: Vec<_> = Vertex::fields()
+            .iter()
+            .map(|field| (field.name, field.format, field.offset))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("position", VertexFormat::FLOAT3, 0),
+                ("uv", VertexFormat::FLOAT2, 12),
+                ("color", VertexFormat::UCHAR4_NORMALIZED, 20),
+                ("material", VertexFormat::USHORT, 24),
+            ]
+        );
+        assert_eq!(Vertex::STRIDE, 28);
+    }
+
+    #[test]
+    fn test_vertex_descriptor_from_struct() {
+        let descriptor = Vertex::vertex_descriptor(1).unwrap();
+        let color = descriptor.attributes().object(2).unwrap();
+        assert_eq!(color.format(), VertexFormat::UCHAR4_NORMALIZED);
+        assert_eq!(color.offset(), 20);
+        assert_eq!(color.buffer_index(), 1);
+        assert_eq!(descriptor.layouts().object(1).unwrap().stride(), 28);
+    }
+}
@@ -3,7 +3,8 @@
 //! Corresponds to `Metal/MTLVertexDescriptor.hpp`.
 //!
 //! Vertex descriptors describe the organization of vertex data in buffers
-//! for use with render pipelines.
+//! for use with render pipelines. [`VertexLayout`] builds them from Rust
+//! vertex structs declared with [`vertex_struct!`](crate::vertex_struct).
 
 use std::ffi::c_void;
 use std::ptr::NonNull;
@@ -13,6 +14,12 @@ use mtl_sys::{Class, msg_send_0, msg_send_1, msg_send_2, sel};
 
 use crate::enums::{VertexFormat, VertexStepFunction};
 
+mod config;
+mod layout;
+
+pub use config::{VertexAttributeConfig, VertexBufferLayoutConfig, VertexDescriptorConfig};
+pub use layout::{Normalized, VertexAttributeType, VertexField, VertexLayout};
+
 /// Constant indicating that the stride should be computed dynamically.
 ///
 /// C++ equivalent: `MTL::BufferLayoutStrideDynamic`
@@ -0,0 +1,400 @@
+//! ABI Header Conformance Tests
+//!
+//! `abi_validation.rs` checks struct layouts against hand-written numbers.
+//! This file checks them against the Metal headers themselves: it generates
+//! a small Objective-C program that prints `sizeof`/`offsetof` for each
+//! Metal struct, compiles it with the installed SDK, and compares the output
+//! with the Rust layouts. A header change that moves or resizes a field
+//! fails here instead of corrupting memory at runtime.
+//!
+//! Structs that only exist in newer SDKs are wrapped in `__has_include`
+//! checks and skipped when the SDK doesn't declare them.
+
+#![cfg(target_os = "macos")]
+
+use std::collections::HashMap;
+use std::mem::{offset_of, size_of};
+use std::path::PathBuf;
+use std::process::Command;
+
+use mtl_gpu::mtl4::{BufferRange, TimestampHeapEntry};
+use mtl_gpu::{
+    AccelerationStructureInstanceDescriptor, AccelerationStructureMotionInstanceDescriptor,
+    AccelerationStructureSizes, AccelerationStructureUserIDInstanceDescriptor,
+    AxisAlignedBoundingBox, ClearColor, ComponentTransform, CounterResultStageUtilization,
+    CounterResultStatistic, CounterResultTimestamp, DispatchThreadgroupsIndirectArguments,
+    DispatchThreadsIndirectArguments, DrawIndexedPrimitivesIndirectArguments,
+    DrawPatchIndirectArguments, DrawPrimitivesIndirectArguments,
+    IndirectAccelerationStructureInstanceDescriptor,
+    IndirectAccelerationStructureMotionInstanceDescriptor, IndirectCommandBufferExecutionRange,
+    IntersectionFunctionBufferArguments, MapIndirectArguments, Origin, PackedFloat3,
+    PackedFloat4x3, PackedFloatQuaternion, QuadTessellationFactorsHalf, Region, ResourceID,
+    SamplePosition, ScissorRect, Size, SizeAndAlign, StageInRegionIndirectArguments,
+    TextureSwizzleChannels, TriangleTessellationFactorsHalf, VertexAmplificationViewMapping,
+    Viewport,
+};
+
+// =============================================================================
+// Layout Table
+// =============================================================================
+
+/// Layout of one Rust struct and the Metal struct it mirrors.
+struct StructLayout {
+    rust_name: &'static str,
+    c_name: &'static str,
+    /// Header that must exist for the C struct to be declared, if any.
+    requires: Option<&'static str>,
+    size: usize,
+    /// `(rust field, C field, Rust offset)`
+    fields: Vec<(&'static str, &'static str, usize)>,
+}
+
+macro_rules! layout {
+    ($rust:ident => $c:literal $(requires $header:literal)? { $($field:ident => $c_field:literal),* $(,)? }) => {
+        StructLayout {
+            rust_name: stringify!($rust),
+            c_name: $c,
+            requires: layout!(@requires $($header)?),
+            size: size_of::<$rust>(),
+            fields: vec![$((stringify!($field), $c_field, offset_of!($rust, $field))),*],
+        }
+    };
+    (@requires $header:literal) => { Some($header) };
+    (@requires) => { None };
+}
+
+fn layouts() -> Vec<StructLayout> {
+    vec![
+        // Core geometry
+        layout!(Origin => "MTLOrigin" { x => "x", y => "y", z => "z" }),
+        layout!(Size => "MTLSize" { width => "width", height => "height", depth => "depth" }),
+        layout!(Region => "MTLRegion" { origin => "origin", size => "size" }),
+        layout!(SamplePosition => "MTLSamplePosition" { x => "x", y => "y" }),
+        layout!(ResourceID => "MTLResourceID" { _impl => "_impl" }),
+        layout!(SizeAndAlign => "MTLSizeAndAlign" { size => "size", align => "align" }),
+        // Render state
+        layout!(Viewport => "MTLViewport" {
+            origin_x => "originX",
+            origin_y => "originY",
+            width => "width",
+            height => "height",
+            znear => "znear",
+            zfar => "zfar",
+        }),
+        layout!(ClearColor => "MTLClearColor" {
+            red => "red",
+            green => "green",
+            blue => "blue",
+            alpha => "alpha",
+        }),
+        layout!(ScissorRect => "MTLScissorRect" {
+            x => "x",
+            y => "y",
+            width => "width",
+            height => "height",
+        }),
+        layout!(VertexAmplificationViewMapping => "MTLVertexAmplificationViewMapping" {
+            viewport_array_index_offset => "viewportArrayIndexOffset",
+            render_target_array_index_offset => "renderTargetArrayIndexOffset",
+        }),
+        layout!(TextureSwizzleChannels => "MTLTextureSwizzleChannels" {
+            red => "red",
+            green => "green",
+            blue => "blue",
+            alpha => "alpha",
+        }),
+        // Indirect arguments
+        layout!(DrawPrimitivesIndirectArguments => "MTLDrawPrimitivesIndirectArguments" {
+            vertex_count => "vertexCount",
+            instance_count => "instanceCount",
+            vertex_start => "vertexStart",
+            base_instance => "baseInstance",
+        }),
+        layout!(DrawIndexedPrimitivesIndirectArguments => "MTLDrawIndexedPrimitivesIndirectArguments" {
+            index_count => "indexCount",
+            instance_count => "instanceCount",
+            index_start => "indexStart",
+            base_vertex => "baseVertex",
+            base_instance => "baseInstance",
+        }),
+        layout!(DrawPatchIndirectArguments => "MTLDrawPatchIndirectArguments" {
+            patch_count => "patchCount",
+            instance_count => "instanceCount",
+            patch_start => "patchStart",
+            base_instance => "baseInstance",
+        }),
+        layout!(DispatchThreadgroupsIndirectArguments => "MTLDispatchThreadgroupsIndirectArguments" {
+            threadgroups_per_grid => "threadgroupsPerGrid",
+        }),
+        layout!(DispatchThreadsIndirectArguments => "MTLDispatchThreadsIndirectArguments" {
+            threads_per_grid => "threadsPerGrid",
+            threads_per_threadgroup => "threadsPerThreadgroup",
+        }),
+        layout!(StageInRegionIndirectArguments => "MTLStageInRegionIndirectArguments" {
+            stage_in_origin => "stageInOrigin",
+            stage_in_size => "stageInSize",
+        }),
+        layout!(MapIndirectArguments => "MTLMapIndirectArguments" {
+            region_origin_x => "regionOriginX",
+            region_origin_y => "regionOriginY",
+            region_origin_z => "regionOriginZ",
+            region_size_width => "regionSizeWidth",
+            region_size_height => "regionSizeHeight",
+            region_size_depth => "regionSizeDepth",
+            mip_map_level => "mipMapLevel",
+            slice_id => "sliceId",
+        }),
+        layout!(IndirectCommandBufferExecutionRange => "MTLIndirectCommandBufferExecutionRange" {
+            location => "location",
+            length => "length",
+        }),
+        layout!(QuadTessellationFactorsHalf => "MTLQuadTessellationFactorsHalf" {
+            edge_tessellation_factor => "edgeTessellationFactor",
+            inside_tessellation_factor => "insideTessellationFactor",
+        }),
+        layout!(TriangleTessellationFactorsHalf => "MTLTriangleTessellationFactorsHalf" {
+            edge_tessellation_factor => "edgeTessellationFactor",
+            inside_tessellation_factor => "insideTessellationFactor",
+        }),
+        // Acceleration structures
+        layout!(AccelerationStructureSizes => "MTLAccelerationStructureSizes" {
+            acceleration_structure_size => "accelerationStructureSize",
+            build_scratch_buffer_size => "buildScratchBufferSize",
+            refit_scratch_buffer_size => "refitScratchBufferSize",
+        }),
+        layout!(PackedFloat3 => "MTLPackedFloat3" { x => "x", y => "y", z => "z" }),
+        layout!(PackedFloat4x3 => "MTLPackedFloat4x3" { columns => "columns" }),
+        layout!(PackedFloatQuaternion => "MTLPackedFloatQuaternion" {
+            x => "x",
+            y => "y",
+            z => "z",
+            w => "w",
+        }),
+        layout!(AxisAlignedBoundingBox => "MTLAxisAlignedBoundingBox" {
+            min => "min",
+            max => "max",
+        }),
+        layout!(ComponentTransform => "MTLComponentTransform" {
+            scale => "scale",
+            shear => "shear",
+            pivot => "pivot",
+            rotation => "rotation",
+            translation => "translation",
+        }),
+        layout!(AccelerationStructureInstanceDescriptor => "MTLAccelerationStructureInstanceDescriptor" {
+            transformation_matrix => "transformationMatrix",
+            options => "options",
+            mask => "mask",
+            intersection_function_table_offset => "intersectionFunctionTableOffset",
+            acceleration_structure_index => "accelerationStructureIndex",
+        }),
+        layout!(AccelerationStructureUserIDInstanceDescriptor => "MTLAccelerationStructureUserIDInstanceDescriptor" {
+            transformation_matrix => "transformationMatrix",
+            options => "options",
+            mask => "mask",
+            intersection_function_table_offset => "intersectionFunctionTableOffset",
+            acceleration_structure_index => "accelerationStructureIndex",
+            user_id => "userID",
+        }),
+        layout!(AccelerationStructureMotionInstanceDescriptor => "MTLAccelerationStructureMotionInstanceDescriptor" {
+            options => "options",
+            mask => "mask",
+            intersection_function_table_offset => "intersectionFunctionTableOffset",
+            acceleration_structure_index => "accelerationStructureIndex",
+            user_id => "userID",
+            motion_transforms_start_index => "motionTransformsStartIndex",
+            motion_transforms_count => "motionTransformsCount",
+            motion_start_border_mode => "motionStartBorderMode",
+            motion_end_border_mode => "motionEndBorderMode",
+            motion_start_time => "motionStartTime",
+            motion_end_time => "motionEndTime",
+        }),
+        layout!(IndirectAccelerationStructureInstanceDescriptor => "MTLIndirectAccelerationStructureInstanceDescriptor" {
+            transformation_matrix => "transformationMatrix",
+            options => "options",
+            mask => "mask",
+            intersection_function_table_offset => "intersectionFunctionTableOffset",
+            user_id => "userID",
+            acceleration_structure_id => "accelerationStructureID",
+        }),
+        layout!(IndirectAccelerationStructureMotionInstanceDescriptor => "MTLIndirectAccelerationStructureMotionInstanceDescriptor" {
+            options => "options",
+            mask => "mask",
+            intersection_function_table_offset => "intersectionFunctionTableOffset",
+            user_id => "userID",
+            acceleration_structure_id => "accelerationStructureID",
+            motion_transforms_start_index => "motionTransformsStartIndex",
+            motion_transforms_count => "motionTransformsCount",
+            motion_start_border_mode => "motionStartBorderMode",
+            motion_end_border_mode => "motionEndBorderMode",
+            motion_start_time => "motionStartTime",
+            motion_end_time => "motionEndTime",
+        }),
+        // Added in the same SDK as Metal 4, which has no header of its own.
+        layout!(IntersectionFunctionBufferArguments => "MTLIntersectionFunctionBufferArguments"
+            requires "Metal/MTL4Counters.h" {
+            intersection_function_buffer => "intersectionFunctionBuffer",
+            intersection_function_buffer_size => "intersectionFunctionBufferSize",
+            intersection_function_stride => "intersectionFunctionStride",
+        }),
+        // Counters
+        layout!(CounterResultTimestamp => "MTLCounterResultTimestamp" { timestamp => "timestamp" }),
+        layout!(CounterResultStageUtilization => "MTLCounterResultStageUtilization" {
+            total_cycles => "totalCycles",
+            vertex_cycles => "vertexCycles",
+            tessellation_cycles => "tessellationCycles",
+            post_tessellation_vertex_cycles => "postTessellationVertexCycles",
+            fragment_cycles => "fragmentCycles",
+            render_target_cycles => "renderTargetCycles",
+        }),
+        layout!(CounterResultStatistic => "MTLCounterResultStatistic" {
+            tessellation_input_patches => "tessellationInputPatches",
+            vertex_invocations => "vertexInvocations",
+            post_tessellation_vertex_invocations => "postTessellationVertexInvocations",
+            clipper_invocations => "clipperInvocations",
+            clipper_primitives_out => "clipperPrimitivesOut",
+            fragment_invocations => "fragmentInvocations",
+            fragments_passed => "fragmentsPassed",
+            compute_kernel_invocations => "computeKernelInvocations",
+        }),
+        // Metal 4
+        layout!(BufferRange => "MTL4BufferRange" requires "Metal/MTL4BufferRange.h" {
+            buffer_address => "bufferAddress",
+            length => "length",
+        }),
+        layout!(TimestampHeapEntry => "MTL4TimestampHeapEntry" requires "Metal/MTL4Counters.h" {
+            timestamp => "timestamp",
+        }),
+    ]
+}
+
+// =============================================================================
+// Header Shim
+// =============================================================================
+
+/// Generate an Objective-C program printing `<C name> <size>` and
+/// `<C name>.<field> <offset>` lines for every layout.
+fn shim_source(layouts: &[StructLayout]) -> String {
+    let mut source = String::from(
+        "#import <Metal/Metal.h>\n#include <stddef.h>\n#include <stdio.h>\n\nint main(void) {\n",
+    );
+    for layout in layouts {
+        if let Some(header) = layout.requires {
+            source.push_str(&format!("#if __has_include(<{}>)\n", header));
+        }
+        source.push_str(&format!(
+            "    printf(\"{0} %zu\\n\", sizeof({0}));\n",
+            layout.c_name
+        ));
+        for (_, c_field, _) in &layout.fields {
+            source.push_str(&format!(
+                "    printf(\"{0}.{1} %zu\\n\", offsetof({0}, {1}));\n",
+                layout.c_name, c_field
+            ));
+        }
+        if layout.requires.is_some() {
+            source.push_str("#endif\n");
+        }
+    }
+    source.push_str("    return 0;\n}\n");
+    source
+}
+
+/// Compile and run the shim, returning the values it printed.
+///
+/// Returns `None` when no Clang toolchain is installed.
+fn header_layouts(layouts: &[StructLayout]) -> Option<HashMap<String, usize>> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("abi_headers");
+    std::fs::create_dir_all(&dir).expect("failed to create shim directory");
+    let source = dir.join("shim.m");
+    let binary = dir.join("shim");
+    std::fs::write(&source, shim_source(layouts)).expect("failed to write shim source");
+
+    let output = Command::new("xcrun")
+        .args(["--sdk", "macosx", "clang", "-fobjc-arc", "-o"])
+        .arg(&binary)
+        .arg(&source)
+        .output()
+        .ok()?;
+    assert!(
+        output.status.success(),
+        "failed to compile header shim:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = Command::new(&binary)
+        .output()
+        .expect("failed to run header shim");
+    assert!(output.status.success(), "header shim exited with an error");
+
+    let stdout = String::from_utf8(output.stdout).expect("shim output is not UTF-8");
+    Some(
+        stdout
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(' ')?;
+                Some((key.to_string(), value.parse().ok()?))
+            })
+            .collect(),
+    )
+}
+
+// =============================================================================
+// Conformance
+// =============================================================================
+
+#[test]
+fn test_struct_layouts_match_metal_headers() {
+    let layouts = layouts();
+    let Some(header) = header_layouts(&layouts) else {
+        eprintln!("xcrun not available, skipping header ABI check");
+        return;
+    };
+
+    let mut mismatches = Vec::new();
+    for layout in &layouts {
+        let Some(&size) = header.get(layout.c_name) else {
+            // Only layouts behind a `requires` header can be missing.
+            assert!(
+                layout.requires.is_some(),
+                "{} missing from shim output",
+                layout.c_name
+            );
+            eprintln!("{} not declared by this SDK, skipping", layout.c_name);
+            continue;
+        };
+        if size != layout.size {
+            mismatches.push(format!(
+                "{}: size {} != {} ({})",
+                layout.rust_name, layout.size, size, layout.c_name
+            ));
+        }
+        for (field, c_field, offset) in &layout.fields {
+            let key = format!("{}.{}", layout.c_name, c_field);
+            let c_offset = header[&key];
+            if c_offset != *offset {
+                mismatches.push(format!(
+                    "{}.{}: offset {} != {} ({})",
+                    layout.rust_name, field, offset, c_offset, key
+                ));
+            }
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "Rust layouts differ from the Metal headers:\n{}",
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn test_shim_source_guards_optional_structs() {
+    let source = shim_source(&layouts());
+    assert!(source.contains("#if __has_include(<Metal/MTL4Counters.h>)"));
+    assert!(source.contains("offsetof(MTLViewport, originX)"));
+    assert_eq!(
+        source.matches("#if").count(),
+        source.matches("#endif").count()
+    );
+}
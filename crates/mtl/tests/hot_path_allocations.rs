@@ -0,0 +1,122 @@
+//! Hot Path Allocation Tests
+//!
+//! These tests count Rust heap allocations made while encoding a frame and
+//! check that the per-frame calls (slice setters, residency sets, debug
+//! groups with prebuilt strings) make none. The validation feature keeps
+//! its own bookkeeping, so they only run without it.
+
+#![cfg(not(feature = "validation"))]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use mtl_foundation::Referencing;
+use mtl_gpu::{ComputeCommandEncoder, ResourceOptions, device};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Run `f` and return how many allocations it made on this thread.
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+/// Get the default Metal device or skip the test.
+fn get_device() -> mtl_gpu::Device {
+    device::system_default().expect("No Metal device available")
+}
+
+// =============================================================================
+// Per-Frame Encoding
+// =============================================================================
+
+#[test]
+fn test_compute_frame_does_not_allocate() {
+    const FRAMES: usize = 100;
+
+    let device = get_device();
+    let queue = device.new_command_queue().expect("Failed to create queue");
+    let buffers: Vec<_> = (0..8)
+        .map(|_| {
+            device
+                .new_buffer(256, ResourceOptions::STORAGE_MODE_SHARED)
+                .expect("Failed to create buffer")
+        })
+        .collect();
+    let buffer_refs: Vec<_> = buffers.iter().collect();
+    let offsets = [0; 8];
+    let group = mtl_foundation::String::from_str("frame")
+        .expect("Failed to create string")
+        .retain();
+
+    let command_buffers: Vec<_> = (0..FRAMES)
+        .map(|_| {
+            queue
+                .command_buffer()
+                .expect("Failed to create command buffer")
+        })
+        .collect();
+
+    let allocations = count_allocations(|| {
+        for command_buffer in &command_buffers {
+            command_buffer.push_debug_group_ns(&group);
+            let encoder = unsafe {
+                ComputeCommandEncoder::from_raw(command_buffer.compute_command_encoder())
+            }
+            .expect("Failed to create encoder");
+            encoder.push_debug_group_ns(&group);
+            for _ in 0..16 {
+                encoder.set_buffers(&buffer_refs, &offsets, 0);
+            }
+            encoder.pop_debug_group();
+            encoder.end_encoding();
+            drop(encoder);
+            command_buffer.pop_debug_group();
+        }
+    });
+
+    assert_eq!(
+        allocations, 0,
+        "{} allocations over {} frames",
+        allocations, FRAMES
+    );
+}
+
+#[test]
+fn test_residency_set_updates_do_not_allocate() {
+    let device = get_device();
+    let queue = device.new_command_queue().expect("Failed to create queue");
+    let descriptor = mtl_gpu::ResidencySetDescriptor::new().expect("Failed to create descriptor");
+    let Ok(set) = device.new_residency_set(&descriptor) else {
+        // Residency sets need macOS 15.
+        return;
+    };
+
+    let allocations = count_allocations(|| {
+        for _ in 0..100 {
+            queue.add_residency_sets(&[&set]);
+            queue.remove_residency_sets(&[&set]);
+        }
+    });
+
+    assert_eq!(allocations, 0);
+}
@@ -4,8 +4,8 @@
 //! They test buffer copies, texture copies, fill operations, and synchronization.
 
 use mtl_gpu::{
-    BlitCommandEncoder, Origin, PixelFormat, Region, ResourceOptions, Size, StorageMode,
-    TextureDescriptor, TextureUsage, device,
+    BlitCommandEncoder, BlitError, Origin, PixelFormat, Region, ResourceOptions, Size, StorageMode,
+    TextureDescriptor, TextureType, TextureUsage, cube_array_slice, device,
 };
 
 /// Get the default Metal device or skip the test.
@@ -303,6 +303,122 @@ fn test_blit_buffer_to_texture() {
     // The copy should succeed (texture now contains the buffer data)
 }
 
+// =============================================================================
+// Volume Copy Tests
+// =============================================================================
+
+/// Create an 8x4x4 R8 3D texture.
+fn create_volume(device: &mtl_gpu::Device) -> mtl_gpu::Texture {
+    let descriptor = TextureDescriptor::new().unwrap();
+    descriptor.set_texture_type(TextureType::TYPE_3D);
+    descriptor.set_pixel_format(PixelFormat::R8_UNORM);
+    descriptor.set_width(8);
+    descriptor.set_height(4);
+    descriptor.set_depth(4);
+    descriptor.set_storage_mode(StorageMode::PRIVATE);
+    device
+        .new_texture_with_descriptor(&descriptor)
+        .expect("Failed to create 3D texture")
+}
+
+#[test]
+fn test_blit_volume_round_trip() {
+    let device = get_device();
+    let queue = device
+        .new_command_queue()
+        .expect("Failed to create command queue");
+    let volume = create_volume(&device);
+    let scratch = create_volume(&device);
+
+    let data: Vec<u8> = (0..128).collect();
+    let upload = device
+        .new_buffer_with_bytes(&data, ResourceOptions::STORAGE_MODE_SHARED)
+        .unwrap();
+    let download = device
+        .new_buffer(64, ResourceOptions::STORAGE_MODE_SHARED)
+        .unwrap();
+
+    let command_buffer = queue.command_buffer().unwrap();
+    let encoder = create_blit_encoder(&command_buffer);
+    let full = Region::new_3d(0, 0, 0, 8, 4, 4);
+    assert_eq!(
+        encoder.copy_buffer_to_texture_3d(&upload, 0, &volume, 0, full),
+        Ok(128)
+    );
+    // Depth images 2 and 3 become images 0 and 1 of the scratch volume.
+    encoder
+        .copy_texture_depth_slices(&volume, 0, 2..4, &scratch, 0, 0)
+        .unwrap();
+    assert_eq!(
+        encoder.copy_texture_3d_to_buffer(
+            &scratch,
+            0,
+            Region::new_3d(0, 0, 0, 8, 4, 2),
+            &download,
+            0
+        ),
+        Ok(64)
+    );
+
+    // Invalid copies are rejected before encoding.
+    assert_eq!(
+        encoder.copy_buffer_to_texture_3d(&download, 0, &volume, 0, full),
+        Err(BlitError::BufferTooSmall {
+            required: 128,
+            length: 64
+        })
+    );
+    assert_eq!(
+        encoder.copy_texture_depth_slices(&volume, 0, 3..5, &scratch, 0, 0),
+        Err(BlitError::InvalidRegion)
+    );
+    encoder.end_encoding();
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+
+    let contents = download.contents().unwrap();
+    let result = unsafe { std::slice::from_raw_parts(contents as *const u8, 64) };
+    assert_eq!(result, &data[64..]);
+}
+
+#[test]
+fn test_blit_copy_cubes() {
+    let device = get_device();
+    let queue = device
+        .new_command_queue()
+        .expect("Failed to create command queue");
+
+    let descriptor = TextureDescriptor::new().unwrap();
+    descriptor.set_texture_type(TextureType::TYPE_CUBE_ARRAY);
+    descriptor.set_pixel_format(PixelFormat::RGBA8_UNORM);
+    descriptor.set_width(4);
+    descriptor.set_height(4);
+    descriptor.set_array_length(3);
+    descriptor.set_storage_mode(StorageMode::PRIVATE);
+    let source = device.new_texture_with_descriptor(&descriptor).unwrap();
+    descriptor.set_array_length(2);
+    let destination = device.new_texture_with_descriptor(&descriptor).unwrap();
+
+    assert_eq!(cube_array_slice(2, 4), 16);
+
+    let command_buffer = queue.command_buffer().unwrap();
+    let encoder = create_blit_encoder(&command_buffer);
+    encoder
+        .copy_cubes(&source, 1, 0, &destination, 0, 0, 2, 1)
+        .unwrap();
+    assert_eq!(
+        encoder.copy_cubes(&source, 2, 0, &destination, 0, 0, 2, 1),
+        Err(BlitError::InvalidRegion)
+    );
+    assert_eq!(
+        encoder.copy_cubes(&source, 0, 0, &create_volume(&device), 0, 0, 1, 1),
+        Err(BlitError::TextureType(TextureType::TYPE_3D))
+    );
+    encoder.end_encoding();
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+}
+
 // =============================================================================
 // Synchronize Tests
 // =============================================================================
@@ -3,7 +3,10 @@
 //! These tests verify that buffer operations work correctly with the Metal GPU.
 //! They test real GPU operations, not just struct layouts or selector existence.
 
-use mtl_gpu::{ResourceOptions, device};
+use mtl_gpu::{
+    GpuVec, GpuVecError, MaterialBlockAllocator, MaterialBlockError, ResourceOptions,
+    TensorDataType, TensorDescriptor, TensorUsage, device,
+};
 
 /// Get the default Metal device or skip the test.
 fn get_device() -> mtl_gpu::Device {
@@ -349,3 +352,164 @@ fn test_large_buffer() {
         assert_eq!(*contents.add(size - 1), 3);
     }
 }
+
+// =============================================================================
+// Tensor Data Tests
+// =============================================================================
+
+#[test]
+fn test_tensor_write_and_read_round_trip() {
+    let device = get_device();
+
+    // Tensors require a recent OS; skip where unavailable.
+    let Some(descriptor) = TensorDescriptor::for_shape(&[2, 3], TensorDataType::FLOAT32) else {
+        return;
+    };
+    descriptor.set_usage(TensorUsage::COMPUTE);
+    let Ok(tensor) = device.new_tensor(&descriptor) else {
+        return;
+    };
+    assert_eq!(tensor.shape(), vec![2, 3]);
+
+    let data = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+    tensor.write_from_slice(&data).unwrap();
+    assert_eq!(tensor.read_to_vec::<f32>().unwrap(), data);
+
+    // Rows padded to 4 elements.
+    let padded = [1.0f32, 2.0, 3.0, -1.0, 4.0, 5.0, 6.0];
+    tensor
+        .write_from_slice_with_strides(&padded, &[4, 1])
+        .unwrap();
+    assert_eq!(tensor.read_to_vec::<f32>().unwrap(), data);
+
+    assert!(tensor.read_to_vec::<i32>().is_err());
+    assert!(tensor.write_from_slice(&data[..4]).is_err());
+}
+
+// =============================================================================
+// GpuVec Tests
+// =============================================================================
+
+#[test]
+fn test_gpu_vec_grows_and_preserves_contents() {
+    let device = get_device();
+    let queue = device.new_command_queue().expect("Failed to create queue");
+
+    let mut values: GpuVec<u32> =
+        GpuVec::new(&device, ResourceOptions::STORAGE_MODE_SHARED).unwrap();
+    let initial_capacity = values.capacity();
+    let mut data: Vec<u32> = (0..initial_capacity as u32 * 3).collect();
+
+    let command_buffer = queue.command_buffer().unwrap();
+    values
+        .extend_from_slice(&command_buffer, &data[..initial_capacity])
+        .unwrap();
+    assert_eq!(values.retired_count(), 0);
+
+    values.set_epoch(1);
+    values
+        .extend_from_slice(&command_buffer, &data[initial_capacity..])
+        .unwrap();
+    assert!(values.capacity() >= data.len());
+    assert_eq!(values.retired_count(), 1);
+
+    // A write into the copied elements before the blit runs survives it.
+    values.write(0, &[42]);
+    data[0] = 42;
+
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+    assert_eq!(values.as_slice(), &data[..]);
+
+    assert_eq!(values.collect(0), 0);
+    assert_eq!(values.collect(1), 1);
+    assert_eq!(values.retired_count(), 0);
+}
+
+#[test]
+fn test_gpu_vec_orders_writes_after_committed_growth() {
+    let device = get_device();
+    let queue = device.new_command_queue().expect("Failed to create queue");
+
+    let mut values: GpuVec<u32> =
+        GpuVec::new(&device, ResourceOptions::STORAGE_MODE_SHARED).unwrap();
+    let data: Vec<u32> = (0..values.capacity() as u32 + 1).collect();
+
+    let command_buffer = queue.command_buffer().unwrap();
+    values
+        .extend_from_slice(&command_buffer, &data[..data.len() - 1])
+        .unwrap();
+    values.push(&command_buffer, data[data.len() - 1]).unwrap();
+    command_buffer.commit();
+
+    // The blit is committed but may not have run; the write waits for it.
+    values.write(1, &[7]);
+    assert_eq!(values.as_slice()[1], 7);
+    assert_eq!(values.as_slice()[2..], data[2..]);
+
+    // Growing on a second command buffer while the first one is still
+    // being encoded can't be ordered.
+    let first = queue.command_buffer().unwrap();
+    let second = queue.command_buffer().unwrap();
+    values.reserve(&first, values.capacity()).unwrap();
+    assert!(matches!(
+        values.reserve(&second, values.capacity()),
+        Err(GpuVecError::GrowthPending)
+    ));
+}
+
+#[test]
+fn test_gpu_vec_rejects_private_storage() {
+    let device = get_device();
+
+    let result = GpuVec::<f32>::new(&device, ResourceOptions::STORAGE_MODE_PRIVATE);
+    assert!(matches!(result, Err(GpuVecError::NotCpuAccessible)));
+}
+
+// =============================================================================
+// MaterialBlockAllocator Tests
+// =============================================================================
+
+#[test]
+fn test_material_blocks_flush_dirty_range() {
+    let device = get_device();
+    let queue = device.new_command_queue().expect("Failed to create queue");
+
+    let mut materials: MaterialBlockAllocator<[f32; 4]> =
+        MaterialBlockAllocator::new(&device, 8, ResourceOptions::STORAGE_MODE_SHARED).unwrap();
+    let a = materials.allocate([1.0; 4]).unwrap();
+    let b = materials.allocate([2.0; 4]).unwrap();
+    assert_eq!(materials.dirty_range(), Some(0..2));
+
+    let command_buffer = queue.command_buffer().unwrap();
+    assert_eq!(materials.flush(&command_buffer).unwrap(), Some(0..32));
+    assert_eq!(materials.flush(&command_buffer).unwrap(), None);
+
+    assert!(materials.update(b, |block| block[3] = 5.0));
+    assert_eq!(materials.dirty_range(), Some(1..2));
+    assert_eq!(materials.flush(&command_buffer).unwrap(), Some(16..32));
+
+    let contents = materials.buffer().contents().unwrap() as *const [f32; 4];
+    let blocks = unsafe { std::slice::from_raw_parts(contents, 2) };
+    assert_eq!(blocks, &[[1.0; 4], [2.0, 2.0, 2.0, 5.0]]);
+
+    // Freed slots are reused, and the old handle goes stale.
+    assert_eq!(materials.free(a), Some([1.0; 4]));
+    let c = materials.allocate([3.0; 4]).unwrap();
+    assert_eq!(c.index(), a.index());
+    assert!(!materials.set(a, [0.0; 4]));
+    assert_eq!(materials.get(c), Some(&[3.0; 4]));
+}
+
+#[test]
+fn test_material_blocks_full() {
+    let device = get_device();
+
+    let mut materials: MaterialBlockAllocator<u32> =
+        MaterialBlockAllocator::new(&device, 1, ResourceOptions::STORAGE_MODE_SHARED).unwrap();
+    materials.allocate(7).unwrap();
+    assert_eq!(
+        materials.allocate(8),
+        Err(MaterialBlockError::Full { capacity: 1 })
+    );
+}
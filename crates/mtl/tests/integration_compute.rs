@@ -3,7 +3,10 @@
 //! These tests verify that compute pipeline operations work correctly with the Metal GPU.
 //! They test real GPU operations including shader compilation, pipeline creation, and dispatch.
 
-use mtl_gpu::{ComputeCommandEncoder, ComputePipelineState, ResourceOptions, Size, device};
+use mtl_gpu::{
+    BindingKind, ComputeCommandEncoder, ComputeJobError, ComputeJobRunner, ComputePass,
+    ComputePipelineState, GpuFence, QueueTimelines, ResourceOptions, Size, Timeout, device,
+};
 
 /// Get the default Metal device or skip the test.
 fn get_device() -> mtl_gpu::Device {
@@ -135,6 +138,33 @@ fn test_function_not_found() {
     assert!(result.is_none(), "Nonexistent function should return None");
 }
 
+#[test]
+fn test_compile_to_metallib() {
+    let device = get_device();
+    if !device.supports_dynamic_libraries() {
+        return;
+    }
+
+    let bytes =
+        mtl_gpu::library::compile_to_metallib(&device, MULTIPLY_KERNEL, "multiply.metallib", None)
+            .expect("Failed to compile metallib");
+    assert!(
+        bytes.starts_with(b"MTLB"),
+        "metallib should start with its magic"
+    );
+
+    let invalid = mtl_gpu::library::compile_to_metallib(
+        &device,
+        "kernel void broken( {",
+        "broken.metallib",
+        None,
+    );
+    assert!(matches!(
+        invalid,
+        Err(mtl_gpu::library::MetallibError::Compile(_))
+    ));
+}
+
 // =============================================================================
 // Pipeline Creation Tests
 // =============================================================================
@@ -291,6 +321,95 @@ fn test_dispatch_multiply_kernel() {
     assert_eq!(results, expected);
 }
 
+/// Shader that scales buffer elements by a constant
+const SCALE_KERNEL: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+struct ScaleParams {
+    float scale;
+    uint count;
+};
+
+kernel void scale(device float* data [[buffer(0)]],
+                  constant ScaleParams& params [[buffer(1)]],
+                  uint id [[thread_position_in_grid]]) {
+    if (id < params.count) {
+        data[id] *= params.scale;
+    }
+}
+"#;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct ScaleParams {
+    scale: f32,
+    count: u32,
+}
+
+#[test]
+fn test_dispatch_with_inline_value() {
+    let device = get_device();
+    let element_count = 32usize;
+
+    let input_data: Vec<f32> = (0..element_count).map(|i| i as f32).collect();
+    let bytes: &[u8] = unsafe {
+        std::slice::from_raw_parts(
+            input_data.as_ptr() as *const u8,
+            input_data.len() * std::mem::size_of::<f32>(),
+        )
+    };
+    let buffer = device
+        .new_buffer_with_bytes(bytes, ResourceOptions::STORAGE_MODE_SHARED)
+        .expect("Failed to create buffer");
+
+    let library = device
+        .new_library_with_source(SCALE_KERNEL, None)
+        .expect("Failed to compile");
+    let function = library
+        .new_function_with_name("scale")
+        .expect("Function not found");
+    let pipeline = device
+        .new_compute_pipeline_state_with_function(&function)
+        .expect("Failed to create pipeline");
+
+    let command_queue = device.new_command_queue().expect("Failed to create queue");
+    let command_buffer = command_queue
+        .command_buffer()
+        .expect("Failed to create command buffer");
+    let encoder =
+        unsafe { ComputeCommandEncoder::from_raw(command_buffer.compute_command_encoder()) }
+            .expect("Failed to create encoder");
+
+    // Only the first half is scaled.
+    let params = ScaleParams {
+        scale: 3.0,
+        count: (element_count / 2) as u32,
+    };
+    ComputePass::new(&encoder, &pipeline)
+        .buffer(0, &buffer, 0)
+        .value(1, &params)
+        .dispatch(Size::new(element_count, 1, 1));
+
+    encoder.end_encoding();
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+
+    let result_ptr = buffer.contents().expect("Buffer contents null") as *const f32;
+    let results: Vec<f32> =
+        unsafe { std::slice::from_raw_parts(result_ptr, element_count).to_vec() };
+    let expected: Vec<f32> = (0..element_count)
+        .map(|i| {
+            if i < element_count / 2 {
+                i as f32 * 3.0
+            } else {
+                i as f32
+            }
+        })
+        .collect();
+    assert_eq!(results, expected);
+}
+
 #[test]
 fn test_dispatch_add_buffers_kernel() {
     let device = get_device();
@@ -654,6 +773,31 @@ fn test_command_buffer_timing() {
     }
 }
 
+#[test]
+fn test_command_buffer_wait_timeout() {
+    let device = get_device();
+    let command_queue = device.new_command_queue().expect("Failed to create queue");
+    let event = device.new_shared_event().expect("Failed to create event");
+
+    // A buffer blocked on an event that is never signaled must time out.
+    let blocked = command_queue
+        .command_buffer()
+        .expect("Failed to create command buffer");
+    blocked.encode_wait_for_event(event.as_event(), 1);
+    blocked.commit();
+    assert_eq!(
+        blocked.wait_until_completed_timeout(std::time::Duration::from_millis(20)),
+        Err(Timeout)
+    );
+
+    event.set_signaled_value(1);
+    assert_eq!(
+        blocked.wait_until_completed_timeout(std::time::Duration::from_secs(5)),
+        Ok(())
+    );
+    assert_eq!(blocked.status(), mtl_gpu::CommandBufferStatus::COMPLETED);
+}
+
 // =============================================================================
 // Pipeline Label Tests
 // =============================================================================
@@ -744,3 +888,344 @@ fn test_dispatch_single_thread() {
     let result = unsafe { *result_ptr };
     assert_eq!(result, 84.0); // 42.0 * 2.0
 }
+
+#[test]
+fn test_compute_pass_dispatch_exact() {
+    let device = get_device();
+    // Deliberately not a multiple of any SIMD width
+    let element_count = 1000usize;
+
+    let input_data: Vec<f32> = (0..element_count).map(|i| i as f32).collect();
+    let bytes: &[u8] = unsafe {
+        std::slice::from_raw_parts(
+            input_data.as_ptr() as *const u8,
+            input_data.len() * std::mem::size_of::<f32>(),
+        )
+    };
+    let buffer = device
+        .new_buffer_with_bytes(bytes, ResourceOptions::STORAGE_MODE_SHARED)
+        .expect("Failed to create buffer");
+
+    let library = device
+        .new_library_with_source(MULTIPLY_KERNEL, None)
+        .expect("Failed to compile");
+    let function = library
+        .new_function_with_name("multiply_by_two")
+        .expect("Function not found");
+    let pipeline: ComputePipelineState = device
+        .new_compute_pipeline_state_with_function(&function)
+        .expect("Failed to create pipeline");
+
+    let grid = Size::new(element_count, 1, 1);
+    let group = pipeline.threadgroup_size_for_grid(grid);
+    assert!(
+        group.width * group.height * group.depth <= pipeline.max_total_threads_per_threadgroup()
+    );
+
+    let command_queue = device.new_command_queue().expect("Failed to create queue");
+    let command_buffer = command_queue
+        .command_buffer()
+        .expect("Failed to create command buffer");
+
+    let encoder_ptr = command_buffer.compute_command_encoder();
+    let encoder =
+        unsafe { ComputeCommandEncoder::from_raw(encoder_ptr) }.expect("Failed to create encoder");
+
+    ComputePass::new(&encoder, &pipeline)
+        .buffer(0, &buffer, 0)
+        .dispatch(grid);
+
+    encoder.end_encoding();
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+
+    let result_ptr = buffer.contents().expect("Buffer contents null") as *const f32;
+    let results: Vec<f32> =
+        unsafe { std::slice::from_raw_parts(result_ptr, element_count).to_vec() };
+    let expected: Vec<f32> = (0..element_count).map(|i| (i as f32) * 2.0).collect();
+    assert_eq!(results, expected);
+}
+
+// =============================================================================
+// Reflection Tests
+// =============================================================================
+
+/// Shader with buffer, texture and sampler bindings
+const REFLECTION_KERNEL: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+kernel void reflect_bindings(
+    device float* output [[buffer(1)]],
+    texture2d<float, access::sample> input [[texture(2)]],
+    sampler linear [[sampler(3)]],
+    uint id [[thread_position_in_grid]]
+) {
+    output[id] = input.sample(linear, float2(0.5)).x;
+}
+"#;
+
+#[test]
+fn test_library_functions() {
+    let device = get_device();
+    let library = device
+        .new_library_with_source(REFLECTION_KERNEL, None)
+        .expect("Failed to compile");
+
+    let functions: Vec<_> = library.functions().collect();
+    assert_eq!(functions.len(), 1);
+    assert_eq!(functions[0].name().as_deref(), Some("reflect_bindings"));
+    assert_eq!(functions[0].function_type(), mtl_gpu::FunctionType::KERNEL);
+}
+
+#[test]
+fn test_compute_pipeline_binding_iteration() {
+    let device = get_device();
+    let library = device
+        .new_library_with_source(REFLECTION_KERNEL, None)
+        .expect("Failed to compile");
+    let function = library
+        .new_function_with_name("reflect_bindings")
+        .expect("Function not found");
+
+    let mut reflection_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+    let _pipeline = unsafe {
+        device.new_compute_pipeline_state_with_function_and_reflection(
+            &function,
+            mtl_gpu::PipelineOption::BINDING_INFO,
+            &mut reflection_ptr,
+        )
+    }
+    .expect("Failed to create pipeline");
+    // The reflection object is autoreleased; take our own reference.
+    let reflection = unsafe {
+        mtl_gpu::ComputePipelineReflection::from_raw(reflection_ptr)
+            .map(|reflection| std::mem::ManuallyDrop::new(reflection).clone())
+    }
+    .expect("No reflection returned");
+
+    let mut buffers = Vec::new();
+    let mut textures = Vec::new();
+    let mut samplers = Vec::new();
+    for binding in reflection.bindings() {
+        match binding.kind() {
+            BindingKind::Buffer(buffer) => {
+                buffers.push((buffer.index(), buffer.buffer_data_type()))
+            }
+            BindingKind::Texture(texture) => {
+                textures.push((texture.index(), texture.texture_type()))
+            }
+            BindingKind::Sampler(sampler) => samplers.push(sampler.index()),
+            _ => {}
+        }
+    }
+
+    assert_eq!(buffers, vec![(1, mtl_gpu::DataType::FLOAT)]);
+    assert_eq!(textures, vec![(2, mtl_gpu::TextureType::TYPE_2D)]);
+    assert_eq!(samplers, vec![3]);
+}
+
+// =============================================================================
+// Compute Job Tests
+// =============================================================================
+
+/// Adds one to every element per chunk.
+const INCREMENT_KERNEL: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+kernel void increment(device uint* data [[buffer(0)]],
+                      uint id [[thread_position_in_grid]]) {
+    data[id] += 1;
+}
+"#;
+
+#[test]
+fn test_compute_job_runner_checkpoint_and_migrate() {
+    let device = get_device();
+    let element_count = 64usize;
+
+    let mut runner = ComputeJobRunner::new(&device, INCREMENT_KERNEL, "increment", 10)
+        .expect("failed to create runner")
+        .with_chunks_per_command_buffer(3)
+        .with_checkpoint_interval(6);
+    let index = runner
+        .add_buffer(&vec![0u8; element_count * 4])
+        .expect("failed to add buffer");
+
+    let mut encode = |encoder: &ComputeCommandEncoder, buffers: &[mtl_gpu::Buffer], _chunk| {
+        encoder.set_buffer(&buffers[index], 0, 0);
+        encoder.dispatch_threads(Size::new(element_count, 1, 1), Size::new(32, 1, 1));
+    };
+
+    runner.run_chunks(5, &mut encode).expect("job failed");
+    assert_eq!(runner.next_chunk(), 5);
+    assert_eq!(runner.checkpoint().next_chunk(), 0);
+
+    // Migrating rolls back to the checkpoint; the repeated chunks must not
+    // be applied twice.
+    runner.migrate(&device).expect("migration failed");
+    assert_eq!(runner.next_chunk(), 0);
+
+    runner.run(&mut encode).expect("job failed");
+    assert!(runner.is_complete());
+    assert_eq!(runner.checkpoint().next_chunk(), 10);
+
+    let bytes = runner.buffer_contents(index).expect("buffer not readable");
+    let values: Vec<u32> = bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_ne_bytes(b.try_into().unwrap()))
+        .collect();
+    assert_eq!(values, vec![10; element_count]);
+}
+
+#[test]
+fn test_compute_job_runner_stops_when_device_lost() {
+    let device = get_device();
+    let mut runner = ComputeJobRunner::new(&device, INCREMENT_KERNEL, "increment", 4)
+        .expect("failed to create runner");
+    runner.add_buffer(&[0u8; 16]).expect("failed to add buffer");
+
+    runner
+        .device_lost_flag()
+        .store(true, std::sync::atomic::Ordering::Release);
+    let result = runner.run(|_, _, _| {});
+    assert!(matches!(
+        result,
+        Err(ComputeJobError::DeviceLost { next_chunk: 0 })
+    ));
+
+    assert!(matches!(
+        ComputeJobRunner::new(&device, INCREMENT_KERNEL, "missing", 1),
+        Err(ComputeJobError::FunctionNotFound(_))
+    ));
+}
+
+// =============================================================================
+// GPU Fence Tests
+// =============================================================================
+
+#[test]
+fn test_gpu_fence_signal_and_wait_cpu() {
+    let device = get_device();
+    let fence = GpuFence::new(&device).expect("Failed to create fence");
+    let queue = device.new_command_queue().expect("Failed to create queue");
+
+    assert!(!fence.is_signaled(1));
+    assert!(!fence.wait_cpu(1, std::time::Duration::from_millis(10)));
+
+    let command_buffer = queue
+        .command_buffer()
+        .expect("Failed to create command buffer");
+    fence.signal_on_gpu(&command_buffer, 5);
+    command_buffer.commit();
+
+    assert!(fence.wait_cpu(5, std::time::Duration::from_secs(5)));
+    assert!(fence.is_signaled(5));
+    assert_eq!(fence.completed_value(), 5);
+
+    let command_buffer = queue
+        .command_buffer()
+        .expect("Failed to create command buffer");
+    let value = fence.signal_next(&command_buffer);
+    assert_eq!(value, 6);
+    command_buffer.commit();
+    assert!(fence.wait_cpu(value, std::time::Duration::from_secs(5)));
+}
+
+#[cfg(feature = "blocks")]
+#[test]
+fn test_gpu_fence_wait_future() {
+    use std::future::Future;
+    use std::task::{Context, Poll, Waker};
+
+    let device = get_device();
+    let fence = GpuFence::new(&device).expect("Failed to create fence");
+    let queue = device.new_command_queue().expect("Failed to create queue");
+
+    let command_buffer = queue
+        .command_buffer()
+        .expect("Failed to create command buffer");
+    fence.wait_on_gpu(&command_buffer, 1);
+    let value = 2;
+    fence.signal_on_gpu(&command_buffer, value);
+    let mut wait = std::pin::pin!(fence.wait(value));
+    command_buffer.commit();
+
+    let mut cx = Context::from_waker(Waker::noop());
+    assert!(wait.as_mut().poll(&mut cx).is_pending());
+
+    // Release the GPU wait from the CPU; the GPU then signals `value`.
+    fence.signal_cpu(1);
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while wait.as_mut().poll(&mut cx) == Poll::Pending {
+        assert!(std::time::Instant::now() < deadline, "fence wait timed out");
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    assert!(fence.is_signaled(value));
+}
+
+// =============================================================================
+// Queue Timeline Tests
+// =============================================================================
+
+#[test]
+fn test_queue_timelines_align_frames() {
+    let device = get_device();
+    let graphics = device.new_command_queue().expect("Failed to create queue");
+    let compute = device.new_command_queue().expect("Failed to create queue");
+    let mut timelines = QueueTimelines::new(&device, 2).expect("Failed to create timelines");
+
+    for frame in 1..=3 {
+        let graphics_cb = graphics
+            .command_buffer()
+            .expect("Failed to create command buffer");
+        let compute_cb = compute
+            .command_buffer()
+            .expect("Failed to create command buffer");
+        timelines.mark_frame_boundary(0, frame, &graphics_cb);
+        timelines.mark_frame_boundary(1, frame, &compute_cb);
+        graphics_cb.commit();
+        compute_cb.commit();
+        graphics_cb.wait_until_completed();
+        compute_cb.wait_until_completed();
+    }
+
+    assert_eq!(timelines.poll(), 3);
+    assert_eq!(timelines.event(1).signaled_value(), 3);
+    let frames: Vec<_> = timelines.frames().map(|timeline| timeline.frame).collect();
+    assert_eq!(frames, vec![1, 2, 3]);
+
+    let latest = timelines.latest().expect("No completed frame");
+    assert_eq!(latest.spans.len(), 2);
+    assert!(latest.spans.iter().all(Option::is_some));
+    assert_eq!(latest.offset(0), Some(0.0));
+    assert!(latest.start().unwrap() <= latest.end().unwrap());
+    assert_eq!(timelines.poll(), 0);
+}
+
+// =============================================================================
+// Metal 4 Submission Tests
+// =============================================================================
+
+#[test]
+fn test_mtl4_commit_and_wait() {
+    let device = get_device();
+
+    // Metal 4 queues are only available on supported OS and hardware.
+    let Some(queue) = device.new_mtl4_command_queue() else {
+        return;
+    };
+    let allocator = device.new_command_allocator().unwrap();
+    let command_buffer = device.new_command_buffer().unwrap();
+    let event = device.new_shared_event().unwrap();
+
+    command_buffer.begin_command_buffer(&allocator);
+    let encoder = command_buffer.compute_command_encoder().unwrap();
+    encoder.end_encoding();
+    command_buffer.end_command_buffer();
+
+    assert!(queue.commit_and_wait(&[&command_buffer], &event, 1, 5000));
+    assert!(event.signaled_value() >= 1);
+}
@@ -3,7 +3,14 @@
 //! These tests verify that render pipeline operations work correctly with the Metal GPU.
 //! They test real GPU operations including shader compilation, pipeline creation, and state.
 
-use mtl_gpu::{PixelFormat, RenderPipelineDescriptor, device};
+use mtl_foundation::Referencing;
+use mtl_gpu::{
+    FoveationController, FoveationParams, IndirectCommandBufferBuilder, IndirectCommandBufferError,
+    IndirectCommandType, MultisampleDepthResolveFilter, MultisampleStencilResolveFilter,
+    PixelFormat, RenderPassDescriptor, RenderPipelineDescriptor, ResourceOptions, ShadowPass,
+    ShadowPassError, Size, StorageMode, StoreAction, TextureDescriptor, TextureType, TextureUsage,
+    VertexCapture, VertexCaptureError, device,
+};
 
 /// Get the default Metal device or skip the test.
 fn get_device() -> mtl_gpu::Device {
@@ -342,6 +349,53 @@ fn test_render_pipeline_sample_count() {
     }
 }
 
+// =============================================================================
+// Render Pass Resolve Tests
+// =============================================================================
+
+#[test]
+fn test_render_pass_depth_resolve() {
+    let device = get_device();
+    if !device.supports_texture_sample_count(4) {
+        return;
+    }
+
+    let msaa_desc =
+        TextureDescriptor::texture_2d_descriptor(PixelFormat::DEPTH32_FLOAT, 64, 64, false)
+            .unwrap();
+    msaa_desc.set_texture_type(TextureType::TYPE_2D_MULTISAMPLE);
+    msaa_desc.set_sample_count(4);
+    msaa_desc.set_storage_mode(StorageMode::PRIVATE);
+    msaa_desc.set_usage(TextureUsage::RENDER_TARGET);
+    let msaa_depth = device.new_texture_with_descriptor(&msaa_desc).unwrap();
+
+    let resolve_desc =
+        TextureDescriptor::texture_2d_descriptor(PixelFormat::DEPTH32_FLOAT, 64, 64, false)
+            .unwrap();
+    resolve_desc.set_storage_mode(StorageMode::PRIVATE);
+    resolve_desc.set_usage(TextureUsage::RENDER_TARGET | TextureUsage::SHADER_READ);
+    let resolved_depth = device.new_texture_with_descriptor(&resolve_desc).unwrap();
+
+    let pass = RenderPassDescriptor::new().unwrap();
+    let depth = pass.depth_attachment().unwrap();
+    depth.set_texture(Some(&msaa_depth));
+    depth.set_multisample_resolve(&resolved_depth, MultisampleDepthResolveFilter::MAX, false);
+
+    assert!(depth.resolve_texture().is_some());
+    assert_eq!(
+        depth.depth_resolve_filter(),
+        MultisampleDepthResolveFilter::MAX
+    );
+    assert_eq!(depth.store_action(), StoreAction::MULTISAMPLE_RESOLVE);
+
+    let stencil = pass.stencil_attachment().unwrap();
+    stencil.set_stencil_resolve_filter(MultisampleStencilResolveFilter::DEPTH_RESOLVED_SAMPLE);
+    assert_eq!(
+        stencil.stencil_resolve_filter(),
+        MultisampleStencilResolveFilter::DEPTH_RESOLVED_SAMPLE
+    );
+}
+
 // =============================================================================
 // Size Tests
 // =============================================================================
@@ -355,9 +409,208 @@ fn test_render_pipeline_descriptor_size() {
 }
 
 #[test]
+#[cfg(not(feature = "validation"))]
 fn test_render_pipeline_state_size() {
     assert_eq!(
         std::mem::size_of::<mtl_gpu::RenderPipelineState>(),
         std::mem::size_of::<*mut std::ffi::c_void>()
     );
 }
+
+// =============================================================================
+// Foveated Rendering Tests
+// =============================================================================
+
+#[test]
+fn test_foveation_controller_rebuilds_on_change() {
+    let device = get_device();
+    if !device.supports_rasterization_rate_map(1) {
+        return;
+    }
+
+    let mut foveation = FoveationController::new(&device, Size::new(1024, 1024, 0));
+    assert!(foveation.update(FoveationParams::centered()).unwrap());
+    assert!(foveation.map().is_some());
+    assert!(foveation.parameter_buffer().is_some());
+
+    let physical = foveation.physical_size().unwrap();
+    assert!(physical.width <= 1024 && physical.height <= 1024);
+
+    // Small gaze jitter stays within tolerance.
+    assert!(!foveation.update(FoveationParams::gaze(0.505, 0.5)).unwrap());
+
+    // A frame in flight keeps the old buffer; a rebuild must not touch it.
+    let old_buffer = foveation.parameter_buffer().unwrap().clone();
+    let read = |buffer: &mtl_gpu::Buffer| {
+        let contents = buffer.contents().unwrap() as *const u8;
+        unsafe { std::slice::from_raw_parts(contents, buffer.length()) }.to_vec()
+    };
+    let old_contents = read(&old_buffer);
+    assert!(foveation.update(FoveationParams::gaze(0.2, 0.5)).unwrap());
+    assert_eq!(read(&old_buffer), old_contents);
+    assert_ne!(
+        foveation.parameter_buffer().unwrap().as_ptr(),
+        old_buffer.as_ptr()
+    );
+}
+
+// =============================================================================
+// Shadow Pass Tests
+// =============================================================================
+
+#[test]
+fn test_shadow_pass_renders_depth_only() {
+    let device = get_device();
+
+    let library = device
+        .new_library_with_source(COMBINED_SHADER, None)
+        .expect("Failed to compile shader");
+    let vertex_fn = library.new_function_with_name("simple_vertex").unwrap();
+
+    let shadow = ShadowPass::new(&device, 256, 256, PixelFormat::DEPTH32_FLOAT).unwrap();
+    assert_eq!(shadow.depth_texture().storage_mode(), StorageMode::PRIVATE);
+    assert!(
+        shadow
+            .depth_texture()
+            .usage()
+            .contains(TextureUsage::RENDER_TARGET | TextureUsage::SHADER_READ)
+    );
+
+    let descriptor = shadow.pipeline_descriptor(&vertex_fn).unwrap();
+    assert!(descriptor.fragment_function().is_none());
+    let pipeline = device
+        .new_render_pipeline_state_with_descriptor(&descriptor)
+        .expect("Failed to create depth-only pipeline");
+
+    let queue = device.new_command_queue().unwrap();
+    let command_buffer = queue.command_buffer().unwrap();
+    let encoder = shadow.begin(&command_buffer).unwrap();
+    encoder.set_render_pipeline_state(&pipeline);
+    encoder.end_encoding();
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+
+    assert_eq!(
+        ShadowPass::new(&device, 256, 256, PixelFormat::RGBA8_UNORM).err(),
+        Some(ShadowPassError::UnsupportedPixelFormat(
+            PixelFormat::RGBA8_UNORM
+        ))
+    );
+}
+
+#[test]
+fn test_stencil_view() {
+    let device = get_device();
+
+    let descriptor =
+        TextureDescriptor::depth_2d(PixelFormat::DEPTH32_FLOAT_STENCIL8, 64, 64).unwrap();
+    let texture = device.new_texture_with_descriptor(&descriptor).unwrap();
+    let view = texture.new_stencil_view().unwrap();
+    assert_eq!(view.pixel_format(), PixelFormat::X32_STENCIL8);
+
+    let depth_only = TextureDescriptor::depth_2d(PixelFormat::DEPTH32_FLOAT, 64, 64).unwrap();
+    let depth_texture = device.new_texture_with_descriptor(&depth_only).unwrap();
+    assert!(depth_texture.new_stencil_view().is_none());
+}
+
+// =============================================================================
+// Vertex Capture Tests
+// =============================================================================
+
+const VERTEX_CAPTURE_SHADER: &str = r#"
+#include <metal_stdlib>
+using namespace metal;
+
+vertex void scale_vertices(
+    uint vid [[vertex_id]],
+    const device float4* input [[buffer(0)]],
+    device float4* output [[buffer(1)]]
+) {
+    output[vid] = input[vid] * 2.0;
+}
+"#;
+
+#[test]
+fn test_vertex_capture_writes_transformed_vertices() {
+    let device = get_device();
+
+    let library = device
+        .new_library_with_source(VERTEX_CAPTURE_SHADER, None)
+        .expect("Failed to compile shader");
+    let vertex_fn = library.new_function_with_name("scale_vertices").unwrap();
+
+    let capture = VertexCapture::new(&device, &vertex_fn, 16, 4, 1).unwrap();
+
+    let input: [f32; 16] = [
+        1.0, 0.0, 0.0, 1.0, //
+        0.0, 1.0, 0.0, 1.0, //
+        0.0, 0.0, 1.0, 1.0, //
+        1.0, 1.0, 1.0, 1.0,
+    ];
+    let bytes: Vec<u8> = input.iter().flat_map(|v| v.to_ne_bytes()).collect();
+    let input_buffer = device
+        .new_buffer_with_bytes(&bytes, ResourceOptions::STORAGE_MODE_SHARED)
+        .unwrap();
+
+    let queue = device.new_command_queue().unwrap();
+    let command_buffer = queue.command_buffer().unwrap();
+    capture
+        .encode(&command_buffer, 4, |encoder| {
+            encoder.set_vertex_buffer(&input_buffer, 0, 0);
+        })
+        .unwrap();
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+
+    let output: Vec<[f32; 4]> = unsafe { capture.read(4) }.unwrap();
+    assert_eq!(output[0], [2.0, 0.0, 0.0, 2.0]);
+    assert_eq!(output[3], [2.0, 2.0, 2.0, 2.0]);
+
+    assert!(matches!(
+        unsafe { capture.read::<[f32; 4]>(5) },
+        Err(VertexCaptureError::CapacityExceeded { .. })
+    ));
+    assert!(matches!(
+        unsafe { capture.read::<[f32; 2]>(4) },
+        Err(VertexCaptureError::StrideMismatch { .. })
+    ));
+    assert!(matches!(
+        VertexCapture::new(&device, &vertex_fn, 16, usize::MAX, 1),
+        Err(VertexCaptureError::SizeOverflow { .. })
+    ));
+}
+
+// =============================================================================
+// Indirect Command Buffer Tests
+// =============================================================================
+
+#[test]
+fn test_indirect_command_buffer_builder() {
+    let device = get_device();
+
+    let icb = IndirectCommandBufferBuilder::new(4)
+        .unwrap()
+        .command_types(IndirectCommandType::DRAW | IndirectCommandType::DRAW_INDEXED)
+        .max_vertex_buffer_bind_count(2)
+        .max_fragment_buffer_bind_count(1)
+        .build(&device)
+        .expect("Failed to create indirect command buffer");
+    assert_eq!(icb.size(), 4);
+    assert_eq!(icb.render_commands().count(), 4);
+    assert_eq!(icb.render_commands_in(2..10).count(), 2);
+
+    for command in icb.render_commands_in(0..2) {
+        command.set_barrier();
+    }
+    icb.reset_range(0..2);
+    icb.reset_all();
+
+    let mixed = IndirectCommandBufferBuilder::new(4)
+        .unwrap()
+        .command_types(IndirectCommandType::DRAW | IndirectCommandType::CONCURRENT_DISPATCH)
+        .build(&device);
+    assert!(matches!(
+        mixed,
+        Err(IndirectCommandBufferError::MixedCommandTypes)
+    ));
+}
@@ -3,7 +3,10 @@
 //! These tests verify that texture operations work correctly with the Metal GPU.
 //! They test texture creation, pixel data read/write, and texture properties.
 
-use mtl_gpu::{PixelFormat, Region, StorageMode, TextureDescriptor, TextureUsage, device};
+use mtl_gpu::{
+    DebugViewChannels, DebugViewer, DebugViewerError, PixelFormat, ReadbackError, Region,
+    StorageMode, TextureDescriptor, TextureUsage, UploadError, UploadOptions, device,
+};
 
 /// Get the default Metal device or skip the test.
 fn get_device() -> mtl_gpu::Device {
@@ -120,6 +123,33 @@ fn test_texture_invalid_dimensions() {
     }
 }
 
+#[test]
+fn test_shared_texture_round_trip() {
+    let device = get_device();
+
+    let descriptor =
+        TextureDescriptor::texture_2d_descriptor(PixelFormat::RGBA8_UNORM, 64, 64, false).unwrap();
+    descriptor.set_storage_mode(StorageMode::PRIVATE);
+    descriptor.set_usage(TextureUsage::SHADER_READ | TextureUsage::RENDER_TARGET);
+
+    // Shared textures are not available on every device
+    let Some(texture) = device.new_shared_texture(&descriptor) else {
+        return;
+    };
+    assert!(texture.is_shareable());
+
+    let handle = texture
+        .new_shared_texture_handle()
+        .expect("Failed to create shared texture handle");
+    let opened = device
+        .new_shared_texture_from_handle(&handle)
+        .expect("Failed to open shared texture");
+
+    assert_eq!(opened.width(), 64);
+    assert_eq!(opened.height(), 64);
+    assert_eq!(opened.pixel_format(), PixelFormat::RGBA8_UNORM);
+}
+
 // =============================================================================
 // Texture Data Tests
 // =============================================================================
@@ -156,6 +186,142 @@ fn test_texture_replace_region() {
     // Note: reading back requires more setup with blit encoder
 }
 
+#[test]
+fn test_texture_read_region() {
+    let device = get_device();
+    let queue = device.new_command_queue().unwrap();
+
+    let descriptor =
+        TextureDescriptor::texture_2d_descriptor(PixelFormat::RGBA8_UNORM, 4, 4, false).unwrap();
+    descriptor.set_storage_mode(StorageMode::SHARED);
+    let texture = device.new_texture_with_descriptor(&descriptor).unwrap();
+
+    let pixels: Vec<u8> = (0..64).collect();
+    unsafe {
+        texture.replace_region_simple(
+            Region::new_2d(0, 0, 4, 4),
+            0,
+            pixels.as_ptr() as *const std::ffi::c_void,
+            16,
+        );
+    }
+
+    let full = texture
+        .read_region(&queue, Region::new_2d(0, 0, 4, 4), 0, 0)
+        .expect("readback failed");
+    assert_eq!(full, pixels);
+
+    // Second row, last two pixels.
+    let partial = texture
+        .read_region(&queue, Region::new_2d(2, 1, 2, 1), 0, 0)
+        .expect("readback failed");
+    assert_eq!(partial, pixels[24..32]);
+
+    let out_of_bounds = texture.read_region(&queue, Region::new_2d(3, 0, 2, 1), 0, 0);
+    assert!(matches!(out_of_bounds, Err(ReadbackError::InvalidRegion)));
+
+    let missing_slice = texture.read_region(&queue, Region::new_2d(0, 0, 4, 4), 0, 1);
+    assert!(matches!(
+        missing_slice,
+        Err(ReadbackError::InvalidSlice { slice: 1, count: 1 })
+    ));
+
+    #[cfg(feature = "blocks")]
+    {
+        let (tx, rx) = std::sync::mpsc::channel();
+        texture
+            .read_region_async(&queue, Region::new_2d(0, 0, 4, 4), 0, 0, move |result| {
+                let _ = tx.send(result);
+            })
+            .expect("failed to encode readback");
+        let result = rx
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .expect("readback handler was not called");
+        assert_eq!(result.expect("readback failed"), pixels);
+    }
+}
+
+#[test]
+fn test_texture_upload_from_bytes() {
+    let device = get_device();
+    let queue = device.new_command_queue().unwrap();
+
+    let descriptor =
+        TextureDescriptor::texture_2d_descriptor(PixelFormat::RGBA8_UNORM, 4, 4, true).unwrap();
+    descriptor.set_storage_mode(StorageMode::PRIVATE);
+    let texture = device.new_texture_with_descriptor(&descriptor).unwrap();
+
+    // Rows padded to 32 bytes; only the first 16 of each belong to the image.
+    let mut padded = vec![0u8; 32 * 4];
+    for (row, chunk) in padded.chunks_mut(32).enumerate() {
+        for (i, byte) in chunk[..16].iter_mut().enumerate() {
+            *byte = (row * 16 + i) as u8;
+        }
+    }
+    texture
+        .upload_from_bytes(&queue, &padded, 32)
+        .expect("upload failed");
+    let pixels: Vec<u8> = (0..64).collect();
+    let readback = texture
+        .read_region(&queue, Region::new_2d(0, 0, 4, 4), 0, 0)
+        .expect("readback failed");
+    assert_eq!(readback, pixels);
+
+    let options = UploadOptions {
+        generate_mipmaps: true,
+        ..Default::default()
+    };
+    texture
+        .upload_from_bytes_with_options(&queue, &pixels, 16, options)
+        .expect("upload failed");
+    let smallest = texture
+        .read_region(&queue, Region::new_2d(0, 0, 1, 1), 2, 0)
+        .expect("readback failed");
+    assert_eq!(smallest.len(), 4);
+
+    assert!(matches!(
+        texture.upload_from_bytes(&queue, &pixels, 8),
+        Err(UploadError::InvalidBytesPerRow {
+            minimum: 16,
+            bytes_per_row: 8
+        })
+    ));
+    assert!(matches!(
+        texture.upload_from_bytes(&queue, &pixels[..60], 16),
+        Err(UploadError::DataTooShort {
+            required: 64,
+            length: 60
+        })
+    ));
+
+    #[cfg(feature = "blocks")]
+    {
+        use std::future::Future;
+        use std::task::{Context, Poll, Waker};
+
+        let reversed: Vec<u8> = pixels.iter().rev().copied().collect();
+        let mut upload = std::pin::pin!(
+            texture
+                .upload_from_bytes_async(&queue, &reversed, 16, UploadOptions::default())
+                .expect("failed to encode upload")
+        );
+        let mut cx = Context::from_waker(Waker::noop());
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let result = loop {
+            if let Poll::Ready(result) = upload.as_mut().poll(&mut cx) {
+                break result;
+            }
+            assert!(std::time::Instant::now() < deadline, "upload timed out");
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        };
+        result.expect("upload failed");
+        let readback = texture
+            .read_region(&queue, Region::new_2d(0, 0, 4, 4), 0, 0)
+            .expect("readback failed");
+        assert_eq!(readback, reversed);
+    }
+}
+
 #[test]
 fn test_texture_bytes_per_row() {
     let device = get_device();
@@ -238,6 +404,71 @@ fn test_texture_array() {
     assert_eq!(texture.array_length(), 4);
 }
 
+// =============================================================================
+// Debug View Tests
+// =============================================================================
+
+#[test]
+fn test_debug_viewer_capture() {
+    let device = get_device();
+    let queue = device.new_command_queue().unwrap();
+
+    let descriptor =
+        TextureDescriptor::texture_2d_descriptor(PixelFormat::RGBA8_UNORM, 8, 8, false).unwrap();
+    descriptor.set_storage_mode(StorageMode::SHARED);
+    descriptor.set_usage(TextureUsage::SHADER_READ);
+    let texture = device.new_texture_with_descriptor(&descriptor).unwrap();
+    let pixels: Vec<u8> = [255u8, 0, 0, 255].repeat(64);
+    unsafe {
+        texture.replace_region_simple(
+            Region::new_2d(0, 0, 8, 8),
+            0,
+            pixels.as_ptr() as *const std::ffi::c_void,
+            32,
+        );
+    }
+
+    let mut viewer = DebugViewer::new(&device, 4, 4).expect("failed to create viewer");
+    assert!(viewer.bytes().is_none());
+
+    let command_buffer = queue.command_buffer().unwrap();
+    viewer
+        .capture(&command_buffer, &texture)
+        .expect("capture failed");
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+    assert_eq!(
+        viewer.bytes().expect("no capture"),
+        [255, 0, 0, 255].repeat(16)
+    );
+
+    // The green channel alone is black.
+    viewer.set_channels(DebugViewChannels::Green);
+    let command_buffer = queue.command_buffer().unwrap();
+    viewer
+        .capture(&command_buffer, &texture)
+        .expect("capture failed");
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+    assert_eq!(
+        viewer.bytes().expect("no capture"),
+        [0, 0, 0, 255].repeat(16)
+    );
+    assert_eq!(viewer.frame_count(), 2);
+
+    let descriptor =
+        TextureDescriptor::texture_2d_descriptor(PixelFormat::R32_UINT, 8, 8, false).unwrap();
+    descriptor.set_usage(TextureUsage::SHADER_READ);
+    let integer = device.new_texture_with_descriptor(&descriptor).unwrap();
+    let command_buffer = queue.command_buffer().unwrap();
+    assert!(matches!(
+        viewer.capture(&command_buffer, &integer),
+        Err(DebugViewerError::UnsupportedPixelFormat(
+            PixelFormat::R32_UINT
+        ))
+    ));
+}
+
 // =============================================================================
 // Texture Properties Tests
 // =============================================================================
@@ -0,0 +1,88 @@
+//! Mock Device Tests
+//!
+//! These tests drive code written against the `DeviceLike` traits with a
+//! `MockDevice`. They need no GPU and run on any platform, including the
+//! Linux CI job:
+//!
+//! ```text
+//! cargo test -p mtl-gpu --features mock --test mock_device
+//! ```
+
+#![cfg(feature = "mock")]
+
+use mtl_gpu::{
+    BufferLike, CommandBufferLike, CommandBufferStatus, CommandQueueLike, DeviceLike, MockCall,
+    MockDevice, ResourceOptions,
+};
+
+/// Engine-style code that only knows about the traits.
+fn upload_and_submit<D: DeviceLike>(device: &D, data: &[u8]) -> Option<D::Buffer> {
+    let buffer = device.new_buffer_with_bytes(data, ResourceOptions::STORAGE_MODE_MANAGED)?;
+    buffer.set_label("upload");
+    buffer.did_modify_range(0, buffer.length());
+
+    let queue = device.new_command_queue()?;
+    queue.set_label("main");
+    let command_buffer = queue.command_buffer()?;
+    command_buffer.set_label("frame");
+    command_buffer.commit();
+    command_buffer.wait_until_completed();
+    assert_eq!(command_buffer.status(), CommandBufferStatus::COMPLETED);
+    Some(buffer)
+}
+
+#[test]
+fn test_mock_records_generic_code() {
+    let device = MockDevice::new();
+    let buffer = upload_and_submit(&device, &[1, 2, 3, 4]).unwrap();
+    assert_eq!(buffer.bytes(), vec![1, 2, 3, 4]);
+    assert_eq!(buffer.label().as_deref(), Some("upload"));
+
+    assert_eq!(
+        device.calls(),
+        vec![
+            MockCall::NewBufferWithBytes {
+                length: 4,
+                options: ResourceOptions::STORAGE_MODE_MANAGED,
+            },
+            MockCall::DidModifyRange {
+                location: 0,
+                length: 4,
+            },
+            MockCall::NewCommandQueue,
+            MockCall::NewCommandBuffer {
+                queue_label: Some("main".to_string()),
+            },
+            MockCall::Commit {
+                label: Some("frame".to_string()),
+            },
+            MockCall::WaitUntilCompleted {
+                label: Some("frame".to_string()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_mock_enforces_limits() {
+    let device = MockDevice::new().with_max_buffer_length(8);
+    assert!(
+        device
+            .new_buffer(9, ResourceOptions::STORAGE_MODE_SHARED)
+            .is_none()
+    );
+    assert!(upload_and_submit(&device, &[0; 16]).is_none());
+}
+
+#[test]
+#[should_panic(expected = "already committed")]
+fn test_mock_rejects_double_commit() {
+    let device = MockDevice::new();
+    let command_buffer = device
+        .new_command_queue()
+        .unwrap()
+        .command_buffer()
+        .unwrap();
+    command_buffer.commit();
+    command_buffer.commit();
+}
@@ -132,6 +132,7 @@ fn validate_device_selectors() {
         "newLibraryWithSource:options:error:",
         "newLibraryWithData:error:",
         "newLibraryWithURL:error:",
+        "newDynamicLibrary:error:",
         "newDefaultLibrary",
         "newDefaultLibraryWithBundle:error:",
         "newRenderPipelineStateWithDescriptor:error:",
@@ -844,6 +845,7 @@ fn validate_event_selectors() {
         "setSignaledValue:",
         "newSharedEventHandle",
         "notifyListener:atValue:block:",
+        "waitUntilSignaledValue:timeoutMS:",
     ]));
 
     if !failures.is_empty() {
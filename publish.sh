@@ -36,6 +36,7 @@ CRATES=(
     "mtl-sys"
     "mtl-foundation"
     "mtl-gpu"
+    "mtl-mps"
     "mtl-quartz-core"
     "mtl-fx"
 )
@@ -73,7 +74,7 @@ for crate in "${CRATES[@]}"; do
     echo -e "${GREEN}✓${NC} $crate published successfully"
 
     # Wait between publishes for crates.io to index
-    if [ "$crate" != "mtl-fx" ]; then
+    if [ "$crate" != "${CRATES[-1]}" ]; then
         echo "  Waiting 30 seconds for crates.io to index..."
         sleep 30
     fi
@@ -88,6 +89,7 @@ echo "Your crates are now available at:"
 echo "  https://crates.io/crates/mtl-sys"
 echo "  https://crates.io/crates/mtl-foundation"
 echo "  https://crates.io/crates/mtl-gpu"
+echo "  https://crates.io/crates/mtl-mps"
 echo "  https://crates.io/crates/mtl-quartz-core"
 echo "  https://crates.io/crates/mtl-fx"
 echo ""